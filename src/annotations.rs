@@ -0,0 +1,169 @@
+use crate::error::Result;
+use crate::scanner::build_globs;
+use globset::GlobSet;
+use std::path::Path;
+
+/// Excludes the whole file from summarization, e.g. `# doctreeai:ignore`.
+pub const IGNORE_FILE_MARKER: &str = "doctreeai:ignore";
+
+/// Starts a region that is never sent to the LLM.
+pub const IGNORE_START_MARKER: &str = "doctreeai:ignore-start";
+
+/// Ends a region started with `doctreeai:ignore-start`.
+pub const IGNORE_END_MARKER: &str = "doctreeai:ignore-end";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnnotatedContent {
+    /// The file opted out of documentation entirely.
+    Ignored,
+    /// Content with ignored regions removed.
+    Content(String),
+}
+
+/// Comment prefixes recognized for annotations across common languages.
+pub(crate) const COMMENT_PREFIXES: [&str; 8] = ["//", "#", "--", "/*", "<!--", ";", "%", "*"];
+
+/// Whether the line is a comment consisting solely of the marker, so string
+/// literals or prose that merely mention a marker are not treated as one.
+fn is_marker_comment(line: &str, marker: &str) -> bool {
+    let trimmed = line.trim();
+
+    COMMENT_PREFIXES.iter().any(|prefix| {
+        trimmed.strip_prefix(prefix).is_some_and(|rest| {
+            rest.trim()
+                .trim_end_matches("-->")
+                .trim_end_matches("*/")
+                .trim()
+                == marker
+        })
+    })
+}
+
+/// Applies `doctreeai:ignore` annotations found in comments of a source file.
+pub fn apply_annotations(content: &str) -> AnnotatedContent {
+    let mut kept = String::with_capacity(content.len());
+    let mut in_ignored_region = false;
+    let mut removed_lines = 0;
+
+    for line in content.lines() {
+        if is_marker_comment(line, IGNORE_START_MARKER) {
+            in_ignored_region = true;
+            removed_lines += 1;
+            continue;
+        }
+
+        if is_marker_comment(line, IGNORE_END_MARKER) {
+            in_ignored_region = false;
+            removed_lines += 1;
+            continue;
+        }
+
+        if in_ignored_region {
+            removed_lines += 1;
+            continue;
+        }
+
+        if is_marker_comment(line, IGNORE_FILE_MARKER) {
+            return AnnotatedContent::Ignored;
+        }
+
+        kept.push_str(line);
+        kept.push('\n');
+    }
+
+    if removed_lines > 0 {
+        tracing::debug!("Removed {removed_lines} annotated lines from prompt content");
+    }
+
+    AnnotatedContent::Content(kept)
+}
+
+/// Glob patterns (relative to the project root) whose files are never summarized.
+#[derive(Debug, Clone)]
+pub struct IgnorePatterns {
+    globs: GlobSet,
+}
+
+impl Default for IgnorePatterns {
+    fn default() -> Self {
+        Self {
+            globs: GlobSet::empty(),
+        }
+    }
+}
+
+impl IgnorePatterns {
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        Ok(Self { globs: build_globs(patterns, "ignore")? })
+    }
+
+    pub fn is_ignored(&self, relative_path: &Path) -> bool {
+        self.globs.is_match(relative_path)
+    }
+}
+
+/// Glob patterns (relative to the project root) whose file contents are
+/// never sent to the LLM. Those files are summarized from their path, size,
+/// and identifiers only.
+#[derive(Debug, Clone)]
+pub struct PrivatePatterns {
+    globs: GlobSet,
+}
+
+impl Default for PrivatePatterns {
+    fn default() -> Self {
+        Self {
+            globs: GlobSet::empty(),
+        }
+    }
+}
+
+impl PrivatePatterns {
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        Ok(Self { globs: build_globs(patterns, "private path")? })
+    }
+
+    pub fn is_private(&self, relative_path: &Path) -> bool {
+        self.globs.is_match(relative_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_marker_ignores_file() {
+        let content = "// doctreeai:ignore\nfn secret() {}\n";
+        assert_eq!(apply_annotations(content), AnnotatedContent::Ignored);
+    }
+
+    #[test]
+    fn test_markers_must_be_comments() {
+        let content = "let marker = \"doctreeai:ignore\";\n<!-- doctreeai:ignore-start -->\nhidden\n<!-- doctreeai:ignore-end -->\n";
+        assert_eq!(
+            apply_annotations(content),
+            AnnotatedContent::Content("let marker = \"doctreeai:ignore\";\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_regions_are_removed() {
+        let content = "fn public() {}\n# doctreeai:ignore-start\nfn embarrassing() {}\n# doctreeai:ignore-end\nfn other() {}\n";
+        assert_eq!(
+            apply_annotations(content),
+            AnnotatedContent::Content("fn public() {}\nfn other() {}\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_ignore_patterns() -> Result<()> {
+        let patterns = IgnorePatterns::new(&["experiments/**".to_string(), "src/legacy_*.rs".to_string()])?;
+        assert!(patterns.is_ignored(Path::new("experiments/a/b.py")));
+        assert!(patterns.is_ignored(Path::new("src/legacy_parser.rs")));
+        assert!(!patterns.is_ignored(Path::new("src/parser.rs")));
+
+        assert!(IgnorePatterns::new(&["[".to_string()]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+use crate::config::Config;
+use crate::error::{DocTreeError, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Subdirectory of the cache directory holding audit records.
+pub const AUDIT_DIR: &str = "audit";
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Orders records written within the same millisecond.
+static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// Replaces every match of the configured patterns before text is written.
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    patterns: Vec<Regex>,
+}
+
+impl Redactor {
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| {
+                    DocTreeError::config(format!("Invalid redaction pattern '{pattern}': {e}"))
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { patterns })
+    }
+
+    /// Also redacts this exact text, e.g. the API key.
+    pub fn with_literal(mut self, literal: &str) -> Self {
+        if let Ok(pattern) = Regex::new(&regex::escape(literal)) {
+            self.patterns.push(pattern);
+        }
+        self
+    }
+
+    pub fn redact(&self, text: &str) -> String {
+        self.patterns
+            .iter()
+            .fold(text.to_string(), |text, pattern| pattern.replace_all(&text, REDACTED).into_owned())
+    }
+}
+
+/// One LLM request and what came back.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Unix time in milliseconds.
+    pub timestamp: u64,
+    pub model: String,
+    pub prompt: String,
+    /// The raw response, before any output checks.
+    pub response: Option<String>,
+    pub error: Option<String>,
+    pub latency_ms: u64,
+}
+
+/// Writes every prompt and raw response to `<cache>/audit/` as one JSON file
+/// per request, named so they sort chronologically.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    dir: PathBuf,
+    redactor: Redactor,
+}
+
+impl AuditLog {
+    pub fn new(cache_dir: &Path, redactor: Redactor) -> Self {
+        Self {
+            dir: cache_dir.join(AUDIT_DIR),
+            redactor,
+        }
+    }
+
+    /// The audit log configured by `DOCTREEAI_AUDIT_LOG`, if enabled. The
+    /// API key is always redacted along with `DOCTREEAI_AUDIT_REDACT`.
+    pub fn from_config(config: &Config, base_path: &Path) -> Result<Option<Self>> {
+        if !config.audit_log {
+            return Ok(None);
+        }
+
+        let mut redactor = Redactor::new(&config.audit_redact)?;
+        if config.openai_api_key != Config::default().openai_api_key {
+            redactor = redactor.with_literal(&config.openai_api_key);
+        }
+        Ok(Some(Self::new(&config.get_cache_dir_path(base_path), redactor)))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn record(&self, model: &str, prompt: &str, result: &Result<String>, latency_ms: u64) -> Result<()> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let (response, error) = match result {
+            Ok(response) => (Some(self.redactor.redact(response)), None),
+            Err(e) => (None, Some(self.redactor.redact(&e.to_string()))),
+        };
+        let record = AuditRecord {
+            timestamp,
+            model: model.to_string(),
+            prompt: self.redactor.redact(prompt),
+            response,
+            error,
+            latency_ms,
+        };
+
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| DocTreeError::cache(format!("Failed to create audit directory: {e}")))?;
+        let content = serde_json::to_string_pretty(&record)
+            .map_err(|e| DocTreeError::cache(format!("Failed to serialize audit record: {e}")))?;
+        let sequence = SEQUENCE.fetch_add(1, Ordering::Relaxed);
+        fs::write(self.dir.join(format!("{timestamp}-{sequence:06}.json")), content)
+            .map_err(|e| DocTreeError::cache(format!("Failed to write audit record: {e}")))
+    }
+
+    /// Every record, oldest first.
+    pub fn records(&self) -> Result<Vec<AuditRecord>> {
+        Self::read_records(&self.dir)
+    }
+
+    /// Every record in an audit directory, oldest first.
+    pub fn read_records(dir: &Path) -> Result<Vec<AuditRecord>> {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Ok(Vec::new());
+        };
+
+        let mut paths: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+            .collect();
+        paths.sort();
+
+        paths
+            .iter()
+            .map(|path| {
+                let content = fs::read_to_string(path)?;
+                serde_json::from_str(&content).map_err(|e| {
+                    DocTreeError::cache(format!("Invalid audit record {}: {e}", path.display()))
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_audit_log_redacts_records() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let redactor = Redactor::new(&[r"ghp_[A-Za-z0-9]+".to_string()])?.with_literal("sk-secret");
+        let audit_log = AuditLog::new(temp_dir.path(), redactor);
+
+        audit_log.record(
+            "test-model",
+            "Summarize: token = ghp_abc123, key = sk-secret",
+            &Ok("Reads the token.".to_string()),
+            12,
+        )?;
+        audit_log.record("test-model", "Second prompt", &Err(DocTreeError::timeout("too slow")), 5)?;
+
+        let records = audit_log.records()?;
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].prompt, "Summarize: token = [REDACTED], key = [REDACTED]");
+        assert_eq!(records[0].response.as_deref(), Some("Reads the token."));
+        assert!(records[1].error.as_deref().is_some_and(|error| error.contains("too slow")));
+
+        assert!(Redactor::new(&["(".to_string()]).is_err());
+
+        Ok(())
+    }
+}
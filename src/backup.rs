@@ -0,0 +1,136 @@
+use crate::error::{DocTreeError, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Subdirectory of the cache holding README.md backups.
+pub const BACKUP_DIR: &str = "readme_backups";
+
+/// Copies of README.md taken before doctreeai overwrites it, newest last.
+/// Only the most recent `keep` copies are retained.
+pub struct ReadmeBackups {
+    dir: PathBuf,
+    keep: usize,
+}
+
+/// One saved copy of README.md.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadmeBackup {
+    pub path: PathBuf,
+    /// Unix time in milliseconds when the copy was taken.
+    pub taken_at_ms: u128,
+}
+
+impl ReadmeBackup {
+    pub fn taken_at_secs(&self) -> u64 {
+        (self.taken_at_ms / 1000) as u64
+    }
+}
+
+impl ReadmeBackups {
+    pub fn new(cache_dir: &Path, keep: usize) -> Self {
+        Self {
+            dir: cache_dir.join(BACKUP_DIR),
+            keep,
+        }
+    }
+
+    /// Saves `content` as the newest backup and prunes the oldest ones.
+    /// Does nothing when backups are disabled (`keep` is 0).
+    pub fn save(&self, content: &str) -> Result<Option<PathBuf>> {
+        if self.keep == 0 {
+            return Ok(None);
+        }
+
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| DocTreeError::cache(format!("Failed to create backup directory: {e}")))?;
+
+        let mut taken_at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        // Keep names unique and ordered even for saves within the same millisecond
+        if let Some(latest) = self.list()?.last() {
+            taken_at_ms = taken_at_ms.max(latest.taken_at_ms + 1);
+        }
+
+        let path = self.dir.join(format!("README.{taken_at_ms:013}.md"));
+        fs::write(&path, content)
+            .map_err(|e| DocTreeError::cache(format!("Failed to write README backup: {e}")))?;
+
+        let backups = self.list()?;
+        for old in &backups[..backups.len().saturating_sub(self.keep)] {
+            fs::remove_file(&old.path)?;
+        }
+
+        Ok(Some(path))
+    }
+
+    /// All backups, oldest first.
+    pub fn list(&self) -> Result<Vec<ReadmeBackup>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut backups: Vec<ReadmeBackup> = fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let path = entry.path();
+                let taken_at_ms = path
+                    .file_name()?
+                    .to_str()?
+                    .strip_prefix("README.")?
+                    .strip_suffix(".md")?
+                    .parse()
+                    .ok()?;
+                Some(ReadmeBackup { path, taken_at_ms })
+            })
+            .collect();
+        backups.sort_by_key(|backup| backup.taken_at_ms);
+        Ok(backups)
+    }
+
+    /// Restores the newest backup over README.md and removes it, so repeated
+    /// rollbacks step further back. Returns None when there is no backup.
+    pub fn restore_latest(&self, base_path: &Path) -> Result<Option<ReadmeBackup>> {
+        let Some(latest) = self.list()?.pop() else {
+            return Ok(None);
+        };
+
+        let readme_path = base_path.join("README.md");
+        fs::copy(&latest.path, &readme_path)
+            .map_err(|e| DocTreeError::readme(format!("Failed to restore README.md: {e}")))?;
+        fs::remove_file(&latest.path)?;
+
+        Ok(Some(latest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_backups_are_pruned_and_restored() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        let backups = ReadmeBackups::new(&base_path.join(".cache"), 2);
+
+        for version in 1..=3 {
+            backups.save(&format!("# Version {version}\n"))?;
+        }
+        assert_eq!(backups.list()?.len(), 2);
+
+        fs::write(base_path.join("README.md"), "# Broken rewrite\n")?;
+        assert!(backups.restore_latest(base_path)?.is_some());
+        assert_eq!(fs::read_to_string(base_path.join("README.md"))?, "# Version 3\n");
+        assert!(backups.restore_latest(base_path)?.is_some());
+        assert_eq!(fs::read_to_string(base_path.join("README.md"))?, "# Version 2\n");
+        assert!(backups.restore_latest(base_path)?.is_none());
+
+        assert_eq!(ReadmeBackups::new(&base_path.join(".cache"), 0).save("x")?, None);
+
+        Ok(())
+    }
+}
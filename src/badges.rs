@@ -0,0 +1,184 @@
+use crate::cache::CacheSummary;
+use crate::github::repository_from_remote;
+use crate::license::License;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+/// Markdown for a badge image linking to `link`.
+pub fn badge_markdown(label: &str, image: &str, link: &str) -> String {
+    format!("[![{label}]({image})]({link})")
+}
+
+/// Badges for the project, in display order: registry versions, CI status
+/// for each GitHub Actions workflow, the license, and API docs. Built only
+/// from the repository's own files and its origin remote.
+pub fn detect_badges(base_path: &Path, summaries: &[CacheSummary]) -> Vec<String> {
+    let read = |name: &str| fs::read_to_string(base_path.join(name)).ok();
+    let mut badges = Vec::new();
+    let mut docs = Vec::new();
+
+    if let Some(name) = read("Cargo.toml").and_then(|content| published_crate(&content)) {
+        badges.push(badge_markdown(
+            "crates.io",
+            &format!("https://img.shields.io/crates/v/{name}.svg"),
+            &format!("https://crates.io/crates/{name}"),
+        ));
+        docs.push(badge_markdown(
+            "docs.rs",
+            &format!("https://img.shields.io/docsrs/{name}"),
+            &format!("https://docs.rs/{name}"),
+        ));
+    }
+    if let Some(name) = read("package.json").and_then(|content| published_package(&content)) {
+        badges.push(badge_markdown(
+            "npm",
+            &format!("https://img.shields.io/npm/v/{name}.svg"),
+            &format!("https://www.npmjs.com/package/{name}"),
+        ));
+    }
+    if let Some(name) = read("pyproject.toml").and_then(|content| python_project(&content)) {
+        badges.push(badge_markdown(
+            "PyPI",
+            &format!("https://img.shields.io/pypi/v/{name}.svg"),
+            &format!("https://pypi.org/project/{name}/"),
+        ));
+        if [".readthedocs.yaml", ".readthedocs.yml"].iter().any(|file| base_path.join(file).is_file()) {
+            docs.push(badge_markdown(
+                "Documentation",
+                &format!("https://readthedocs.org/projects/{name}/badge/?version=latest"),
+                &format!("https://{name}.readthedocs.io"),
+            ));
+        }
+    }
+    if let Some(module) = read("go.mod").and_then(|content| go_module(&content)) {
+        docs.push(badge_markdown(
+            "Go Reference",
+            &format!("https://pkg.go.dev/badge/{module}.svg"),
+            &format!("https://pkg.go.dev/{module}"),
+        ));
+    }
+
+    if let Some(repository) = origin_repository(base_path) {
+        for (file, name) in workflows(base_path) {
+            let workflow = format!("https://github.com/{repository}/actions/workflows/{file}");
+            badges.push(badge_markdown(&name, &format!("{workflow}/badge.svg"), &workflow));
+        }
+    }
+    if let Some(badge) = License::detect(base_path, summaries).and_then(|license| license.badge()) {
+        badges.push(badge);
+    }
+
+    badges.extend(docs);
+    badges
+}
+
+/// The crate name, unless the package sets `publish = false`.
+fn published_crate(content: &str) -> Option<String> {
+    let manifest = content.parse::<toml::Table>().ok()?;
+    let package = manifest.get("package")?;
+    if package.get("publish").and_then(|publish| publish.as_bool()) == Some(false) {
+        return None;
+    }
+    package.get("name")?.as_str().map(str::to_string)
+}
+
+/// The package name, unless it is `private`.
+fn published_package(content: &str) -> Option<String> {
+    let package: serde_json::Value = serde_json::from_str(content).ok()?;
+    if package["private"].as_bool() == Some(true) {
+        return None;
+    }
+    package["name"].as_str().map(str::to_string)
+}
+
+fn python_project(content: &str) -> Option<String> {
+    let project = content.parse::<toml::Table>().ok()?;
+    project
+        .get("project")
+        .and_then(|project| project.get("name")?.as_str())
+        .or_else(|| project.get("tool")?.get("poetry")?.get("name")?.as_str())
+        .map(str::to_string)
+}
+
+fn go_module(content: &str) -> Option<String> {
+    content
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("module "))
+        .map(|module| module.trim().to_string())
+}
+
+/// `owner/name` of the GitHub origin remote.
+fn origin_repository(base_path: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(base_path)
+        .args(["remote", "get-url", "origin"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    repository_from_remote(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// GitHub Actions workflow files with their `name:` (or file stem), sorted
+/// by file name.
+fn workflows(base_path: &Path) -> Vec<(String, String)> {
+    let mut workflows: Vec<(String, String)> = fs::read_dir(base_path.join(".github/workflows"))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let file = entry.file_name().to_str()?.to_string();
+            let stem = file.strip_suffix(".yml").or_else(|| file.strip_suffix(".yaml"))?.to_string();
+            let name = fs::read_to_string(entry.path())
+                .ok()
+                .and_then(|content| {
+                    content.lines().find_map(|line| {
+                        line.strip_prefix("name:")
+                            .map(|name| name.trim().trim_matches(['"', '\'']).to_string())
+                    })
+                })
+                .filter(|name| !name.is_empty())
+                .unwrap_or(stem);
+            Some((file, name))
+        })
+        .collect();
+    workflows.sort();
+    workflows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_badges() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        assert!(detect_badges(base_path, &[]).is_empty());
+
+        fs::write(base_path.join("Cargo.toml"), "[package]\nname = \"mytool\"\nlicense = \"MIT\"\n")?;
+        fs::write(base_path.join("package.json"), r#"{"name": "web", "private": true}"#)?;
+        fs::create_dir_all(base_path.join(".github/workflows"))?;
+        fs::write(base_path.join(".github/workflows/ci.yml"), "name: CI\non: push\n")?;
+        Command::new("git").arg("-C").arg(base_path).arg("init").arg("-q").status()?;
+        Command::new("git")
+            .arg("-C")
+            .arg(base_path)
+            .args(["remote", "add", "origin", "git@github.com:acme/mytool.git"])
+            .status()?;
+
+        let badges = detect_badges(base_path, &[]);
+        assert_eq!(badges.len(), 4);
+        assert!(badges[0].starts_with("[![crates.io](https://img.shields.io/crates/v/mytool.svg)]"));
+        assert_eq!(
+            badges[1],
+            "[![CI](https://github.com/acme/mytool/actions/workflows/ci.yml/badge.svg)](https://github.com/acme/mytool/actions/workflows/ci.yml)"
+        );
+        assert!(badges[2].contains("badge/license-MIT-blue.svg"));
+        assert!(badges[3].starts_with("[![docs.rs]"));
+        Ok(())
+    }
+}
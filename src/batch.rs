@@ -0,0 +1,69 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How often a submitted batch job is checked for completion.
+pub const BATCH_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Answer given to every collected request. Follow-up prompts built from it
+/// (directory summaries, budget rewrites) contain it and are left to the run.
+const PLACEHOLDER: &str = "Summary pending in a batch job.";
+
+/// A completion request to send in a batch job.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PendingCompletion {
+    pub model: String,
+    pub max_tokens: u32,
+    pub prompt: String,
+}
+
+/// Records the completion requests a run would make instead of sending
+/// them, answering each with a placeholder, so they can be sent together as
+/// one batch job before the run.
+#[derive(Debug, Clone, Default)]
+pub struct BatchCollector {
+    requests: Arc<Mutex<Vec<PendingCompletion>>>,
+}
+
+impl BatchCollector {
+    /// Records a request and returns the placeholder answer, as JSON when
+    /// `structured` so it parses like a structured summary.
+    pub fn record(&self, request: PendingCompletion, structured: bool) -> String {
+        self.requests.lock().expect("batch collector lock").push(request);
+        if structured {
+            return serde_json::json!({ "purpose": PLACEHOLDER }).to_string();
+        }
+        PLACEHOLDER.to_string()
+    }
+
+    /// The distinct requests recorded so far whose prompts do not depend on
+    /// an earlier placeholder answer.
+    pub fn take(&self) -> Vec<PendingCompletion> {
+        let requests = std::mem::take(&mut *self.requests.lock().expect("batch collector lock"));
+        let mut seen = HashSet::new();
+        requests
+            .into_iter()
+            .filter(|request| !request.prompt.contains(PLACEHOLDER))
+            .filter(|request| seen.insert(request.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collector_drops_prompts_built_from_placeholders() {
+        let collector = BatchCollector::default();
+        let request = |prompt: &str| PendingCompletion { model: "m".to_string(), max_tokens: 100, prompt: prompt.to_string() };
+
+        let answer = collector.record(request("Summarize a.rs"), false);
+        collector.record(request("Summarize a.rs"), false);
+        collector.record(request(&format!("Summarize the directory:\n{answer}")), false);
+        assert!(collector.record(request("Describe b.rs as JSON"), true).starts_with('{'));
+
+        assert_eq!(collector.take(), vec![request("Summarize a.rs"), request("Describe b.rs as JSON")]);
+        assert!(collector.take().is_empty());
+    }
+}
@@ -0,0 +1,198 @@
+use crate::error::{DocTreeError, Result};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Completion token cap for levels without a word budget.
+pub const DEFAULT_MAX_COMPLETION_TOKENS: u32 = 1000;
+
+/// Rewrites requested when a summary breaks its budget before the last
+/// answer is trimmed to fit.
+pub const BUDGET_RETRIES: usize = 2;
+
+/// Prompt instruction for `SummaryStyle::Bullets`.
+pub const BULLET_INSTRUCTION: &str = "Format the answer as terse Markdown bullet points (\"- \"), one fact per bullet, with no headings or prose paragraphs.";
+
+/// Which level of the summary tree a summary is written for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryLevel {
+    File,
+    Directory,
+    /// The project root, which README synthesis starts from.
+    Root,
+}
+
+/// How generated summaries are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SummaryStyle {
+    #[default]
+    Prose,
+    Bullets,
+}
+
+impl FromStr for SummaryStyle {
+    type Err = DocTreeError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "prose" => Ok(SummaryStyle::Prose),
+            "bullets" => Ok(SummaryStyle::Bullets),
+            other => Err(DocTreeError::config(format!(
+                "Invalid summary style '{other}' (expected prose or bullets)"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for SummaryStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SummaryStyle::Prose => write!(f, "prose"),
+            SummaryStyle::Bullets => write!(f, "bullets"),
+        }
+    }
+}
+
+/// Length and style limits for each level of the summary tree. Levels
+/// without a word budget keep the default completion cap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SummaryBudgets {
+    pub file_max_words: Option<usize>,
+    pub directory_max_words: Option<usize>,
+    pub root_max_words: Option<usize>,
+    pub style: SummaryStyle,
+}
+
+impl SummaryBudgets {
+    pub fn max_words(&self, level: SummaryLevel) -> Option<usize> {
+        match level {
+            SummaryLevel::File => self.file_max_words,
+            SummaryLevel::Directory => self.directory_max_words,
+            SummaryLevel::Root => self.root_max_words,
+        }
+    }
+
+    /// Appended to summary prompts; None when the level is unconstrained.
+    pub fn instruction(&self, level: SummaryLevel) -> Option<String> {
+        let mut parts = Vec::new();
+        if self.style == SummaryStyle::Bullets {
+            parts.push(BULLET_INSTRUCTION.to_string());
+        }
+        if let Some(max_words) = self.max_words(level) {
+            parts.push(format!("Use at most {max_words} words."));
+        }
+        (!parts.is_empty()).then(|| parts.join(" "))
+    }
+
+    /// Completion token cap: about two tokens per allowed word, leaving
+    /// room for Markdown and tokenizer differences.
+    pub fn max_completion_tokens(&self, level: SummaryLevel) -> u32 {
+        self.max_words(level)
+            .map(|max_words| u32::try_from(max_words * 2 + 50).unwrap_or(u32::MAX))
+            .unwrap_or(DEFAULT_MAX_COMPLETION_TOKENS)
+    }
+
+    /// Why a summary breaks the budget, or None when it fits.
+    pub fn violation(&self, level: SummaryLevel, summary: &str) -> Option<String> {
+        let words = summary.split_whitespace().count();
+        if let Some(max_words) = self.max_words(level).filter(|max_words| words > *max_words) {
+            return Some(format!("it has {words} words, the limit is {max_words}"));
+        }
+        if self.style == SummaryStyle::Bullets && !is_bullet_list(summary) {
+            return Some("it is not a bullet list".to_string());
+        }
+        None
+    }
+
+    /// Cuts a summary to the word budget, at the last sentence or line end
+    /// that fits.
+    pub fn trim(&self, level: SummaryLevel, summary: &str) -> String {
+        let summary = summary.trim();
+        let max_words = match self.max_words(level) {
+            Some(max_words) if max_words > 0 && summary.split_whitespace().count() > max_words => max_words,
+            _ => return summary.to_string(),
+        };
+
+        let last_word = summary.split_whitespace().nth(max_words - 1).unwrap_or_default();
+        let head = &summary[..last_word.as_ptr() as usize - summary.as_ptr() as usize + last_word.len()];
+        match head.rfind(['.', '!', '?', '\n']) {
+            Some(end) => head[..=end].trim_end().to_string(),
+            None => format!("{head}…"),
+        }
+    }
+}
+
+/// Cap on the prompt and completion tokens one run may spend. Clones share
+/// the count, so the summarizer and README validator draw from one budget.
+#[derive(Debug, Clone)]
+pub struct TokenBudget {
+    limit: u64,
+    used: Arc<AtomicU64>,
+}
+
+impl TokenBudget {
+    pub fn new(limit: u64) -> Self {
+        Self { limit, used: Arc::new(AtomicU64::new(0)) }
+    }
+
+    pub fn used(&self) -> u64 {
+        self.used.load(Ordering::SeqCst)
+    }
+
+    pub fn record(&self, tokens: u64) {
+        self.used.fetch_add(tokens, Ordering::SeqCst);
+    }
+
+    /// Fails with `DocTreeError::Cancelled` once the budget is spent, so the
+    /// run stops like an interrupted one and can be resumed.
+    pub fn check(&self) -> Result<()> {
+        let used = self.used();
+        if used >= self.limit {
+            return Err(DocTreeError::cancelled(format!(
+                "Token budget of {} per run exhausted ({used} tokens used)",
+                self.limit
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn is_bullet_list(summary: &str) -> bool {
+    let mut lines = summary.lines().map(str::trim).filter(|line| !line.is_empty()).peekable();
+    lines.peek().is_some() && lines.all(|line| ["- ", "* ", "• "].iter().any(|bullet| line.starts_with(bullet)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_violations_and_trimming() {
+        let budgets = SummaryBudgets {
+            file_max_words: Some(6),
+            style: SummaryStyle::Bullets,
+            ..Default::default()
+        };
+        assert_eq!(
+            budgets.instruction(SummaryLevel::File),
+            Some(format!("{BULLET_INSTRUCTION} Use at most 6 words."))
+        );
+        assert_eq!(budgets.max_completion_tokens(SummaryLevel::File), 62);
+        assert_eq!(budgets.max_completion_tokens(SummaryLevel::Root), DEFAULT_MAX_COMPLETION_TOKENS);
+
+        assert_eq!(budgets.violation(SummaryLevel::File, "- Parses flags.\n- Loads config."), None);
+        assert!(budgets.violation(SummaryLevel::File, "Parses flags.").is_some_and(|v| v.contains("bullet")));
+        assert!(budgets
+            .violation(SummaryLevel::File, "- Parses flags.\n- Loads config from the environment.")
+            .is_some_and(|v| v.contains("9 words")));
+
+        assert_eq!(
+            budgets.trim(SummaryLevel::File, "- Parses flags.\n- Loads config from the environment."),
+            "- Parses flags."
+        );
+        assert_eq!(budgets.trim(SummaryLevel::File, "one two three four five six seven"), "one two three four five six…");
+        assert_eq!("bullets".parse::<SummaryStyle>().ok(), Some(SummaryStyle::Bullets));
+        assert!("table".parse::<SummaryStyle>().is_err());
+    }
+}
@@ -1,7 +1,32 @@
+use crate::classifier::DirectoryRole;
 use crate::error::{DocTreeError, Result};
+use crate::provenance::ProvenanceTracker;
+use crate::examples::{UsageExamples, USAGE_EXAMPLES_FILE};
+use crate::file_lock::FileLock;
+use crate::format::current_timestamp;
+use crate::hasher::FileHasher;
+use crate::run_state::{RunState, RUN_STATE_FILE};
+use crate::structured::StructuredSummary;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// How a cached summary was produced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SummaryMethod {
+    #[default]
+    Llm,
+    /// Built from doc comments and symbols without the LLM; replaced by an
+    /// LLM summary on the next run that can make one.
+    Extractive,
+    /// Built from the path, size, and identifiers of a private file, whose
+    /// contents are never sent to the LLM.
+    #[serde(rename = "structure-only")]
+    StructureOnly,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheSummary {
@@ -10,6 +35,53 @@ pub struct CacheSummary {
     pub summary: String,
     pub timestamp: u64,
     pub is_directory: bool,
+    #[serde(default)]
+    pub role: Option<DirectoryRole>,
+    /// Human-curated entries are pinned and never overwritten by regeneration.
+    #[serde(default)]
+    pub pinned: bool,
+    /// Size in bytes of the source file when it was hashed.
+    #[serde(default)]
+    pub content_size: Option<u64>,
+    /// Modification time of the source file when it was hashed, in
+    /// nanoseconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<u64>,
+    /// Whether `content_hash` is of the file's normalized form, insensitive
+    /// to formatting and comments.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub normalized_hash: bool,
+    /// `DOCTREEAI_DOC_LANGUAGE` when the summary was written; None for the
+    /// model's default language.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub method: SummaryMethod,
+    /// The fields behind `summary`, when it was requested as JSON.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured: Option<StructuredSummary>,
+    /// Hash of the sibling summaries the file was re-summarized with, when
+    /// the sibling-context pass rewrote it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sibling_context: Option<String>,
+}
+
+impl CacheSummary {
+    /// Whether an identical file may reuse this summary: a file summary
+    /// written by the LLM and not edited by hand.
+    fn is_reusable(&self) -> bool {
+        !self.is_directory && !self.pinned && self.method == SummaryMethod::Llm
+    }
+
+    /// Relative change in file size since the summary was written, used to
+    /// flag pinned summaries whose source has changed substantially.
+    pub fn size_drift(&self, current_size: u64) -> Option<f64> {
+        let original = self.content_size?;
+        if original == 0 {
+            return Some(if current_size == 0 { 0.0 } else { 1.0 });
+        }
+        Some((current_size as f64 - original as f64).abs() / original as f64)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +90,18 @@ pub struct ReadmeLineMapping {
     pub line_content: String,
     pub cache_keys: Vec<String>,
     pub last_validated_hash: Option<String>,
+    /// Anchor of the README section the line belongs to ("" before the
+    /// first heading). The line number is recomputed from the section and
+    /// the line content whenever the README moves around.
+    #[serde(default)]
+    pub section: String,
+}
+
+/// A README section as it was when its lines were mapped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadmeSectionMapping {
+    pub anchor: String,
+    pub content_hash: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +109,44 @@ pub struct ReadmeMappingData {
     pub version: String,
     pub readme_hash: String,
     pub mappings: Vec<ReadmeLineMapping>,
+    /// The sections `mappings` were built from, so sections whose content
+    /// is unchanged keep their mappings when the README is edited elsewhere.
+    #[serde(default)]
+    pub sections: Vec<ReadmeSectionMapping>,
+    /// Hashes of sentences doctreeai has generated, used to tell generated
+    /// README prose apart from manual prose.
+    #[serde(default)]
+    pub generated_sentences: BTreeSet<String>,
+    /// Bumped on every save, so a writer can tell another process saved
+    /// since it loaded the file and merge instead of overwriting.
+    #[serde(default)]
+    pub revision: u64,
+}
+
+impl ReadmeMappingData {
+    /// Folds this process's changes into a newer copy read from disk.
+    /// Generated sentences are unioned. Line mappings are only taken from
+    /// `self` when it replaced them: line by line for the same README,
+    /// wholesale when `self` mapped a different README.
+    fn merge_into(&self, mut newer: ReadmeMappingData, mappings_replaced: bool) -> ReadmeMappingData {
+        newer.generated_sentences.extend(self.generated_sentences.iter().cloned());
+
+        if mappings_replaced {
+            if newer.readme_hash == self.readme_hash {
+                newer.mappings.retain(|theirs| {
+                    !self.mappings.iter().any(|ours| ours.line_number == theirs.line_number)
+                });
+                newer.mappings.extend(self.mappings.iter().cloned());
+                newer.mappings.sort_by_key(|mapping| mapping.line_number);
+            } else {
+                newer.readme_hash = self.readme_hash.clone();
+                newer.mappings = self.mappings.clone();
+            }
+            newer.sections = self.sections.clone();
+        }
+
+        newer
+    }
 }
 
 impl Default for ReadmeMappingData {
@@ -33,16 +155,198 @@ impl Default for ReadmeMappingData {
             version: "1.0.0".to_string(),
             readme_hash: String::new(),
             mappings: Vec::new(),
+            sections: Vec::new(),
+            generated_sentences: BTreeSet::new(),
+            revision: 0,
+        }
+    }
+}
+
+/// How long to wait for another process to finish saving the mapping.
+const MAPPING_LOCK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TtlRule {
+    /// Path prefix relative to the project root, or `*` for every entry.
+    pub prefix: String,
+    pub ttl_secs: u64,
+}
+
+impl TtlRule {
+    fn matches(&self, relative_path: &Path) -> bool {
+        if self.prefix == "*" {
+            return true;
+        }
+
+        let prefix = Path::new(self.prefix.trim_end_matches('/'));
+        relative_path.starts_with(prefix)
+    }
+}
+
+/// Decides when a cache entry is too old to trust, even if its content hash
+/// still matches. The most specific (longest) matching prefix wins.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RefreshPolicy {
+    pub rules: Vec<TtlRule>,
+}
+
+impl RefreshPolicy {
+    pub fn uniform(ttl_secs: u64) -> Self {
+        Self {
+            rules: vec![TtlRule {
+                prefix: "*".to_string(),
+                ttl_secs,
+            }],
+        }
+    }
+
+    /// Parses `prefix=duration` pairs separated by commas, e.g.
+    /// `docs/=7d,src/generated/=12h,*=90d`.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut rules = Vec::new();
+
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (prefix, duration) = entry.split_once('=').ok_or_else(|| {
+                DocTreeError::config(format!("Invalid cache TTL rule '{entry}', expected prefix=duration"))
+            })?;
+            rules.push(TtlRule {
+                prefix: prefix.trim().to_string(),
+                ttl_secs: parse_duration_secs(duration.trim())?,
+            });
+        }
+
+        Ok(Self { rules })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Expires everything older than `max_age_secs`, keeping shorter TTLs.
+    pub fn cap(&mut self, max_age_secs: u64) {
+        for rule in &mut self.rules {
+            rule.ttl_secs = rule.ttl_secs.min(max_age_secs);
+        }
+        if !self.rules.iter().any(|rule| rule.prefix == "*") {
+            self.rules.push(TtlRule {
+                prefix: "*".to_string(),
+                ttl_secs: max_age_secs,
+            });
+        }
+    }
+
+    pub fn ttl_for(&self, relative_path: &Path) -> Option<u64> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.matches(relative_path))
+            .max_by_key(|rule| if rule.prefix == "*" { 0 } else { rule.prefix.len() + 1 })
+            .map(|rule| rule.ttl_secs)
+    }
+
+    pub fn is_expired(&self, relative_path: &Path, timestamp: u64, now: u64) -> bool {
+        match self.ttl_for(relative_path) {
+            Some(ttl) => now.saturating_sub(timestamp) > ttl,
+            None => false,
         }
     }
 }
 
+/// Parses durations such as `90d`, `2w`, `12h`, `30m`, or `3600s` (bare
+/// numbers are seconds).
+pub fn parse_duration_secs(value: &str) -> Result<u64> {
+    let value = value.trim();
+    let split_at = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split_at);
+
+    let number: u64 = number
+        .parse()
+        .map_err(|_| DocTreeError::config(format!("Invalid duration '{value}'")))?;
+
+    let multiplier = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(DocTreeError::config(format!("Invalid duration unit in '{value}'"))),
+    };
+
+    number
+        .checked_mul(multiplier)
+        .ok_or_else(|| DocTreeError::config(format!("Duration '{value}' is too long")))
+}
+
+/// Subdirectory of the cache holding each workspace package's own cache
+/// (`run --workspace`). Hidden, so it never collides with a scanned path.
+pub const PACKAGE_CACHE_DIR: &str = ".packages";
+
+fn modified_nanos(metadata: &fs::Metadata) -> Option<u64> {
+    let modified = metadata.modified().ok()?.duration_since(std::time::UNIX_EPOCH).ok()?;
+    u64::try_from(modified.as_nanos()).ok()
+}
+
+/// A file's size and modification time as read just before it was hashed,
+/// stored with the summary of exactly that hash.
+#[derive(Debug, Clone)]
+pub struct HashedFile {
+    content_hash: String,
+    size: u64,
+    /// None when the file was modified in the second before it was read:
+    /// an edit made during hashing could leave the same time behind.
+    modified: Option<u64>,
+}
+
+impl HashedFile {
+    fn new(content_hash: String, metadata: &fs::Metadata) -> Self {
+        let settled_before = current_timestamp().saturating_sub(1).saturating_mul(1_000_000_000);
+        Self {
+            content_hash,
+            size: metadata.len(),
+            modified: modified_nanos(metadata).filter(|&modified| modified < settled_before),
+        }
+    }
+}
+
+/// Hashes source files the way a `CacheManager` with the same settings
+/// does (see `CacheManager::source_hasher`).
+#[derive(Debug, Clone, Copy)]
+pub struct SourceHasher {
+    normalized_hashing: bool,
+}
+
+impl SourceHasher {
+    pub fn hash(self, source_path: &Path) -> Result<HashedFile> {
+        // Read before the contents, so an edit made while hashing leaves a
+        // newer modification time than the one recorded
+        let metadata = fs::metadata(source_path)?;
+        let content_hash = FileHasher::compute_source_hash(source_path, self.normalized_hashing)?;
+        Ok(HashedFile::new(content_hash, &metadata))
+    }
+}
 
 pub struct CacheManager {
     cache_dir: PathBuf,
     base_path: PathBuf,
     mapping_file: PathBuf,
     mapping_data: ReadmeMappingData,
+    /// Whether `mapping_data.mappings` was replaced since the last save.
+    mappings_replaced: bool,
+    refresh_policy: RefreshPolicy,
+    /// When set, nothing is written to disk; entries written during the run
+    /// live in `memory` instead (`None` marks an entry removed this run).
+    read_only: bool,
+    memory: HashMap<PathBuf, Option<CacheSummary>>,
+    /// Content hash to the file whose LLM summary can be reused for it,
+    /// built from the cache on first lookup.
+    hash_index: Option<HashMap<String, PathBuf>>,
+    /// Whether files are hashed insensitive to formatting and comments.
+    normalized_hashing: bool,
+    /// Language summaries are written in; entries in another are misses.
+    doc_language: Option<String>,
+    /// What each file hashed this run looked like when it was read.
+    hashed: Mutex<HashMap<PathBuf, HashedFile>>,
 }
 
 impl CacheManager {
@@ -55,21 +359,133 @@ impl CacheManager {
             base_path: base_path.to_path_buf(),
             mapping_file,
             mapping_data: ReadmeMappingData::default(),
+            mappings_replaced: false,
+            refresh_policy: RefreshPolicy::default(),
+            read_only: false,
+            memory: HashMap::new(),
+            hash_index: None,
+            normalized_hashing: false,
+            doc_language: None,
+            hashed: Mutex::default(),
         };
 
         manager.load_mapping()?;
         Ok(manager)
     }
 
+    pub fn set_refresh_policy(&mut self, refresh_policy: RefreshPolicy) {
+        self.refresh_policy = refresh_policy;
+    }
+
+    /// Keeps all cache writes in memory for this manager's lifetime.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    pub fn set_normalized_hashing(&mut self, normalized_hashing: bool) {
+        self.normalized_hashing = normalized_hashing;
+    }
+
+    pub fn set_doc_language(&mut self, doc_language: Option<String>) {
+        self.doc_language = doc_language;
+    }
+
+    /// Hashes a source file the way entries written by this manager are,
+    /// remembering its size and modification time for the entry the hash
+    /// is stored with.
+    pub fn hash_file(&self, source_path: &Path) -> Result<String> {
+        let hashed = self.source_hasher().hash(source_path)?;
+        Ok(self.record_hash(source_path, hashed))
+    }
+
+    /// Hashes files like `hash_file` without borrowing the manager, e.g. on
+    /// a blocking thread; hand the result back with `record_hash`.
+    pub fn source_hasher(&self) -> SourceHasher {
+        SourceHasher { normalized_hashing: self.normalized_hashing }
+    }
+
+    /// Remembers a file hashed by a `SourceHasher`, returning its hash.
+    pub fn record_hash(&self, source_path: &Path, hashed: HashedFile) -> String {
+        let content_hash = hashed.content_hash.clone();
+        self.remember_hashed(source_path, hashed);
+        content_hash
+    }
+
+    fn remember_hashed(&self, source_path: &Path, hashed: HashedFile) {
+        self.hashed.lock().expect("hashed files lock").insert(source_path.to_path_buf(), hashed);
+    }
+
+    fn read_entry(&self, cache_path: &Path) -> Option<CacheSummary> {
+        if let Some(entry) = self.memory.get(cache_path) {
+            return entry.clone();
+        }
+
+        let content = fs::read_to_string(cache_path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn write_entry(&mut self, cache_path: &Path, entry: &CacheSummary) -> Result<()> {
+        if self.read_only {
+            self.memory.insert(cache_path.to_path_buf(), Some(entry.clone()));
+            return Ok(());
+        }
+
+        // Create parent directory if needed
+        if let Some(parent) = cache_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| DocTreeError::cache(format!("Failed to create cache directory: {e}")))?;
+        }
+
+        let content = serde_json::to_string_pretty(entry)
+            .map_err(|e| DocTreeError::cache(format!("Failed to serialize cache: {e}")))?;
+
+        // Written to a temporary file first so an interrupted run never
+        // leaves a truncated entry behind
+        let mut temp_file = cache_path.as_os_str().to_owned();
+        temp_file.push(".tmp");
+        fs::write(&temp_file, content)
+            .and_then(|_| fs::rename(&temp_file, cache_path))
+            .map_err(|e| DocTreeError::cache(format!("Failed to write cache file: {e}")))
+    }
+
+    fn remove_entry(&mut self, cache_path: &Path) -> Result<()> {
+        if self.read_only {
+            self.memory.insert(cache_path.to_path_buf(), None);
+            return Ok(());
+        }
+
+        if cache_path.exists() {
+            fs::remove_file(cache_path)
+                .map_err(|e| DocTreeError::cache(format!("Failed to remove cache file: {e}")))?;
+        }
+        Ok(())
+    }
+
+    fn relative_path<'a>(&self, source_path: &'a Path) -> &'a Path {
+        source_path.strip_prefix(&self.base_path).unwrap_or(source_path)
+    }
+
     pub fn initialize_cache_directory(&self) -> Result<()> {
+        if self.read_only {
+            tracing::debug!("Read-only mode, not creating {}", self.cache_dir.display());
+            return Ok(());
+        }
+
         if !self.cache_dir.exists() {
             fs::create_dir_all(&self.cache_dir)
                 .map_err(|e| DocTreeError::cache(format!("Failed to create cache directory: {e}")))?;
-            log::info!("Created cache directory: {}", self.cache_dir.display());
+            tracing::info!("Created cache directory: {}", self.cache_dir.display());
         }
 
-        // Update .gitignore to include cache directory
-        self.update_gitignore()?;
+        // Update .gitignore to include cache directory, unless the cache
+        // lives elsewhere (e.g. a package's subtree of the workspace cache)
+        if self.cache_dir.parent() == Some(self.base_path.as_path()) {
+            self.update_gitignore()?;
+        }
 
         Ok(())
     }
@@ -90,13 +506,13 @@ impl CacheManager {
             let content = fs::read_to_string(&gitignore_path)?;
             if !content.contains(cache_dir_name) {
                 fs::write(&gitignore_path, content + &gitignore_entry)?;
-                log::info!("Added {cache_dir_name} to .gitignore");
+                tracing::info!("Added {cache_dir_name} to .gitignore");
             } else {
-                log::debug!("Cache directory already in .gitignore");
+                tracing::debug!("Cache directory already in .gitignore");
             }
         } else {
             fs::write(&gitignore_path, gitignore_entry)?;
-            log::info!("Created .gitignore with cache directory entry");
+            tracing::info!("Created .gitignore with cache directory entry");
         }
 
         Ok(())
@@ -120,76 +536,279 @@ impl CacheManager {
         Ok(cache_path)
     }
 
+    /// The cached hash of a file whose size and modification time are the
+    /// same as when the cached hash was computed, so it need not be rehashed.
+    pub fn unchanged_hash(&self, source_path: &Path) -> Option<String> {
+        let entry = self.read_entry(&self.get_cache_path(source_path).ok()?)?;
+        let metadata = fs::metadata(source_path).ok()?;
+        let unchanged = !entry.is_directory
+            && entry.normalized_hash == self.normalized_hashing
+            && entry.content_size == Some(metadata.len())
+            && entry.modified.is_some()
+            && entry.modified == modified_nanos(&metadata);
+        if !unchanged {
+            return None;
+        }
+        self.remember_hashed(source_path, HashedFile::new(entry.content_hash.clone(), &metadata));
+        Some(entry.content_hash)
+    }
+
     pub fn get_cached_summary(&self, source_path: &Path, content_hash: &str) -> Option<String> {
+        self.get_cached_entry(source_path, content_hash).map(|entry| entry.summary)
+    }
+
+    /// Like `get_cached_summary`, but returns the whole entry so callers can
+    /// tell how the summary was produced.
+    pub fn get_cached_entry(&self, source_path: &Path, content_hash: &str) -> Option<CacheSummary> {
         let cache_path = self.get_cache_path(source_path).ok()?;
         
-        if !cache_path.exists() {
-            log::debug!("Cache miss (file not found) for: {}", source_path.display());
+        let Some(cache_summary) = self.read_entry(&cache_path) else {
+            tracing::debug!("Cache miss (file not found) for: {}", source_path.display());
+            return None;
+        };
+
+        if self.refresh_policy.is_expired(
+            self.relative_path(source_path),
+            cache_summary.timestamp,
+            current_timestamp(),
+        ) {
+            tracing::debug!("Cache miss (TTL expired) for: {}", source_path.display());
             return None;
         }
         
-        let content = fs::read_to_string(&cache_path).ok()?;
-        let cache_summary: CacheSummary = serde_json::from_str(&content).ok()?;
-        
+        // Pinned summaries are written by people, in whatever language they chose
+        if !cache_summary.pinned && cache_summary.language != self.doc_language {
+            tracing::debug!("Cache miss (language changed) for: {}", source_path.display());
+            return None;
+        }
+
         if cache_summary.content_hash == content_hash {
-            log::debug!("Cache hit for: {}", source_path.display());
-            Some(cache_summary.summary)
+            tracing::debug!("Cache hit for: {}", source_path.display());
+            Some(cache_summary)
         } else {
-            log::debug!("Cache miss (hash mismatch) for: {}", source_path.display());
+            tracing::debug!("Cache miss (hash mismatch) for: {}", source_path.display());
             None
         }
     }
 
+    /// The LLM summary of another file with exactly these contents, e.g. a
+    /// vendored copy, so an identical file can reuse it instead of asking
+    /// the LLM again.
+    pub fn find_identical(&mut self, source_path: &Path, content_hash: &str) -> Option<CacheSummary> {
+        if self.hash_index.is_none() {
+            let mut index = HashMap::new();
+            for summary in self.get_all_summaries().into_iter().filter(CacheSummary::is_reusable) {
+                index.entry(summary.content_hash).or_insert(summary.source_path);
+            }
+            self.hash_index = Some(index);
+        }
+
+        let twin = self.hash_index.as_ref()?.get(content_hash)?.clone();
+        if twin == source_path {
+            return None;
+        }
+        // The index is only a hint: the twin may have changed since
+        self.get_cached_entry(&twin, content_hash).filter(CacheSummary::is_reusable)
+    }
+
     pub fn store_summary(&mut self, source_path: &Path, content_hash: String, summary: String) -> Result<()> {
+        self.write_summary(source_path, content_hash, summary, None, SummaryMethod::Llm, None)
+    }
+
+    /// Stores a structured summary; its rendered text becomes the summary.
+    pub fn store_structured_summary(
+        &mut self,
+        source_path: &Path,
+        content_hash: String,
+        structured: StructuredSummary,
+    ) -> Result<()> {
+        let summary = structured.render();
+        self.write_summary(source_path, content_hash, summary, None, SummaryMethod::Llm, Some(structured))
+    }
+
+    /// Stores a summary built without the LLM, marked so it can be upgraded later.
+    pub fn store_extractive_summary(&mut self, source_path: &Path, content_hash: String, summary: String) -> Result<()> {
+        self.write_summary(source_path, content_hash, summary, None, SummaryMethod::Extractive, None)
+    }
+
+    /// Stores the summary of a private file, built without sending its contents.
+    pub fn store_structure_only_summary(&mut self, source_path: &Path, content_hash: String, summary: String) -> Result<()> {
+        self.write_summary(source_path, content_hash, summary, None, SummaryMethod::StructureOnly, None)
+    }
+
+    pub fn store_directory_summary(
+        &mut self,
+        source_path: &Path,
+        content_hash: String,
+        summary: String,
+        role: DirectoryRole,
+    ) -> Result<()> {
+        self.write_summary(source_path, content_hash, summary, Some(role), SummaryMethod::Llm, None)
+    }
+
+    /// Stores a directory summary built without the LLM, e.g. in offline mode.
+    pub fn store_extractive_directory_summary(
+        &mut self,
+        source_path: &Path,
+        content_hash: String,
+        summary: String,
+        role: DirectoryRole,
+    ) -> Result<()> {
+        self.write_summary(source_path, content_hash, summary, Some(role), SummaryMethod::Extractive, None)
+    }
+
+    fn write_summary(
+        &mut self,
+        source_path: &Path,
+        content_hash: String,
+        summary: String,
+        role: Option<DirectoryRole>,
+        method: SummaryMethod,
+        structured: Option<StructuredSummary>,
+    ) -> Result<()> {
         let cache_path = self.get_cache_path(source_path)?;
-        
-        // Create parent directory if needed
-        if let Some(parent) = cache_path.parent() {
-            fs::create_dir_all(parent)
-                .map_err(|e| DocTreeError::cache(format!("Failed to create cache directory: {e}")))?;
+
+        if let Some(existing) = self.get_cache_summary(source_path) {
+            if existing.pinned {
+                tracing::debug!("Keeping pinned summary for: {}", source_path.display());
+                return Ok(());
+            }
         }
         
-        let timestamp = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
+        let timestamp = current_timestamp();
+        let is_directory = source_path.is_dir();
+        // Only the file as it was hashed, never as it is now: it may have
+        // changed since
+        let hashed = self
+            .hashed
+            .lock()
+            .expect("hashed files lock")
+            .get(source_path)
+            .filter(|hashed| hashed.content_hash == content_hash)
+            .cloned();
 
         let cache_summary = CacheSummary {
             source_path: source_path.to_path_buf(),
             content_hash,
             summary,
             timestamp,
-            is_directory: source_path.is_dir(),
+            is_directory,
+            role,
+            pinned: false,
+            content_size: hashed.as_ref().map(|hashed| hashed.size),
+            modified: hashed.and_then(|hashed| hashed.modified),
+            normalized_hash: !is_directory && self.normalized_hashing,
+            language: self.doc_language.clone(),
+            method,
+            structured,
+            sibling_context: None,
         };
 
-        let content = serde_json::to_string_pretty(&cache_summary)
-            .map_err(|e| DocTreeError::cache(format!("Failed to serialize cache: {e}")))?;
-        
-        fs::write(&cache_path, content)
-            .map_err(|e| DocTreeError::cache(format!("Failed to write cache file: {e}")))?;
+        self.write_entry(&cache_path, &cache_summary)?;
+        if let Some(index) = self.hash_index.as_mut().filter(|_| cache_summary.is_reusable()) {
+            index.entry(cache_summary.content_hash).or_insert(cache_summary.source_path);
+        }
         
-        log::debug!("Stored summary for: {} at {}", source_path.display(), cache_path.display());
+        tracing::debug!("Stored summary for: {} at {}", source_path.display(), cache_path.display());
         
         Ok(())
     }
 
+    /// Marks an existing entry as pinned (or unpins it). Returns false when
+    /// there is no cached summary for the path.
+    pub fn set_pinned(&mut self, source_path: &Path, pinned: bool) -> Result<bool> {
+        let Some(mut summary) = self.get_cache_summary(source_path) else {
+            return Ok(false);
+        };
+
+        summary.pinned = pinned;
+        let cache_path = self.get_cache_path(source_path)?;
+        self.write_entry(&cache_path, &summary)?;
+
+        Ok(true)
+    }
+
+    /// Records the sibling summaries an entry was re-summarized with, so the
+    /// pass is skipped until they change.
+    pub fn set_sibling_context(&mut self, source_path: &Path, siblings_hash: String) -> Result<()> {
+        let Some(mut summary) = self.get_cache_summary(source_path) else {
+            return Ok(());
+        };
+
+        summary.sibling_context = Some(siblings_hash);
+        let cache_path = self.get_cache_path(source_path)?;
+        self.write_entry(&cache_path, &summary)
+    }
+
+    /// Replaces the summary text of an existing entry with a human-edited
+    /// version and pins it so regeneration never overwrites it.
+    pub fn store_pinned_summary(&mut self, source_path: &Path, summary: String) -> Result<()> {
+        let mut entry = self.get_cache_summary(source_path).ok_or_else(|| {
+            DocTreeError::cache(format!("No cached summary for {}", source_path.display()))
+        })?;
+
+        entry.summary = summary;
+        entry.pinned = true;
+        entry.timestamp = current_timestamp();
+
+        let cache_path = self.get_cache_path(source_path)?;
+        self.write_entry(&cache_path, &entry)?;
+
+        tracing::debug!("Stored pinned summary for: {}", source_path.display());
+        Ok(())
+    }
+
+    /// Invalidates the unpinned directory summaries of every ancestor of a
+    /// path up to the project root, so the next run re-rolls them.
+    pub fn invalidate_ancestors(&mut self, source_path: &Path) -> Result<Vec<PathBuf>> {
+        let mut invalidated = Vec::new();
+        let mut current = source_path.parent();
+
+        while let Some(ancestor) = current {
+            if !ancestor.starts_with(&self.base_path) {
+                break;
+            }
+
+            if let Some(entry) = self.get_cache_summary(ancestor) {
+                if !entry.pinned {
+                    self.invalidate_entry(ancestor)?;
+                    invalidated.push(ancestor.to_path_buf());
+                }
+            }
+
+            if ancestor == self.base_path {
+                break;
+            }
+            current = ancestor.parent();
+        }
+
+        Ok(invalidated)
+    }
+
     pub fn invalidate_entry(&mut self, source_path: &Path) -> Result<()> {
         let cache_path = self.get_cache_path(source_path)?;
         
-        if cache_path.exists() {
-            fs::remove_file(&cache_path)
-                .map_err(|e| DocTreeError::cache(format!("Failed to remove cache file: {e}")))?;
-            log::debug!("Invalidated cache entry for: {}", source_path.display());
-        }
+        self.remove_entry(&cache_path)?;
+        tracing::debug!("Invalidated cache entry for: {}", source_path.display());
         
         Ok(())
     }
 
     pub fn clear_cache(&mut self) -> Result<()> {
+        if self.read_only {
+            return Err(DocTreeError::cache("Cannot clear the cache in read-only mode"));
+        }
+
         if self.cache_dir.exists() {
             // Remove all .summary.json and .dir_summary.json files but keep mappings
             Self::clear_cache_files(&self.cache_dir)?;
-            log::info!("Cleared cache files in: {}", self.cache_dir.display());
+            for file in [RUN_STATE_FILE, USAGE_EXAMPLES_FILE] {
+                let path = self.cache_dir.join(file);
+                if path.exists() {
+                    fs::remove_file(path)?;
+                }
+            }
+            tracing::info!("Cleared cache files in: {}", self.cache_dir.display());
         }
         
         Ok(())
@@ -215,6 +834,30 @@ impl CacheManager {
         Ok(())
     }
 
+    /// Progress of the latest run, if one was recorded.
+    pub fn load_run_state(&self) -> Result<Option<RunState>> {
+        RunState::load(&self.cache_dir)
+    }
+
+    pub fn save_run_state(&self, state: &RunState) -> Result<()> {
+        if self.read_only {
+            return Ok(());
+        }
+        state.save(&self.cache_dir)
+    }
+
+    /// Usage examples distilled on an earlier run, if any.
+    pub fn load_usage_examples(&self) -> Result<Option<UsageExamples>> {
+        UsageExamples::load(&self.cache_dir)
+    }
+
+    pub fn save_usage_examples(&self, examples: &UsageExamples) -> Result<()> {
+        if self.read_only {
+            return Ok(());
+        }
+        examples.save(&self.cache_dir)
+    }
+
     pub fn get_cache_stats(&self) -> (usize, u64) {
         let mut entry_count = 0;
         let mut total_size = 0u64;
@@ -246,80 +889,185 @@ impl CacheManager {
     }
 
 
+    /// Summary files a run cannot use: unparseable JSON, or a content hash
+    /// that is not a SHA-256 hex digest. Runs ignore and regenerate them.
+    pub fn check_integrity(&self) -> Vec<(PathBuf, String)> {
+        let mut problems = Vec::new();
+        if self.cache_dir.exists() {
+            Self::check_summary_files(&self.cache_dir, &mut problems);
+        }
+        problems
+    }
+
+    fn check_summary_files(dir: &Path, problems: &mut Vec<(PathBuf, String)>) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if path.is_dir() {
+                Self::check_summary_files(&path, problems);
+            } else if name.ends_with(".summary.json") || name == ".dir_summary.json" {
+                let problem = match fs::read_to_string(&path).map(|content| serde_json::from_str::<CacheSummary>(&content)) {
+                    Err(e) => Some(format!("unreadable: {e}")),
+                    Ok(Err(e)) => Some(format!("invalid JSON: {e}")),
+                    Ok(Ok(summary))
+                        if summary.content_hash.len() != 64
+                            || !summary.content_hash.chars().all(|c| c.is_ascii_hexdigit()) =>
+                    {
+                        Some(format!("content hash '{}' is not a SHA-256 digest", summary.content_hash))
+                    }
+                    Ok(Ok(_)) => None,
+                };
+                if let Some(problem) = problem {
+                    problems.push((path, problem));
+                }
+            }
+        }
+    }
+
     pub fn is_cache_valid(&self) -> bool {
         // Cache is always valid in the new structure since each file is independent
         true
     }
 
     pub fn cleanup_old_entries(&mut self, max_age_days: u64) -> Result<()> {
-        let cutoff_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs() - (max_age_days * 24 * 60 * 60);
-
-        Self::cleanup_old_files(&self.cache_dir, cutoff_time)?;
+        self.apply_refresh_policy(&RefreshPolicy::uniform(max_age_days * 24 * 60 * 60))?;
         Ok(())
     }
-    
-    fn cleanup_old_files(dir: &Path, cutoff_time: u64) -> Result<()> {
-        if !dir.exists() {
-            return Ok(());
-        }
-        
-        for entry in fs::read_dir(dir)? {
-            let entry = entry?;
-            let path = entry.path();
-            
-            if path.is_dir() {
-                Self::cleanup_old_files(&path, cutoff_time)?;
-            } else if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                if name.ends_with(".summary.json") || name == ".dir_summary.json" {
-                    if let Ok(content) = fs::read_to_string(&path) {
-                        if let Ok(summary) = serde_json::from_str::<CacheSummary>(&content) {
-                            if summary.timestamp < cutoff_time {
-                                fs::remove_file(&path)?;
-                                log::debug!("Removed old cache file: {}", path.display());
-                            }
-                        }
-                    }
-                }
+
+    /// Removes every cache entry the policy considers expired and returns how
+    /// many were removed.
+    pub fn apply_refresh_policy(&mut self, policy: &RefreshPolicy) -> Result<usize> {
+        let now = current_timestamp();
+        let mut removed = 0;
+
+        for summary in self.get_all_summaries() {
+            if !summary.pinned
+                && policy.is_expired(self.relative_path(&summary.source_path), summary.timestamp, now)
+            {
+                self.remove_cache_file(&summary.source_path, summary.is_directory)?;
+                removed += 1;
             }
         }
+
+        if removed > 0 {
+            tracing::info!("Refreshed {removed} expired cache entries");
+        }
+        Ok(removed)
+    }
+
+    fn remove_cache_file(&mut self, source_path: &Path, is_directory: bool) -> Result<()> {
+        let relative_path = self.relative_path(source_path);
+        let cache_path = if is_directory {
+            self.cache_dir.join(relative_path).join(".dir_summary.json")
+        } else {
+            self.get_cache_path(source_path)?
+        };
+
+        self.remove_entry(&cache_path)?;
+        tracing::debug!("Removed old cache file: {}", cache_path.display());
         Ok(())
     }
 
     pub fn load_mapping(&mut self) -> Result<()> {
-        if self.mapping_file.exists() {
-            let content = fs::read_to_string(&self.mapping_file)?;
-            self.mapping_data = serde_json::from_str(&content)
-                .map_err(|e| DocTreeError::cache(format!("Failed to parse mapping: {e}")))?;
-            
-            log::info!("Loaded README mapping with {} entries", self.mapping_data.mappings.len());
-        } else {
-            log::info!("No existing README mapping found");
-            self.mapping_data = ReadmeMappingData::default();
+        match self.read_mapping_file()? {
+            Some(mapping_data) => {
+                self.mapping_data = mapping_data;
+                tracing::info!("Loaded README mapping with {} entries", self.mapping_data.mappings.len());
+            }
+            None => {
+                tracing::info!("No existing README mapping found");
+                self.mapping_data = ReadmeMappingData::default();
+            }
         }
+        self.mappings_replaced = false;
         Ok(())
     }
 
-    pub fn save_mapping(&self) -> Result<()> {
+    fn read_mapping_file(&self) -> Result<Option<ReadmeMappingData>> {
+        if !self.mapping_file.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(&self.mapping_file)?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| DocTreeError::cache(format!("Failed to parse mapping: {e}")))
+    }
+
+    /// Saves the mapping, merging with changes another process (e.g. a
+    /// watch task) saved since it was loaded instead of overwriting them.
+    pub async fn save_mapping(&mut self) -> Result<()> {
+        if self.read_only {
+            tracing::debug!("Read-only mode, keeping README mapping in memory");
+            return Ok(());
+        }
+
         self.initialize_cache_directory()?;
-        
+        let lock_path = self.mapping_file.with_extension("json.lock");
+        let _lock = tokio::time::timeout(MAPPING_LOCK_TIMEOUT, FileLock::acquire(&lock_path))
+            .await
+            .map_err(|_| DocTreeError::cache(format!("Timed out waiting for {} to be released", lock_path.display())))??;
+
+        if let Some(on_disk) = self.read_mapping_file()? {
+            if on_disk.revision != self.mapping_data.revision {
+                tracing::debug!(
+                    "README mapping was saved elsewhere (revision {} -> {}), merging",
+                    self.mapping_data.revision,
+                    on_disk.revision
+                );
+                self.mapping_data = self.mapping_data.merge_into(on_disk, self.mappings_replaced);
+            }
+        }
+        self.mapping_data.revision += 1;
+
         let content = serde_json::to_string_pretty(&self.mapping_data)
             .map_err(|e| DocTreeError::cache(format!("Failed to serialize mapping: {e}")))?;
-        
-        fs::write(&self.mapping_file, content)
+
+        // Write then rename so readers never see a half-written file
+        let temp_file = self.mapping_file.with_extension("json.tmp");
+        fs::write(&temp_file, content)
+            .and_then(|_| fs::rename(&temp_file, &self.mapping_file))
             .map_err(|e| DocTreeError::cache(format!("Failed to write mapping file: {e}")))?;
-        
-        log::debug!("README mapping saved with {} entries", self.mapping_data.mappings.len());
+        self.mappings_replaced = false;
+
+        tracing::debug!("README mapping saved with {} entries", self.mapping_data.mappings.len());
         Ok(())
     }
 
 
-    pub fn update_readme_mapping(&mut self, readme_hash: String, mappings: Vec<ReadmeLineMapping>) -> Result<()> {
+    pub async fn update_readme_mapping(
+        &mut self,
+        readme_hash: String,
+        sections: Vec<ReadmeSectionMapping>,
+        mappings: Vec<ReadmeLineMapping>,
+    ) -> Result<()> {
         self.mapping_data.readme_hash = readme_hash;
+        self.mapping_data.sections = sections;
         self.mapping_data.mappings = mappings;
-        self.save_mapping()
+        self.mappings_replaced = true;
+        self.save_mapping().await
+    }
+
+    pub async fn record_generated_content(&mut self, content: &str) -> Result<()> {
+        let hashes = ProvenanceTracker::generated_sentence_hashes(content);
+        let before = self.mapping_data.generated_sentences.len();
+        self.mapping_data.generated_sentences.extend(hashes);
+
+        if self.mapping_data.generated_sentences.len() != before {
+            self.save_mapping().await?;
+        }
+        Ok(())
+    }
+
+    /// Records the lines a document gained when doctreeai's suggestions were
+    /// applied to it as generated content. Suggestions that were declined
+    /// never reach a document, so they are not recorded.
+    pub async fn record_applied_content(&mut self, before: &str, after: &str) -> Result<()> {
+        let existing: HashSet<&str> = before.lines().collect();
+        let added: Vec<&str> = after.lines().filter(|line| !existing.contains(line)).collect();
+        self.record_generated_content(&added.join("\n")).await
     }
 
     pub fn get_readme_mapping(&self) -> &ReadmeMappingData {
@@ -340,13 +1088,7 @@ impl CacheManager {
 
     pub fn get_cache_summary(&self, source_path: &Path) -> Option<CacheSummary> {
         let cache_path = self.get_cache_path(source_path).ok()?;
-        
-        if !cache_path.exists() {
-            return None;
-        }
-        
-        let content = fs::read_to_string(&cache_path).ok()?;
-        serde_json::from_str(&content).ok()
+        self.read_entry(&cache_path)
     }
 
     pub fn get_all_summaries(&self) -> Vec<CacheSummary> {
@@ -354,6 +1096,15 @@ impl CacheManager {
         if self.cache_dir.exists() {
             Self::collect_summaries(&self.cache_dir, &mut summaries);
         }
+
+        if !self.memory.is_empty() {
+            summaries.retain(|summary| {
+                self.get_cache_path(&summary.source_path)
+                    .map(|path| !self.memory.contains_key(&path))
+                    .unwrap_or(true)
+            });
+            summaries.extend(self.memory.values().flatten().cloned());
+        }
         summaries
     }
     
@@ -363,7 +1114,9 @@ impl CacheManager {
                 let path = entry.path();
                 
                 if path.is_dir() {
-                    Self::collect_summaries(&path, summaries);
+                    if entry.file_name() != PACKAGE_CACHE_DIR {
+                        Self::collect_summaries(&path, summaries);
+                    }
                 } else if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
                     if name.ends_with(".summary.json") || name == ".dir_summary.json" {
                         if let Ok(content) = fs::read_to_string(&path) {
@@ -410,6 +1163,236 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_concurrent_mapping_saves_merge() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut validator = CacheManager::new(temp_dir.path(), ".test_cache")?;
+        let mut watcher = CacheManager::new(temp_dir.path(), ".test_cache")?;
+
+        let mapping = |line_number: usize, key: &str| ReadmeLineMapping {
+            line_number,
+            line_content: format!("Line {line_number}"),
+            cache_keys: vec![key.to_string()],
+            last_validated_hash: None,
+            section: String::new(),
+        };
+
+        watcher.record_generated_content("Generated by the watcher.").await?;
+        // Only what a document gained counts as generated
+        validator.record_applied_content("Kept by hand.", "Kept by hand.\nApplied from a suggestion.").await?;
+        validator
+            .update_readme_mapping("readme".to_string(), Vec::new(), vec![mapping(1, "a"), mapping(2, "b")])
+            .await?;
+        watcher.update_readme_mapping("readme".to_string(), Vec::new(), vec![mapping(2, "c")]).await?;
+        validator.record_generated_content("Generated by the validator.").await?;
+
+        let mut reloaded = CacheManager::new(temp_dir.path(), ".test_cache")?;
+        reloaded.load_mapping()?;
+        let data = reloaded.get_readme_mapping();
+        assert_eq!(data.revision, 5);
+        assert_eq!(data.generated_sentences.len(), 3);
+        assert!(!data.generated_sentences.contains(&ProvenanceTracker::sentence_hash("Kept by hand.")));
+        let keys: Vec<_> = data.mappings.iter().map(|m| m.cache_keys[0].as_str()).collect();
+        assert_eq!(keys, vec!["a", "c"]);
+        assert!(FileLock::try_acquire(&temp_dir.path().join(".test_cache/readme_mapping.json.lock"))?.is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_only_mode_writes_nothing() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut cache = CacheManager::new(temp_dir.path(), ".test_cache")?;
+        cache.set_read_only(true);
+
+        let file_path = temp_dir.path().join("lib.rs");
+        fs::write(&file_path, "pub fn a() {}")?;
+
+        cache.initialize_cache_directory()?;
+        cache.store_summary(&file_path, "hash".to_string(), "Summary".to_string())?;
+        cache.record_generated_content("Generated sentence.").await?;
+
+        assert_eq!(cache.get_cached_summary(&file_path, "hash"), Some("Summary".to_string()));
+        assert_eq!(cache.get_all_summaries().len(), 1);
+        assert!(!temp_dir.path().join(".test_cache").exists());
+        assert!(!temp_dir.path().join(".gitignore").exists());
+
+        cache.invalidate_entry(&file_path)?;
+        assert_eq!(cache.get_cached_summary(&file_path, "hash"), None);
+        assert!(cache.clear_cache().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_external_cache_dir_leaves_gitignore_alone() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let package_path = temp_dir.path().join("crates/api");
+        fs::create_dir_all(&package_path)?;
+        let cache_dir = temp_dir.path().join(".test_cache").join(PACKAGE_CACHE_DIR).join("crates/api");
+
+        let cache = CacheManager::new(&package_path, &cache_dir.to_string_lossy())?;
+        cache.initialize_cache_directory()?;
+
+        assert!(cache_dir.is_dir());
+        assert!(!package_path.join(".gitignore").exists());
+        assert!(!cache_dir.parent().unwrap().join(".gitignore").exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_refresh_policy_parsing_and_matching() -> Result<()> {
+        let policy = RefreshPolicy::parse("docs/=7d, docs/api=1h, *=90d")?;
+        assert_eq!(policy.rules.len(), 3);
+
+        assert_eq!(policy.ttl_for(Path::new("docs/guide.md")), Some(7 * 24 * 3600));
+        assert_eq!(policy.ttl_for(Path::new("docs/api/index.md")), Some(3600));
+        assert_eq!(policy.ttl_for(Path::new("src/main.rs")), Some(90 * 24 * 3600));
+        assert_eq!(policy.ttl_for(Path::new("docsite/index.md")), Some(90 * 24 * 3600));
+
+        assert!(policy.is_expired(Path::new("docs/api/index.md"), 0, 3601));
+        assert!(!policy.is_expired(Path::new("docs/api/index.md"), 0, 3600));
+
+        let mut capped = RefreshPolicy::parse("docs/=7d, docs/api=1h")?;
+        capped.cap(24 * 3600);
+        assert_eq!(capped.ttl_for(Path::new("docs/guide.md")), Some(24 * 3600));
+        assert_eq!(capped.ttl_for(Path::new("docs/api/index.md")), Some(3600));
+        assert_eq!(capped.ttl_for(Path::new("src/main.rs")), Some(24 * 3600));
+
+        assert!(RefreshPolicy::parse("docs").is_err());
+        assert!(parse_duration_secs("5y").is_err());
+        assert!(parse_duration_secs(&format!("{}w", u64::MAX / 2)).is_err());
+        assert_eq!(parse_duration_secs("2w")?, 14 * 24 * 3600);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expired_entries_are_cache_misses() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut cache = CacheManager::new(temp_dir.path(), ".test_cache")?;
+        let docs_path = temp_dir.path().join("docs/guide.md");
+        let src_path = temp_dir.path().join("src/main.rs");
+
+        cache.store_summary(&docs_path, "hash".to_string(), "Guide".to_string())?;
+        cache.store_summary(&src_path, "hash".to_string(), "Main".to_string())?;
+
+        // A zero TTL on docs/ expires entries written in a previous second
+        let policy = RefreshPolicy::parse("docs/=0s")?;
+        let mut summary = cache.get_cache_summary(&docs_path).unwrap();
+        summary.timestamp -= 10;
+        let cache_path = cache.get_cache_path(&docs_path)?;
+        fs::write(&cache_path, serde_json::to_string(&summary)?)?;
+
+        cache.set_refresh_policy(policy.clone());
+        assert_eq!(cache.get_cached_summary(&docs_path, "hash"), None);
+        assert_eq!(cache.get_cached_summary(&src_path, "hash"), Some("Main".to_string()));
+
+        assert_eq!(cache.apply_refresh_policy(&policy)?, 1);
+        assert!(cache.get_cache_summary(&docs_path).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pinned_entries_are_not_overwritten() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut cache = CacheManager::new(temp_dir.path(), ".test_cache")?;
+        let file_path = temp_dir.path().join("lib.rs");
+        fs::write(&file_path, "pub fn a() {}")?;
+
+        let hash1 = cache.hash_file(&file_path)?;
+        cache.store_summary(&file_path, hash1.clone(), "Curated".to_string())?;
+        assert!(cache.set_pinned(&file_path, true)?);
+
+        cache.store_summary(&file_path, "hash2".to_string(), "Generated".to_string())?;
+        let entry = cache.get_cache_summary(&file_path).unwrap();
+        assert!(entry.pinned);
+        assert_eq!(entry.summary, "Curated");
+        assert_eq!(entry.content_size, Some(13));
+        assert_eq!(entry.size_drift(26), Some(1.0));
+
+        assert!(!cache.set_pinned(&temp_dir.path().join("missing.rs"), true)?);
+
+        // Kept after a language change, unlike generated summaries
+        cache.set_doc_language(Some("German".to_string()));
+        assert!(cache.get_cached_entry(&file_path, &hash1).is_some());
+        let other_path = temp_dir.path().join("other.rs");
+        fs::write(&other_path, "pub fn b() {}")?;
+        cache.store_summary(&other_path, "hash3".to_string(), "Zusammenfassung".to_string())?;
+        assert!(cache.get_cached_entry(&other_path, "hash3").is_some());
+        cache.set_doc_language(None);
+        assert!(cache.get_cached_entry(&other_path, "hash3").is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unchanged_files_keep_their_cached_hash() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut cache = CacheManager::new(temp_dir.path(), ".test_cache")?;
+        let file_path = temp_dir.path().join("lib.rs");
+        let an_hour_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        fs::write(&file_path, "pub fn a() {}")?;
+
+        // Modified in the second it was hashed: always rehashed
+        let hash1 = cache.hash_file(&file_path)?;
+        cache.store_summary(&file_path, hash1.clone(), "Summary".to_string())?;
+        assert_eq!(cache.unchanged_hash(&file_path), None);
+
+        fs::File::options().write(true).open(&file_path)?.set_modified(an_hour_ago)?;
+        assert_eq!(cache.hash_file(&file_path)?, hash1);
+        // Edited while the summary was being generated: the entry keeps
+        // what was hashed, so the next run notices the edit
+        fs::write(&file_path, "pub fn bb() {}")?;
+        cache.store_summary(&file_path, hash1.clone(), "Summary".to_string())?;
+        assert_eq!(cache.get_cache_summary(&file_path).unwrap().content_size, Some(13));
+        assert_eq!(cache.unchanged_hash(&file_path), None);
+
+        fs::write(&file_path, "pub fn a() {}")?;
+        fs::File::options().write(true).open(&file_path)?.set_modified(an_hour_ago)?;
+        assert_eq!(cache.unchanged_hash(&file_path), Some(hash1));
+
+        fs::write(&file_path, "pub fn b() {}")?;
+        fs::File::options().write(true).open(&file_path)?.set_modified(an_hour_ago + std::time::Duration::from_secs(1))?;
+        assert_eq!(cache.unchanged_hash(&file_path), None);
+
+        let missing = temp_dir.path().join("missing.rs");
+        let hashed = cache.source_hasher().hash(&file_path)?;
+        assert_eq!(cache.record_hash(&file_path, hashed), FileHasher::compute_file_hash(&file_path)?);
+        assert!(cache.source_hasher().hash(&missing).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_edit_pins_summary_and_invalidates_ancestors() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        let src_dir = base_path.join("src");
+        let file_path = src_dir.join("lib.rs");
+        fs::create_dir_all(&src_dir)?;
+        fs::write(&file_path, "pub fn a() {}")?;
+
+        let mut cache = CacheManager::new(base_path, ".test_cache")?;
+        cache.store_summary(&file_path, "hash".to_string(), "Generated".to_string())?;
+        cache.store_summary(&src_dir, "dirhash".to_string(), "Src".to_string())?;
+        cache.store_summary(base_path, "roothash".to_string(), "Root".to_string())?;
+
+        cache.store_pinned_summary(&file_path, "Curated".to_string())?;
+        let entry = cache.get_cache_summary(&file_path).unwrap();
+        assert!(entry.pinned);
+        assert_eq!(entry.summary, "Curated");
+
+        let invalidated = cache.invalidate_ancestors(&file_path)?;
+        assert_eq!(invalidated, vec![src_dir.clone(), base_path.to_path_buf()]);
+        assert!(cache.get_cache_summary(&src_dir).is_none());
+        assert!(cache.get_cache_summary(base_path).is_none());
+
+        Ok(())
+    }
+
     #[test]
     fn test_cache_persistence() -> Result<()> {
         let temp_dir = TempDir::new()?;
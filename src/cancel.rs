@@ -0,0 +1,107 @@
+use crate::error::{DocTreeError, Result};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+/// Shared flag that stops a run between (and during) LLM calls. Clones
+/// observe the same state, so one can be handed to a Ctrl+C handler.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    notify: Arc<Notify>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Fails with `DocTreeError::Cancelled` once cancelled.
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(DocTreeError::cancelled("Run was cancelled"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Resolves once the token is cancelled.
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Runs the future unless the token is cancelled first, in which case
+    /// the future is dropped and `DocTreeError::Cancelled` returned.
+    pub async fn run<T>(&self, future: impl Future<Output = Result<T>>) -> Result<T> {
+        self.check()?;
+        tokio::select! {
+            result = future => result,
+            _ = self.cancelled() => Err(DocTreeError::cancelled("Run was cancelled")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheManager;
+    use crate::config::Config;
+    use crate::llm::LanguageModelClient;
+    use crate::summarizer::HierarchicalSummarizer;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_cancel_interrupts_pending_future() -> Result<()> {
+        let token = CancellationToken::new();
+        assert_eq!(token.run(async { Ok(1) }).await?, 1);
+
+        let handle = token.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            handle.cancel();
+        });
+        let result = token.run(std::future::pending::<Result<()>>()).await;
+        assert!(matches!(result, Err(DocTreeError::Cancelled(_))));
+        assert!(token.check().is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_summarizer_stops_before_summarizing() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        std::fs::write(temp_dir.path().join("main.rs"), "fn main() {}")?;
+
+        let cache_manager = CacheManager::new(temp_dir.path(), ".test_cache")?;
+        let mut summarizer =
+            HierarchicalSummarizer::new(LanguageModelClient::mock(&Config::default()), cache_manager, false);
+        let token = CancellationToken::new();
+        token.cancel();
+        summarizer.set_cancellation(token);
+
+        match summarizer.generate_project_summary(temp_dir.path()).await {
+            Err(DocTreeError::Cancelled(message)) => assert!(message.contains("after 0 new summaries")),
+            other => panic!("expected cancellation, got {other:?}"),
+        }
+        assert_eq!(summarizer.get_cache_stats().0, 0);
+
+        Ok(())
+    }
+}
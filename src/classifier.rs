@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DirectoryRole {
+    Api,
+    Domain,
+    Infra,
+    Tooling,
+    Tests,
+    Docs,
+}
+
+impl DirectoryRole {
+    /// Roles in the order they are presented in generated documentation.
+    pub const ALL: [DirectoryRole; 6] = [
+        DirectoryRole::Api,
+        DirectoryRole::Domain,
+        DirectoryRole::Infra,
+        DirectoryRole::Tooling,
+        DirectoryRole::Tests,
+        DirectoryRole::Docs,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            DirectoryRole::Api => "API & Interfaces",
+            DirectoryRole::Domain => "Core Domain",
+            DirectoryRole::Infra => "Infrastructure",
+            DirectoryRole::Tooling => "Tooling",
+            DirectoryRole::Tests => "Tests",
+            DirectoryRole::Docs => "Documentation",
+        }
+    }
+
+    fn directory_names(&self) -> &'static [&'static str] {
+        match self {
+            DirectoryRole::Api => &[
+                "api", "apis", "routes", "handlers", "controllers", "endpoints", "server", "http",
+                "grpc", "rpc", "graphql", "cli", "cmd", "web", "ui", "views",
+            ],
+            DirectoryRole::Domain => &[
+                "domain", "core", "model", "models", "entities", "services", "logic", "lib", "pkg",
+                "internal",
+            ],
+            DirectoryRole::Infra => &[
+                "infra", "infrastructure", "deploy", "deployment", "k8s", "kubernetes", "terraform",
+                "helm", "docker", "ops", "db", "database", "migrations", "storage", "persistence",
+                "config", "configs",
+            ],
+            DirectoryRole::Tooling => &[
+                "tools", "tooling", "scripts", "bin", "xtask", "hack", "build", "ci", "devtools",
+            ],
+            DirectoryRole::Tests => &[
+                "test", "tests", "spec", "specs", "__tests__", "e2e", "integration", "fixtures",
+                "testdata", "benches", "benchmarks",
+            ],
+            DirectoryRole::Docs => &["doc", "docs", "documentation", "guides", "examples", "manual"],
+        }
+    }
+
+    fn summary_keywords(&self) -> &'static [&'static str] {
+        match self {
+            DirectoryRole::Api => &[
+                "endpoint", "route", "http", "request", "handler", "command-line", "cli", "rest",
+                "grpc", "interface",
+            ],
+            DirectoryRole::Domain => &[
+                "business", "model", "entity", "logic", "core", "algorithm", "domain", "engine",
+            ],
+            DirectoryRole::Infra => &[
+                "database", "deploy", "docker", "storage", "persistence", "infrastructure",
+                "kubernetes", "migration", "provision",
+            ],
+            DirectoryRole::Tooling => &[
+                "script", "build", "lint", "tooling", "automation", "generator", "task runner",
+            ],
+            DirectoryRole::Tests => &["test", "assert", "fixture", "mock", "benchmark"],
+            DirectoryRole::Docs => &["documentation", "guide", "tutorial", "markdown", "example"],
+        }
+    }
+}
+
+impl fmt::Display for DirectoryRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DirectoryRole::Api => "api",
+            DirectoryRole::Domain => "domain",
+            DirectoryRole::Infra => "infra",
+            DirectoryRole::Tooling => "tooling",
+            DirectoryRole::Tests => "tests",
+            DirectoryRole::Docs => "docs",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// How prominently a child appears in its parent directory's summary
+/// prompt, most important first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChildImportance {
+    EntryPoint,
+    Primary,
+    Supporting,
+    Tests,
+}
+
+/// File stems that usually mark a module's or program's entry point.
+const ENTRY_POINT_STEMS: &[&str] = &[
+    "main", "lib", "mod", "index", "__init__", "__main__", "app", "server", "cli",
+];
+
+/// Name fragments of test and fixture files.
+const TEST_FILE_MARKERS: &[&str] = &["test_", "_test.", ".test.", "_spec.", ".spec.", "conftest.", "fixture", "mock"];
+
+impl ChildImportance {
+    /// Annotation shown next to a file in the prompt; directories show their role instead.
+    pub fn label(&self) -> Option<&'static str> {
+        match self {
+            ChildImportance::EntryPoint => Some("entry point"),
+            ChildImportance::Tests => Some("tests"),
+            ChildImportance::Primary | ChildImportance::Supporting => None,
+        }
+    }
+
+    pub fn of_file(file_name: &str) -> Self {
+        let name = file_name.to_lowercase();
+        let stem = name.split('.').next().unwrap_or(&name);
+
+        if name == "tests.rs" || stem == "test" || TEST_FILE_MARKERS.iter().any(|marker| name.contains(marker)) {
+            ChildImportance::Tests
+        } else if ENTRY_POINT_STEMS.contains(&stem) {
+            ChildImportance::EntryPoint
+        } else {
+            ChildImportance::Primary
+        }
+    }
+
+    pub fn of_directory(role: DirectoryRole) -> Self {
+        match role {
+            DirectoryRole::Tests => ChildImportance::Tests,
+            DirectoryRole::Docs | DirectoryRole::Tooling => ChildImportance::Supporting,
+            DirectoryRole::Api | DirectoryRole::Domain | DirectoryRole::Infra => ChildImportance::Primary,
+        }
+    }
+}
+
+pub struct DirectoryClassifier;
+
+impl DirectoryClassifier {
+    /// Classifies a directory by its name first, falling back to keyword
+    /// matches in its summary. Unrecognized directories are treated as core
+    /// project code.
+    pub fn classify(directory_name: &str, summary: &str) -> DirectoryRole {
+        let name = directory_name.to_lowercase();
+
+        if let Some(role) = DirectoryRole::ALL
+            .iter()
+            .find(|role| role.directory_names().contains(&name.as_str()))
+        {
+            return *role;
+        }
+
+        let summary_lower = summary.to_lowercase();
+        let mut best = (DirectoryRole::Domain, 0);
+
+        for role in DirectoryRole::ALL {
+            let score = role
+                .summary_keywords()
+                .iter()
+                .map(|keyword| summary_lower.matches(keyword).count())
+                .sum::<usize>();
+            if score > best.1 {
+                best = (role, score);
+            }
+        }
+
+        best.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_by_name() {
+        assert_eq!(DirectoryClassifier::classify("tests", ""), DirectoryRole::Tests);
+        assert_eq!(DirectoryClassifier::classify("Docs", ""), DirectoryRole::Docs);
+        assert_eq!(DirectoryClassifier::classify("handlers", ""), DirectoryRole::Api);
+        assert_eq!(DirectoryClassifier::classify("terraform", ""), DirectoryRole::Infra);
+        assert_eq!(DirectoryClassifier::classify("scripts", ""), DirectoryRole::Tooling);
+    }
+
+    #[test]
+    fn test_classify_by_summary() {
+        let summary = "Database migrations and storage adapters used for persistence";
+        assert_eq!(DirectoryClassifier::classify("sql_stuff", summary), DirectoryRole::Infra);
+
+        assert_eq!(
+            DirectoryClassifier::classify("misc", "Assorted helpers"),
+            DirectoryRole::Domain
+        );
+    }
+
+    #[test]
+    fn test_child_importance() {
+        assert_eq!(ChildImportance::of_file("main.rs"), ChildImportance::EntryPoint);
+        assert_eq!(ChildImportance::of_file("__init__.py"), ChildImportance::EntryPoint);
+        assert_eq!(ChildImportance::of_file("parser.rs"), ChildImportance::Primary);
+        assert_eq!(ChildImportance::of_file("parser_test.go"), ChildImportance::Tests);
+        assert_eq!(ChildImportance::of_file("button.spec.ts"), ChildImportance::Tests);
+        assert_eq!(ChildImportance::of_directory(DirectoryRole::Docs), ChildImportance::Supporting);
+        assert!(ChildImportance::EntryPoint < ChildImportance::Tests);
+    }
+}
@@ -0,0 +1,544 @@
+use crate::cache::CacheSummary;
+use crate::references::{brace_delta, declared_binaries, kebab_case};
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Nesting limit for flattened argument structs and nested subcommands.
+const MAX_NESTING: usize = 8;
+
+/// One option or positional argument of a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliArgument {
+    /// How it is written: `-p, --path <PATH>`, `--force`, or `<TARGET>`.
+    pub usage: String,
+    pub help: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CliCommand {
+    /// Nested subcommands are space-separated, e.g. `index build`.
+    pub name: String,
+    pub about: String,
+    pub arguments: Vec<CliArgument>,
+}
+
+/// A command-line interface as declared in code, so README usage is
+/// rendered from the real flags instead of the LLM's guesses.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CliDefinition {
+    pub binary: String,
+    /// Options accepted before the subcommand.
+    pub global_arguments: Vec<CliArgument>,
+    pub commands: Vec<CliCommand>,
+}
+
+impl CliDefinition {
+    /// Finds the CLI in the summarized files: clap's derive API in Rust,
+    /// argparse in Python, and commander in JavaScript or TypeScript. None
+    /// when the project defines no CLI.
+    pub fn detect(base_path: &Path, summaries: &[CacheSummary]) -> Option<Self> {
+        let mut files: Vec<&CacheSummary> = summaries.iter().filter(|summary| !summary.is_directory).collect();
+        files.sort_by(|a, b| a.source_path.cmp(&b.source_path));
+
+        let mut definition = Self::default();
+        let mut clap = ClapItems::default();
+        let mut binaries = Vec::new();
+        for summary in files {
+            let Ok(content) = fs::read_to_string(&summary.source_path) else {
+                continue;
+            };
+            let relative_path = summary
+                .source_path
+                .strip_prefix(base_path)
+                .unwrap_or(&summary.source_path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            binaries.extend(declared_binaries(&relative_path, &content));
+            match summary.source_path.extension().and_then(|ext| ext.to_str()) {
+                Some("rs") => clap.parse(&content),
+                Some("py") => definition.merge(argparse(&content)),
+                Some("js" | "mjs" | "cjs" | "ts") => definition.merge(commander(&content)),
+                _ => {}
+            }
+        }
+        definition.merge(clap.resolve());
+
+        if definition.commands.is_empty() && definition.global_arguments.is_empty() {
+            return None;
+        }
+        definition.binary = binaries.into_iter().next().unwrap_or_else(|| {
+            base_path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("app")
+                .to_string()
+        });
+        Some(definition)
+    }
+
+    fn merge(&mut self, other: CliDefinition) {
+        self.global_arguments.extend(other.global_arguments);
+        self.commands.extend(other.commands);
+    }
+}
+
+#[derive(Debug, Default)]
+struct ClapField {
+    name: String,
+    ty: String,
+    attrs: String,
+    doc: String,
+}
+
+#[derive(Debug, Default)]
+struct ClapVariant {
+    name: String,
+    attrs: String,
+    doc: String,
+    fields: Vec<ClapField>,
+    /// `Variant(Args)`: the struct or enum it wraps.
+    wraps: Option<String>,
+}
+
+/// `#[derive(Parser)]`, `#[derive(Args)]`, and `#[derive(Subcommand)]`
+/// items, collected across files and resolved once everything is read.
+#[derive(Debug, Default)]
+struct ClapItems {
+    parser: Option<String>,
+    structs: HashMap<String, Vec<ClapField>>,
+    enums: HashMap<String, Vec<ClapVariant>>,
+}
+
+impl ClapItems {
+    fn parse(&mut self, content: &str) {
+        let item = Regex::new(r"^(?:pub(?:\([a-z]+\))?\s+)?(struct|enum)\s+([A-Za-z0-9_]+)").expect("valid regex");
+        let field = Regex::new(r"^(?:pub(?:\([a-z]+\))?\s+)?(?:r#)?([a-z_][a-z0-9_]*)\s*:\s*(.+?),?$").expect("valid regex");
+        let variant = Regex::new(r"^([A-Z][A-Za-z0-9]*)\s*(?:\(\s*([A-Za-z0-9_:<>]+)\s*\))?\s*[{,]?$").expect("valid regex");
+
+        let mut derive: Option<&str> = None;
+        // (derive, item name) while inside a clap item, with its brace depth
+        let mut current: Option<(&str, String)> = None;
+        let mut depth = 0;
+        let mut fields: Vec<ClapField> = Vec::new();
+        let mut variants: Vec<ClapVariant> = Vec::new();
+        let (mut attrs, mut doc) = (String::new(), String::new());
+        let mut in_attribute = false;
+
+        for line in content.lines() {
+            let trimmed = line.trim();
+            let Some((kind, name)) = current.clone() else {
+                if trimmed.starts_with("#[derive(") {
+                    derive = ["Parser", "Args", "Subcommand"].into_iter().find(|kind| trimmed.contains(kind));
+                } else if let Some(captures) = item.captures(trimmed) {
+                    if let Some(kind) = derive.take().filter(|_| brace_delta(trimmed) > 0) {
+                        current = Some((kind, captures[2].to_string()));
+                        depth = brace_delta(trimmed);
+                    }
+                } else if !trimmed.is_empty() && !trimmed.starts_with("#[") && !trimmed.starts_with("//") {
+                    derive = None;
+                }
+                continue;
+            };
+
+            if in_attribute || trimmed.starts_with("#[") {
+                attrs.push_str(trimmed);
+                attrs.push(' ');
+                in_attribute = !trimmed.ends_with(']');
+                continue;
+            }
+            if let Some(comment) = trimmed.strip_prefix("///") {
+                doc.push_str(comment.trim());
+                doc.push(' ');
+                continue;
+            }
+
+            let is_enum = kind == "Subcommand";
+            if depth == 1 && !is_enum {
+                if let Some(captures) = field.captures(trimmed) {
+                    fields.push(ClapField {
+                        name: captures[1].to_string(),
+                        ty: captures[2].to_string(),
+                        attrs: std::mem::take(&mut attrs),
+                        doc: std::mem::take(&mut doc),
+                    });
+                }
+            } else if depth == 1 {
+                if let Some(captures) = variant.captures(trimmed) {
+                    variants.push(ClapVariant {
+                        name: captures[1].to_string(),
+                        attrs: std::mem::take(&mut attrs),
+                        doc: std::mem::take(&mut doc),
+                        fields: Vec::new(),
+                        wraps: captures.get(2).map(|ty| ty.as_str().to_string()),
+                    });
+                }
+            } else if depth == 2 && is_enum {
+                if let (Some(captures), Some(variant)) = (field.captures(trimmed), variants.last_mut()) {
+                    variant.fields.push(ClapField {
+                        name: captures[1].to_string(),
+                        ty: captures[2].to_string(),
+                        attrs: std::mem::take(&mut attrs),
+                        doc: std::mem::take(&mut doc),
+                    });
+                }
+            }
+            attrs.clear();
+            doc.clear();
+
+            depth += brace_delta(trimmed);
+            if depth <= 0 {
+                match kind {
+                    "Subcommand" => {
+                        self.enums.insert(name, std::mem::take(&mut variants));
+                    }
+                    kind => {
+                        if kind == "Parser" {
+                            self.parser = Some(name.clone());
+                        }
+                        self.structs.insert(name, std::mem::take(&mut fields));
+                    }
+                }
+                current = None;
+            }
+        }
+    }
+
+    /// The CLI rooted at the `#[derive(Parser)]` struct, if there is one.
+    fn resolve(&self) -> CliDefinition {
+        let mut definition = CliDefinition::default();
+        let Some(fields) = self.parser.as_ref().and_then(|parser| self.structs.get(parser)) else {
+            return definition;
+        };
+
+        let mut subcommand = None;
+        self.arguments(fields, &mut definition.global_arguments, &mut subcommand, 0);
+        if let Some(subcommand) = subcommand {
+            self.commands(&subcommand, "", &mut definition.commands, 0);
+        }
+        definition
+    }
+
+    fn arguments(&self, fields: &[ClapField], arguments: &mut Vec<CliArgument>, subcommand: &mut Option<String>, nesting: usize) {
+        for field in fields {
+            let keywords = without_strings(&field.attrs);
+            if has_keyword(&keywords, "flatten") {
+                if let Some(flattened) = self.structs.get(&type_name(&field.ty)).filter(|_| nesting < MAX_NESTING) {
+                    self.arguments(flattened, arguments, subcommand, nesting + 1);
+                }
+            } else if has_keyword(&keywords, "subcommand") {
+                *subcommand = Some(type_name(&field.ty));
+            } else if !has_keyword(&keywords, "skip") && !keywords.contains("hide = true") {
+                arguments.push(clap_argument(field));
+            }
+        }
+    }
+
+    fn commands(&self, enum_name: &str, prefix: &str, commands: &mut Vec<CliCommand>, nesting: usize) {
+        let Some(variants) = self.enums.get(enum_name).filter(|_| nesting < MAX_NESTING) else {
+            return;
+        };
+
+        for variant in variants {
+            if has_keyword(&without_strings(&variant.attrs), "skip") {
+                continue;
+            }
+            let name = format!(
+                "{prefix}{}",
+                attribute_value(&variant.attrs, "name").unwrap_or_else(|| kebab_case(&variant.name))
+            );
+            let mut arguments = Vec::new();
+            let mut nested = None;
+            match variant.wraps.as_deref().map(type_name) {
+                Some(wrapped) if self.enums.contains_key(&wrapped) => nested = Some(wrapped),
+                Some(wrapped) => {
+                    if let Some(fields) = self.structs.get(&wrapped) {
+                        self.arguments(fields, &mut arguments, &mut nested, nesting + 1);
+                    }
+                }
+                None => {}
+            }
+            self.arguments(&variant.fields, &mut arguments, &mut nested, nesting + 1);
+
+            commands.push(CliCommand {
+                name: name.clone(),
+                about: attribute_value(&variant.attrs, "about").unwrap_or_else(|| variant.doc.trim().to_string()),
+                arguments,
+            });
+            if let Some(nested) = nested {
+                self.commands(&nested, &format!("{name} "), commands, nesting + 1);
+            }
+        }
+    }
+}
+
+fn clap_argument(field: &ClapField) -> CliArgument {
+    let keywords = without_strings(&field.attrs);
+    let long = has_keyword(&keywords, "long")
+        .then(|| attribute_value(&field.attrs, "long").unwrap_or_else(|| field.name.replace('_', "-")));
+    let short = has_keyword(&keywords, "short").then(|| {
+        Regex::new(r"\bshort\s*=\s*'(.)'")
+            .expect("valid regex")
+            .captures(&field.attrs)
+            .and_then(|captures| captures[1].chars().next())
+            .unwrap_or_else(|| field.name.chars().next().unwrap_or('?'))
+    });
+    let value_name = attribute_value(&field.attrs, "value_name").unwrap_or_else(|| field.name.to_uppercase());
+    let takes_value = field.ty != "bool" && !["ArgAction::Count", "ArgAction::SetTrue", "ArgAction::SetFalse"]
+        .iter()
+        .any(|action| keywords.contains(action));
+
+    let usage = if long.is_none() && short.is_none() {
+        if field.ty.starts_with("Option<") {
+            format!("[{value_name}]")
+        } else {
+            format!("<{value_name}>")
+        }
+    } else {
+        let names: Vec<String> = short
+            .map(|short| format!("-{short}"))
+            .into_iter()
+            .chain(long.map(|long| format!("--{long}")))
+            .collect();
+        if takes_value {
+            format!("{} <{value_name}>", names.join(", "))
+        } else {
+            names.join(", ")
+        }
+    };
+
+    CliArgument {
+        usage,
+        help: attribute_value(&field.attrs, "help").unwrap_or_else(|| field.doc.trim().to_string()),
+    }
+}
+
+/// The named type inside `Option<...>` or `Box<...>`, without its module
+/// path.
+fn type_name(ty: &str) -> String {
+    let mut ty = ty.trim();
+    for wrapper in ["Option<", "Box<"] {
+        if let Some(inner) = ty.strip_prefix(wrapper).and_then(|inner| inner.strip_suffix('>')) {
+            ty = inner.trim();
+        }
+    }
+    ty.rsplit("::").next().unwrap_or(ty).to_string()
+}
+
+/// Attribute text with string literals emptied, so keywords inside help
+/// text are not mistaken for settings.
+fn without_strings(attrs: &str) -> String {
+    Regex::new(r#""(?:[^"\\]|\\.)*""#)
+        .expect("valid regex")
+        .replace_all(attrs, "\"\"")
+        .to_string()
+}
+
+fn has_keyword(keywords: &str, keyword: &str) -> bool {
+    Regex::new(&format!(r"\b{keyword}\b"))
+        .expect("valid regex")
+        .is_match(keywords)
+}
+
+/// `key = "value"` from a Rust attribute or Python/JS keyword arguments.
+fn attribute_value(attrs: &str, key: &str) -> Option<String> {
+    Regex::new(&format!(r#"\b{key}\s*=\s*(?:"((?:[^"\\]|\\.)*)"|'([^']*)')"#))
+        .expect("valid regex")
+        .captures(attrs)
+        .and_then(|captures| captures.get(1).or(captures.get(2)))
+        .map(|value| value.as_str().replace("\\\"", "\""))
+}
+
+/// Subparsers and arguments declared with Python's argparse.
+fn argparse(content: &str) -> CliDefinition {
+    let mut definition = CliDefinition::default();
+    if !content.contains("argparse") {
+        return definition;
+    }
+    let call = Regex::new(r#"(?:(\w+)\s*=\s*)?(\w+)\.(add_parser|add_argument)\(([^)]*)\)"#).expect("valid regex");
+    let literal = Regex::new(r#"^\s*(?:"([^"]*)"|'([^']*)')\s*$"#).expect("valid regex");
+
+    let mut parsers: HashMap<String, usize> = HashMap::new();
+    for captures in call.captures_iter(content) {
+        let args = &captures[4];
+        let names: Vec<String> = args
+            .split(',')
+            .map_while(|piece| literal.captures(piece))
+            .filter_map(|literal| literal.get(1).or(literal.get(2)).map(|name| name.as_str().to_string()))
+            .collect();
+        let Some(first) = names.first() else {
+            continue;
+        };
+        let help = attribute_value(args, "help").unwrap_or_default();
+
+        if &captures[3] == "add_parser" {
+            if let Some(variable) = captures.get(1) {
+                parsers.insert(variable.as_str().to_string(), definition.commands.len());
+            }
+            definition.commands.push(CliCommand {
+                name: first.clone(),
+                about: help,
+                arguments: Vec::new(),
+            });
+            continue;
+        }
+
+        let flags: Vec<&String> = names.iter().filter(|name| name.starts_with('-')).collect();
+        let value_name = attribute_value(args, "metavar").unwrap_or_else(|| {
+            flags
+                .iter()
+                .find(|flag| flag.starts_with("--"))
+                .map(|flag| flag.trim_start_matches('-'))
+                .unwrap_or(first.as_str())
+                .replace('-', "_")
+                .to_uppercase()
+        });
+        let takes_value = !matches!(
+            attribute_value(args, "action").as_deref(),
+            Some("store_true" | "store_false" | "store_const" | "count" | "help" | "version")
+        );
+        let usage = if flags.is_empty() {
+            format!("<{value_name}>")
+        } else {
+            let names = flags.iter().map(|flag| flag.as_str()).collect::<Vec<_>>().join(", ");
+            if takes_value {
+                format!("{names} <{value_name}>")
+            } else {
+                names
+            }
+        };
+
+        let argument = CliArgument { usage, help };
+        match parsers.get(&captures[2]) {
+            Some(&index) => definition.commands[index].arguments.push(argument),
+            None => definition.global_arguments.push(argument),
+        }
+    }
+    definition
+}
+
+/// Commands, options, and arguments declared with commander's chained API.
+fn commander(content: &str) -> CliDefinition {
+    let mut definition = CliDefinition::default();
+    if !content.contains("commander") {
+        return definition;
+    }
+    let string = r#"(?:"([^"]*)"|'([^']*)'|`([^`]*)`)"#;
+    let call = Regex::new(&format!(
+        r"(\w+)?\s*\.(command|description|option|requiredOption|argument)\(\s*{string}(?:\s*,\s*{string})?"
+    ))
+    .expect("valid regex");
+
+    let mut current: Option<usize> = None;
+    for captures in call.captures_iter(content) {
+        let text = |first: usize| {
+            (first..first + 3)
+                .find_map(|index| captures.get(index))
+                .map(|value| value.as_str().to_string())
+        };
+        let (Some(first), second) = (text(3), text(6)) else {
+            continue;
+        };
+        // A call on a named object starts a new chain on the program
+        if captures.get(1).is_some() && &captures[2] != "command" {
+            current = None;
+        }
+
+        let argument = |usage: &str| CliArgument {
+            usage: usage.to_string(),
+            help: second.clone().unwrap_or_default(),
+        };
+        match (&captures[2], current) {
+            ("command", _) => {
+                let mut parts = first.split_whitespace();
+                let name = parts.next().unwrap_or_default().to_string();
+                let arguments = parts.map(argument).collect();
+                current = Some(definition.commands.len());
+                definition.commands.push(CliCommand { name, about: String::new(), arguments });
+            }
+            ("description", Some(index)) => definition.commands[index].about = first,
+            ("description", None) => {}
+            (_, Some(index)) => definition.commands[index].arguments.push(argument(&first)),
+            (_, None) => definition.global_arguments.push(argument(&first)),
+        }
+    }
+    definition
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clap_derive_definition() {
+        let source = r#"
+#[derive(Parser)]
+struct Cli {
+    #[arg(short, long, global = true, help = "Enable verbose logging")]
+    verbose: bool,
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Args)]
+struct ScanArgs {
+    /// Only scan these globs
+    #[arg(long = "include", value_name = "GLOB")]
+    include_patterns: Vec<String>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    #[command(about = "Run the pipeline")]
+    Run {
+        #[arg(short, long, help = "Target directory path")]
+        path: Option<PathBuf>,
+        #[arg(long, help = "Don't use the long cache")]
+        no_cache: bool,
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+    /// Manage the index
+    Index {
+        #[command(subcommand)]
+        action: IndexAction,
+    },
+}
+
+#[derive(Subcommand, Clone, Copy)]
+enum IndexAction {
+    #[command(name = "rebuild", about = "Embed everything")]
+    Build,
+}
+"#;
+        let mut items = ClapItems::default();
+        items.parse(source);
+        let cli = items.resolve();
+
+        assert_eq!(cli.global_arguments[0].usage, "-v, --verbose");
+        let names: Vec<&str> = cli.commands.iter().map(|command| command.name.as_str()).collect();
+        assert_eq!(names, vec!["run", "index", "index rebuild"]);
+        let usages: Vec<&str> = cli.commands[0].arguments.iter().map(|argument| argument.usage.as_str()).collect();
+        assert_eq!(usages, vec!["-p, --path <PATH>", "--no-cache", "--include <GLOB>"]);
+        assert_eq!(cli.commands[0].arguments[2].help, "Only scan these globs");
+        assert_eq!(cli.commands[1].about, "Manage the index");
+    }
+
+    #[test]
+    fn test_argparse_and_commander_definitions() {
+        let python = "import argparse\nparser = argparse.ArgumentParser()\nparser.add_argument('-q', '--quiet', action='store_true', help='Less output')\nsub = parser.add_subparsers()\nserve = sub.add_parser('serve', help='Start the server')\nserve.add_argument('--port', type=int, help='Port to bind')\n";
+        let cli = argparse(python);
+        assert_eq!(cli.global_arguments[0].usage, "-q, --quiet");
+        assert_eq!(cli.commands[0].about, "Start the server");
+        assert_eq!(cli.commands[0].arguments[0].usage, "--port <PORT>");
+
+        let javascript = "const { program } = require('commander');\nprogram.option('-d, --debug', 'Debug output');\nprogram\n  .command('clone <source>')\n  .description('Clone a repository')\n  .option('--depth <n>', 'History depth');\n";
+        let cli = commander(javascript);
+        assert_eq!(cli.global_arguments[0].usage, "-d, --debug");
+        assert_eq!(cli.commands[0].name, "clone");
+        assert_eq!(cli.commands[0].about, "Clone a repository");
+        let usages: Vec<&str> = cli.commands[0].arguments.iter().map(|argument| argument.usage.as_str()).collect();
+        assert_eq!(usages, vec!["<source>", "--depth <n>"]);
+    }
+}
@@ -1,13 +1,389 @@
+use crate::budget::SummaryBudgets;
+use crate::cache::{parse_duration_secs, RefreshPolicy};
 use crate::error::{DocTreeError, Result};
+use crate::limits::{FileSizeLimit, OversizeStrategy, ScanLimits};
+use crate::debt::DebtOutput;
+use crate::http::HttpHeaders;
+use crate::keychain::StoredKey;
+use crate::language::LanguagePrompts;
+use crate::notify::NotifyMode;
+use crate::outline::SummaryInput;
+use crate::references::ReferenceCheck;
+use crate::scanner::WalkOptions;
+use crate::submodules::{SubmoduleMode, SubmodulePolicy};
+use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Project-level config file names, checked in order in the project root.
+pub const PROJECT_CONFIG_FILES: [&str; 2] = ["doctreeai.toml", ".doctreeai.toml"];
+
+/// Keys `doctreeai config set` writes to the project file, with the setting
+/// each one configures. `submodules.<path>` sets one submodule's mode.
+pub const PROJECT_FILE_KEYS: [(&str, &str); 6] = [
+    ("scan.include", "DOCTREEAI_INCLUDE"),
+    ("scan.exclude", "DOCTREEAI_EXCLUDE"),
+    ("scan.extra_extensions", "DOCTREEAI_EXTRA_EXTENSIONS"),
+    ("scan.extra_filenames", "DOCTREEAI_EXTRA_FILENAMES"),
+    ("readme.required_sections", "DOCTREEAI_REQUIRED_SECTIONS"),
+    ("submodules", "DOCTREEAI_SUBMODULES"),
+];
+
+/// Settings read from a project's doctreeai.toml.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProjectFile {
+    #[serde(default)]
+    scan: ScanSection,
+    /// Submodule path (or `*`) to mode: opaque, skip, or scan.
+    #[serde(default)]
+    submodules: BTreeMap<String, String>,
+    #[serde(default)]
+    readme: ReadmeFileSection,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ReadmeFileSection {
+    #[serde(default)]
+    required_sections: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScanSection {
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    exclude: Vec<String>,
+    #[serde(default)]
+    extra_extensions: Vec<String>,
+    #[serde(default)]
+    extra_filenames: Vec<String>,
+}
+
+/// Where a resolved setting came from, in increasing precedence.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SettingSource {
+    #[default]
+    Default,
+    File,
+    Env,
+    Cli,
+    Keychain,
+}
+
+impl fmt::Display for SettingSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SettingSource::Default => "default",
+            SettingSource::File => "file",
+            SettingSource::Env => "env",
+            SettingSource::Cli => "cli",
+            SettingSource::Keychain => "keychain",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// One line of the configuration report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedSetting {
+    /// The setting's environment variable name.
+    pub name: &'static str,
+    pub value: String,
+    pub source: SettingSource,
+}
+
+/// Environment variables read for each setting; aliases follow the primary name.
+const ENV_SETTINGS: &[&[&str]] = &[
+    &["OPENAI_API_BASE", "OPENAI_BASE_URL"],
+    &["OPENAI_API_KEY"],
+    &["OPENAI_MODEL_NAME", "OPENAI_MODEL"],
+    &["DOCTREEAI_SUMMARY_MODEL"],
+    &["DOCTREEAI_SYNTHESIS_MODEL"],
+    &["DOCTREEAI_EMBEDDING_MODEL"],
+    &["DOCTREEAI_CACHE_DIR"],
+    &["DOCTREEAI_LOG_LEVEL", "LOG_LEVEL"],
+    &["DOCTREEAI_MAX_RETRIES"],
+    &["DOCTREEAI_RETRY_BASE_DELAY_MS"],
+    &["DOCTREEAI_RETRY_MAX_DELAY_MS"],
+    &["DOCTREEAI_REQUEST_TIMEOUT_SECS"],
+    &["DOCTREEAI_RUN_TIMEOUT_SECS"],
+    &["DOCTREEAI_CACHE_TTL"],
+    &["DOCTREEAI_RESPONSE_CACHE_TTL"],
+    &["DOCTREEAI_NORMALIZED_HASHING"],
+    &["DOCTREEAI_VALIDATION_BATCH_SIZE"],
+    &["DOCTREEAI_DIRECTORY_TOKEN_BUDGET"],
+    &["DOCTREEAI_IGNORE_PATHS"],
+    &["DOCTREEAI_DOC_LANGUAGE"],
+    &["DOCTREEAI_README_TRANSLATIONS"],
+    &["DOCTREEAI_NOTIFY"],
+    &["DOCTREEAI_INCLUDE"],
+    &["DOCTREEAI_EXCLUDE"],
+    &["DOCTREEAI_EXTRA_EXTENSIONS"],
+    &["DOCTREEAI_EXTRA_FILENAMES"],
+    &["DOCTREEAI_MAX_FILE_BYTES"],
+    &["DOCTREEAI_OVERSIZE_STRATEGY"],
+    &["DOCTREEAI_MAX_DEPTH"],
+    &["DOCTREEAI_MAX_FILES"],
+    &["DOCTREEAI_MAX_FILES_PER_DIR"],
+    &["DOCTREEAI_SKIP_GENERATED"],
+    &["DOCTREEAI_TICKET_URL"],
+    &["DOCTREEAI_TICKET_PROJECTS"],
+    &["DOCTREEAI_TICKET_COMMITS"],
+    &["DOCTREEAI_SANDBOX_IMAGE"],
+    &["DOCTREEAI_SANDBOX_NETWORK"],
+    &["DOCTREEAI_FOLLOW_SYMLINKS"],
+    &["DOCTREEAI_CROSS_FILESYSTEMS"],
+    &["DOCTREEAI_TRACKED_ONLY"],
+    &["DOCTREEAI_SUBMODULES"],
+    &["DOCTREEAI_SMALL_MODEL"],
+    &["DOCTREEAI_EXTRACTIVE_BELOW_BYTES"],
+    &["DOCTREEAI_README_BACKUPS"],
+    &["DOCTREEAI_TECH_DEBT"],
+    &["DOCTREEAI_AUDIT_LOG"],
+    &["DOCTREEAI_AUDIT_REDACT"],
+    &["DOCTREEAI_OFFLINE"],
+    &["DOCTREEAI_STRUCTURED_SUMMARIES"],
+    &["DOCTREEAI_FILE_SUMMARY_WORDS"],
+    &["DOCTREEAI_DIRECTORY_SUMMARY_WORDS"],
+    &["DOCTREEAI_ROOT_SUMMARY_WORDS"],
+    &["DOCTREEAI_SUMMARY_STYLE"],
+    &["DOCTREEAI_REFERENCE_CHECK"],
+    &["DOCTREEAI_REQUIRED_SECTIONS"],
+    &["DOCTREEAI_USAGE_EXAMPLES"],
+    &["DOCTREEAI_SUMMARY_INPUT"],
+    &["DOCTREEAI_SIBLING_CONTEXT"],
+    &["DOCTREEAI_LANGUAGE_PROMPTS"],
+    &["DOCTREEAI_PRIVATE_PATHS"],
+    &["DOCTREEAI_REDACT_SECRETS"],
+    &["DOCTREEAI_MAX_TOKENS_PER_RUN"],
+    &["DOCTREEAI_REQUESTS_PER_MINUTE"],
+    &["DOCTREEAI_TOKENS_PER_MINUTE"],
+    &["DOCTREEAI_CONCURRENCY"],
+    &["DOCTREEAI_STREAM"],
+    &["DOCTREEAI_STREAM_CHUNK_TIMEOUT_SECS"],
+    &["DOCTREEAI_HTTP_HEADERS"],
+    &["DOCTREEAI_HTTP_PROXY"],
+    &["DOCTREEAI_CA_CERT"],
+    &["DOCTREEAI_INSECURE_TLS"],
+    &["DOCTREEAI_KEYRING"],
+];
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub openai_api_base: String,
     pub openai_api_key: String,
     pub openai_model_name: String,
+    /// Model for per-file and directory summaries; defaults to `openai_model_name`.
+    pub summary_model_name: Option<String>,
+    /// Model for README synthesis and validation suggestions; defaults to `openai_model_name`.
+    pub synthesis_model_name: Option<String>,
+    /// Embedding model for the `index` store; the index is unavailable when unset.
+    pub embedding_model_name: Option<String>,
     pub cache_dir_name: String,
     pub log_level: String,
+    pub max_retries: u32,
+    pub retry_base_delay_ms: u64,
+    pub retry_max_delay_ms: u64,
+    /// Client-side limits on LLM requests and tokens per minute; unlimited
+    /// when unset.
+    pub requests_per_minute: Option<u32>,
+    pub tokens_per_minute: Option<u64>,
+    /// Most LLM requests in flight at once. Above 1, files and the
+    /// directories whose children are done are summarized concurrently.
+    pub concurrency: usize,
+    pub request_timeout_secs: u64,
+    /// Stream completions, failing a request whose stream sends nothing for
+    /// `stream_chunk_timeout_secs` instead of waiting out the request timeout.
+    pub stream_completions: bool,
+    pub stream_chunk_timeout_secs: u64,
+    /// Sent with every LLM request, for gateways like LiteLLM or Helicone.
+    pub http_headers: HttpHeaders,
+    /// Proxy for LLM requests; `HTTPS_PROXY`/`HTTP_PROXY` apply when unset.
+    pub http_proxy: Option<String>,
+    /// Extra PEM certificates to trust, e.g. a corporate proxy's CA.
+    pub ca_cert: Option<PathBuf>,
+    /// Skip certificate verification for the LLM server.
+    pub insecure_tls: bool,
+    /// Read OPENAI_API_KEY from the OS keychain when it is not set.
+    pub keyring: bool,
+    pub run_timeout_secs: Option<u64>,
+    pub cache_ttl: RefreshPolicy,
+    /// How long identical LLM requests are answered from the response
+    /// cache; None disables it.
+    pub response_cache_ttl_secs: Option<u64>,
+    /// Hash source files without their comments and formatting, so that
+    /// reformatting a file does not invalidate its summary.
+    pub normalized_hashing: bool,
+    pub validation_batch_size: usize,
+    pub directory_token_budget: u64,
+    /// Glob patterns of files that are never summarized ("do not document").
+    pub ignore_paths: Vec<String>,
+    /// Glob patterns of files whose contents are never sent to the LLM; they
+    /// are summarized from their path, size, and identifiers.
+    pub private_paths: Vec<String>,
+    /// Replace API keys, tokens, private keys, and connection string
+    /// passwords in file content before it is put in a prompt.
+    pub redact_secrets: bool,
+    /// Prompt and completion tokens one run may spend before it stops,
+    /// keeping what it completed; unlimited when unset.
+    pub max_tokens_per_run: Option<u64>,
+    /// Language for summaries and README output, e.g. "Japanese"; English when unset.
+    pub doc_language: Option<String>,
+    /// Language codes of localized READMEs (README.<code>.md) kept in sync with README.md.
+    pub readme_translations: Vec<String>,
+    /// How newly found README drift is surfaced (off, bell, or desktop).
+    pub notify: NotifyMode,
+    /// Globs of files to scan; everything is scanned when empty.
+    pub include_patterns: Vec<String>,
+    /// Globs of files and directories left out of the scan.
+    pub exclude_patterns: Vec<String>,
+    /// Extensions summarized in addition to the built-in list, e.g. "gotmpl".
+    pub extra_extensions: Vec<String>,
+    /// Filename globs summarized in addition to the built-in ones, e.g. "BUCK".
+    pub extra_filenames: Vec<String>,
+    /// Largest file sent to the LLM as-is, and what happens to bigger ones.
+    pub file_size_limit: FileSizeLimit,
+    /// Depth and file count at which a scan aborts.
+    pub scan_limits: ScanLimits,
+    /// Whether the scan follows symlinks and crosses filesystem boundaries.
+    pub walk_options: WalkOptions,
+    /// Whether git submodules are scanned, skipped, or summarized as opaque
+    /// external dependencies.
+    pub submodule_policy: SubmodulePolicy,
+    /// Skip binary, minified, lockfile, and generated files detected by content.
+    pub skip_generated: bool,
+    /// Tune prompts and validation for 3-8B models and describe trivial
+    /// files (re-exports, tiny configs) without the LLM.
+    pub small_model: bool,
+    /// Files smaller than this get an extractive summary (doc comments and
+    /// top-level symbols) instead of an LLM call; 0 disables it.
+    pub extractive_below_bytes: u64,
+    /// Send the LLM an outline of each file (signatures and doc comments)
+    /// instead of its content, for languages with a tree-sitter grammar.
+    pub summary_input: SummaryInput,
+    /// Summarize each directory's entry points (`mod.rs`, `index.ts`, ...)
+    /// a second time with the other files' summaries as context.
+    pub sibling_context: bool,
+    /// Per-extension overrides of the language-specific instructions in
+    /// file summary prompts.
+    pub language_prompts: LanguagePrompts,
+    /// Previous README.md versions kept in the cache for `doctreeai rollback`.
+    pub readme_backups: usize,
+    /// Whether TODO/FIXME/HACK comments are published as a README section or TODO.md.
+    pub tech_debt: DebtOutput,
+    /// Record every LLM prompt and raw response under `<cache>/audit/`.
+    pub audit_log: bool,
+    /// Regular expressions whose matches are replaced in audit records.
+    pub audit_redact: Vec<String>,
+    /// Build structural summaries (symbols, imports, size) without the LLM,
+    /// for when no server is reachable. They are replaced on the next online run.
+    pub offline: bool,
+    /// Ask the LLM for file summaries as JSON (purpose, public API, key
+    /// types, dependencies, caveats) and keep the fields in the cache.
+    pub structured_summaries: bool,
+    /// Word budgets for file, directory, and root summaries, and whether
+    /// they are written as prose or bullet points.
+    pub summary_budgets: SummaryBudgets,
+    /// Whether README suggestions mentioning files or flags the project
+    /// does not have are flagged, dropped, or left alone.
+    pub reference_check: ReferenceCheck,
+    /// README sections the project requires, e.g. "Installation" or
+    /// "License"; `doctreeai check` reports the ones that are missing.
+    pub required_sections: Vec<String>,
+    /// Distill usage snippets from `examples/`, doc tests, and integration
+    /// tests for the README's Usage section.
+    pub usage_examples: bool,
+    /// Ticket URL with an `{id}` placeholder; enables ticket linking when set.
+    pub ticket_url_template: Option<String>,
+    /// Project keys (e.g. "ENG") to link; any `KEY-123` ID is linked when empty.
+    pub ticket_projects: Vec<String>,
+    /// How many recent commits per change are searched for ticket IDs.
+    pub ticket_commit_limit: usize,
+    /// Image used by `run --sandbox docker`; it must provide the doctreeai binary.
+    pub sandbox_image: String,
+    /// Docker network the sandbox joins; it should only reach the LLM endpoint.
+    pub sandbox_network: Option<String>,
+    /// Source of every setting not left at its default, keyed by env var name.
+    pub sources: BTreeMap<&'static str, SettingSource>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            openai_api_base: String::new(),
+            openai_api_key: "local".to_string(),
+            openai_model_name: String::new(),
+            summary_model_name: None,
+            synthesis_model_name: None,
+            embedding_model_name: None,
+            cache_dir_name: ".doctreeai_cache".to_string(),
+            log_level: "info".to_string(),
+            max_retries: 3,
+            retry_base_delay_ms: 2000,
+            retry_max_delay_ms: 60_000,
+            requests_per_minute: None,
+            tokens_per_minute: None,
+            concurrency: 1,
+            request_timeout_secs: 300,
+            stream_completions: false,
+            stream_chunk_timeout_secs: 60,
+            http_headers: HttpHeaders::default(),
+            http_proxy: None,
+            ca_cert: None,
+            insecure_tls: false,
+            keyring: true,
+            run_timeout_secs: None,
+            cache_ttl: RefreshPolicy::default(),
+            response_cache_ttl_secs: Some(7 * 24 * 60 * 60),
+            normalized_hashing: false,
+            validation_batch_size: 10,
+            directory_token_budget: 6000,
+            ignore_paths: Vec::new(),
+            private_paths: Vec::new(),
+            redact_secrets: true,
+            max_tokens_per_run: None,
+            doc_language: None,
+            readme_translations: Vec::new(),
+            notify: NotifyMode::Off,
+            include_patterns: Vec::new(),
+            exclude_patterns: Vec::new(),
+            extra_extensions: Vec::new(),
+            extra_filenames: Vec::new(),
+            file_size_limit: FileSizeLimit::default(),
+            scan_limits: ScanLimits::default(),
+            walk_options: WalkOptions::default(),
+            submodule_policy: SubmodulePolicy::default(),
+            skip_generated: true,
+            small_model: false,
+            extractive_below_bytes: 0,
+            summary_input: SummaryInput::default(),
+            sibling_context: false,
+            language_prompts: LanguagePrompts::default(),
+            readme_backups: 5,
+            tech_debt: DebtOutput::Off,
+            audit_log: false,
+            audit_redact: Vec::new(),
+            offline: false,
+            structured_summaries: false,
+            summary_budgets: SummaryBudgets::default(),
+            reference_check: ReferenceCheck::default(),
+            required_sections: Vec::new(),
+            usage_examples: true,
+            ticket_url_template: None,
+            ticket_projects: Vec::new(),
+            ticket_commit_limit: 20,
+            sandbox_image: "doctreeai:latest".to_string(),
+            sandbox_network: None,
+            sources: BTreeMap::new(),
+        }
+    }
 }
 
 impl Config {
@@ -15,6 +391,8 @@ impl Config {
         // Load .env file if it exists (ignore errors if not found)
         let _ = dotenvy::dotenv();
 
+        let defaults = Config::default();
+
         // API base URL is required - no default
         let openai_api_base = env::var("OPENAI_API_BASE")
             .or_else(|_| env::var("OPENAI_BASE_URL"))
@@ -24,8 +402,17 @@ impl Config {
                 )
             })?;
 
-        // API key can default to "local" for local model instances
-        let openai_api_key = env::var("OPENAI_API_KEY").unwrap_or_else(|_| "local".to_string());
+        // API key can default to "local" for local model instances, or be
+        // stored with `doctreeai config set-key`
+        let keyring = parse_env("DOCTREEAI_KEYRING")?.unwrap_or(defaults.keyring);
+        let keychain_api_key = match env::var("OPENAI_API_KEY") {
+            Err(_) if keyring => StoredKey::OpenAi.load(),
+            _ => None,
+        };
+        let openai_api_key = env::var("OPENAI_API_KEY")
+            .ok()
+            .or_else(|| keychain_api_key.clone())
+            .unwrap_or(defaults.openai_api_key);
 
         // Model name is required - no default
         let openai_model_name = env::var("OPENAI_MODEL_NAME")
@@ -36,53 +423,774 @@ impl Config {
                 )
             })?;
 
-        let cache_dir_name =
-            env::var("DOCTREEAI_CACHE_DIR").unwrap_or_else(|_| ".doctreeai_cache".to_string());
+        let summary_model_name = env::var("DOCTREEAI_SUMMARY_MODEL").ok().filter(|m| !m.is_empty());
+        let synthesis_model_name = env::var("DOCTREEAI_SYNTHESIS_MODEL").ok().filter(|m| !m.is_empty());
+        let embedding_model_name = env::var("DOCTREEAI_EMBEDDING_MODEL").ok().filter(|m| !m.is_empty());
+
+        let cache_dir_name = env::var("DOCTREEAI_CACHE_DIR").unwrap_or(defaults.cache_dir_name);
 
         let log_level = env::var("DOCTREEAI_LOG_LEVEL")
             .or_else(|_| env::var("LOG_LEVEL"))
-            .unwrap_or_else(|_| "info".to_string());
+            .unwrap_or(defaults.log_level);
+
+        let max_retries = parse_env("DOCTREEAI_MAX_RETRIES")?.unwrap_or(defaults.max_retries);
+        let retry_base_delay_ms =
+            parse_env("DOCTREEAI_RETRY_BASE_DELAY_MS")?.unwrap_or(defaults.retry_base_delay_ms);
+        let retry_max_delay_ms =
+            parse_env("DOCTREEAI_RETRY_MAX_DELAY_MS")?.unwrap_or(defaults.retry_max_delay_ms);
+        // 0 means no limit
+        let requests_per_minute = parse_env("DOCTREEAI_REQUESTS_PER_MINUTE")?
+            .or(defaults.requests_per_minute)
+            .filter(|&requests: &u32| requests > 0);
+        let tokens_per_minute = parse_env("DOCTREEAI_TOKENS_PER_MINUTE")?
+            .or(defaults.tokens_per_minute)
+            .filter(|&tokens: &u64| tokens > 0);
+        let concurrency = parse_env("DOCTREEAI_CONCURRENCY")?.unwrap_or(defaults.concurrency);
+        let request_timeout_secs =
+            parse_env("DOCTREEAI_REQUEST_TIMEOUT_SECS")?.unwrap_or(defaults.request_timeout_secs);
+        let stream_completions = parse_env("DOCTREEAI_STREAM")?.unwrap_or(defaults.stream_completions);
+        let stream_chunk_timeout_secs =
+            parse_env("DOCTREEAI_STREAM_CHUNK_TIMEOUT_SECS")?.unwrap_or(defaults.stream_chunk_timeout_secs);
+        let http_headers = match env::var("DOCTREEAI_HTTP_HEADERS") {
+            Ok(spec) => HttpHeaders::parse(&spec)?,
+            Err(_) => defaults.http_headers,
+        };
+        let http_proxy = env::var("DOCTREEAI_HTTP_PROXY").ok().filter(|p| !p.is_empty()).or(defaults.http_proxy);
+        let ca_cert = env::var("DOCTREEAI_CA_CERT").ok().filter(|p| !p.is_empty()).map(PathBuf::from).or(defaults.ca_cert);
+        let insecure_tls = parse_env("DOCTREEAI_INSECURE_TLS")?.unwrap_or(defaults.insecure_tls);
+        let run_timeout_secs = parse_env("DOCTREEAI_RUN_TIMEOUT_SECS")?.or(defaults.run_timeout_secs);
+
+        let cache_ttl = match env::var("DOCTREEAI_CACHE_TTL") {
+            Ok(spec) => RefreshPolicy::parse(&spec)?,
+            Err(_) => defaults.cache_ttl,
+        };
+
+        // 0 disables the response cache
+        let response_cache_ttl_secs = match env::var("DOCTREEAI_RESPONSE_CACHE_TTL") {
+            Ok(value) => Some(parse_duration_secs(&value)?).filter(|&ttl| ttl > 0),
+            Err(_) => defaults.response_cache_ttl_secs,
+        };
+        let normalized_hashing = parse_env("DOCTREEAI_NORMALIZED_HASHING")?.unwrap_or(defaults.normalized_hashing);
+
+        let validation_batch_size = parse_env("DOCTREEAI_VALIDATION_BATCH_SIZE")?
+            .unwrap_or(defaults.validation_batch_size);
+
+        let directory_token_budget = parse_env("DOCTREEAI_DIRECTORY_TOKEN_BUDGET")?
+            .unwrap_or(defaults.directory_token_budget);
+
+        let ignore_paths = env::var("DOCTREEAI_IGNORE_PATHS")
+            .map(|value| parse_list(&value))
+            .unwrap_or(defaults.ignore_paths);
+        let private_paths = env::var("DOCTREEAI_PRIVATE_PATHS")
+            .map(|value| parse_list(&value))
+            .unwrap_or(defaults.private_paths);
+        let redact_secrets = parse_env("DOCTREEAI_REDACT_SECRETS")?.unwrap_or(defaults.redact_secrets);
+        // 0 means no budget
+        let max_tokens_per_run = parse_env("DOCTREEAI_MAX_TOKENS_PER_RUN")?
+            .or(defaults.max_tokens_per_run)
+            .filter(|&tokens: &u64| tokens > 0);
+
+        let doc_language = env::var("DOCTREEAI_DOC_LANGUAGE")
+            .ok()
+            .filter(|language| !language.trim().is_empty())
+            .or(defaults.doc_language);
+
+        let readme_translations = env::var("DOCTREEAI_README_TRANSLATIONS")
+            .map(|value| parse_list(&value))
+            .unwrap_or(defaults.readme_translations);
+
+        let notify = parse_env("DOCTREEAI_NOTIFY")?.unwrap_or(defaults.notify);
+
+        let include_patterns = env::var("DOCTREEAI_INCLUDE")
+            .map(|value| parse_list(&value))
+            .unwrap_or(defaults.include_patterns);
+
+        let exclude_patterns = env::var("DOCTREEAI_EXCLUDE")
+            .map(|value| parse_list(&value))
+            .unwrap_or(defaults.exclude_patterns);
+
+        let extra_extensions = env::var("DOCTREEAI_EXTRA_EXTENSIONS")
+            .map(|value| parse_list(&value))
+            .unwrap_or(defaults.extra_extensions);
+
+        let extra_filenames = env::var("DOCTREEAI_EXTRA_FILENAMES")
+            .map(|value| parse_list(&value))
+            .unwrap_or(defaults.extra_filenames);
+
+        // 0 disables the limit
+        let file_size_limit = FileSizeLimit {
+            max_bytes: match parse_env::<u64>("DOCTREEAI_MAX_FILE_BYTES")? {
+                Some(0) => None,
+                Some(max_bytes) => Some(max_bytes),
+                None => defaults.file_size_limit.max_bytes,
+            },
+            strategy: parse_env::<OversizeStrategy>("DOCTREEAI_OVERSIZE_STRATEGY")?
+                .unwrap_or(defaults.file_size_limit.strategy),
+        };
+
+        // 0 disables a limit
+        let scan_limits = ScanLimits {
+            max_depth: match parse_env::<usize>("DOCTREEAI_MAX_DEPTH")? {
+                Some(0) => None,
+                Some(max_depth) => Some(max_depth),
+                None => defaults.scan_limits.max_depth,
+            },
+            max_files: match parse_env::<usize>("DOCTREEAI_MAX_FILES")? {
+                Some(0) => None,
+                Some(max_files) => Some(max_files),
+                None => defaults.scan_limits.max_files,
+            },
+            max_files_per_dir: match parse_env::<usize>("DOCTREEAI_MAX_FILES_PER_DIR")? {
+                Some(0) => None,
+                Some(max_files) => Some(max_files),
+                None => defaults.scan_limits.max_files_per_dir,
+            },
+        };
+
+        let walk_options = WalkOptions {
+            follow_symlinks: parse_env("DOCTREEAI_FOLLOW_SYMLINKS")?
+                .unwrap_or(defaults.walk_options.follow_symlinks),
+            cross_filesystems: parse_env("DOCTREEAI_CROSS_FILESYSTEMS")?
+                .unwrap_or(defaults.walk_options.cross_filesystems),
+            tracked_only: parse_env("DOCTREEAI_TRACKED_ONLY")?
+                .unwrap_or(defaults.walk_options.tracked_only),
+        };
+
+        let submodule_policy = match env::var("DOCTREEAI_SUBMODULES") {
+            Ok(spec) => SubmodulePolicy::parse(&spec)?,
+            Err(_) => defaults.submodule_policy,
+        };
+
+        let skip_generated =
+            parse_env("DOCTREEAI_SKIP_GENERATED")?.unwrap_or(defaults.skip_generated);
+        let small_model = parse_env("DOCTREEAI_SMALL_MODEL")?.unwrap_or(defaults.small_model);
+        let extractive_below_bytes = parse_env("DOCTREEAI_EXTRACTIVE_BELOW_BYTES")?
+            .unwrap_or(defaults.extractive_below_bytes);
+        let summary_input = parse_env("DOCTREEAI_SUMMARY_INPUT")?.unwrap_or(defaults.summary_input);
+        let sibling_context = parse_env("DOCTREEAI_SIBLING_CONTEXT")?.unwrap_or(defaults.sibling_context);
+        let language_prompts = match env::var("DOCTREEAI_LANGUAGE_PROMPTS") {
+            Ok(spec) => LanguagePrompts::parse(&spec)?,
+            Err(_) => defaults.language_prompts,
+        };
+        let readme_backups = parse_env("DOCTREEAI_README_BACKUPS")?.unwrap_or(defaults.readme_backups);
+        let tech_debt = parse_env("DOCTREEAI_TECH_DEBT")?.unwrap_or(defaults.tech_debt);
+        let audit_log = parse_env("DOCTREEAI_AUDIT_LOG")?.unwrap_or(defaults.audit_log);
+        // Whitespace-separated, since patterns may contain commas
+        let audit_redact = env::var("DOCTREEAI_AUDIT_REDACT")
+            .map(|value| value.split_whitespace().map(str::to_string).collect())
+            .unwrap_or(defaults.audit_redact);
+        let offline = parse_env("DOCTREEAI_OFFLINE")?.unwrap_or(defaults.offline);
+        let structured_summaries =
+            parse_env("DOCTREEAI_STRUCTURED_SUMMARIES")?.unwrap_or(defaults.structured_summaries);
+
+        // 0 removes a level's budget
+        let word_budget = |name: &str, default: Option<usize>| -> Result<Option<usize>> {
+            Ok(match parse_env::<usize>(name)? {
+                Some(0) => None,
+                Some(max_words) => Some(max_words),
+                None => default,
+            })
+        };
+        let summary_budgets = SummaryBudgets {
+            file_max_words: word_budget("DOCTREEAI_FILE_SUMMARY_WORDS", defaults.summary_budgets.file_max_words)?,
+            directory_max_words: word_budget(
+                "DOCTREEAI_DIRECTORY_SUMMARY_WORDS",
+                defaults.summary_budgets.directory_max_words,
+            )?,
+            root_max_words: word_budget("DOCTREEAI_ROOT_SUMMARY_WORDS", defaults.summary_budgets.root_max_words)?,
+            style: parse_env("DOCTREEAI_SUMMARY_STYLE")?.unwrap_or(defaults.summary_budgets.style),
+        };
+        let reference_check = parse_env("DOCTREEAI_REFERENCE_CHECK")?.unwrap_or(defaults.reference_check);
+        let required_sections = env::var("DOCTREEAI_REQUIRED_SECTIONS")
+            .map(|value| parse_list(&value))
+            .unwrap_or(defaults.required_sections);
+        let usage_examples = parse_env("DOCTREEAI_USAGE_EXAMPLES")?.unwrap_or(defaults.usage_examples);
+
+        let ticket_url_template = env::var("DOCTREEAI_TICKET_URL")
+            .ok()
+            .filter(|template| !template.trim().is_empty())
+            .or(defaults.ticket_url_template);
+        let ticket_projects = env::var("DOCTREEAI_TICKET_PROJECTS")
+            .map(|value| parse_list(&value))
+            .unwrap_or(defaults.ticket_projects);
+        let ticket_commit_limit =
+            parse_env("DOCTREEAI_TICKET_COMMITS")?.unwrap_or(defaults.ticket_commit_limit);
+
+        let sandbox_image = env::var("DOCTREEAI_SANDBOX_IMAGE").unwrap_or(defaults.sandbox_image);
+        let sandbox_network = env::var("DOCTREEAI_SANDBOX_NETWORK").ok().or(defaults.sandbox_network);
+
+        let mut sources: BTreeMap<_, _> = ENV_SETTINGS
+            .iter()
+            .filter(|names| names.iter().any(|name| env::var(name).is_ok()))
+            .map(|names| (names[0], SettingSource::Env))
+            .collect();
+        if keychain_api_key.is_some() {
+            sources.insert("OPENAI_API_KEY", SettingSource::Keychain);
+        }
 
         Ok(Config {
             openai_api_base,
             openai_api_key,
             openai_model_name,
+            summary_model_name,
+            synthesis_model_name,
+            embedding_model_name,
             cache_dir_name,
             log_level,
+            max_retries,
+            retry_base_delay_ms,
+            retry_max_delay_ms,
+            requests_per_minute,
+            tokens_per_minute,
+            concurrency,
+            request_timeout_secs,
+            stream_completions,
+            stream_chunk_timeout_secs,
+            http_headers,
+            http_proxy,
+            ca_cert,
+            insecure_tls,
+            keyring,
+            run_timeout_secs,
+            cache_ttl,
+            response_cache_ttl_secs,
+            normalized_hashing,
+            validation_batch_size,
+            directory_token_budget,
+            ignore_paths,
+            private_paths,
+            redact_secrets,
+            max_tokens_per_run,
+            doc_language,
+            readme_translations,
+            notify,
+            include_patterns,
+            exclude_patterns,
+            extra_extensions,
+            extra_filenames,
+            file_size_limit,
+            scan_limits,
+            walk_options,
+            submodule_policy,
+            skip_generated,
+            small_model,
+            extractive_below_bytes,
+            summary_input,
+            sibling_context,
+            language_prompts,
+            readme_backups,
+            tech_debt,
+            audit_log,
+            audit_redact,
+            offline,
+            structured_summaries,
+            summary_budgets,
+            reference_check,
+            required_sections,
+            usage_examples,
+            ticket_url_template,
+            ticket_projects,
+            ticket_commit_limit,
+            sandbox_image,
+            sandbox_network,
+            sources,
         })
     }
 
-    pub fn validate(&self) -> Result<()> {
-        if self.openai_api_base.is_empty() {
-            return Err(DocTreeError::config("OPENAI_API_BASE cannot be empty"));
+    /// Records where a setting came from, for the configuration report.
+    pub fn set_source(&mut self, name: &'static str, source: SettingSource) {
+        self.sources.insert(name, source);
+    }
+
+    pub fn source_of(&self, name: &str) -> SettingSource {
+        self.sources.get(name).copied().unwrap_or_default()
+    }
+
+    /// Finds the project config file in the project root, if any.
+    pub fn project_file_path(project_path: &Path) -> Option<PathBuf> {
+        PROJECT_CONFIG_FILES
+            .iter()
+            .map(|name| project_path.join(name))
+            .find(|path| path.is_file())
+    }
+
+    /// Applies settings from the project's doctreeai.toml. Environment
+    /// variables take precedence over the file.
+    pub fn apply_project_file(&mut self, project_path: &Path) -> Result<()> {
+        let Some(path) = Self::project_file_path(project_path) else {
+            return Ok(());
+        };
+
+        let invalid = |e: &dyn fmt::Display| DocTreeError::config(format!("Invalid {}: {e}", path.display()));
+        let content = fs::read_to_string(&path)?;
+        let table: toml::Table = content.parse().map_err(|e| invalid(&e))?;
+        let mut value = toml::Value::Table(table);
+        expand_toml_value(&mut value).map_err(|e| invalid(&e))?;
+        let project_file: ProjectFile = value.try_into().map_err(|e| invalid(&e))?;
+
+        tracing::debug!("Loaded project config from {}", path.display());
+
+        let scan = project_file.scan;
+        for (name, file_value, setting) in [
+            ("DOCTREEAI_INCLUDE", scan.include, &mut self.include_patterns),
+            ("DOCTREEAI_EXCLUDE", scan.exclude, &mut self.exclude_patterns),
+            ("DOCTREEAI_EXTRA_EXTENSIONS", scan.extra_extensions, &mut self.extra_extensions),
+            ("DOCTREEAI_EXTRA_FILENAMES", scan.extra_filenames, &mut self.extra_filenames),
+            ("DOCTREEAI_REQUIRED_SECTIONS", project_file.readme.required_sections, &mut self.required_sections),
+        ] {
+            if env::var(name).is_err() && !file_value.is_empty() {
+                *setting = file_value;
+                self.sources.insert(name, SettingSource::File);
+            }
         }
 
-        if self.openai_model_name.is_empty() {
-            return Err(DocTreeError::config("OPENAI_MODEL_NAME cannot be empty"));
+        if env::var("DOCTREEAI_SUBMODULES").is_err() && !project_file.submodules.is_empty() {
+            for (path, mode) in &project_file.submodules {
+                self.submodule_policy.set(path, mode.parse()?);
+            }
+            self.sources.insert("DOCTREEAI_SUBMODULES", SettingSource::File);
         }
 
+        Ok(())
+    }
+
+    /// Writes one setting to the project's config file, creating doctreeai.toml
+    /// if there is none and keeping the file's comments. `key` is a project
+    /// file key or its environment variable; lists are comma-separated and an
+    /// empty value removes the key. Returns the file written.
+    pub fn set_project_value(project_path: &Path, key: &str, value: &str) -> Result<PathBuf> {
+        let key = PROJECT_FILE_KEYS
+            .iter()
+            .find(|(file_key, name)| *file_key != "submodules" && name.eq_ignore_ascii_case(key))
+            .map_or(key, |(file_key, _)| file_key);
+        let unknown = || {
+            let keys: Vec<&str> = PROJECT_FILE_KEYS.iter().map(|(file_key, _)| *file_key).collect();
+            DocTreeError::config(format!(
+                "'{key}' cannot be set in the project file (expected one of {}.<path>)",
+                keys.join(", ")
+            ))
+        };
+        let (section, name) = key.split_once('.').ok_or_else(unknown)?;
+        let value = value.trim();
+        let item = if section == "submodules" {
+            if !value.is_empty() {
+                value.parse::<SubmoduleMode>()?;
+            }
+            toml_edit::value(value)
+        } else if PROJECT_FILE_KEYS.iter().any(|(file_key, _)| *file_key == key) {
+            let items: toml_edit::Array = value.split(',').map(str::trim).filter(|v| !v.is_empty()).collect();
+            toml_edit::value(items)
+        } else {
+            return Err(unknown());
+        };
+
+        let path = Self::project_file_path(project_path).unwrap_or_else(|| project_path.join(PROJECT_CONFIG_FILES[0]));
+        let invalid = |e: &dyn fmt::Display| DocTreeError::config(format!("Invalid {}: {e}", path.display()));
+        let content = if path.is_file() { fs::read_to_string(&path)? } else { String::new() };
+        let mut document: toml_edit::DocumentMut = content.parse().map_err(|e| invalid(&e))?;
+
+        let table = document
+            .entry(section)
+            .or_insert(toml_edit::table())
+            .as_table_mut()
+            .ok_or_else(|| invalid(&format!("[{section}] is not a table")))?;
+        if value.is_empty() {
+            table.remove(name);
+        } else {
+            table[name] = item;
+        }
+
+        // Never leave a file behind that doctreeai cannot load
+        let updated = document.to_string();
+        toml::from_str::<ProjectFile>(&updated).map_err(|e| invalid(&e))?;
+        fs::write(&path, updated)?;
+        Ok(path)
+    }
+
+    /// The resolved setting for an environment variable (or alias) or a
+    /// project file key, e.g. `DOCTREEAI_EXCLUDE` or `scan.exclude`.
+    pub fn setting(&self, name: &str) -> Option<ResolvedSetting> {
+        let section = name.split('.').next().unwrap_or(name);
+        let primary = PROJECT_FILE_KEYS
+            .iter()
+            .find(|(file_key, _)| *file_key == name || (*file_key == "submodules" && section == "submodules"))
+            .map(|(_, primary)| *primary)
+            .or_else(|| {
+                ENV_SETTINGS
+                    .iter()
+                    .find(|names| names.iter().any(|alias| alias.eq_ignore_ascii_case(name)))
+                    .map(|names| names[0])
+            })?;
+        self.report().into_iter().find(|setting| setting.name == primary)
+    }
+
+    /// Every setting with its resolved value and where it came from.
+    pub fn report(&self) -> Vec<ResolvedSetting> {
+        let list = |items: &[String]| items.join(",");
+        let optional = |value: Option<&str>| value.unwrap_or("").to_string();
+        let limit = |value: Option<usize>| value.map(|v| v.to_string()).unwrap_or_else(|| "0".to_string());
+        let api_key = if self.openai_api_key == Config::default().openai_api_key {
+            self.openai_api_key.clone()
+        } else {
+            "********".to_string()
+        };
+        // Proxy credentials are hidden like the API key
+        let http_proxy = match self.http_proxy.as_deref().map(|proxy| (proxy, proxy.rsplit_once('@'))) {
+            Some((_, Some((credentials, host)))) => {
+                let scheme = credentials.split_once("://").map(|(scheme, _)| scheme).unwrap_or("http");
+                format!("{scheme}://********@{host}")
+            }
+            Some((proxy, None)) => proxy.to_string(),
+            None => String::new(),
+        };
+
+        let values = [
+            self.openai_api_base.clone(),
+            api_key,
+            self.openai_model_name.clone(),
+            optional(self.summary_model_name.as_deref()),
+            optional(self.synthesis_model_name.as_deref()),
+            optional(self.embedding_model_name.as_deref()),
+            self.cache_dir_name.clone(),
+            self.log_level.clone(),
+            self.max_retries.to_string(),
+            self.retry_base_delay_ms.to_string(),
+            self.retry_max_delay_ms.to_string(),
+            self.request_timeout_secs.to_string(),
+            self.run_timeout_secs.map(|v| v.to_string()).unwrap_or_default(),
+            self.cache_ttl
+                .rules
+                .iter()
+                .map(|rule| format!("{}={}s", rule.prefix, rule.ttl_secs))
+                .collect::<Vec<_>>()
+                .join(","),
+            format!("{}s", self.response_cache_ttl_secs.unwrap_or(0)),
+            self.normalized_hashing.to_string(),
+            self.validation_batch_size.to_string(),
+            self.directory_token_budget.to_string(),
+            list(&self.ignore_paths),
+            optional(self.doc_language.as_deref()),
+            list(&self.readme_translations),
+            format!("{:?}", self.notify).to_lowercase(),
+            list(&self.include_patterns),
+            list(&self.exclude_patterns),
+            list(&self.extra_extensions),
+            list(&self.extra_filenames),
+            self.file_size_limit.max_bytes.unwrap_or(0).to_string(),
+            format!("{:?}", self.file_size_limit.strategy).to_lowercase(),
+            limit(self.scan_limits.max_depth),
+            limit(self.scan_limits.max_files),
+            limit(self.scan_limits.max_files_per_dir),
+            self.skip_generated.to_string(),
+            optional(self.ticket_url_template.as_deref()),
+            list(&self.ticket_projects),
+            self.ticket_commit_limit.to_string(),
+            self.sandbox_image.clone(),
+            optional(self.sandbox_network.as_deref()),
+            self.walk_options.follow_symlinks.to_string(),
+            self.walk_options.cross_filesystems.to_string(),
+            self.walk_options.tracked_only.to_string(),
+            self.submodule_policy.to_string(),
+            self.small_model.to_string(),
+            self.extractive_below_bytes.to_string(),
+            self.readme_backups.to_string(),
+            self.tech_debt.to_string(),
+            self.audit_log.to_string(),
+            self.audit_redact.join(" "),
+            self.offline.to_string(),
+            self.structured_summaries.to_string(),
+            limit(self.summary_budgets.file_max_words),
+            limit(self.summary_budgets.directory_max_words),
+            limit(self.summary_budgets.root_max_words),
+            self.summary_budgets.style.to_string(),
+            self.reference_check.to_string(),
+            list(&self.required_sections),
+            self.usage_examples.to_string(),
+            self.summary_input.to_string(),
+            self.sibling_context.to_string(),
+            self.language_prompts.to_string(),
+            list(&self.private_paths),
+            self.redact_secrets.to_string(),
+            self.max_tokens_per_run.unwrap_or(0).to_string(),
+            self.requests_per_minute.unwrap_or(0).to_string(),
+            self.tokens_per_minute.unwrap_or(0).to_string(),
+            self.concurrency.to_string(),
+            self.stream_completions.to_string(),
+            self.stream_chunk_timeout_secs.to_string(),
+            self.http_headers.to_string(),
+            http_proxy,
+            self.ca_cert.as_ref().map(|path| path.display().to_string()).unwrap_or_default(),
+            self.insecure_tls.to_string(),
+            self.keyring.to_string(),
+        ];
+
+        ENV_SETTINGS
+            .iter()
+            .zip(values)
+            .map(|(names, value)| ResolvedSetting {
+                name: names[0],
+                value,
+                source: self.source_of(names[0]),
+            })
+            .collect()
+    }
+
+    pub fn validate(&self) -> Result<()> {
         if self.cache_dir_name.is_empty() {
             return Err(DocTreeError::config("Cache directory name cannot be empty"));
         }
 
-        if !self.openai_api_base.starts_with("http://")
-            && !self.openai_api_base.starts_with("https://")
-        {
+        // Offline runs never reach the endpoint, so it need not be configured
+        if !self.offline {
+            if self.openai_api_base.is_empty() {
+                return Err(DocTreeError::config("OPENAI_API_BASE cannot be empty"));
+            }
+
+            if self.openai_model_name.is_empty() {
+                return Err(DocTreeError::config("OPENAI_MODEL_NAME cannot be empty"));
+            }
+
+            if !self.openai_api_base.starts_with("http://")
+                && !self.openai_api_base.starts_with("https://")
+            {
+                return Err(DocTreeError::config(
+                    "OPENAI_API_BASE must be a valid HTTP/HTTPS URL",
+                ));
+            }
+        }
+
+        if self.request_timeout_secs == 0 {
+            return Err(DocTreeError::config(
+                "DOCTREEAI_REQUEST_TIMEOUT_SECS must be greater than zero",
+            ));
+        }
+
+        if self.stream_chunk_timeout_secs == 0 {
             return Err(DocTreeError::config(
-                "OPENAI_API_BASE must be a valid HTTP/HTTPS URL",
+                "DOCTREEAI_STREAM_CHUNK_TIMEOUT_SECS must be greater than zero",
             ));
         }
 
-        log::info!("Configuration loaded successfully:");
-        log::info!("  API Base: {}", self.openai_api_base);
-        log::info!("  Model: {}", self.openai_model_name);
-        log::info!("  Cache Dir: {}", self.cache_dir_name);
-        log::info!("  Log Level: {}", self.log_level);
+        if self.retry_max_delay_ms < self.retry_base_delay_ms {
+            return Err(DocTreeError::config(
+                "DOCTREEAI_RETRY_MAX_DELAY_MS cannot be lower than DOCTREEAI_RETRY_BASE_DELAY_MS",
+            ));
+        }
+
+        if self.validation_batch_size == 0 {
+            return Err(DocTreeError::config(
+                "DOCTREEAI_VALIDATION_BATCH_SIZE must be greater than zero",
+            ));
+        }
+
+        if self.concurrency == 0 {
+            return Err(DocTreeError::config(
+                "DOCTREEAI_CONCURRENCY must be greater than zero",
+            ));
+        }
+
+        tracing::info!("Configuration loaded successfully:");
+        tracing::info!("  API Base: {}", self.openai_api_base);
+        tracing::info!("  Model: {}", self.openai_model_name);
+        tracing::info!("  Summary Model: {}", self.summary_model());
+        tracing::info!("  Synthesis Model: {}", self.synthesis_model());
+        tracing::info!("  Cache Dir: {}", self.cache_dir_name);
+        tracing::info!("  Log Level: {}", self.log_level);
+        tracing::info!(
+            "  Retries: {} (backoff {}ms-{}ms, request timeout {}s)",
+            self.max_retries,
+            self.retry_base_delay_ms,
+            self.retry_max_delay_ms,
+            self.request_timeout_secs
+        );
+        for setting in self.report() {
+            tracing::debug!("  {} = {} ({})", setting.name, setting.value, setting.source);
+        }
 
         Ok(())
     }
 
+    pub fn summary_model(&self) -> &str {
+        self.summary_model_name
+            .as_deref()
+            .unwrap_or(&self.openai_model_name)
+    }
+
+    pub fn synthesis_model(&self) -> &str {
+        self.synthesis_model_name
+            .as_deref()
+            .unwrap_or(&self.openai_model_name)
+    }
+
     pub fn get_cache_dir_path(&self, base_path: &std::path::Path) -> std::path::PathBuf {
         base_path.join(&self.cache_dir_name)
     }
 }
+
+/// Expands `${VAR}` references in every string of a doctreeai.toml value.
+fn expand_toml_value(value: &mut toml::Value) -> Result<()> {
+    match value {
+        toml::Value::String(text) => *text = expand_env_vars(text)?,
+        toml::Value::Array(items) => {
+            for item in items {
+                expand_toml_value(item)?;
+            }
+        }
+        toml::Value::Table(table) => {
+            for (_, item) in table.iter_mut() {
+                expand_toml_value(item)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Replaces `${VAR}` with the variable's value; unset variables are an error.
+fn expand_env_vars(text: &str) -> Result<String> {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| DocTreeError::config(format!("Unterminated '${{' in '{text}'")))?;
+        let name = &rest[start + 2..start + end];
+        let value = env::var(name)
+            .map_err(|_| DocTreeError::config(format!("Environment variable '{name}' is not set")))?;
+        expanded.push_str(&value);
+        rest = &rest[start + end + 1..];
+    }
+
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn parse_env<T: FromStr>(variable: &str) -> Result<Option<T>> {
+    match env::var(variable) {
+        Ok(value) => value.trim().parse().map(Some).map_err(|_| {
+            DocTreeError::config(format!("{variable} has an invalid value: {value}"))
+        }),
+        Err(_) => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_roles_fall_back_to_default_model() {
+        let mut config = Config {
+            openai_model_name: "base-model".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(config.summary_model(), "base-model");
+        assert_eq!(config.synthesis_model(), "base-model");
+
+        config.summary_model_name = Some("fast-model".to_string());
+        config.synthesis_model_name = Some("strong-model".to_string());
+        assert_eq!(config.summary_model(), "fast-model");
+        assert_eq!(config.synthesis_model(), "strong-model");
+    }
+
+    #[test]
+    fn test_project_file_scan_patterns() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let mut config = Config::default();
+
+        config.apply_project_file(temp_dir.path())?;
+        assert!(config.include_patterns.is_empty());
+
+        fs::write(
+            temp_dir.path().join(".doctreeai.toml"),
+            "[scan]\ninclude = [\"src/**\"]\nexclude = [\"examples/**\"]\n",
+        )?;
+        config.apply_project_file(temp_dir.path())?;
+        assert_eq!(config.include_patterns, vec!["src/**".to_string()]);
+        assert_eq!(config.exclude_patterns, vec!["examples/**".to_string()]);
+
+        fs::write(
+            temp_dir.path().join(".doctreeai.toml"),
+            "[submodules]\n\"*\" = \"skip\"\n\"vendor/ui\" = \"opaque\"\n",
+        )?;
+        config.apply_project_file(temp_dir.path())?;
+        assert_eq!(config.submodule_policy.to_string(), "skip,vendor/ui=opaque");
+
+        fs::write(
+            temp_dir.path().join(".doctreeai.toml"),
+            "[readme]\nrequired_sections = [\"Installation\", \"License\"]\n",
+        )?;
+        config.apply_project_file(temp_dir.path())?;
+        assert_eq!(config.required_sections, vec!["Installation".to_string(), "License".to_string()]);
+
+        fs::write(temp_dir.path().join("doctreeai.toml"), "[scan]\nunknown = 1\n")?;
+        assert!(config.apply_project_file(temp_dir.path()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_project_file_env_expansion_and_report() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let mut config = Config::default();
+
+        env::set_var("DOCTREEAI_TEST_GENERATED_DIR", "gen");
+        fs::write(
+            temp_dir.path().join("doctreeai.toml"),
+            "[scan]\nexclude = [\"${DOCTREEAI_TEST_GENERATED_DIR}/**\"]\n",
+        )?;
+        config.apply_project_file(temp_dir.path())?;
+        assert_eq!(config.exclude_patterns, vec!["gen/**".to_string()]);
+
+        config.set_source("DOCTREEAI_MAX_FILES", SettingSource::Cli);
+        let report = config.report();
+        let setting = |name: &str| report.iter().find(|s| s.name == name).unwrap().clone();
+        assert_eq!(setting("DOCTREEAI_EXCLUDE").source, SettingSource::File);
+        assert_eq!(setting("DOCTREEAI_EXCLUDE").value, "gen/**");
+        assert_eq!(setting("DOCTREEAI_MAX_FILES").source, SettingSource::Cli);
+        assert_eq!(setting("DOCTREEAI_CACHE_DIR").source, SettingSource::Default);
+        assert_eq!(report.len(), ENV_SETTINGS.len());
+
+        fs::write(
+            temp_dir.path().join("doctreeai.toml"),
+            "[scan]\nexclude = [\"${DOCTREEAI_TEST_UNSET_VARIABLE}\"]\n",
+        )?;
+        assert!(config.apply_project_file(temp_dir.path()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_set_writes_the_project_file() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        fs::write(temp_dir.path().join(".doctreeai.toml"), "# Keep generated code out\n[scan]\nexclude = [\"gen/**\"]\n")?;
+
+        let path = Config::set_project_value(temp_dir.path(), "scan.include", "src/**, docs/**")?;
+        assert!(path.ends_with(".doctreeai.toml"));
+        Config::set_project_value(temp_dir.path(), "DOCTREEAI_EXCLUDE", "")?;
+        Config::set_project_value(temp_dir.path(), "submodules.vendor/ui", "opaque")?;
+        assert!(fs::read_to_string(&path)?.starts_with("# Keep generated code out"));
+        assert!(Config::set_project_value(temp_dir.path(), "submodules.vendor/ui", "fork").is_err());
+        assert!(Config::set_project_value(temp_dir.path(), "DOCTREEAI_MAX_RETRIES", "5").is_err());
+
+        let mut config = Config::default();
+        config.apply_project_file(temp_dir.path())?;
+        let include = config.setting("scan.include").unwrap();
+        assert_eq!((include.value.as_str(), include.source), ("src/**,docs/**", SettingSource::File));
+        assert_eq!(config.setting("doctreeai_exclude").unwrap().source, SettingSource::Default);
+        assert_eq!(config.setting("submodules.vendor/ui").unwrap().value, "opaque,vendor/ui=opaque");
+        assert!(config.setting("NOT_A_SETTING").is_none());
+        Ok(())
+    }
+}
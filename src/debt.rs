@@ -0,0 +1,269 @@
+use crate::error::{DocTreeError, Result};
+use crate::scanner::FileNode;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Heading of the generated tech debt section.
+pub const TECH_DEBT_HEADING: &str = "Known Limitations / Tech Debt";
+
+/// File written instead of a README section with `DOCTREEAI_TECH_DEBT=file`.
+pub const TECH_DEBT_FILE: &str = "TODO.md";
+
+/// Comment openers a marker must follow to count, so string literals and
+/// identifiers such as `TODO_LIST` are not picked up.
+const COMMENT_OPENERS: &[&str] = &["//", "#", "/*", "*", "--", "<!--", ";"];
+
+/// Where TODO/FIXME/HACK comments collected from the code are published.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DebtOutput {
+    #[default]
+    Off,
+    /// A generated section in README.md.
+    Section,
+    /// A separate TODO.md next to README.md.
+    File,
+}
+
+impl FromStr for DebtOutput {
+    type Err = DocTreeError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "" | "off" => Ok(DebtOutput::Off),
+            "section" | "readme" => Ok(DebtOutput::Section),
+            "file" => Ok(DebtOutput::File),
+            other => Err(DocTreeError::config(format!(
+                "Invalid tech debt output '{other}' (expected off, section, or file)"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for DebtOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DebtOutput::Off => "off",
+            DebtOutput::Section => "section",
+            DebtOutput::File => "file",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DebtKind {
+    Fixme,
+    Hack,
+    Todo,
+}
+
+impl DebtKind {
+    const ALL: [DebtKind; 3] = [DebtKind::Fixme, DebtKind::Hack, DebtKind::Todo];
+
+    fn keyword(self) -> &'static str {
+        match self {
+            DebtKind::Fixme => "FIXME",
+            DebtKind::Hack => "HACK",
+            DebtKind::Todo => "TODO",
+        }
+    }
+}
+
+/// One TODO/FIXME/HACK comment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebtMarker {
+    /// File path relative to the project root.
+    pub path: PathBuf,
+    /// 1-based line number.
+    pub line: usize,
+    pub kind: DebtKind,
+    pub text: String,
+}
+
+/// Markers found in a file's content.
+pub fn find_markers(relative_path: &Path, content: &str) -> Vec<DebtMarker> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let (kind, text) = parse_marker(line)?;
+            Some(DebtMarker {
+                path: relative_path.to_path_buf(),
+                line: index + 1,
+                kind,
+                text,
+            })
+        })
+        .collect()
+}
+
+fn parse_marker(line: &str) -> Option<(DebtKind, String)> {
+    let comment_start = COMMENT_OPENERS
+        .iter()
+        .filter_map(|opener| line.find(opener))
+        .min()?;
+    // An opener after an odd number of quotes is inside a string literal
+    if line[..comment_start].matches('"').count() % 2 == 1 {
+        return None;
+    }
+
+    let comment = &line[comment_start..];
+    DebtKind::ALL.iter().find_map(|kind| {
+        let keyword = kind.keyword();
+        let start = comment.find(keyword)?;
+        let before = comment[..start].chars().next_back();
+        let rest = &comment[start + keyword.len()..];
+        let after = rest.chars().next();
+        let is_word = before.is_none_or(|c| !c.is_alphanumeric() && c != '_')
+            && after.is_none_or(|c| !c.is_alphanumeric() && c != '_');
+        if !is_word {
+            return None;
+        }
+
+        // Drop an owner like `TODO(alice):` and the closing of block comments
+        let rest = match rest.strip_prefix('(') {
+            Some(owned) => owned.split_once(')').map(|(_, rest)| rest).unwrap_or(owned),
+            None => rest,
+        };
+        let text = rest
+            .trim_start_matches([':', '-', ' '])
+            .trim_end_matches("-->")
+            .trim_end_matches("*/")
+            .trim();
+        Some((*kind, text.to_string()))
+    })
+}
+
+/// TODO/FIXME/HACK comments across the project, grouped by directory.
+#[derive(Debug, Clone, Default)]
+pub struct DebtReport {
+    pub markers: Vec<DebtMarker>,
+}
+
+impl DebtReport {
+    /// Reads every file in the scanned tree; unreadable and binary files
+    /// are skipped.
+    pub fn collect(root: &FileNode, base_path: &Path) -> Self {
+        let mut markers = Vec::new();
+        Self::collect_node(root, base_path, &mut markers);
+        markers.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+        Self { markers }
+    }
+
+    fn collect_node(node: &FileNode, base_path: &Path, markers: &mut Vec<DebtMarker>) {
+        if node.submodule.is_some() {
+            return;
+        }
+        if node.is_directory {
+            for child in &node.children {
+                Self::collect_node(child, base_path, markers);
+            }
+            return;
+        }
+
+        if let Ok(content) = fs::read_to_string(&node.path) {
+            let relative_path = node.path.strip_prefix(base_path).unwrap_or(&node.path);
+            markers.extend(find_markers(relative_path, &content));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.markers.is_empty()
+    }
+
+    /// Markers keyed by the directory of their file ("" for the root).
+    pub fn by_directory(&self) -> BTreeMap<PathBuf, Vec<&DebtMarker>> {
+        let mut directories: BTreeMap<PathBuf, Vec<&DebtMarker>> = BTreeMap::new();
+        for marker in &self.markers {
+            let directory = marker.path.parent().map(Path::to_path_buf).unwrap_or_default();
+            directories.entry(directory).or_default().push(marker);
+        }
+        directories
+    }
+
+    /// Renders the markers as a `## Known Limitations / Tech Debt` section,
+    /// one subsection per directory.
+    pub fn render_section(&self) -> String {
+        let mut section = format!("## {TECH_DEBT_HEADING}\n");
+        if self.is_empty() {
+            section.push_str("\nNo TODO, FIXME, or HACK comments in the code.\n");
+            return section;
+        }
+
+        for (directory, markers) in self.by_directory() {
+            let label = if directory.as_os_str().is_empty() {
+                "./".to_string()
+            } else {
+                format!("{}/", directory.display())
+            };
+            section.push_str(&format!("\n### `{label}`\n\n"));
+            for marker in markers {
+                let text = if marker.text.is_empty() { "(no description)" } else { &marker.text };
+                section.push_str(&format!(
+                    "- **{}** `{}:{}`: {text}\n",
+                    marker.kind.keyword(),
+                    marker.path.display(),
+                    marker.line
+                ));
+            }
+        }
+        section
+    }
+
+    /// The full TODO.md document.
+    pub fn render_file(&self) -> String {
+        format!(
+            "<!-- Generated by doctreeai from TODO/FIXME/HACK comments; edits are overwritten -->\n\n{}",
+            self.render_section().replacen("## ", "# ", 1)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_markers() {
+        let content = "fn main() {\n    // TODO(alice): handle errors\n    let s = \"// TODO not a comment\";\n    /* FIXME - leaks memory */\n    let TODO_LIST = 1; // HACK\n}\n# TODOS are not markers\n";
+        let markers = find_markers(Path::new("src/main.rs"), content);
+        let found: Vec<_> = markers.iter().map(|m| (m.line, m.kind, m.text.as_str())).collect();
+        assert_eq!(
+            found,
+            vec![
+                (2, DebtKind::Todo, "handle errors"),
+                (4, DebtKind::Fixme, "leaks memory"),
+                (5, DebtKind::Hack, ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_section_groups_by_directory() {
+        let report = DebtReport {
+            markers: vec![
+                DebtMarker {
+                    path: PathBuf::from("build.rs"),
+                    line: 3,
+                    kind: DebtKind::Todo,
+                    text: "cache the output".to_string(),
+                },
+                DebtMarker {
+                    path: PathBuf::from("src/llm.rs"),
+                    line: 42,
+                    kind: DebtKind::Fixme,
+                    text: "retry on 429".to_string(),
+                },
+            ],
+        };
+
+        assert_eq!(
+            report.render_section(),
+            "## Known Limitations / Tech Debt\n\n### `./`\n\n- **TODO** `build.rs:3`: cache the output\n\n### `src/`\n\n- **FIXME** `src/llm.rs:42`: retry on 429\n"
+        );
+        assert!(report.render_file().contains("\n# Known Limitations / Tech Debt\n"));
+    }
+}
@@ -0,0 +1,127 @@
+/// Lines of unchanged context shown around each change.
+pub const DEFAULT_CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineChange {
+    Same,
+    Removed,
+    Added,
+}
+
+/// Renders a unified diff between two texts, or an empty string when they
+/// are identical line for line.
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str, context: usize) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let changes = line_changes(&old_lines, &new_lines);
+
+    if changes.iter().all(|(change, _, _)| *change == LineChange::Same) {
+        return String::new();
+    }
+
+    let mut output = format!("--- {old_label}\n+++ {new_label}\n");
+    for hunk in hunks(&changes, context) {
+        let hunk_changes = &changes[hunk.clone()];
+        let (old_start, new_start) = (hunk_changes[0].1, hunk_changes[0].2);
+        let old_count = hunk_changes.iter().filter(|(change, _, _)| *change != LineChange::Added).count();
+        let new_count = hunk_changes.iter().filter(|(change, _, _)| *change != LineChange::Removed).count();
+
+        output.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_start, old_count),
+            hunk_range(new_start, new_count)
+        ));
+        for (change, old_index, new_index) in hunk_changes {
+            match change {
+                LineChange::Same => output.push_str(&format!(" {}\n", old_lines[*old_index])),
+                LineChange::Removed => output.push_str(&format!("-{}\n", old_lines[*old_index])),
+                LineChange::Added => output.push_str(&format!("+{}\n", new_lines[*new_index])),
+            }
+        }
+    }
+
+    output
+}
+
+/// `start,count` in the 1-based form unified diffs use; an empty range
+/// points at the line before it.
+fn hunk_range(start: usize, count: usize) -> String {
+    match count {
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{count}", start + 1),
+    }
+}
+
+/// The edit script from a longest common subsequence of lines, as
+/// (change, old index, new index) where the index of the side a line is
+/// missing from is where it would be inserted.
+fn line_changes(old: &[&str], new: &[&str]) -> Vec<(LineChange, usize, usize)> {
+    let (n, m) = (old.len(), new.len());
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old[i] == new[j] {
+            changes.push((LineChange::Same, i, j));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            changes.push((LineChange::Removed, i, j));
+            i += 1;
+        } else {
+            changes.push((LineChange::Added, i, j));
+            j += 1;
+        }
+    }
+    changes
+}
+
+/// Ranges of `changes` to print: every change plus `context` lines around
+/// it, merging hunks whose context overlaps.
+fn hunks(changes: &[(LineChange, usize, usize)], context: usize) -> Vec<std::ops::Range<usize>> {
+    let mut hunks: Vec<std::ops::Range<usize>> = Vec::new();
+    for (index, (change, _, _)) in changes.iter().enumerate() {
+        if *change == LineChange::Same {
+            continue;
+        }
+        let start = index.saturating_sub(context);
+        let end = (index + context + 1).min(changes.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.end => last.end = end,
+            _ => hunks.push(start..end),
+        }
+    }
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "# Project\n\nIntro.\n\n## Usage\nrun it\n";
+        let new = "# Project\n\nBetter intro.\n\n## Usage\nrun it\n";
+        assert_eq!(
+            unified_diff(old, new, "a/README.md", "b/README.md", 1),
+            "--- a/README.md\n+++ b/README.md\n@@ -2,3 +2,3 @@\n \n-Intro.\n+Better intro.\n \n"
+        );
+
+        assert_eq!(unified_diff(old, old, "a", "b", 3), "");
+        assert_eq!(
+            unified_diff("", "new line\n", "a", "b", 3),
+            "--- a\n+++ b\n@@ -0,0 +1 @@\n+new line\n"
+        );
+    }
+}
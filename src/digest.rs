@@ -0,0 +1,530 @@
+use crate::error::{DocTreeError, Result};
+use std::path::Path;
+
+/// Longest summary accepted from a small model; longer output is cut at the
+/// last sentence that fits.
+pub const MAX_DIGEST_CHARS: usize = 600;
+
+/// Files with at most this many meaningful lines count as tiny configs.
+const TINY_CONFIG_MAX_LINES: usize = 12;
+
+const CONFIG_EXTENSIONS: &[&str] = &["json", "toml", "yaml", "yml", "ini", "cfg", "conf"];
+
+const MARKDOWN_EXTENSIONS: &[&str] = &["md", "markdown", "rst", "txt"];
+
+/// Keywords that introduce a top-level definition, after any visibility prefix.
+const SYMBOL_KEYWORDS: &[&str] = &[
+    "fn ", "struct ", "enum ", "trait ", "type ", "class ", "def ", "function ", "interface ", "func ",
+];
+
+/// Top-level symbols listed by name before the rest are counted.
+const MAX_LISTED_SYMBOLS: usize = 8;
+
+/// Imports listed by name before the rest are counted.
+const MAX_LISTED_IMPORTS: usize = 6;
+
+/// Lines of a subdirectory's entry list kept in its parent's offline
+/// summary before the rest are counted.
+const MAX_NESTED_LINES: usize = 20;
+
+/// Openings small models use when they refuse or ramble instead of summarizing.
+const REJECTED_OPENINGS: &[&str] = &[
+    "i'm sorry", "i am sorry", "i cannot", "i can't", "as an ai", "sure, here", "sure! here",
+    "here is a summary", "here's a summary",
+];
+
+/// Describes trivial files without an LLM call: modules that only re-export
+/// other modules, and tiny config files. Returns None for anything else.
+pub fn extractive_summary(relative_path: &Path, content: &str) -> Option<String> {
+    let lines: Vec<&str> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !is_comment(line))
+        .collect();
+    if lines.is_empty() {
+        return None;
+    }
+
+    let extension = relative_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    if CONFIG_EXTENSIONS.contains(&extension.as_str()) {
+        if lines.len() > TINY_CONFIG_MAX_LINES {
+            return None;
+        }
+        let keys = config_keys(&extension, content, &lines);
+        return Some(if keys.is_empty() {
+            format!("Small {extension} configuration file.")
+        } else {
+            format!("Small {extension} configuration file setting {}.", keys.join(", "))
+        });
+    }
+
+    let targets: Option<Vec<String>> = lines.iter().map(|line| reexport_target(line)).collect();
+    let targets = targets?;
+    Some(format!("Module entry point that only declares and re-exports: {}.", targets.join(", ")))
+}
+
+/// Fallback summary built without the LLM from what a file says about
+/// itself: the trivial cases above, the first paragraph of a markdown file,
+/// or a source file's leading doc comment and top-level symbols.
+pub fn heuristic_summary(relative_path: &Path, content: &str) -> String {
+    if let Some(summary) = extractive_summary(relative_path, content) {
+        return summary;
+    }
+
+    let extension = relative_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    if MARKDOWN_EXTENSIONS.contains(&extension.as_str()) {
+        if let Some(paragraph) = first_paragraph(content) {
+            return truncate_digest(&paragraph);
+        }
+    }
+
+    let mut parts = Vec::new();
+    if let Some(doc) = leading_doc_comment(content) {
+        parts.push(doc);
+    }
+
+    let symbols = top_level_symbols(content);
+    if !symbols.is_empty() {
+        let listed = symbols.iter().take(MAX_LISTED_SYMBOLS).cloned().collect::<Vec<_>>().join(", ");
+        let rest = symbols.len().saturating_sub(MAX_LISTED_SYMBOLS);
+        parts.push(if rest > 0 {
+            format!("Defines {listed} and {rest} more.")
+        } else {
+            format!("Defines {listed}.")
+        });
+    }
+
+    if parts.is_empty() {
+        let kind = if extension.is_empty() { "File".to_string() } else { format!("{extension} file") };
+        return format!("{kind} with {} lines.", content.lines().count());
+    }
+
+    truncate_digest(&parts.join(" "))
+}
+
+/// Offline summary of a file: the heuristic summary plus what it imports
+/// and its size, so the tree still says how files relate without an LLM.
+pub fn structural_summary(relative_path: &Path, content: &str) -> String {
+    let mut summary = heuristic_summary(relative_path, content);
+
+    let imports = imports(content);
+    if !imports.is_empty() {
+        let listed = imports.iter().take(MAX_LISTED_IMPORTS).cloned().collect::<Vec<_>>().join(", ");
+        let rest = imports.len().saturating_sub(MAX_LISTED_IMPORTS);
+        summary.push_str(&if rest > 0 {
+            format!(" Imports {listed} and {rest} more.")
+        } else {
+            format!(" Imports {listed}.")
+        });
+    }
+
+    summary.push_str(&format!(" {} lines, {} bytes.", content.lines().count(), content.len()));
+    summary
+}
+
+/// Summary of a private file from what may leave the machine: its path,
+/// the names it defines, and its size. Doc comments and imports are left
+/// out, since they are part of the contents.
+pub fn structure_only_summary(relative_path: &Path, content: &str) -> String {
+    let extension = relative_path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let kind = if extension.is_empty() { "File".to_string() } else { format!("{extension} file") };
+    let mut summary = format!("{kind} with private contents (structure only).");
+
+    let symbols = top_level_symbols(content);
+    if !symbols.is_empty() {
+        let listed = symbols.iter().take(MAX_LISTED_SYMBOLS).cloned().collect::<Vec<_>>().join(", ");
+        let rest = symbols.len().saturating_sub(MAX_LISTED_SYMBOLS);
+        summary.push_str(&if rest > 0 {
+            format!(" Defines {listed} and {rest} more.")
+        } else {
+            format!(" Defines {listed}.")
+        });
+    }
+
+    summary.push_str(&format!(" {} lines, {} bytes.", content.lines().count(), content.len()));
+    summary
+}
+
+/// Offline summary of a directory from its children's summaries (formatted
+/// as `**name**: summary`). A subdirectory's own entry list is nested under
+/// it, indented, so the root summary still describes the whole tree.
+pub fn structural_directory_summary(children_summaries: &[String]) -> String {
+    let entries = match children_summaries.len() {
+        1 => "1 entry".to_string(),
+        count => format!("{count} entries"),
+    };
+    let mut summary = format!("Contains {entries}:");
+    for child in children_summaries {
+        let mut lines = child.lines();
+        summary.push_str(&format!("\n- {}", lines.next().unwrap_or_default()));
+        let nested: Vec<&str> = lines.collect();
+        for line in nested.iter().take(MAX_NESTED_LINES) {
+            summary.push_str(&format!("\n  {line}"));
+        }
+        if nested.len() > MAX_NESTED_LINES {
+            summary.push_str(&format!("\n  - ...and {} more lines", nested.len() - MAX_NESTED_LINES));
+        }
+    }
+    summary
+}
+
+/// Modules a source file imports, in file order.
+fn imports(content: &str) -> Vec<String> {
+    let mut imports: Vec<String> = Vec::new();
+
+    for line in content.lines().map(str::trim) {
+        let line = line.trim_end_matches(';');
+        let import = if let Some(path) = line.strip_prefix("use ").or_else(|| line.strip_prefix("pub use ")) {
+            // Rust: `use std::io::{self, Read}` imports from `std::io`
+            path.split("::{").next().map(str::to_string)
+        } else if let Some(rest) = line.strip_prefix("from ") {
+            rest.split_once(" import ").map(|(module, _)| module.to_string())
+        } else if let Some(module) = line.strip_prefix("import ") {
+            // JavaScript/TypeScript `import x from 'y'` names the module last
+            match module.split_once(" from ") {
+                Some((_, source)) => Some(source.to_string()),
+                None => Some(module.split([' ', ',']).next().unwrap_or_default().to_string()),
+            }
+        } else {
+            line.strip_prefix("#include ").map(str::to_string)
+        };
+
+        let Some(import) = import else {
+            continue;
+        };
+        let import = import.trim().trim_matches(|c| matches!(c, '\'' | '"' | '<' | '>')).to_string();
+        if !import.is_empty() && !imports.contains(&import) {
+            imports.push(import);
+        }
+    }
+
+    imports
+}
+
+/// The first prose paragraph of a markdown document, skipping headings,
+/// badges, and HTML comments.
+fn first_paragraph(content: &str) -> Option<String> {
+    let mut paragraph: Vec<&str> = Vec::new();
+    for line in content.lines().map(str::trim) {
+        let is_prose = !line.is_empty()
+            && !line.starts_with('#')
+            && !line.starts_with("<!--")
+            && !line.starts_with("[![")
+            && !line.starts_with("![")
+            && !line.starts_with("```")
+            && !line.starts_with("---");
+        if is_prose {
+            paragraph.push(line);
+        } else if !paragraph.is_empty() {
+            break;
+        }
+    }
+    (!paragraph.is_empty()).then(|| paragraph.join(" "))
+}
+
+/// The comment block or docstring a file opens with, without comment markers.
+/// License headers are skipped.
+fn leading_doc_comment(content: &str) -> Option<String> {
+    let mut doc: Vec<String> = Vec::new();
+    let mut in_docstring = false;
+
+    for line in content.lines().map(str::trim) {
+        if in_docstring {
+            let (text, closed) = match line.find("\"\"\"").or_else(|| line.find("'''")) {
+                Some(end) => (&line[..end], true),
+                None => (line, false),
+            };
+            doc.push(text.to_string());
+            if closed {
+                break;
+            }
+            continue;
+        }
+
+        if line.is_empty() || line.starts_with("#!") || line.starts_with("#[") || line.starts_with("# -*-") {
+            if doc.is_empty() {
+                continue;
+            }
+            break;
+        }
+
+        if let Some(rest) = line.strip_prefix("\"\"\"").or_else(|| line.strip_prefix("'''")) {
+            if !doc.is_empty() {
+                break;
+            }
+            match rest.find("\"\"\"").or_else(|| rest.find("'''")) {
+                Some(end) => {
+                    doc.push(rest[..end].to_string());
+                    break;
+                }
+                None => {
+                    doc.push(rest.to_string());
+                    in_docstring = true;
+                }
+            }
+            continue;
+        }
+
+        // C preprocessor directives are code, not comments
+        if ["#include", "#define", "#pragma", "#if"].iter().any(|directive| line.starts_with(directive)) {
+            break;
+        }
+
+        let text = ["//!", "///", "//", "/**", "/*", "*/", "*", "#", "--"]
+            .iter()
+            .find_map(|marker| line.strip_prefix(marker));
+        match text {
+            Some(text) => doc.push(text.trim_end_matches("*/").trim().to_string()),
+            None => break,
+        }
+    }
+
+    let text = doc
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let lower = text.to_lowercase();
+    if text.is_empty() || lower.contains("copyright") || lower.contains("spdx-license") {
+        return None;
+    }
+    Some(text)
+}
+
+/// Names defined at the top level of a source file, in file order.
+fn top_level_symbols(content: &str) -> Vec<String> {
+    let mut symbols: Vec<String> = Vec::new();
+
+    for line in content.lines() {
+        if line.starts_with(char::is_whitespace) {
+            continue;
+        }
+
+        let mut rest = line.trim();
+        for prefix in ["pub(crate) ", "pub ", "export default ", "export ", "async ", "unsafe "] {
+            rest = rest.strip_prefix(prefix).unwrap_or(rest);
+        }
+
+        let Some(after) = SYMBOL_KEYWORDS.iter().find_map(|keyword| rest.strip_prefix(keyword)) else {
+            continue;
+        };
+        let name: String = after.chars().take_while(|c| c.is_alphanumeric() || *c == '_').collect();
+        if !name.is_empty() && !symbols.contains(&name) {
+            symbols.push(name);
+        }
+    }
+
+    symbols
+}
+
+/// Cuts text longer than `MAX_DIGEST_CHARS` at the last sentence that fits.
+fn truncate_digest(text: &str) -> String {
+    if text.chars().count() <= MAX_DIGEST_CHARS {
+        return text.to_string();
+    }
+
+    let truncated: String = text.chars().take(MAX_DIGEST_CHARS).collect();
+    match truncated.rfind(". ") {
+        Some(end) => truncated[..=end].to_string(),
+        None => format!("{}...", truncated.trim_end()),
+    }
+}
+
+/// Comment lines, plus Rust attributes, which say nothing about what a file exports.
+fn is_comment(line: &str) -> bool {
+    ["//", "#", "/*", "*", "--", ";"].iter().any(|marker| line.starts_with(marker))
+}
+
+/// What a re-export or module declaration line points at, if that is all it is.
+fn reexport_target(line: &str) -> Option<String> {
+    let line = line.trim_end_matches(';').trim();
+
+    // Rust: `mod a`, `pub mod a`, `pub(crate) mod a`, `pub use a::b`
+    let rust = line
+        .strip_prefix("pub ")
+        .or_else(|| line.strip_prefix("pub(crate) "))
+        .unwrap_or(line);
+    if let Some(name) = rust.strip_prefix("mod ") {
+        let name = name.trim();
+        return name.chars().all(|c| c.is_alphanumeric() || c == '_').then(|| name.to_string());
+    }
+    if let Some(path) = rust.strip_prefix("use ").filter(|_| rust != line) {
+        return Some(path.trim().to_string());
+    }
+
+    // JavaScript/TypeScript: `export * from './a'`, `export { b } from './a'`
+    if line.starts_with("export ") {
+        let (_, source) = line.split_once(" from ")?;
+        return Some(source.trim().trim_matches(|c| c == '\'' || c == '"').to_string());
+    }
+
+    // Python package `__init__.py`: `from .a import b`, `__all__ = [...]`
+    if let Some(rest) = line.strip_prefix("from ") {
+        let (module, _) = rest.split_once(" import ")?;
+        return Some(module.trim().to_string());
+    }
+    if line.starts_with("__all__") {
+        return Some("__all__".to_string());
+    }
+
+    None
+}
+
+fn config_keys(extension: &str, content: &str, lines: &[&str]) -> Vec<String> {
+    if extension == "json" {
+        return match serde_json::from_str::<serde_json::Value>(content) {
+            Ok(serde_json::Value::Object(map)) => map.keys().cloned().collect(),
+            _ => Vec::new(),
+        };
+    }
+
+    lines
+        .iter()
+        .filter_map(|line| {
+            if let Some(section) = line.strip_prefix('[') {
+                return Some(format!("[{}", section));
+            }
+            let separator = if matches!(extension, "yaml" | "yml") { ':' } else { '=' };
+            let (key, _) = line.split_once(separator)?;
+            let key = key.trim();
+            (!key.is_empty() && !key.contains(' ')).then(|| key.to_string())
+        })
+        .collect()
+}
+
+/// Stricter checks for small-model output: reasoning blocks and code fences
+/// are stripped, refusals and empty answers are rejected, and overly long
+/// answers are cut at a sentence boundary.
+pub fn validate_digest(output: &str) -> Result<String> {
+    let mut text = output.to_string();
+    while let (Some(start), Some(end)) = (text.find("<think>"), text.find("</think>")) {
+        if end < start {
+            break;
+        }
+        text.replace_range(start..end + "</think>".len(), "");
+    }
+
+    let text = text
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let text = text.trim();
+
+    if text.is_empty() {
+        return Err(DocTreeError::summarizer("Model returned an empty summary"));
+    }
+
+    let lower = text.to_lowercase();
+    if let Some(opening) = REJECTED_OPENINGS.iter().find(|opening| lower.starts_with(*opening)) {
+        return Err(DocTreeError::summarizer(format!(
+            "Model output starts with '{opening}' instead of a summary"
+        )));
+    }
+
+    Ok(truncate_digest(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extractive_summary() {
+        let reexports = "//! Crate root\npub mod cache;\npub mod config;\n\npub use error::{DocTreeError, Result};\n";
+        assert_eq!(
+            extractive_summary(Path::new("src/lib.rs"), reexports).as_deref(),
+            Some("Module entry point that only declares and re-exports: cache, config, error::{DocTreeError, Result}.")
+        );
+
+        let init = "from .client import Client\n__all__ = [\"Client\"]\n";
+        assert!(extractive_summary(Path::new("pkg/__init__.py"), init).is_some());
+
+        let index = "export * from './button';\nexport { Card } from \"./card\";\n";
+        assert_eq!(
+            extractive_summary(Path::new("ui/index.ts"), index).as_deref(),
+            Some("Module entry point that only declares and re-exports: ./button, ./card.")
+        );
+
+        assert_eq!(
+            extractive_summary(Path::new("rustfmt.toml"), "edition = \"2021\"\nmax_width = 120\n").as_deref(),
+            Some("Small toml configuration file setting edition, max_width.")
+        );
+        assert_eq!(
+            extractive_summary(Path::new("tsconfig.json"), "{\"compilerOptions\": {}}").as_deref(),
+            Some("Small json configuration file setting compilerOptions.")
+        );
+
+        assert!(extractive_summary(Path::new("src/main.rs"), "fn main() {}\n").is_none());
+    }
+
+    #[test]
+    fn test_heuristic_summary() {
+        let rust = "//! Cache of file summaries keyed by content hash.\n\nuse std::fs;\n\npub struct CacheManager;\n\nimpl CacheManager {\n    pub fn new() {}\n}\n\npub(crate) fn current_timestamp() {}\n";
+        assert_eq!(
+            heuristic_summary(Path::new("src/cache.rs"), rust),
+            "Cache of file summaries keyed by content hash. Defines CacheManager, current_timestamp."
+        );
+
+        let python = "#!/usr/bin/env python\n\"\"\"Command-line entry point.\n\nParses arguments.\n\"\"\"\nimport sys\n\ndef main():\n    pass\n\nclass App:\n    pass\n";
+        assert_eq!(
+            heuristic_summary(Path::new("cli.py"), python),
+            "Command-line entry point. Parses arguments. Defines main, App."
+        );
+
+        let licensed = "// Copyright 2024 Example Corp.\n// SPDX-License-Identifier: MIT\n\nexport async function fetchUser() {}\n";
+        assert_eq!(heuristic_summary(Path::new("api.ts"), licensed), "Defines fetchUser.");
+
+        let markdown = "# Guide\n\n[![CI](badge.svg)](ci)\n\nHow to deploy the service\nto production.\n\nMore text.\n";
+        assert_eq!(
+            heuristic_summary(Path::new("docs/guide.md"), markdown),
+            "How to deploy the service to production."
+        );
+
+        assert_eq!(heuristic_summary(Path::new("run.sh"), "set -e\nmake\n"), "sh file with 2 lines.");
+    }
+
+    #[test]
+    fn test_structural_summary() {
+        let rust = "//! Sums numbers.\nuse std::io::{self, Read};\nuse crate::parser;\n\npub fn parse() {}\n";
+        assert_eq!(
+            structural_summary(Path::new("src/main.rs"), rust),
+            "Sums numbers. Defines parse. Imports std::io, crate::parser. 5 lines, 83 bytes."
+        );
+
+        let typescript = "import { Card } from './card';\nimport React from \"react\";\n";
+        assert!(structural_summary(Path::new("ui/app.ts"), typescript).contains("Imports ./card, react."));
+
+        let children = vec!["**main.rs**: Starts the CLI.".to_string(), "**src/** (directory, source): Contains 2 entries:\n- **a.rs**: A.".to_string()];
+        assert_eq!(
+            structural_directory_summary(&children),
+            "Contains 2 entries:\n- **main.rs**: Starts the CLI.\n- **src/** (directory, source): Contains 2 entries:\n  - **a.rs**: A."
+        );
+    }
+
+    #[test]
+    fn test_validate_digest() {
+        assert_eq!(
+            validate_digest("<think>hmm</think>\nParses CLI flags.").unwrap(),
+            "Parses CLI flags."
+        );
+        assert!(validate_digest("  ").is_err());
+        assert!(validate_digest("I'm sorry, I can't help with that.").is_err());
+
+        let long = "Handles requests. ".repeat(60);
+        let validated = validate_digest(&long).unwrap();
+        assert!(validated.len() <= MAX_DIGEST_CHARS);
+        assert!(validated.ends_with('.'));
+    }
+}
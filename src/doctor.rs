@@ -0,0 +1,201 @@
+use crate::cache::CacheManager;
+use crate::config::Config;
+use crate::error::Result;
+use crate::models::list_models;
+use std::fs;
+use std::path::Path;
+
+/// Longest a reachability check waits for the endpoint.
+const ENDPOINT_TIMEOUT_SECS: u64 = 10;
+
+/// The outcome of one `doctreeai doctor` check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub check: &'static str,
+    /// What was found: the state when the check passed, the problem when not.
+    pub message: String,
+    /// What to do about a failed check; None when it passed.
+    pub fix: Option<String>,
+}
+
+impl Finding {
+    fn pass(check: &'static str, message: impl Into<String>) -> Self {
+        Self { check, message: message.into(), fix: None }
+    }
+
+    fn fail(check: &'static str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { check, message: message.into(), fix: Some(fix.into()) }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.fix.is_none()
+    }
+}
+
+/// Runs every check for the project at `path`. The endpoint and model
+/// checks are skipped when the configuration itself is invalid.
+pub async fn diagnose(path: &Path) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    let config = Config::load().and_then(|mut config| {
+        config.apply_project_file(path)?;
+        config.validate()?;
+        Ok(config)
+    });
+    let config = match config {
+        Ok(config) => {
+            findings.push(Finding::pass("Configuration", "loaded and valid"));
+            Some(config)
+        }
+        Err(e) => {
+            findings.push(Finding::fail(
+                "Configuration",
+                e.to_string(),
+                "Set the variable in the environment, .env, or doctreeai.toml ('doctreeai init --interactive' sets up the server and model)",
+            ));
+            None
+        }
+    };
+
+    if let Some(config) = config.as_ref().filter(|config| !config.offline) {
+        findings.extend(check_endpoint(config).await);
+    }
+    let cache_dir_name = config.as_ref().map_or_else(|| Config::default().cache_dir_name, |config| config.cache_dir_name.clone());
+    findings.push(check_cache(path, &cache_dir_name));
+    findings.push(check_gitignore(path, &cache_dir_name));
+    findings.push(check_writable(path, &cache_dir_name));
+    findings
+}
+
+/// Reachability of the endpoint, then whether it serves the configured models.
+async fn check_endpoint(config: &Config) -> Vec<Finding> {
+    let probe = Config { request_timeout_secs: config.request_timeout_secs.min(ENDPOINT_TIMEOUT_SECS), ..config.clone() };
+    let available = match list_models(&probe).await {
+        Ok(available) => available,
+        Err(e) => {
+            return vec![Finding::fail(
+                "Endpoint",
+                e.to_string(),
+                format!(
+                    "Start the LLM server or correct OPENAI_API_BASE ({}); for hosted APIs check OPENAI_API_KEY and DOCTREEAI_HTTP_PROXY",
+                    config.openai_api_base
+                ),
+            )]
+        }
+    };
+    let mut findings = vec![Finding::pass("Endpoint", format!("{} answered", config.openai_api_base))];
+
+    let mut models = vec![config.summary_model(), config.synthesis_model()];
+    models.extend(config.embedding_model_name.as_deref());
+    models.dedup();
+    let missing: Vec<&str> = models.iter().copied().filter(|model| !available.iter().any(|a| a.id == *model)).collect();
+    findings.push(if available.is_empty() {
+        Finding::pass("Models", "the server lists no models, so they could not be verified")
+    } else if missing.is_empty() {
+        Finding::pass("Models", format!("{} available", models.join(", ")))
+    } else {
+        let suggestions: Vec<&str> = available.iter().take(5).map(|a| a.id.as_str()).collect();
+        Finding::fail(
+            "Models",
+            format!("not served by the endpoint: {}", missing.join(", ")),
+            format!(
+                "Pull the model on the server (e.g. 'ollama pull {}') or choose one of: {} ('doctreeai models' lists all)",
+                missing[0],
+                suggestions.join(", ")
+            ),
+        )
+    });
+    findings
+}
+
+fn check_cache(path: &Path, cache_dir_name: &str) -> Finding {
+    let cache_manager = match CacheManager::new(path, cache_dir_name) {
+        Ok(cache_manager) => cache_manager,
+        Err(e) => return Finding::fail("Cache", e.to_string(), "Run 'doctreeai clean' to start with a fresh cache"),
+    };
+    let problems = cache_manager.check_integrity();
+    let Some((first, problem)) = problems.first() else {
+        let (entries, _) = cache_manager.get_cache_stats();
+        return Finding::pass("Cache", format!("{entries} entries, all readable"));
+    };
+    Finding::fail(
+        "Cache",
+        format!("{} unusable entries, e.g. {}: {problem}", problems.len(), first.display()),
+        "Delete those files (they are regenerated on the next run) or run 'doctreeai clean'",
+    )
+}
+
+/// Whether the cache directory is kept out of git, as `doctreeai init` does.
+fn check_gitignore(path: &Path, cache_dir_name: &str) -> Finding {
+    if !path.join(".git").exists() {
+        return Finding::pass("Gitignore", "not a git repository");
+    }
+    let gitignore = fs::read_to_string(path.join(".gitignore")).unwrap_or_default();
+    if gitignore.contains(cache_dir_name) {
+        Finding::pass("Gitignore", format!("{cache_dir_name}/ is ignored"))
+    } else {
+        Finding::fail(
+            "Gitignore",
+            format!("{cache_dir_name}/ is not in .gitignore, so the cache can be committed"),
+            format!("Run 'doctreeai init' or add '{cache_dir_name}/' to .gitignore"),
+        )
+    }
+}
+
+fn check_writable(path: &Path, cache_dir_name: &str) -> Finding {
+    let cache_dir = path.join(cache_dir_name);
+    let readme = path.join("README.md");
+    let mut problems = Vec::new();
+
+    for dir in [path, cache_dir.as_path()].into_iter().filter(|dir| dir.is_dir()) {
+        if let Err(e) = probe_write(dir) {
+            problems.push(format!("{}: {e}", dir.display()));
+        }
+    }
+    if fs::metadata(&readme).is_ok_and(|metadata| metadata.permissions().readonly()) {
+        problems.push(format!("{} is read-only", readme.display()));
+    }
+
+    if problems.is_empty() {
+        Finding::pass("Permissions", "the project, cache, and README.md are writable")
+    } else {
+        Finding::fail(
+            "Permissions",
+            problems.join("; "),
+            "Make them writable for your user (e.g. 'chmod u+w'), or use --no-write for read-only checks",
+        )
+    }
+}
+
+fn probe_write(dir: &Path) -> Result<()> {
+    let probe = dir.join(".doctreeai_write_probe");
+    fs::write(&probe, b"")?;
+    fs::remove_file(&probe)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_and_gitignore_checks_report_fixes() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let path = temp_dir.path();
+        fs::create_dir_all(path.join(".git"))?;
+        fs::create_dir_all(path.join(".test_cache/src"))?;
+        fs::write(path.join(".test_cache/src/lib.rs.summary.json"), "{ truncated")?;
+
+        let cache = check_cache(path, ".test_cache");
+        assert!(!cache.passed());
+        assert!(cache.message.starts_with("1 unusable entries"));
+        assert!(!check_gitignore(path, ".test_cache").passed());
+
+        fs::write(path.join(".gitignore"), ".test_cache/\n")?;
+        fs::remove_file(path.join(".test_cache/src/lib.rs.summary.json"))?;
+        assert!(check_cache(path, ".test_cache").passed());
+        assert!(check_gitignore(path, ".test_cache").passed());
+        assert!(check_writable(path, ".test_cache").passed());
+        Ok(())
+    }
+}
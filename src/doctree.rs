@@ -0,0 +1,690 @@
+use crate::audit::AuditLog;
+use crate::batch::{BatchCollector, BATCH_POLL_INTERVAL};
+use crate::budget::TokenBudget;
+use crate::cache::{CacheManager, CacheSummary};
+use crate::cancel::CancellationToken;
+use crate::config::Config;
+use crate::debt::{DebtOutput, DebtReport, TECH_DEBT_HEADING};
+use crate::error::{DocTreeError, Result};
+use crate::format::current_timestamp;
+use crate::llm::LanguageModelClient;
+use crate::lock::RunLock;
+use crate::progress::ProgressListener;
+use crate::readme::{ReadmeManager, SERVICE_CATALOG_HEADING};
+use crate::readme_validator::{ReadmeUpdate, ReadmeValidator, ValidationResult};
+use crate::reporter::{Reporter, SilentReporter};
+use crate::response_cache::ResponseCache;
+use crate::run_state::RunState;
+use crate::staleness::StalenessReport;
+use crate::strategy::StrategyKind;
+use crate::summarizer::HierarchicalSummarizer;
+use crate::tree::SummaryTreeNode;
+use crate::workspace::Workspace;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// What `DocTree::run` should do.
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// Ignore cached summaries and regenerate everything.
+    pub force: bool,
+    /// Only summarize this subtree (relative to the project root) and
+    /// validate the README lines mapped to it.
+    pub scope: Option<PathBuf>,
+    pub strategy: StrategyKind,
+    /// Stop after summarizing, without validating the README.
+    pub skip_validation: bool,
+    /// Also render the README with every suggestion applied.
+    pub render_document: bool,
+    /// Keep new cache entries in memory instead of writing them.
+    pub read_only: bool,
+    /// Treat cache entries older than this many seconds as misses even when
+    /// their hash matches, on top of `DOCTREEAI_CACHE_TTL`.
+    pub refresh_older_than: Option<u64>,
+    /// Continue the run recorded in the cache instead of starting a new
+    /// one; see `DocTree::last_run`.
+    pub resume: bool,
+    /// Drop README suggestions the LLM is less sure about than this.
+    pub min_confidence: f32,
+    /// Ask the LLM to draft the required README sections that are missing.
+    pub draft_sections: bool,
+    /// Send the file summary requests as one batch job before the run, which
+    /// then answers them from the response cache.
+    pub batch: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RunStats {
+    pub cache_entries: usize,
+    pub cache_size: u64,
+}
+
+/// Everything a run produced. Nothing outside the cache is written; apply
+/// `readme_update` or write `tech_debt` to change the project.
+#[derive(Debug, Clone)]
+pub struct RunReport {
+    pub project_summary: String,
+    pub tree: SummaryTreeNode,
+    pub summaries: Vec<CacheSummary>,
+    /// Empty when validation was skipped.
+    pub validation_results: Vec<ValidationResult>,
+    /// Why each affected section is outdated, when more than one line is.
+    pub digest: Option<String>,
+    /// Regenerated managed README sections, if any changed.
+    pub readme_update: Option<ReadmeUpdate>,
+    /// The README with every suggestion applied, with `render_document`.
+    pub document: Option<String>,
+    /// TODO/FIXME/HACK markers, unless `DOCTREEAI_TECH_DEBT` is off.
+    pub tech_debt: Option<DebtReport>,
+    pub workspace: Option<Workspace>,
+    pub stats: RunStats,
+}
+
+/// Outcome of `DocTree::retry_failed`.
+#[derive(Debug, Clone, Default)]
+pub struct RetryReport {
+    /// Paths that had failed and were attempted again.
+    pub retried: Vec<PathBuf>,
+    /// Paths that failed again, with the new error.
+    pub failed: BTreeMap<PathBuf, String>,
+}
+
+/// Library entry point: summarizes a project and validates its README,
+/// returning the results instead of printing them.
+///
+/// ```no_run
+/// # async fn example() -> doctreeai::Result<()> {
+/// use doctreeai::doctree::{DocTree, RunOptions};
+///
+/// let report = DocTree::open("path/to/project")?.run(&RunOptions::default()).await?;
+/// for result in &report.validation_results {
+///     println!("line {}: {}", result.line_number, result.suggested_content);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct DocTree {
+    path: PathBuf,
+    config: Config,
+    llm_client: Option<LanguageModelClient>,
+    reporter: Arc<dyn Reporter>,
+    progress: Option<Arc<dyn ProgressListener>>,
+    cancellation: CancellationToken,
+    wait_for_lock: bool,
+}
+
+impl DocTree {
+    /// Opens a project with the configuration from the environment and its
+    /// `.doctreeai.toml`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.is_dir() {
+            return Err(DocTreeError::path(format!("{} is not a directory", path.display())));
+        }
+
+        let mut config = Config::load()?;
+        config.apply_project_file(path)?;
+        Ok(Self::with_config(path, config))
+    }
+
+    pub fn with_config(path: impl Into<PathBuf>, config: Config) -> Self {
+        Self {
+            path: path.into(),
+            config,
+            llm_client: None,
+            reporter: Arc::new(SilentReporter),
+            progress: None,
+            cancellation: CancellationToken::new(),
+            wait_for_lock: false,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn config_mut(&mut self) -> &mut Config {
+        &mut self.config
+    }
+
+    /// Uses this client instead of one built from the configuration, e.g.
+    /// `LanguageModelClient::mock` for offline runs.
+    pub fn set_llm_client(&mut self, llm_client: LanguageModelClient) {
+        self.llm_client = Some(llm_client);
+    }
+
+    /// Receives warnings from the run; silent by default.
+    pub fn set_reporter(&mut self, reporter: Arc<dyn Reporter>) {
+        self.reporter = reporter;
+    }
+
+    /// Receives an event as each file and directory is summarized.
+    pub fn set_progress(&mut self, progress: Arc<dyn ProgressListener>) {
+        self.progress = Some(progress);
+    }
+
+    /// Cancelling the token stops the run with `DocTreeError::Cancelled`;
+    /// summaries finished until then stay cached, so the next run resumes.
+    pub fn set_cancellation(&mut self, cancellation: CancellationToken) {
+        self.cancellation = cancellation;
+    }
+
+    /// Waits for another run on the same cache to finish instead of failing;
+    /// see `RunLock`.
+    pub fn set_wait_for_lock(&mut self, wait_for_lock: bool) {
+        self.wait_for_lock = wait_for_lock;
+    }
+
+    /// Keeps other runs off the cache until the lock is dropped. Read-only
+    /// runs write nothing and take no lock.
+    async fn lock(&self, read_only: bool) -> Result<Option<RunLock>> {
+        if read_only {
+            return Ok(None);
+        }
+        let cache_dir = self.config.get_cache_dir_path(&self.path);
+        let lock = if self.wait_for_lock {
+            RunLock::wait(&cache_dir, &self.cancellation).await?
+        } else {
+            RunLock::acquire(&cache_dir)?
+        };
+        Ok(Some(lock))
+    }
+
+    /// The client for a run; it writes to the audit log when enabled and the
+    /// run may write to disk.
+    fn llm_client(&self, read_only: bool) -> Result<LanguageModelClient> {
+        let mut llm_client = match &self.llm_client {
+            Some(llm_client) => llm_client.clone(),
+            None => LanguageModelClient::new(&self.config)?,
+        };
+        if !read_only {
+            if let Some(audit_log) = AuditLog::from_config(&self.config, &self.path)? {
+                llm_client.set_audit_log(audit_log);
+            }
+        }
+        Ok(llm_client)
+    }
+
+    /// The client for a run, answering repeated requests from the response
+    /// cache unless the run regenerates everything.
+    fn run_llm_client(&self, options: &RunOptions) -> Result<LanguageModelClient> {
+        self.llm_client_with_refresh(options, options.force && !options.batch)
+    }
+
+    /// Like `run_llm_client`; `refresh` ignores responses cached earlier.
+    fn llm_client_with_refresh(&self, options: &RunOptions, refresh: bool) -> Result<LanguageModelClient> {
+        let mut llm_client = self.llm_client(options.read_only)?;
+        if let Some(ttl_secs) = self.config.response_cache_ttl_secs {
+            let mut response_cache = ResponseCache::new(&self.config.get_cache_dir_path(&self.path), ttl_secs);
+            response_cache.set_read_only(options.read_only);
+            response_cache.set_refresh(refresh);
+            response_cache.prune_expired()?;
+            llm_client.set_response_cache(response_cache);
+        }
+        Ok(llm_client)
+    }
+
+    /// Progress of the latest run, e.g. to offer resuming it with
+    /// `RunState::resume_options`.
+    pub fn last_run(&self) -> Result<Option<RunState>> {
+        RunState::load(&self.config.get_cache_dir_path(&self.path))
+    }
+
+    /// Marks the recorded run as finished once everything after
+    /// summarization succeeded too.
+    fn complete_run(&self, read_only: bool) -> Result<()> {
+        if read_only {
+            return Ok(());
+        }
+        let cache_dir = self.config.get_cache_dir_path(&self.path);
+        if let Some(mut state) = RunState::load(&cache_dir)? {
+            state.complete();
+            state.save(&cache_dir)?;
+        }
+        Ok(())
+    }
+
+    /// Summarizes again only the paths whose summaries fell back after LLM
+    /// errors in earlier runs, then re-rolls their parent directories. The
+    /// README is not validated; run again for that.
+    pub async fn retry_failed(&self) -> Result<RetryReport> {
+        self.config.validate()?;
+        let _lock = self.lock(false).await?;
+        let Some(state) = self.last_run()? else {
+            return Ok(RetryReport::default());
+        };
+        let retried: Vec<PathBuf> = state.failed.keys().cloned().collect();
+        if retried.is_empty() {
+            return Ok(RetryReport::default());
+        }
+
+        let status = state.status;
+        let options = RunOptions {
+            force: false,
+            ..state.resume_options()?
+        };
+        let mut summarizer = self.summarizer(&options, state)?;
+        summarizer.set_scopes(retried.clone());
+        options.strategy.build(summarizer).generate_project_summary(&self.path).await?;
+
+        // Retrying does not finish an interrupted run
+        let cache_dir = self.config.get_cache_dir_path(&self.path);
+        let mut state = RunState::load(&cache_dir)?
+            .ok_or_else(|| DocTreeError::cache("Run state disappeared during the retry"))?;
+        state.status = status;
+        state.save(&cache_dir)?;
+
+        Ok(RetryReport { retried, failed: state.failed })
+    }
+
+    pub async fn test_connection(&self) -> Result<()> {
+        self.llm_client(true)?.test_connection().await
+    }
+
+    /// Compares every cached summary with the code by content hash and finds
+    /// the README lines mapped to changed entries, without any LLM call.
+    pub fn check_staleness(&self) -> Result<StalenessReport> {
+        let mut cache_manager = CacheManager::new(&self.path, &self.config.cache_dir_name)?;
+        cache_manager.set_read_only(true);
+        let summaries = cache_manager.get_all_summaries();
+        let tree = SummaryTreeNode::from_cache(&summaries, &self.path, &self.config.cache_ttl, current_timestamp());
+
+        let readme_validator = ReadmeValidator::new(cache_manager, self.llm_client(true)?);
+        let mappings = readme_validator.current_mappings(&self.path)?;
+        Ok(StalenessReport::collect(&tree, &mappings, &self.path))
+    }
+
+    fn cache_manager(&self, options: &RunOptions) -> Result<CacheManager> {
+        let mut cache_manager = CacheManager::new(&self.path, &self.config.cache_dir_name)?;
+        let mut refresh_policy = self.config.cache_ttl.clone();
+        if let Some(max_age_secs) = options.refresh_older_than {
+            refresh_policy.cap(max_age_secs);
+        }
+        cache_manager.set_refresh_policy(refresh_policy);
+        cache_manager.set_read_only(options.read_only);
+        cache_manager.set_normalized_hashing(self.config.normalized_hashing);
+        cache_manager.set_doc_language(self.config.doc_language.clone());
+        Ok(cache_manager)
+    }
+
+    fn summarizer(&self, options: &RunOptions, run_state: RunState) -> Result<HierarchicalSummarizer> {
+        let cache_manager = self.cache_manager(options)?;
+        let mut summarizer = HierarchicalSummarizer::new(self.run_llm_client(options)?, cache_manager, options.force);
+        summarizer.apply_config(&self.config, &self.path)?;
+        summarizer.set_scope(options.scope.clone());
+        summarizer.set_progress(self.progress.clone());
+        summarizer.set_cancellation(self.cancellation.clone());
+        summarizer.set_run_state(Some(run_state));
+        Ok(summarizer)
+    }
+
+    /// Finds the requests a run would send by running it against an
+    /// in-memory copy of the cache with a `collecting` client, sends the
+    /// file summary requests among them as one batch job, and stores the
+    /// responses in the response cache for the real run. Requests built
+    /// from other summaries (directories, the project root) are left to it.
+    async fn prefill_with_batch(&self, options: &RunOptions, token_budget: Option<TokenBudget>) -> Result<()> {
+        if options.read_only {
+            return Err(DocTreeError::config("--batch stores responses in the cache and cannot run read-only"));
+        }
+        let mut llm_client = self.llm_client_with_refresh(options, options.force)?;
+        let collector = BatchCollector::default();
+
+        let mut cache_manager = self.cache_manager(options)?;
+        cache_manager.set_read_only(true);
+        let mut collecting = HierarchicalSummarizer::new(llm_client.collecting(collector.clone()), cache_manager, options.force);
+        collecting.apply_config(&self.config, &self.path)?;
+        collecting.set_scope(options.scope.clone());
+        collecting.set_cancellation(self.cancellation.clone());
+        options.strategy.build(collecting).generate_project_summary(&self.path).await?;
+
+        let requests = collector.take();
+        if requests.is_empty() {
+            tracing::info!("Batch mode: every file summary is cached, nothing to send");
+            return Ok(());
+        }
+        if let Some(token_budget) = token_budget {
+            llm_client.set_token_budget(token_budget);
+        }
+        let stored = self.cancellation.run(llm_client.run_batch(&requests, BATCH_POLL_INTERVAL)).await?;
+        tracing::info!("Batch job answered {stored} of {} file summary requests", requests.len());
+        Ok(())
+    }
+
+    /// Summarizes the project and validates the README, within
+    /// `DOCTREEAI_RUN_TIMEOUT_SECS` when set. Waiting for the run lock does
+    /// not count toward it.
+    pub async fn run(&self, options: &RunOptions) -> Result<RunReport> {
+        self.config.validate()?;
+        let _lock = self.lock(options.read_only).await?;
+
+        match self.config.run_timeout_secs {
+            Some(secs) => tokio::time::timeout(Duration::from_secs(secs), self.run_locked(options))
+                .await
+                .map_err(|_| DocTreeError::timeout(format!("Run exceeded the overall timeout of {secs}s")))?,
+            None => self.run_locked(options).await,
+        }
+    }
+
+    async fn run_locked(&self, options: &RunOptions) -> Result<RunReport> {
+        let path = self.path.as_path();
+        let config = &self.config;
+
+        let run_state = match self.last_run()? {
+            Some(previous) if options.resume => previous,
+            previous => {
+                let mut run_state = RunState::new(options, config);
+                if let Some(previous) = previous {
+                    run_state.carry_failures(&previous);
+                }
+                run_state
+            }
+        };
+        // Shared by the summarizer and the README validator
+        let token_budget = config.max_tokens_per_run.map(TokenBudget::new);
+        if options.batch {
+            self.prefill_with_batch(options, token_budget.clone()).await?;
+        }
+        let mut summarizer = self.summarizer(options, run_state)?;
+        if let Some(token_budget) = &token_budget {
+            summarizer.set_token_budget(token_budget.clone());
+        }
+        let tech_debt = match config.tech_debt {
+            DebtOutput::Off => None,
+            _ => Some(DebtReport::collect(&summarizer.scan_tree(path)?, path)),
+        };
+        let mut summarizer = options.strategy.build(summarizer);
+
+        let project_summary = summarizer.generate_project_summary(path).await?;
+
+        let (cache_entries, cache_size) = summarizer.get_cache_stats();
+        let cache_manager = summarizer.into_cache_manager();
+        let summaries = cache_manager.get_all_summaries();
+        let tree = SummaryTreeNode::from_cache(&summaries, path, &config.cache_ttl, current_timestamp());
+        let workspace = Workspace::detect(path)?;
+
+        let mut report = RunReport {
+            project_summary,
+            tree,
+            summaries,
+            validation_results: Vec::new(),
+            digest: None,
+            readme_update: None,
+            document: None,
+            tech_debt,
+            workspace,
+            stats: RunStats { cache_entries, cache_size },
+        };
+        if options.skip_validation {
+            self.complete_run(options.read_only)?;
+            return Ok(report);
+        }
+
+        let mut llm_client = self.run_llm_client(options)?;
+        if let Some(token_budget) = token_budget {
+            llm_client.set_token_budget(token_budget);
+        }
+
+        let mut readme_validator = ReadmeValidator::new(cache_manager, llm_client);
+        readme_validator.set_batch_size(config.validation_batch_size);
+        readme_validator.set_scope(options.scope.clone());
+        readme_validator.set_offline(config.offline);
+        readme_validator.set_min_confidence(options.min_confidence);
+        readme_validator.set_reference_check(config.reference_check);
+        readme_validator.set_usage_examples(config.usage_examples);
+        readme_validator.set_secret_redaction(config.redact_secrets);
+        if let Some(workspace) = &report.workspace {
+            let catalog = ReadmeManager::new().generate_service_catalog(workspace, &report.summaries, path);
+            readme_validator.add_generated_section(SERVICE_CATALOG_HEADING, catalog);
+        }
+        if let (Some(debt), DebtOutput::Section) = (&report.tech_debt, config.tech_debt) {
+            readme_validator.add_generated_section(TECH_DEBT_HEADING, debt.render_section());
+        }
+
+        report.validation_results = self
+            .cancellation
+            .run(readme_validator.validate_readme(path, &report.project_summary))
+            .await?;
+        self.cancellation.check()?;
+        if options.draft_sections && !config.offline {
+            let drafts = self
+                .cancellation
+                .run(readme_validator.draft_missing_sections(path, &config.required_sections, &report.project_summary))
+                .await?;
+            report.validation_results.extend(drafts);
+        } else if let Ok(readme) = std::fs::read_to_string(path.join("README.md")) {
+            let missing = ReadmeManager::new().missing_sections(&readme, &config.required_sections);
+            if !missing.is_empty() {
+                self.reporter.warn(&format!(
+                    "📑 README.md is missing required sections: {} (draft them with --draft-sections)",
+                    missing.join(", ")
+                ));
+            }
+        }
+        if report.validation_results.len() > 1 && !config.offline {
+            match readme_validator.generate_digest(&report.validation_results).await {
+                Ok(digest) => report.digest = Some(digest),
+                Err(e) => self.reporter.warn(&format!("⚠️  Failed to generate validation digest: {e}")),
+            }
+        }
+        if options.render_document {
+            report.document = Some(readme_validator.render_document(path, &report.validation_results)?);
+        }
+        report.readme_update = readme_validator.managed_readme_update(path)?;
+        self.complete_run(options.read_only)?;
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::ScriptedProvider;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_run_returns_structured_report() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        fs::create_dir_all(base_path.join("src"))?;
+        fs::write(base_path.join("src/main.rs"), "fn main() {}")?;
+        fs::write(base_path.join("README.md"), "# Demo\n\nThe scheduler runs nightly jobs.\n")?;
+
+        let config = Config {
+            openai_api_base: "http://localhost:11434/v1".to_string(),
+            openai_api_key: "test".to_string(),
+            openai_model_name: "test-model".to_string(),
+            cache_dir_name: ".test_cache".to_string(),
+            ..Default::default()
+        };
+        let mut doc_tree = DocTree::with_config(base_path, config);
+        doc_tree.set_llm_client(LanguageModelClient::mock(doc_tree.config()));
+
+        let summary_only = doc_tree
+            .run(&RunOptions {
+                skip_validation: true,
+                read_only: true,
+                ..Default::default()
+            })
+            .await?;
+        assert!(!summary_only.project_summary.is_empty());
+        assert!(summary_only.tree.find(Path::new("src/main.rs")).is_some());
+        assert!(summary_only.summaries.iter().any(|entry| entry.source_path.ends_with("src/main.rs")));
+        assert!(!base_path.join(".test_cache").exists());
+
+        let report = doc_tree
+            .run(&RunOptions {
+                render_document: true,
+                ..Default::default()
+            })
+            .await?;
+        assert!(report.stats.cache_entries > 0);
+        assert!(report.document.is_some());
+        assert!(report.workspace.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retry_failed_regenerates_only_failures() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        fs::write(base_path.join("good.rs"), "fn good() {}")?;
+        fs::write(base_path.join("flaky.rs"), "fn flaky() {}")?;
+
+        let config = Config {
+            openai_api_base: "http://localhost:11434/v1".to_string(),
+            openai_api_key: "test".to_string(),
+            openai_model_name: "test-model".to_string(),
+            cache_dir_name: ".test_cache".to_string(),
+            ..Default::default()
+        };
+        let mut doc_tree = DocTree::with_config(base_path, config);
+        // Only good.rs gets an answer: flaky.rs and the root directory fail
+        let provider = ScriptedProvider::new().respond("File: good.rs", "Good helpers.");
+        doc_tree.set_llm_client(provider.client(doc_tree.config()));
+        doc_tree
+            .run(&RunOptions {
+                skip_validation: true,
+                ..Default::default()
+            })
+            .await?;
+
+        let state = doc_tree.last_run()?.unwrap();
+        assert_eq!(state.failed.keys().collect::<Vec<_>>(), vec![Path::new(""), Path::new("flaky.rs")]);
+        assert!(state.completed.contains(Path::new("good.rs")));
+
+        doc_tree.set_llm_client(LanguageModelClient::mock(doc_tree.config()));
+        let report = doc_tree.retry_failed().await?;
+        assert_eq!(report.retried.len(), 2);
+        assert!(report.failed.is_empty());
+
+        let state = doc_tree.last_run()?.unwrap();
+        assert!(state.failed.is_empty());
+        assert!(!state.is_resumable());
+        let cached = CacheManager::new(base_path, ".test_cache")?.get_cache_summary(&base_path.join("good.rs"));
+        assert_eq!(cached.map(|entry| entry.summary), Some("Good helpers.".to_string()));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_batch_mode_answers_file_summaries_before_the_run() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        fs::write(base_path.join("a.rs"), "fn a() {}")?;
+        fs::write(base_path.join("b.rs"), "fn b() {}")?;
+
+        let config = Config {
+            openai_api_base: "http://localhost:11434/v1".to_string(),
+            openai_api_key: "test".to_string(),
+            openai_model_name: "test-model".to_string(),
+            cache_dir_name: ".test_cache".to_string(),
+            ..Default::default()
+        };
+        let mut doc_tree = DocTree::with_config(base_path, config);
+        let provider = ScriptedProvider::new().fallback("Summary.");
+        doc_tree.set_llm_client(provider.client(doc_tree.config()));
+
+        let file_prompts = || provider.prompts().iter().filter(|prompt| prompt.contains("File: ")).count();
+        let options = RunOptions { skip_validation: true, batch: true, ..Default::default() };
+        doc_tree.run(&options).await?;
+        assert_eq!(file_prompts(), 2);
+        assert!(provider.prompts().iter().all(|prompt| !prompt.contains("pending in a batch job")));
+        let cached = CacheManager::new(base_path, ".test_cache")?.get_cache_summary(&base_path.join("a.rs"));
+        assert_eq!(cached.map(|entry| entry.summary), Some("Summary.".to_string()));
+
+        // --force sends every file again, once, and the run uses those answers
+        doc_tree.run(&RunOptions { force: true, ..options }).await?;
+        assert_eq!(file_prompts(), 4);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_runs_send_each_request_once_in_dependency_order() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        for file in ["src/a.rs", "src/b.rs", "lib/c.rs"] {
+            fs::create_dir_all(base_path.join(file).parent().unwrap())?;
+            fs::write(base_path.join(file), format!("// {file}"))?;
+        }
+
+        let config = Config {
+            openai_api_base: "http://localhost:11434/v1".to_string(),
+            openai_api_key: "test".to_string(),
+            openai_model_name: "test-model".to_string(),
+            cache_dir_name: ".test_cache".to_string(),
+            concurrency: 4,
+            ..Default::default()
+        };
+        let mut doc_tree = DocTree::with_config(base_path, config);
+        let provider = ScriptedProvider::new().fallback("Summary.");
+        doc_tree.set_llm_client(provider.client(doc_tree.config()));
+
+        let options = RunOptions { skip_validation: true, ..Default::default() };
+        let distinct = |prompts: &[String]| prompts.iter().collect::<std::collections::HashSet<_>>().len();
+        doc_tree.run(&options).await?;
+        let prompts = provider.prompts();
+        assert_eq!(distinct(&prompts), prompts.len());
+        assert!(prompts.iter().all(|prompt| !prompt.contains("pending in a batch job")));
+        // Each directory is summarized after its own files, the root last
+        let position = |needle: &str| prompts.iter().position(|prompt| prompt.contains(needle)).unwrap();
+        for (directory, files) in [("src", &["a.rs", "b.rs"][..]), ("lib", &["c.rs"][..])] {
+            let summarized = position(&format!("'{directory}' directory"));
+            assert!(files.iter().all(|file| position(&format!("File: {file}")) < summarized));
+        }
+        assert!(prompts.last().unwrap().contains("'project root'"));
+
+        doc_tree.run(&RunOptions { force: true, ..options }).await?;
+        assert_eq!(provider.prompts().len(), 2 * prompts.len());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_resumed_run_keeps_its_token_budget() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        for file in ["a.rs", "b.rs", "c.rs"] {
+            fs::write(base_path.join(file), format!("// {file}"))?;
+        }
+
+        let config = Config {
+            openai_api_base: "http://localhost:11434/v1".to_string(),
+            openai_api_key: "test".to_string(),
+            openai_model_name: "test-model".to_string(),
+            cache_dir_name: ".test_cache".to_string(),
+            max_tokens_per_run: Some(1),
+            ..Default::default()
+        };
+        let provider = ScriptedProvider::new().fallback("Summary.");
+        let mut doc_tree = DocTree::with_config(base_path, config.clone());
+        doc_tree.set_llm_client(provider.client(doc_tree.config()));
+        let stopped = doc_tree.run(&RunOptions { skip_validation: true, ..Default::default() }).await;
+        assert!(matches!(stopped, Err(DocTreeError::Cancelled(_))));
+        assert_eq!(provider.prompts().len(), 1);
+
+        // Resumed like `doctreeai resume`, from a configuration without the budget
+        let state = doc_tree.last_run()?.unwrap();
+        assert_eq!(state.options.max_tokens_per_run, Some(1));
+        // The stopped run still wrote what it completed
+        assert_eq!(state.completed.len(), 1);
+        let mut config = Config { max_tokens_per_run: None, ..config };
+        state.options.restore_config(&mut config);
+        let mut resumed = DocTree::with_config(base_path, config);
+        resumed.set_llm_client(provider.client(resumed.config()));
+        match resumed.run(&state.resume_options()?).await {
+            Err(DocTreeError::Cancelled(message)) => assert!(message.starts_with("Token budget of 1 per run exhausted")),
+            other => panic!("expected the resumed run to stop, got {other:?}"),
+        }
+        assert_eq!(provider.prompts().len(), 2);
+        Ok(())
+    }
+}
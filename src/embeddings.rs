@@ -0,0 +1,251 @@
+use crate::cache::CacheSummary;
+use crate::error::{DocTreeError, Result};
+use crate::llm::LanguageModelClient;
+use crate::reporter::Reporter;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name of the index inside the cache directory.
+pub const EMBEDDING_INDEX_FILE: &str = "embeddings.json";
+
+/// Summaries embedded per request.
+const EMBEDDING_BATCH_SIZE: usize = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    /// Content hash of the summary's source when it was embedded.
+    content_hash: String,
+    vector: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IndexUpdate {
+    pub added: usize,
+    pub updated: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+}
+
+impl IndexUpdate {
+    pub fn report(&self, reporter: &dyn Reporter) {
+        reporter.section(
+            "🧬 Index changes",
+            &[
+                format!("Added: {}", self.added),
+                format!("Updated: {}", self.updated),
+                format!("Removed: {}", self.removed),
+                format!("Unchanged: {}", self.unchanged),
+            ],
+        );
+    }
+}
+
+/// Embeddings of cached summaries keyed by path relative to the project
+/// root. Entries are only re-embedded when their content hash changes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EmbeddingIndex {
+    model: String,
+    entries: BTreeMap<PathBuf, IndexEntry>,
+}
+
+impl EmbeddingIndex {
+    pub fn new(model: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            entries: BTreeMap::new(),
+        }
+    }
+
+    pub fn index_path(cache_dir: &Path) -> PathBuf {
+        cache_dir.join(EMBEDDING_INDEX_FILE)
+    }
+
+    /// Loads the index, or an empty one when missing or built with another model.
+    pub fn load(cache_dir: &Path, model: &str) -> Result<Self> {
+        let index_path = Self::index_path(cache_dir);
+        if !index_path.exists() {
+            return Ok(Self::new(model));
+        }
+
+        let content = fs::read_to_string(&index_path)?;
+        let index: Self = serde_json::from_str(&content)
+            .map_err(|e| DocTreeError::cache(format!("Failed to parse embedding index: {e}")))?;
+
+        if index.model != model {
+            tracing::info!("Embedding model changed from {} to {model}, rebuilding index", index.model);
+            return Ok(Self::new(model));
+        }
+        Ok(index)
+    }
+
+    pub fn save(&self, cache_dir: &Path) -> Result<()> {
+        fs::create_dir_all(cache_dir)?;
+        let content = serde_json::to_string(self)
+            .map_err(|e| DocTreeError::cache(format!("Failed to serialize embedding index: {e}")))?;
+        fs::write(Self::index_path(cache_dir), content)
+            .map_err(|e| DocTreeError::cache(format!("Failed to write embedding index: {e}")))
+    }
+
+    /// Removes the index file; returns whether there was one.
+    pub fn clear(cache_dir: &Path) -> Result<bool> {
+        let index_path = Self::index_path(cache_dir);
+        if !index_path.exists() {
+            return Ok(false);
+        }
+        fs::remove_file(&index_path)
+            .map_err(|e| DocTreeError::cache(format!("Failed to remove embedding index: {e}")))?;
+        Ok(true)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Brings the index in line with the cached summaries: new and changed
+    /// summaries are embedded, summaries no longer cached are dropped.
+    pub async fn update(
+        &mut self,
+        summaries: &[CacheSummary],
+        base_path: &Path,
+        llm_client: &LanguageModelClient,
+    ) -> Result<IndexUpdate> {
+        let mut update = IndexUpdate::default();
+        let mut pending: Vec<(PathBuf, &CacheSummary)> = Vec::new();
+
+        let current: BTreeMap<PathBuf, &CacheSummary> = summaries
+            .iter()
+            .map(|summary| {
+                let relative_path = summary
+                    .source_path
+                    .strip_prefix(base_path)
+                    .unwrap_or(&summary.source_path)
+                    .to_path_buf();
+                (relative_path, summary)
+            })
+            .collect();
+
+        let before = self.entries.len();
+        self.entries.retain(|path, _| current.contains_key(path));
+        update.removed = before - self.entries.len();
+
+        for (path, summary) in current {
+            match self.entries.get(&path) {
+                Some(entry) if entry.content_hash == summary.content_hash => update.unchanged += 1,
+                Some(_) => {
+                    update.updated += 1;
+                    pending.push((path, summary));
+                }
+                None => {
+                    update.added += 1;
+                    pending.push((path, summary));
+                }
+            }
+        }
+
+        for batch in pending.chunks(EMBEDDING_BATCH_SIZE) {
+            let texts: Vec<String> = batch
+                .iter()
+                .map(|(path, summary)| format!("{}\n{}", path.display(), summary.summary))
+                .collect();
+            let vectors = llm_client.embed(&texts, &self.model).await?;
+
+            for ((path, summary), vector) in batch.iter().zip(vectors) {
+                self.entries.insert(
+                    path.clone(),
+                    IndexEntry {
+                        content_hash: summary.content_hash.clone(),
+                        vector,
+                    },
+                );
+            }
+        }
+
+        Ok(update)
+    }
+
+    /// The `limit` entries most similar to the query vector, best first.
+    pub fn nearest(&self, query: &[f32], limit: usize) -> Vec<(PathBuf, f32)> {
+        let mut scored: Vec<(PathBuf, f32)> = self
+            .entries
+            .iter()
+            .map(|(path, entry)| (path.clone(), cosine_similarity(query, &entry.vector)))
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(limit);
+        scored
+    }
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a * norm_b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::SummaryMethod;
+    use crate::config::Config;
+    use tempfile::TempDir;
+
+    fn summary(base_path: &Path, path: &str, hash: &str) -> CacheSummary {
+        CacheSummary {
+            source_path: base_path.join(path),
+            content_hash: hash.to_string(),
+            summary: format!("Summary of {path}"),
+            timestamp: 0,
+            is_directory: false,
+            role: None,
+            pinned: false,
+            content_size: None,
+            modified: None,
+            normalized_hash: false,
+            language: None,
+            method: SummaryMethod::Llm,
+            structured: None,
+            sibling_context: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_incremental_update() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        let cache_dir = base_path.join(".doctreeai_cache");
+        let llm_client = LanguageModelClient::mock(&Config::default());
+
+        let mut index = EmbeddingIndex::load(&cache_dir, "embed")?;
+        let summaries = vec![summary(base_path, "a.rs", "1"), summary(base_path, "b.rs", "1")];
+        let update = index.update(&summaries, base_path, &llm_client).await?;
+        assert_eq!(update, IndexUpdate { added: 2, ..Default::default() });
+        index.save(&cache_dir)?;
+
+        let mut index = EmbeddingIndex::load(&cache_dir, "embed")?;
+        let summaries = vec![summary(base_path, "a.rs", "2"), summary(base_path, "c.rs", "1")];
+        let update = index.update(&summaries, base_path, &llm_client).await?;
+        assert_eq!(
+            update,
+            IndexUpdate { added: 1, updated: 1, removed: 1, unchanged: 0 }
+        );
+
+        let query = &index.entries[Path::new("c.rs")].vector;
+        assert_eq!(index.nearest(query, 1)[0].0, PathBuf::from("c.rs"));
+
+        assert!(EmbeddingIndex::load(&cache_dir, "other-model")?.is_empty());
+        assert!(EmbeddingIndex::clear(&cache_dir)?);
+        assert!(!EmbeddingIndex::clear(&cache_dir)?);
+
+        Ok(())
+    }
+}
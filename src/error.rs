@@ -13,6 +13,14 @@ pub enum DocTreeError {
     #[error("OpenAI API error: {0}")]
     OpenAi(#[from] async_openai::error::OpenAIError),
 
+    /// An error status from the LLM server, with its `Retry-After` wait.
+    #[error("LLM API error ({status}): {error}")]
+    Api {
+        status: u16,
+        retry_after: Option<std::time::Duration>,
+        error: async_openai::error::ApiError,
+    },
+
     #[error("Configuration error: {0}")]
     Config(String),
 
@@ -31,6 +39,15 @@ pub enum DocTreeError {
     #[error("Path error: {0}")]
     Path(String),
 
+    #[error("Timeout: {0}")]
+    Timeout(String),
+
+    #[error("Cancelled: {0}")]
+    Cancelled(String),
+
+    #[error("GitHub error: {0}")]
+    Github(String),
+
     #[error("Environment variable error: {variable}")]
     EnvironmentVariable { variable: String },
 
@@ -63,6 +80,18 @@ impl DocTreeError {
         Self::Path(msg.into())
     }
 
+    pub fn timeout(msg: impl Into<String>) -> Self {
+        Self::Timeout(msg.into())
+    }
+
+    pub fn cancelled(msg: impl Into<String>) -> Self {
+        Self::Cancelled(msg.into())
+    }
+
+    pub fn github(msg: impl Into<String>) -> Self {
+        Self::Github(msg.into())
+    }
+
     pub fn environment_variable(variable: impl Into<String>) -> Self {
         Self::EnvironmentVariable {
             variable: variable.into(),
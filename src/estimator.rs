@@ -0,0 +1,375 @@
+use crate::cache::{CacheManager, SummaryMethod};
+use crate::config::Config;
+use crate::digest::{extractive_summary, heuristic_summary};
+use crate::error::Result;
+use crate::generated::GeneratedFileDetector;
+use crate::hasher::FileHasher;
+use crate::limits::{FileSizeLimit, ScanLimits};
+use crate::outline::{extract_outline, SummaryInput};
+use crate::reporter::Reporter;
+use crate::scanner::{DirectoryScanner, FileNode, ScanFilter, SourceFileMatcher, WalkOptions};
+use crate::submodules::SubmodulePolicy;
+use std::fs;
+use std::path::Path;
+
+/// Rough number of bytes per token used for source code.
+const BYTES_PER_TOKEN: u64 = 4;
+
+/// Fixed prompt overhead (instructions, system message) added to every call.
+const PROMPT_OVERHEAD_TOKENS: u64 = 150;
+
+/// Expected completion length per call; the client caps completions at 1000 tokens.
+const ESTIMATED_OUTPUT_TOKENS_PER_CALL: u64 = 400;
+
+/// Approximates the token count of a text for budgeting purposes.
+pub fn estimate_tokens(text: &str) -> u64 {
+    (text.len() as u64).div_ceil(BYTES_PER_TOKEN)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CostEstimate {
+    pub total_files: usize,
+    pub files_to_summarize: usize,
+    pub cached_files: usize,
+    pub total_directories: usize,
+    pub directories_to_summarize: usize,
+    pub cached_directories: usize,
+    pub estimated_input_tokens: u64,
+    pub estimated_output_tokens: u64,
+    pub estimated_cost: f64,
+}
+
+impl CostEstimate {
+    pub fn llm_calls(&self) -> usize {
+        self.files_to_summarize + self.directories_to_summarize
+    }
+
+    pub fn report(&self, reporter: &dyn Reporter) {
+        reporter.section(
+            "🧮 Estimate",
+            &[
+                format!("Files: {} total, {} to summarize, {} cached",
+                    self.total_files, self.files_to_summarize, self.cached_files),
+                format!("Directories: {} total, {} to summarize, {} cached",
+                    self.total_directories, self.directories_to_summarize, self.cached_directories),
+                format!("Projected LLM calls: {}", self.llm_calls()),
+                format!("Estimated input tokens: {}", self.estimated_input_tokens),
+                format!("Estimated output tokens: {}", self.estimated_output_tokens),
+                format!("Estimated cost: ${:.4}", self.estimated_cost),
+            ],
+        );
+    }
+}
+
+/// Pricing in currency units per million tokens. Local models default to zero.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokenPricing {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+struct NodeEstimate {
+    content_hash: Option<String>,
+    has_summary: bool,
+    summary_tokens: u64,
+}
+
+impl NodeEstimate {
+    fn without_summary(content_hash: Option<String>) -> Self {
+        Self {
+            content_hash,
+            has_summary: false,
+            summary_tokens: 0,
+        }
+    }
+
+    fn with_summary(content_hash: String, summary_tokens: u64) -> Self {
+        Self {
+            content_hash: Some(content_hash),
+            has_summary: true,
+            summary_tokens,
+        }
+    }
+}
+
+pub struct CostEstimator<'a> {
+    cache_manager: &'a CacheManager,
+    force_regeneration: bool,
+    pricing: TokenPricing,
+    scan_filter: ScanFilter,
+    scan_limits: ScanLimits,
+    walk_options: WalkOptions,
+    submodule_policy: SubmodulePolicy,
+    source_matcher: SourceFileMatcher,
+    file_size_limit: FileSizeLimit,
+    generated_detector: GeneratedFileDetector,
+    small_model: bool,
+    extractive_below_bytes: u64,
+    summary_input: SummaryInput,
+}
+
+impl<'a> CostEstimator<'a> {
+    pub fn new(cache_manager: &'a CacheManager, force_regeneration: bool, pricing: TokenPricing) -> Self {
+        Self {
+            cache_manager,
+            force_regeneration,
+            pricing,
+            scan_filter: ScanFilter::default(),
+            scan_limits: ScanLimits::default(),
+            walk_options: WalkOptions::default(),
+            submodule_policy: SubmodulePolicy::default(),
+            source_matcher: SourceFileMatcher::default(),
+            file_size_limit: FileSizeLimit::default(),
+            generated_detector: GeneratedFileDetector::default(),
+            small_model: false,
+            extractive_below_bytes: 0,
+            summary_input: SummaryInput::default(),
+        }
+    }
+
+    /// Applies the scan and summarization settings a run would use, the
+    /// same ones `HierarchicalSummarizer::apply_config` applies.
+    pub fn apply_config(&mut self, config: &Config, base_path: &Path) -> Result<()> {
+        self.set_scan_filter(ScanFilter::new(&config.include_patterns, &config.exclude_patterns)?);
+        self.set_scan_limits(config.scan_limits);
+        self.set_walk_options(config.walk_options);
+        self.set_submodule_policy(config.submodule_policy.clone());
+        self.set_small_model(config.small_model);
+        self.set_extractive_below_bytes(config.extractive_below_bytes);
+        self.set_summary_input(config.summary_input);
+        self.set_source_matcher(SourceFileMatcher::new(&config.extra_extensions, &config.extra_filenames)?);
+        self.set_file_size_limit(config.file_size_limit);
+        self.set_generated_detector(GeneratedFileDetector::from_config(config, base_path)?);
+        Ok(())
+    }
+
+    pub fn set_scan_filter(&mut self, scan_filter: ScanFilter) {
+        self.scan_filter = scan_filter;
+    }
+
+    pub fn set_scan_limits(&mut self, scan_limits: ScanLimits) {
+        self.scan_limits = scan_limits;
+    }
+
+    pub fn set_walk_options(&mut self, walk_options: WalkOptions) {
+        self.walk_options = walk_options;
+    }
+
+    pub fn set_submodule_policy(&mut self, submodule_policy: SubmodulePolicy) {
+        self.submodule_policy = submodule_policy;
+    }
+
+    pub fn set_source_matcher(&mut self, source_matcher: SourceFileMatcher) {
+        self.source_matcher = source_matcher;
+    }
+
+    pub fn set_file_size_limit(&mut self, file_size_limit: FileSizeLimit) {
+        self.file_size_limit = file_size_limit;
+    }
+
+    pub fn set_generated_detector(&mut self, generated_detector: GeneratedFileDetector) {
+        self.generated_detector = generated_detector;
+    }
+
+    /// Trivial files are described without the LLM in small-model mode.
+    pub fn set_small_model(&mut self, small_model: bool) {
+        self.small_model = small_model;
+    }
+
+    /// Files below this size are described without the LLM (0 disables it).
+    pub fn set_extractive_below_bytes(&mut self, bytes: u64) {
+        self.extractive_below_bytes = bytes;
+    }
+
+    /// Outlines are counted instead of the content where one is sent.
+    pub fn set_summary_input(&mut self, summary_input: SummaryInput) {
+        self.summary_input = summary_input;
+    }
+
+    pub fn estimate(&self, base_path: &Path) -> Result<CostEstimate> {
+        let mut scanner = DirectoryScanner::new(base_path.to_path_buf());
+        scanner.set_filter(self.scan_filter.clone());
+        scanner.set_limits(self.scan_limits);
+        scanner.set_walk_options(self.walk_options);
+        scanner.set_submodule_policy(self.submodule_policy.clone());
+        let root_node = scanner.scan_directory()?;
+
+        let mut estimate = CostEstimate::default();
+        self.estimate_node(&root_node, base_path, &mut estimate)?;
+
+        estimate.estimated_cost = estimate.estimated_input_tokens as f64 * self.pricing.input_per_million / 1_000_000.0
+            + estimate.estimated_output_tokens as f64 * self.pricing.output_per_million / 1_000_000.0;
+
+        Ok(estimate)
+    }
+
+    /// Mirrors HierarchicalSummarizer's traversal so that directory hashes are
+    /// computed exactly as a real run would compute them.
+    fn estimate_node(&self, node: &FileNode, base_path: &Path, estimate: &mut CostEstimate) -> Result<NodeEstimate> {
+        if let Some(submodule) = &node.submodule {
+            // Opaque submodules get a fixed summary without an LLM call
+            let summary = submodule.summary();
+            let content_hash = FileHasher::compute_content_hash(&summary);
+            return Ok(NodeEstimate::with_summary(content_hash, summary.len() as u64 / BYTES_PER_TOKEN));
+        }
+
+        if node.is_directory {
+            self.estimate_directory(node, base_path, estimate)
+        } else {
+            self.estimate_file(node, base_path, estimate)
+        }
+    }
+
+    fn estimate_file(&self, node: &FileNode, base_path: &Path, estimate: &mut CostEstimate) -> Result<NodeEstimate> {
+        if !node.is_source_file(&self.source_matcher) {
+            return Ok(NodeEstimate::without_summary(None));
+        }
+
+        let content_hash = self.cache_manager.hash_file(&node.path)?;
+        estimate.total_files += 1;
+
+        if !self.force_regeneration {
+            // Extractive entries are retried with the LLM, so they cost a call
+            if let Some(cached) = self
+                .cache_manager
+                .get_cached_entry(&node.path, &content_hash)
+                .filter(|cached| cached.method != SummaryMethod::Extractive)
+            {
+                estimate.cached_files += 1;
+                return Ok(NodeEstimate::with_summary(content_hash, cached.summary.len() as u64 / BYTES_PER_TOKEN));
+            }
+        }
+
+        match fs::read_to_string(&node.path) {
+            Ok(content) if !content.trim().is_empty() => {
+                let relative_path = node.get_relative_path(base_path)?;
+                if self.generated_detector.check(&relative_path, &content).is_some() {
+                    estimate.total_files -= 1;
+                    return Ok(NodeEstimate::without_summary(None));
+                }
+                let original_size = content.len() as u64;
+                let Some(content) = self.file_size_limit.apply(content) else {
+                    // Oversized files are skipped entirely
+                    estimate.total_files -= 1;
+                    return Ok(NodeEstimate::without_summary(None));
+                };
+                if self.small_model {
+                    if let Some(summary) = extractive_summary(&relative_path, &content) {
+                        return Ok(NodeEstimate::with_summary(content_hash, summary.len() as u64 / BYTES_PER_TOKEN));
+                    }
+                }
+                if original_size < self.extractive_below_bytes {
+                    let summary = heuristic_summary(&relative_path, &content);
+                    return Ok(NodeEstimate::with_summary(content_hash, summary.len() as u64 / BYTES_PER_TOKEN));
+                }
+                let input_bytes = match self.summary_input {
+                    SummaryInput::Outline => extract_outline(&relative_path, &content).map_or(content.len(), |outline| outline.len()),
+                    SummaryInput::Content => content.len(),
+                };
+                estimate.files_to_summarize += 1;
+                estimate.estimated_input_tokens += input_bytes as u64 / BYTES_PER_TOKEN + PROMPT_OVERHEAD_TOKENS;
+                estimate.estimated_output_tokens += ESTIMATED_OUTPUT_TOKENS_PER_CALL;
+                Ok(NodeEstimate::with_summary(content_hash, ESTIMATED_OUTPUT_TOKENS_PER_CALL))
+            }
+            _ => {
+                // Empty or unreadable files are hashed but never summarized
+                estimate.total_files -= 1;
+                Ok(NodeEstimate::without_summary(Some(content_hash)))
+            }
+        }
+    }
+
+    fn estimate_directory(&self, node: &FileNode, base_path: &Path, estimate: &mut CostEstimate) -> Result<NodeEstimate> {
+        let mut children_hashes = Vec::new();
+        let mut children_tokens = 0;
+        let mut has_summarized_children = false;
+
+        for child in &node.children {
+            let child_estimate = self.estimate_node(child, base_path, estimate)?;
+            if let Some(hash) = child_estimate.content_hash {
+                children_hashes.push(hash);
+            }
+            if child_estimate.has_summary {
+                has_summarized_children = true;
+                children_tokens += child_estimate.summary_tokens;
+            }
+        }
+
+        if !has_summarized_children {
+            return Ok(NodeEstimate::without_summary(None));
+        }
+
+        estimate.total_directories += 1;
+        let directory_hash = FileHasher::compute_directory_hash(&children_hashes);
+
+        if !self.force_regeneration {
+            if let Some(summary) = self.cache_manager.get_cached_summary(&node.path, &directory_hash) {
+                estimate.cached_directories += 1;
+                return Ok(NodeEstimate::with_summary(directory_hash, summary.len() as u64 / BYTES_PER_TOKEN));
+            }
+        }
+
+        estimate.directories_to_summarize += 1;
+        estimate.estimated_input_tokens += children_tokens + PROMPT_OVERHEAD_TOKENS;
+        estimate.estimated_output_tokens += ESTIMATED_OUTPUT_TOKENS_PER_CALL;
+
+        Ok(NodeEstimate::with_summary(directory_hash, ESTIMATED_OUTPUT_TOKENS_PER_CALL))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_estimate_uncached_project() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        fs::create_dir_all(base_path.join("src"))?;
+        fs::write(base_path.join("src/main.rs"), "fn main() {}")?;
+        fs::write(base_path.join("src/lib.rs"), "pub mod utils;")?;
+
+        let cache_manager = CacheManager::new(base_path, ".test_cache")?;
+        let estimator = CostEstimator::new(&cache_manager, false, TokenPricing::default());
+        let estimate = estimator.estimate(base_path)?;
+
+        assert_eq!(estimate.total_files, 2);
+        assert_eq!(estimate.files_to_summarize, 2);
+        // src/ and the project root both need a rollup
+        assert_eq!(estimate.directories_to_summarize, 2);
+        assert_eq!(estimate.llm_calls(), 4);
+        assert_eq!(estimate.estimated_cost, 0.0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_estimate_uses_cache() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        let file_path = base_path.join("main.rs");
+        fs::write(&file_path, "fn main() {}")?;
+
+        let mut cache_manager = CacheManager::new(base_path, ".test_cache")?;
+        let hash = FileHasher::compute_file_hash(&file_path)?;
+        cache_manager.store_summary(&file_path, hash.clone(), "Entry point".to_string())?;
+        let directory_hash = FileHasher::compute_directory_hash(&[hash]);
+        cache_manager.store_summary(base_path, directory_hash, "Project".to_string())?;
+
+        let pricing = TokenPricing {
+            input_per_million: 1.0,
+            output_per_million: 2.0,
+        };
+        let estimate = CostEstimator::new(&cache_manager, false, pricing).estimate(base_path)?;
+        assert_eq!(estimate.cached_files, 1);
+        assert_eq!(estimate.cached_directories, 1);
+        assert_eq!(estimate.llm_calls(), 0);
+
+        let forced = CostEstimator::new(&cache_manager, true, pricing).estimate(base_path)?;
+        assert_eq!(forced.llm_calls(), 2);
+        assert!(forced.estimated_cost > 0.0);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+use crate::error::Result;
+use std::fs::{self, File, TryLockError};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How often a task waiting for a lock tries it again.
+const RETRY_INTERVAL: Duration = Duration::from_millis(20);
+
+/// An exclusive advisory lock on a file. The operating system releases it
+/// when the handle is dropped or the holding process exits, so a crashed
+/// process never leaves a lock behind and there is nothing to take over.
+///
+/// The lock file itself stays in place: deleting it would let a process
+/// that opened the old file and one that creates a new file both hold "the"
+/// lock.
+#[derive(Debug)]
+pub struct FileLock {
+    /// Holds the lock until dropped.
+    file: File,
+    path: PathBuf,
+}
+
+impl FileLock {
+    /// The lock, or None when another handle holds it.
+    pub fn try_acquire(path: &Path) -> Result<Option<Self>> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = fs::OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+
+        match file.try_lock() {
+            Ok(()) => Ok(Some(Self { file, path: path.to_path_buf() })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => Err(e.into()),
+        }
+    }
+
+    /// Waits for the lock, sleeping on the async runtime between attempts
+    /// instead of blocking its worker thread.
+    pub async fn acquire(path: &Path) -> Result<Self> {
+        loop {
+            if let Some(lock) = Self::try_acquire(path)? {
+                return Ok(lock);
+            }
+            tokio::time::sleep(RETRY_INTERVAL).await;
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Replaces the lock file's contents, e.g. with who holds the lock, for
+    /// processes waiting for it to read.
+    pub fn write(&mut self, contents: &str) -> Result<()> {
+        self.file.set_len(0)?;
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.write_all(contents.as_bytes())?;
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_lock_is_exclusive_until_dropped() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let path = temp_dir.path().join("cache/test.lock");
+
+        let lock = FileLock::acquire(&path).await?;
+        assert!(FileLock::try_acquire(&path)?.is_none());
+
+        let waiting = tokio::spawn({
+            let path = path.clone();
+            async move { FileLock::acquire(&path).await }
+        });
+        tokio::time::sleep(RETRY_INTERVAL * 3).await;
+        assert!(!waiting.is_finished());
+
+        drop(lock);
+        let lock = waiting.await.expect("waiting task")?;
+        assert_eq!(lock.path(), path);
+        Ok(())
+    }
+}
@@ -0,0 +1,99 @@
+/// Formats sizes and times for reports: human-readable by default, or the
+/// raw byte counts and unix seconds with `--raw`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReportFormat {
+    pub raw: bool,
+}
+
+impl ReportFormat {
+    pub fn new(raw: bool) -> Self {
+        Self { raw }
+    }
+
+    pub fn bytes(&self, bytes: u64) -> String {
+        if self.raw {
+            bytes.to_string()
+        } else {
+            format_bytes(bytes)
+        }
+    }
+
+    pub fn timestamp(&self, timestamp: u64) -> String {
+        if self.raw {
+            timestamp.to_string()
+        } else {
+            format_relative_time(timestamp, current_timestamp())
+        }
+    }
+}
+
+/// Binary units with one decimal, e.g. `1.5 KiB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next in &UNITS[1..] {
+        if value < 1024.0 {
+            break;
+        }
+        value /= 1024.0;
+        unit = next;
+    }
+    format!("{value:.1} {unit}")
+}
+
+/// Coarse relative time, e.g. `3 hours ago`.
+pub fn format_relative_time(timestamp: u64, now: u64) -> String {
+    if timestamp > now {
+        return "in the future".to_string();
+    }
+
+    let elapsed = now - timestamp;
+    let (amount, unit) = match elapsed {
+        0..=59 => return "just now".to_string(),
+        60..=3_599 => (elapsed / 60, "minute"),
+        3_600..=86_399 => (elapsed / 3_600, "hour"),
+        86_400..=2_591_999 => (elapsed / 86_400, "day"),
+        2_592_000..=31_535_999 => (elapsed / 2_592_000, "month"),
+        _ => (elapsed / 31_536_000, "year"),
+    };
+
+    let plural = if amount == 1 { "" } else { "s" };
+    format!("{amount} {unit}{plural} ago")
+}
+
+pub fn current_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MiB");
+        assert_eq!(ReportFormat::new(true).bytes(1536), "1536");
+    }
+
+    #[test]
+    fn test_format_relative_time() {
+        let now = 1_000_000_000;
+        assert_eq!(format_relative_time(now - 5, now), "just now");
+        assert_eq!(format_relative_time(now - 60, now), "1 minute ago");
+        assert_eq!(format_relative_time(now - 3 * 3_600, now), "3 hours ago");
+        assert_eq!(format_relative_time(now - 40 * 86_400, now), "1 month ago");
+        assert_eq!(format_relative_time(now + 10, now), "in the future");
+        assert_eq!(ReportFormat::new(true).timestamp(42), "42");
+    }
+}
@@ -0,0 +1,232 @@
+use crate::annotations::COMMENT_PREFIXES;
+use crate::config::Config;
+use crate::error::Result;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// Lockfiles are machine-written and never worth a summary.
+const LOCKFILE_NAMES: &[&str] = &[
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "composer.lock",
+    "Pipfile.lock",
+    "poetry.lock",
+    "Gemfile.lock",
+    "Cargo.lock",
+    "go.sum",
+    "flake.lock",
+];
+
+/// Markers that code generators put in header comments.
+const GENERATED_MARKERS: &[&str] = &[
+    "@generated",
+    "do not edit",
+    "autogenerated",
+    "auto-generated",
+    "automatically generated",
+    "code generated",
+];
+
+/// Only the beginning of a file is searched for generator markers.
+const HEADER_BYTES: usize = 1024;
+
+/// Lines longer than this are a strong sign of minified or bundled output.
+const MINIFIED_LINE_LENGTH: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    Binary,
+    Lockfile,
+    Minified,
+    GeneratedMarker,
+    LinguistGenerated,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            SkipReason::Binary => "binary content",
+            SkipReason::Lockfile => "lockfile",
+            SkipReason::Minified => "minified content",
+            SkipReason::GeneratedMarker => "generated-code marker",
+            SkipReason::LinguistGenerated => "linguist-generated in .gitattributes",
+        };
+        write!(f, "{reason}")
+    }
+}
+
+/// Recognizes binary, minified, and generated files by content and by
+/// `linguist-generated` attributes, so they don't cost LLM calls.
+#[derive(Debug, Clone)]
+pub struct GeneratedFileDetector {
+    enabled: bool,
+    linguist_generated: GlobSet,
+}
+
+impl Default for GeneratedFileDetector {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            linguist_generated: GlobSet::empty(),
+        }
+    }
+}
+
+impl GeneratedFileDetector {
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Default::default()
+        }
+    }
+
+    /// Loaded from .gitattributes, or disabled when `DOCTREEAI_SKIP_GENERATED` is off.
+    pub fn from_config(config: &Config, base_path: &Path) -> Result<Self> {
+        if config.skip_generated {
+            Self::load(base_path)
+        } else {
+            Ok(Self::disabled())
+        }
+    }
+
+    /// Loads `linguist-generated` patterns from the project's .gitattributes.
+    pub fn load(base_path: &Path) -> Result<Self> {
+        let Ok(content) = fs::read_to_string(base_path.join(".gitattributes")) else {
+            return Ok(Self::default());
+        };
+
+        Ok(Self {
+            enabled: true,
+            linguist_generated: Self::parse_gitattributes(&content),
+        })
+    }
+
+    fn parse_gitattributes(content: &str) -> GlobSet {
+        let mut builder = GlobSetBuilder::new();
+
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.split_whitespace();
+            let Some(pattern) = parts.next() else {
+                continue;
+            };
+            let generated = parts.any(|attribute| {
+                matches!(attribute, "linguist-generated" | "linguist-generated=true")
+            });
+            if !generated {
+                continue;
+            }
+
+            let glob = match pattern.strip_prefix('/') {
+                Some(anchored) => anchored.to_string(),
+                None if pattern.contains('/') => pattern.to_string(),
+                None => format!("**/{pattern}"),
+            };
+            match Glob::new(&glob) {
+                Ok(glob) => {
+                    builder.add(glob);
+                }
+                Err(e) => tracing::warn!("Ignoring invalid .gitattributes pattern '{pattern}': {e}"),
+            }
+        }
+
+        builder.build().unwrap_or_else(|_| GlobSet::empty())
+    }
+
+    /// Returns why a file should be skipped, or None if it is worth summarizing.
+    pub fn check(&self, relative_path: &Path, content: &str) -> Option<SkipReason> {
+        if !self.enabled {
+            return None;
+        }
+
+        if content.contains('\0') {
+            return Some(SkipReason::Binary);
+        }
+
+        if relative_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|name| LOCKFILE_NAMES.contains(&name))
+        {
+            return Some(SkipReason::Lockfile);
+        }
+
+        if self.linguist_generated.is_match(relative_path) {
+            return Some(SkipReason::LinguistGenerated);
+        }
+
+        let mut header_end = content.len().min(HEADER_BYTES);
+        while !content.is_char_boundary(header_end) {
+            header_end -= 1;
+        }
+        let has_marker = content[..header_end]
+            .lines()
+            .map(str::trim_start)
+            .filter(|line| COMMENT_PREFIXES.iter().any(|prefix| line.starts_with(prefix)))
+            .any(|line| {
+                let line = line.to_lowercase();
+                GENERATED_MARKERS.iter().any(|marker| line.contains(marker))
+            });
+        if has_marker {
+            return Some(SkipReason::GeneratedMarker);
+        }
+
+        if content.lines().any(|line| line.len() > MINIFIED_LINE_LENGTH) {
+            return Some(SkipReason::Minified);
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_sniffing() {
+        let detector = GeneratedFileDetector::default();
+        let path = Path::new("src/file.js");
+
+        assert_eq!(detector.check(path, "a\0b"), Some(SkipReason::Binary));
+        assert_eq!(
+            detector.check(path, "// Code generated by protoc-gen-go. DO NOT EDIT.\npackage pb\n"),
+            Some(SkipReason::GeneratedMarker)
+        );
+        assert_eq!(detector.check(path, &"x".repeat(5000)), Some(SkipReason::Minified));
+        assert_eq!(
+            detector.check(Path::new("web/package-lock.json"), "{}"),
+            Some(SkipReason::Lockfile)
+        );
+        assert_eq!(detector.check(path, "export const a = 1;\n"), None);
+        assert_eq!(detector.check(path, "const markers = [\"@generated\"];\n"), None);
+        assert_eq!(GeneratedFileDetector::disabled().check(path, "a\0b"), None);
+    }
+
+    #[test]
+    fn test_linguist_generated_attributes() {
+        let detector = GeneratedFileDetector {
+            enabled: true,
+            linguist_generated: GeneratedFileDetector::parse_gitattributes(
+                "*.pb.go linguist-generated=true\n/api/openapi/** linguist-generated\ndocs/** linguist-documentation\n",
+            ),
+        };
+
+        assert_eq!(
+            detector.check(Path::new("internal/user.pb.go"), "package user\n"),
+            Some(SkipReason::LinguistGenerated)
+        );
+        assert_eq!(
+            detector.check(Path::new("api/openapi/client.ts"), "export {}\n"),
+            Some(SkipReason::LinguistGenerated)
+        );
+        assert_eq!(detector.check(Path::new("docs/guide.md"), "# Guide\n"), None);
+    }
+}
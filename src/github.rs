@@ -0,0 +1,295 @@
+use crate::error::{DocTreeError, Result};
+use crate::readme_validator::{Severity, ValidationResult};
+use crate::renderer::{MarkdownRenderer, RenderInput, Renderer};
+use base64::Engine;
+use reqwest::{Method, StatusCode};
+use serde_json::{json, Value};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+
+/// Environment variable Actions sets to the file backing the job summary.
+const STEP_SUMMARY_VAR: &str = "GITHUB_STEP_SUMMARY";
+
+pub const DEFAULT_API_URL: &str = "https://api.github.com";
+
+/// Output for GitHub Actions (`doctreeai run --github`): every suggestion as
+/// a workflow-command annotation on its README line, and the markdown report
+/// in the job's step summary.
+pub struct GithubActions {
+    /// README.md relative to the repository root, as annotations need it.
+    readme: String,
+}
+
+impl GithubActions {
+    pub fn new(readme: &Path) -> Self {
+        Self {
+            readme: readme.to_string_lossy().replace('\\', "/"),
+        }
+    }
+
+    /// One `::warning` (outdated) or `::notice` (cosmetic) command per result.
+    pub fn annotations(&self, results: &[ValidationResult]) -> String {
+        let mut out = String::new();
+
+        for result in results {
+            let command = match result.severity {
+                Severity::Outdated => "warning",
+                Severity::Cosmetic => "notice",
+            };
+            let mut properties = vec![format!("file={}", escape_property(&self.readme))];
+            if result.line_number > 0 {
+                let line_count = result.current_content.lines().count().max(1);
+                properties.push(format!("line={}", result.line_number));
+                properties.push(format!("endLine={}", result.line_number + line_count - 1));
+            }
+            let title = match result.section.as_str() {
+                "" => "README drift".to_string(),
+                section => format!("README drift in {section}"),
+            };
+            properties.push(format!("title={}", escape_property(&title)));
+
+            let message = format!(
+                "{} (confidence {:.2})\nSuggested: {}",
+                result.reason, result.confidence, result.suggested_content
+            );
+            out.push_str(&format!("::{command} {}::{}\n", properties.join(","), escape_data(&message)));
+        }
+
+        out
+    }
+
+    /// Appends the markdown report to `$GITHUB_STEP_SUMMARY`. Returns false
+    /// outside Actions, where the variable is not set.
+    pub fn write_step_summary(&self, results: &[ValidationResult]) -> Result<bool> {
+        match std::env::var_os(STEP_SUMMARY_VAR) {
+            Some(path) if !path.is_empty() => {
+                self.append_summary(Path::new(&path), results)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn append_summary(&self, path: &Path, results: &[ValidationResult]) -> Result<()> {
+        let summary = MarkdownRenderer.render(&RenderInput::results(results))?;
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| writeln!(file, "{summary}"))
+            .map_err(|e| DocTreeError::config(format!("Failed to write the step summary {}: {e}", path.display())))
+    }
+}
+
+/// Files proposed as a pull request by `doctreeai pr`.
+#[derive(Debug, Clone)]
+pub struct PullRequestDraft {
+    pub branch: String,
+    /// Target branch; the repository's default branch when None.
+    pub base: Option<String>,
+    pub title: String,
+    pub body: String,
+    /// (path relative to the repository root, new content)
+    pub files: Vec<(String, String)>,
+}
+
+/// Minimal GitHub REST client: just enough to push a branch of file
+/// updates and open a pull request, without a local git checkout.
+pub struct GithubClient {
+    http: reqwest::Client,
+    api_url: String,
+    token: String,
+    /// `owner/name`.
+    repository: String,
+}
+
+impl GithubClient {
+    pub fn new(api_url: &str, token: &str, repository: &str) -> Result<Self> {
+        if token.trim().is_empty() {
+            return Err(DocTreeError::config("A GitHub token is required (set GITHUB_TOKEN or pass --token)"));
+        }
+        let http = reqwest::Client::builder()
+            .user_agent(concat!("doctreeai/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| DocTreeError::github(format!("Failed to create HTTP client: {e}")))?;
+
+        Ok(Self {
+            http,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            token: token.trim().to_string(),
+            repository: repository.to_string(),
+        })
+    }
+
+    /// Creates the branch from the base, commits every file to it, and opens
+    /// the pull request. Returns the pull request's URL.
+    pub async fn open_pull_request(&self, draft: &PullRequestDraft) -> Result<String> {
+        let repo = format!("/repos/{}", self.repository);
+        let base = match &draft.base {
+            Some(base) => base.clone(),
+            None => self.string_field(&self.send(Method::GET, &repo, None).await?, "default_branch")?,
+        };
+
+        let base_ref = self.send(Method::GET, &format!("{repo}/git/ref/heads/{base}"), None).await?;
+        let base_sha = self.string_field(&base_ref["object"], "sha")?;
+        self.send(
+            Method::POST,
+            &format!("{repo}/git/refs"),
+            Some(json!({ "ref": format!("refs/heads/{}", draft.branch), "sha": base_sha })),
+        )
+        .await
+        .map_err(|e| DocTreeError::github(format!("Failed to create branch '{}' ({e}); pass --branch to use another name", draft.branch)))?;
+
+        for (path, content) in &draft.files {
+            let contents_path = format!("{repo}/contents/{path}");
+            let existing = self
+                .send_optional(Method::GET, &format!("{contents_path}?ref={}", draft.branch), None)
+                .await?;
+            let mut update = json!({
+                "message": format!("docs: update {path}"),
+                "content": base64::engine::general_purpose::STANDARD.encode(content),
+                "branch": draft.branch,
+            });
+            if let Some(sha) = existing.as_ref().and_then(|file| file["sha"].as_str()) {
+                update["sha"] = json!(sha);
+            }
+            self.send(Method::PUT, &contents_path, Some(update)).await?;
+        }
+
+        let pull = self
+            .send(
+                Method::POST,
+                &format!("{repo}/pulls"),
+                Some(json!({ "title": draft.title, "head": draft.branch, "base": base, "body": draft.body })),
+            )
+            .await?;
+        self.string_field(&pull, "html_url")
+    }
+
+    async fn send(&self, method: Method, path: &str, body: Option<Value>) -> Result<Value> {
+        self.send_optional(method, path, body)
+            .await?
+            .ok_or_else(|| DocTreeError::github(format!("{path} was not found in {}", self.repository)))
+    }
+
+    /// Like `send`, but a 404 is None instead of an error.
+    async fn send_optional(&self, method: Method, path: &str, body: Option<Value>) -> Result<Option<Value>> {
+        let mut request = self
+            .http
+            .request(method.clone(), format!("{}{path}", self.api_url))
+            .bearer_auth(&self.token)
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28");
+        if let Some(body) = body {
+            request = request.json(&body);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| DocTreeError::github(format!("{method} {path} failed: {e}")))?;
+        let status = response.status();
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let text = response
+            .text()
+            .await
+            .map_err(|e| DocTreeError::github(format!("{method} {path} failed: {e}")))?;
+        if !status.is_success() {
+            let message = serde_json::from_str::<Value>(&text)
+                .ok()
+                .and_then(|error| error["message"].as_str().map(str::to_string))
+                .unwrap_or(text);
+            return Err(DocTreeError::github(format!("{method} {path} returned {status}: {message}")));
+        }
+
+        Ok(Some(serde_json::from_str(&text).unwrap_or(Value::Null)))
+    }
+
+    fn string_field(&self, value: &Value, field: &str) -> Result<String> {
+        value[field]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| DocTreeError::github(format!("Unexpected GitHub response: no '{field}'")))
+    }
+}
+
+/// `owner/name` from a GitHub remote URL (https or ssh), or None for other
+/// hosts.
+pub fn repository_from_remote(url: &str) -> Option<String> {
+    let url = url.trim();
+    let path = url
+        .strip_prefix("git@github.com:")
+        .or_else(|| url.strip_prefix("ssh://git@github.com/"))
+        .or_else(|| url.strip_prefix("https://github.com/"))
+        .or_else(|| url.strip_prefix("http://github.com/"))?;
+    let path = path.trim_end_matches('/').trim_end_matches(".git");
+
+    match path.split('/').collect::<Vec<_>>().as_slice() {
+        [owner, name] if !owner.is_empty() && !name.is_empty() => Some(format!("{owner}/{name}")),
+        _ => None,
+    }
+}
+
+/// Escapes an annotation message, which ends at the first newline.
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Escapes an annotation property, where `:` and `,` are separators too.
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_annotations_and_step_summary() -> Result<()> {
+        let result = |line_number: usize, severity: Severity| ValidationResult {
+            line_number,
+            current_content: "Uses SQLite".to_string(),
+            suggested_content: "Uses Postgres, 100% of the time".to_string(),
+            reason: "Storage changed".to_string(),
+            affected_cache_entries: vec![],
+            section: "Storage: Design".to_string(),
+            confidence: 0.8,
+            severity,
+        };
+        let results = vec![result(3, Severity::Outdated), result(0, Severity::Cosmetic)];
+        let github = GithubActions::new(Path::new("docs/README.md"));
+
+        let annotations = github.annotations(&results);
+        let lines: Vec<&str> = annotations.lines().collect();
+        assert_eq!(
+            lines[0],
+            "::warning file=docs/README.md,line=3,endLine=3,title=README drift in Storage%3A Design::\
+             Storage changed (confidence 0.80)%0ASuggested: Uses Postgres, 100%25 of the time"
+        );
+        assert!(lines[1].starts_with("::notice file=docs/README.md,title="));
+
+        let temp_dir = TempDir::new()?;
+        let summary_path = temp_dir.path().join("summary.md");
+        github.append_summary(&summary_path, &results)?;
+        github.append_summary(&summary_path, &results)?;
+        let summary = std::fs::read_to_string(&summary_path)?;
+        assert_eq!(summary.matches("## README.md Validation Results").count(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_repository_from_remote() {
+        let expected = Some("kstonekuan/docs-tree-ai".to_string());
+        assert_eq!(repository_from_remote("https://github.com/kstonekuan/docs-tree-ai.git"), expected);
+        assert_eq!(repository_from_remote("git@github.com:kstonekuan/docs-tree-ai.git\n"), expected);
+        assert_eq!(repository_from_remote("ssh://git@github.com/kstonekuan/docs-tree-ai"), expected);
+        assert_eq!(repository_from_remote("https://gitlab.com/kstonekuan/docs-tree-ai.git"), None);
+        assert_eq!(repository_from_remote("https://github.com/kstonekuan"), None);
+        assert!(GithubClient::new(DEFAULT_API_URL, " ", "kstonekuan/docs-tree-ai").is_err());
+    }
+}
@@ -0,0 +1,439 @@
+use crate::scanner::FileNode;
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Files named per direction in a prompt before the rest are counted.
+const MAX_PROMPT_FILES: usize = 10;
+
+/// Extensions a JavaScript or TypeScript import may leave out.
+const SCRIPT_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs"];
+
+/// Which project files import which, from the `use`, `import`, and
+/// `require` statements of Rust, Python, JavaScript, TypeScript, and Go
+/// files. Imports of external packages are left out. Paths are relative to
+/// the project root.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DependencyGraph {
+    dependencies: BTreeMap<PathBuf, BTreeSet<PathBuf>>,
+}
+
+impl DependencyGraph {
+    /// Reads every file of a scanned tree and resolves its imports against
+    /// the other files in it.
+    pub fn from_tree(base_path: &Path, root: &FileNode) -> Self {
+        let mut files = BTreeSet::new();
+        collect_files(root, base_path, &mut files);
+        Self::from_files(base_path, &files)
+    }
+
+    /// Reads the given files (relative to the project root) and resolves
+    /// their imports against each other.
+    pub fn from_files(base_path: &Path, files: &BTreeSet<PathBuf>) -> Self {
+        let go_module = fs::read_to_string(base_path.join("go.mod")).ok().and_then(|content| {
+            content
+                .lines()
+                .find_map(|line| line.trim().strip_prefix("module "))
+                .map(|module| module.trim().to_string())
+        });
+
+        let mut dependencies = BTreeMap::new();
+        for file in files {
+            let Ok(content) = fs::read_to_string(base_path.join(file)) else {
+                continue;
+            };
+            let resolved: BTreeSet<PathBuf> = match file.extension().and_then(|ext| ext.to_str()) {
+                Some("rs") => rust_imports(&content)
+                    .iter()
+                    .filter_map(|import| resolve_rust(file, import, files))
+                    .collect(),
+                Some("py") => python_imports(&content)
+                    .iter()
+                    .filter_map(|candidates| candidates.iter().find_map(|import| resolve_python(file, import, files)))
+                    .collect(),
+                Some(ext) if SCRIPT_EXTENSIONS.contains(&ext) => script_imports(&content)
+                    .iter()
+                    .filter_map(|import| resolve_script(file, import, files))
+                    .collect(),
+                Some("go") => go_imports(&content)
+                    .iter()
+                    .flat_map(|import| resolve_go(import, go_module.as_deref(), files))
+                    .collect(),
+                _ => continue,
+            };
+            let resolved: BTreeSet<PathBuf> = resolved.into_iter().filter(|dependency| dependency != file).collect();
+            if !resolved.is_empty() {
+                dependencies.insert(file.clone(), resolved);
+            }
+        }
+        Self { dependencies }
+    }
+
+    /// Project files `path` imports.
+    pub fn dependencies_of(&self, path: &Path) -> Vec<&Path> {
+        self.dependencies
+            .get(path)
+            .into_iter()
+            .flatten()
+            .map(PathBuf::as_path)
+            .collect()
+    }
+
+    /// Project files that import `path`.
+    pub fn dependents_of(&self, path: &Path) -> Vec<&Path> {
+        self.dependencies
+            .iter()
+            .filter(|(_, dependencies)| dependencies.contains(path))
+            .map(|(file, _)| file.as_path())
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dependencies.is_empty()
+    }
+
+    /// Files with their dependencies, sorted by path.
+    pub fn edges(&self) -> impl Iterator<Item = (&Path, Vec<&Path>)> {
+        self.dependencies
+            .iter()
+            .map(|(file, dependencies)| (file.as_path(), dependencies.iter().map(PathBuf::as_path).collect()))
+    }
+
+    /// "Depends on" and "Used by" lines for a file summary prompt, or None
+    /// when the file has neither.
+    pub fn prompt_context(&self, path: &Path) -> Option<String> {
+        let list = |paths: Vec<&Path>| {
+            let mut listed: Vec<String> =
+                paths.iter().take(MAX_PROMPT_FILES).map(|path| path.display().to_string()).collect();
+            if paths.len() > MAX_PROMPT_FILES {
+                listed.push(format!("and {} more", paths.len() - MAX_PROMPT_FILES));
+            }
+            listed.join(", ")
+        };
+
+        let mut lines = Vec::new();
+        let dependencies = self.dependencies_of(path);
+        if !dependencies.is_empty() {
+            lines.push(format!("Depends on: {}", list(dependencies)));
+        }
+        let dependents = self.dependents_of(path);
+        if !dependents.is_empty() {
+            lines.push(format!("Used by: {}", list(dependents)));
+        }
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    /// The graph in Graphviz DOT, one edge per import.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph dependencies {\n    rankdir=LR;\n    node [shape=box];\n");
+        for (file, dependencies) in &self.dependencies {
+            for dependency in dependencies {
+                dot.push_str(&format!("    \"{}\" -> \"{}\";\n", dot_path(file), dot_path(dependency)));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn dot_path(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/").replace('"', "\\\"")
+}
+
+fn collect_files(node: &FileNode, base_path: &Path, files: &mut BTreeSet<PathBuf>) {
+    if node.submodule.is_some() {
+        return;
+    }
+    if node.is_directory {
+        for child in &node.children {
+            collect_files(child, base_path, files);
+        }
+    } else if let Ok(relative_path) = node.path.strip_prefix(base_path) {
+        files.insert(relative_path.to_path_buf());
+    }
+}
+
+/// `use` paths, with one level of `{a, b::c}` groups expanded.
+fn rust_imports(content: &str) -> Vec<String> {
+    let statement = Regex::new(r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?use\s+([^;]+);").expect("valid regex");
+    let rename = Regex::new(r"\s+as\s+\w+").expect("valid regex");
+    let mut imports = Vec::new();
+    for captures in statement.captures_iter(content) {
+        let path: String = rename.replace_all(&captures[1], "").split_whitespace().collect();
+        match path.split_once("::{") {
+            Some((prefix, group)) => {
+                let group = group.strip_suffix('}').unwrap_or(group);
+                let mut depth = 0;
+                let mut item = String::new();
+                for c in group.chars().chain([',']) {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        ',' if depth == 0 => {
+                            let name = item.split("::{").next().unwrap_or_default();
+                            if !name.is_empty() && name != "self" {
+                                imports.push(format!("{prefix}::{name}"));
+                            } else if name == "self" {
+                                imports.push(prefix.to_string());
+                            }
+                            item.clear();
+                            continue;
+                        }
+                        _ => {}
+                    }
+                    item.push(c);
+                }
+            }
+            None => imports.push(path),
+        }
+    }
+    imports
+}
+
+/// The crate's `src/` directory and the module path of a Rust file in it.
+fn rust_module(file: &Path) -> Option<(PathBuf, Vec<String>)> {
+    let components: Vec<&str> = file.iter().filter_map(|component| component.to_str()).collect();
+    let src = components.iter().rposition(|component| *component == "src")?;
+    let source_root: PathBuf = components[..=src].iter().collect();
+
+    let mut segments: Vec<String> = components[src + 1..].iter().map(|component| component.to_string()).collect();
+    let file_name = segments.pop()?;
+    let stem = file_name.trim_end_matches(".rs");
+    if !matches!(stem, "lib" | "main" | "mod") {
+        segments.push(stem.to_string());
+    }
+    Some((source_root, segments))
+}
+
+/// The file of the longest module prefix of a `crate::`, `self::`, or
+/// `super::` path.
+fn resolve_rust(file: &Path, import: &str, files: &BTreeSet<PathBuf>) -> Option<PathBuf> {
+    let (source_root, mut module) = rust_module(file)?;
+    let mut segments = import.split("::").peekable();
+    match segments.peek() {
+        Some(&"crate") => {
+            module.clear();
+            segments.next();
+        }
+        Some(&"self") => {
+            segments.next();
+        }
+        Some(&"super") => {
+            while segments.peek() == Some(&"super") {
+                module.pop();
+                segments.next();
+            }
+        }
+        _ => return None,
+    }
+    module.extend(segments.map(str::to_string));
+
+    (1..=module.len()).rev().find_map(|length| {
+        let directory: PathBuf = source_root.join(module[..length - 1].iter().collect::<PathBuf>());
+        [directory.join(format!("{}.rs", module[length - 1])), directory.join(&module[length - 1]).join("mod.rs")]
+            .into_iter()
+            .find(|candidate| files.contains(candidate))
+    })
+}
+
+/// Imported modules, each with the candidates to try in order: for
+/// `from pkg import name`, `pkg.name` may be a module or a name in `pkg`.
+fn python_imports(content: &str) -> Vec<Vec<String>> {
+    let mut imports = Vec::new();
+    for line in content.lines().map(str::trim) {
+        if let Some(modules) = line.strip_prefix("import ") {
+            for module in modules.split(',') {
+                let module = module.split_whitespace().next().unwrap_or_default();
+                if !module.is_empty() {
+                    imports.push(vec![module.to_string()]);
+                }
+            }
+        } else if let Some((module, names)) = line.strip_prefix("from ").and_then(|rest| rest.split_once(" import ")) {
+            let module = module.trim();
+            for name in names.trim_matches(['(', ')', ' ']).split(',') {
+                let name = name.split_whitespace().next().unwrap_or_default();
+                if name.is_empty() || name == "*" {
+                    continue;
+                }
+                let separator = if module.ends_with('.') { "" } else { "." };
+                imports.push(vec![format!("{module}{separator}{name}"), module.to_string()]);
+            }
+        }
+    }
+    imports
+}
+
+fn resolve_python(file: &Path, import: &str, files: &BTreeSet<PathBuf>) -> Option<PathBuf> {
+    let dots = import.chars().take_while(|c| *c == '.').count();
+    let parts: PathBuf = import[dots..].split('.').filter(|part| !part.is_empty()).collect();
+
+    let roots: Vec<PathBuf> = if dots > 0 {
+        let mut directory = file.parent()?.to_path_buf();
+        for _ in 1..dots {
+            directory = directory.parent()?.to_path_buf();
+        }
+        vec![directory]
+    } else {
+        // Absolute imports start at the project root or a src/ layout
+        vec![PathBuf::new(), PathBuf::from("src")]
+    };
+
+    roots.iter().find_map(|root| {
+        let module = root.join(&parts);
+        [module.with_extension("py"), module.join("__init__.py")]
+            .into_iter()
+            .find(|candidate| !parts.as_os_str().is_empty() && files.contains(candidate))
+            .or_else(|| {
+                // `from . import x` where x is a name in the package itself
+                let init = module.join("__init__.py");
+                (parts.as_os_str().is_empty() && files.contains(&init)).then_some(init)
+            })
+    })
+}
+
+fn script_imports(content: &str) -> Vec<String> {
+    let import = Regex::new(r#"(?:\bfrom|\bimport|\brequire\s*\()\s*\(?\s*['"]([^'"]+)['"]"#).expect("valid regex");
+    import.captures_iter(content).map(|captures| captures[1].to_string()).collect()
+}
+
+/// Relative specifiers only; bare ones name packages.
+fn resolve_script(file: &Path, import: &str, files: &BTreeSet<PathBuf>) -> Option<PathBuf> {
+    if !import.starts_with('.') {
+        return None;
+    }
+    let target = normalize(&file.parent()?.join(import))?;
+    let with_extensions = |path: &Path| -> Vec<PathBuf> {
+        SCRIPT_EXTENSIONS
+            .iter()
+            .map(|ext| {
+                let mut name = path.as_os_str().to_os_string();
+                name.push(format!(".{ext}"));
+                PathBuf::from(name)
+            })
+            .collect()
+    };
+
+    std::iter::once(target.clone())
+        .chain(with_extensions(&target))
+        .chain(with_extensions(&target.join("index")))
+        .find(|candidate| files.contains(candidate))
+}
+
+fn go_imports(content: &str) -> Vec<String> {
+    let mut imports = Vec::new();
+    let mut in_block = false;
+    for line in content.lines().map(str::trim) {
+        let import = if in_block {
+            if line.starts_with(')') {
+                in_block = false;
+                continue;
+            }
+            line
+        } else if line == "import (" {
+            in_block = true;
+            continue;
+        } else if let Some(import) = line.strip_prefix("import ") {
+            import
+        } else {
+            continue;
+        };
+        if let Some(path) = import.split('"').nth(1) {
+            imports.push(path.to_string());
+        }
+    }
+    imports
+}
+
+/// The non-test Go files of a package in this module.
+fn resolve_go(import: &str, module: Option<&str>, files: &BTreeSet<PathBuf>) -> Vec<PathBuf> {
+    let Some(package) = module.and_then(|module| import.strip_prefix(module)) else {
+        return Vec::new();
+    };
+    let directory = PathBuf::from(package.trim_start_matches('/'));
+    files
+        .iter()
+        .filter(|file| file.parent() == Some(directory.as_path()))
+        .filter(|file| {
+            let name = file.to_string_lossy();
+            name.ends_with(".go") && !name.ends_with("_test.go")
+        })
+        .cloned()
+        .collect()
+}
+
+/// Resolves `.` and `..` without touching the filesystem; None when the
+/// path leaves the project root.
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    return None;
+                }
+            }
+            component => normalized.push(component),
+        }
+    }
+    Some(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+    use crate::scanner::DirectoryScanner;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_dependency_graph() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        for (path, content) in [
+            ("src/main.rs", "use crate::cache::{Entry, store::{self, Store}};\nuse std::fs;\nmod cache;\n"),
+            ("src/cache/mod.rs", "pub mod store;\npub struct Entry;\n"),
+            ("src/cache/store.rs", "use super::Entry;\n"),
+            ("app/cli.py", "from . import util\nfrom .models import User\nimport requests\n"),
+            ("app/util.py", "import app.models\n"),
+            ("app/models.py", ""),
+            ("web/index.ts", "import { api } from './api';\nconst x = require('../web/lib');\nimport React from 'react';\n"),
+            ("web/api.ts", ""),
+            ("web/lib/index.js", ""),
+        ] {
+            fs::create_dir_all(base_path.join(path).parent().unwrap())?;
+            fs::write(base_path.join(path), content)?;
+        }
+
+        let root = DirectoryScanner::new(base_path.to_path_buf()).scan_directory()?;
+        let graph = DependencyGraph::from_tree(base_path, &root);
+        let edges: Vec<(String, Vec<String>)> = graph
+            .edges()
+            .map(|(file, dependencies)| {
+                let dependencies = dependencies.iter().map(|path| path.display().to_string()).collect();
+                (file.display().to_string(), dependencies)
+            })
+            .collect();
+        let edge = |file: &str, dependencies: &[&str]| {
+            (file.to_string(), dependencies.iter().map(|path| path.to_string()).collect::<Vec<_>>())
+        };
+        assert_eq!(
+            edges,
+            vec![
+                edge("app/cli.py", &["app/models.py", "app/util.py"]),
+                edge("app/util.py", &["app/models.py"]),
+                edge("src/cache/store.rs", &["src/cache/mod.rs"]),
+                edge("src/main.rs", &["src/cache/mod.rs", "src/cache/store.rs"]),
+                edge("web/index.ts", &["web/api.ts", "web/lib/index.js"]),
+            ]
+        );
+
+        assert_eq!(
+            graph.prompt_context(Path::new("src/cache/mod.rs")).as_deref(),
+            Some("Used by: src/cache/store.rs, src/main.rs")
+        );
+        assert!(graph.to_dot().contains("    \"app/util.py\" -> \"app/models.py\";\n"));
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use crate::error::{DocTreeError, Result};
+use crate::outline::normalized_source;
 use sha2::{Digest, Sha256};
 use std::fs::File;
 use std::io::{BufReader, Read};
@@ -8,7 +9,7 @@ pub struct FileHasher;
 
 impl FileHasher {
     pub fn compute_file_hash(file_path: &Path) -> Result<String> {
-        log::debug!("Computing hash for file: {}", file_path.display());
+        tracing::debug!("Computing hash for file: {}", file_path.display());
         
         let file = File::open(file_path)
             .map_err(DocTreeError::Io)?;
@@ -28,11 +29,24 @@ impl FileHasher {
         let hash = hasher.finalize();
         let hash_string = format!("{hash:x}");
         
-        log::debug!("Hash computed: {} -> {}", file_path.display(), &hash_string[..8]);
+        tracing::debug!("Hash computed: {} -> {}", file_path.display(), &hash_string[..8]);
         
         Ok(hash_string)
     }
 
+    /// Hashes a source file, or with `normalized` its formatting- and
+    /// comment-insensitive form, so reformatting it keeps the hash. Files
+    /// that are not UTF-8 are hashed as they are.
+    pub fn compute_source_hash(file_path: &Path, normalized: bool) -> Result<String> {
+        if !normalized {
+            return Self::compute_file_hash(file_path);
+        }
+        match String::from_utf8(std::fs::read(file_path)?) {
+            Ok(content) => Ok(Self::compute_content_hash(&normalized_source(file_path, &content))),
+            Err(_) => Self::compute_file_hash(file_path),
+        }
+    }
+
     pub fn compute_content_hash(content: &str) -> String {
         let mut hasher = Sha256::new();
         hasher.update(content.as_bytes());
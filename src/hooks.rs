@@ -0,0 +1,152 @@
+use crate::error::{DocTreeError, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Second line of every hook doctreeai writes, so reinstalling can replace
+/// its own hooks without touching hand-written ones.
+const HOOK_MARKER: &str = "# Installed by doctreeai hooks install";
+
+/// A git hook that checks README.md against the code being committed or
+/// pushed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitHook {
+    /// Checks the staged files.
+    PreCommit,
+    /// Checks the commits not yet on the upstream branch.
+    PrePush,
+}
+
+impl GitHook {
+    pub fn file_name(self) -> &'static str {
+        match self {
+            GitHook::PreCommit => "pre-commit",
+            GitHook::PrePush => "pre-push",
+        }
+    }
+
+    /// The hook script: runs `doctreeai hooks check` on the project, from
+    /// the repository root where git runs hooks.
+    pub fn script(self, project: &Path, block: bool) -> String {
+        let project = match project.to_string_lossy().replace('\\', "/") {
+            path if path.is_empty() => ".".to_string(),
+            path => path,
+        };
+        let block = if block { " --block" } else { "" };
+        format!(
+            "#!/bin/sh\n{HOOK_MARKER}\n\
+             # Warns when code that README.md describes changes without a README update.\n\
+             exec doctreeai hooks check --{} --path '{project}'{block}\n",
+            self.file_name()
+        )
+    }
+
+    /// Files changed by the commit or push being checked, relative to
+    /// `path`. Files outside `path` are left out.
+    pub fn changed_files(self, path: &Path) -> Result<Vec<PathBuf>> {
+        let range: &[&str] = match self {
+            GitHook::PreCommit => &["--cached"],
+            GitHook::PrePush => &["@{upstream}...HEAD"],
+        };
+        let output = git(path, &[&["diff", "--name-only", "--relative"], range].concat())?;
+        Ok(output.lines().filter(|line| !line.is_empty()).map(PathBuf::from).collect())
+    }
+}
+
+/// Writes doctreeai's git hooks into a repository.
+pub struct HookInstaller {
+    hooks_dir: PathBuf,
+    /// The project relative to the repository root.
+    project: PathBuf,
+}
+
+impl HookInstaller {
+    /// Finds the hooks directory (honoring `core.hooksPath`) of the
+    /// repository containing `path`.
+    pub fn for_project(path: &Path) -> Result<Self> {
+        let root = PathBuf::from(git(path, &["rev-parse", "--show-toplevel"])?);
+        let hooks_dir = PathBuf::from(git(path, &["rev-parse", "--git-path", "hooks"])?);
+        let hooks_dir = if hooks_dir.is_absolute() { hooks_dir } else { path.join(hooks_dir) };
+        let project = fs::canonicalize(path)?
+            .strip_prefix(fs::canonicalize(&root)?)
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+
+        Ok(Self { hooks_dir, project })
+    }
+
+    /// Writes the hook and returns its path. A hook that doctreeai did not
+    /// write is only replaced with `force`.
+    pub fn install(&self, hook: GitHook, block: bool, force: bool) -> Result<PathBuf> {
+        let hook_path = self.hooks_dir.join(hook.file_name());
+        if let Ok(existing) = fs::read_to_string(&hook_path) {
+            if !existing.contains(HOOK_MARKER) && !force {
+                return Err(DocTreeError::config(format!(
+                    "{} already exists and was not written by doctreeai; pass --force to replace it",
+                    hook_path.display()
+                )));
+            }
+        }
+
+        fs::create_dir_all(&self.hooks_dir)?;
+        fs::write(&hook_path, hook.script(&self.project, block))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&hook_path, fs::Permissions::from_mode(0o755))?;
+        }
+        Ok(hook_path)
+    }
+}
+
+fn git(path: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(args)
+        .output()
+        .map_err(|e| DocTreeError::config(format!("Failed to run git: {e}")))?;
+    if !output.status.success() {
+        return Err(DocTreeError::config(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_install_hooks_and_list_staged_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let repo = temp_dir.path();
+        if git(repo, &["init", "-q"]).is_err() {
+            eprintln!("Skipping: git is not available");
+            return Ok(());
+        }
+        let project = repo.join("app");
+        fs::create_dir_all(project.join("src"))?;
+        fs::write(project.join("src/lib.rs"), "pub fn run() {}")?;
+        fs::write(repo.join("notes.txt"), "outside the project")?;
+        git(repo, &["add", "."])?;
+
+        let installer = HookInstaller::for_project(&project)?;
+        let hook_path = installer.install(GitHook::PreCommit, true, false)?;
+        let script = fs::read_to_string(&hook_path)?;
+        assert!(script.ends_with("exec doctreeai hooks check --pre-commit --path 'app' --block\n"));
+
+        // Reinstalling replaces our own hook, but not someone else's
+        installer.install(GitHook::PreCommit, false, false)?;
+        fs::write(&hook_path, "#!/bin/sh\nmake lint\n")?;
+        assert!(installer.install(GitHook::PreCommit, false, false).is_err());
+        installer.install(GitHook::PreCommit, false, true)?;
+
+        assert_eq!(GitHook::PreCommit.changed_files(&project)?, vec![PathBuf::from("src/lib.rs")]);
+        Ok(())
+    }
+}
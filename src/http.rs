@@ -0,0 +1,108 @@
+use crate::config::Config;
+use crate::error::{DocTreeError, Result};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use std::fmt;
+use std::fs;
+
+/// Extra headers sent with every LLM request, e.g. for LiteLLM or Helicone.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HttpHeaders {
+    headers: Vec<(String, String)>,
+}
+
+impl HttpHeaders {
+    /// Parses `Name: value` headers separated by semicolons, e.g.
+    /// `Helicone-Auth: Bearer sk-...;X-Team: docs`.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut headers = Vec::new();
+
+        for entry in spec.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+            let (name, value) = entry.split_once(':').ok_or_else(|| {
+                DocTreeError::config(format!("Invalid HTTP header '{entry}', expected Name: value"))
+            })?;
+            let (name, value) = (name.trim(), value.trim());
+            HeaderName::try_from(name)
+                .map_err(|_| DocTreeError::config(format!("Invalid HTTP header name '{name}'")))?;
+            HeaderValue::try_from(value)
+                .map_err(|_| DocTreeError::config(format!("Invalid value for HTTP header '{name}'")))?;
+            headers.push((name.to_string(), value.to_string()));
+        }
+
+        Ok(Self { headers })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+    }
+
+    fn header_map(&self) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (name, value) in &self.headers {
+            // Both were checked when parsing
+            if let (Ok(name), Ok(value)) = (HeaderName::try_from(name.as_str()), HeaderValue::try_from(value.as_str())) {
+                map.append(name, value);
+            }
+        }
+        map
+    }
+}
+
+/// Values are hidden, since headers like `Helicone-Auth` carry credentials.
+impl fmt::Display for HttpHeaders {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let headers: Vec<String> = self.headers.iter().map(|(name, _)| format!("{name}: ********")).collect();
+        write!(f, "{}", headers.join(";"))
+    }
+}
+
+/// The HTTP client for LLM requests: extra headers, proxy, and TLS options
+/// from the config. Without `DOCTREEAI_HTTP_PROXY`, the standard
+/// `HTTPS_PROXY`, `HTTP_PROXY`, and `NO_PROXY` variables apply.
+pub fn llm_http_client(config: &Config) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .user_agent(concat!("doctreeai/", env!("CARGO_PKG_VERSION")))
+        .default_headers(config.http_headers.header_map());
+
+    if let Some(proxy) = &config.http_proxy {
+        let proxy = reqwest::Proxy::all(proxy)
+            .map_err(|e| DocTreeError::config(format!("Invalid DOCTREEAI_HTTP_PROXY '{proxy}': {e}")))?;
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(ca_cert) = &config.ca_cert {
+        let pem = fs::read(ca_cert).map_err(|e| {
+            DocTreeError::config(format!("Failed to read DOCTREEAI_CA_CERT {}: {e}", ca_cert.display()))
+        })?;
+        let certificates = reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| {
+            DocTreeError::config(format!("Invalid certificate in DOCTREEAI_CA_CERT {}: {e}", ca_cert.display()))
+        })?;
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    if config.insecure_tls {
+        tracing::warn!("DOCTREEAI_INSECURE_TLS is set: LLM server certificates are not verified");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+
+    builder
+        .build()
+        .map_err(|e| DocTreeError::config(format!("Failed to create HTTP client: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headers_parse_and_hide_their_values() -> Result<()> {
+        let headers = HttpHeaders::parse("Helicone-Auth: Bearer sk-123; X-Team:docs;")?;
+        assert_eq!(headers.to_string(), "Helicone-Auth: ********;X-Team: ********");
+        assert_eq!(headers.header_map()["x-team"], "docs");
+
+        assert!(HttpHeaders::parse("X-Team").is_err());
+        assert!(HttpHeaders::parse("Bad Name: value").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+use std::fs;
+use std::path::Path;
+
+/// A way to install or run the project, detected from a manifest in the
+/// project root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstallMethod {
+    /// Tool the steps are for, e.g. "Cargo" or "Docker".
+    pub tool: String,
+    /// Shell commands, in order.
+    pub commands: Vec<String>,
+}
+
+/// Installation steps for every manifest in the project root: Cargo.toml,
+/// package.json, pyproject.toml, go.mod, and Dockerfile. Empty when there
+/// are none.
+pub fn detect_install_methods(base_path: &Path) -> Vec<InstallMethod> {
+    // Docker image names are lowercase and start with a letter or digit
+    let project_name: String = base_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default()
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        .skip_while(|c| !c.is_ascii_alphanumeric())
+        .collect();
+    let project_name = if project_name.is_empty() { "app".to_string() } else { project_name };
+    let read = |name: &str| fs::read_to_string(base_path.join(name)).ok();
+
+    let mut methods = Vec::new();
+    if let Some(commands) = read("Cargo.toml").and_then(|content| cargo_commands(base_path, &content)) {
+        methods.push(InstallMethod { tool: "Cargo".to_string(), commands });
+    }
+    if let Some(commands) = read("package.json").and_then(|content| npm_commands(base_path, &content)) {
+        methods.push(InstallMethod { tool: "npm".to_string(), commands });
+    }
+    if let Some(commands) = read("pyproject.toml").and_then(|content| python_commands(&content)) {
+        methods.push(InstallMethod { tool: "pip".to_string(), commands });
+    }
+    if let Some(commands) = read("go.mod").and_then(|content| go_commands(base_path, &content)) {
+        methods.push(InstallMethod { tool: "Go".to_string(), commands });
+    }
+    if base_path.join("Dockerfile").is_file() {
+        methods.push(InstallMethod {
+            tool: "Docker".to_string(),
+            commands: vec![
+                format!("docker build -t {project_name} ."),
+                format!("docker run --rm {project_name}"),
+            ],
+        });
+    }
+    methods
+}
+
+/// `cargo install` for binaries, `cargo add` for libraries, and a release
+/// build for virtual workspaces.
+fn cargo_commands(base_path: &Path, content: &str) -> Option<Vec<String>> {
+    let manifest = content.parse::<toml::Table>().ok()?;
+    let Some(package) = manifest.get("package") else {
+        return manifest.contains_key("workspace").then(|| vec!["cargo build --release".to_string()]);
+    };
+    let name = package.get("name")?.as_str()?;
+
+    let binaries: Vec<&str> = manifest
+        .get("bin")
+        .and_then(|bins| bins.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|bin| bin.get("name")?.as_str())
+        .collect();
+    if base_path.join("src/main.rs").is_file() || !binaries.is_empty() {
+        let binary = binaries.first().copied().unwrap_or(name);
+        Some(vec!["cargo install --path .".to_string(), format!("{binary} --help")])
+    } else {
+        Some(vec![format!("cargo add {name}")])
+    }
+}
+
+/// Install with the package manager whose lockfile is present, then the
+/// build and start scripts the package defines.
+fn npm_commands(base_path: &Path, content: &str) -> Option<Vec<String>> {
+    let package: serde_json::Value = serde_json::from_str(content).ok()?;
+    let manager = [("pnpm-lock.yaml", "pnpm"), ("yarn.lock", "yarn"), ("bun.lockb", "bun")]
+        .iter()
+        .find(|(lockfile, _)| base_path.join(lockfile).is_file())
+        .map(|(_, manager)| *manager)
+        .unwrap_or("npm");
+
+    let mut commands = vec![format!("{manager} install")];
+    for script in ["build", "start"] {
+        if package["scripts"].get(script).is_some() {
+            commands.push(format!("{manager} run {script}"));
+        }
+    }
+    Some(commands)
+}
+
+/// `pip install .`, then one of the console scripts it provides.
+fn python_commands(content: &str) -> Option<Vec<String>> {
+    let project = content.parse::<toml::Table>().ok()?;
+    let mut commands = vec!["pip install .".to_string()];
+    let script = project
+        .get("project")
+        .and_then(|project| project.get("scripts")?.as_table()?.keys().next().cloned())
+        .or_else(|| {
+            project
+                .get("tool")?
+                .get("poetry")?
+                .get("scripts")?
+                .as_table()?
+                .keys()
+                .next()
+                .cloned()
+        });
+    if let Some(script) = script {
+        commands.push(format!("{script} --help"));
+    }
+    Some(commands)
+}
+
+/// `go install` for a main package at the root or under cmd/, otherwise
+/// `go get` for a library.
+fn go_commands(base_path: &Path, content: &str) -> Option<Vec<String>> {
+    let module = content
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("module "))?
+        .trim()
+        .to_string();
+
+    let root_is_main = fs::read_dir(base_path)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "go"))
+        .any(|entry| {
+            fs::read_to_string(entry.path()).is_ok_and(|source| source.lines().any(|line| line.trim() == "package main"))
+        });
+    if root_is_main {
+        return Some(vec![format!("go install {module}@latest")]);
+    }
+    if base_path.join("cmd").is_dir() {
+        return Some(vec![format!("go install {module}/cmd/...@latest")]);
+    }
+    Some(vec![format!("go get {module}")])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_install_methods() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        assert!(detect_install_methods(base_path).is_empty());
+
+        fs::write(base_path.join("Cargo.toml"), "[package]\nname = \"mytool\"\n")?;
+        fs::create_dir(base_path.join("src"))?;
+        fs::write(base_path.join("src/main.rs"), "fn main() {}\n")?;
+        fs::write(base_path.join("package.json"), r#"{"scripts": {"start": "node index.js"}}"#)?;
+        fs::write(base_path.join("yarn.lock"), "")?;
+        fs::write(base_path.join("go.mod"), "module example.com/lib\n\ngo 1.22\n")?;
+        fs::write(base_path.join("Dockerfile"), "FROM scratch\n")?;
+
+        let methods = detect_install_methods(base_path);
+        let tools: Vec<&str> = methods.iter().map(|method| method.tool.as_str()).collect();
+        assert_eq!(tools, vec!["Cargo", "npm", "Go", "Docker"]);
+        assert_eq!(methods[0].commands, vec!["cargo install --path .", "mytool --help"]);
+        assert_eq!(methods[1].commands, vec!["yarn install", "yarn run start"]);
+        assert_eq!(methods[2].commands, vec!["go get example.com/lib"]);
+        assert!(methods[3].commands[0].starts_with("docker build -t "));
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+use crate::error::{DocTreeError, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// Service name the keys are stored under in the OS keychain.
+const SERVICE: &str = "doctreeai";
+
+/// A secret that can be kept in the OS keychain instead of the environment
+/// or a `.env` file. The environment variable still wins when set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoredKey {
+    OpenAi,
+    Github,
+}
+
+impl StoredKey {
+    /// The environment variable the key stands in for, also its keychain
+    /// account name.
+    pub fn variable(self) -> &'static str {
+        match self {
+            StoredKey::OpenAi => "OPENAI_API_KEY",
+            StoredKey::Github => "GITHUB_TOKEN",
+        }
+    }
+
+    /// The stored key, or None when there is none or the keychain cannot
+    /// be reached (e.g. no secret service on a headless machine).
+    pub fn load(self) -> Option<String> {
+        match self.with_entry(|entry| entry.get_password()) {
+            Ok(secret) => Some(secret),
+            Err(error) => {
+                tracing::debug!("No {} in the keychain: {error}", self.variable());
+                None
+            }
+        }
+    }
+
+    pub fn store(self, secret: &str) -> Result<()> {
+        self.with_entry(|entry| entry.set_password(secret))
+            .map_err(|e| DocTreeError::config(format!("Failed to store {} in the keychain: {e}", self.variable())))
+    }
+
+    /// Removes the stored key. Returns false when there was none.
+    pub fn delete(self) -> Result<bool> {
+        match self.with_entry(|entry| entry.delete_credential()) {
+            Ok(()) => Ok(true),
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(e) => Err(DocTreeError::config(format!("Failed to delete {} from the keychain: {e}", self.variable()))),
+        }
+    }
+
+    /// Runs a keychain call on a thread of its own: the Linux secret
+    /// service client deadlocks when called from a tokio worker.
+    fn with_entry<T: Send>(
+        self,
+        call: impl FnOnce(&keyring::Entry) -> keyring::Result<T> + Send,
+    ) -> keyring::Result<T> {
+        std::thread::scope(|scope| {
+            scope
+                .spawn(|| call(&keyring::Entry::new(SERVICE, self.variable())?))
+                .join()
+                .unwrap_or_else(|_| Err(keyring::Error::PlatformFailure("keychain thread panicked".into())))
+        })
+    }
+}
+
+impl FromStr for StoredKey {
+    type Err = DocTreeError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "openai" | "openai_api_key" => Ok(StoredKey::OpenAi),
+            "github" | "github_token" => Ok(StoredKey::Github),
+            other => Err(DocTreeError::config(format!(
+                "Unknown key '{other}' (expected openai or github)"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for StoredKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            StoredKey::OpenAi => "openai",
+            StoredKey::Github => "github",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stored_keys_parse_by_provider_or_variable() -> Result<()> {
+        assert_eq!("openai".parse::<StoredKey>()?, StoredKey::OpenAi);
+        assert_eq!("GITHUB_TOKEN".parse::<StoredKey>()?.variable(), "GITHUB_TOKEN");
+        assert_eq!(StoredKey::Github.to_string().parse::<StoredKey>()?, StoredKey::Github);
+        assert!("anthropic".parse::<StoredKey>().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+use crate::classifier::ChildImportance;
+use crate::error::{DocTreeError, Result};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A source language with its own file summary instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Go,
+    Java,
+    Ruby,
+    Shell,
+    Markdown,
+}
+
+impl Language {
+    pub const ALL: [Language; 9] = [
+        Language::Rust,
+        Language::Python,
+        Language::JavaScript,
+        Language::TypeScript,
+        Language::Go,
+        Language::Java,
+        Language::Ruby,
+        Language::Shell,
+        Language::Markdown,
+    ];
+
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_lowercase().as_str() {
+            "rs" => Some(Language::Rust),
+            "py" | "pyi" => Some(Language::Python),
+            "js" | "jsx" | "mjs" | "cjs" => Some(Language::JavaScript),
+            "ts" | "tsx" | "mts" | "cts" => Some(Language::TypeScript),
+            "go" => Some(Language::Go),
+            "java" | "kt" => Some(Language::Java),
+            "rb" => Some(Language::Ruby),
+            "sh" | "bash" | "zsh" => Some(Language::Shell),
+            "md" | "mdx" => Some(Language::Markdown),
+            _ => None,
+        }
+    }
+
+    /// What the summary of a file in this language should concentrate on.
+    pub fn focus(&self) -> &'static str {
+        match self {
+            Language::Rust => "Concentrate on its `pub` items: functions, types, traits and their implementations, error types, and anything behind a feature flag.",
+            Language::Python => "Concentrate on its public functions and classes (names without a leading underscore), `__all__`, and any command-line entry point.",
+            Language::JavaScript => "Concentrate on what it exports (`export` or `module.exports`) and how callers are meant to use it.",
+            Language::TypeScript => "Concentrate on its exported functions, classes, types, and interfaces, and the props of any exported component.",
+            Language::Go => "Concentrate on its exported (capitalized) identifiers, the interfaces it defines or satisfies, and its role in the package.",
+            Language::Java => "Concentrate on its public classes and methods, the interfaces they implement, and annotations that change behavior.",
+            Language::Ruby => "Concentrate on its public methods, the modules it includes or extends, and any DSL it defines.",
+            Language::Shell => "Concentrate on what the script does, the commands it runs, its arguments, and the environment variables it reads.",
+            Language::Markdown => "Concentrate on what the document covers and who it is for.",
+        }
+    }
+}
+
+impl FromStr for Language {
+    type Err = DocTreeError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        Language::ALL
+            .into_iter()
+            .find(|language| language.to_string() == value.trim().to_lowercase())
+            .ok_or_else(|| DocTreeError::config(format!("Unknown language '{}'", value.trim())))
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Language::Rust => "rust",
+            Language::Python => "python",
+            Language::JavaScript => "javascript",
+            Language::TypeScript => "typescript",
+            Language::Go => "go",
+            Language::Java => "java",
+            Language::Ruby => "ruby",
+            Language::Shell => "shell",
+            Language::Markdown => "markdown",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Instruction added for test files, whatever their language.
+const TEST_FOCUS: &str = "This is a test file: describe the behavior it covers and the fixtures and helpers it defines, not the code under test.";
+
+/// What a file's summary prompt emphasizes for one extension: a built-in
+/// language's instructions, the generic prompt, or custom text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PromptFocus {
+    Language(Language),
+    Generic,
+    Custom(String),
+}
+
+impl fmt::Display for PromptFocus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PromptFocus::Language(language) => write!(f, "{language}"),
+            PromptFocus::Generic => write!(f, "generic"),
+            PromptFocus::Custom(text) => write!(f, "{text}"),
+        }
+    }
+}
+
+/// Per-extension overrides of the language-specific prompt instructions.
+/// Files whose extension has no rule use the language detected from it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LanguagePrompts {
+    rules: Vec<(String, PromptFocus)>,
+}
+
+impl LanguagePrompts {
+    /// Parses `extension=focus` rules separated by semicolons, e.g.
+    /// `svelte=typescript;sql=Describe the tables and views it creates;*=generic`.
+    /// A focus is a language name, `generic`, or instructions of your own;
+    /// `*` matches every extension without a rule of its own.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut rules = Vec::new();
+
+        for entry in spec.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+            let (extension, focus) = entry.split_once('=').ok_or_else(|| {
+                DocTreeError::config(format!("Invalid language prompt rule '{entry}', expected extension=focus"))
+            })?;
+            let focus = focus.trim();
+            let focus = if focus.eq_ignore_ascii_case("generic") {
+                PromptFocus::Generic
+            } else if let Ok(language) = focus.parse() {
+                PromptFocus::Language(language)
+            } else {
+                PromptFocus::Custom(focus.to_string())
+            };
+            rules.push((extension.trim().trim_start_matches('.').to_lowercase(), focus));
+        }
+
+        Ok(Self { rules })
+    }
+
+    /// Instructions for summarizing this file, if anything beyond the
+    /// generic prompt applies.
+    pub fn instruction(&self, relative_path: &Path) -> Option<String> {
+        let extension = relative_path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_lowercase();
+        let rule = |extension: &str| self.rules.iter().find(|(pattern, _)| pattern == extension).map(|(_, focus)| focus);
+        let focus = match rule(&extension).or_else(|| rule("*")) {
+            Some(PromptFocus::Generic) => None,
+            Some(PromptFocus::Language(language)) => Some(language.focus().to_string()),
+            Some(PromptFocus::Custom(text)) => Some(text.clone()),
+            None => Language::from_extension(&extension).map(|language| language.focus().to_string()),
+        };
+
+        let instructions: Vec<String> = is_test_path(relative_path)
+            .then(|| TEST_FOCUS.to_string())
+            .into_iter()
+            .chain(focus)
+            .collect();
+        (!instructions.is_empty()).then(|| instructions.join(" "))
+    }
+}
+
+impl fmt::Display for LanguagePrompts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rules: Vec<String> = self.rules.iter().map(|(extension, focus)| format!("{extension}={focus}")).collect();
+        write!(f, "{}", rules.join(";"))
+    }
+}
+
+fn is_test_path(relative_path: &Path) -> bool {
+    let in_test_directory = relative_path
+        .parent()
+        .into_iter()
+        .flat_map(|parent| parent.components())
+        .any(|component| matches!(component.as_os_str().to_str(), Some("tests" | "test" | "__tests__" | "spec")));
+    let file_name = relative_path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    in_test_directory || ChildImportance::of_file(file_name) == ChildImportance::Tests
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_prompt_instructions() -> Result<()> {
+        let defaults = LanguagePrompts::default();
+        assert_eq!(defaults.instruction(Path::new("src/lib.rs")).as_deref(), Some(Language::Rust.focus()));
+        assert_eq!(defaults.instruction(Path::new("Cargo.toml")), None);
+
+        let test_instruction = defaults.instruction(Path::new("tests/cli.rs")).unwrap();
+        assert!(test_instruction.starts_with(TEST_FOCUS));
+        assert!(test_instruction.ends_with(Language::Rust.focus()));
+
+        let prompts = LanguagePrompts::parse("svelte=typescript; sql=Describe the tables it creates, and their keys;*=generic")?;
+        assert_eq!(prompts.instruction(Path::new("App.svelte")).as_deref(), Some(Language::TypeScript.focus()));
+        assert_eq!(prompts.instruction(Path::new("schema.sql")).as_deref(), Some("Describe the tables it creates, and their keys"));
+        assert_eq!(prompts.instruction(Path::new("src/lib.rs")), None);
+        assert_eq!(prompts.to_string(), "svelte=typescript;sql=Describe the tables it creates, and their keys;*=generic");
+        assert!(LanguagePrompts::parse("rs").is_err());
+        Ok(())
+    }
+}
@@ -1,11 +1,69 @@
+pub mod annotations;
+pub mod audit;
+pub mod badges;
+pub mod backup;
+pub mod batch;
+pub mod budget;
 pub mod cache;
+pub mod cancel;
+pub mod classifier;
+pub mod cli_usage;
 pub mod config;
+pub mod debt;
+pub mod diff;
+pub mod doctor;
+pub mod digest;
+pub mod doctree;
+pub mod embeddings;
 pub mod error;
+pub mod estimator;
+pub mod examples;
+pub mod file_lock;
+pub mod format;
+pub mod generated;
+pub mod github;
+pub mod graph;
 pub mod hasher;
+pub mod hooks;
+pub mod http;
+pub mod keychain;
+pub mod install;
+pub mod language;
+pub mod license;
+pub mod limits;
 pub mod llm;
+pub mod lock;
+pub mod logging;
+pub mod models;
+pub mod notify;
+pub mod outline;
+pub mod progress;
+pub mod provenance;
+pub mod rate_limit;
 pub mod readme;
 pub mod readme_validator;
+pub mod references;
+pub mod replay;
+pub mod renderer;
+pub mod reporter;
+pub mod response_cache;
+pub mod run_state;
+pub mod rust_api;
+pub mod sandbox;
 pub mod scanner;
+pub mod secrets;
+pub mod self_doc;
+pub mod staleness;
+pub mod strategy;
+pub mod structured;
+pub mod submodules;
 pub mod summarizer;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
+pub mod tickets;
+pub mod tree;
+pub mod wizard;
+pub mod workspace;
 
+pub use doctree::{DocTree, RunReport};
 pub use error::{DocTreeError, Result};
\ No newline at end of file
@@ -0,0 +1,231 @@
+use crate::badges::badge_markdown;
+use crate::cache::CacheSummary;
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// License files looked for in the project root. `LICENSE-*` files (e.g.
+/// LICENSE-MIT and LICENSE-APACHE) are found as well.
+const LICENSE_FILES: &[&str] = &[
+    "LICENSE", "LICENSE.md", "LICENSE.txt", "LICENCE", "LICENCE.md", "LICENCE.txt", "COPYING", "COPYING.md", "COPYING.txt",
+];
+
+/// Lines at the top of a source file searched for an SPDX header.
+const SPDX_HEADER_LINES: usize = 20;
+
+/// Text that identifies a license, checked in order: the first entry whose
+/// phrases all appear wins.
+const LICENSE_SIGNATURES: &[(&str, &[&str])] = &[
+    ("AGPL-3.0", &["GNU AFFERO GENERAL PUBLIC LICENSE"]),
+    ("LGPL-3.0", &["GNU LESSER GENERAL PUBLIC LICENSE", "Version 3"]),
+    ("LGPL-2.1", &["GNU LESSER GENERAL PUBLIC LICENSE", "Version 2.1"]),
+    ("GPL-3.0", &["GNU GENERAL PUBLIC LICENSE", "Version 3"]),
+    ("GPL-2.0", &["GNU GENERAL PUBLIC LICENSE", "Version 2"]),
+    ("Apache-2.0", &["Apache License", "Version 2.0"]),
+    ("MPL-2.0", &["Mozilla Public License", "2.0"]),
+    ("Unlicense", &["This is free and unencumbered software released into the public domain"]),
+    ("ISC", &["Permission to use, copy, modify, and/or distribute this software for any purpose"]),
+    ("MIT", &["Permission is hereby granted, free of charge"]),
+    ("BSD-3-Clause", &["Redistribution and use in source and binary forms", "Neither the name"]),
+    ("BSD-2-Clause", &["Redistribution and use in source and binary forms"]),
+];
+
+/// Display names for common SPDX identifiers.
+const LICENSE_NAMES: &[(&str, &str)] = &[
+    ("MIT", "MIT License"),
+    ("Apache-2.0", "Apache License 2.0"),
+    ("GPL-3.0", "GNU General Public License v3.0"),
+    ("GPL-2.0", "GNU General Public License v2.0"),
+    ("LGPL-3.0", "GNU Lesser General Public License v3.0"),
+    ("LGPL-2.1", "GNU Lesser General Public License v2.1"),
+    ("AGPL-3.0", "GNU Affero General Public License v3.0"),
+    ("MPL-2.0", "Mozilla Public License 2.0"),
+    ("BSD-3-Clause", "BSD 3-Clause License"),
+    ("BSD-2-Clause", "BSD 2-Clause License"),
+    ("ISC", "ISC License"),
+    ("Unlicense", "The Unlicense"),
+];
+
+/// The project's license, from its license files, manifest `license`
+/// fields, or SPDX headers in the source files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct License {
+    /// SPDX identifier or expression, e.g. `MIT OR Apache-2.0`; None when a
+    /// license file exists but its text is not recognized.
+    pub spdx_id: Option<String>,
+    /// License files in the project root, relative to it.
+    pub files: Vec<String>,
+}
+
+impl License {
+    /// Finds the project license: license files are identified by their
+    /// text, then manifest fields, then the most common SPDX header in the
+    /// summarized files. None when nothing indicates a license.
+    pub fn detect(base_path: &Path, summaries: &[CacheSummary]) -> Option<Self> {
+        let mut files: Vec<String> = fs::read_dir(base_path)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_file())
+            .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+            .filter(|name| {
+                LICENSE_FILES.contains(&name.as_str()) || name.starts_with("LICENSE-") || name.starts_with("LICENCE-")
+            })
+            .collect();
+        files.sort();
+
+        let mut identified: Vec<&str> = files
+            .iter()
+            .filter_map(|file| fs::read_to_string(base_path.join(file)).ok())
+            .filter_map(|text| identify_license(&text))
+            .collect();
+        identified.dedup();
+
+        let spdx_id = if identified.is_empty() {
+            manifest_license(base_path).or_else(|| spdx_header_license(summaries))
+        } else {
+            Some(identified.join(" OR "))
+        };
+
+        (spdx_id.is_some() || !files.is_empty()).then_some(Self { spdx_id, files })
+    }
+
+    /// Human-readable name, e.g. "Apache License 2.0".
+    pub fn name(&self) -> Option<String> {
+        let spdx_id = self.spdx_id.as_deref()?;
+        Some(
+            LICENSE_NAMES
+                .iter()
+                .find(|(id, _)| *id == spdx_id)
+                .map(|(_, name)| name.to_string())
+                .unwrap_or_else(|| spdx_id.to_string()),
+        )
+    }
+
+    /// Where the license text is: the license file, or the SPDX page for the
+    /// (first) identifier.
+    pub fn link(&self) -> Option<String> {
+        self.files.first().cloned().or_else(|| {
+            let first = self.spdx_id.as_deref()?.split_whitespace().next()?;
+            Some(format!("https://spdx.org/licenses/{first}.html"))
+        })
+    }
+
+    /// A static shields.io license badge, or None when the license is not
+    /// identified.
+    pub fn badge(&self) -> Option<String> {
+        let spdx_id = self.spdx_id.as_deref()?;
+        let label = spdx_id.replace('-', "--").replace(' ', "_");
+        let image = format!("https://img.shields.io/badge/license-{label}-blue.svg");
+        Some(badge_markdown(&format!("License: {spdx_id}"), &image, &self.link()?))
+    }
+}
+
+/// Existing static shields.io license badges, as (markdown, license) with
+/// the badge's escapes undone.
+pub fn license_badges(text: &str) -> Vec<(String, String)> {
+    let badge = Regex::new(r"\[?!\[[^\]]*\]\(https://img\.shields\.io/badge/[Ll]icen[sc]e-((?:[^-)]|--)+)-[^)]*\)(?:\]\([^)]*\))?")
+        .expect("valid regex");
+    badge
+        .captures_iter(text)
+        .map(|captures| {
+            let license = captures[1].replace("--", "\u{0}").replace('_', " ").replace("%20", " ").replace('\u{0}', "-");
+            (captures[0].to_string(), license)
+        })
+        .collect()
+}
+
+/// SPDX identifier for a license text.
+pub fn identify_license(text: &str) -> Option<&'static str> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    LICENSE_SIGNATURES
+        .iter()
+        .find(|(_, phrases)| phrases.iter().all(|phrase| text.contains(phrase)))
+        .map(|(id, _)| *id)
+}
+
+/// The `license` field of Cargo.toml, package.json, or pyproject.toml.
+fn manifest_license(base_path: &Path) -> Option<String> {
+    let read = |name: &str| fs::read_to_string(base_path.join(name)).ok();
+    let cargo = || {
+        let manifest = read("Cargo.toml")?.parse::<toml::Table>().ok()?;
+        let package = manifest.get("package").or_else(|| manifest.get("workspace")?.get("package"))?;
+        package.get("license")?.as_str().map(str::to_string)
+    };
+    let npm = || {
+        let package: serde_json::Value = serde_json::from_str(&read("package.json")?).ok()?;
+        package["license"].as_str().map(str::to_string)
+    };
+    let python = || {
+        let project = read("pyproject.toml")?.parse::<toml::Table>().ok()?;
+        let license = project.get("project")?.get("license")?;
+        license
+            .as_str()
+            .or_else(|| license.get("text")?.as_str())
+            .map(str::to_string)
+    };
+
+    cargo()
+        .or_else(npm)
+        .or_else(python)
+        .map(|license| license.trim().replace('/', " OR "))
+        .filter(|license| !license.is_empty())
+}
+
+/// The most common `SPDX-License-Identifier` among the summarized files.
+fn spdx_header_license(summaries: &[CacheSummary]) -> Option<String> {
+    let header = Regex::new(r"SPDX-License-Identifier:\s*([A-Za-z0-9.+() -]+?)\s*(?:\*/|-->)?\s*$").expect("valid regex");
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for summary in summaries.iter().filter(|summary| !summary.is_directory) {
+        let Ok(content) = fs::read_to_string(&summary.source_path) else {
+            continue;
+        };
+        if let Some(captures) = content.lines().take(SPDX_HEADER_LINES).find_map(|line| header.captures(line)) {
+            *counts.entry(captures[1].to_string()).or_insert(0) += 1;
+        }
+    }
+    counts.into_iter().max_by_key(|(_, count)| *count).map(|(license, _)| license)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_license() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        assert_eq!(License::detect(base_path, &[]), None);
+
+        fs::write(base_path.join("Cargo.toml"), "[package]\nname = \"x\"\nlicense = \"MIT/Apache-2.0\"\n")?;
+        let license = License::detect(base_path, &[]).unwrap();
+        assert_eq!(license.spdx_id.as_deref(), Some("MIT OR Apache-2.0"));
+        assert_eq!(license.link().as_deref(), Some("https://spdx.org/licenses/MIT.html"));
+
+        fs::write(base_path.join("LICENSE"), "MIT License\n\nPermission is hereby granted, free\nof charge, to any person")?;
+        let license = License::detect(base_path, &[]).unwrap();
+        assert_eq!(license.spdx_id.as_deref(), Some("MIT"));
+        assert_eq!(license.name().as_deref(), Some("MIT License"));
+        assert_eq!(
+            license.badge().as_deref(),
+            Some("[![License: MIT](https://img.shields.io/badge/license-MIT-blue.svg)](LICENSE)")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_identify_license_and_badges() {
+        assert_eq!(identify_license("Apache License\n  Version 2.0, January 2004"), Some("Apache-2.0"));
+        assert_eq!(identify_license("GNU LESSER GENERAL PUBLIC LICENSE Version 2.1"), Some("LGPL-2.1"));
+        assert_eq!(identify_license("All rights reserved."), None);
+
+        let readme = "# App\n[![License](https://img.shields.io/badge/License-Apache--2.0-blue.svg)](LICENSE) [![CI](https://x)](y)";
+        let badges = license_badges(readme);
+        assert_eq!(badges.len(), 1);
+        assert_eq!(badges[0].1, "Apache-2.0");
+        assert!(badges[0].0.ends_with("(LICENSE)"));
+    }
+}
@@ -0,0 +1,221 @@
+use crate::error::{DocTreeError, Result};
+use std::path::Path;
+use std::str::FromStr;
+
+/// What to do with files larger than the configured limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OversizeStrategy {
+    /// Leave the file out of the run with a logged notice.
+    Skip,
+    /// Summarize from excerpts of the beginning and end of the file.
+    #[default]
+    Truncate,
+}
+
+impl FromStr for OversizeStrategy {
+    type Err = DocTreeError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "skip" => Ok(OversizeStrategy::Skip),
+            "truncate" => Ok(OversizeStrategy::Truncate),
+            other => Err(DocTreeError::config(format!(
+                "Invalid oversize strategy '{other}' (expected skip or truncate)"
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileSizeLimit {
+    /// Maximum bytes of a file sent to the LLM; None disables the limit.
+    pub max_bytes: Option<u64>,
+    pub strategy: OversizeStrategy,
+}
+
+impl Default for FileSizeLimit {
+    fn default() -> Self {
+        Self {
+            max_bytes: Some(100_000),
+            strategy: OversizeStrategy::default(),
+        }
+    }
+}
+
+impl FileSizeLimit {
+    pub fn unlimited() -> Self {
+        Self {
+            max_bytes: None,
+            strategy: OversizeStrategy::default(),
+        }
+    }
+
+    /// Returns the content to send to the LLM, or None when the file should
+    /// be skipped.
+    pub fn apply(&self, content: String) -> Option<String> {
+        let Some(max_bytes) = self.max_bytes.map(|max| max as usize) else {
+            return Some(content);
+        };
+
+        if content.len() <= max_bytes {
+            return Some(content);
+        }
+
+        match self.strategy {
+            OversizeStrategy::Skip => None,
+            OversizeStrategy::Truncate => Some(Self::head_and_tail(&content, max_bytes)),
+        }
+    }
+
+    /// Keeps roughly the first and last half of the byte budget, cut at line
+    /// boundaries, with a marker noting how much was omitted.
+    fn head_and_tail(content: &str, max_bytes: usize) -> String {
+        let half = max_bytes / 2;
+
+        let mut head_end = Self::floor_char_boundary(content, half);
+        if let Some(newline) = content[..head_end].rfind('\n') {
+            head_end = newline + 1;
+        }
+
+        let mut tail_start = Self::floor_char_boundary(content, content.len() - half);
+        if let Some(newline) = content[tail_start..].find('\n') {
+            tail_start += newline + 1;
+        }
+        tail_start = tail_start.max(head_end);
+
+        format!(
+            "{}\n... [{} bytes omitted] ...\n\n{}",
+            &content[..head_end],
+            tail_start - head_end,
+            &content[tail_start..]
+        )
+    }
+
+    fn floor_char_boundary(content: &str, mut index: usize) -> usize {
+        while !content.is_char_boundary(index) {
+            index -= 1;
+        }
+        index
+    }
+}
+
+/// Safeguards against scanning far more than intended, e.g. running at
+/// `$HOME` by accident. Exceeding a limit aborts the scan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScanLimits {
+    pub max_depth: Option<usize>,
+    pub max_files: Option<usize>,
+    /// Files directly in one directory, e.g. a dump of generated fixtures.
+    pub max_files_per_dir: Option<usize>,
+}
+
+impl Default for ScanLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: Some(25),
+            max_files: Some(10_000),
+            max_files_per_dir: Some(1_000),
+        }
+    }
+}
+
+impl ScanLimits {
+    pub fn unlimited() -> Self {
+        Self {
+            max_depth: None,
+            max_files: None,
+            max_files_per_dir: None,
+        }
+    }
+
+    pub fn check_depth(&self, depth: usize, path: &Path) -> Result<()> {
+        match self.max_depth {
+            Some(max_depth) if depth > max_depth => Err(DocTreeError::scanner(format!(
+                "{} is nested {depth} levels deep, beyond the maximum scan depth of {max_depth}. \
+                 Check the target directory, narrow the scan with --exclude, or raise the limit with --max-depth (--no-scan-limits disables it)",
+                path.display()
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_file_count(&self, file_count: usize) -> Result<()> {
+        match self.max_files {
+            Some(max_files) if file_count > max_files => Err(DocTreeError::scanner(format!(
+                "Found more than {max_files} files to scan. \
+                 Check the target directory, narrow the scan with --include/--exclude, or raise the limit with --max-files (--no-scan-limits disables it)"
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check_directory_file_count(&self, file_count: usize, directory: &Path) -> Result<()> {
+        match self.max_files_per_dir {
+            Some(max_files) if file_count > max_files => Err(DocTreeError::scanner(format!(
+                "{} has more than {max_files} files to scan. \
+                 Exclude it with --exclude if it holds generated or vendored files, or raise the limit with --max-files-per-dir (--no-scan-limits disables it)",
+                if directory.as_os_str().is_empty() { Path::new(".") } else { directory }.display()
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_files_pass_through() {
+        let limit = FileSizeLimit::default();
+        assert_eq!(limit.apply("fn main() {}".to_string()), Some("fn main() {}".to_string()));
+    }
+
+    #[test]
+    fn test_oversized_files_are_skipped_or_truncated() {
+        let content: String = (0..100).map(|i| format!("line {i:03}\n")).collect();
+
+        let skip = FileSizeLimit {
+            max_bytes: Some(100),
+            strategy: OversizeStrategy::Skip,
+        };
+        assert_eq!(skip.apply(content.clone()), None);
+
+        let truncate = FileSizeLimit {
+            max_bytes: Some(100),
+            strategy: OversizeStrategy::Truncate,
+        };
+        let excerpt = truncate.apply(content).unwrap();
+        assert!(excerpt.starts_with("line 000\n"));
+        assert!(excerpt.ends_with("line 099\n"));
+        assert!(excerpt.contains("bytes omitted"));
+        assert!(!excerpt.contains("line 050"));
+    }
+
+    #[test]
+    fn test_scan_limits() {
+        let limits = ScanLimits {
+            max_depth: Some(2),
+            max_files: Some(3),
+            max_files_per_dir: Some(2),
+        };
+
+        assert!(limits.check_depth(2, Path::new("a/b")).is_ok());
+        assert!(limits.check_depth(3, Path::new("a/b/c")).is_err());
+        assert!(limits.check_file_count(3).is_ok());
+        assert!(limits.check_file_count(4).is_err());
+        assert!(ScanLimits::unlimited().check_file_count(usize::MAX).is_ok());
+        assert!(limits.check_directory_file_count(2, Path::new("src")).is_ok());
+        assert!(limits.check_directory_file_count(3, Path::new("")).unwrap_err().to_string().contains(". has more"));
+    }
+
+    #[test]
+    fn test_truncation_respects_char_boundaries() {
+        let content = "é".repeat(200);
+        let limit = FileSizeLimit {
+            max_bytes: Some(51),
+            strategy: OversizeStrategy::Truncate,
+        };
+        assert!(limit.apply(content).is_some());
+    }
+}
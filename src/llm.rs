@@ -1,65 +1,425 @@
+use crate::audit::AuditLog;
+use crate::batch::{BatchCollector, PendingCompletion};
+use crate::budget::{
+    SummaryBudgets, SummaryLevel, TokenBudget, BUDGET_RETRIES, BULLET_INSTRUCTION, DEFAULT_MAX_COMPLETION_TOKENS,
+};
 use crate::config::Config;
+use crate::digest::validate_digest;
 use crate::error::{DocTreeError, Result};
+use crate::estimator::estimate_tokens;
+use crate::hasher::FileHasher;
+use crate::language::LanguagePrompts;
+use crate::rate_limit::{is_throttling, RateLimiter};
+use crate::replay::ReplayFixture;
+use crate::response_cache::ResponseCache;
+use crate::structured::{StructuredSummary, STRUCTURED_SUMMARY_SCHEMA};
 use async_openai::{
-    config::OpenAIConfig,
+    config::{Config as _, OpenAIConfig},
+    error::{ApiError, OpenAIError, WrappedError},
     types::{
-        ChatCompletionRequestMessage, ChatCompletionRequestSystemMessage,
+        BatchCompletionWindow, BatchEndpoint, BatchRequest, BatchRequestInput, BatchRequestInputMethod,
+        BatchRequestOutput, BatchStatus, CreateFileRequest, FileInput, FilePurpose, ChatCompletionRequestMessage,
+        ChatCompletionStreamOptions, CompletionUsage, ChatCompletionRequestSystemMessage,
         ChatCompletionRequestSystemMessageContent, ChatCompletionRequestUserMessage,
-        ChatCompletionRequestUserMessageContent, CreateChatCompletionRequest,
+        ChatCompletionRequestUserMessageContent, CreateChatCompletionRequest, CreateChatCompletionResponse,
+        CreateChatCompletionStreamResponse, CreateEmbeddingRequest, EmbeddingInput,
     },
     Client,
 };
+use eventsource_stream::Eventsource;
+use futures::StreamExt;
+use rand::Rng;
+use reqwest::header::HeaderMap;
+use std::future::Future;
 use std::path::Path;
-use tokio::time::{sleep, Duration};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::time::{sleep, timeout, timeout_at, Duration};
+use tracing::Instrument;
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub request_timeout: Duration,
+}
+
+impl RetryPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_retries: config.max_retries,
+            base_delay: Duration::from_millis(config.retry_base_delay_ms),
+            max_delay: Duration::from_millis(config.retry_max_delay_ms),
+            request_timeout: Duration::from_secs(config.request_timeout_secs),
+        }
+    }
+
+    /// Exponential backoff with equal jitter: half of the exponential delay is
+    /// fixed and the other half is random, capped at `max_delay`.
+    pub fn backoff_delay(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(16);
+        let delay = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+
+        let half = delay / 2;
+        let jitter_ms = rand::rng().random_range(0..=half.as_millis() as u64);
+        half + Duration::from_millis(jitter_ms)
+    }
+}
+
+/// The server-requested wait of a throttled response: OpenAI's
+/// `retry-after-ms`, or `Retry-After` in seconds. A `Retry-After` date is
+/// not read, leaving the wait to backoff.
+pub fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let number = |name: &str| -> Option<f64> {
+        let value: f64 = headers.get(name)?.to_str().ok()?.trim().parse().ok()?;
+        (value.is_finite() && value >= 0.0).then_some(value)
+    };
+    number("retry-after-ms")
+        .map(|ms| Duration::from_secs_f64(ms / 1000.0))
+        .or_else(|| number("retry-after").map(Duration::from_secs_f64))
+}
+
+/// Source characters sent per file in small-model mode; the rest is cut.
+const SMALL_MODEL_MAX_CONTENT_CHARS: usize = 6000;
+
+/// Sampling parameters of every completion request.
+const TEMPERATURE: f32 = 0.3;
+const TOP_P: f32 = 0.9;
+
+/// Receives the pieces of a streamed answer as they arrive.
+pub type StreamOutput = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Where completions come from.
+#[derive(Clone)]
+enum CompletionBackend {
+    /// An OpenAI-compatible server. Chat completions go through the HTTP
+    /// client directly, so throttled responses' headers can be read;
+    /// embeddings and batch jobs go through the SDK.
+    OpenAi(Client<OpenAIConfig>, reqwest::Client),
+    /// Deterministic offline responses for end-to-end tests and `self-doc`.
+    Mock,
+    /// Responses recorded from an earlier run.
+    Replay(ReplayFixture),
+    /// Responses from a test script (`test-util` feature).
+    #[cfg(any(test, feature = "test-util"))]
+    Scripted(crate::testing::ScriptedProvider),
+    /// Records requests for a batch job instead of sending them.
+    Collect(BatchCollector),
+}
 
+#[derive(Clone)]
 pub struct LanguageModelClient {
-    client: Client<OpenAIConfig>,
-    model_name: String,
-    max_retries: u32,
-    retry_delay: Duration,
+    backend: CompletionBackend,
+    summary_model: String,
+    synthesis_model: String,
+    retry_policy: RetryPolicy,
+    rate_limiter: RateLimiter,
+    doc_language: Option<String>,
+    response_cache: Option<ResponseCache>,
+    audit_log: Option<AuditLog>,
+    small_model: bool,
+    structured_summaries: bool,
+    budgets: SummaryBudgets,
+    language_prompts: LanguagePrompts,
+    token_budget: Option<TokenBudget>,
+    streaming: bool,
+    stream_chunk_timeout: Duration,
+    stream_output: Option<StreamOutput>,
+    /// Whether this request's answer goes to `stream_output`.
+    live: bool,
 }
 
+/// First words of the structured file summary prompt, which the mock
+/// backend answers with JSON.
+const STRUCTURED_PROMPT_PREFIX: &str = "Describe this source file as a JSON object";
+
+/// Shown after a live answer that broke off, so a retried answer is not
+/// read as its continuation.
+const LIVE_INTERRUPTED: &str = "\n\n⚠️  Answer interrupted\n\n";
+
 impl LanguageModelClient {
     pub fn new(config: &Config) -> Result<Self> {
         let openai_config = OpenAIConfig::new()
             .with_api_base(config.openai_api_base.clone())
             .with_api_key(config.openai_api_key.clone());
 
-        let client = Client::with_config(openai_config);
+        // Retries are driven by RetryPolicy; disable the SDK's own rate limit backoff
+        let no_backoff = backoff::ExponentialBackoffBuilder::new()
+            .with_max_elapsed_time(Some(Duration::ZERO))
+            .build();
+
+        let http_client = crate::http::llm_http_client(config)?;
+        let client = Client::with_config(openai_config)
+            .with_http_client(http_client.clone())
+            .with_backoff(no_backoff);
 
         Ok(Self {
-            client,
-            model_name: config.openai_model_name.clone(),
-            max_retries: 3,
-            retry_delay: Duration::from_secs(2),
+            backend: CompletionBackend::OpenAi(client, http_client),
+            summary_model: config.summary_model().to_string(),
+            synthesis_model: config.synthesis_model().to_string(),
+            retry_policy: RetryPolicy::from_config(config),
+            rate_limiter: RateLimiter::from_config(config),
+            doc_language: config.doc_language.clone(),
+            response_cache: None,
+            audit_log: None,
+            small_model: config.small_model,
+            structured_summaries: config.structured_summaries,
+            budgets: config.summary_budgets,
+            language_prompts: config.language_prompts.clone(),
+            token_budget: None,
+            streaming: config.stream_completions,
+            stream_chunk_timeout: Duration::from_secs(config.stream_chunk_timeout_secs),
+            stream_output: None,
+            live: false,
         })
     }
 
+    /// A client that never touches the network. Each response is derived from
+    /// the first line of its prompt, i.e. the instruction and the file or
+    /// directory name, so output only changes when prompts or the tree change.
+    pub fn mock(config: &Config) -> Self {
+        Self {
+            backend: CompletionBackend::Mock,
+            summary_model: config.summary_model().to_string(),
+            synthesis_model: config.synthesis_model().to_string(),
+            retry_policy: RetryPolicy::from_config(config),
+            rate_limiter: RateLimiter::from_config(config),
+            doc_language: config.doc_language.clone(),
+            response_cache: None,
+            audit_log: None,
+            small_model: config.small_model,
+            structured_summaries: config.structured_summaries,
+            budgets: config.summary_budgets,
+            language_prompts: config.language_prompts.clone(),
+            token_budget: None,
+            streaming: config.stream_completions,
+            stream_chunk_timeout: Duration::from_secs(config.stream_chunk_timeout_secs),
+            stream_output: None,
+            live: false,
+        }
+    }
+
+    /// A client answering from recorded responses, without retries since a
+    /// missing recording stays missing.
+    pub fn replay(config: &Config, fixture: ReplayFixture) -> Self {
+        let mut client = Self::mock(config);
+        client.backend = CompletionBackend::Replay(fixture);
+        client.retry_policy.max_retries = 0;
+        client
+    }
+
+    /// A client that records the requests this one would send, for
+    /// `run_batch`, and answers them with placeholders. Cached responses are
+    /// still served but nothing is written, so placeholders never reach the
+    /// response cache or the audit log.
+    pub fn collecting(&self, collector: BatchCollector) -> Self {
+        let mut client = self.clone();
+        client.backend = CompletionBackend::Collect(collector);
+        client.retry_policy.max_retries = 0;
+        client.rate_limiter = RateLimiter::default();
+        client.audit_log = None;
+        client.token_budget = None;
+        if let Some(response_cache) = &mut client.response_cache {
+            response_cache.set_read_only(true);
+        }
+        client
+    }
+
+    /// A client answering from a `ScriptedProvider`, without retries.
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn scripted(config: &Config, provider: crate::testing::ScriptedProvider) -> Self {
+        let mut client = Self::mock(config);
+        client.backend = CompletionBackend::Scripted(provider);
+        client.retry_policy.max_retries = 0;
+        client
+    }
+
+    /// Whether prompts and output checks are tuned for small local models.
+    pub fn is_small_model(&self) -> bool {
+        self.small_model
+    }
+
+    /// Whether file summaries are requested as JSON; see
+    /// `generate_structured_file_summary`.
+    pub fn uses_structured_summaries(&self) -> bool {
+        self.structured_summaries
+    }
+
+    /// Answers completions from the cache when an identical request was
+    /// sent to the same model within the cache TTL.
+    pub fn set_response_cache(&mut self, response_cache: ResponseCache) {
+        self.response_cache = Some(response_cache);
+    }
+
+    /// Records every completion request and its raw response.
+    pub fn set_audit_log(&mut self, audit_log: AuditLog) {
+        self.audit_log = Some(audit_log);
+    }
+
+    /// Shows long answers (README rewrites and translations) as they are
+    /// written. They are streamed even when `DOCTREEAI_STREAM` is off.
+    pub fn set_stream_output(&mut self, stream_output: StreamOutput) {
+        self.stream_output = Some(stream_output);
+    }
+
+    /// Counts every completion's tokens against the budget and refuses new
+    /// requests once it is spent.
+    pub fn set_token_budget(&mut self, token_budget: TokenBudget) {
+        self.token_budget = Some(token_budget);
+    }
+
     pub async fn generate_file_summary(&self, file_path: &Path, content: &str) -> Result<String> {
+        self.generate_file_summary_with_context(file_path, content, None).await
+    }
+
+    /// Like `generate_file_summary`, with notes about where the file sits in
+    /// the project (e.g. which files import it) placed before the code.
+    pub async fn generate_file_summary_with_context(
+        &self,
+        file_path: &Path,
+        content: &str,
+        context: Option<&str>,
+    ) -> Result<String> {
         let filename = file_path
             .file_name()
             .and_then(|n| n.to_str())
             .unwrap_or("unknown");
+        let context = self.context_block(file_path, context);
+
+        if self.small_model {
+            let content: String = content.chars().take(SMALL_MODEL_MAX_CONTENT_CHARS).collect();
+            let prompt = format!(
+                "Summarize this file in 2-3 plain sentences: what it does and its main functions or types. File: {filename}{context}\n\n```\n{content}\n```"
+            );
+            return self.generate_budgeted_summary(prompt, SummaryLevel::File).await;
+        }
 
         let prompt = format!(
-            "Analyze this source code file and provide a comprehensive description of its purpose, functionality, key features, and how it contributes to the overall project. Include details about APIs, configuration options, usage patterns, and any important behaviors that would be relevant for complete project documentation. File: {filename}\n\nCode:\n```\n{content}\n```"
+            "Analyze this source code file and provide a comprehensive description of its purpose, functionality, key features, and how it contributes to the overall project. Include details about APIs, configuration options, usage patterns, and any important behaviors that would be relevant for complete project documentation. File: {filename}{context}\n\nCode:\n```\n{content}\n```"
         );
 
-        self.generate_completion(&prompt).await
+        self.generate_budgeted_summary(prompt, SummaryLevel::File).await
+    }
+
+    /// Asks for the file summary as a JSON object following
+    /// `STRUCTURED_SUMMARY_SCHEMA`. A response that does not match the
+    /// schema is an error, like any other failed summary.
+    pub async fn generate_structured_file_summary(&self, file_path: &Path, content: &str) -> Result<StructuredSummary> {
+        self.generate_structured_file_summary_with_context(file_path, content, None).await
+    }
+
+    pub async fn generate_structured_file_summary_with_context(
+        &self,
+        file_path: &Path,
+        content: &str,
+        context: Option<&str>,
+    ) -> Result<StructuredSummary> {
+        let filename = file_path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown");
+        let context = self.context_block(file_path, context);
+
+        let prompt = self.localize(format!(
+            "{STRUCTURED_PROMPT_PREFIX} matching this JSON Schema. File: {filename}{context}\n\n{STRUCTURED_SUMMARY_SCHEMA}\n\nRespond with the JSON object only, without Markdown or any other text.\n\nCode:\n```\n{content}\n```"
+        ));
+
+        let output = self.generate_completion(&prompt, &self.summary_model).await?;
+        StructuredSummary::parse(&output)
+    }
+
+    /// Text placed after the file name: the language-specific instructions
+    /// for the file, then notes about where it sits in the project.
+    fn context_block(&self, file_path: &Path, context: Option<&str>) -> String {
+        let mut block = String::new();
+        if let Some(instruction) = self.language_prompts.instruction(file_path) {
+            block.push_str(&format!("\n\n{instruction}"));
+        }
+        if let Some(context) = context.map(str::trim).filter(|context| !context.is_empty()) {
+            block.push_str(&format!("\n\nProject context:\n{context}"));
+        }
+        block
     }
 
     pub async fn generate_directory_summary(
         &self,
         directory_name: &str,
         children_summaries: &[String],
+    ) -> Result<String> {
+        self.generate_summary_from_children(directory_name, children_summaries, SummaryLevel::Directory)
+            .await
+    }
+
+    /// Summarizes the project root from its children, under the root's
+    /// length budget.
+    pub async fn generate_root_summary(&self, directory_name: &str, children_summaries: &[String]) -> Result<String> {
+        self.generate_summary_from_children(directory_name, children_summaries, SummaryLevel::Root)
+            .await
+    }
+
+    async fn generate_summary_from_children(
+        &self,
+        directory_name: &str,
+        children_summaries: &[String],
+        level: SummaryLevel,
     ) -> Result<String> {
         let combined_summaries = children_summaries.join("\n\n");
 
+        if self.small_model {
+            let prompt = format!(
+                "Summarize the '{directory_name}' directory in 2-3 plain sentences from these descriptions of its contents.\n\n{combined_summaries}"
+            );
+            return self.generate_budgeted_summary(prompt, level).await;
+        }
+
         let prompt = format!(
             "Based on the following detailed descriptions of files in the '{directory_name}' directory, provide a comprehensive summary of this directory's role in the project. Include information about functionality, APIs, configuration, usage patterns, and any features that would be important for complete project documentation.\n\nComponent Descriptions:\n{combined_summaries}"
         );
 
-        self.generate_completion(&prompt).await
+        self.generate_budgeted_summary(prompt, level).await
+    }
+
+    /// Completes a summary prompt within the level's length and style
+    /// budget. An answer that breaks it is sent back for a rewrite, up to
+    /// `BUDGET_RETRIES` times, and the last answer is trimmed to fit.
+    async fn generate_budgeted_summary(&self, prompt: String, level: SummaryLevel) -> Result<String> {
+        let prompt = match self.budgets.instruction(level) {
+            Some(instruction) => self.localize(format!("{prompt}\n\n{instruction}")),
+            None => self.localize(prompt),
+        };
+        let max_tokens = self.budgets.max_completion_tokens(level);
+
+        let mut output = self.generate_summary_completion(&prompt, max_tokens).await?;
+        for _ in 0..BUDGET_RETRIES {
+            let Some(violation) = self.budgets.violation(level, &output) else {
+                return Ok(output);
+            };
+            tracing::debug!("Summary breaks its budget ({violation}), asking for a rewrite");
+            let rewrite = format!(
+                "{prompt}\n\nYour previous answer did not fit the limits ({violation}). Rewrite it to fit:\n\n{output}"
+            );
+            output = self.generate_summary_completion(&rewrite, max_tokens).await?;
+        }
+
+        if let Some(violation) = self.budgets.violation(level, &output) {
+            tracing::warn!("Summary still breaks its budget ({violation}) after {BUDGET_RETRIES} rewrites, trimming it");
+            return Ok(self.budgets.trim(level, &output));
+        }
+        Ok(output)
+    }
+
+    async fn generate_summary_completion(&self, prompt: &str, max_tokens: u32) -> Result<String> {
+        let output = self
+            .generate_completion_with_limit(prompt, &self.summary_model, max_tokens)
+            .await?;
+        if self.small_model {
+            return validate_digest(&output);
+        }
+        Ok(output)
     }
 
     pub async fn update_readme(
@@ -67,11 +427,11 @@ impl LanguageModelClient {
         existing_readme: &str,
         project_summary: &str,
     ) -> Result<String> {
-        let prompt = format!(
+        let prompt = self.localize(format!(
             "Update the existing README.md file by intelligently merging it with new project analysis. Preserve valuable manual content (installation instructions, configuration examples, troubleshooting tips, etc.) while updating sections that should reflect the current codebase.\n\nYour task:\n1. Keep well-written manual sections that are still accurate\n2. Update project description based on current code analysis\n3. Update architecture/features sections if the code has changed\n4. Add any new sections that the project analysis reveals are needed\n5. Remove sections that are no longer relevant\n6. Ensure all examples and instructions match the current codebase\n\n**Existing README:**\n---\n{existing_readme}\n---\n\n**Current Project Analysis:**\n---\n{project_summary}\n---\n\nReturn an updated README that intelligently merges the best of both - preserving good manual content while updating with current project reality."
-        );
+        ));
 
-        self.generate_completion(&prompt).await
+        self.generate_live_completion(&prompt, &self.synthesis_model).await
     }
 
     pub async fn create_new_readme(
@@ -79,38 +439,295 @@ impl LanguageModelClient {
         project_summary: &str,
         project_name: &str,
     ) -> Result<String> {
-        let prompt = format!(
+        let prompt = self.localize(format!(
             "Create a comprehensive, user-friendly README.md file for a project called '{project_name}'. Focus on what the tool does for users and how they can use it. Include all standard sections: installation, configuration, usage examples, troubleshooting, and contributing guidelines.\n\n**Project Information:**\n{project_summary}\n\nCreate a complete README that focuses on user needs and practical usage, not technical implementation details."
-        );
+        ));
 
-        self.generate_completion(&prompt).await
+        self.generate_live_completion(&prompt, &self.synthesis_model).await
     }
 
     pub async fn generate_readme_suggestion(&self, prompt: &str) -> Result<String> {
-        self.generate_completion(prompt).await
+        self.generate_completion(&self.localize(prompt.to_string()), &self.synthesis_model)
+            .await
+    }
+
+    /// Asks for a README check whose answer is parsed, such as line verdicts
+    /// with the `NO_CHANGE` sentinel. Not localized, so the reply keeps the
+    /// format the prompt asks for.
+    pub async fn check_readme(&self, prompt: &str) -> Result<String> {
+        self.generate_completion(prompt, &self.synthesis_model).await
+    }
+
+    pub async fn translate_readme(&self, readme: &str, language: &str) -> Result<String> {
+        let prompt = format!(
+            "Translate the following README.md into the language '{language}'. Preserve the Markdown structure, links, code blocks, commands, and identifiers exactly; translate only the prose. Return only the translated README.\n\n---\n{readme}\n---"
+        );
+
+        self.generate_live_completion(&prompt, &self.synthesis_model).await
+    }
+
+    /// Appends the configured output language instruction, if any.
+    fn localize(&self, prompt: String) -> String {
+        match &self.doc_language {
+            Some(language) => format!("{prompt}\n\nWrite your entire response in {language}."),
+            None => prompt,
+        }
+    }
+
+    /// Like `generate_completion`, passing the answer to the stream output
+    /// as it arrives.
+    async fn generate_live_completion(&self, prompt: &str, model: &str) -> Result<String> {
+        if self.stream_output.is_none() {
+            return self.generate_completion(prompt, model).await;
+        }
+        let mut client = self.clone();
+        client.live = true;
+        client.generate_completion(prompt, model).await
+    }
+
+    async fn generate_completion(&self, prompt: &str, model: &str) -> Result<String> {
+        self.generate_completion_with_limit(prompt, model, DEFAULT_MAX_COMPLETION_TOKENS)
+            .await
+    }
+
+    /// A completion from the response cache, or else from the LLM. Cached
+    /// responses cost nothing against the token budget.
+    async fn generate_completion_with_limit(&self, prompt: &str, model: &str, max_tokens: u32) -> Result<String> {
+        let Some(cache) = &self.response_cache else {
+            return self.request_completion(prompt, model, max_tokens).await;
+        };
+
+        let params = Self::completion_params(max_tokens);
+        if let Some(response) = cache.get(model, &params, prompt) {
+            tracing::debug!("Response cache hit for model {model}");
+            return Ok(response);
+        }
+
+        let response = self.request_completion(prompt, model, max_tokens).await?;
+        if let Err(e) = cache.put(model, &params, prompt, &response) {
+            tracing::warn!("Failed to cache LLM response: {e}");
+        }
+        Ok(response)
+    }
+
+    /// Request parameters other than the prompt, part of the response cache key.
+    fn completion_params(max_tokens: u32) -> String {
+        format!("max_tokens={max_tokens} temperature={TEMPERATURE} top_p={TOP_P}")
+    }
+
+    /// Completes the requests collected by a `collecting` client and stores
+    /// the responses in the response cache, where the run then finds them.
+    /// OpenAI-compatible servers get one batch job, checked every
+    /// `poll_interval` until it finishes; other backends answer each request
+    /// directly. Returns how many responses were stored.
+    pub async fn run_batch(&self, requests: &[PendingCompletion], poll_interval: Duration) -> Result<usize> {
+        let Some(cache) = &self.response_cache else {
+            return Err(DocTreeError::config(
+                "Batch mode stores its responses in the response cache; set DOCTREEAI_RESPONSE_CACHE_TTL above 0",
+            ));
+        };
+        if let Some(token_budget) = &self.token_budget {
+            token_budget.check()?;
+        }
+
+        let responses = match &self.backend {
+            CompletionBackend::OpenAi(client, _) => self.submit_batch_job(client, requests, poll_interval).await?,
+            _ => {
+                let mut responses = Vec::new();
+                for request in requests {
+                    let response = self.try_generate_completion(&request.prompt, &request.model, request.max_tokens).await;
+                    responses.push(response.ok());
+                }
+                responses
+            }
+        };
+
+        let mut stored = 0;
+        for (request, response) in requests.iter().zip(responses) {
+            let Some(response) = response else {
+                continue;
+            };
+            cache.put(&request.model, &Self::completion_params(request.max_tokens), &request.prompt, &response)?;
+            stored += 1;
+        }
+        Ok(stored)
+    }
+
+    /// Uploads the requests as a JSONL file, starts a batch job on it, waits
+    /// for the job, and reads its output. Requests that failed in the job
+    /// have no response.
+    async fn submit_batch_job(
+        &self,
+        client: &Client<OpenAIConfig>,
+        requests: &[PendingCompletion],
+        poll_interval: Duration,
+    ) -> Result<Vec<Option<String>>> {
+        let mut input = Vec::new();
+        for (index, request) in requests.iter().enumerate() {
+            let line = BatchRequestInput {
+                custom_id: index.to_string(),
+                method: BatchRequestInputMethod::POST,
+                url: BatchEndpoint::V1ChatCompletions,
+                body: Some(serde_json::to_value(Self::chat_request(&request.prompt, &request.model, request.max_tokens))?),
+            };
+            input.extend(serde_json::to_vec(&line)?);
+            input.push(b'\n');
+        }
+
+        let file = client
+            .files()
+            .create(CreateFileRequest {
+                file: FileInput::from_vec_u8("doctreeai-batch.jsonl".to_string(), input),
+                purpose: FilePurpose::Batch,
+            })
+            .await?;
+        let mut batch = client
+            .batches()
+            .create(BatchRequest {
+                input_file_id: file.id,
+                endpoint: BatchEndpoint::V1ChatCompletions,
+                completion_window: BatchCompletionWindow::W24H,
+                metadata: None,
+            })
+            .await?;
+        tracing::info!("Submitted batch job {} with {} requests", batch.id, requests.len());
+
+        // An expired job still returns the requests it completed
+        while !matches!(batch.status, BatchStatus::Completed | BatchStatus::Expired) {
+            if matches!(batch.status, BatchStatus::Failed | BatchStatus::Cancelling | BatchStatus::Cancelled) {
+                return Err(DocTreeError::summarizer(format!("Batch job {} ended as {:?}", batch.id, batch.status)));
+            }
+            sleep(poll_interval).await;
+            batch = client.batches().retrieve(&batch.id).await?;
+            if let Some(counts) = &batch.request_counts {
+                tracing::info!("Batch job {}: {}/{} requests done", batch.id, counts.completed, counts.total);
+            }
+        }
+
+        let mut responses = vec![None; requests.len()];
+        let Some(output_file_id) = &batch.output_file_id else {
+            return Ok(responses);
+        };
+        let output = client.files().content(output_file_id).await?;
+        for line in String::from_utf8_lossy(&output).lines().filter(|line| !line.trim().is_empty()) {
+            let output: BatchRequestOutput = serde_json::from_str(line)?;
+            let Some(index) = output.custom_id.parse::<usize>().ok().filter(|index| *index < requests.len()) else {
+                continue;
+            };
+            let Some(response) = output.response.filter(|response| response.status_code == 200) else {
+                continue;
+            };
+            if let (Some(token_budget), Some(total_tokens)) =
+                (&self.token_budget, response.body["usage"]["total_tokens"].as_u64())
+            {
+                token_budget.record(total_tokens);
+            }
+            responses[index] = response.body["choices"][0]["message"]["content"]
+                .as_str()
+                .map(|content| content.trim().to_string());
+        }
+        Ok(responses)
     }
 
-    async fn generate_completion(&self, prompt: &str) -> Result<String> {
+    /// One LLM call, in a span recording its tokens, retries, and latency.
+    async fn request_completion(&self, prompt: &str, model: &str, max_tokens: u32) -> Result<String> {
+        if let Some(token_budget) = &self.token_budget {
+            token_budget.check()?;
+        }
+        let span = tracing::info_span!(
+            "llm_call",
+            model,
+            prompt_tokens = estimate_tokens(prompt),
+            completion_tokens = tracing::field::Empty,
+            retries = 0,
+            latency_ms = tracing::field::Empty,
+        );
+        let started = Instant::now();
+        let result = self
+            .with_retries(estimate_tokens(prompt), !self.streams(), || self.try_generate_completion(prompt, model, max_tokens))
+            .instrument(span.clone())
+            .await;
+        if let Ok(response) = &result {
+            self.rate_limiter.record_tokens(estimate_tokens(response));
+        }
+        let latency_ms = started.elapsed().as_millis() as u64;
+        span.record("latency_ms", latency_ms);
+        if let Some(audit_log) = &self.audit_log {
+            if let Err(e) = audit_log.record(model, prompt, &result, latency_ms) {
+                tracing::warn!("Failed to write audit record: {e}");
+            }
+        }
+        result
+    }
+
+    /// Embeds each text with the given embedding model, in input order.
+    pub async fn embed(&self, texts: &[String], model: &str) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let tokens = texts.iter().map(|text| estimate_tokens(text)).sum();
+        self.with_retries(tokens, true, || self.try_embed(texts, model)).await
+    }
+
+    /// Runs a request of about `tokens` under the rate limiter, and under the
+    /// retry policy's timeout when `timed` (streamed requests time out on
+    /// their own). Rate limit and server errors slow the limiter down
+    /// (honoring the server's Retry-After hint); other failures are retried
+    /// with backoff.
+    async fn with_retries<T, F, Fut>(&self, tokens: u64, timed: bool, mut request: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
         let mut attempt = 0;
 
         loop {
-            match self.try_generate_completion(prompt).await {
-                Ok(response) => return Ok(response),
-                Err(e) if attempt < self.max_retries => {
+            self.rate_limiter.acquire(tokens).await;
+            let result = if timed {
+                timeout(self.retry_policy.request_timeout, request())
+                    .await
+                    .unwrap_or_else(|_| Err(self.request_timed_out()))
+            } else {
+                request().await
+            };
+
+            match result {
+                Ok(response) => {
+                    self.rate_limiter.on_success();
+                    return Ok(response);
+                }
+                Err(e) if attempt < self.retry_policy.max_retries && is_throttling(&e) => {
+                    attempt += 1;
+                    tracing::Span::current().record("retries", attempt);
+                    // The limiter holds back this retry and every other request
+                    let pause = self.rate_limiter.on_throttled(Self::retry_after_hint(&e));
+                    tracing::warn!(
+                        "LLM API throttled the request (attempt {}/{}), slowing down and retrying in {:.1}s: {}",
+                        attempt,
+                        self.retry_policy.max_retries + 1,
+                        pause.as_secs_f64(),
+                        e
+                    );
+                    continue;
+                }
+                Err(e) if attempt < self.retry_policy.max_retries => {
                     attempt += 1;
-                    log::warn!(
-                        "LLM API call failed (attempt {}/{}): {}",
+                    tracing::Span::current().record("retries", attempt);
+                    let delay = self.retry_policy.backoff_delay(attempt);
+                    tracing::warn!(
+                        "LLM API call failed (attempt {}/{}), retrying in {:.1}s: {}",
                         attempt,
-                        self.max_retries + 1,
+                        self.retry_policy.max_retries + 1,
+                        delay.as_secs_f64(),
                         e
                     );
-                    sleep(self.retry_delay * attempt).await;
+                    sleep(delay).await;
                     continue;
                 }
                 Err(e) => {
                     return Err(DocTreeError::summarizer(format!(
                         "LLM API failed after {} retries: {}",
-                        self.max_retries + 1,
+                        self.retry_policy.max_retries + 1,
                         e
                     )));
                 }
@@ -118,7 +735,231 @@ impl LanguageModelClient {
         }
     }
 
-    async fn try_generate_completion(&self, prompt: &str) -> Result<String> {
+    fn retry_after_hint(error: &DocTreeError) -> Option<Duration> {
+        match error {
+            DocTreeError::Api { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    async fn try_embed(&self, texts: &[String], model: &str) -> Result<Vec<Vec<f32>>> {
+        let client = match &self.backend {
+            CompletionBackend::OpenAi(client, _) => client,
+            CompletionBackend::Mock | CompletionBackend::Replay(_) | CompletionBackend::Collect(_) => {
+                return Ok(texts.iter().map(|text| Self::mock_embedding(text)).collect())
+            }
+            #[cfg(any(test, feature = "test-util"))]
+            CompletionBackend::Scripted(_) => {
+                return Ok(texts.iter().map(|text| Self::mock_embedding(text)).collect())
+            }
+        };
+
+        let request = CreateEmbeddingRequest {
+            model: model.to_string(),
+            input: EmbeddingInput::StringArray(texts.to_vec()),
+            ..Default::default()
+        };
+
+        tracing::debug!("Embedding {} texts with model: {model}", texts.len());
+
+        let mut data = client.embeddings().create(request).await?.data;
+        if data.len() != texts.len() {
+            return Err(DocTreeError::summarizer(format!(
+                "Expected {} embeddings, received {}",
+                texts.len(),
+                data.len()
+            )));
+        }
+        data.sort_by_key(|embedding| embedding.index);
+
+        Ok(data.into_iter().map(|embedding| embedding.embedding).collect())
+    }
+
+    async fn try_generate_completion(&self, prompt: &str, model: &str, max_tokens: u32) -> Result<String> {
+        let (client, http_client) = match &self.backend {
+            CompletionBackend::OpenAi(client, http_client) => (client, http_client),
+            CompletionBackend::Mock => return Ok(self.local_response(prompt, Self::mock_completion(prompt))),
+            CompletionBackend::Replay(fixture) => {
+                return fixture.complete(prompt).map(|response| self.local_response(prompt, response))
+            }
+            #[cfg(any(test, feature = "test-util"))]
+            CompletionBackend::Scripted(provider) => {
+                return provider.complete(prompt).map(|response| self.local_response(prompt, response))
+            }
+            CompletionBackend::Collect(collector) => {
+                let request = PendingCompletion { model: model.to_string(), max_tokens, prompt: prompt.to_string() };
+                return Ok(collector.record(request, prompt.starts_with(STRUCTURED_PROMPT_PREFIX)));
+            }
+        };
+
+        let request = Self::chat_request(prompt, model, max_tokens);
+        tracing::debug!("Sending request to LLM with model: {model}");
+
+        let (content, usage) = if self.streams() {
+            self.stream_completion(client, http_client, request).await?
+        } else {
+            let body = Self::send_chat_request(client, http_client, &request).await?.bytes().await.map_err(OpenAIError::Reqwest)?;
+            let response: CreateChatCompletionResponse = serde_json::from_slice(&body)?;
+            let content = response
+                .choices
+                .into_iter()
+                .next()
+                .and_then(|choice| choice.message.content)
+                .ok_or_else(|| DocTreeError::summarizer("No response content from LLM"))?;
+            (content, response.usage)
+        };
+        if let Some(usage) = &usage {
+            let span = tracing::Span::current();
+            span.record("prompt_tokens", usage.prompt_tokens);
+            span.record("completion_tokens", usage.completion_tokens);
+        }
+
+        tracing::debug!("Received LLM response: {} characters", content.len());
+
+        let content = content.trim().to_string();
+        match (&self.token_budget, &usage) {
+            (Some(token_budget), Some(usage)) => token_budget.record(u64::from(usage.total_tokens)),
+            _ => return Ok(self.charge_estimate(prompt, content)),
+        }
+        Ok(content)
+    }
+
+    /// Whether completions are streamed rather than sent in one response.
+    fn streams(&self) -> bool {
+        self.streaming || self.live
+    }
+
+    fn request_timed_out(&self) -> DocTreeError {
+        DocTreeError::timeout(format!("LLM request exceeded {}s", self.retry_policy.request_timeout.as_secs()))
+    }
+
+    /// Streams a completion into the live output. The request timeout only
+    /// covers the wait for the first chunk; after that the request fails
+    /// when the server sends nothing for the chunk timeout, e.g. a stalled
+    /// local server, however long the whole answer takes.
+    async fn stream_completion(
+        &self,
+        client: &Client<OpenAIConfig>,
+        http_client: &reqwest::Client,
+        mut request: CreateChatCompletionRequest,
+    ) -> Result<(String, Option<CompletionUsage>)> {
+        request.stream = Some(true);
+        request.stream_options = Some(ChatCompletionStreamOptions { include_usage: true });
+        let first_chunk_by = Instant::now() + self.retry_policy.request_timeout;
+        let mut stream = timeout_at(first_chunk_by.into(), Self::send_chat_request(client, http_client, &request))
+            .await
+            .unwrap_or_else(|_| Err(self.request_timed_out()))?
+            .bytes_stream()
+            .eventsource();
+
+        let mut content = String::new();
+        let streamed: Result<Option<CompletionUsage>> = async {
+            let mut usage = None;
+            let mut started = false;
+            loop {
+                let next = if started {
+                    timeout(self.stream_chunk_timeout, stream.next()).await
+                } else {
+                    timeout_at(first_chunk_by.into(), stream.next()).await
+                };
+                let event = match next {
+                    Ok(Some(event)) => event.map_err(|e| OpenAIError::StreamError(e.to_string()))?,
+                    Ok(None) => break,
+                    Err(_) if !started => return Err(self.request_timed_out()),
+                    Err(_) => {
+                        return Err(DocTreeError::timeout(format!(
+                            "LLM stream stalled: nothing received for {}s",
+                            self.stream_chunk_timeout.as_secs()
+                        )))
+                    }
+                };
+                started = true;
+                if event.data == "[DONE]" {
+                    break;
+                }
+                if let Ok(wrapped) = serde_json::from_str::<WrappedError>(&event.data) {
+                    return Err(OpenAIError::ApiError(wrapped.error).into());
+                }
+                let chunk: CreateChatCompletionStreamResponse = serde_json::from_str(&event.data)?;
+                usage = chunk.usage.or(usage);
+                for piece in chunk.choices.into_iter().filter_map(|choice| choice.delta.content) {
+                    self.show_live(&piece);
+                    content.push_str(&piece);
+                }
+            }
+            Ok(usage)
+        }
+        .await;
+        let usage = match streamed {
+            Ok(usage) => usage,
+            Err(e) => {
+                self.interrupt_live(&content);
+                return Err(e);
+            }
+        };
+
+        if content.is_empty() {
+            return Err(DocTreeError::summarizer("No response content from LLM"));
+        }
+        Ok((content, usage))
+    }
+
+    /// Posts a chat request like the SDK would. An error status fails with
+    /// `DocTreeError::Api`, carrying the server's `Retry-After`.
+    async fn send_chat_request(
+        client: &Client<OpenAIConfig>,
+        http_client: &reqwest::Client,
+        request: &CreateChatCompletionRequest,
+    ) -> Result<reqwest::Response> {
+        let config = client.config();
+        let response = http_client
+            .post(config.url("/chat/completions"))
+            .query(&config.query())
+            .headers(config.headers())
+            .json(request)
+            .send()
+            .await
+            .map_err(OpenAIError::Reqwest)?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        let retry_after = parse_retry_after(response.headers());
+        let body = response.bytes().await.map_err(OpenAIError::Reqwest)?;
+        // Server errors are not always JSON
+        let error = serde_json::from_slice::<WrappedError>(&body).map(|wrapped| wrapped.error).unwrap_or_else(|_| ApiError {
+            message: String::from_utf8_lossy(&body).into_owned(),
+            r#type: None,
+            param: None,
+            code: None,
+        });
+        Err(DocTreeError::Api { status: status.as_u16(), retry_after, error })
+    }
+
+    fn show_live(&self, piece: &str) {
+        if let Some(stream_output) = self.stream_output.as_ref().filter(|_| self.live) {
+            stream_output(piece);
+        }
+    }
+
+    /// Marks the end of a streamed answer that broke off, so a retry's answer
+    /// is not read as its continuation.
+    fn interrupt_live(&self, shown: &str) {
+        if !shown.is_empty() {
+            self.show_live(LIVE_INTERRUPTED);
+        }
+    }
+
+    /// A response from a backend without a server, shown as live output in
+    /// one piece and counted against the token budget by estimate.
+    fn local_response(&self, prompt: &str, response: String) -> String {
+        self.show_live(&response);
+        self.charge_estimate(prompt, response)
+    }
+
+    /// The chat request sent for a prompt, directly or in a batch job.
+    fn chat_request(prompt: &str, model: &str, max_tokens: u32) -> CreateChatCompletionRequest {
         let messages = vec![
             ChatCompletionRequestMessage::System(ChatCompletionRequestSystemMessage {
                 content: ChatCompletionRequestSystemMessageContent::Text("You are a helpful assistant that generates concise, accurate documentation. Always respond in Markdown format. Focus on clarity and brevity.".to_string()),
@@ -130,50 +971,82 @@ impl LanguageModelClient {
             }),
         ];
 
-        let request = CreateChatCompletionRequest {
-            model: self.model_name.clone(),
+        CreateChatCompletionRequest {
+            model: model.to_string(),
             messages,
-            max_completion_tokens: Some(1000),
-            temperature: Some(0.3),
-            top_p: Some(0.9),
+            max_completion_tokens: Some(max_tokens),
+            temperature: Some(TEMPERATURE),
+            top_p: Some(TOP_P),
             n: Some(1),
             stream: Some(false),
             stop: None,
             presence_penalty: Some(0.0),
             frequency_penalty: Some(0.0),
             ..Default::default()
-        };
-
-        log::debug!("Sending request to LLM with model: {}", self.model_name);
-
-        let response = self.client.chat().create(request).await?;
+        }
+    }
 
-        let content = response
-            .choices
-            .first()
-            .and_then(|choice| choice.message.content.as_ref())
-            .ok_or_else(|| DocTreeError::summarizer("No response content from LLM"))?;
+    /// Counts a completion against the token budget by estimate, for
+    /// backends and servers that report no usage.
+    fn charge_estimate(&self, prompt: &str, response: String) -> String {
+        if let Some(token_budget) = &self.token_budget {
+            token_budget.record(estimate_tokens(prompt) + estimate_tokens(&response));
+        }
+        response
+    }
 
-        log::debug!("Received LLM response: {} characters", content.len());
+    fn mock_completion(prompt: &str) -> String {
+        let instruction = prompt.lines().next().unwrap_or_default();
+        let hash = FileHasher::compute_content_hash(instruction);
+        let response = format!("Canned response {}.", &hash[..12]);
+        if prompt.starts_with(STRUCTURED_PROMPT_PREFIX) {
+            return serde_json::json!({ "purpose": response }).to_string();
+        }
+        if prompt.contains(BULLET_INSTRUCTION) {
+            return format!("- {response}");
+        }
+        response
+    }
 
-        Ok(content.trim().to_string())
+    /// A small unit vector derived from the text's hash.
+    fn mock_embedding(text: &str) -> Vec<f32> {
+        let hash = FileHasher::compute_content_hash(text);
+        let vector: Vec<f32> = hash
+            .as_bytes()
+            .chunks(8)
+            .map(|chunk| {
+                let value = u32::from_str_radix(std::str::from_utf8(chunk).unwrap_or("0"), 16).unwrap_or(0);
+                value as f32 / u32::MAX as f32 - 0.5
+            })
+            .collect();
+        let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt().max(f32::EPSILON);
+        vector.into_iter().map(|v| v / norm).collect()
     }
 
     pub async fn test_connection(&self) -> Result<()> {
-        log::info!("Testing LLM connection...");
+        tracing::info!("Testing LLM connection...");
 
         let test_prompt = "Respond with exactly: 'Connection test successful'";
 
-        match self.generate_completion(test_prompt).await {
-            Ok(response) => {
-                log::info!("LLM connection test successful. Response: {response}");
-                Ok(())
-            }
-            Err(e) => {
-                log::error!("LLM connection test failed: {e}");
-                Err(e)
+        let mut models = vec![&self.summary_model];
+        if self.synthesis_model != self.summary_model {
+            models.push(&self.synthesis_model);
+        }
+
+        for model in models {
+            // Always a real request, never a cached response
+            match self.request_completion(test_prompt, model, DEFAULT_MAX_COMPLETION_TOKENS).await {
+                Ok(response) => {
+                    tracing::info!("LLM connection test successful for {model}. Response: {response}");
+                }
+                Err(e) => {
+                    tracing::error!("LLM connection test failed for {model}: {e}");
+                    return Err(e);
+                }
             }
         }
+
+        Ok(())
     }
 }
 
@@ -181,6 +1054,163 @@ impl LanguageModelClient {
 mod tests {
     use super::*;
     use crate::config::Config;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_backoff_delay_is_capped() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+            request_timeout: Duration::from_secs(30),
+        };
+
+        let first = policy.backoff_delay(1);
+        assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+
+        let late = policy.backoff_delay(10);
+        assert!(late >= Duration::from_millis(500) && late <= Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let headers = |pairs: &[(&'static str, &'static str)]| {
+            let mut headers = HeaderMap::new();
+            for (name, value) in pairs {
+                headers.insert(*name, HeaderValue::from_static(value));
+            }
+            headers
+        };
+        assert_eq!(parse_retry_after(&headers(&[("retry-after", "20")])), Some(Duration::from_secs(20)));
+        assert_eq!(
+            parse_retry_after(&headers(&[("retry-after", "20"), ("retry-after-ms", "500")])),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(parse_retry_after(&headers(&[("retry-after", "Wed, 21 Oct 2026 07:28:00 GMT")])), None);
+        assert_eq!(parse_retry_after(&headers(&[])), None);
+    }
+
+    #[tokio::test]
+    async fn test_mock_client_is_deterministic() {
+        let client = LanguageModelClient::mock(&Config::default());
+
+        let first = client.generate_file_summary(Path::new("main.rs"), "fn main() {}").await.unwrap();
+        let edited = client.generate_file_summary(Path::new("main.rs"), "fn main() { run() }").await.unwrap();
+        let other = client.generate_file_summary(Path::new("lib.rs"), "fn main() {}").await.unwrap();
+
+        assert_eq!(first, edited);
+        assert_ne!(first, other);
+        assert!(first.starts_with("Canned response "));
+        assert!(client.test_connection().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_summaries_are_rewritten_to_fit_their_budget() -> Result<()> {
+        let config = Config {
+            summary_budgets: SummaryBudgets {
+                file_max_words: Some(5),
+                root_max_words: Some(3),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let provider = crate::testing::ScriptedProvider::new()
+            .respond("Rewrite it to fit", "Parses CLI flags.")
+            .fallback("Parses CLI flags and loads the configuration from the environment.");
+        let client = provider.client(&config);
+
+        let summary = client.generate_file_summary(Path::new("cli.rs"), "fn parse() {}").await?;
+        assert_eq!(summary, "Parses CLI flags.");
+        let prompts = provider.prompts();
+        assert_eq!(prompts.len(), 2);
+        assert!(prompts[0].ends_with("Use at most 5 words."));
+        assert!(prompts[1].contains("(it has 10 words, the limit is 5)"));
+
+        // Directories have no budget here; the root's answers never fit, so it is trimmed
+        let provider = crate::testing::ScriptedProvider::new().fallback("A CLI tool. It parses flags.");
+        let client = provider.client(&config);
+        let children = ["Parses CLI flags.".to_string()];
+        assert_eq!(client.generate_directory_summary("src", &children).await?, "A CLI tool. It parses flags.");
+        assert_eq!(client.generate_root_summary("project root", &children).await?, "A CLI tool.");
+        assert_eq!(provider.prompts().len(), 1 + 1 + BUDGET_RETRIES);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_repeated_requests_are_answered_from_the_response_cache() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let provider = crate::testing::ScriptedProvider::new().fallback("Parses CLI flags.");
+        let mut client = provider.client(&Config::default());
+        client.set_response_cache(ResponseCache::new(temp_dir.path(), 3600));
+
+        for _ in 0..2 {
+            assert_eq!(client.generate_file_summary(Path::new("cli.rs"), "fn parse() {}").await?, "Parses CLI flags.");
+        }
+        assert_eq!(provider.prompts().len(), 1);
+        client.generate_file_summary(Path::new("cli.rs"), "fn parse() -> bool {}").await?;
+        assert_eq!(provider.prompts().len(), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_only_long_answers_go_to_the_stream_output() -> Result<()> {
+        let provider = crate::testing::ScriptedProvider::new()
+            .respond("File: cli.rs", "Parses CLI flags.")
+            .fallback("# Projekt");
+        let mut client = provider.client(&Config::default());
+        let shown = Arc::new(std::sync::Mutex::new(String::new()));
+        let sink = Arc::clone(&shown);
+        client.set_stream_output(Arc::new(move |piece: &str| sink.lock().unwrap().push_str(piece)));
+
+        client.generate_file_summary(Path::new("cli.rs"), "fn parse() {}").await?;
+        assert!(shown.lock().unwrap().is_empty());
+        assert_eq!(client.translate_readme("# Project", "de").await?, "# Projekt");
+        assert_eq!(*shown.lock().unwrap(), "# Projekt");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_streamed_answer_may_outlast_the_request_timeout() -> Result<()> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let address = listener.local_addr()?;
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await?;
+            let mut request = Vec::new();
+            let mut buffer = [0; 4096];
+            while !String::from_utf8_lossy(&request).contains("\"stream\":true") {
+                let read = socket.read(&mut buffer).await?;
+                request.extend_from_slice(&buffer[..read]);
+            }
+            socket.write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n").await?;
+            // Slower than the request timeout in total, never stalling for the chunk timeout
+            for piece in ["Parses ", "CLI ", "flags."] {
+                let chunk = serde_json::json!({
+                    "id": "1", "object": "chat.completion.chunk", "created": 0, "model": "test-model",
+                    "choices": [{"index": 0, "delta": {"content": piece}, "finish_reason": null}],
+                });
+                socket.write_all(format!("data: {chunk}\n\n").as_bytes()).await?;
+                sleep(Duration::from_millis(600)).await;
+            }
+            socket.write_all(b"data: [DONE]\n\n").await
+        });
+
+        let config = Config {
+            openai_api_base: format!("http://{address}/v1"),
+            openai_api_key: "test".to_string(),
+            openai_model_name: "test-model".to_string(),
+            request_timeout_secs: 1,
+            stream_chunk_timeout_secs: 1,
+            stream_completions: true,
+            max_retries: 0,
+            ..Default::default()
+        };
+        let client = LanguageModelClient::new(&config)?;
+        assert_eq!(client.request_completion("Summarize", "test-model", 100).await?, "Parses CLI flags.");
+        server.await.unwrap()?;
+        Ok(())
+    }
 
     async fn create_test_client() -> LanguageModelClient {
         // Note: These tests require a running local LLM server
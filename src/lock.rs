@@ -0,0 +1,115 @@
+use crate::cancel::CancellationToken;
+use crate::error::{DocTreeError, Result};
+use crate::file_lock::FileLock;
+use crate::format::current_timestamp;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// File in the cache directory held by the run that writes to the cache.
+pub const RUN_LOCK_FILE: &str = "run.lock";
+
+/// How often a run waiting for the lock checks it again.
+const WAIT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The run holding the lock, as written to the lock file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockHolder {
+    pub pid: u32,
+    /// When the run took the lock, in seconds since the Unix epoch.
+    pub started: u64,
+}
+
+/// Keeps other runs from writing to the same cache (summaries, README
+/// mappings, run state) at the same time. Held as a `FileLock`, so a run
+/// that crashes or is killed releases it too. Released when dropped.
+#[derive(Debug)]
+pub struct RunLock {
+    _lock: FileLock,
+}
+
+impl RunLock {
+    /// Takes the lock, or fails naming the run holding it.
+    pub fn acquire(cache_dir: &Path) -> Result<Self> {
+        match Self::try_acquire(cache_dir)? {
+            Ok(lock) => Ok(lock),
+            Err(holder) => Err(DocTreeError::cache(format!(
+                "Another doctreeai run{} is using {}; pass --wait to run after it",
+                describe(holder.as_ref()),
+                cache_dir.display()
+            ))),
+        }
+    }
+
+    /// Takes the lock once the run holding it finishes, or fails with
+    /// `DocTreeError::Cancelled` when cancelled first.
+    pub async fn wait(cache_dir: &Path, cancellation: &CancellationToken) -> Result<Self> {
+        let mut waiting_for = None;
+        loop {
+            let holder = match Self::try_acquire(cache_dir)? {
+                Ok(lock) => return Ok(lock),
+                Err(holder) => holder,
+            };
+            if holder.is_some() && waiting_for != holder {
+                tracing::info!("Waiting for the doctreeai run{} to finish", describe(holder.as_ref()));
+                waiting_for = holder;
+            }
+            cancellation
+                .run(async {
+                    tokio::time::sleep(WAIT_POLL_INTERVAL).await;
+                    Ok(())
+                })
+                .await?;
+        }
+    }
+
+    /// The lock, or who holds it when the lock file says.
+    fn try_acquire(cache_dir: &Path) -> Result<std::result::Result<Self, Option<LockHolder>>> {
+        let path = cache_dir.join(RUN_LOCK_FILE);
+        let Some(mut lock) = FileLock::try_acquire(&path)? else {
+            return Ok(Err(read_holder(&path)));
+        };
+        let holder = LockHolder { pid: std::process::id(), started: current_timestamp() };
+        lock.write(&serde_json::to_string(&holder)?)?;
+        Ok(Ok(Self { _lock: lock }))
+    }
+}
+
+/// None while the holder is still writing the lock file.
+fn read_holder(path: &Path) -> Option<LockHolder> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+fn describe(holder: Option<&LockHolder>) -> String {
+    holder.map_or_else(String::new, |holder| {
+        format!(" (pid {}, started {}s ago)", holder.pid, current_timestamp().saturating_sub(holder.started))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_lock_excludes_other_runs_until_dropped() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let cache_dir = temp_dir.path().join(".test_cache");
+
+        let lock = RunLock::acquire(&cache_dir)?;
+        let error = RunLock::acquire(&cache_dir).unwrap_err().to_string();
+        assert!(error.contains(&format!("pid {}", std::process::id())));
+        drop(lock);
+        drop(RunLock::acquire(&cache_dir)?);
+
+        // A lock file left by a run whose process is gone, or unreadable,
+        // holds nothing
+        let gone = LockHolder { pid: u32::MAX, started: current_timestamp() };
+        for left_behind in [serde_json::to_string(&gone)?, String::new()] {
+            fs::write(cache_dir.join(RUN_LOCK_FILE), left_behind)?;
+            let _lock = RunLock::acquire(&cache_dir)?;
+            assert_eq!(read_holder(&cache_dir.join(RUN_LOCK_FILE)).map(|holder| holder.pid), Some(std::process::id()));
+        }
+        Ok(())
+    }
+}
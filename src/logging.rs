@@ -0,0 +1,70 @@
+use crate::error::{DocTreeError, Result};
+use std::fmt;
+use std::str::FromStr;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::EnvFilter;
+
+/// How log lines are written to stderr, selected with `--log-format`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    /// One JSON object per line. Closing file, directory, and LLM call spans
+    /// are logged too, with their timings, so slow files can be found.
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = DocTreeError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            other => Err(DocTreeError::config(format!(
+                "Unknown log format '{other}' (expected text or json)"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LogFormat::Text => "text",
+            LogFormat::Json => "json",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Installs the global tracing subscriber, which also receives records from
+/// dependencies using `log`. `RUST_LOG` takes precedence over `verbose`.
+pub fn init_logging(verbose: bool, format: LogFormat) {
+    let default_level = if verbose { "debug" } else { "info" };
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_level));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter).with_writer(std::io::stderr);
+
+    match format {
+        LogFormat::Text => builder.init(),
+        LogFormat::Json => builder
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .with_span_events(FmtSpan::CLOSE)
+            .init(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_format_parsing() -> Result<()> {
+        assert_eq!("JSON".parse::<LogFormat>()?, LogFormat::Json);
+        assert_eq!(LogFormat::default().to_string(), "text");
+        assert!("yaml".parse::<LogFormat>().is_err());
+        Ok(())
+    }
+}
@@ -1,14 +1,44 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, CommandFactory, Parser, Subcommand};
 use doctreeai::{
-    cache::CacheManager,
-    config::Config, 
-    error::Result,
+    audit::AuditLog,
+    backup::ReadmeBackups,
+    cache::{parse_duration_secs, CacheManager, SummaryMethod, PACKAGE_CACHE_DIR},
+    cancel::CancellationToken,
+    config::{Config, SettingSource},
+    debt::{DebtOutput, DebtReport, TECH_DEBT_FILE},
+    doctree::{self, DocTree},
+    embeddings::EmbeddingIndex,
+    error::{DocTreeError, Result},
+    estimator::{CostEstimator, TokenPricing},
+    format::{current_timestamp, ReportFormat},
+    graph::DependencyGraph,
+    github::{repository_from_remote, GithubActions, GithubClient, PullRequestDraft, DEFAULT_API_URL},
+    hooks::{GitHook, HookInstaller},
+    keychain::StoredKey,
+    limits::ScanLimits,
     llm::LanguageModelClient,
+    logging::{init_logging, LogFormat},
+    notify::Notifier,
+    provenance::ProvenanceTracker,
     readme::ReadmeManager,
-    readme_validator::ReadmeValidator,
+    readme_validator::{parse_confidence, ReadmeValidator},
+    references::ReferenceGuard,
+    renderer::{MarkdownRenderer, RenderInput, Renderer, RendererRegistry},
+    run_state::{RunState, RunStatus},
+    reporter::{ConsoleReporter, Reporter, ReporterKind},
+    sandbox::{DockerSandbox, SandboxKind},
+    self_doc::SelfDoc,
+    scanner::{DirectoryScanner, ScanFilter},
+    strategy::StrategyKind,
     summarizer::HierarchicalSummarizer,
+    tickets::TicketLinker,
+    tree::SummaryTreeNode,
+    wizard::{self, Endpoint, LOCAL_ENDPOINTS, OPENAI_ENDPOINT},
+    workspace::Workspace,
 };
-use std::path::{Path, PathBuf};
+use std::io::{IsTerminal, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
 
 #[derive(Parser)]
 #[command(name = "doctreeai")]
@@ -20,6 +50,174 @@ struct Cli {
     
     #[arg(short, long, global = true, help = "Enable verbose logging")]
     verbose: bool,
+
+    #[arg(long, global = true, env = "DOCTREEAI_LOG_FORMAT", default_value_t = LogFormat::Text, help = "Log line format: text, or json with per-file and per-LLM-call timings")]
+    log_format: LogFormat,
+
+    #[arg(long, global = true, help = "Never write to the filesystem (no cache, .gitignore, or README changes)")]
+    no_write: bool,
+
+    #[arg(long, global = true, help = "Print raw byte counts and unix timestamps instead of human-readable values")]
+    raw: bool,
+}
+
+#[derive(Args, Clone, Default)]
+struct ScanArgs {
+    #[arg(long, value_delimiter = ',', help = "Only scan files matching these globs (e.g. 'src/**')")]
+    include: Vec<String>,
+    #[arg(long, value_delimiter = ',', help = "Skip files and directories matching these globs")]
+    exclude: Vec<String>,
+    #[arg(long, help = "Abort if the tree is nested deeper than this (default 25)")]
+    max_depth: Option<usize>,
+    #[arg(long, help = "Abort if more than this many files would be scanned (default 10000)")]
+    max_files: Option<usize>,
+    #[arg(long, help = "Abort if one directory holds more than this many files to scan (default 1000)")]
+    max_files_per_dir: Option<usize>,
+    #[arg(long, help = "Disable the depth and file count safeguards")]
+    no_scan_limits: bool,
+    #[arg(long, help = "Follow symlinks (loops are detected and skipped)")]
+    follow_symlinks: bool,
+    #[arg(long, help = "Descend into directories on other filesystems")]
+    cross_filesystems: bool,
+    #[arg(long, help = "Scan only files tracked by git (git ls-files) instead of walking the filesystem")]
+    tracked_only: bool,
+}
+
+impl ScanArgs {
+    /// CLI globs override the environment and doctreeai.toml.
+    fn apply(&self, config: &mut Config) {
+        if !self.include.is_empty() {
+            config.include_patterns = self.include.clone();
+            config.set_source("DOCTREEAI_INCLUDE", SettingSource::Cli);
+        }
+        if !self.exclude.is_empty() {
+            config.exclude_patterns = self.exclude.clone();
+            config.set_source("DOCTREEAI_EXCLUDE", SettingSource::Cli);
+        }
+        if self.max_depth.is_some() {
+            config.scan_limits.max_depth = self.max_depth;
+            config.set_source("DOCTREEAI_MAX_DEPTH", SettingSource::Cli);
+        }
+        if self.max_files.is_some() {
+            config.scan_limits.max_files = self.max_files;
+            config.set_source("DOCTREEAI_MAX_FILES", SettingSource::Cli);
+        }
+        if self.max_files_per_dir.is_some() {
+            config.scan_limits.max_files_per_dir = self.max_files_per_dir;
+            config.set_source("DOCTREEAI_MAX_FILES_PER_DIR", SettingSource::Cli);
+        }
+        if self.no_scan_limits {
+            config.scan_limits = ScanLimits::unlimited();
+            config.set_source("DOCTREEAI_MAX_DEPTH", SettingSource::Cli);
+            config.set_source("DOCTREEAI_MAX_FILES", SettingSource::Cli);
+            config.set_source("DOCTREEAI_MAX_FILES_PER_DIR", SettingSource::Cli);
+        }
+        if self.follow_symlinks {
+            config.walk_options.follow_symlinks = true;
+            config.set_source("DOCTREEAI_FOLLOW_SYMLINKS", SettingSource::Cli);
+        }
+        if self.cross_filesystems {
+            config.walk_options.cross_filesystems = true;
+            config.set_source("DOCTREEAI_CROSS_FILESYSTEMS", SettingSource::Cli);
+        }
+        if self.tracked_only {
+            config.walk_options.tracked_only = true;
+            config.set_source("DOCTREEAI_TRACKED_ONLY", SettingSource::Cli);
+        }
+    }
+
+    /// The same options as command-line arguments, for re-invoking doctreeai.
+    fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if !self.include.is_empty() {
+            args.push(format!("--include={}", self.include.join(",")));
+        }
+        if !self.exclude.is_empty() {
+            args.push(format!("--exclude={}", self.exclude.join(",")));
+        }
+        if let Some(max_depth) = self.max_depth {
+            args.push(format!("--max-depth={max_depth}"));
+        }
+        if let Some(max_files) = self.max_files {
+            args.push(format!("--max-files={max_files}"));
+        }
+        if let Some(max_files_per_dir) = self.max_files_per_dir {
+            args.push(format!("--max-files-per-dir={max_files_per_dir}"));
+        }
+        if self.no_scan_limits {
+            args.push("--no-scan-limits".to_string());
+        }
+        if self.follow_symlinks {
+            args.push("--follow-symlinks".to_string());
+        }
+        if self.cross_filesystems {
+            args.push("--cross-filesystems".to_string());
+        }
+        if self.tracked_only {
+            args.push("--tracked-only".to_string());
+        }
+        args
+    }
+}
+
+#[derive(Subcommand, Clone, Copy)]
+enum IndexAction {
+    #[command(about = "Embed every cached summary from scratch")]
+    Build,
+    #[command(about = "Embed only new and changed summaries and drop removed ones")]
+    Update,
+    #[command(about = "Delete the embedding index")]
+    Clear,
+}
+
+#[derive(Subcommand, Clone, Copy)]
+enum HooksAction {
+    #[command(about = "Write a pre-commit hook (and optionally a pre-push hook) that checks README.md against changed code")]
+    Install {
+        #[arg(long, help = "Also install a pre-push hook checking the commits being pushed")]
+        pre_push: bool,
+        #[arg(long, help = "Stop the commit or push instead of only warning")]
+        block: bool,
+        #[arg(long, help = "Replace hooks that were not written by doctreeai")]
+        force: bool,
+    },
+    #[command(about = "Check the staged or pushed files against README.md (run by the installed hooks)")]
+    Check {
+        #[arg(long, conflicts_with = "pre_push", help = "Check the staged files (default)")]
+        pre_commit: bool,
+        #[arg(long, help = "Check the commits not yet on the upstream branch")]
+        pre_push: bool,
+        #[arg(long, help = "Exit with 1 when README-mapped code changed without a README.md update")]
+        block: bool,
+    },
+}
+
+#[derive(Subcommand, Clone)]
+enum ConfigAction {
+    #[command(about = "Show every setting's effective value and where it came from (env, file, or default)")]
+    List,
+    #[command(about = "Print one setting's effective value")]
+    Get {
+        #[arg(help = "Environment variable (e.g. DOCTREEAI_EXCLUDE) or project file key (e.g. scan.exclude)")]
+        name: String,
+    },
+    #[command(about = "Write a setting to the project's doctreeai.toml")]
+    Set {
+        #[arg(help = "Project file key: scan.include, scan.exclude, scan.extra_extensions, scan.extra_filenames, readme.required_sections, or submodules.<path>")]
+        key: String,
+        #[arg(help = "New value; lists are comma-separated, and an empty value removes the key")]
+        value: String,
+    },
+    #[command(about = "Store an API key in the OS keychain, read from stdin")]
+    SetKey {
+        #[arg(default_value = "openai", help = "Key to store: openai (OPENAI_API_KEY) or github (GITHUB_TOKEN)")]
+        key: StoredKey,
+    },
+    #[command(about = "Remove an API key from the OS keychain")]
+    DeleteKey {
+        #[arg(default_value = "openai", help = "Key to remove: openai or github")]
+        key: StoredKey,
+    },
 }
 
 #[derive(Subcommand)]
@@ -28,6 +226,8 @@ enum Commands {
     Init {
         #[arg(short, long, help = "Target directory path")]
         path: Option<PathBuf>,
+        #[arg(short, long, help = "Find an LLM server, pick a model, write .env and doctreeai.toml, and test the connection")]
+        interactive: bool,
     },
     #[command(about = "Execute the main documentation generation and update logic")]
     Run {
@@ -37,16 +237,211 @@ enum Commands {
         force: bool,
         #[arg(long, help = "Show the tree structure and summaries without updating README")]
         dry_run: bool,
+        #[arg(long, value_name = "PATH", help = "Only summarize this subtree (and its ancestors) and validate the README lines mapped to it")]
+        only: Option<PathBuf>,
+        #[arg(long, conflicts_with = "only", help = "Document each workspace package separately, with its own README and cache")]
+        workspace: bool,
+        #[arg(long, env = "DOCTREEAI_STRATEGY", default_value_t = StrategyKind::Hierarchical, help = "How summaries are built: hierarchical (files, directories, root) or flat (files, then root)")]
+        strategy: StrategyKind,
+        #[arg(long, help = "Use short prompts, strict output checks, and non-LLM summaries of trivial files (for 3-8B models)")]
+        small_model: bool,
+        #[arg(long, help = "Summarize from code structure (symbols, imports, size) without the LLM, e.g. when no server is reachable")]
+        offline: bool,
+        #[arg(long, conflicts_with_all = ["force", "offline", "workspace", "output", "sandbox"], help = "Skip the LLM and only report cached summaries and README lines that are stale; exits with 1 if any are (for CI)")]
+        no_llm: bool,
+        #[arg(long, value_name = "DURATION", value_parser = parse_duration_secs, help = "Re-summarize cache entries older than this (e.g. 90d), even if unchanged")]
+        refresh_older_than: Option<u64>,
+        #[arg(long, value_name = "SCORE", env = "DOCTREEAI_MIN_CONFIDENCE", default_value_t = 0.0, value_parser = parse_confidence, help = "Hide README suggestions the LLM is less confident about than this (0.0-1.0)")]
+        min_confidence: f32,
+        #[arg(long, env = "DOCTREEAI_DRAFT_SECTIONS", help = "Ask the LLM to draft the required README sections that are missing")]
+        draft_sections: bool,
+        #[arg(long, value_name = "TOKENS", help = "Stop once LLM calls have used this many prompt and completion tokens, keeping completed summaries (resume with 'doctreeai resume')")]
+        max_tokens_per_run: Option<u64>,
+        #[arg(long, conflicts_with_all = ["offline", "no_llm"], help = "Send the file summary requests as one batch job first (OpenAI Batch API: cheaper, but can take hours), then run from the responses")]
+        batch: bool,
+        #[arg(short, long, help = "Apply README changes without showing a confirmation prompt")]
+        yes: bool,
+        #[arg(short, long, value_name = "PATH", conflicts_with_all = ["workspace", "sandbox"], help = "Write the updated document to this file ('-' for stdout) instead of changing README.md")]
+        output: Option<PathBuf>,
+        #[arg(long, alias = "report", default_value = "text", help = "Format of the validation report: text, markdown, json, html, or sarif")]
+        format: String,
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["workspace", "sandbox"], help = "Write the validation report to this file instead of stdout (e.g. a .sarif file for code scanning)")]
+        report_file: Option<PathBuf>,
+        #[arg(long, conflicts_with = "sandbox", help = "Report suggestions as GitHub Actions annotations on README.md and add the report to the job summary")]
+        github: bool,
+        #[arg(long, default_value_t = ReporterKind::Console, help = "Where progress goes: console, json (JSON lines), or silent")]
+        reporter: ReporterKind,
+        #[command(flatten)]
+        scan: ScanArgs,
+        #[arg(long, help = "Run inside a sandbox (docker) with the project mounted read-only")]
+        sandbox: Option<SandboxKind>,
+        #[arg(long, help = "If another run is using the cache, wait for it to finish instead of failing")]
+        wait: bool,
+    },
+    #[command(about = "Continue the last interrupted or partially failed run where it stopped")]
+    Resume {
+        #[arg(short, long, help = "Target directory path")]
+        path: Option<PathBuf>,
+        #[arg(short, long, help = "Apply README changes without showing a confirmation prompt")]
+        yes: bool,
+        #[arg(long, default_value = "text", help = "Format of the validation report: text, markdown, json, html, or sarif")]
+        format: String,
+        #[arg(long, default_value_t = ReporterKind::Console, help = "Where progress goes: console, json (JSON lines), or silent")]
+        reporter: ReporterKind,
+        #[arg(long, help = "If another run is using the cache, wait for it to finish instead of failing")]
+        wait: bool,
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+    #[command(about = "Run doctreeai and open a GitHub pull request with the updated documentation")]
+    Pr {
+        #[arg(short, long, help = "Target directory path")]
+        path: Option<PathBuf>,
+        #[arg(long, env = "GITHUB_TOKEN", hide_env_values = true, help = "GitHub token allowed to push branches and open pull requests")]
+        token: Option<String>,
+        #[arg(long, env = "GITHUB_REPOSITORY", value_name = "OWNER/NAME", help = "Repository to open the pull request in (default: from the origin remote)")]
+        repo: Option<String>,
+        #[arg(long, help = "Branch to merge into (default: the repository's default branch)")]
+        base: Option<String>,
+        #[arg(long, help = "Branch to create for the changes (default: doctreeai/docs-<timestamp>)")]
+        branch: Option<String>,
+        #[arg(long, env = "GITHUB_API_URL", default_value = DEFAULT_API_URL, help = "GitHub API URL, for GitHub Enterprise")]
+        api_url: String,
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+    #[command(about = "Summarize again only the files whose summaries failed in earlier runs")]
+    RetryFailed {
+        #[arg(short, long, help = "Target directory path")]
+        path: Option<PathBuf>,
+    },
+    #[command(about = "Estimate LLM calls, tokens, and cost of a run without calling the LLM")]
+    Estimate {
+        #[arg(short, long, help = "Target directory path")]
+        path: Option<PathBuf>,
+        #[arg(long, help = "Estimate as if all cached content were ignored")]
+        force: bool,
+        #[arg(long, env = "DOCTREEAI_INPUT_PRICE", default_value_t = 0.0, help = "Price per million input tokens")]
+        input_price: f64,
+        #[arg(long, env = "DOCTREEAI_OUTPUT_PRICE", default_value_t = 0.0, help = "Price per million output tokens")]
+        output_price: f64,
+        #[command(flatten)]
+        scan: ScanArgs,
+        #[arg(long, default_value_t = ReporterKind::Console, help = "Where progress goes: console, json (JSON lines), or silent")]
+        reporter: ReporterKind,
+    },
+    #[command(about = "Check README.md for paths, binaries, subcommands, and flags that don't exist, without the LLM")]
+    Lint {
+        #[arg(short, long, help = "Target directory path")]
+        path: Option<PathBuf>,
+        #[arg(long, default_value = "text", help = "Output format: text or json")]
+        format: String,
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+    #[command(about = "Show which project files import which, without the LLM")]
+    Graph {
+        #[arg(short, long, help = "Target directory path")]
+        path: Option<PathBuf>,
+        #[arg(long, help = "Print the graph in Graphviz DOT format")]
+        dot: bool,
+        #[command(flatten)]
+        scan: ScanArgs,
+    },
+    #[command(about = "Check README.md for the required sections configured for the project")]
+    Check {
+        #[arg(short, long, help = "Target directory path")]
+        path: Option<PathBuf>,
+    },
+    #[command(about = "Edit a cached summary in $EDITOR and pin it")]
+    Edit {
+        #[arg(help = "File or directory whose summary should be edited")]
+        target: PathBuf,
+        #[arg(short, long, help = "Target directory path")]
+        path: Option<PathBuf>,
+    },
+    #[command(about = "Manage the embedding index of cached summaries")]
+    Index {
+        #[command(subcommand)]
+        action: IndexAction,
+        #[arg(short, long, global = true, help = "Target directory path")]
+        path: Option<PathBuf>,
+    },
+    #[command(about = "Install git hooks that warn when README-described code changes without README updates")]
+    Hooks {
+        #[command(subcommand)]
+        action: HooksAction,
+        #[arg(short, long, global = true, help = "Target directory path")]
+        path: Option<PathBuf>,
     },
     #[command(about = "Remove the .doctreeai_cache/ directory")]
     Clean {
         #[arg(short, long, help = "Target directory path")]
         path: Option<PathBuf>,
     },
+    #[command(about = "Print the cached summary tree with each entry's freshness")]
+    Tree {
+        #[arg(short, long, help = "Target directory path")]
+        path: Option<PathBuf>,
+        #[arg(long, help = "Only print this many levels below the project root")]
+        depth: Option<usize>,
+        #[arg(long, default_value = "text", help = "Output format: text, markdown, json, html, or sarif")]
+        format: String,
+    },
+    #[command(about = "Restore README.md from the backup taken before doctreeai last wrote it")]
+    Rollback {
+        #[arg(short, long, help = "Target directory path")]
+        path: Option<PathBuf>,
+        #[arg(long, help = "List the available backups instead of restoring one")]
+        list: bool,
+    },
+    #[command(about = "Show one file's or directory's cached summary, freshness, and mapped README lines")]
+    Show {
+        #[arg(help = "File or directory to inspect, relative to the project root")]
+        target: PathBuf,
+        #[arg(short, long, help = "Target directory path")]
+        path: Option<PathBuf>,
+    },
     #[command(about = "Show information about the current README and cache")]
     Info {
         #[arg(short, long, help = "Target directory path")]
         path: Option<PathBuf>,
+        #[arg(long, help = "List every resolved setting and its source (default, file, env, cli)")]
+        settings: bool,
+    },
+    #[command(about = "Document doctreeai's own source with a mock LLM and check it against golden files")]
+    SelfDoc {
+        #[arg(short, long, help = "doctreeai checkout to use instead of the one this binary was built from")]
+        path: Option<PathBuf>,
+        #[arg(long, help = "Overwrite the golden files with the current output")]
+        bless: bool,
+    },
+    #[command(about = "List the models the configured endpoint serves, with context windows where known")]
+    Models {
+        #[arg(short, long, help = "Target directory path")]
+        path: Option<PathBuf>,
+    },
+    #[command(about = "Check the configuration, LLM endpoint, models, cache, and permissions, with fixes for each problem")]
+    Doctor {
+        #[arg(short, long, help = "Target directory path")]
+        path: Option<PathBuf>,
+    },
+    #[command(about = "Manage doctreeai settings and stored API keys")]
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+        #[arg(short, long, global = true, help = "Target directory path")]
+        path: Option<PathBuf>,
+    },
+    #[command(about = "Print a shell completion script, e.g. 'doctreeai completions bash > /etc/bash_completion.d/doctreeai'")]
+    Completions {
+        #[arg(value_enum, help = "Shell to complete in")]
+        shell: clap_complete::Shell,
+    },
+    #[command(about = "Print the man page, or write one per subcommand to a directory")]
+    Manpage {
+        #[arg(long, value_name = "DIR", help = "Write doctreeai.1 and a page for each subcommand (doctreeai-run.1, ...) here")]
+        out_dir: Option<PathBuf>,
     },
     #[command(about = "Test connection to the configured LLM")]
     Test {
@@ -59,33 +454,216 @@ enum Commands {
 async fn main() -> Result<()> {
     let cli = Cli::parse();
     
-    // Initialize logging
-    if cli.verbose {
-        env_logger::Builder::from_default_env()
-            .filter_level(log::LevelFilter::Debug)
-            .init();
-    } else {
-        env_logger::Builder::from_default_env()
-            .filter_level(log::LevelFilter::Info)
-            .init();
-    }
+    init_logging(cli.verbose, cli.log_format);
     
+    if cli.no_write
+        && matches!(
+            cli.command,
+            Commands::Init { .. }
+                | Commands::Edit { .. }
+                | Commands::Clean { .. }
+                | Commands::Rollback { list: false, .. }
+                | Commands::Index { .. }
+                | Commands::Hooks { action: HooksAction::Install { .. }, .. }
+                | Commands::RetryFailed { .. }
+                | Commands::SelfDoc { bless: true, .. }
+                | Commands::Config { action: ConfigAction::Set { .. }, .. }
+                | Commands::Manpage { out_dir: Some(_) }
+        )
+    {
+        return Err(DocTreeError::config(
+            "This command modifies files and cannot be used with --no-write",
+        ));
+    }
+
     match &cli.command {
-        Commands::Init { path } => {
+        Commands::Init { path, interactive } => {
+            let target_path = path.clone().unwrap_or_else(|| std::env::current_dir().unwrap());
+            if *interactive {
+                init_wizard(&target_path).await?;
+            }
+            init_command(&target_path).await?;
+            if *interactive {
+                test_command().await?;
+            }
+            Ok(())
+        }
+        Commands::Run { path, force, dry_run, only, workspace, strategy, small_model, offline, no_llm, refresh_older_than, min_confidence, draft_sections, max_tokens_per_run, batch, yes, output, format, report_file, github, reporter, scan, sandbox, wait } => {
+            let target_path = path.clone().unwrap_or_else(|| std::env::current_dir().unwrap());
+            let scope = only
+                .as_deref()
+                .map(|only| scope_path(&target_path, only))
+                .transpose()?;
+            let options = RunOptions {
+                force: *force,
+                dry_run: *dry_run,
+                scope,
+                workspace: *workspace,
+                strategy: *strategy,
+                small_model: *small_model,
+                offline: *offline,
+                refresh_older_than: *refresh_older_than,
+                min_confidence: *min_confidence,
+                draft_sections: *draft_sections,
+                max_tokens_per_run: *max_tokens_per_run,
+                batch: *batch,
+                resume: false,
+                wait: *wait,
+                yes: *yes,
+                output: output.clone(),
+                report: format.clone(),
+                report_file: report_file.clone(),
+                github: *github,
+                reporter: Arc::from(reporter.build()),
+                reporter_kind: *reporter,
+                cancellation: CancellationToken::new(),
+                no_write: cli.no_write,
+                format: ReportFormat::new(cli.raw),
+            };
+            if sandbox.is_none() {
+                cancel_on_ctrl_c(&options);
+            }
+            match sandbox {
+                Some(SandboxKind::Docker) => sandboxed_run_command(&target_path, &options, scan, cli.verbose, cli.log_format),
+                None if *no_llm => no_llm_command(&target_path, &options),
+                None if options.workspace => workspace_run_command(&target_path, &options, scan).await,
+                None => run_command(&target_path, &options, scan, None).await,
+            }
+        }
+        Commands::Resume { path, yes, format, reporter, wait, scan } => {
+            let target_path = path.clone().unwrap_or_else(|| std::env::current_dir().unwrap());
+            let options = RunOptions {
+                force: false,
+                dry_run: false,
+                scope: None,
+                workspace: false,
+                strategy: StrategyKind::default(),
+                small_model: false,
+                offline: false,
+                refresh_older_than: None,
+                min_confidence: 0.0,
+                draft_sections: false,
+                max_tokens_per_run: None,
+                batch: false,
+                resume: true,
+                wait: *wait,
+                yes: *yes,
+                output: None,
+                report: format.clone(),
+                report_file: None,
+                github: false,
+                reporter: Arc::from(reporter.build()),
+                reporter_kind: *reporter,
+                cancellation: CancellationToken::new(),
+                no_write: cli.no_write,
+                format: ReportFormat::new(cli.raw),
+            };
+            resume_command(&target_path, options, scan).await
+        }
+        Commands::Pr { path, token, repo, base, branch, api_url, scan } => {
+            let target_path = path.clone().unwrap_or_else(|| std::env::current_dir().unwrap());
+            let target = PullRequestTarget {
+                token: token.clone().or_else(|| StoredKey::Github.load()).unwrap_or_default(),
+                repository: repo.clone(),
+                base: base.clone(),
+                branch: branch.clone(),
+                api_url: api_url.clone(),
+            };
+            pr_command(&target_path, &target, scan, cli.no_write).await
+        }
+        Commands::RetryFailed { path } => {
+            let target_path = path.clone().unwrap_or_else(|| std::env::current_dir().unwrap());
+            retry_failed_command(&target_path).await
+        }
+        Commands::Estimate { path, force, input_price, output_price, scan, reporter } => {
+            let target_path = path.clone().unwrap_or_else(|| std::env::current_dir().unwrap());
+            let pricing = TokenPricing {
+                input_per_million: *input_price,
+                output_per_million: *output_price,
+            };
+            estimate_command(&target_path, *force, pricing, scan, reporter.build().as_ref()).await
+        }
+        Commands::Lint { path, format, scan } => {
+            let target_path = path.clone().unwrap_or_else(|| std::env::current_dir().unwrap());
+            lint_command(&target_path, format, scan)
+        }
+        Commands::Graph { path, dot, scan } => {
+            let target_path = path.clone().unwrap_or_else(|| std::env::current_dir().unwrap());
+            graph_command(&target_path, *dot, scan)
+        }
+        Commands::Check { path } => {
+            let target_path = path.clone().unwrap_or_else(|| std::env::current_dir().unwrap());
+            check_command(&target_path)
+        }
+        Commands::Edit { target, path } => {
+            let target_path = path.clone().unwrap_or_else(|| std::env::current_dir().unwrap());
+            edit_command(&target_path, target).await
+        }
+        Commands::Index { action, path } => {
             let target_path = path.clone().unwrap_or_else(|| std::env::current_dir().unwrap());
-            init_command(&target_path).await
+            index_command(&target_path, *action).await
         }
-        Commands::Run { path, force, dry_run } => {
+        Commands::Hooks { action, path } => {
             let target_path = path.clone().unwrap_or_else(|| std::env::current_dir().unwrap());
-            run_command(&target_path, *force, *dry_run).await
+            match *action {
+                HooksAction::Install { pre_push, block, force } => hooks_install_command(&target_path, pre_push, block, force),
+                HooksAction::Check { pre_push, block, .. } => {
+                    let hook = if pre_push { GitHook::PrePush } else { GitHook::PreCommit };
+                    hooks_check_command(&target_path, hook, block)
+                }
+            }
         }
         Commands::Clean { path } => {
             let target_path = path.clone().unwrap_or_else(|| std::env::current_dir().unwrap());
             clean_command(&target_path).await
         }
-        Commands::Info { path } => {
+        Commands::Tree { path, depth, format } => {
+            let target_path = path.clone().unwrap_or_else(|| std::env::current_dir().unwrap());
+            tree_command(&target_path, *depth, format)
+        }
+        Commands::Rollback { path, list } => {
+            let target_path = path.clone().unwrap_or_else(|| std::env::current_dir().unwrap());
+            rollback_command(&target_path, *list, ReportFormat::new(cli.raw))
+        }
+        Commands::Show { target, path } => {
+            let target_path = path.clone().unwrap_or_else(|| std::env::current_dir().unwrap());
+            show_command(&target_path, target, ReportFormat::new(cli.raw))
+        }
+        Commands::Info { path, settings } => {
+            let target_path = path.clone().unwrap_or_else(|| std::env::current_dir().unwrap());
+            info_command(&target_path, *settings, ReportFormat::new(cli.raw)).await
+        }
+        Commands::SelfDoc { path, bless } => {
+            let self_doc = match path {
+                Some(path) => SelfDoc::new(path),
+                None => SelfDoc::from_build()?,
+            };
+            self_doc_command(&self_doc, *bless).await
+        }
+        Commands::Completions { shell } => {
+            // Buffered: clap_complete panics when stdout closes early, e.g. `| head`
+            let mut script = Vec::new();
+            clap_complete::generate(*shell, &mut Cli::command(), "doctreeai", &mut script);
+            Ok(std::io::stdout().write_all(&script)?)
+        }
+        Commands::Manpage { out_dir } => manpage_command(out_dir.as_deref()),
+        Commands::Models { path } => {
+            let target_path = path.clone().unwrap_or_else(|| std::env::current_dir().unwrap());
+            models_command(&target_path).await
+        }
+        Commands::Doctor { path } => {
+            let target_path = path.clone().unwrap_or_else(|| std::env::current_dir().unwrap());
+            doctor_command(&target_path).await
+        }
+        Commands::Config { action, path } => {
             let target_path = path.clone().unwrap_or_else(|| std::env::current_dir().unwrap());
-            info_command(&target_path).await
+            match action {
+                ConfigAction::List => config_list_command(&target_path),
+                ConfigAction::Get { name } => config_get_command(&target_path, name),
+                ConfigAction::Set { key, value } => config_set_command(&target_path, key, value),
+                ConfigAction::SetKey { key } => set_key_command(*key),
+                ConfigAction::DeleteKey { key } => delete_key_command(*key),
+            }
         }
         Commands::Test { path: _ } => {
             test_command().await
@@ -110,115 +688,1557 @@ async fn init_command(path: &Path) -> Result<()> {
     Ok(())
 }
 
-async fn run_command(path: &Path, force: bool, dry_run: bool) -> Result<()> {
-    println!("🔍 Running DocTreeAI on: {}", path.display());
-    if force {
-        println!("⚡ Force mode enabled - regenerating all summaries");
+/// One-command onboarding: finds a local LLM server (or asks for a hosted
+/// one), picks a model, and writes .env and a starter doctreeai.toml. The
+/// settings also apply to this process, for the init and connection test.
+async fn init_wizard(path: &Path) -> Result<()> {
+    if !std::io::stdin().is_terminal() {
+        return Err(DocTreeError::config("'doctreeai init --interactive' needs an interactive terminal"));
     }
-    if dry_run {
-        println!("🔍 Dry run mode - will not update README.md");
+
+    println!("🔎 Looking for local LLM servers...");
+    let probes = futures::future::join_all(LOCAL_ENDPOINTS.iter().map(|endpoint| wizard::probe(endpoint.api_base, "local"))).await;
+    let mut choices: Vec<(Endpoint, Option<Vec<String>>)> = Vec::new();
+    for (endpoint, models) in LOCAL_ENDPOINTS.iter().zip(probes) {
+        match models {
+            Some(models) => {
+                println!("  ✅ {} at {} ({} models)", endpoint.name, endpoint.api_base, models.len());
+                choices.push((endpoint.clone(), Some(models)));
+            }
+            None => println!("  ❌ {} not running at {}", endpoint.name, endpoint.api_base),
+        }
     }
-    
-    let config = Config::load()?;
-    config.validate()?;
-    
-    // Initialize components
-    let llm_client = LanguageModelClient::new(&config)?;
-    let cache_manager = CacheManager::new(path, &config.cache_dir_name)?;
-    
-    // Test LLM connection first
-    println!("🧠 Testing LLM connection...");
-    if let Err(e) = llm_client.test_connection().await {
-        eprintln!("❌ LLM connection failed: {e}");
-        eprintln!("💡 Make sure your local LLM server is running and environment variables are set correctly:");
-        eprintln!("   OPENAI_API_BASE={}", config.openai_api_base);
-        eprintln!("   OPENAI_MODEL_NAME={}", config.openai_model_name);
-        return Err(e);
+    choices.push((OPENAI_ENDPOINT, None));
+
+    println!("\nWhich server should DocTreeAI use?");
+    for (number, (endpoint, _)) in choices.iter().enumerate() {
+        println!("  {}. {} ({})", number + 1, endpoint.name, endpoint.api_base);
     }
-    println!("✅ LLM connection successful");
-    
-    // Create summarizer and generate project summary
-    let llm_client_2 = LanguageModelClient::new(&config)?;
-    let cache_manager_2 = CacheManager::new(path, &config.cache_dir_name)?;
-    let mut summarizer = HierarchicalSummarizer::new(llm_client, cache_manager, force);
-    
-    println!("📊 Generating hierarchical project summary...");
-    let project_summary = summarizer.generate_project_summary(path).await?;
-    
-    let (cache_entries, cache_size) = summarizer.get_cache_stats();
-    println!("📊 Cache stats: {cache_entries} entries, {cache_size} bytes");
-    
-    if dry_run {
-        println!("\n📋 Generated Project Summary:");
-        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-        println!("{project_summary}");
-        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-        println!("🔍 Dry run complete - README.md was not modified");
+    println!("  {}. Another OpenAI-compatible server", choices.len() + 1);
+    let choice = ask("Server", "1")?.parse::<usize>().unwrap_or(0);
+
+    let (api_base, needs_key, mut models) = match choice.checked_sub(1).and_then(|index| choices.get(index)) {
+        Some((endpoint, models)) => (endpoint.api_base.to_string(), endpoint.needs_key, models.clone()),
+        None if choice == choices.len() + 1 => (ask("API base URL (ending in /v1)", "")?, true, None),
+        None => return Err(DocTreeError::config(format!("No server numbered {choice}"))),
+    };
+
+    let mut api_key = std::env::var("OPENAI_API_KEY").ok().or_else(|| StoredKey::OpenAi.load());
+    if needs_key && api_key.is_none() {
+        let key = ask("API key (leave empty if the server needs none)", "")?;
+        if !key.is_empty() {
+            match StoredKey::OpenAi.store(&key) {
+                Ok(()) => println!("🔑 Stored the API key in the OS keychain"),
+                Err(e) => println!("⚠️  {e} - export OPENAI_API_KEY before running doctreeai"),
+            }
+            api_key = Some(key);
+        }
+    }
+    let api_key = api_key.unwrap_or_else(|| "local".to_string());
+    if models.is_none() {
+        models = wizard::probe(&api_base, &api_key).await;
+    }
+
+    let models = models.unwrap_or_default();
+    let model = if models.is_empty() {
+        ask("Model name", "")?
+    } else {
+        println!("\nAvailable models:");
+        for (number, model) in models.iter().enumerate() {
+            println!("  {}. {model}", number + 1);
+        }
+        let answer = ask("Model (number or name)", "1")?;
+        match answer.parse::<usize>().ok().and_then(|number| models.get(number.wrapping_sub(1))) {
+            Some(model) => model.clone(),
+            None => answer,
+        }
+    };
+    if model.is_empty() {
+        return Err(DocTreeError::config("No model chosen"));
+    }
+
+    let env_file = path.join(".env");
+    wizard::update_env_file(&env_file, &[("OPENAI_API_BASE", &api_base), ("OPENAI_MODEL_NAME", &model)])?;
+    std::env::set_var("OPENAI_API_BASE", &api_base);
+    std::env::set_var("OPENAI_MODEL_NAME", &model);
+    println!("\n✅ Wrote OPENAI_API_BASE and OPENAI_MODEL_NAME to {}", env_file.display());
+
+    if Config::project_file_path(path).is_none() {
+        let project_file = path.join(doctreeai::config::PROJECT_CONFIG_FILES[0]);
+        std::fs::write(&project_file, wizard::PROJECT_FILE_TEMPLATE)?;
+        println!("✅ Wrote {}", project_file.display());
+    }
+    Ok(())
+}
+
+/// Asks for one line of input; an empty answer gives `default`.
+fn ask(question: &str, default: &str) -> Result<String> {
+    if default.is_empty() {
+        print!("❓ {question}: ");
+    } else {
+        print!("❓ {question} [{default}]: ");
+    }
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    let answer = answer.trim();
+    Ok(if answer.is_empty() { default } else { answer }.to_string())
+}
+
+/// Flags of the `run` command.
+struct RunOptions {
+    force: bool,
+    dry_run: bool,
+    /// `--only`, relative to the project root.
+    scope: Option<PathBuf>,
+    workspace: bool,
+    strategy: StrategyKind,
+    small_model: bool,
+    offline: bool,
+    /// `--refresh-older-than`, in seconds.
+    refresh_older_than: Option<u64>,
+    min_confidence: f32,
+    /// `--draft-sections`: draft missing required README sections.
+    draft_sections: bool,
+    /// `--max-tokens-per-run`, over `DOCTREEAI_MAX_TOKENS_PER_RUN`.
+    max_tokens_per_run: Option<u64>,
+    /// `--batch`: send file summary requests as one batch job first.
+    batch: bool,
+    /// Continue the run recorded in the cache (`doctreeai resume`).
+    resume: bool,
+    /// `--wait`: wait for another run on the cache instead of failing.
+    wait: bool,
+    /// Apply README changes without asking.
+    yes: bool,
+    /// `--output`: where the updated document goes instead of README.md.
+    output: Option<PathBuf>,
+    /// `--format`: renderer used for the validation report.
+    report: String,
+    /// `--report-file`: where the report goes instead of stdout.
+    report_file: Option<PathBuf>,
+    /// `--github`: annotations on stdout and a job summary.
+    github: bool,
+    reporter: Arc<dyn Reporter>,
+    /// `--reporter`, forwarded to sandboxed runs.
+    reporter_kind: ReporterKind,
+    /// Cancelled by Ctrl+C.
+    cancellation: CancellationToken,
+    no_write: bool,
+    format: ReportFormat,
+}
+
+/// The path relative to the working directory, which is the repository root
+/// in GitHub Actions.
+fn repository_relative(path: &Path) -> PathBuf {
+    let absolute = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    std::env::current_dir()
+        .and_then(std::fs::canonicalize)
+        .ok()
+        .and_then(|cwd| absolute.strip_prefix(cwd).ok().map(Path::to_path_buf))
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Resolves `--only` to a path relative to the project root.
+fn scope_path(path: &Path, only: &Path) -> Result<PathBuf> {
+    let relative = if only.is_absolute() {
+        only.strip_prefix(path).map_err(|_| {
+            DocTreeError::path(format!("--only {} is outside {}", only.display(), path.display()))
+        })?
+    } else {
+        only
+    };
+
+    let relative: PathBuf = relative
+        .components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .collect();
+
+    if !path.join(&relative).exists() {
+        return Err(DocTreeError::path(format!("--only {} does not exist", only.display())));
+    }
+    Ok(relative)
+}
+
+/// Asks a yes/no question on the terminal. Without a terminal (CI, pipes)
+/// the answer is no, so nothing is written unless `--yes` was passed.
+fn confirm(question: &str) -> Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        return Ok(false);
+    }
+
+    print!("❓ {question} [y/N] ");
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Marks what a written document gained over the README as generated, for
+/// provenance tracking.
+async fn record_applied(path: &Path, config: &Config, document: &str) -> Result<()> {
+    let readme = std::fs::read_to_string(path.join("README.md")).unwrap_or_default();
+    CacheManager::new(path, &config.cache_dir_name)?.record_applied_content(&readme, document).await
+}
+
+/// The first Ctrl+C cancels the run after the current LLM call so finished
+/// summaries stay cached; a second one exits immediately.
+fn cancel_on_ctrl_c(options: &RunOptions) {
+    let cancellation = options.cancellation.clone();
+    let reporter = options.reporter.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        reporter.warn("⏹️  Interrupted - stopping after the current step (Ctrl+C again to quit immediately)");
+        cancellation.cancel();
+
+        if tokio::signal::ctrl_c().await.is_ok() {
+            std::process::exit(130);
+        }
+    });
+}
+
+/// Re-runs the last recorded run with its original options, skipping the
+/// files it already completed.
+async fn resume_command(path: &Path, mut options: RunOptions, scan: &ScanArgs) -> Result<()> {
+    let mut config = Config::load()?;
+    config.apply_project_file(path)?;
+    let reporter = options.reporter.clone();
+
+    let Some(state) = RunState::load(&config.get_cache_dir_path(path))?.filter(RunState::is_resumable) else {
+        reporter.info("✅ Nothing to resume - the last run completed. Use 'doctreeai run' to start a new one");
+        return Ok(());
+    };
+    reporter.info(&format!(
+        "⏯️  Resuming the run started {}: {} completed, {} failed, {} pending",
+        options.format.timestamp(state.started_at),
+        state.completed.len(),
+        state.failed.len(),
+        state.pending.len()
+    ));
+
+    let resume = state.resume_options()?;
+    options.force = resume.force;
+    options.scope = resume.scope;
+    options.strategy = resume.strategy;
+    options.dry_run = resume.skip_validation;
+    options.refresh_older_than = resume.refresh_older_than;
+    options.min_confidence = resume.min_confidence;
+    options.draft_sections = resume.draft_sections;
+    options.batch = resume.batch;
+    cancel_on_ctrl_c(&options);
+    run_command(path, &options, scan, None).await
+}
+
+async fn retry_failed_command(path: &Path) -> Result<()> {
+    let mut doc_tree = DocTree::open(path)?;
+    doc_tree.set_reporter(Arc::new(ConsoleReporter));
+
+    let Some(state) = doc_tree.last_run()?.filter(|state| !state.failed.is_empty()) else {
+        println!("✅ No failed summaries to retry");
         return Ok(());
+    };
+    println!("🔁 Retrying {} failed summaries...", state.failed.len());
+
+    let report = doc_tree.retry_failed().await?;
+    for retried in &report.retried {
+        match report.failed.get(retried) {
+            Some(error) => println!("  ❌ {}: {error}", retried.display()),
+            None => println!("  ✅ {}", retried.display()),
+        }
     }
-    
-    // Validate README.md against cache
-    println!("📝 Validating README.md against current codebase...");
-    let mut readme_validator = ReadmeValidator::new(cache_manager_2, llm_client_2);
-    let validation_results = readme_validator.validate_readme(path, &project_summary).await?;
-    
-    ReadmeValidator::print_validation_results(&validation_results);
-    
-    if validation_results.is_empty() {
-        println!("✅ README.md validation completed - no updates needed!");
+
+    if report.failed.is_empty() {
+        println!("✅ All failed summaries were regenerated - run 'doctreeai run' to validate the README");
     } else {
-        println!("✅ README.md validation completed - {} suggestions generated!", validation_results.len());
-        println!("💡 Review the suggestions above and update your README.md accordingly");
+        println!("⚠️  {} summaries still failed", report.failed.len());
     }
-    
     Ok(())
 }
 
-async fn clean_command(path: &Path) -> Result<()> {
-    println!("🧹 Cleaning DocTreeAI cache in: {}", path.display());
-    
-    let config = Config::load()?;
-    let mut cache_manager = CacheManager::new(path, &config.cache_dir_name)?;
-    
-    cache_manager.clear_cache()?;
-    println!("✅ Cache directory removed");
-    
+/// Staleness audit from the cache alone: no summaries are generated and the
+/// README is left untouched. Exits with 1 when anything is stale.
+fn no_llm_command(path: &Path, options: &RunOptions) -> Result<()> {
+    let reporter = options.reporter.as_ref();
+    let doc_tree = DocTree::open(path)?;
+    let report = doc_tree.check_staleness()?;
+
+    if options.report == "json" {
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| DocTreeError::config(format!("Failed to serialize staleness report: {e}")))?;
+        reporter.output(&json);
+    } else if report.is_clean() {
+        reporter.info("✅ Every cached summary matches the code");
+    } else {
+        let entries: Vec<String> = report
+            .stale_entries
+            .iter()
+            .map(|entry| {
+                let icon = if entry.is_directory { "📁" } else { "📄" };
+                let path = if entry.path.as_os_str().is_empty() { Path::new(".") } else { entry.path.as_path() };
+                format!("{icon} {} [{}]", path.display(), entry.status)
+            })
+            .collect();
+        reporter.section(&format!("🕰️  {} stale cache entries:", entries.len()), &entries);
+
+        if !report.stale_lines.is_empty() {
+            let lines: Vec<String> = report
+                .stale_lines
+                .iter()
+                .map(|line| format!("{}: {}", line.line_number, line.line_content.trim()))
+                .collect();
+            reporter.section(&format!("📝 {} README lines map to stale entries:", lines.len()), &lines);
+        }
+        reporter.info("💡 Run 'doctreeai run' to regenerate them and validate the README");
+    }
+
+    if !report.is_clean() {
+        std::process::exit(1);
+    }
     Ok(())
 }
 
-async fn info_command(path: &Path) -> Result<()> {
-    println!("ℹ️  DocTreeAI Information for: {}", path.display());
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+/// Runs each workspace package as its own project: the package's README is
+/// validated against summaries cached under `<cache>/.packages/<package path>`.
+async fn workspace_run_command(path: &Path, options: &RunOptions, scan: &ScanArgs) -> Result<()> {
+    let workspace = Workspace::detect(path)?
+        .ok_or_else(|| DocTreeError::config(format!("No workspace detected in {}", path.display())))?;
+
+    let mut config = Config::load()?;
+    config.apply_project_file(path)?;
+    let packages_cache_dir = config.get_cache_dir_path(path).join(PACKAGE_CACHE_DIR);
+    let reporter = options.reporter.as_ref();
+    reporter.info(&format!("📦 Workspace detected with {} packages", workspace.packages.len()));
+
+    let mut failed = Vec::new();
+    for package in &workspace.packages {
+        reporter.info("");
+        reporter.info("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        reporter.info(&format!("📦 {} ({})", package.name, package.path.display()));
+        reporter.info("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+
+        let cache_dir = packages_cache_dir.join(&package.path);
+        if let Err(e) = run_command(&path.join(&package.path), options, scan, Some(&cache_dir)).await {
+            reporter.error(&format!("❌ {} failed: {e}", package.name));
+            failed.push(package.name.clone());
+        }
+    }
+
+    if !failed.is_empty() {
+        return Err(DocTreeError::summarizer(format!(
+            "{} of {} packages failed: {}",
+            failed.len(),
+            workspace.packages.len(),
+            failed.join(", ")
+        )));
+    }
+
+    reporter.info("");
+    reporter.info(&format!("✅ All {} packages processed", workspace.packages.len()));
+    Ok(())
+}
+
+/// `cache_dir` overrides the configured cache directory, for workspace packages.
+async fn run_command(
+    path: &Path,
+    options: &RunOptions,
+    scan: &ScanArgs,
+    cache_dir: Option<&Path>,
+) -> Result<()> {
+    let RunOptions { force, dry_run, strategy, no_write, yes, format, .. } = *options;
+    let scope = options.scope.as_deref();
+    let renderers = RendererRegistry::with_builtins();
+    let report_renderer = renderers.get(&options.report)?;
+    let reporter = options.reporter.as_ref();
+
+    reporter.info(&format!("🔍 Running DocTreeAI on: {}", path.display()));
+    if force {
+        reporter.info("⚡ Force mode enabled - regenerating all summaries");
+    }
+    if let Some(max_age_secs) = options.refresh_older_than {
+        reporter.info(&format!("⏳ Re-summarizing cache entries older than {max_age_secs}s"));
+    }
+    if let Some(scope) = scope {
+        reporter.info(&format!("🎯 Only summarizing {} and its parent directories", scope.display()));
+    }
+    if dry_run {
+        reporter.info("🔍 Dry run mode - will not update README.md");
+    }
+    if no_write {
+        reporter.info("🔒 Read-only mode - nothing will be written to disk");
+    }
     
-    let config = Config::load()?;
+    let mut config = Config::load()?;
+    config.apply_project_file(path)?;
+    scan.apply(&mut config);
+    if options.small_model {
+        config.small_model = true;
+        config.set_source("DOCTREEAI_SMALL_MODEL", SettingSource::Cli);
+    }
+    if options.offline {
+        config.offline = true;
+        config.set_source("DOCTREEAI_OFFLINE", SettingSource::Cli);
+    }
+    if let Some(max_tokens) = options.max_tokens_per_run {
+        config.max_tokens_per_run = Some(max_tokens).filter(|&tokens| tokens > 0);
+        config.set_source("DOCTREEAI_MAX_TOKENS_PER_RUN", SettingSource::Cli);
+    }
+    if let Some(cache_dir) = cache_dir {
+        config.cache_dir_name = cache_dir.to_string_lossy().to_string();
+    }
+    if options.resume {
+        if let Some(state) = RunState::load(&config.get_cache_dir_path(path))? {
+            state.options.restore_config(&mut config);
+        }
+    }
     config.validate()?;
+    if let Some(max_tokens) = config.max_tokens_per_run {
+        reporter.info(&format!("💰 Stopping after {max_tokens} LLM tokens"));
+    }
+    
+    let mut doc_tree = DocTree::with_config(path, config);
+    doc_tree.set_reporter(options.reporter.clone());
+    doc_tree.set_cancellation(options.cancellation.clone());
+    doc_tree.set_wait_for_lock(options.wait);
+
+    // Test LLM connection first
+    if doc_tree.config().offline {
+        reporter.info("📴 Offline mode - summaries are built from code structure and replaced on the next online run");
+    } else {
+        reporter.info("🧠 Testing LLM connection...");
+        if let Err(e) = doctreeai::models::ensure_available(doc_tree.config()).await {
+            reporter.error(&format!("❌ {e}"));
+            return Err(e);
+        }
+        if let Err(e) = doc_tree.test_connection().await {
+            reporter.error(&format!("❌ LLM connection failed: {e}"));
+            reporter.error("💡 Make sure your local LLM server is running and environment variables are set correctly:");
+            reporter.error(&format!("   OPENAI_API_BASE={}", doc_tree.config().openai_api_base));
+            reporter.error(&format!("   OPENAI_MODEL_NAME={}", doc_tree.config().openai_model_name));
+            reporter.error("   or run with --offline for structural summaries without the LLM");
+            return Err(e);
+        }
+        reporter.info("✅ LLM connection successful");
+    }
+
+    if options.batch {
+        reporter.info("📦 Batch mode - file summaries are requested as one batch job first, which can take hours");
+    }
+    reporter.info(&format!("📊 Generating {strategy} project summary..."));
+    if !dry_run {
+        reporter.info("📝 Then validating README.md against current codebase...");
+    }
+    let result = doc_tree
+        .run(&doctree::RunOptions {
+            force,
+            scope: scope.map(Path::to_path_buf),
+            strategy,
+            skip_validation: dry_run,
+            render_document: options.output.is_some(),
+            read_only: no_write,
+            refresh_older_than: options.refresh_older_than,
+            resume: options.resume,
+            min_confidence: options.min_confidence,
+            draft_sections: options.draft_sections,
+            batch: options.batch,
+        })
+        .await;
+    let report = match result {
+        Err(DocTreeError::Cancelled(message)) => {
+            reporter.warn(&format!("⏹️  {message}"));
+            reporter.warn("💾 Completed summaries are cached - run 'doctreeai resume' to continue");
+            std::process::exit(130);
+        }
+        result => result?,
+    };
+    let config = doc_tree.config();
+    if let Some(state) = doc_tree.last_run()?.filter(|state| !no_write && !state.failed.is_empty()) {
+        reporter.warn(&format!(
+            "⚠️  {} summaries fell back to extractive text after LLM errors - run 'doctreeai resume' to retry them",
+            state.failed.len()
+        ));
+    }
+
+    if let Some(state) = doc_tree.last_run()?.filter(|state| !state.redacted.is_empty()) {
+        let findings: usize = state.redacted.values().map(Vec::len).sum();
+        reporter.warn(&format!(
+            "🔒 Redacted {findings} secret(s) in {} file(s) before sending them to the LLM - see 'doctreeai info'",
+            state.redacted.len()
+        ));
+    }
+
+    reporter.info(&format!("📊 Cache stats: {} entries, {}", report.stats.cache_entries, format.bytes(report.stats.cache_size)));
+
+    if dry_run {
+        let lines: Vec<String> = report.project_summary.lines().map(str::to_string).collect();
+        reporter.info("");
+        reporter.section("📋 Generated Project Summary:", &lines);
+        reporter.info("🔍 Dry run complete - README.md was not modified");
+        return Ok(());
+    }
+
+    if let Some(workspace) = &report.workspace {
+        reporter.info(&format!("📦 Workspace detected with {} packages", workspace.packages.len()));
+    }
+    if let (Some(debt), DebtOutput::File) = (&report.tech_debt, config.tech_debt) {
+        sync_tech_debt_file(path, debt, no_write, reporter)?;
+    }
+    let validation_results = &report.validation_results;
+    if let Some(digest) = &report.digest {
+        ReadmeValidator::report_digest(reporter, digest);
+    }
+
+    if let Some(linker) = TicketLinker::from_config(config) {
+        let mut directories: Vec<PathBuf> = validation_results
+            .iter()
+            .flat_map(|result| &result.affected_cache_entries)
+            .map(|entry| {
+                let entry = PathBuf::from(entry);
+                if entry.is_dir() {
+                    entry
+                } else {
+                    entry.parent().map(Path::to_path_buf).unwrap_or(entry)
+                }
+            })
+            .collect();
+        directories.sort();
+        directories.dedup();
+        TicketLinker::report_tickets(reporter, &linker.tickets_for_paths(path, &directories));
+    }
+
+    if options.github {
+        let github = GithubActions::new(&repository_relative(&path.join("README.md")));
+        reporter.output(&github.annotations(validation_results));
+        if github.write_step_summary(validation_results)? {
+            reporter.info("📝 Added the validation report to the job summary");
+        }
+    }
+
+    let rendered_report = report_renderer.render(&RenderInput::results(validation_results))?;
+    // Read-only runs print the report instead of writing it
+    match options.report_file.as_ref().filter(|_| !no_write) {
+        Some(report_file) => {
+            std::fs::write(report_file, rendered_report).map_err(|e| {
+                DocTreeError::config(format!("Failed to write {}: {e}", report_file.display()))
+            })?;
+            reporter.info(&format!("📄 Validation report written to {}", report_file.display()));
+        }
+        // With --github the annotations already are the report
+        None if options.github => {}
+        None => reporter.output(&rendered_report),
+    }
+
+    if let (Some(output), Some(document)) = (&options.output, &report.document) {
+        if output.as_os_str() == "-" {
+            reporter.info("\n📄 Updated document:");
+            reporter.info("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+            reporter.output(document);
+            reporter.info("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        } else if no_write {
+            reporter.info(&format!("🔒 Not writing {} in read-only mode", output.display()));
+        } else {
+            if let Some(parent) = output.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(output, document).map_err(|e| {
+                DocTreeError::readme(format!("Failed to write {}: {e}", output.display()))
+            })?;
+            record_applied(path, config, document).await?;
+            reporter.info(&format!("✍️  Wrote the updated document to {} (README.md unchanged)", output.display()));
+        }
+    } else if !no_write {
+        if let Some(update) = &report.readme_update {
+            reporter.info("\n📝 Proposed README.md changes:");
+            reporter.info("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+            reporter.output(&update.diff());
+            reporter.info("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+            if yes || confirm("Apply these changes to README.md?")? {
+                record_applied(path, config, &update.proposed).await?;
+                update.apply(&ReadmeBackups::new(&config.get_cache_dir_path(path), config.readme_backups))?;
+                reporter.info(&format!("✍️  Regenerated {} managed README.md sections", update.sections));
+            } else {
+                reporter.info("⏭️  README.md left unchanged (pass --yes to apply without asking)");
+            }
+        }
+    }
+    
+    if validation_results.is_empty() {
+        reporter.info("✅ README.md validation completed - no updates needed!");
+    } else {
+        reporter.info(&format!("✅ README.md validation completed - {} suggestions generated!", validation_results.len()));
+        reporter.info("💡 Review the suggestions above and update your README.md accordingly");
+    }
+
+    let project_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("project");
+    Notifier::new(config.notify).notify_new_drift(&config.get_cache_dir_path(path), project_name, validation_results, no_write)?;
+
+    if !config.readme_translations.is_empty() {
+        if no_write {
+            reporter.info("🔒 Skipping localized READMEs in read-only mode");
+        } else {
+            sync_localized_readmes(path, config, reporter).await?;
+        }
+    }
+    
+    Ok(())
+}
+
+/// Where `doctreeai pr` opens its pull request.
+struct PullRequestTarget {
+    token: String,
+    /// `--repo`; read from the origin remote when None.
+    repository: Option<String>,
+    base: Option<String>,
+    branch: Option<String>,
+    api_url: String,
+}
+
+/// Runs doctreeai and proposes the updated README (and TODO.md, when tech
+/// debt goes to a file) as a pull request instead of editing the checkout.
+async fn pr_command(path: &Path, target: &PullRequestTarget, scan: &ScanArgs, no_write: bool) -> Result<()> {
+    let repository = match &target.repository {
+        Some(repository) => repository.clone(),
+        None => repository_from_remote(&git_output(path, &["remote", "get-url", "origin"])?).ok_or_else(|| {
+            DocTreeError::config("The origin remote is not on GitHub; pass --repo OWNER/NAME")
+        })?,
+    };
+    let client = GithubClient::new(&target.api_url, &target.token, &repository)?;
+    let repository_root = PathBuf::from(git_output(path, &["rev-parse", "--show-toplevel"])?);
+
+    println!("🔍 Running DocTreeAI on: {}", path.display());
+    let mut config = Config::load()?;
+    config.apply_project_file(path)?;
+    scan.apply(&mut config);
+    config.validate()?;
+    let mut doc_tree = DocTree::with_config(path, config);
+    doc_tree.set_reporter(Arc::new(ConsoleReporter));
+    doc_tree.test_connection().await?;
+    let report = doc_tree
+        .run(&doctree::RunOptions {
+            render_document: true,
+            read_only: no_write,
+            ..Default::default()
+        })
+        .await?;
+
+    let mut files = Vec::new();
+    let mut propose = |file: PathBuf, content: String| -> Result<()> {
+        if std::fs::read_to_string(&file).ok().as_deref() == Some(content.as_str()) {
+            return Ok(());
+        }
+        let absolute = std::fs::canonicalize(file.parent().unwrap_or(path))?.join(file.file_name().unwrap_or_default());
+        let relative = absolute.strip_prefix(&repository_root).map_err(|_| {
+            DocTreeError::path(format!("{} is outside the repository", file.display()))
+        })?;
+        files.push((relative.to_string_lossy().replace('\\', "/"), content));
+        Ok(())
+    };
+    if let Some(document) = &report.document {
+        propose(path.join("README.md"), document.clone())?;
+    }
+    if let (Some(debt), DebtOutput::File) = (&report.tech_debt, doc_tree.config().tech_debt) {
+        propose(path.join(TECH_DEBT_FILE), debt.render_file())?;
+    }
+    if files.is_empty() {
+        println!("✅ Documentation is up to date - no pull request needed");
+        return Ok(());
+    }
+
+    let mut body = String::from("Documentation updates generated by doctreeai from the current code.\n\n");
+    if let Some(digest) = &report.digest {
+        body.push_str(&format!("{}\n\n", digest.trim_end()));
+    }
+    body.push_str(&MarkdownRenderer.render(&RenderInput::results(&report.validation_results))?);
+    let draft = PullRequestDraft {
+        branch: target
+            .branch
+            .clone()
+            .unwrap_or_else(|| format!("doctreeai/docs-{}", current_timestamp())),
+        base: target.base.clone(),
+        title: "docs: update documentation to match the code".to_string(),
+        body,
+        files,
+    };
+
+    println!("🚀 Opening a pull request in {repository} from branch '{}'...", draft.branch);
+    let url = client.open_pull_request(&draft).await?;
+    if let Some(document) = report.document.as_ref().filter(|_| !no_write) {
+        record_applied(path, doc_tree.config(), document).await?;
+    }
+    println!("✅ Opened {url} ({} files changed)", draft.files.len());
+    Ok(())
+}
+
+fn hooks_install_command(path: &Path, pre_push: bool, block: bool, force: bool) -> Result<()> {
+    let installer = HookInstaller::for_project(path)?;
+    let mut hooks = vec![GitHook::PreCommit];
+    if pre_push {
+        hooks.push(GitHook::PrePush);
+    }
+
+    for hook in hooks {
+        let hook_path = installer.install(hook, block, force)?;
+        println!("🪝 Installed {}", hook_path.display());
+    }
+    if block {
+        println!("🛑 Commits that change README-mapped code without updating README.md will be stopped (skip with --no-verify)");
+    } else {
+        println!("⚠️  Commits that change README-mapped code without updating README.md will get a warning");
+    }
+    Ok(())
+}
+
+/// Run by the installed hooks: warns (or fails with `block`) when changed
+/// files are mapped to README lines and README.md itself did not change.
+/// Only the cache is consulted, so the check needs no LLM.
+fn hooks_check_command(path: &Path, hook: GitHook, block: bool) -> Result<()> {
+    let changed = match hook.changed_files(path) {
+        Ok(changed) => changed,
+        Err(e) => {
+            eprintln!("⚠️  doctreeai: skipping the README check: {e}");
+            return Ok(());
+        }
+    };
+    if changed.is_empty() || changed.iter().any(|file| file == Path::new("README.md")) {
+        return Ok(());
+    }
+
+    // A misconfigured doctreeai should not get in the way of committing
+    let report = match DocTree::open(path).and_then(|doc_tree| doc_tree.check_staleness()) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("⚠️  doctreeai: skipping the README check: {e}");
+            return Ok(());
+        }
+    };
+    let lines = report.lines_for(&changed);
+    if lines.is_empty() {
+        return Ok(());
+    }
+
+    let action = match hook {
+        GitHook::PreCommit => "commit",
+        GitHook::PrePush => "push",
+    };
+    eprintln!("📝 README.md describes code changed in this {action} but was not updated:");
+    for line in lines {
+        let files: Vec<String> = line
+            .entries
+            .iter()
+            .filter(|entry| changed.contains(entry))
+            .map(|entry| entry.display().to_string())
+            .collect();
+        eprintln!("   Line {}: {} ({})", line.line_number, line.line_content.trim(), files.join(", "));
+    }
+    if block {
+        eprintln!("❌ Update README.md (see 'doctreeai run') or skip this check with --no-verify");
+        std::process::exit(1);
+    }
+    eprintln!("💡 Run 'doctreeai run' for suggested README updates");
+    Ok(())
+}
+
+/// Trimmed stdout of a git command run in `path`.
+fn git_output(path: &Path, args: &[&str]) -> Result<String> {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(args)
+        .output()
+        .map_err(|e| DocTreeError::config(format!("Failed to run git: {e}")))?;
+    if !output.status.success() {
+        return Err(DocTreeError::config(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Rewrites TODO.md when the TODO/FIXME/HACK comments in the code changed.
+fn sync_tech_debt_file(path: &Path, report: &DebtReport, no_write: bool, reporter: &dyn Reporter) -> Result<()> {
+    let todo_path = path.join(TECH_DEBT_FILE);
+    let content = report.render_file();
+    if std::fs::read_to_string(&todo_path).ok().as_deref() == Some(content.as_str()) {
+        reporter.info(&format!("📋 {TECH_DEBT_FILE} is up-to-date"));
+        return Ok(());
+    }
+
+    if no_write {
+        reporter.info(&format!(
+            "🔒 {TECH_DEBT_FILE} is out of date ({} markers); not writing in read-only mode",
+            report.markers.len()
+        ));
+        return Ok(());
+    }
+
+    std::fs::write(&todo_path, content)
+        .map_err(|e| DocTreeError::readme(format!("Failed to write {TECH_DEBT_FILE}: {e}")))?;
+    reporter.info(&format!("📋 Updated {TECH_DEBT_FILE} with {} TODO/FIXME/HACK markers", report.markers.len()));
+    Ok(())
+}
+
+/// An LLM client that also writes to the audit log, when it is enabled.
+fn audited_llm_client(config: &Config, path: &Path) -> Result<LanguageModelClient> {
+    let mut llm_client = LanguageModelClient::new(config)?;
+    if let Some(audit_log) = AuditLog::from_config(config, path)? {
+        llm_client.set_audit_log(audit_log);
+    }
+    Ok(llm_client)
+}
+
+async fn sync_localized_readmes(path: &Path, config: &Config, reporter: &dyn Reporter) -> Result<()> {
+    let readme_manager = ReadmeManager::new();
+    if !readme_manager.readme_exists(path) {
+        tracing::debug!("No README.md to translate");
+        return Ok(());
+    }
+
+    let readme_content = std::fs::read_to_string(path.join("README.md"))?;
+    let mut llm_client = audited_llm_client(config, path)?;
+    if std::io::stderr().is_terminal() {
+        llm_client.set_stream_output(Arc::new(|piece: &str| {
+            eprint!("{piece}");
+            let _ = std::io::stderr().flush();
+        }));
+    }
+
+    for language_code in &config.readme_translations {
+        if readme_manager.is_translation_current(path, language_code, &readme_content) {
+            reporter.info(&format!("🌐 README.{language_code}.md is up-to-date"));
+            continue;
+        }
+
+        reporter.info(&format!("🌐 Translating README.md to '{language_code}'..."));
+        let translation = llm_client.translate_readme(&readme_content, language_code).await?;
+        if std::io::stderr().is_terminal() {
+            eprintln!();
+        }
+        let localized_path =
+            readme_manager.write_localized_readme(path, language_code, &readme_content, &translation)?;
+        reporter.info(&format!("✅ Wrote {}", localized_path.display()));
+    }
+
+    Ok(())
+}
+
+fn sandboxed_run_command(
+    path: &Path,
+    options: &RunOptions,
+    scan: &ScanArgs,
+    verbose: bool,
+    log_format: LogFormat,
+) -> Result<()> {
+    options.reporter.info(&format!("📦 Running DocTreeAI in a Docker sandbox on: {}", path.display()));
+
+    let config = Config::load()?;
+    config.validate()?;
+
+    // The mount is read-only, so the containerized run must not write either
+    let mut args = vec!["--no-write".to_string()];
+    if verbose {
+        args.push("--verbose".to_string());
+    }
+    args.push(format!("--log-format={log_format}"));
+    args.push("run".to_string());
+    if options.force {
+        args.push("--force".to_string());
+    }
+    if options.dry_run {
+        args.push("--dry-run".to_string());
+    }
+    if let Some(scope) = &options.scope {
+        args.push(format!("--only={}", scope.display()));
+    }
+    if options.workspace {
+        args.push("--workspace".to_string());
+    }
+    args.push(format!("--strategy={}", options.strategy));
+    args.push(format!("--format={}", options.report));
+    args.push(format!("--reporter={}", options.reporter_kind));
+    if let Some(max_age_secs) = options.refresh_older_than {
+        args.push(format!("--refresh-older-than={max_age_secs}s"));
+    }
+    if options.min_confidence > 0.0 {
+        args.push(format!("--min-confidence={}", options.min_confidence));
+    }
+    if options.draft_sections {
+        args.push("--draft-sections".to_string());
+    }
+    if let Some(max_tokens) = options.max_tokens_per_run {
+        args.push(format!("--max-tokens-per-run={max_tokens}"));
+    }
+    if options.batch {
+        args.push("--batch".to_string());
+    }
+    if options.small_model {
+        args.push("--small-model".to_string());
+    }
+    if options.offline {
+        args.push("--offline".to_string());
+    }
+    if options.wait {
+        args.push("--wait".to_string());
+    }
+    args.extend(scan.to_args());
+
+    DockerSandbox::from_config(&config).run(path, &args)
+}
+
+async fn estimate_command(
+    path: &Path,
+    force: bool,
+    pricing: TokenPricing,
+    scan: &ScanArgs,
+    reporter: &dyn Reporter,
+) -> Result<()> {
+    reporter.info(&format!("🧮 Estimating DocTreeAI run for: {}", path.display()));
+    if force {
+        reporter.info("⚡ Force mode enabled - assuming all summaries are regenerated");
+    }
+
+    let mut config = Config::load()?;
+    config.apply_project_file(path)?;
+    scan.apply(&mut config);
+    let mut cache_manager = CacheManager::new(path, &config.cache_dir_name)?;
+    cache_manager.set_refresh_policy(config.cache_ttl.clone());
+    cache_manager.set_normalized_hashing(config.normalized_hashing);
+    cache_manager.set_doc_language(config.doc_language.clone());
+
+    let mut estimator = CostEstimator::new(&cache_manager, force, pricing);
+    estimator.apply_config(&config, path)?;
+    let estimate = estimator.estimate(path)?;
+
+    estimate.report(reporter);
+
+    if estimate.llm_calls() == 0 {
+        reporter.info("✅ Everything is cached - a run would not call the LLM");
+    } else {
+        reporter.info("💡 Use 'doctreeai run' to generate summaries");
+    }
+
+    Ok(())
+}
+
+/// A scanner with the filter, limits, walk options, and submodule policy a
+/// run would scan with.
+fn scanner_for(config: &Config, path: &Path) -> Result<DirectoryScanner> {
+    let mut scanner = DirectoryScanner::new(path.to_path_buf());
+    scanner.set_filter(ScanFilter::new(&config.include_patterns, &config.exclude_patterns)?);
+    scanner.set_limits(config.scan_limits);
+    scanner.set_walk_options(config.walk_options);
+    scanner.set_submodule_policy(config.submodule_policy.clone());
+    Ok(scanner)
+}
+
+/// Checks README.md's references against a fresh scan and the CLI the code
+/// defines. Exits with 1 when any are broken.
+fn lint_command(path: &Path, format: &str, scan: &ScanArgs) -> Result<()> {
+    let readme_path = path.join("README.md");
+    let readme = std::fs::read_to_string(&readme_path)
+        .map_err(|e| DocTreeError::readme(format!("Failed to read {}: {e}", readme_path.display())))?;
+
+    // Linting never calls the LLM, so it runs without an endpoint configured
+    let mut config = Config::load().unwrap_or_default();
+    config.apply_project_file(path)?;
+    scan.apply(&mut config);
+    let root = scanner_for(&config, path)?.scan_directory()?;
+
+    let broken = ReferenceGuard::from_tree(path, &root).lint(&readme);
+    match format {
+        "json" => {
+            let json = serde_json::to_string_pretty(&broken)
+                .map_err(|e| DocTreeError::readme(format!("Failed to serialize lint results: {e}")))?;
+            println!("{json}");
+        }
+        "text" if broken.is_empty() => println!("✅ Every path, binary, subcommand, and flag in README.md exists"),
+        "text" => {
+            println!("🔗 {} broken references in README.md:", broken.len());
+            for reference in &broken {
+                println!("   Line {}: {} `{}` does not exist", reference.line_number, reference.kind, reference.reference);
+            }
+        }
+        other => return Err(DocTreeError::config(format!("Unknown lint format '{other}' (expected text or json)"))),
+    }
+
+    if !broken.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Prints the import graph between the scanned files, as a list or in DOT.
+fn graph_command(path: &Path, dot: bool, scan: &ScanArgs) -> Result<()> {
+    let mut config = Config::load().unwrap_or_default();
+    config.apply_project_file(path)?;
+    scan.apply(&mut config);
+    let graph = DependencyGraph::from_tree(path, &scanner_for(&config, path)?.scan_directory()?);
+
+    if dot {
+        print!("{}", graph.to_dot());
+    } else if graph.is_empty() {
+        println!("📭 No imports between project files found");
+    } else {
+        for (file, dependencies) in graph.edges() {
+            println!("{}", file.display());
+            for dependency in dependencies {
+                println!("   → {}", dependency.display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reports the required README sections (`[readme] required_sections` or
+/// `DOCTREEAI_REQUIRED_SECTIONS`) that README.md is missing. Exits with 1
+/// when any are.
+fn check_command(path: &Path) -> Result<()> {
+    let mut config = Config::load().unwrap_or_default();
+    config.apply_project_file(path)?;
+    if config.required_sections.is_empty() {
+        println!("ℹ️  No required sections configured - set [readme] required_sections in doctreeai.toml");
+        return Ok(());
+    }
+
+    let readme_path = path.join("README.md");
+    let readme = std::fs::read_to_string(&readme_path)
+        .map_err(|e| DocTreeError::readme(format!("Failed to read {}: {e}", readme_path.display())))?;
+    let missing = ReadmeManager::new().missing_sections(&readme, &config.required_sections);
+    if missing.is_empty() {
+        println!("✅ README.md has all {} required sections", config.required_sections.len());
+        return Ok(());
+    }
+
+    println!("📑 README.md is missing {} required sections:", missing.len());
+    for name in &missing {
+        println!("   {name}");
+    }
+    println!("💡 Use 'doctreeai run --draft-sections' to have them drafted");
+    std::process::exit(1);
+}
+
+async fn edit_command(path: &Path, target: &Path) -> Result<()> {
+    let mut config = Config::load()?;
+    config.apply_project_file(path)?;
+    config.validate()?;
+
+    let source_path = if target.is_absolute() {
+        target.to_path_buf()
+    } else {
+        path.join(target)
+    };
+
+    let mut cache_manager = CacheManager::new(path, &config.cache_dir_name)?;
+    let entry = cache_manager.get_cache_summary(&source_path).ok_or_else(|| {
+        DocTreeError::cache(format!(
+            "No cached summary for {} - run 'doctreeai run' first",
+            target.display()
+        ))
+    })?;
+
+    println!("✏️  Editing summary for: {}", target.display());
+    let Some(edited) = edit_in_editor(&entry.summary)? else {
+        println!("ℹ️  Summary unchanged - nothing to do");
+        return Ok(());
+    };
+
+    cache_manager.store_pinned_summary(&source_path, edited)?;
+    println!("📌 Summary saved and pinned");
+
+    let invalidated = cache_manager.invalidate_ancestors(&source_path)?;
+    if invalidated.is_empty() {
+        return Ok(());
+    }
+
+    println!("🔄 Re-rolling {} ancestor directory summaries...", invalidated.len());
+    let llm_client = audited_llm_client(&config, path)?;
+    let mut summarizer = HierarchicalSummarizer::new(llm_client, cache_manager, false);
+    summarizer.apply_config(&config, path)?;
+    summarizer.generate_project_summary(path).await?;
+    println!("✅ Ancestor summaries updated");
+
+    Ok(())
+}
+
+/// Opens the text in $VISUAL/$EDITOR and returns the edited text, or None
+/// when it was left unchanged.
+fn edit_in_editor(initial: &str) -> Result<Option<String>> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+
+    // Created exclusively with a random name, and removed when dropped
+    // after the editor exits
+    let mut edit_file = tempfile::Builder::new().prefix("doctreeai-edit-").suffix(".md").tempfile()?;
+    edit_file.write_all(initial.as_bytes())?;
+    edit_file.flush()?;
+    let edit_path = edit_file.path();
+
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    let status = std::process::Command::new(program)
+        .args(parts)
+        .arg(edit_path)
+        .status();
+
+    // Editors that save by replacing the file leave a new one at the path
+    let edited = std::fs::read_to_string(edit_path);
+    drop(edit_file);
+
+    let status = status.map_err(|e| DocTreeError::config(format!("Failed to launch editor '{editor}': {e}")))?;
+    if !status.success() {
+        return Err(DocTreeError::config(format!("Editor '{editor}' exited with {status}")));
+    }
+
+    let edited = edited?.trim().to_string();
+    if edited.is_empty() || edited == initial.trim() {
+        Ok(None)
+    } else {
+        Ok(Some(edited))
+    }
+}
+
+async fn index_command(path: &Path, action: IndexAction) -> Result<()> {
+    let mut config = Config::load()?;
+    config.apply_project_file(path)?;
+    config.validate()?;
+    let cache_dir = config.get_cache_dir_path(path);
+
+    if let IndexAction::Clear = action {
+        if EmbeddingIndex::clear(&cache_dir)? {
+            println!("✅ Embedding index removed");
+        } else {
+            println!("ℹ️  No embedding index to remove");
+        }
+        return Ok(());
+    }
+
+    let model = config.embedding_model_name.clone().ok_or_else(|| {
+        DocTreeError::config("DOCTREEAI_EMBEDDING_MODEL must be set to build the embedding index")
+    })?;
+
+    let cache_manager = CacheManager::new(path, &config.cache_dir_name)?;
+    let summaries = cache_manager.get_all_summaries();
+    if summaries.is_empty() {
+        return Err(DocTreeError::cache("No cached summaries - run 'doctreeai run' first"));
+    }
+
+    let mut index = match action {
+        IndexAction::Build => EmbeddingIndex::new(&model),
+        _ => EmbeddingIndex::load(&cache_dir, &model)?,
+    };
+
+    println!("🧭 Embedding summaries with {model}...");
+    let llm_client = LanguageModelClient::new(&config)?;
+    let update = index.update(&summaries, path, &llm_client).await?;
+    index.save(&cache_dir)?;
+
+    println!("✅ Embedding index has {} entries", index.len());
+    update.report(&ConsoleReporter);
+
+    Ok(())
+}
+
+async fn clean_command(path: &Path) -> Result<()> {
+    println!("🧹 Cleaning DocTreeAI cache in: {}", path.display());
+    
+    let config = Config::load()?;
+    let mut cache_manager = CacheManager::new(path, &config.cache_dir_name)?;
+    
+    cache_manager.clear_cache()?;
+    println!("✅ Cache directory removed");
+    
+    Ok(())
+}
+
+fn rollback_command(path: &Path, list: bool, format: ReportFormat) -> Result<()> {
+    let mut config = Config::load()?;
+    config.apply_project_file(path)?;
+    let backups = ReadmeBackups::new(&config.get_cache_dir_path(path), config.readme_backups);
+
+    if list {
+        let available = backups.list()?;
+        if available.is_empty() {
+            println!("📭 No README.md backups yet");
+        }
+        for backup in available.iter().rev() {
+            println!("🗂️  {} ({})", backup.path.display(), format.timestamp(backup.taken_at_secs()));
+        }
+        return Ok(());
+    }
+
+    match backups.restore_latest(path)? {
+        Some(backup) => println!(
+            "⏪ Restored README.md from the backup taken {}",
+            format.timestamp(backup.taken_at_secs())
+        ),
+        None => println!("📭 No README.md backups to restore"),
+    }
+    Ok(())
+}
+
+fn tree_command(path: &Path, depth: Option<usize>, format: &str) -> Result<()> {
+    let renderers = RendererRegistry::with_builtins();
+    let renderer = renderers.get(format)?;
+    let mut config = Config::load()?;
+    config.apply_project_file(path)?;
+
+    let cache_manager = CacheManager::new(path, &config.cache_dir_name)?;
+    let summaries = cache_manager.get_all_summaries();
+    if summaries.is_empty() {
+        println!("📭 No cached summaries yet - run 'doctreeai run' first");
+        return Ok(());
+    }
+
+    let now = current_timestamp();
+    let mut tree = SummaryTreeNode::from_cache(&summaries, path, &config.cache_ttl, now);
+    if let Some(depth) = depth {
+        tree.truncate(depth);
+    }
+
+    print!("{}", renderer.render(&RenderInput::tree(&tree))?);
+    Ok(())
+}
+
+fn show_command(path: &Path, target: &Path, format: ReportFormat) -> Result<()> {
+    let mut config = Config::load()?;
+    config.apply_project_file(path)?;
+
+    let source_path = if target.is_absolute() {
+        target.to_path_buf()
+    } else {
+        path.join(target)
+    };
+    let relative_path = source_path.strip_prefix(path).unwrap_or(&source_path).to_path_buf();
+
+    let cache_manager = CacheManager::new(path, &config.cache_dir_name)?;
+    let entry = cache_manager.get_cache_summary(&source_path).ok_or_else(|| {
+        DocTreeError::cache(format!(
+            "No cached summary for {} - run 'doctreeai run' first",
+            target.display()
+        ))
+    })?;
+
+    let now = current_timestamp();
+    let tree = SummaryTreeNode::from_cache(&cache_manager.get_all_summaries(), path, &config.cache_ttl, now);
+    let status = tree.find(&relative_path).map(|node| node.status);
+
+    println!("{} {}", if entry.is_directory { "📁" } else { "📄" }, relative_path.display());
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!("  Content Hash: {}", entry.content_hash);
+    println!("  Updated: {}", format.timestamp(entry.timestamp));
+    if let Some(status) = status {
+        println!("  Status: {status}");
+    }
+    if let Some(role) = entry.role {
+        println!("  Role: {role}");
+    }
+    if entry.pinned {
+        println!("  Pinned: yes");
+    }
+    match entry.method {
+        SummaryMethod::Extractive => println!("  Method: extractive (replaced by an LLM summary on the next run)"),
+        SummaryMethod::StructureOnly => println!("  Method: structure only (private path, contents not sent to the LLM)"),
+        SummaryMethod::Llm => {}
+    }
+    println!();
+    println!("{}", entry.summary);
+
+    let cache_key = source_path.to_string_lossy();
+    let mapped: Vec<_> = cache_manager
+        .get_readme_mapping()
+        .mappings
+        .iter()
+        .filter(|mapping| mapping.cache_keys.iter().any(|key| key == cache_key.as_ref()))
+        .collect();
+    println!();
+    if mapped.is_empty() {
+        println!("📝 No README lines map to this entry");
+    } else {
+        println!("📝 README lines mapped to this entry:");
+        for mapping in mapped {
+            println!("  {}: {}", mapping.line_number, mapping.line_content.trim());
+        }
+    }
+
+    Ok(())
+}
+
+async fn info_command(path: &Path, settings: bool, format: ReportFormat) -> Result<()> {
+    println!("ℹ️  DocTreeAI Information for: {}", path.display());
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    
+    let mut config = Config::load()?;
+    config.apply_project_file(path)?;
+    config.validate()?;
+
+    if settings {
+        println!("🔧 Resolved Settings:");
+        for setting in config.report() {
+            println!("  {} = {} ({})", setting.name, setting.value, setting.source);
+        }
+        return Ok(());
+    }
+    
+    // Configuration info
+    println!("📋 Configuration:");
+    println!("  API Base: {}", config.openai_api_base);
+    println!("  Model: {}", config.openai_model_name);
+    println!("  Summary Model: {}", config.summary_model());
+    println!("  Synthesis Model: {}", config.synthesis_model());
+    if let Some(language) = &config.doc_language {
+        println!("  Documentation Language: {language}");
+    }
+    if !config.readme_translations.is_empty() {
+        println!("  README Translations: {}", config.readme_translations.join(", "));
+    }
+    println!("  Cache Dir: {}", config.cache_dir_name);
+    println!();
     
-    // Configuration info
-    println!("📋 Configuration:");
-    println!("  API Base: {}", config.openai_api_base);
-    println!("  Model: {}", config.openai_model_name);
-    println!("  Cache Dir: {}", config.cache_dir_name);
-    println!();
-    
     // Cache info
     let cache_manager = CacheManager::new(path, &config.cache_dir_name)?;
     let (cache_entries, cache_size) = cache_manager.get_cache_stats();
     println!("💾 Cache Information:");
     println!("  Entries: {cache_entries}");
-    println!("  Size: {cache_size} bytes");
+    println!("  Size: {}", format.bytes(cache_size));
+    if let Some(last_updated) = cache_manager.get_all_summaries().iter().map(|s| s.timestamp).max() {
+        println!("  Last Updated: {}", format.timestamp(last_updated));
+    }
     println!("  Valid: {}", cache_manager.is_cache_valid());
     println!();
+
+    if let Some(state) = cache_manager.load_run_state()? {
+        let status = match state.status {
+            RunStatus::Completed => "completed",
+            RunStatus::InProgress => "interrupted or still running",
+        };
+        println!("🏃 Last Run:");
+        println!("  Started: {} ({status})", format.timestamp(state.started_at));
+        println!("  Completed: {}, Pending: {}", state.completed.len(), state.pending.len());
+        if !state.failed.is_empty() {
+            println!("  Failed: {}", state.failed.len());
+            for (failed, error) in &state.failed {
+                println!("    ❌ {}: {error}", failed.display());
+            }
+            println!("  💡 Run 'doctreeai retry-failed' to reattempt them");
+        }
+        if !state.redacted.is_empty() {
+            println!("  Redacted secrets:");
+            for (redacted, findings) in &state.redacted {
+                println!("    🔒 {}: {}", redacted.display(), findings.join(", "));
+            }
+        }
+        println!();
+    }
     
     // README info
     let readme_manager = ReadmeManager::new();
     let readme_info = readme_manager.get_readme_info(path)?;
     
-    println!("📄 README Information:");
-    readme_info.print_summary();
+    readme_info.report(&ConsoleReporter, format);
+
+    let readme_path = path.join("README.md");
+    if readme_path.exists() {
+        let readme_content = std::fs::read_to_string(&readme_path)?;
+        let provenance = ProvenanceTracker::analyze(
+            &readme_content,
+            &cache_manager.get_readme_mapping().generated_sentences,
+        );
+        println!();
+        provenance.report(&ConsoleReporter);
+    }
     
     Ok(())
 }
 
+async fn self_doc_command(self_doc: &SelfDoc, bless: bool) -> Result<()> {
+    println!("🪞 Documenting doctreeai with the mock LLM");
+    let files = self_doc.generate().await?;
+
+    if bless {
+        self_doc.bless(&files)?;
+        println!("✅ Golden files updated in {}", self_doc.golden_dir().display());
+        return Ok(());
+    }
+
+    let mut mismatches = 0;
+    for file in &files {
+        match file.first_difference() {
+            None => println!("✅ {}", file.name),
+            Some((line, expected, actual)) => {
+                mismatches += 1;
+                println!("❌ {} differs at line {line}", file.name);
+                println!("   expected: {expected}");
+                println!("   actual:   {actual}");
+            }
+        }
+    }
+
+    if mismatches > 0 {
+        println!("💡 If the change is intended, run 'doctreeai self-doc --bless' and commit the golden files");
+        return Err(DocTreeError::readme(format!(
+            "{mismatches} of {} golden files differ from the generated output",
+            files.len()
+        )));
+    }
+
+    println!("🎉 Output matches the golden files in {}", self_doc.golden_dir().display());
+    Ok(())
+}
+
+fn manpage_command(out_dir: Option<&Path>) -> Result<()> {
+    let command = Cli::command();
+    let Some(out_dir) = out_dir else {
+        return Ok(clap_mangen::Man::new(command).render(&mut std::io::stdout())?);
+    };
+
+    std::fs::create_dir_all(out_dir)?;
+    clap_mangen::generate_to(command, out_dir)?;
+    println!("✅ Wrote man pages to {}", out_dir.display());
+    Ok(())
+}
+
+async fn models_command(path: &Path) -> Result<()> {
+    let mut config = Config::load()?;
+    config.apply_project_file(path)?;
+    config.validate()?;
+
+    let mut models = doctreeai::models::list_models(&config).await?;
+    doctreeai::models::add_ollama_context_windows(&config, &mut models).await?;
+    if models.is_empty() {
+        println!("ℹ️  {} lists no models", config.openai_api_base);
+        return Ok(());
+    }
+
+    println!("📚 Models served by {}:", config.openai_api_base);
+    let width = models.iter().map(|model| model.id.len()).max().unwrap_or_default();
+    for model in &models {
+        let context_window = model
+            .context_window
+            .map(|tokens| format!("{tokens:>9} tokens"))
+            .unwrap_or_else(|| format!("{:>16}", "-"));
+        let mut roles = Vec::new();
+        if model.id == config.summary_model() {
+            roles.push("summary");
+        }
+        if model.id == config.synthesis_model() {
+            roles.push("synthesis");
+        }
+        if config.embedding_model_name.as_deref() == Some(model.id.as_str()) {
+            roles.push("embedding");
+        }
+        let roles = if roles.is_empty() { String::new() } else { format!("  ← {}", roles.join(", ")) };
+        println!("  {:width$}  {context_window}{roles}", model.id);
+    }
+
+    for model in [config.summary_model(), config.synthesis_model()] {
+        if !models.iter().any(|available| available.id == model) {
+            println!("⚠️  Configured model '{model}' is not in this list");
+        }
+    }
+    Ok(())
+}
+
+async fn doctor_command(path: &Path) -> Result<()> {
+    println!("🩺 Checking DocTreeAI setup in: {}", path.display());
+
+    let findings = doctreeai::doctor::diagnose(path).await;
+    for finding in &findings {
+        match &finding.fix {
+            None => println!("✅ {}: {}", finding.check, finding.message),
+            Some(fix) => {
+                println!("❌ {}: {}", finding.check, finding.message);
+                println!("   💡 {fix}");
+            }
+        }
+    }
+
+    let failed = findings.iter().filter(|finding| !finding.passed()).count();
+    if failed > 0 {
+        println!("\n{failed} of {} checks failed", findings.len());
+        std::process::exit(1);
+    }
+    println!("\n🎯 Everything looks good");
+    Ok(())
+}
+
+/// The effective configuration: environment, then the project file, then
+/// the defaults.
+fn config_list_command(path: &Path) -> Result<()> {
+    let mut config = Config::load()?;
+    config.apply_project_file(path)?;
+
+    let report = config.report();
+    let width = report.iter().map(|setting| setting.name.len()).max().unwrap_or_default();
+    for setting in report {
+        println!("{:width$} = {} ({})", setting.name, setting.value, setting.source);
+    }
+    if let Some(file) = Config::project_file_path(path) {
+        println!();
+        println!("📄 Project file: {}", file.display());
+    }
+    Ok(())
+}
+
+fn config_get_command(path: &Path, name: &str) -> Result<()> {
+    let mut config = Config::load()?;
+    config.apply_project_file(path)?;
+
+    let setting = config.setting(name).ok_or_else(|| {
+        DocTreeError::config(format!("Unknown setting '{name}' - 'doctreeai config list' shows them all"))
+    })?;
+    println!("{}", setting.value);
+    Ok(())
+}
+
+fn config_set_command(path: &Path, key: &str, value: &str) -> Result<()> {
+    let file = Config::set_project_value(path, key, value)?;
+    println!("✅ Updated {}", file.display());
+
+    // The file was written either way; only warn when the env overrides it
+    let Ok(mut config) = Config::load() else {
+        return Ok(());
+    };
+    config.apply_project_file(path)?;
+    if let Some(setting) = config.setting(key).filter(|setting| setting.source == SettingSource::Env) {
+        println!("⚠️  {} is set in the environment, which takes precedence over the file", setting.name);
+    }
+    Ok(())
+}
+
+/// Keeps an API key out of the shell history and dotfiles: the key is read
+/// from stdin and used whenever its environment variable is not set.
+fn set_key_command(key: StoredKey) -> Result<()> {
+    if std::io::stdin().is_terminal() {
+        print!("🔑 Paste the {key} key ({}) and press Enter: ", key.variable());
+        std::io::stdout().flush()?;
+    }
+    let mut secret = String::new();
+    std::io::stdin().read_line(&mut secret)?;
+    let secret = secret.trim();
+    if secret.is_empty() {
+        return Err(DocTreeError::config("No key given - nothing was stored"));
+    }
+
+    key.store(secret)?;
+    println!("✅ Stored {} in the OS keychain", key.variable());
+    let _ = dotenvy::dotenv();
+    if std::env::var(key.variable()).is_ok() {
+        println!("⚠️  {} is also set in the environment or .env, which takes precedence", key.variable());
+    }
+    Ok(())
+}
+
+fn delete_key_command(key: StoredKey) -> Result<()> {
+    if key.delete()? {
+        println!("🗑️  Removed {} from the OS keychain", key.variable());
+    } else {
+        println!("ℹ️  No {} stored in the OS keychain", key.variable());
+    }
+    Ok(())
+}
+
 async fn test_command() -> Result<()> {
     println!("🧪 Testing DocTreeAI configuration...");
     
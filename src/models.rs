@@ -0,0 +1,157 @@
+use crate::config::Config;
+use crate::error::{DocTreeError, Result};
+use crate::http::llm_http_client;
+use tokio::time::{timeout, Duration};
+
+/// Fields servers use for a model's context window in their `/models` list
+/// (Groq, OpenRouter, LM Studio, vLLM).
+const CONTEXT_WINDOW_FIELDS: [&str; 4] = ["context_window", "context_length", "max_context_length", "max_model_len"];
+
+/// Context windows of well-known model families, by model name prefix, for
+/// servers that do not report them. Longer prefixes come first.
+const KNOWN_CONTEXT_WINDOWS: [(&str, u64); 10] = [
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-3.5-turbo", 16_385),
+    ("gpt-oss", 131_072),
+    ("o4-mini", 200_000),
+    ("o3", 200_000),
+    ("o1", 200_000),
+    ("qwen2.5-coder", 32_768),
+    ("llama3.1", 131_072),
+];
+
+/// A model an endpoint serves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelInfo {
+    pub id: String,
+    /// Tokens of prompt and completion the model accepts, when known.
+    pub context_window: Option<u64>,
+}
+
+/// The models an OpenAI-compatible endpoint serves, sorted by name, from its
+/// `/models` list. Parsed leniently: servers like LM Studio leave out fields
+/// the OpenAI schema requires.
+pub async fn list_models(config: &Config) -> Result<Vec<ModelInfo>> {
+    let url = format!("{}/models", config.openai_api_base.trim_end_matches('/'));
+    let request = llm_http_client(config)?.get(&url).bearer_auth(&config.openai_api_key);
+    let body = send_json(config, &url, request).await?;
+    Ok(parse_models(&body))
+}
+
+/// Fills in unknown context windows from Ollama's `/api/show`, which its
+/// OpenAI-compatible `/models` list leaves out. Other servers are left as
+/// they are.
+pub async fn add_ollama_context_windows(config: &Config, models: &mut [ModelInfo]) -> Result<()> {
+    let root = config.openai_api_base.trim_end_matches('/').trim_end_matches("/v1");
+    let url = format!("{root}/api/show");
+    let http = llm_http_client(config)?;
+
+    for model in models.iter_mut().filter(|model| model.context_window.is_none()) {
+        let request = http.post(&url).json(&serde_json::json!({ "model": model.id }));
+        let Ok(body) = send_json(config, &url, request).await else {
+            tracing::debug!("{url} did not answer; not an Ollama server");
+            break;
+        };
+        model.context_window = body["model_info"]
+            .as_object()
+            .and_then(|info| info.iter().find(|(key, _)| key.ends_with(".context_length")))
+            .and_then(|(_, length)| length.as_u64());
+    }
+    Ok(())
+}
+
+/// Checks that the endpoint serves the configured models before a run
+/// spends time scanning. Endpoints that cannot list their models pass.
+pub async fn ensure_available(config: &Config) -> Result<()> {
+    let available = match list_models(config).await {
+        Ok(available) if !available.is_empty() => available,
+        Ok(_) => return Ok(()),
+        Err(e) => {
+            tracing::debug!("Not checking the configured models: {e}");
+            return Ok(());
+        }
+    };
+
+    for model in [config.summary_model(), config.synthesis_model()] {
+        if !available.iter().any(|available| available.id == model) {
+            let names: Vec<&str> = available.iter().take(5).map(|available| available.id.as_str()).collect();
+            return Err(DocTreeError::config(format!(
+                "Model '{model}' is not served by {} (available: {}); 'doctreeai models' lists them all",
+                config.openai_api_base,
+                names.join(", ")
+            )));
+        }
+    }
+    Ok(())
+}
+
+async fn send_json(config: &Config, url: &str, request: reqwest::RequestBuilder) -> Result<serde_json::Value> {
+    let failed = |e: &dyn std::fmt::Display| DocTreeError::config(format!("Could not list models at {url}: {e}"));
+    let response = timeout(Duration::from_secs(config.request_timeout_secs), request.send())
+        .await
+        .map_err(|_| failed(&format!("no answer within {}s", config.request_timeout_secs)))?
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| failed(&e))?;
+    response.json().await.map_err(|e| failed(&e))
+}
+
+fn parse_models(body: &serde_json::Value) -> Vec<ModelInfo> {
+    let mut models: Vec<ModelInfo> = body["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|model| {
+            let id = model["id"].as_str()?.to_string();
+            let context_window = CONTEXT_WINDOW_FIELDS
+                .iter()
+                .find_map(|field| model[field].as_u64())
+                .or_else(|| known_context_window(&id));
+            Some(ModelInfo { id, context_window })
+        })
+        .collect();
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    models.dedup_by(|a, b| a.id == b.id);
+    models
+}
+
+fn known_context_window(id: &str) -> Option<u64> {
+    // Provider prefixes like `openai/gpt-4o` (OpenRouter, LiteLLM) are ignored
+    let name = id.rsplit('/').next().unwrap_or(id).to_lowercase();
+    KNOWN_CONTEXT_WINDOWS
+        .iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+        .map(|(_, context_window)| *context_window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_models_parse_leniently_with_context_windows() {
+        let body = serde_json::json!({
+            "object": "list",
+            "data": [
+                { "id": "qwen2.5-coder:7b", "object": "model", "created": 1, "owned_by": "library" },
+                { "id": "openai/gpt-4o-mini", "object": "model", "context_length": 64000 },
+                { "id": "gpt-oss-20b", "object": "model" },
+                { "id": "mystery", "object": "model" },
+                { "object": "model" },
+            ],
+        });
+        let models: Vec<(String, Option<u64>)> =
+            parse_models(&body).into_iter().map(|model| (model.id, model.context_window)).collect();
+        assert_eq!(
+            models,
+            vec![
+                ("gpt-oss-20b".to_string(), Some(131_072)),
+                ("mystery".to_string(), None),
+                ("openai/gpt-4o-mini".to_string(), Some(64_000)),
+                ("qwen2.5-coder:7b".to_string(), Some(32_768)),
+            ]
+        );
+        assert!(parse_models(&serde_json::json!({ "error": "unauthorized" })).is_empty());
+    }
+}
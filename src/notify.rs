@@ -0,0 +1,192 @@
+use crate::error::{DocTreeError, Result};
+use crate::hasher::FileHasher;
+use crate::readme_validator::ValidationResult;
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+use std::str::FromStr;
+
+/// Drift found by the last run, in the cache directory, so only drift that
+/// is new since then is notified.
+pub const REPORTED_DRIFT_FILE: &str = "reported_drift.json";
+
+/// How README drift found during a run is surfaced to the developer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NotifyMode {
+    #[default]
+    Off,
+    /// Ring the terminal bell; tmux and most terminals flag the window.
+    Bell,
+    /// Send a desktop notification via notify-send (Linux) or osascript (macOS),
+    /// falling back to the terminal bell.
+    Desktop,
+}
+
+impl FromStr for NotifyMode {
+    type Err = DocTreeError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "" | "off" | "none" => Ok(NotifyMode::Off),
+            "bell" => Ok(NotifyMode::Bell),
+            "desktop" => Ok(NotifyMode::Desktop),
+            other => Err(DocTreeError::config(format!(
+                "Invalid notification mode '{other}' (expected off, bell, or desktop)"
+            ))),
+        }
+    }
+}
+
+pub struct Notifier {
+    mode: NotifyMode,
+}
+
+impl Notifier {
+    pub fn new(mode: NotifyMode) -> Self {
+        Self { mode }
+    }
+
+    /// Notifies about README lines that drifted from the codebase since the
+    /// last run, and records this run's drift in the cache directory unless
+    /// `read_only`. Drift that was already reported, or none at all, sends
+    /// nothing, so repeated runs (e.g. in watch mode) stay quiet.
+    pub fn notify_new_drift(
+        &self,
+        cache_dir: &Path,
+        project_name: &str,
+        results: &[ValidationResult],
+        read_only: bool,
+    ) -> Result<()> {
+        if self.mode == NotifyMode::Off {
+            return Ok(());
+        }
+        let new_drift = Self::record_drift(cache_dir, results, read_only)?;
+        self.notify_drift(project_name, new_drift);
+        Ok(())
+    }
+
+    /// Counts the drifted lines the last run did not report, then replaces
+    /// its record with this run's.
+    fn record_drift(cache_dir: &Path, results: &[ValidationResult], read_only: bool) -> Result<usize> {
+        let path = cache_dir.join(REPORTED_DRIFT_FILE);
+        let reported: BTreeSet<String> = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        let drift: BTreeSet<String> = results.iter().map(Self::drift_key).collect();
+        let new_drift = drift.difference(&reported).count();
+
+        if !read_only && drift != reported {
+            fs::create_dir_all(cache_dir)?;
+            let temp_path = cache_dir.join(format!("{REPORTED_DRIFT_FILE}.tmp"));
+            fs::write(&temp_path, serde_json::to_string(&drift)?)
+                .and_then(|_| fs::rename(&temp_path, &path))
+                .map_err(|e| DocTreeError::cache(format!("Failed to write {}: {e}", path.display())))?;
+        }
+        Ok(new_drift)
+    }
+
+    /// Identifies a drifted line by its section and text rather than its
+    /// number, which shifts as the README is edited above it.
+    fn drift_key(result: &ValidationResult) -> String {
+        FileHasher::compute_content_hash(&format!("{}\n{}", result.section, result.current_content))
+    }
+
+    fn notify_drift(&self, project_name: &str, drifted_lines: usize) {
+        if drifted_lines == 0 {
+            return;
+        }
+
+        let message = Self::drift_message(drifted_lines);
+        match self.mode {
+            NotifyMode::Off => {}
+            NotifyMode::Bell => Self::ring_bell(),
+            NotifyMode::Desktop => {
+                if let Err(e) = Self::send_desktop(&format!("DocTreeAI: {project_name}"), &message) {
+                    tracing::debug!("Desktop notification failed, ringing bell instead: {e}");
+                    Self::ring_bell();
+                }
+            }
+        }
+    }
+
+    fn drift_message(drifted_lines: usize) -> String {
+        if drifted_lines == 1 {
+            "1 README line is out of date".to_string()
+        } else {
+            format!("{drifted_lines} README lines are out of date")
+        }
+    }
+
+    fn ring_bell() {
+        let mut stderr = std::io::stderr();
+        let _ = stderr.write_all(b"\x07");
+        let _ = stderr.flush();
+    }
+
+    fn send_desktop(title: &str, message: &str) -> Result<()> {
+        let status = if cfg!(target_os = "macos") {
+            let script = format!(
+                "display notification {:?} with title {:?}",
+                message, title
+            );
+            Command::new("osascript").args(["-e", &script]).status()?
+        } else {
+            Command::new("notify-send").args([title, message]).status()?
+        };
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(DocTreeError::config(format!("Notifier exited with {status}")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::readme_validator::Severity;
+
+    #[test]
+    fn test_parse_notify_mode() -> Result<()> {
+        assert_eq!("bell".parse::<NotifyMode>()?, NotifyMode::Bell);
+        assert_eq!("Desktop".parse::<NotifyMode>()?, NotifyMode::Desktop);
+        assert_eq!("off".parse::<NotifyMode>()?, NotifyMode::Off);
+        assert!("email".parse::<NotifyMode>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_only_new_drift_is_counted() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let cache_dir = temp_dir.path().join(".test_cache");
+        let drift = |current: &str| ValidationResult {
+            line_number: 3,
+            current_content: current.to_string(),
+            suggested_content: String::new(),
+            reason: String::new(),
+            affected_cache_entries: Vec::new(),
+            section: "Usage".to_string(),
+            confidence: 1.0,
+            severity: Severity::Outdated,
+        };
+
+        assert_eq!(Notifier::record_drift(&cache_dir, &[drift("a")], true)?, 1);
+        assert_eq!(Notifier::record_drift(&cache_dir, &[drift("a")], false)?, 1);
+        assert_eq!(Notifier::record_drift(&cache_dir, &[drift("a")], false)?, 0);
+        assert_eq!(Notifier::record_drift(&cache_dir, &[drift("a"), drift("b")], false)?, 1);
+        // Fixed, then drifted again
+        assert_eq!(Notifier::record_drift(&cache_dir, &[], false)?, 0);
+        assert_eq!(Notifier::record_drift(&cache_dir, &[drift("a")], false)?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_drift_message() {
+        assert_eq!(Notifier::drift_message(1), "1 README line is out of date");
+        assert_eq!(Notifier::drift_message(3), "3 README lines are out of date");
+    }
+}
@@ -0,0 +1,440 @@
+use crate::error::{DocTreeError, Result};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use tree_sitter::{Language, Node, Parser};
+
+/// Longest type definition (struct fields, enum variants) kept whole.
+const MAX_DEFINITION_LINES: usize = 12;
+
+/// Longest doc comment kept, in lines.
+const MAX_DOC_LINES: usize = 6;
+
+/// Longest signature kept, in characters.
+const MAX_SIGNATURE_CHARS: usize = 300;
+
+/// What file summaries are generated from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SummaryInput {
+    /// The file content, after the file size limit.
+    #[default]
+    Content,
+    /// An outline of the file's functions, types, signatures, and doc
+    /// comments, for languages with a grammar; other files are sent whole.
+    Outline,
+}
+
+impl FromStr for SummaryInput {
+    type Err = DocTreeError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "content" => Ok(SummaryInput::Content),
+            "outline" => Ok(SummaryInput::Outline),
+            other => Err(DocTreeError::config(format!(
+                "Invalid summary input '{other}' (expected content or outline)"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for SummaryInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SummaryInput::Content => write!(f, "content"),
+            SummaryInput::Outline => write!(f, "outline"),
+        }
+    }
+}
+
+/// Node kinds that make up a language's outline.
+struct Grammar {
+    language: Language,
+    comment_prefix: &'static str,
+    /// Declarations listed in the outline.
+    items: &'static [&'static str],
+    /// Declarations whose bodies hold more items (impl blocks, classes).
+    containers: &'static [&'static str],
+    /// Declarations shown whole when short (struct fields, enum variants).
+    definitions: &'static [&'static str],
+    /// Wrappers listed as the declaration they hold (exports, decorators).
+    wrappers: &'static [&'static str],
+    /// Comments that document the declaration after them.
+    doc_prefixes: &'static [&'static str],
+}
+
+fn grammar(relative_path: &Path) -> Option<Grammar> {
+    let extension = relative_path.extension()?.to_str()?;
+    let script_items: &[&str] = &[
+        "function_declaration",
+        "generator_function_declaration",
+        "class_declaration",
+        "abstract_class_declaration",
+        "method_definition",
+        "lexical_declaration",
+        "interface_declaration",
+        "type_alias_declaration",
+        "enum_declaration",
+        "function_signature",
+        "method_signature",
+        "abstract_method_signature",
+    ];
+    let script = |language: Language| Grammar {
+        language,
+        comment_prefix: "//",
+        items: script_items,
+        containers: &["class_declaration", "abstract_class_declaration"],
+        definitions: &["interface_declaration", "enum_declaration"],
+        wrappers: &["export_statement"],
+        doc_prefixes: &["/**"],
+    };
+
+    Some(match extension {
+        "rs" => Grammar {
+            language: tree_sitter_rust::LANGUAGE.into(),
+            comment_prefix: "//",
+            items: &[
+                "function_item",
+                "function_signature_item",
+                "struct_item",
+                "enum_item",
+                "union_item",
+                "trait_item",
+                "impl_item",
+                "type_item",
+                "const_item",
+                "static_item",
+                "mod_item",
+                "macro_definition",
+            ],
+            containers: &["impl_item", "trait_item", "mod_item"],
+            definitions: &["struct_item", "enum_item", "union_item"],
+            wrappers: &[],
+            doc_prefixes: &["///", "/**"],
+        },
+        "py" | "pyi" => Grammar {
+            language: tree_sitter_python::LANGUAGE.into(),
+            comment_prefix: "#",
+            items: &["function_definition", "class_definition"],
+            containers: &["class_definition"],
+            definitions: &[],
+            wrappers: &["decorated_definition"],
+            doc_prefixes: &[],
+        },
+        "js" | "jsx" | "mjs" | "cjs" => script(tree_sitter_javascript::LANGUAGE.into()),
+        "ts" | "mts" | "cts" => script(tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into()),
+        "tsx" => script(tree_sitter_typescript::LANGUAGE_TSX.into()),
+        "go" => Grammar {
+            language: tree_sitter_go::LANGUAGE.into(),
+            comment_prefix: "//",
+            items: &["function_declaration", "method_declaration", "type_declaration"],
+            containers: &[],
+            definitions: &["type_declaration"],
+            wrappers: &[],
+            doc_prefixes: &["//"],
+        },
+        _ => return None,
+    })
+}
+
+/// An outline of a source file: its functions, types, and signatures with
+/// their doc comments, nested under impl blocks and classes, and without
+/// bodies. None for languages without a grammar, or when the file declares
+/// nothing.
+pub fn extract_outline(relative_path: &Path, content: &str) -> Option<String> {
+    let grammar = grammar(relative_path)?;
+    let mut parser = Parser::new();
+    parser.set_language(&grammar.language).ok()?;
+    let tree = parser.parse(content, None)?;
+    let root = tree.root_node();
+
+    let mut lines = Vec::new();
+    if let Some(docstring) = docstring(root, content) {
+        lines.push(docstring);
+    }
+    outline_items(root, content, &grammar, 0, &mut lines);
+    if lines.is_empty() {
+        return None;
+    }
+
+    Some(format!(
+        "{} Outline of {}: signatures and doc comments only, bodies are omitted.\n{}\n",
+        grammar.comment_prefix,
+        relative_path.display(),
+        lines.join("\n")
+    ))
+}
+
+/// A form of the file that is the same however it is formatted or
+/// commented, for hashing. Languages with a grammar are reduced to their
+/// syntax tree without comments; other files (and files that do not parse)
+/// only lose trailing whitespace and blank lines, since indentation may
+/// carry meaning there.
+pub fn normalized_source(relative_path: &Path, content: &str) -> String {
+    let tree = grammar(relative_path).and_then(|grammar| {
+        let mut parser = Parser::new();
+        parser.set_language(&grammar.language).ok()?;
+        parser.parse(content, None).filter(|tree| !tree.root_node().has_error())
+    });
+    let Some(tree) = tree else {
+        return content.lines().map(str::trim_end).filter(|line| !line.is_empty()).collect::<Vec<_>>().join("\n");
+    };
+
+    let mut normalized = String::new();
+    write_syntax(tree.root_node(), content, &mut normalized);
+    normalized
+}
+
+/// Writes tokens one per line, with named nodes bracketed by their kind so
+/// that structure carried by indentation (Python blocks) is kept. Text
+/// between children that no child covers (string contents in some
+/// grammars) is written too.
+fn write_syntax(node: Node, source: &str, out: &mut String) {
+    if node.kind().contains("comment") {
+        return;
+    }
+    if node.child_count() == 0 {
+        out.push_str(&text_between(source, node.start_byte(), node.end_byte()));
+        return;
+    }
+    if node.is_named() {
+        out.push_str(&format!("({}\n", node.kind()));
+    }
+    let mut position = node.start_byte();
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        out.push_str(&text_between(source, position, child.start_byte()));
+        write_syntax(child, source, out);
+        position = child.end_byte();
+    }
+    out.push_str(&text_between(source, position, node.end_byte()));
+    if node.is_named() {
+        out.push_str(")\n");
+    }
+}
+
+/// The trimmed text in a byte range, as a line of its own; empty when blank.
+fn text_between(source: &str, start: usize, end: usize) -> String {
+    let text = source.get(start..end).unwrap_or_default().trim();
+    if text.is_empty() { String::new() } else { format!("{text}\n") }
+}
+
+fn outline_items(parent: Node, source: &str, grammar: &Grammar, depth: usize, lines: &mut Vec<String>) {
+    let indent = "    ".repeat(depth);
+    let mut cursor = parent.walk();
+
+    for node in parent.named_children(&mut cursor) {
+        let text = &source[node.byte_range()];
+        // Rust module docs (`//!`) describe the file, not the next item
+        if node.kind().contains("comment") && text.starts_with("//!") {
+            lines.push(format!("{indent}{}", text.trim_end()));
+            continue;
+        }
+
+        let declaration = if grammar.wrappers.contains(&node.kind()) {
+            let Some(inner) = node
+                .child_by_field_name("declaration")
+                .or_else(|| node.child_by_field_name("definition"))
+            else {
+                continue;
+            };
+            inner
+        } else {
+            node
+        };
+        if !grammar.items.contains(&declaration.kind()) {
+            continue;
+        }
+        let Some(signature) = signature(node, declaration, source, grammar, &indent) else {
+            continue;
+        };
+
+        lines.extend(doc_comments(node, source, grammar).into_iter().map(|line| format!("{indent}{line}")));
+        lines.push(signature);
+
+        let body = declaration.child_by_field_name("body");
+        if let Some(docstring) = body.and_then(|body| docstring(body, source)) {
+            lines.push(format!("{indent}    {docstring}"));
+        }
+        if let Some(body) = body.filter(|_| grammar.containers.contains(&declaration.kind())) {
+            outline_items(body, source, grammar, depth + 1, lines);
+        }
+    }
+}
+
+/// The declaration up to its body, or whole when it is a short type
+/// definition. None for declarations not worth listing (non-function
+/// variables that are not exported).
+fn signature(node: Node, declaration: Node, source: &str, grammar: &Grammar, indent: &str) -> Option<String> {
+    let text = &source[node.byte_range()];
+    if grammar.definitions.contains(&declaration.kind()) && text.lines().count() <= MAX_DEFINITION_LINES {
+        let column = node.start_position().column;
+        let lines: Vec<String> = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| {
+                let line = if index == 0 { line } else { line.get(column..).unwrap_or(line.trim_start()) };
+                format!("{indent}{}", line.trim_end())
+            })
+            .collect();
+        return Some(lines.join("\n"));
+    }
+
+    let end = match declaration.child_by_field_name("body") {
+        Some(body) => body.start_byte(),
+        None if declaration.kind() == "lexical_declaration" => {
+            // `const handler = async (req) => { ... }` is listed as a function
+            let function_body = declaration
+                .named_child(0)
+                .and_then(|declarator| declarator.child_by_field_name("value"))
+                .filter(|value| value.kind().contains("function"))
+                .and_then(|value| value.child_by_field_name("body"));
+            match function_body {
+                Some(body) => body.start_byte(),
+                None if node.id() != declaration.id() => short_end(node, source),
+                None => return None,
+            }
+        }
+        None => short_end(node, source),
+    };
+
+    let signature = source[node.start_byte()..end]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let signature = signature.trim_end_matches(['{', ':', '=', ' ']).trim_end_matches("=>").trim_end();
+    let signature: String = signature.chars().take(MAX_SIGNATURE_CHARS).collect();
+    Some(format!("{indent}{signature}"))
+}
+
+/// End of a declaration without a body: all of it when it is one short
+/// line, otherwise up to its value or the end of the first line.
+fn short_end(node: Node, source: &str) -> usize {
+    let text = &source[node.byte_range()];
+    if !text.contains('\n') && text.len() <= MAX_SIGNATURE_CHARS {
+        return node.end_byte();
+    }
+    let first_line = text.find('\n').unwrap_or(text.len());
+    let value = text.find(" = ").unwrap_or(text.len());
+    node.start_byte() + first_line.min(value)
+}
+
+/// Doc comments directly above a declaration, skipping Rust attributes
+/// between them.
+fn doc_comments(node: Node, source: &str, grammar: &Grammar) -> Vec<String> {
+    let mut comments = Vec::new();
+    let mut next_row = node.start_position().row;
+    let mut sibling = node.prev_named_sibling();
+
+    while let Some(previous) = sibling {
+        if previous.end_position().row + 1 < next_row {
+            break;
+        }
+        let text = source[previous.byte_range()].trim();
+        if previous.kind() == "attribute_item" {
+            next_row = previous.start_position().row;
+        } else if previous.kind().contains("comment") && grammar.doc_prefixes.iter().any(|prefix| text.starts_with(prefix)) {
+            comments.push(text.to_string());
+            next_row = previous.start_position().row;
+        } else {
+            break;
+        }
+        sibling = previous.prev_named_sibling();
+    }
+
+    comments.reverse();
+    comments
+        .iter()
+        .flat_map(|comment| comment.lines())
+        .map(str::trim)
+        .take(MAX_DOC_LINES)
+        .map(str::to_string)
+        .collect()
+}
+
+/// First line of a Python docstring: a string as the first statement of a
+/// module, class, or function body.
+fn docstring(body: Node, source: &str) -> Option<String> {
+    let statement = body.named_child(0).filter(|statement| statement.kind() == "expression_statement")?;
+    let string = statement.named_child(0).filter(|string| string.kind() == "string")?;
+    let text = source[string.byte_range()].trim_matches(['"', '\'']).trim();
+    text.lines().next().map(|line| format!("\"\"\"{}\"\"\"", line.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rust_outline() {
+        let source = r#"//! Parses input.
+use std::fmt;
+
+/// A parsed value.
+#[derive(Debug)]
+pub struct Value {
+    pub name: String,
+}
+
+impl Value {
+    /// Creates a value.
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string() }
+    }
+}
+
+pub fn parse(
+    input: &str,
+) -> Option<Value> {
+    None
+}
+"#;
+        let outline = extract_outline(Path::new("src/parser.rs"), source).unwrap();
+        assert_eq!(
+            outline,
+            "// Outline of src/parser.rs: signatures and doc comments only, bodies are omitted.\n\
+            //! Parses input.\n\
+            /// A parsed value.\n\
+            pub struct Value {\n    pub name: String,\n}\n\
+            impl Value\n    \
+            /// Creates a value.\n    \
+            pub fn new(name: &str) -> Self\n\
+            pub fn parse( input: &str, ) -> Option<Value>\n"
+        );
+        assert!(extract_outline(Path::new("notes.txt"), source).is_none());
+    }
+
+    #[test]
+    fn test_python_and_typescript_outlines() {
+        let python = "\"\"\"Command helpers.\"\"\"\n\n@cache\ndef run(args):\n    \"\"\"Runs a command.\n\n    More.\"\"\"\n    return 1\n\nclass Tool:\n    def call(self):\n        pass\n";
+        let outline = extract_outline(Path::new("tool.py"), python).unwrap();
+        assert!(outline.starts_with("# Outline of tool.py"));
+        assert!(outline.contains("\"\"\"Command helpers.\"\"\"\n@cache def run(args)\n    \"\"\"Runs a command.\"\"\"\nclass Tool\n    def call(self)\n"));
+
+        let typescript = "/** Loads a card. */\nexport async function load(id: string): Promise<Card> {\n  return fetch(id);\n}\nconst local = 1;\nexport const save = async (card: Card) => {\n  return card;\n};\n";
+        let outline = extract_outline(Path::new("api.ts"), typescript).unwrap();
+        assert!(outline.contains(
+            "/** Loads a card. */\nexport async function load(id: string): Promise<Card>\nexport const save = async (card: Card)\n"
+        ));
+        assert!(!outline.contains("local"));
+    }
+
+    #[test]
+    fn test_normalized_source_ignores_formatting_and_comments() {
+        let normalized = |path: &str, source: &str| normalized_source(Path::new(path), source);
+        let rust = normalized("lib.rs", "/// Adds.\nfn add(a: i32, b: i32) -> i32 { a + b }\n");
+        assert_eq!(rust, normalized("lib.rs", "// Reformatted\nfn add(a: i32,\n       b: i32) -> i32 {\n    a + b // sum\n}\n"));
+        assert_ne!(rust, normalized("lib.rs", "fn add(a: i32, b: i32) -> i32 { a - b }\n"));
+        assert_ne!(
+            normalized("log.go", "package log\nvar prefix = \"a b\"\n"),
+            normalized("log.go", "package log\nvar prefix = \"ab\"\n")
+        );
+
+        // Indentation is structure in Python
+        let python = normalized("run.py", "if ready:\n    start()\n    stop()\n");
+        assert_eq!(python, normalized("run.py", "if ready:  # check\n\n    start()\n    stop()\n"));
+        assert_ne!(python, normalized("run.py", "if ready:\n    start()\nstop()\n"));
+
+        assert_eq!(normalized("notes.txt", "a  \n\n  b\n"), "a\n  b");
+    }
+}
@@ -0,0 +1,165 @@
+use crate::cache::SummaryMethod;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Live progress from `HierarchicalSummarizer`, for GUIs and editor plugins.
+/// Paths are relative to the project root; every method defaults to a no-op.
+pub trait ProgressListener: Send + Sync {
+    /// The scan found a file the run will summarize; its hashing starts
+    /// while the scan goes on.
+    fn on_file_discovered(&self, _path: &Path) {}
+
+    /// A file is about to be summarized (after cache and skip checks).
+    fn on_file_start(&self, _path: &Path) {}
+
+    fn on_file_summarized(&self, _path: &Path, _method: SummaryMethod) {}
+
+    /// A file or directory summary was taken from the cache (or is pinned).
+    fn on_cache_hit(&self, _path: &Path) {}
+
+    /// A directory has its summary, whether cached, generated, or a fallback.
+    fn on_directory_done(&self, _path: &Path) {}
+}
+
+/// The callbacks as values, for consumers that prefer a stream: a channel
+/// sender is itself a `ProgressListener`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    FileDiscovered { path: PathBuf },
+    FileStart { path: PathBuf },
+    FileSummarized { path: PathBuf, method: SummaryMethod },
+    CacheHit { path: PathBuf },
+    DirectoryDone { path: PathBuf },
+}
+
+/// Forwards events as `ProgressEvent`s; a dropped receiver is ignored.
+pub trait ProgressSink: Send + Sync {
+    fn send_event(&self, event: ProgressEvent);
+}
+
+impl ProgressSink for std::sync::mpsc::Sender<ProgressEvent> {
+    fn send_event(&self, event: ProgressEvent) {
+        let _ = self.send(event);
+    }
+}
+
+impl ProgressSink for tokio::sync::mpsc::UnboundedSender<ProgressEvent> {
+    fn send_event(&self, event: ProgressEvent) {
+        let _ = self.send(event);
+    }
+}
+
+impl<T: ProgressSink> ProgressListener for T {
+    fn on_file_discovered(&self, path: &Path) {
+        self.send_event(ProgressEvent::FileDiscovered { path: path.to_path_buf() });
+    }
+
+    fn on_file_start(&self, path: &Path) {
+        self.send_event(ProgressEvent::FileStart { path: path.to_path_buf() });
+    }
+
+    fn on_file_summarized(&self, path: &Path, method: SummaryMethod) {
+        self.send_event(ProgressEvent::FileSummarized { path: path.to_path_buf(), method });
+    }
+
+    fn on_cache_hit(&self, path: &Path) {
+        self.send_event(ProgressEvent::CacheHit { path: path.to_path_buf() });
+    }
+
+    fn on_directory_done(&self, path: &Path) {
+        self.send_event(ProgressEvent::DirectoryDone { path: path.to_path_buf() });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheManager;
+    use crate::config::Config;
+    use crate::error::Result;
+    use crate::annotations::IgnorePatterns;
+    use crate::llm::LanguageModelClient;
+    use crate::summarizer::HierarchicalSummarizer;
+    use std::fs;
+    use std::sync::{mpsc, Arc};
+    use tempfile::TempDir;
+
+    async fn run(base_path: &Path) -> Result<Vec<ProgressEvent>> {
+        run_with(base_path, |_| {}).await
+    }
+
+    async fn run_with(base_path: &Path, configure: impl FnOnce(&mut HierarchicalSummarizer)) -> Result<Vec<ProgressEvent>> {
+        let (sender, receiver) = mpsc::channel();
+        let cache_manager = CacheManager::new(base_path, ".test_cache")?;
+        let mut summarizer =
+            HierarchicalSummarizer::new(LanguageModelClient::mock(&Config::default()), cache_manager, false);
+        configure(&mut summarizer);
+        summarizer.set_progress(Some(Arc::new(sender)));
+        summarizer.generate_project_summary(base_path).await?;
+        drop(summarizer);
+        Ok(receiver.iter().collect())
+    }
+
+    #[tokio::test]
+    async fn test_summarizer_emits_progress_events() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        fs::create_dir_all(base_path.join("src"))?;
+        fs::write(base_path.join("src/main.rs"), "fn main() {}")?;
+
+        let main_rs = PathBuf::from("src/main.rs");
+        assert_eq!(
+            run(base_path).await?,
+            vec![
+                ProgressEvent::FileDiscovered { path: main_rs.clone() },
+                ProgressEvent::FileStart { path: main_rs.clone() },
+                ProgressEvent::FileSummarized { path: main_rs.clone(), method: SummaryMethod::Llm },
+                ProgressEvent::DirectoryDone { path: PathBuf::from("src") },
+                ProgressEvent::DirectoryDone { path: PathBuf::new() },
+            ]
+        );
+
+        let cached = run(base_path).await?;
+        assert_eq!(cached[1], ProgressEvent::CacheHit { path: main_rs });
+        assert!(!cached.iter().any(|event| matches!(event, ProgressEvent::FileStart { .. })));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_directories_are_summarized_while_the_scan_continues() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        for directory in ["api", "cli"] {
+            fs::create_dir_all(base_path.join(directory))?;
+            fs::write(base_path.join(directory).join("mod.rs"), "pub fn run() {}")?;
+        }
+        fs::write(base_path.join("api/schema.rs"), "pub struct Schema;")?;
+        // Old enough that the second run takes the cached hashes without
+        // waiting on hashing tasks
+        let an_hour_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        for file in ["api/mod.rs", "cli/mod.rs"] {
+            fs::File::options().write(true).open(base_path.join(file))?.set_modified(an_hour_ago)?;
+        }
+
+        let configure = |summarizer: &mut HierarchicalSummarizer| {
+            summarizer.set_offline(true);
+            summarizer.set_ignore_patterns(IgnorePatterns::new(&["api/schema.rs".to_string()]).unwrap());
+        };
+        run_with(base_path, configure).await?;
+        let events = run_with(base_path, configure).await?;
+        let position = |expected: ProgressEvent| events.iter().position(|event| *event == expected).unwrap();
+        let discovered = |path: &str| position(ProgressEvent::FileDiscovered { path: PathBuf::from(path) });
+        let done = |path: &str| position(ProgressEvent::DirectoryDone { path: PathBuf::from(path) });
+
+        // Whichever directory the walk visits first is summarized before the
+        // other one's files are found
+        let (first, second) = if discovered("api/mod.rs") < discovered("cli/mod.rs") { ("api", "cli") } else { ("cli", "api") };
+        assert!(done(first) < discovered(&format!("{second}/mod.rs")));
+        assert_eq!(events.last(), Some(&ProgressEvent::DirectoryDone { path: PathBuf::new() }));
+        assert!(!events.contains(&ProgressEvent::FileDiscovered { path: PathBuf::from("api/schema.rs") }));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+use crate::hasher::FileHasher;
+use crate::reporter::Reporter;
+use std::collections::{BTreeSet, HashSet};
+
+/// Marks the following paragraph as machine-generated.
+pub const GENERATED_MARKER: &str = "<!-- doctreeai:generated -->";
+
+/// Marks the following paragraph as deliberately manual; the validator never
+/// suggests changes to it.
+pub const MANUAL_MARKER: &str = "<!-- doctreeai:manual -->";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provenance {
+    Generated,
+    Manual,
+}
+
+#[derive(Debug, Clone)]
+pub struct LineProvenance {
+    pub line_number: usize,
+    pub provenance: Provenance,
+    pub locked: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ProvenanceReport {
+    pub lines: Vec<LineProvenance>,
+    pub generated_sentences: usize,
+    pub manual_sentences: usize,
+}
+
+impl ProvenanceReport {
+    pub fn generated_ratio(&self) -> f64 {
+        let total = self.generated_sentences + self.manual_sentences;
+        if total == 0 {
+            0.0
+        } else {
+            self.generated_sentences as f64 / total as f64
+        }
+    }
+
+    /// Whether the line sits in a paragraph explicitly marked as manual.
+    pub fn is_locked_manual(&self, line_number: usize) -> bool {
+        self.lines
+            .iter()
+            .any(|line| line.line_number == line_number && line.locked)
+    }
+
+    pub fn report(&self, reporter: &dyn Reporter) {
+        let total = self.generated_sentences + self.manual_sentences;
+        let mut lines = vec![
+            format!("Sentences: {total} total"),
+            format!(
+                "  Generated: {} ({:.0}%)",
+                self.generated_sentences,
+                self.generated_ratio() * 100.0
+            ),
+            format!("  Manual: {}", self.manual_sentences),
+        ];
+
+        let locked = self.lines.iter().filter(|line| line.locked).count();
+        if locked > 0 {
+            lines.push(format!("  Lines protected by manual markers: {locked}"));
+        }
+        reporter.section("✍️  README Provenance:", &lines);
+    }
+}
+
+pub struct ProvenanceTracker;
+
+impl ProvenanceTracker {
+    /// Classifies every prose line of a README as generated or manual, using
+    /// paragraph markers first and then the sentence hashes recorded whenever
+    /// doctreeai produced content.
+    pub fn analyze(readme_content: &str, generated_hashes: &BTreeSet<String>) -> ProvenanceReport {
+        let mut report = ProvenanceReport::default();
+        let mut paragraph_marker: Option<Provenance> = None;
+        let mut in_code_block = false;
+
+        for (index, line) in readme_content.lines().enumerate() {
+            let trimmed = line.trim();
+
+            if trimmed.starts_with("```") {
+                in_code_block = !in_code_block;
+                continue;
+            }
+
+            if trimmed.is_empty() {
+                paragraph_marker = None;
+                continue;
+            }
+
+            if trimmed == GENERATED_MARKER {
+                paragraph_marker = Some(Provenance::Generated);
+                continue;
+            }
+
+            if trimmed == MANUAL_MARKER {
+                paragraph_marker = Some(Provenance::Manual);
+                continue;
+            }
+
+            if in_code_block || trimmed.starts_with('#') || trimmed.starts_with("<!--") {
+                continue;
+            }
+
+            let sentences = Self::split_sentences(trimmed);
+            let provenance = match paragraph_marker {
+                Some(marked) => marked,
+                None if !sentences.is_empty()
+                    && sentences
+                        .iter()
+                        .all(|sentence| generated_hashes.contains(&Self::sentence_hash(sentence))) =>
+                {
+                    Provenance::Generated
+                }
+                None => Provenance::Manual,
+            };
+
+            for sentence in &sentences {
+                let generated = match paragraph_marker {
+                    Some(marked) => marked == Provenance::Generated,
+                    None => generated_hashes.contains(&Self::sentence_hash(sentence)),
+                };
+                if generated {
+                    report.generated_sentences += 1;
+                } else {
+                    report.manual_sentences += 1;
+                }
+            }
+
+            report.lines.push(LineProvenance {
+                line_number: index + 1,
+                provenance,
+                locked: paragraph_marker == Some(Provenance::Manual),
+            });
+        }
+
+        report
+    }
+
+    /// Hashes of every sentence in generated text, suitable for recording in
+    /// the mapping store.
+    pub fn generated_sentence_hashes(content: &str) -> HashSet<String> {
+        content
+            .lines()
+            .flat_map(Self::split_sentences)
+            .map(|sentence| Self::sentence_hash(&sentence))
+            .collect()
+    }
+
+    pub fn sentence_hash(sentence: &str) -> String {
+        let normalized = sentence
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .to_lowercase();
+        FileHasher::compute_content_hash(&normalized)
+    }
+
+    fn split_sentences(line: &str) -> Vec<String> {
+        let mut sentences = Vec::new();
+        let mut current = String::new();
+        let mut chars = line.trim().chars().peekable();
+
+        while let Some(c) = chars.next() {
+            current.push(c);
+            let at_boundary = matches!(c, '.' | '!' | '?')
+                && chars.peek().is_none_or(|next| next.is_whitespace());
+            if at_boundary {
+                let sentence = current.trim();
+                if !sentence.is_empty() {
+                    sentences.push(sentence.to_string());
+                }
+                current.clear();
+            }
+        }
+
+        let rest = current.trim();
+        if !rest.is_empty() {
+            sentences.push(rest.to_string());
+        }
+
+        sentences
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markers_and_recorded_hashes() {
+        let generated: BTreeSet<String> =
+            ProvenanceTracker::generated_sentence_hashes("The cache stores summaries.")
+                .into_iter()
+                .collect();
+
+        let readme = "# Title\n\nThe cache stores summaries.\n\n<!-- doctreeai:manual -->\nHand-written notes. Keep these.\n\nSomething else entirely.\n";
+        let report = ProvenanceTracker::analyze(readme, &generated);
+
+        assert_eq!(report.generated_sentences, 1);
+        assert_eq!(report.manual_sentences, 3);
+        assert_eq!(report.lines[0].provenance, Provenance::Generated);
+        assert!(report.is_locked_manual(6));
+        assert!(!report.is_locked_manual(8));
+    }
+
+    #[test]
+    fn test_generated_marker_applies_to_paragraph() {
+        let readme = "<!-- doctreeai:generated -->\nFirst line.\nSecond line.\n\nManual line.";
+        let report = ProvenanceTracker::analyze(readme, &BTreeSet::new());
+
+        assert_eq!(report.generated_sentences, 2);
+        assert_eq!(report.manual_sentences, 1);
+        assert!((report.generated_ratio() - 2.0 / 3.0).abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,217 @@
+use crate::config::Config;
+use crate::error::DocTreeError;
+use async_openai::error::OpenAIError;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use tokio::time::{sleep, Duration, Instant};
+
+/// Requests and tokens are counted over a sliding window of this length.
+const WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Default)]
+struct LimiterState {
+    /// Start of each request in the window.
+    requests: VecDeque<Instant>,
+    /// Tokens spent in the window, by when they were counted.
+    tokens: VecDeque<(Instant, u64)>,
+    /// Minimum gap between requests, grown while the server throttles and
+    /// shrunk again as requests succeed.
+    spacing: Duration,
+    last_request: Option<Instant>,
+    /// No request starts before this, e.g. the server's Retry-After.
+    paused_until: Option<Instant>,
+}
+
+impl LimiterState {
+    fn expire(&mut self, now: Instant) {
+        while self.requests.front().is_some_and(|start| now.duration_since(*start) >= WINDOW) {
+            self.requests.pop_front();
+        }
+        while self.tokens.front().is_some_and(|(counted, _)| now.duration_since(*counted) >= WINDOW) {
+            self.tokens.pop_front();
+        }
+    }
+}
+
+/// Client-side limit on requests and tokens per minute, shared by every
+/// clone of an LLM client, that also slows down on its own while the
+/// server answers with rate limit (429) or server (5xx) errors. The default
+/// never waits.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    requests_per_minute: Option<u32>,
+    tokens_per_minute: Option<u64>,
+    /// Spacing after the first throttled response, doubled on each one after.
+    min_slowdown: Duration,
+    max_slowdown: Duration,
+    state: Arc<Mutex<LimiterState>>,
+}
+
+impl RateLimiter {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            requests_per_minute: config.requests_per_minute,
+            tokens_per_minute: config.tokens_per_minute,
+            min_slowdown: Duration::from_millis(config.retry_base_delay_ms),
+            max_slowdown: Duration::from_millis(config.retry_max_delay_ms),
+            state: Arc::default(),
+        }
+    }
+
+    /// Waits until a request estimated at `tokens` fits within the limits,
+    /// then counts it.
+    pub async fn acquire(&self, tokens: u64) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().expect("rate limiter lock");
+                let now = Instant::now();
+                let wait = self.delay(&mut state, now, tokens);
+                if wait.is_zero() {
+                    state.requests.push_back(now);
+                    state.tokens.push_back((now, tokens));
+                    state.last_request = Some(now);
+                    return;
+                }
+                wait
+            };
+            tracing::debug!("Rate limit: waiting {:.1}s before the next LLM request", wait.as_secs_f64());
+            sleep(wait).await;
+        }
+    }
+
+    /// Counts tokens only known once a request finished, e.g. the completion.
+    pub fn record_tokens(&self, tokens: u64) {
+        let mut state = self.state.lock().expect("rate limiter lock");
+        state.tokens.push_back((Instant::now(), tokens));
+    }
+
+    /// Slows down after the server throttled a request: requests are spaced
+    /// further apart and none starts until `retry_after` (or the new spacing)
+    /// has passed. Returns that pause.
+    pub fn on_throttled(&self, retry_after: Option<Duration>) -> Duration {
+        let mut state = self.state.lock().expect("rate limiter lock");
+        state.spacing = state.spacing.saturating_mul(2).clamp(self.min_slowdown, self.max_slowdown);
+        let pause = retry_after.unwrap_or(state.spacing);
+        state.paused_until = Some(Instant::now() + pause);
+        pause
+    }
+
+    /// Speeds back up after a successful request.
+    pub fn on_success(&self) {
+        let mut state = self.state.lock().expect("rate limiter lock");
+        state.spacing /= 2;
+        if state.spacing < self.min_slowdown {
+            state.spacing = Duration::ZERO;
+        }
+    }
+
+    /// How long a request of `tokens` has to wait at `now`.
+    fn delay(&self, state: &mut LimiterState, now: Instant, tokens: u64) -> Duration {
+        state.expire(now);
+        let until = |instant: Instant| instant.saturating_duration_since(now);
+
+        let mut wait = state.paused_until.map(until).unwrap_or_default();
+        if let Some(last_request) = state.last_request {
+            wait = wait.max(until(last_request + state.spacing));
+        }
+        if let Some(limit) = self.requests_per_minute {
+            if state.requests.len() >= limit as usize {
+                let oldest = state.requests[state.requests.len() - limit as usize];
+                wait = wait.max(until(oldest + WINDOW));
+            }
+        }
+        if let Some(limit) = self.tokens_per_minute {
+            // Wait for enough of the oldest tokens to leave the window; a
+            // request larger than the whole limit only waits for it to empty
+            let mut used: u64 = state.tokens.iter().map(|(_, tokens)| tokens).sum();
+            for (counted, counted_tokens) in &state.tokens {
+                if used + tokens <= limit {
+                    break;
+                }
+                used -= counted_tokens;
+                wait = wait.max(until(*counted + WINDOW));
+            }
+        }
+        wait
+    }
+}
+
+/// Whether an error means the server is overloaded or rate limiting us
+/// rather than rejecting the request: a 429 (other than an exhausted quota)
+/// or a 5xx. For requests sent through the SDK, which does not expose the
+/// status code, a 5xx arrives as an error with only a message and a 429
+/// mentions the rate limit in its type, code, or message.
+pub fn is_throttling(error: &DocTreeError) -> bool {
+    let api_error = match error {
+        DocTreeError::Api { status, error, .. } => {
+            return *status >= 500 || (*status == 429 && error.r#type.as_deref() != Some("insufficient_quota"))
+        }
+        DocTreeError::OpenAi(OpenAIError::ApiError(api_error)) => api_error,
+        _ => return false,
+    };
+    let is_server_error = api_error.r#type.is_none() && api_error.code.is_none() && api_error.param.is_none();
+    let mentions_rate_limit = [api_error.r#type.as_deref(), api_error.code.as_deref(), Some(api_error.message.as_str())]
+        .into_iter()
+        .flatten()
+        .map(str::to_lowercase)
+        .any(|text| text.contains("rate_limit") || text.contains("rate limit") || text.contains("too many requests"));
+    is_server_error || mentions_rate_limit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_openai::error::ApiError;
+
+    #[test]
+    fn test_requests_wait_for_the_limits_and_throttling() {
+        let config = Config { requests_per_minute: Some(2), tokens_per_minute: Some(1000), ..Default::default() };
+        let limiter = RateLimiter::from_config(&config);
+        let mut state = LimiterState::default();
+        let start = Instant::now();
+
+        assert_eq!(limiter.delay(&mut state, start, 400), Duration::ZERO);
+        state.requests.push_back(start);
+        state.tokens.push_back((start, 400));
+        let later = start + Duration::from_secs(10);
+        state.requests.push_back(later);
+        state.tokens.push_back((later, 400));
+
+        // Third request in the minute, and 1200 tokens: both wait for the first to expire
+        assert_eq!(limiter.delay(&mut state, later, 400), Duration::from_secs(50));
+        assert_eq!(limiter.delay(&mut state, start + WINDOW, 100), Duration::ZERO);
+
+        let pause = limiter.on_throttled(None);
+        assert_eq!(pause, Duration::from_millis(config.retry_base_delay_ms));
+        assert_eq!(limiter.on_throttled(Some(Duration::from_secs(20))), Duration::from_secs(20));
+        limiter.on_success();
+        limiter.on_success();
+        assert_eq!(limiter.state.lock().unwrap().spacing, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_throttling_errors_are_recognized() {
+        let api_error = |r#type: Option<&str>, message: &str| {
+            DocTreeError::OpenAi(OpenAIError::ApiError(ApiError {
+                message: message.to_string(),
+                r#type: r#type.map(str::to_string),
+                param: None,
+                code: None,
+            }))
+        };
+        assert!(is_throttling(&api_error(Some("requests"), "Rate limit reached for gpt-4o")));
+        assert!(is_throttling(&api_error(None, "502 Bad Gateway")));
+        assert!(!is_throttling(&api_error(Some("invalid_request_error"), "maximum context length exceeded")));
+        assert!(!is_throttling(&DocTreeError::timeout("LLM request exceeded 300s")));
+
+        let status_error = |status, r#type: Option<&str>| DocTreeError::Api {
+            status,
+            retry_after: None,
+            error: ApiError { message: String::new(), r#type: r#type.map(str::to_string), param: None, code: None },
+        };
+        assert!(is_throttling(&status_error(429, Some("requests"))));
+        assert!(is_throttling(&status_error(503, None)));
+        assert!(!is_throttling(&status_error(429, Some("insufficient_quota"))));
+        assert!(!is_throttling(&status_error(400, None)));
+    }
+}
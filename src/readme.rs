@@ -1,6 +1,76 @@
+use crate::cache::CacheSummary;
+use crate::classifier::{DirectoryClassifier, DirectoryRole};
+use crate::cli_usage::{CliArgument, CliDefinition};
 use crate::error::{DocTreeError, Result};
+use crate::examples::UsageExample;
+use crate::format::ReportFormat;
+use crate::hasher::FileHasher;
+use crate::install::InstallMethod;
+use crate::license::{license_badges, License};
+use crate::reporter::Reporter;
+use crate::rust_api::{ApiItem, RustApi};
+use crate::workspace::Workspace;
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// First line of a localized README, recording the README.md hash it was
+/// translated from.
+const TRANSLATED_FROM_PREFIX: &str = "<!-- doctreeai:translated-from ";
+
+/// Heading of the generated monorepo package table.
+pub const SERVICE_CATALOG_HEADING: &str = "Services / Packages";
+
+/// Opens a managed block, followed by the section name and ` -->`.
+pub const MANAGED_BEGIN_PREFIX: &str = "<!-- doctreeai:begin ";
+
+/// Closes the innermost open managed block.
+pub const MANAGED_END: &str = "<!-- doctreeai:end -->";
+
+/// Managed sections DocTreeAI knows how to regenerate.
+pub const MANAGED_SECTION_NAMES: &[&str] =
+    &["badges", "overview", "installation", "usage", "examples", "architecture", "modules", "api", "license"];
+
+/// A block of README.md between `<!-- doctreeai:begin name -->` and
+/// `<!-- doctreeai:end -->`; everything outside such blocks is manual.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManagedSection {
+    pub name: String,
+    /// 1-based line number of the begin marker.
+    pub line_number: usize,
+    /// Content between the markers, without them.
+    pub body: String,
+}
+
+/// The lines under one markdown heading, up to the next heading of any
+/// level. Text before the first heading is a section with an empty anchor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadmeSection {
+    /// GitHub-style heading anchor, e.g. `getting-started`; repeated
+    /// headings get `-1`, `-2`, ... like on GitHub.
+    pub anchor: String,
+    pub heading: String,
+    /// (1-based line number, line) for every line after the heading.
+    pub lines: Vec<(usize, String)>,
+    /// Hash of the section's lines, so unchanged sections are recognized
+    /// wherever they moved.
+    pub content_hash: String,
+}
+
+/// The anchor GitHub generates for a heading: lowercase, spaces as dashes,
+/// punctuation dropped.
+pub fn heading_anchor(heading: &str) -> String {
+    heading
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect()
+}
 
 pub struct ReadmeManager;
 
@@ -77,6 +147,470 @@ impl ReadmeManager {
         sections
     }
 
+    /// Renders an Architecture section from cached directory summaries,
+    /// grouped by directory role instead of raw folder order.
+    pub fn generate_architecture_section(&self, summaries: &[CacheSummary], base_path: &Path) -> String {
+        let mut by_role: BTreeMap<DirectoryRole, Vec<(String, String)>> = BTreeMap::new();
+
+        for summary in summaries.iter().filter(|s| s.is_directory) {
+            let relative_path = summary
+                .source_path
+                .strip_prefix(base_path)
+                .unwrap_or(&summary.source_path);
+
+            if relative_path.as_os_str().is_empty() {
+                continue;
+            }
+
+            let role = summary.role.unwrap_or_else(|| {
+                let name = relative_path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("");
+                DirectoryClassifier::classify(name, &summary.summary)
+            });
+
+            by_role.entry(role).or_default().push((
+                relative_path.to_string_lossy().to_string(),
+                Self::first_sentence(&summary.summary),
+            ));
+        }
+
+        let mut section = String::from("## Architecture\n");
+
+        for role in DirectoryRole::ALL {
+            if let Some(entries) = by_role.get_mut(&role) {
+                entries.sort();
+                section.push_str(&format!("\n### {}\n\n", role.title()));
+                for (path, description) in entries.iter() {
+                    section.push_str(&format!("- **`{path}/`**: {description}\n"));
+                }
+            }
+        }
+
+        section
+    }
+
+    /// Renders a table of workspace packages with the first sentence of each
+    /// package's cached directory summary.
+    pub fn generate_service_catalog(&self, workspace: &Workspace, summaries: &[CacheSummary], base_path: &Path) -> String {
+        let mut section = format!(
+            "## {SERVICE_CATALOG_HEADING}\n\n| Name | Path | Summary | Owner |\n| --- | --- | --- | --- |\n"
+        );
+
+        for package in &workspace.packages {
+            let package_path = base_path.join(&package.path);
+            let summary = summaries
+                .iter()
+                .find(|summary| summary.is_directory && summary.source_path == package_path)
+                .map(|summary| Self::first_sentence(&summary.summary))
+                .filter(|summary| !summary.is_empty())
+                .unwrap_or_else(|| "_Not yet summarized_".to_string());
+
+            section.push_str(&format!(
+                "| {} | `{}/` | {} | {} |\n",
+                Self::escape_cell(&package.name),
+                package.path.display(),
+                Self::escape_cell(&summary),
+                package.owner.as_deref().map(Self::escape_cell).unwrap_or_else(|| "-".to_string())
+            ));
+        }
+
+        section
+    }
+
+    fn escape_cell(text: &str) -> String {
+        text.replace('|', "\\|")
+    }
+
+    /// Finds a `##`-level section by heading and returns its 1-based heading
+    /// line number with the section text up to the next heading of the same
+    /// or higher level.
+    pub fn extract_section(&self, content: &str, heading: &str) -> Option<(usize, String)> {
+        let lines: Vec<&str> = content.lines().collect();
+        let start = lines.iter().position(|line| {
+            let trimmed = line.trim();
+            trimmed.starts_with("## ") && trimmed.trim_start_matches('#').trim() == heading
+        })?;
+
+        let end = lines[start + 1..]
+            .iter()
+            .position(|line| {
+                let trimmed = line.trim();
+                trimmed.starts_with("# ") || trimmed.starts_with("## ")
+            })
+            .map(|offset| start + 1 + offset)
+            .unwrap_or(lines.len());
+
+        Some((start + 1, lines[start..end].join("\n").trim_end().to_string()))
+    }
+
+    /// Like `extract_section`, for a heading of any level: the section runs
+    /// to the next heading of the same or a higher level, and headings
+    /// inside code blocks are ignored.
+    pub fn extract_subsection(&self, content: &str, heading: &str) -> Option<(usize, String)> {
+        let lines: Vec<&str> = content.lines().collect();
+        let mut in_code_block = false;
+        let mut start: Option<(usize, usize)> = None;
+
+        for (index, line) in lines.iter().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.starts_with("```") {
+                in_code_block = !in_code_block;
+                continue;
+            }
+            let level = trimmed.chars().take_while(|c| *c == '#').count();
+            if in_code_block || level == 0 || !trimmed[level..].starts_with(' ') {
+                continue;
+            }
+            match start {
+                Some((begin, start_level)) if level <= start_level => {
+                    return Some((begin + 1, lines[begin..index].join("\n").trim_end().to_string()));
+                }
+                None if trimmed[level..].trim().eq_ignore_ascii_case(heading) => start = Some((index, level)),
+                _ => {}
+            }
+        }
+
+        start.map(|(begin, _)| (begin + 1, lines[begin..].join("\n").trim_end().to_string()))
+    }
+
+    /// Splits a README into its sections in document order. Headings inside
+    /// code blocks are ignored.
+    pub fn sections(&self, content: &str) -> Vec<ReadmeSection> {
+        let mut sections: Vec<ReadmeSection> = Vec::new();
+        let mut current = (String::new(), Vec::new());
+        let mut seen: BTreeMap<String, usize> = BTreeMap::new();
+        let mut in_code_block = false;
+
+        let mut finish = |(heading, lines): (String, Vec<(usize, String)>), sections: &mut Vec<ReadmeSection>| {
+            let mut anchor = heading_anchor(&heading);
+            let count = seen.entry(anchor.clone()).or_insert(0);
+            if *count > 0 {
+                anchor = format!("{anchor}-{count}");
+            }
+            *count += 1;
+            let body: Vec<&str> = lines.iter().map(|(_, line): &(usize, String)| line.as_str()).collect();
+            sections.push(ReadmeSection {
+                anchor,
+                heading,
+                content_hash: FileHasher::compute_content_hash(&body.join("\n")),
+                lines,
+            });
+        };
+
+        for (index, line) in content.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.starts_with("```") {
+                in_code_block = !in_code_block;
+            } else if !in_code_block && trimmed.starts_with('#') {
+                let heading = trimmed.trim_start_matches('#').trim().to_string();
+                finish(std::mem::replace(&mut current, (heading, Vec::new())), &mut sections);
+                continue;
+            }
+            current.1.push((index + 1, line.to_string()));
+        }
+        finish(current, &mut sections);
+
+        sections
+    }
+
+    /// The required sections a README has no heading for. A heading counts
+    /// when it starts with the required name, ignoring case and leading
+    /// emoji, so "Installation" satisfies "Install".
+    pub fn missing_sections(&self, content: &str, required: &[String]) -> Vec<String> {
+        let normalize = |heading: &str| {
+            heading
+                .trim_start_matches(|c: char| !c.is_alphanumeric())
+                .trim()
+                .to_lowercase()
+        };
+        let headings: Vec<String> = self
+            .sections(content)
+            .iter()
+            .filter(|section| !section.heading.is_empty())
+            .map(|section| normalize(&section.heading))
+            .collect();
+
+        required
+            .iter()
+            .filter(|name| {
+                let name = normalize(name);
+                !name.is_empty() && !headings.iter().any(|heading| heading.starts_with(&name))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Renders an Installation section with the commands for each detected
+    /// manifest, one code block per tool.
+    pub fn generate_installation_section(&self, methods: &[InstallMethod]) -> String {
+        let mut section = String::from("## Installation\n");
+        for method in methods {
+            if methods.len() > 1 {
+                section.push_str(&format!("\n### {}\n", method.tool));
+            }
+            section.push_str(&format!("\n```bash\n{}\n```\n", method.commands.join("\n")));
+        }
+        section
+    }
+
+    /// Renders the badges on one line, as they are usually shown under the
+    /// title.
+    pub fn generate_badges_section(&self, badges: &[String]) -> String {
+        format!("{}\n", badges.join(" "))
+    }
+
+    /// Renders a License section naming the detected license and linking
+    /// its text.
+    pub fn generate_license_section(&self, license: &License) -> String {
+        let body = match (license.name(), license.files.as_slice()) {
+            (Some(name), []) => format!("This project is licensed under the {name}."),
+            (Some(name), files) => format!(
+                "This project is licensed under the {name}. See {} for details.",
+                Self::file_links(files)
+            ),
+            (None, files) => format!("See {} for the license terms.", Self::file_links(files)),
+        };
+        format!("## License\n\n{body}\n")
+    }
+
+    fn file_links(files: &[String]) -> String {
+        files
+            .iter()
+            .map(|file| format!("[{file}]({file})"))
+            .collect::<Vec<_>>()
+            .join(" and ")
+    }
+
+    /// README.md with every static shields.io license badge that names a
+    /// different license replaced by the current one, or None when they are
+    /// all up to date.
+    pub fn update_license_badges(&self, content: &str, license: &License) -> Option<String> {
+        let (Some(spdx_id), Some(badge)) = (license.spdx_id.as_deref(), license.badge()) else {
+            return None;
+        };
+        let mut updated = content.to_string();
+        for (stale, _) in license_badges(content).iter().filter(|(_, named)| named != spdx_id) {
+            updated = updated.replace(stale, &badge);
+        }
+        (updated != content).then_some(updated)
+    }
+
+    /// Renders a Usage section from the CLI declared in code: a synopsis,
+    /// the global options, then each command with its arguments.
+    pub fn generate_usage_section(&self, cli: &CliDefinition) -> String {
+        let push_arguments = |section: &mut String, arguments: &[CliArgument]| {
+            section.push('\n');
+            for argument in arguments {
+                match argument.help.as_str() {
+                    "" => section.push_str(&format!("- `{}`\n", argument.usage)),
+                    help => section.push_str(&format!("- `{}`: {help}\n", argument.usage)),
+                }
+            }
+        };
+
+        let options = if cli.global_arguments.is_empty() { "" } else { " [OPTIONS]" };
+        let command = if cli.commands.is_empty() { "" } else { " <COMMAND>" };
+        let mut section = format!("## Usage\n\n```text\n{}{options}{command}\n```\n", cli.binary);
+
+        if !cli.global_arguments.is_empty() {
+            section.push_str("\nOptions:\n");
+            push_arguments(&mut section, &cli.global_arguments);
+        }
+        for command in &cli.commands {
+            section.push_str(&format!("\n### `{} {}`\n", cli.binary, command.name));
+            if !command.about.is_empty() {
+                section.push_str(&format!("\n{}\n", command.about));
+            }
+            if !command.arguments.is_empty() {
+                push_arguments(&mut section, &command.arguments);
+            }
+        }
+
+        section
+    }
+
+    /// Renders the distilled usage examples as an Examples subsection, each
+    /// linking the file it came from.
+    pub fn generate_examples_section(&self, examples: &[UsageExample]) -> String {
+        let mut section = String::from("### Examples\n");
+        for example in examples {
+            section.push_str(&format!(
+                "\n**{}** ([`{}`]({}))\n\n```{}\n{}\n```\n",
+                example.title.trim(),
+                example.source,
+                example.source,
+                example.language.trim(),
+                example.code.trim_end()
+            ));
+        }
+        section
+    }
+
+    /// Renders an API Reference section: each public module with its items,
+    /// and their fields and methods nested below them.
+    pub fn generate_api_reference_section(&self, api: &RustApi) -> String {
+        let entry = |item: &ApiItem| match &item.docs {
+            Some(docs) => format!("`{}`: {docs}", item.signature),
+            None => format!("`{}`", item.signature),
+        };
+
+        let mut section = String::from("## API Reference\n");
+        for module in &api.modules {
+            section.push_str(&format!("\n### `{}`\n\n", api.module_name(module)));
+            if let Some(docs) = &module.docs {
+                section.push_str(&format!("{docs}\n\n"));
+            }
+            for item in &module.items {
+                section.push_str(&format!("- {}\n", entry(item)));
+                for member in &item.members {
+                    section.push_str(&format!("  - {}\n", entry(member)));
+                }
+            }
+        }
+        section
+    }
+
+    /// Renders a list of the top-level modules with the first sentence of
+    /// each cached summary.
+    pub fn generate_module_list(&self, summaries: &[CacheSummary], base_path: &Path) -> String {
+        let mut entries: Vec<(String, String)> = summaries
+            .iter()
+            .filter_map(|summary| {
+                let relative_path = summary.source_path.strip_prefix(base_path).ok()?;
+                if relative_path.components().count() != 1 {
+                    return None;
+                }
+                let suffix = if summary.is_directory { "/" } else { "" };
+                Some((
+                    format!("{}{suffix}", relative_path.display()),
+                    Self::first_sentence(&summary.summary),
+                ))
+            })
+            .collect();
+        entries.sort();
+
+        let mut section = String::from("## Modules\n\n");
+        for (path, description) in &entries {
+            section.push_str(&format!("- **`{path}`**: {description}\n"));
+        }
+        section
+    }
+
+    /// Finds every managed block in a README. Unterminated blocks are ignored
+    /// so a half-edited README is never rewritten past the end marker.
+    pub fn managed_sections(&self, content: &str) -> Vec<ManagedSection> {
+        let mut sections = Vec::new();
+        let mut open: Option<(String, usize, Vec<&str>)> = None;
+
+        for (index, line) in content.lines().enumerate() {
+            let trimmed = line.trim();
+            if let Some(name) = trimmed
+                .strip_prefix(MANAGED_BEGIN_PREFIX)
+                .and_then(|rest| rest.strip_suffix("-->"))
+            {
+                open = Some((name.trim().to_string(), index + 1, Vec::new()));
+            } else if trimmed == MANAGED_END {
+                if let Some((name, line_number, body)) = open.take() {
+                    sections.push(ManagedSection {
+                        name,
+                        line_number,
+                        body: body.join("\n"),
+                    });
+                }
+            } else if let Some((_, _, body)) = open.as_mut() {
+                body.push(line);
+            }
+        }
+
+        sections
+    }
+
+    /// Replaces the body of the named managed block, leaving every byte
+    /// outside the markers untouched. Returns None when the block is missing.
+    pub fn replace_managed_section(&self, content: &str, name: &str, body: &str) -> Option<String> {
+        let begin = format!("{MANAGED_BEGIN_PREFIX}{name} -->");
+        let mut offset = 0;
+        let mut body_start = None;
+
+        for line in content.split_inclusive('\n') {
+            let trimmed = line.trim();
+            match body_start {
+                None if trimmed == begin => body_start = Some(offset + line.len()),
+                Some(start) if trimmed == MANAGED_END => {
+                    let mut updated = String::with_capacity(content.len());
+                    updated.push_str(&content[..start]);
+                    updated.push_str(body.trim_end());
+                    updated.push('\n');
+                    updated.push_str(&content[offset..]);
+                    return Some(updated);
+                }
+                _ => {}
+            }
+            offset += line.len();
+        }
+
+        None
+    }
+
+    /// Wraps content in managed markers for a newly created README.
+    pub fn wrap_managed(name: &str, body: &str) -> String {
+        format!("{MANAGED_BEGIN_PREFIX}{name} -->\n{}\n{MANAGED_END}\n", body.trim_end())
+    }
+
+    pub fn localized_readme_path(&self, base_path: &Path, language_code: &str) -> PathBuf {
+        base_path.join(format!("README.{language_code}.md"))
+    }
+
+    /// Whether README.<code>.md was translated from the current README.md.
+    pub fn is_translation_current(&self, base_path: &Path, language_code: &str, readme_content: &str) -> bool {
+        let expected = format!(
+            "{TRANSLATED_FROM_PREFIX}{} -->",
+            FileHasher::compute_content_hash(readme_content)
+        );
+
+        fs::read_to_string(self.localized_readme_path(base_path, language_code))
+            .map(|content| content.lines().next() == Some(expected.as_str()))
+            .unwrap_or(false)
+    }
+
+    pub fn write_localized_readme(
+        &self,
+        base_path: &Path,
+        language_code: &str,
+        readme_content: &str,
+        translation: &str,
+    ) -> Result<PathBuf> {
+        let path = self.localized_readme_path(base_path, language_code);
+        let content = format!(
+            "{TRANSLATED_FROM_PREFIX}{} -->\n{}\n",
+            FileHasher::compute_content_hash(readme_content),
+            translation.trim_end()
+        );
+
+        fs::write(&path, content).map_err(|e| {
+            DocTreeError::readme(format!("Failed to write {}: {e}", path.display()))
+        })?;
+
+        Ok(path)
+    }
+
+    fn first_sentence(summary: &str) -> String {
+        let line = summary
+            .lines()
+            .map(|line| line.trim())
+            .find(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with("---"))
+            .unwrap_or("")
+            .trim_start_matches(['-', '*', ' '])
+            .replace("**", "");
+
+        match line.find(". ") {
+            Some(idx) => line[..=idx].to_string(),
+            None => line,
+        }
+    }
+
 }
 
 #[derive(Debug)]
@@ -88,28 +622,31 @@ pub struct ReadmeInfo {
 }
 
 impl ReadmeInfo {
-    pub fn print_summary(&self) {
+    pub fn report(&self, reporter: &dyn Reporter, format: ReportFormat) {
+        let mut lines = Vec::new();
         if self.exists {
-            println!("README.md exists ({} bytes)", self.size);
-            println!("Has project description: {}", self.has_project_description);
-            
+            lines.push(format!("README.md exists ({})", format.bytes(self.size as u64)));
+            lines.push(format!("Has project description: {}", self.has_project_description));
+
             if !self.sections.is_empty() {
-                println!("Sections found:");
+                lines.push("Sections found:".to_string());
                 for (i, section) in self.sections.iter().enumerate() {
-                    println!("  {}. {}", i + 1, section);
+                    lines.push(format!("  {}. {}", i + 1, section));
                 }
             } else {
-                println!("No sections detected");
+                lines.push("No sections detected".to_string());
             }
         } else {
-            println!("README.md does not exist");
+            lines.push("README.md does not exist".to_string());
         }
+        reporter.section("📄 README Information:", &lines);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cache::SummaryMethod;
     use tempfile::TempDir;
     use std::fs;
 
@@ -135,6 +672,86 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_service_catalog_and_section_extraction() {
+        let manager = create_test_manager();
+        let base_path = Path::new("/repo");
+        let workspace = Workspace {
+            packages: vec![crate::workspace::Package {
+                name: "api".to_string(),
+                path: "services/api".into(),
+                owner: Some("@team/api".to_string()),
+            }],
+        };
+        let summaries = vec![CacheSummary {
+            source_path: base_path.join("services/api"),
+            content_hash: String::new(),
+            summary: "Serves the public REST API. Uses axum.".to_string(),
+            timestamp: 0,
+            is_directory: true,
+            role: None,
+            pinned: false,
+            content_size: None,
+            modified: None,
+            normalized_hash: false,
+            language: None,
+            method: SummaryMethod::Llm,
+            structured: None,
+            sibling_context: None,
+        }];
+
+        let catalog = manager.generate_service_catalog(&workspace, &summaries, base_path);
+        assert!(catalog.contains("| api | `services/api/` | Serves the public REST API. | @team/api |"));
+
+        let readme = format!("# Repo\n\nIntro.\n\n{catalog}\n## License\n\nMIT\n");
+        let (line, section) = manager.extract_section(&readme, SERVICE_CATALOG_HEADING).unwrap();
+        assert_eq!(line, 5);
+        assert_eq!(section, catalog.trim_end());
+    }
+
+    #[test]
+    fn test_localized_readme_tracks_source() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let manager = create_test_manager();
+        let readme = "# Project\n\nDoes things.\n";
+
+        assert!(!manager.is_translation_current(temp_dir.path(), "ja", readme));
+
+        let path = manager.write_localized_readme(temp_dir.path(), "ja", readme, "# プロジェクト\n")?;
+        assert_eq!(path, temp_dir.path().join("README.ja.md"));
+        assert!(manager.is_translation_current(temp_dir.path(), "ja", readme));
+        assert!(!manager.is_translation_current(temp_dir.path(), "ja", "# Project\n\nChanged.\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_managed_sections_round_trip() {
+        let manager = create_test_manager();
+        let content = "# Project\n\nHand-written intro.\n\n<!-- doctreeai:begin overview -->\nOld overview.\n<!-- doctreeai:end -->\n\n## Notes\n  Keep   this spacing.\n<!-- doctreeai:begin modules -->\nunterminated\n";
+
+        let sections = manager.managed_sections(content);
+        assert_eq!(
+            sections,
+            vec![ManagedSection {
+                name: "overview".to_string(),
+                line_number: 5,
+                body: "Old overview.".to_string(),
+            }]
+        );
+
+        let updated = manager.replace_managed_section(content, "overview", "New overview.\n\nSecond paragraph.").unwrap();
+        assert_eq!(
+            updated,
+            content.replace("Old overview.\n", "New overview.\n\nSecond paragraph.\n")
+        );
+        assert!(manager.replace_managed_section(content, "modules", "x").is_none());
+        assert_eq!(
+            ReadmeManager::wrap_managed("overview", "Text\n"),
+            "<!-- doctreeai:begin overview -->\nText\n<!-- doctreeai:end -->\n"
+        );
+    }
+
     #[test]
     fn test_extract_sections() {
         let manager = create_test_manager();
@@ -145,6 +762,89 @@ mod tests {
         assert_eq!(sections, vec!["Main Title", "Installation", "Subsection", "Usage"]);
     }
 
+    #[test]
+    fn test_examples_subsection() {
+        let manager = create_test_manager();
+        let examples = vec![UsageExample {
+            title: "Run a scan".to_string(),
+            language: "rust".to_string(),
+            code: "app::run();\n".to_string(),
+            source: "examples/basic.rs".to_string(),
+        }];
+        let section = manager.generate_examples_section(&examples);
+        assert_eq!(
+            section,
+            "### Examples\n\n**Run a scan** ([`examples/basic.rs`](examples/basic.rs))\n\n```rust\napp::run();\n```\n"
+        );
+
+        let content = format!("# App\n\n## Usage\n\nRun it.\n\n{section}\n```bash\n# comment\n```\n\n## License\nMIT\n");
+        let (line_number, current) = manager.extract_subsection(&content, "Usage").unwrap();
+        assert_eq!(line_number, 3);
+        assert!(current.ends_with("# comment\n```"));
+        assert_eq!(manager.extract_subsection(&content, "examples").unwrap().0, 7);
+        assert!(manager.extract_subsection(&content, "Contributing").is_none());
+    }
+
+    #[test]
+    fn test_sections_with_anchors() {
+        let manager = create_test_manager();
+
+        let content = "Intro\n# Getting Started!\ntext\n```\n# not a heading\n```\n## Usage\nmore\n## Usage\nagain";
+        let sections = manager.sections(content);
+
+        let anchors: Vec<&str> = sections.iter().map(|s| s.anchor.as_str()).collect();
+        assert_eq!(anchors, vec!["", "getting-started", "usage", "usage-1"]);
+        assert_eq!(sections[1].lines[0], (3, "text".to_string()));
+        assert_eq!(sections[1].lines.len(), 4);
+        assert_ne!(sections[2].content_hash, sections[3].content_hash);
+    }
+
+    #[test]
+    fn test_license_section_and_badges() {
+        let manager = create_test_manager();
+        let license = License { spdx_id: Some("MIT".to_string()), files: vec!["LICENSE".to_string()] };
+
+        assert_eq!(
+            manager.generate_license_section(&license),
+            "## License\n\nThis project is licensed under the MIT License. See [LICENSE](LICENSE) for details.\n"
+        );
+        let unknown = License { spdx_id: None, files: vec!["COPYING".to_string()] };
+        assert_eq!(manager.generate_license_section(&unknown), "## License\n\nSee [COPYING](COPYING) for the license terms.\n");
+
+        let readme = "# App\n[![License: GPL-3.0](https://img.shields.io/badge/license-GPL--3.0-blue.svg)](LICENSE)\n";
+        let updated = manager.update_license_badges(readme, &license).unwrap();
+        assert!(updated.contains("badge/license-MIT-blue.svg"));
+        assert_eq!(manager.update_license_badges(&updated, &license), None);
+    }
+
+    #[test]
+    fn test_generate_usage_section() {
+        let manager = create_test_manager();
+        let cli = CliDefinition {
+            binary: "tool".to_string(),
+            global_arguments: vec![CliArgument { usage: "-v, --verbose".to_string(), help: "More output".to_string() }],
+            commands: vec![crate::cli_usage::CliCommand {
+                name: "run".to_string(),
+                about: "Run it".to_string(),
+                arguments: vec![CliArgument { usage: "--force".to_string(), help: String::new() }],
+            }],
+        };
+
+        assert_eq!(
+            manager.generate_usage_section(&cli),
+            "## Usage\n\n```text\ntool [OPTIONS] <COMMAND>\n```\n\nOptions:\n\n- `-v, --verbose`: More output\n\n### `tool run`\n\nRun it\n\n- `--force`\n"
+        );
+    }
+
+    #[test]
+    fn test_missing_sections() {
+        let manager = create_test_manager();
+
+        let content = "# App\n## 🚀 Installation\n```\n# License\n```\n## usage\n";
+        let required = ["Install", "Usage", "License"].map(String::from);
+        assert_eq!(manager.missing_sections(content, &required), vec!["License".to_string()]);
+    }
+
     #[test]
     fn test_detect_project_description() {
         let manager = create_test_manager();
@@ -156,6 +856,45 @@ mod tests {
         assert!(!manager.detect_project_description(content_without_desc));
     }
 
+    #[test]
+    fn test_generate_architecture_section() {
+        let manager = create_test_manager();
+        let base_path = Path::new("/project");
+        let summary = |path: &str, text: &str, role: Option<DirectoryRole>| CacheSummary {
+            source_path: base_path.join(path),
+            content_hash: "hash".to_string(),
+            summary: text.to_string(),
+            timestamp: 0,
+            is_directory: true,
+            role,
+            pinned: false,
+            content_size: None,
+            modified: None,
+            normalized_hash: false,
+            language: None,
+            method: SummaryMethod::Llm,
+            structured: None,
+            sibling_context: None,
+        };
+
+        let summaries = vec![
+            summary("", "Project root", Some(DirectoryRole::Domain)),
+            summary("tests", "Integration tests. More detail here.", Some(DirectoryRole::Tests)),
+            summary("src", "## Overview\n\nCore engine logic.", Some(DirectoryRole::Domain)),
+            summary("docs", "User guides", None),
+        ];
+
+        let section = manager.generate_architecture_section(&summaries, base_path);
+        let domain = section.find("### Core Domain").unwrap();
+        let tests = section.find("### Tests").unwrap();
+        let docs = section.find("### Documentation").unwrap();
+
+        assert!(domain < tests && tests < docs);
+        assert!(section.contains("- **`src/`**: Core engine logic."));
+        assert!(section.contains("- **`tests/`**: Integration tests."));
+        assert!(!section.contains("Project root"));
+    }
+
     #[test]
     fn test_get_readme_info() -> Result<()> {
         let temp_dir = TempDir::new()?;
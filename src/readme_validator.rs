@@ -1,22 +1,129 @@
-use crate::cache::{CacheManager, ReadmeLineMapping};
+use crate::backup::ReadmeBackups;
+use crate::badges::detect_badges;
+use crate::cache::{CacheManager, ReadmeLineMapping, ReadmeSectionMapping};
+use crate::cli_usage::CliDefinition;
+use crate::diff::{unified_diff, DEFAULT_CONTEXT_LINES};
 use crate::error::{DocTreeError, Result};
+use crate::examples::{
+    collect_example_sources, examples_prompt, parse_examples, sources_hash, ExampleSource, UsageExample, UsageExamples,
+};
 use crate::hasher::FileHasher;
+use crate::install::detect_install_methods;
+use crate::license::License;
 use crate::llm::LanguageModelClient;
+use crate::provenance::ProvenanceTracker;
+use crate::readme::{ManagedSection, ReadmeManager, ReadmeSection, MANAGED_SECTION_NAMES};
+use crate::references::{ReferenceCheck, ReferenceGuard, UNVERIFIED_CONFIDENCE};
+use crate::renderer::{RenderInput, Renderer, TextRenderer};
+use crate::reporter::Reporter;
+use crate::rust_api::RustApi;
+use crate::secrets::SecretScanner;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ValidationResult {
     pub line_number: usize,
     pub current_content: String,
     pub suggested_content: String,
     pub reason: String,
     pub affected_cache_entries: Vec<String>,
+    /// Heading of the README section containing the line (empty before the first heading).
+    pub section: String,
+    /// How sure the LLM is that the line needs this change, from 0.0 to 1.0.
+    /// Suggestions made without the LLM (managed and generated sections, a
+    /// missing README) are 1.0.
+    pub confidence: f32,
+    pub severity: Severity,
+}
+
+/// How much a suggested README change matters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The line is accurate; only its wording would improve.
+    Cosmetic,
+    /// The line describes something the code no longer does.
+    #[default]
+    Outdated,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Cosmetic => write!(f, "cosmetic"),
+            Severity::Outdated => write!(f, "outdated"),
+        }
+    }
+}
+
+impl Severity {
+    fn reason(self) -> &'static str {
+        match self {
+            Severity::Cosmetic => "Wording could better match the current code",
+            Severity::Outdated => "Content outdated based on current code",
+        }
+    }
+}
+
+/// A pending rewrite of README.md, shown as a diff before it is applied.
+#[derive(Debug, Clone)]
+pub struct ReadmeUpdate {
+    pub path: PathBuf,
+    pub current: String,
+    pub proposed: String,
+    /// Number of managed sections regenerated.
+    pub sections: usize,
+}
+
+impl ReadmeUpdate {
+    pub fn diff(&self) -> String {
+        unified_diff(&self.current, &self.proposed, "a/README.md", "b/README.md", DEFAULT_CONTEXT_LINES)
+    }
+
+    /// Backs up the current README.md, then writes the proposed content.
+    pub fn apply(&self, backups: &ReadmeBackups) -> Result<()> {
+        backups.save(&self.current)?;
+        fs::write(&self.path, &self.proposed).map_err(|e| {
+            DocTreeError::readme(format!("Failed to write {}: {e}", self.path.display()))
+        })
+    }
 }
 
+/// Parses a `--min-confidence` value between 0.0 and 1.0.
+pub fn parse_confidence(value: &str) -> Result<f32> {
+    match value.trim().parse::<f32>() {
+        Ok(confidence) if (0.0..=1.0).contains(&confidence) => Ok(confidence),
+        _ => Err(DocTreeError::config(format!("Confidence must be between 0 and 1, got '{value}'"))),
+    }
+}
+
+/// How the LLM is asked to judge a suggestion, shared by the single-line and
+/// batch prompts.
+const JUDGMENT_INSTRUCTIONS: &str = "Severity is 'outdated' if the line describes something the code no longer does, \
+    or 'cosmetic' if it is still accurate and only the wording improves. \
+    Confidence is a number from 0 to 1 saying how sure you are the change is needed.";
+
 pub struct ReadmeValidator {
     cache_manager: CacheManager,
     llm_client: LanguageModelClient,
+    batch_size: usize,
+    /// Sections rendered entirely from the codebase, as (heading, content).
+    generated_sections: Vec<(String, String)>,
+    scope: Option<PathBuf>,
+    offline: bool,
+    min_confidence: f32,
+    reference_check: ReferenceCheck,
+    /// Regenerated managed blocks that differ from README.md, as (name, body).
+    managed_updates: Vec<(String, String)>,
+    distill_examples: bool,
+    /// Replaces secrets in the sources usage examples are distilled from;
+    /// None when redaction is turned off.
+    secret_scanner: Option<SecretScanner>,
+    /// Usage examples for this run, distilled before the checks that use them.
+    usage_examples: Option<Vec<UsageExample>>,
 }
 
 impl ReadmeValidator {
@@ -24,50 +131,165 @@ impl ReadmeValidator {
         Self {
             cache_manager,
             llm_client,
+            batch_size: 10,
+            generated_sections: Vec::new(),
+            scope: None,
+            offline: false,
+            min_confidence: 0.0,
+            reference_check: ReferenceCheck::default(),
+            managed_updates: Vec::new(),
+            distill_examples: false,
+            secret_scanner: Some(SecretScanner::default()),
+            usage_examples: None,
         }
     }
 
+    /// Only validates README lines mapped to this subtree (relative to the
+    /// project root).
+    pub fn set_scope(&mut self, scope: Option<PathBuf>) {
+        self.scope = scope;
+    }
+
+    /// Skips the checks that need the LLM. Managed and generated sections,
+    /// and a skeleton for a missing README, are still produced.
+    pub fn set_offline(&mut self, offline: bool) {
+        self.offline = offline;
+    }
+
+    /// Drops LLM suggestions with a lower confidence than this (0.0 keeps
+    /// everything).
+    pub fn set_min_confidence(&mut self, min_confidence: f32) {
+        self.min_confidence = min_confidence;
+    }
+
+    /// What happens to LLM suggestions that mention files or flags the
+    /// project does not have.
+    pub fn set_reference_check(&mut self, reference_check: ReferenceCheck) {
+        self.reference_check = reference_check;
+    }
+
+    /// Distills usage snippets from `examples/`, doc tests, and integration
+    /// tests into an Examples subsection of the Usage section.
+    pub fn set_usage_examples(&mut self, enabled: bool) {
+        self.distill_examples = enabled;
+    }
+
+    /// Whether secrets are replaced in the sources sent to distill usage
+    /// examples.
+    pub fn set_secret_redaction(&mut self, enabled: bool) {
+        self.secret_scanner = enabled.then(SecretScanner::default);
+    }
+
+    /// Maximum number of lines from one README section validated in a single
+    /// LLM call. A batch size of 1 validates every line individually.
+    pub fn set_batch_size(&mut self, batch_size: usize) {
+        self.batch_size = batch_size.max(1);
+    }
+
+    /// Registers a section that is generated verbatim (e.g. the service
+    /// catalog). Validation suggests replacing the README's copy whenever it
+    /// differs, or adding it when missing.
+    pub fn add_generated_section(&mut self, heading: &str, content: String) {
+        self.generated_sections.push((heading.to_string(), content));
+    }
+
     pub async fn validate_readme(
         &mut self,
         base_path: &Path,
         project_summary: &str,
     ) -> Result<Vec<ValidationResult>> {
         let readme_path = base_path.join("README.md");
+        self.usage_examples = self.distill_usage_examples(base_path).await?;
 
         if !readme_path.exists() {
+            let mut suggested_content = format!(
+                "# {}\n",
+                base_path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("Project")
+            );
+            for name in MANAGED_SECTION_NAMES {
+                let Some(body) = self.managed_section_body(name, base_path, project_summary) else {
+                    continue;
+                };
+                suggested_content.push_str(&format!("\n{}", ReadmeManager::wrap_managed(name, &body)));
+            }
+            for (_, content) in &self.generated_sections {
+                suggested_content.push_str(&format!("\n{content}"));
+            }
+
             return Ok(vec![ValidationResult {
                 line_number: 0,
                 current_content: String::new(),
-                suggested_content: format!(
-                    "# {}\n\n{}",
-                    base_path
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("Project"),
-                    project_summary
-                ),
+                suggested_content,
                 reason: "README.md does not exist".to_string(),
                 affected_cache_entries: vec![],
+                section: String::new(),
+                confidence: 1.0,
+                severity: Severity::Outdated,
             }]);
         }
 
         let readme_content = fs::read_to_string(&readme_path)
             .map_err(|e| DocTreeError::readme(format!("Failed to read README.md: {e}")))?;
 
+        let managed_sections = ReadmeManager::new().managed_sections(&readme_content);
+        if !managed_sections.is_empty() {
+            tracing::info!("README.md has {} managed sections; manual content is left alone", managed_sections.len());
+            let mut validation_results = self.check_managed_sections(&managed_sections, base_path, project_summary);
+            validation_results.extend(self.check_generated_sections(&readme_content));
+            validation_results.extend(self.check_license_badges(&readme_content, base_path));
+            return Ok(validation_results);
+        }
+
+        if self.offline {
+            tracing::info!("Offline mode: README lines are not checked against the code");
+            let mut validation_results = self.check_generated_sections(&readme_content);
+            validation_results.extend(self.check_usage_examples(&readme_content));
+            validation_results.extend(self.check_license_badges(&readme_content, base_path));
+            return Ok(validation_results);
+        }
+
         let readme_hash = FileHasher::compute_content_hash(&readme_content);
 
         if !self.cache_manager.validate_readme_hash(&readme_hash) {
-            log::info!("README has changed, regenerating mappings");
-            let new_mappings = self.generate_mappings(&readme_content, base_path).await?;
+            tracing::info!("README has changed, remapping changed sections");
+            let (sections, new_mappings) = self.remap_readme(&readme_content, base_path)?;
             self.cache_manager
-                .update_readme_mapping(readme_hash.clone(), new_mappings)?;
+                .update_readme_mapping(readme_hash.clone(), sections, new_mappings)
+                .await?;
         }
 
         let mut validation_results = Vec::new();
 
+        let provenance = ProvenanceTracker::analyze(
+            &readme_content,
+            &self.cache_manager.get_readme_mapping().generated_sentences,
+        );
+
         let mappings = &self.cache_manager.get_readme_mapping().mappings;
+        let mut pending: Vec<&ReadmeLineMapping> = Vec::new();
 
         for mapping in mappings {
+            if provenance.is_locked_manual(mapping.line_number) {
+                tracing::debug!("Skipping manual line {}", mapping.line_number);
+                continue;
+            }
+
+            if let Some(scope) = &self.scope {
+                let in_scope = mapping.cache_keys.iter().any(|key| {
+                    let source_path = Path::new(key);
+                    source_path
+                        .strip_prefix(base_path)
+                        .unwrap_or(source_path)
+                        .starts_with(scope)
+                });
+                if !in_scope {
+                    continue;
+                }
+            }
+
             let validation_needed = mapping.cache_keys.iter().any(|key| {
                 // Parse key as path to get cache summary
                 let source_path = Path::new(key);
@@ -79,34 +301,490 @@ impl ReadmeValidator {
             });
 
             if validation_needed {
-                if let Some(suggestion) = self.suggest_update(mapping, project_summary).await? {
-                    validation_results.push(suggestion);
+                pending.push(mapping);
+            }
+        }
+
+        // Related lines in the same section share one LLM call
+        let sections = Self::section_headings(&readme_content);
+        let mut groups: Vec<(String, Vec<&ReadmeLineMapping>)> = Vec::new();
+        for mapping in pending {
+            let section = sections
+                .get(mapping.line_number.saturating_sub(1))
+                .cloned()
+                .unwrap_or_default();
+            // Generated sections are compared as a whole below
+            if self.generated_sections.iter().any(|(heading, _)| *heading == section) {
+                continue;
+            }
+            match groups.last_mut() {
+                Some((current, group)) if *current == section => group.push(mapping),
+                _ => groups.push((section, vec![mapping])),
+            }
+        }
+
+        for (section, group) in &groups {
+            for chunk in group.chunks(self.batch_size) {
+                if let [mapping] = chunk {
+                    if let Some(suggestion) = self.suggest_update(mapping, section, project_summary).await? {
+                        validation_results.push(suggestion);
+                    }
+                } else {
+                    tracing::debug!("Validating {} lines of section '{}' in one call", chunk.len(), section);
+                    validation_results.extend(
+                        self.suggest_batch_update(section, chunk, project_summary).await?,
+                    );
                 }
             }
         }
 
+        if self.reference_check != ReferenceCheck::Off && !validation_results.is_empty() {
+            let guard = ReferenceGuard::for_project(base_path, &self.cache_manager.get_all_summaries(), &readme_content);
+            validation_results = self.check_references(&guard, validation_results);
+        }
+
+        validation_results.extend(self.check_generated_sections(&readme_content));
+        validation_results.extend(self.check_usage_examples(&readme_content));
+        validation_results.extend(self.check_license_badges(&readme_content, base_path));
+
+        let found = validation_results.len();
+        validation_results.retain(|result| result.confidence >= self.min_confidence);
+        if validation_results.len() < found {
+            tracing::info!(
+                "Dropped {} suggestions below confidence {}",
+                found - validation_results.len(),
+                self.min_confidence
+            );
+        }
+
         Ok(validation_results)
     }
 
-    async fn generate_mappings(
+    /// Asks the LLM to draft each required section README.md is missing, as
+    /// suggestions to append. Nothing is drafted without a README, since the
+    /// skeleton suggested for it is reviewed as a whole.
+    pub async fn draft_missing_sections(
+        &mut self,
+        base_path: &Path,
+        required: &[String],
+        project_summary: &str,
+    ) -> Result<Vec<ValidationResult>> {
+        let readme_path = base_path.join("README.md");
+        if required.is_empty() || !readme_path.exists() {
+            return Ok(Vec::new());
+        }
+        let readme_content = fs::read_to_string(&readme_path)
+            .map_err(|e| DocTreeError::readme(format!("Failed to read README.md: {e}")))?;
+
+        let readme_manager = ReadmeManager::new();
+        let install_methods = detect_install_methods(base_path);
+        let mut drafts = Vec::new();
+        for name in readme_manager.missing_sections(&readme_content, required) {
+            // Installation steps come from the manifests rather than the LLM
+            let suggested_content = if name.to_lowercase().starts_with("install") && !install_methods.is_empty() {
+                let generated = readme_manager.generate_installation_section(&install_methods);
+                let body = generated.split_once('\n').map(|(_, body)| body).unwrap_or_default();
+                format!("## {name}\n{body}").trim_end().to_string()
+            } else {
+                tracing::info!("Drafting the missing '{}' section", name);
+                let prompt = format!(
+                    "README.md has no '{name}' section. Write one for this project, starting with the heading '## {name}'. \
+                    Use only facts from the project summary below; where details such as commands, versions, or license terms \
+                    are not in it, leave a short TODO note instead of guessing. Respond with the Markdown section only.\n\n\
+                    Project summary:\n{project_summary}"
+                );
+                let draft = self.llm_client.generate_readme_suggestion(&prompt).await?;
+                let draft = draft.trim();
+                if draft.starts_with('#') {
+                    draft.to_string()
+                } else {
+                    format!("## {name}\n\n{draft}")
+                }
+            };
+
+            drafts.push(ValidationResult {
+                line_number: 0,
+                current_content: String::new(),
+                suggested_content,
+                reason: format!("README.md has no required '{name}' section"),
+                affected_cache_entries: vec![],
+                section: name,
+                confidence: 1.0,
+                severity: Severity::Outdated,
+            });
+        }
+        Ok(drafts)
+    }
+
+    /// The regenerated body of a managed section, or None for names
+    /// DocTreeAI does not manage (and for `badges`, `installation`, `usage`,
+    /// `examples`, `api`, and `license` when there is nothing to generate
+    /// them from).
+    fn managed_section_body(&self, name: &str, base_path: &Path, project_summary: &str) -> Option<String> {
+        let readme_manager = ReadmeManager::new();
+        let summaries = self.cache_manager.get_all_summaries();
+        match name {
+            "badges" => {
+                let badges = detect_badges(base_path, &summaries);
+                (!badges.is_empty()).then(|| readme_manager.generate_badges_section(&badges))
+            }
+            "overview" => Some(project_summary.trim().to_string()),
+            "installation" => {
+                let methods = detect_install_methods(base_path);
+                (!methods.is_empty()).then(|| readme_manager.generate_installation_section(&methods))
+            }
+            "architecture" => Some(readme_manager.generate_architecture_section(&summaries, base_path)),
+            "modules" => Some(readme_manager.generate_module_list(&summaries, base_path)),
+            "api" => RustApi::detect(base_path).map(|api| readme_manager.generate_api_reference_section(&api)),
+            "license" => License::detect(base_path, &summaries).map(|license| readme_manager.generate_license_section(&license)),
+            "usage" => CliDefinition::detect(base_path, &summaries).map(|cli| readme_manager.generate_usage_section(&cli)),
+            "examples" => self
+                .usage_examples
+                .as_deref()
+                .filter(|examples| !examples.is_empty())
+                .map(|examples| readme_manager.generate_examples_section(examples)),
+            _ => None,
+        }
+    }
+
+    fn check_managed_sections(
+        &mut self,
+        sections: &[ManagedSection],
+        base_path: &Path,
+        project_summary: &str,
+    ) -> Vec<ValidationResult> {
+        let mut results = Vec::new();
+        self.managed_updates.clear();
+
+        for section in sections {
+            let Some(expected) = self.managed_section_body(&section.name, base_path, project_summary) else {
+                tracing::warn!("Managed README section '{}' has nothing to generate from; left unchanged", section.name);
+                continue;
+            };
+            if section.body.trim() == expected.trim() {
+                continue;
+            }
+
+            results.push(ValidationResult {
+                line_number: section.line_number,
+                current_content: section.body.clone(),
+                suggested_content: expected.trim_end().to_string(),
+                reason: format!("The managed '{}' section no longer matches the codebase", section.name),
+                affected_cache_entries: vec![],
+                section: section.name.clone(),
+                confidence: 1.0,
+                severity: Severity::Outdated,
+            });
+            self.managed_updates.push((section.name.clone(), expected));
+        }
+
+        results
+    }
+
+    /// README.md with the managed blocks found stale by the last
+    /// `validate_readme` call regenerated, or None when nothing changed.
+    /// Content outside the markers is carried over unchanged.
+    pub fn managed_readme_update(&self, base_path: &Path) -> Result<Option<ReadmeUpdate>> {
+        if self.managed_updates.is_empty() {
+            return Ok(None);
+        }
+
+        let path = base_path.join("README.md");
+        let current = fs::read_to_string(&path)
+            .map_err(|e| DocTreeError::readme(format!("Failed to read README.md: {e}")))?;
+        let readme_manager = ReadmeManager::new();
+
+        let mut proposed = current.clone();
+        let mut sections = 0;
+        for (name, body) in &self.managed_updates {
+            if let Some(replaced) = readme_manager.replace_managed_section(&proposed, name, body) {
+                proposed = replaced;
+                sections += 1;
+            }
+        }
+
+        Ok((proposed != current).then_some(ReadmeUpdate { path, current, proposed, sections }))
+    }
+
+    /// The README with every suggestion from `results` applied: managed
+    /// blocks regenerated, suggested lines and sections substituted, and
+    /// missing sections appended. Used to write the updated document
+    /// somewhere other than README.md.
+    pub fn render_document(&self, base_path: &Path, results: &[ValidationResult]) -> Result<String> {
+        let readme_path = base_path.join("README.md");
+        if !readme_path.exists() {
+            return Ok(results
+                .iter()
+                .find(|result| result.line_number == 0 && result.section.is_empty())
+                .map(|result| format!("{}\n", result.suggested_content.trim_end()))
+                .unwrap_or_default());
+        }
+
+        let current = fs::read_to_string(&readme_path)
+            .map_err(|e| DocTreeError::readme(format!("Failed to read README.md: {e}")))?;
+        let managed: Vec<String> = ReadmeManager::new()
+            .managed_sections(&current)
+            .into_iter()
+            .map(|section| section.name)
+            .collect();
+        let mut document = match self.managed_readme_update(base_path)? {
+            Some(update) => update.proposed,
+            None => current,
+        };
+
+        for result in results.iter().filter(|result| !managed.contains(&result.section)) {
+            if result.current_content.is_empty() {
+                document = format!("{}\n\n{}\n", document.trim_end(), result.suggested_content.trim_end());
+            } else {
+                document = document.replacen(&result.current_content, &result.suggested_content, 1);
+            }
+        }
+
+        Ok(document)
+    }
+
+    fn check_generated_sections(&self, readme_content: &str) -> Vec<ValidationResult> {
+        let readme_manager = ReadmeManager::new();
+
+        self.generated_sections
+            .iter()
+            .filter_map(|(heading, expected)| {
+                match readme_manager.extract_section(readme_content, heading) {
+                    Some((_, current)) if current.trim() == expected.trim() => None,
+                    Some((line_number, current)) => Some(ValidationResult {
+                        line_number,
+                        current_content: current,
+                        suggested_content: expected.trim_end().to_string(),
+                        reason: format!("The '{heading}' section no longer matches the codebase"),
+                        affected_cache_entries: vec![],
+                        section: heading.clone(),
+                        confidence: 1.0,
+                        severity: Severity::Outdated,
+                    }),
+                    None => Some(ValidationResult {
+                        line_number: 0,
+                        current_content: String::new(),
+                        suggested_content: expected.trim_end().to_string(),
+                        reason: format!("README.md has no '{heading}' section"),
+                        affected_cache_entries: vec![],
+                        section: heading.clone(),
+                        confidence: 1.0,
+                        severity: Severity::Outdated,
+                    }),
+                }
+            })
+            .collect()
+    }
+
+    /// Usage examples for the current sources: the cached ones while the
+    /// sources are unchanged, otherwise distilled by the LLM (not offline).
+    async fn distill_usage_examples(&self, base_path: &Path) -> Result<Option<Vec<UsageExample>>> {
+        if !self.distill_examples {
+            return Ok(None);
+        }
+        let sources = collect_example_sources(
+            base_path,
+            &self.cache_manager.get_all_summaries(),
+            self.secret_scanner.as_ref(),
+        );
+        if sources.is_empty() {
+            return Ok(None);
+        }
+
+        let sources_hash = sources_hash(&sources);
+        if let Some(cached) = self.cache_manager.load_usage_examples()? {
+            if cached.sources_hash == sources_hash {
+                return Ok(Some(cached.examples));
+            }
+        }
+        if self.offline {
+            return Ok(None);
+        }
+
+        self.record_redacted(&sources)?;
+        tracing::info!("Distilling usage examples from {} files", sources.len());
+        let response = self.llm_client.check_readme(&examples_prompt(&sources)).await?;
+        let examples = match parse_examples(&response, &sources) {
+            Ok(examples) => examples,
+            Err(e) => {
+                tracing::warn!("{e}");
+                return Ok(None);
+            }
+        };
+        self.cache_manager.save_usage_examples(&UsageExamples { sources_hash, examples: examples.clone() })?;
+        Ok(Some(examples))
+    }
+
+    /// Records the secrets replaced in the example sources in the run state,
+    /// alongside those replaced in summarized files.
+    fn record_redacted(&self, sources: &[ExampleSource]) -> Result<()> {
+        let redacted: Vec<&ExampleSource> = sources.iter().filter(|source| !source.redacted.is_empty()).collect();
+        if redacted.is_empty() {
+            return Ok(());
+        }
+        let Some(mut state) = self.cache_manager.load_run_state()? else {
+            return Ok(());
+        };
+        for source in redacted {
+            tracing::warn!("Redacted {} secret(s) in {} before distilling usage examples", source.redacted.len(), source.path);
+            state.record_redacted(Path::new(&source.path), source.redacted.clone());
+        }
+        self.cache_manager.save_run_state(&state)
+    }
+
+    /// The Examples subsection with the distilled usage examples: replaced
+    /// when it differs, otherwise added at the end of the Usage section (or
+    /// in a new one). README.md files with managed blocks use an `examples`
+    /// block instead.
+    fn check_usage_examples(&self, readme_content: &str) -> Option<ValidationResult> {
+        let examples = self.usage_examples.as_deref().filter(|examples| !examples.is_empty())?;
+        let readme_manager = ReadmeManager::new();
+        let expected = readme_manager.generate_examples_section(examples);
+        let mut sources: Vec<String> = examples.iter().map(|example| format!("`{}`", example.source)).collect();
+        sources.dedup();
+
+        let (line_number, current_content, suggested_content) =
+            match readme_manager.extract_subsection(readme_content, "Examples") {
+                Some((_, current)) if current.trim() == expected.trim() => return None,
+                Some((line_number, current)) => (line_number, current, expected.trim_end().to_string()),
+                None => match readme_manager.extract_subsection(readme_content, "Usage") {
+                    Some((line_number, current)) => {
+                        let suggested = format!("{current}\n\n{}", expected.trim_end());
+                        (line_number, current, suggested)
+                    }
+                    None => (0, String::new(), format!("## Usage\n\n{}", expected.trim_end())),
+                },
+            };
+
+        Some(ValidationResult {
+            line_number,
+            current_content,
+            suggested_content,
+            reason: format!("Usage examples distilled from {}", sources.join(", ")),
+            affected_cache_entries: vec![],
+            section: "Usage".to_string(),
+            confidence: 1.0,
+            severity: Severity::Outdated,
+        })
+    }
+
+    /// Lines with a shields.io license badge naming another license than the
+    /// one detected, with the badge replaced. Badges in managed blocks are
+    /// regenerated with the block instead.
+    fn check_license_badges(&self, readme_content: &str, base_path: &Path) -> Vec<ValidationResult> {
+        let Some(license) = License::detect(base_path, &self.cache_manager.get_all_summaries()) else {
+            return Vec::new();
+        };
+        let readme_manager = ReadmeManager::new();
+        let sections = Self::section_headings(readme_content);
+        let managed_lines: Vec<(usize, usize)> = readme_manager
+            .managed_sections(readme_content)
+            .iter()
+            .map(|section| (section.line_number, section.line_number + section.body.lines().count() + 1))
+            .collect();
+
+        readme_content
+            .lines()
+            .enumerate()
+            .filter(|(index, _)| !managed_lines.iter().any(|(begin, end)| (*begin..=*end).contains(&(index + 1))))
+            .filter_map(|(index, line)| {
+                let updated = readme_manager.update_license_badges(line, &license)?;
+                Some(ValidationResult {
+                    line_number: index + 1,
+                    current_content: line.to_string(),
+                    suggested_content: updated,
+                    reason: format!(
+                        "The license badge does not match the project license ({})",
+                        license.spdx_id.as_deref().unwrap_or_default()
+                    ),
+                    affected_cache_entries: vec![],
+                    section: sections.get(index).cloned().unwrap_or_default(),
+                    confidence: 1.0,
+                    severity: Severity::Outdated,
+                })
+            })
+            .collect()
+    }
+
+    /// The README line mappings for the current README.md: the cached ones
+    /// while README.md is unchanged, otherwise freshly matched (no LLM call).
+    pub fn current_mappings(&self, base_path: &Path) -> Result<Vec<ReadmeLineMapping>> {
+        let readme_path = base_path.join("README.md");
+        if !readme_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let readme_content = fs::read_to_string(&readme_path)
+            .map_err(|e| DocTreeError::readme(format!("Failed to read README.md: {e}")))?;
+        if self.cache_manager.validate_readme_hash(&FileHasher::compute_content_hash(&readme_content)) {
+            return Ok(self.cache_manager.get_readme_mapping().mappings.clone());
+        }
+        Ok(self.remap_readme(&readme_content, base_path)?.1)
+    }
+
+    /// Maps README lines to cache entries section by section. Sections whose
+    /// anchor and content hash are unchanged keep their previous mappings,
+    /// with line numbers recomputed from where the section now sits; only
+    /// new or edited sections are matched against the cache again.
+    fn remap_readme(
         &self,
         readme_content: &str,
         base_path: &Path,
-    ) -> Result<Vec<ReadmeLineMapping>> {
+    ) -> Result<(Vec<ReadmeSectionMapping>, Vec<ReadmeLineMapping>)> {
+        let previous = self.cache_manager.get_readme_mapping();
+        let mut sections = Vec::new();
         let mut mappings = Vec::new();
 
-        for (line_number, line) in readme_content.lines().enumerate() {
-            let line_number = line_number + 1;
+        for section in ReadmeManager::new().sections(readme_content) {
+            let unchanged = previous
+                .sections
+                .iter()
+                .any(|old| old.anchor == section.anchor && old.content_hash == section.content_hash);
 
+            if unchanged {
+                let mut used = Vec::new();
+                for old in previous.mappings.iter().filter(|old| old.section == section.anchor) {
+                    let line_number = section
+                        .lines
+                        .iter()
+                        .find(|(number, line)| *line == old.line_content && !used.contains(number))
+                        .map(|(number, _)| *number);
+                    if let Some(line_number) = line_number {
+                        used.push(line_number);
+                        mappings.push(ReadmeLineMapping {
+                            line_number,
+                            ..old.clone()
+                        });
+                    }
+                }
+            } else {
+                mappings.extend(self.section_mappings(&section, base_path)?);
+            }
+
+            sections.push(ReadmeSectionMapping {
+                anchor: section.anchor,
+                content_hash: section.content_hash,
+            });
+        }
+
+        mappings.sort_by_key(|mapping| mapping.line_number);
+        Ok((sections, mappings))
+    }
+
+    fn section_mappings(&self, section: &ReadmeSection, base_path: &Path) -> Result<Vec<ReadmeLineMapping>> {
+        let mut mappings = Vec::new();
+
+        for (line_number, line) in &section.lines {
             if self.is_content_line(line) {
                 let cache_keys = self.find_relevant_cache_keys(line, base_path)?;
 
                 if !cache_keys.is_empty() {
                     mappings.push(ReadmeLineMapping {
-                        line_number,
+                        line_number: *line_number,
                         line_content: line.to_string(),
                         cache_keys,
                         last_validated_hash: None,
+                        section: section.anchor.clone(),
                     });
                 }
             }
@@ -209,14 +887,30 @@ impl ReadmeValidator {
         Ok(cache_keys)
     }
 
-    async fn suggest_update(
-        &self,
-        mapping: &ReadmeLineMapping,
-        project_summary: &str,
-    ) -> Result<Option<ValidationResult>> {
+    /// Returns the heading each README line falls under (empty before the
+    /// first heading), indexed by zero-based line number.
+    fn section_headings(readme_content: &str) -> Vec<String> {
+        let mut current = String::new();
+        let mut in_code_block = false;
+
+        readme_content
+            .lines()
+            .map(|line| {
+                let trimmed = line.trim();
+                if trimmed.starts_with("```") {
+                    in_code_block = !in_code_block;
+                } else if !in_code_block && trimmed.starts_with('#') {
+                    current = trimmed.trim_start_matches('#').trim().to_string();
+                }
+                current.clone()
+            })
+            .collect()
+    }
+
+    fn collect_relevant_summaries(&self, cache_keys: &[String]) -> Vec<String> {
         let mut relevant_summaries = Vec::new();
 
-        for key in &mapping.cache_keys {
+        for key in cache_keys {
             let source_path = Path::new(key);
             if let Some(summary) = self.cache_manager.get_cache_summary(source_path) {
                 let relative_path = summary
@@ -228,6 +922,106 @@ impl ReadmeValidator {
             }
         }
 
+        relevant_summaries
+    }
+
+    async fn suggest_batch_update(
+        &self,
+        section: &str,
+        mappings: &[&ReadmeLineMapping],
+        project_summary: &str,
+    ) -> Result<Vec<ValidationResult>> {
+        let mut cache_keys: Vec<String> = Vec::new();
+        for mapping in mappings {
+            for key in &mapping.cache_keys {
+                if !cache_keys.contains(key) {
+                    cache_keys.push(key.clone());
+                }
+            }
+        }
+
+        let relevant_summaries = self.collect_relevant_summaries(&cache_keys);
+        if relevant_summaries.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let lines = mappings
+            .iter()
+            .map(|mapping| format!("Line {}: \"{}\"", mapping.line_number, mapping.line_content))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let section_name = if section.is_empty() { "introduction" } else { section };
+
+        let prompt = format!(
+            "The following lines from the '{}' section of README.md may be outdated:\n\n\
+            {}\n\n\
+            Current code summaries:\n{}\n\n\
+            Project context:\n{}\n\n\
+            For every line above, respond with exactly one line in the format \
+            'LINE <number>: [<severity> <confidence>] <corrected text>', or 'LINE <number>: NO_CHANGE' if the line is still accurate. \
+            {}\n\
+            Do not include anything else.",
+            section_name,
+            lines,
+            relevant_summaries.join("\n"),
+            project_summary,
+            JUDGMENT_INSTRUCTIONS
+        );
+
+        let response = self.llm_client.check_readme(&prompt).await?;
+        let suggestions = Self::parse_batch_response(&response);
+
+        let mut results = Vec::new();
+        for mapping in mappings {
+            match suggestions.get(&mapping.line_number) {
+                Some(suggested) if suggested != "NO_CHANGE" => {
+                    if let Some(result) = Self::judged_result(mapping, section, suggested) {
+                        results.push(result);
+                    }
+                }
+                Some(_) => {}
+                None => tracing::debug!(
+                    "No batch verdict returned for line {}",
+                    mapping.line_number
+                ),
+            }
+        }
+
+        Ok(results)
+    }
+
+    fn parse_batch_response(response: &str) -> HashMap<usize, String> {
+        let mut suggestions = HashMap::new();
+
+        for line in response.lines() {
+            let trimmed = line.trim().trim_start_matches(['-', '*', ' ']);
+            let Some(rest) = trimmed
+                .strip_prefix("LINE ")
+                .or_else(|| trimmed.strip_prefix("Line "))
+            else {
+                continue;
+            };
+
+            if let Some((number, text)) = rest.split_once(':') {
+                if let Ok(line_number) = number.trim().parse::<usize>() {
+                    let text = text.trim().trim_matches('"').trim().to_string();
+                    suggestions.insert(line_number, text);
+                }
+            }
+        }
+
+        suggestions
+    }
+
+    async fn suggest_update(
+        &self,
+        mapping: &ReadmeLineMapping,
+        section: &str,
+        project_summary: &str,
+    ) -> Result<Option<ValidationResult>> {
+        let relevant_summaries = self.collect_relevant_summaries(&mapping.cache_keys);
+
         if relevant_summaries.is_empty() {
             return Ok(None);
         }
@@ -239,51 +1033,155 @@ impl ReadmeValidator {
             Line {}: \"{}\"\n\n\
             Current code summaries:\n{}\n\n\
             Project context:\n{}\n\n\
-            If this line needs updating based on the current code, provide a corrected version. \
+            If this line needs updating based on the current code, respond with \
+            '[<severity> <confidence>] <corrected line>'. {}\n\
             If the line is still accurate, respond with 'NO_CHANGE'. \
-            Only provide the updated line text, nothing else.",
-            mapping.line_number, mapping.line_content, combined_summaries, project_summary
+            Only provide the judgment and the updated line text, nothing else.",
+            mapping.line_number, mapping.line_content, combined_summaries, project_summary, JUDGMENT_INSTRUCTIONS
         );
 
-        let response = self.llm_client.generate_readme_suggestion(&prompt).await?;
+        let response = self.llm_client.check_readme(&prompt).await?;
 
-        if response.trim() != "NO_CHANGE" && response.trim() != mapping.line_content {
-            Ok(Some(ValidationResult {
-                line_number: mapping.line_number,
-                current_content: mapping.line_content.clone(),
-                suggested_content: response.trim().to_string(),
-                reason: "Content outdated based on current code".to_string(),
-                affected_cache_entries: mapping.cache_keys.clone(),
-            }))
-        } else {
-            Ok(None)
+        if response.trim() == "NO_CHANGE" {
+            return Ok(None);
         }
+        Ok(Self::judged_result(mapping, section, response.trim()))
+    }
+
+    /// Flags or drops suggestions that introduce references to files or
+    /// flags the project does not have. References already on the README
+    /// line are left to the line's own review.
+    fn check_references(&self, guard: &ReferenceGuard, results: Vec<ValidationResult>) -> Vec<ValidationResult> {
+        results
+            .into_iter()
+            .filter_map(|mut result| {
+                let unknown: Vec<String> = guard
+                    .unknown_references(&result.suggested_content)
+                    .into_iter()
+                    .filter(|reference| !result.current_content.contains(reference.as_str()))
+                    .collect();
+                if unknown.is_empty() {
+                    return Some(result);
+                }
+
+                let listed = unknown.iter().map(|reference| format!("`{reference}`")).collect::<Vec<_>>().join(", ");
+                if self.reference_check == ReferenceCheck::Strip {
+                    tracing::info!("Dropped the suggestion for line {}: unverified references {listed}", result.line_number);
+                    return None;
+                }
+                result.reason = format!("{}; unverified references: {listed}", result.reason);
+                result.confidence = result.confidence.min(UNVERIFIED_CONFIDENCE);
+                Some(result)
+            })
+            .collect()
+    }
+
+    /// Builds a suggestion from an LLM answer, unless it leaves the line as
+    /// it is.
+    fn judged_result(mapping: &ReadmeLineMapping, section: &str, answer: &str) -> Option<ValidationResult> {
+        let (severity, confidence, suggested) = Self::parse_judgment(answer);
+        (suggested != mapping.line_content).then(|| ValidationResult {
+            line_number: mapping.line_number,
+            current_content: mapping.line_content.clone(),
+            suggested_content: suggested,
+            reason: severity.reason().to_string(),
+            affected_cache_entries: mapping.cache_keys.clone(),
+            section: section.to_string(),
+            confidence,
+            severity,
+        })
     }
 
-    pub fn print_validation_results(results: &[ValidationResult]) {
-        if results.is_empty() {
-            println!("✅ README.md is up-to-date with the current codebase");
-            return;
+    /// Splits an optional `[<severity> <confidence>]` prefix off a suggested
+    /// line. Answers without one count as outdated with full confidence.
+    fn parse_judgment(answer: &str) -> (Severity, f32, String) {
+        let mut severity = Severity::Outdated;
+        let mut confidence = 1.0;
+        let mut text = answer.trim();
+
+        if let Some((judgment, rest)) = text.strip_prefix('[').and_then(|rest| rest.split_once(']')) {
+            for word in judgment.split_whitespace() {
+                match word.to_lowercase().as_str() {
+                    "cosmetic" => severity = Severity::Cosmetic,
+                    "outdated" => severity = Severity::Outdated,
+                    word => {
+                        if let Ok(value) = word.parse::<f32>() {
+                            confidence = value.clamp(0.0, 1.0);
+                        }
+                    }
+                }
+            }
+            text = rest;
         }
 
-        println!("📋 README.md Validation Results");
-        println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+        (severity, confidence, text.trim().trim_matches('"').trim().to_string())
+    }
+
+    /// Groups results by README section, preserving document order.
+    pub fn group_by_section(results: &[ValidationResult]) -> Vec<(String, Vec<&ValidationResult>)> {
+        let mut groups: Vec<(String, Vec<&ValidationResult>)> = Vec::new();
 
         for result in results {
-            println!("\n⚠️  Line {}: {}", result.line_number, result.reason);
-            println!("   Current: \"{}\"", result.current_content);
-            println!("   Suggested: \"{}\"", result.suggested_content);
-
-            if !result.affected_cache_entries.is_empty() {
-                println!("   Affected files:");
-                for entry in &result.affected_cache_entries {
-                    println!("     - {}", entry);
-                }
+            match groups.iter_mut().find(|(section, _)| *section == result.section) {
+                Some((_, group)) => group.push(result),
+                None => groups.push((result.section.clone(), vec![result])),
             }
         }
 
-        println!("\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-        println!("💡 {} lines need updating", results.len());
+        groups
+    }
+
+    pub fn section_label(section: &str) -> &str {
+        if section.is_empty() {
+            "Introduction"
+        } else {
+            section
+        }
+    }
+
+    /// Asks the LLM for a short natural-language explanation of why each
+    /// affected section is outdated, for quick human triage.
+    pub async fn generate_digest(&self, results: &[ValidationResult]) -> Result<String> {
+        let groups = Self::group_by_section(results);
+        let mut findings = String::new();
+
+        for (section, group) in &groups {
+            findings.push_str(&format!("Section '{}':\n", Self::section_label(section)));
+            for result in group {
+                findings.push_str(&format!(
+                    "- Line {}: \"{}\" -> \"{}\"\n",
+                    result.line_number, result.current_content, result.suggested_content
+                ));
+            }
+        }
+
+        let prompt = format!(
+            "The following README.md lines were found to be outdated, grouped by section, with suggested replacements:\n\n\
+            {findings}\n\
+            Write one sentence per section explaining why that section is outdated, \
+            for example: 'The Architecture section is outdated because the plugin system was removed.' \
+            Respond with one sentence per line and nothing else."
+        );
+
+        self.llm_client.generate_readme_suggestion(&prompt).await
+    }
+
+    pub fn report_digest(reporter: &dyn Reporter, digest: &str) {
+        let lines: Vec<String> = digest
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(|line| format!("• {}", line.trim_start_matches(['-', '*', '•', ' '])))
+            .collect();
+        reporter.section("🧭 Validation Digest", &lines);
+    }
+
+    /// Reports the results in the terminal format; other formats go through
+    /// `RendererRegistry`.
+    pub fn report_validation_results(reporter: &dyn Reporter, results: &[ValidationResult]) {
+        if let Ok(output) = TextRenderer.render(&RenderInput::results(results)) {
+            reporter.output(&output);
+        }
     }
 }
 
@@ -291,6 +1189,7 @@ impl ReadmeValidator {
 mod tests {
     use super::*;
     use crate::config::Config;
+    use crate::reporter::SilentReporter;
     use tempfile::TempDir;
 
     fn create_test_validator() -> (ReadmeValidator, TempDir) {
@@ -301,6 +1200,7 @@ mod tests {
             openai_model_name: "test-model".to_string(),
             cache_dir_name: ".test_cache".to_string(),
             log_level: "debug".to_string(),
+            ..Default::default()
         };
 
         let cache_manager = CacheManager::new(temp_dir.path(), ".test_cache").unwrap();
@@ -324,6 +1224,192 @@ mod tests {
         assert!(!validator.is_content_line("---"));
     }
 
+    #[test]
+    fn test_section_headings() {
+        let content = "Intro line\n# Title\ntext\n```\n# not a heading\n```\n## Usage\nmore";
+        let sections = ReadmeValidator::section_headings(content);
+
+        assert_eq!(sections[0], "");
+        assert_eq!(sections[2], "Title");
+        assert_eq!(sections[4], "Title");
+        assert_eq!(sections[7], "Usage");
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_sections_keep_mappings_when_lines_move() -> Result<()> {
+        let (mut validator, temp_dir) = create_test_validator();
+        let base_path = temp_dir.path();
+        validator.cache_manager.store_summary(
+            &base_path.join("src/parser.rs"),
+            "hash".to_string(),
+            "Parses input.".to_string(),
+        )?;
+
+        let readme = "# Demo\n\nIntro.\n\n## Modules\nThe parser module reads input.\n";
+        let (sections, mut mappings) = validator.remap_readme(readme, base_path)?;
+        assert_eq!(mappings.len(), 1);
+        assert_eq!((mappings[0].line_number, mappings[0].section.as_str()), (6, "modules"));
+        mappings[0].last_validated_hash = Some("validated".to_string());
+        validator
+            .cache_manager
+            .update_readme_mapping("old".to_string(), sections, mappings)
+            .await?;
+
+        // Editing the intro shifts the Modules section down a line
+        let edited = readme.replace("Intro.", "Intro.\nMore intro.");
+        let (_, mappings) = validator.remap_readme(&edited, base_path)?;
+        assert_eq!(mappings.len(), 1);
+        assert_eq!(mappings[0].line_number, 7);
+        assert_eq!(mappings[0].last_validated_hash.as_deref(), Some("validated"));
+
+        // Editing the section itself maps it afresh
+        let edited = edited.replace("reads input", "reads all input");
+        let (_, mappings) = validator.remap_readme(&edited, base_path)?;
+        assert_eq!(mappings[0].last_validated_hash, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_batch_response() {
+        let response = "LINE 3: The cache module stores summaries\nLine 5: NO_CHANGE\n- LINE 9: \"Quoted\"\nnoise";
+        let parsed = ReadmeValidator::parse_batch_response(response);
+
+        assert_eq!(parsed.len(), 3);
+        assert_eq!(parsed[&3], "The cache module stores summaries");
+        assert_eq!(parsed[&5], "NO_CHANGE");
+        assert_eq!(parsed[&9], "Quoted");
+    }
+
+    #[test]
+    fn test_parse_judgment() {
+        assert_eq!(
+            ReadmeValidator::parse_judgment("[cosmetic 0.4] \"The cache module stores summaries\""),
+            (Severity::Cosmetic, 0.4, "The cache module stores summaries".to_string())
+        );
+        assert_eq!(
+            ReadmeValidator::parse_judgment("[Outdated 7] Uses Postgres"),
+            (Severity::Outdated, 1.0, "Uses Postgres".to_string())
+        );
+        assert_eq!(
+            ReadmeValidator::parse_judgment("Uses Postgres"),
+            (Severity::Outdated, 1.0, "Uses Postgres".to_string())
+        );
+    }
+
+    #[test]
+    fn test_check_references_flags_or_drops_hallucinations() -> Result<()> {
+        let (mut validator, temp_dir) = create_test_validator();
+        let base_path = temp_dir.path();
+        fs::create_dir_all(base_path.join("src"))?;
+        fs::write(base_path.join("src/parser.rs"), "pub fn parse() {}")?;
+        validator
+            .cache_manager
+            .store_summary(&base_path.join("src/parser.rs"), "hash".to_string(), "Parses input.".to_string())?;
+        let guard = ReferenceGuard::for_project(base_path, &validator.cache_manager.get_all_summaries(), "");
+
+        let result = |current: &str, suggested: &str| ValidationResult {
+            line_number: 3,
+            current_content: current.to_string(),
+            suggested_content: suggested.to_string(),
+            reason: "Content outdated based on current code".to_string(),
+            affected_cache_entries: vec![],
+            section: String::new(),
+            confidence: 0.9,
+            severity: Severity::Outdated,
+        };
+        let results = vec![
+            result("The parser lives in parser.rs", "The parser lives in `src/parser.rs`"),
+            result("Run with --fast", "Run with --fast and see src/lexer.rs"),
+        ];
+
+        let flagged = validator.check_references(&guard, results.clone());
+        assert_eq!(flagged.len(), 2);
+        assert_eq!(flagged[0].confidence, 0.9);
+        assert_eq!(flagged[1].confidence, UNVERIFIED_CONFIDENCE);
+        assert!(flagged[1].reason.ends_with("; unverified references: `src/lexer.rs`"));
+
+        validator.set_reference_check(ReferenceCheck::Strip);
+        let stripped = validator.check_references(&guard, results);
+        assert_eq!(stripped.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_by_section() {
+        let result = |line_number: usize, section: &str| ValidationResult {
+            line_number,
+            current_content: String::new(),
+            suggested_content: String::new(),
+            reason: String::new(),
+            affected_cache_entries: vec![],
+            section: section.to_string(),
+            confidence: 1.0,
+            severity: Severity::Outdated,
+        };
+        let results = vec![result(3, "Usage"), result(10, "Architecture"), result(4, "Usage")];
+
+        let groups = ReadmeValidator::group_by_section(&results);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0, "Usage");
+        assert_eq!(groups[0].1.len(), 2);
+        assert_eq!(groups[1].0, "Architecture");
+    }
+
+    #[tokio::test]
+    async fn test_managed_sections_leave_manual_content() -> Result<()> {
+        let (mut validator, temp_dir) = create_test_validator();
+        let base_path = temp_dir.path();
+        let readme = "# Project\n\nManual intro that mentions an old API.\n\n<!-- doctreeai:begin overview -->\nStale overview.\n<!-- doctreeai:end -->\n\n## FAQ\nManual answers.\n";
+        fs::write(base_path.join("README.md"), readme)?;
+
+        let results = validator.validate_readme(base_path, "Fresh overview.").await?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].section, "overview");
+        assert_eq!(results[0].line_number, 5);
+
+        let update = validator.managed_readme_update(base_path)?.unwrap();
+        assert_eq!(update.sections, 1);
+        assert!(update.diff().contains("-Stale overview.\n+Fresh overview.\n"));
+        let backups = ReadmeBackups::new(&base_path.join(".test_cache"), 5);
+        update.apply(&backups)?;
+        assert_eq!(backups.list()?.len(), 1);
+        assert_eq!(
+            fs::read_to_string(base_path.join("README.md"))?,
+            readme.replace("Stale overview.", "Fresh overview.")
+        );
+
+        assert!(validator.validate_readme(base_path, "Fresh overview.").await?.is_empty());
+
+        let catalog = "## Services / Packages\n\n| Name |\n";
+        validator.add_generated_section("Services / Packages", catalog.to_string());
+        let results = validator.validate_readme(base_path, "Newer overview.").await?;
+        let document = validator.render_document(base_path, &results)?;
+        assert!(document.contains("begin overview -->\nNewer overview.\n<!-- doctreeai:end"));
+        assert!(document.ends_with("Manual answers.\n\n## Services / Packages\n\n| Name |\n"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_draft_missing_sections() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let cache_manager = CacheManager::new(temp_dir.path(), ".test_cache")?;
+        let mut validator = ReadmeValidator::new(cache_manager, LanguageModelClient::mock(&Config::default()));
+        fs::write(temp_dir.path().join("README.md"), "# Project\n\n## Installation\ncargo install\n")?;
+
+        let required = ["Installation", "License"].map(String::from);
+        let drafts = validator.draft_missing_sections(temp_dir.path(), &required, "A CLI.").await?;
+        assert_eq!(drafts.len(), 1);
+        assert_eq!(drafts[0].section, "License");
+        assert_eq!(drafts[0].line_number, 0);
+        assert!(drafts[0].suggested_content.starts_with("## License\n\n"));
+
+        fs::write(temp_dir.path().join("Dockerfile"), "FROM scratch\n")?;
+        let required = ["Install steps".to_string()];
+        let drafts = validator.draft_missing_sections(temp_dir.path(), &required, "A CLI.").await?;
+        assert!(drafts[0].suggested_content.starts_with("## Install steps\n\n```bash\ndocker build -t "));
+        Ok(())
+    }
+
     #[test]
     fn test_validation_result_display() {
         let results = vec![ValidationResult {
@@ -332,8 +1418,11 @@ mod tests {
             suggested_content: "New content".to_string(),
             reason: "Outdated".to_string(),
             affected_cache_entries: vec!["src/main.rs".to_string()],
+            section: "Architecture".to_string(),
+            confidence: 0.8,
+            severity: Severity::Outdated,
         }];
 
-        ReadmeValidator::print_validation_results(&results);
+        ReadmeValidator::report_validation_results(&SilentReporter, &results);
     }
 }
@@ -0,0 +1,556 @@
+use crate::cache::CacheSummary;
+use crate::error::{DocTreeError, Result};
+use crate::scanner::FileNode;
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Extensions that make a bare word a file reference ("see config.toml").
+const FILE_EXTENSIONS: &[&str] = &[
+    "rs", "py", "js", "jsx", "ts", "tsx", "go", "java", "kt", "c", "h", "cpp", "hpp", "cs", "rb", "php",
+    "swift", "sh", "md", "toml", "yaml", "yml", "json", "lock", "cfg", "ini", "txt", "sql", "proto",
+];
+
+/// Build output directories, which are not scanned and may not exist yet.
+const BUILD_OUTPUT_DIRS: &[&str] = &["target/", "dist/", "build/", "node_modules/"];
+
+/// Flags every CLI has, which are never reported.
+const COMMON_FLAGS: &[&str] = &["--help", "--version"];
+
+/// What happens to LLM suggestions that mention files or flags the project
+/// does not have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReferenceCheck {
+    Off,
+    /// Keep the suggestion, but name the unverified references in its reason
+    /// and cap its confidence at `UNVERIFIED_CONFIDENCE`.
+    #[default]
+    Flag,
+    /// Drop the suggestion.
+    Strip,
+}
+
+/// Highest confidence a flagged suggestion keeps, so `--min-confidence 0.6`
+/// and above filters them out.
+pub const UNVERIFIED_CONFIDENCE: f32 = 0.5;
+
+impl FromStr for ReferenceCheck {
+    type Err = DocTreeError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "off" => Ok(ReferenceCheck::Off),
+            "flag" => Ok(ReferenceCheck::Flag),
+            "strip" => Ok(ReferenceCheck::Strip),
+            other => Err(DocTreeError::config(format!(
+                "Invalid reference check '{other}' (expected off, flag, or strip)"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for ReferenceCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReferenceCheck::Off => write!(f, "off"),
+            ReferenceCheck::Flag => write!(f, "flag"),
+            ReferenceCheck::Strip => write!(f, "strip"),
+        }
+    }
+}
+
+/// What a broken README reference points at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReferenceKind {
+    Path,
+    Flag,
+    Binary,
+    Subcommand,
+}
+
+impl fmt::Display for ReferenceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReferenceKind::Path => write!(f, "path"),
+            ReferenceKind::Flag => write!(f, "flag"),
+            ReferenceKind::Binary => write!(f, "binary"),
+            ReferenceKind::Subcommand => write!(f, "subcommand"),
+        }
+    }
+}
+
+/// A README reference that resolves to nothing in the project, found by
+/// `doctreeai lint`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BrokenReference {
+    pub line_number: usize,
+    pub kind: ReferenceKind,
+    pub reference: String,
+    pub line_content: String,
+}
+
+/// Checks the file paths, flags, binaries, and subcommands in README text
+/// against the scanned tree and the CLI the project's code defines.
+pub struct ReferenceGuard {
+    base_path: PathBuf,
+    /// Scanned files and directories relative to the project root, with `/`
+    /// separators.
+    paths: HashSet<String>,
+    flags: HashSet<String>,
+    /// Executables the project builds (Cargo, npm, and Python scripts).
+    binaries: HashSet<String>,
+    /// Subcommands defined with clap, argparse, or commander.
+    subcommands: HashSet<String>,
+}
+
+impl ReferenceGuard {
+    /// Known flags are the `--flags` written anywhere in the summarized files,
+    /// clap `long` arguments, and the flags the README already uses.
+    pub fn for_project(base_path: &Path, summaries: &[CacheSummary], readme: &str) -> Self {
+        let mut guard = Self::empty(base_path);
+        guard.flags.extend(extract_flags(readme));
+        for summary in summaries {
+            guard.add(&summary.source_path, summary.is_directory);
+        }
+        guard
+    }
+
+    /// A guard over a freshly scanned tree, for checking the README itself
+    /// without a cache.
+    pub fn from_tree(base_path: &Path, root: &FileNode) -> Self {
+        fn walk(guard: &mut ReferenceGuard, node: &FileNode) {
+            guard.add(&node.path, node.is_directory);
+            for child in &node.children {
+                walk(guard, child);
+            }
+        }
+
+        let mut guard = Self::empty(base_path);
+        walk(&mut guard, root);
+        guard
+    }
+
+    fn empty(base_path: &Path) -> Self {
+        Self {
+            base_path: base_path.to_path_buf(),
+            paths: HashSet::new(),
+            flags: COMMON_FLAGS.iter().map(|flag| flag.to_string()).collect(),
+            binaries: HashSet::new(),
+            subcommands: HashSet::new(),
+        }
+    }
+
+    fn add(&mut self, path: &Path, is_directory: bool) {
+        let relative_path = path
+            .strip_prefix(&self.base_path)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        if is_directory {
+            self.paths.insert(relative_path);
+            return;
+        }
+
+        if let Ok(content) = fs::read_to_string(path) {
+            self.flags.extend(extract_flags(&content));
+            self.flags.extend(clap_flags(&content));
+            self.subcommands.extend(subcommands(&content));
+            self.binaries.extend(declared_binaries(&relative_path, &content));
+        }
+        // src/bin/<name>.rs and src/bin/<name>/main.rs
+        if let Some(name) = relative_path.strip_prefix("src/bin/").and_then(|rest| {
+            rest.strip_suffix("/main.rs").or_else(|| rest.strip_suffix(".rs").filter(|name| !name.contains('/')))
+        }) {
+            self.binaries.insert(name.to_string());
+        }
+        self.paths.insert(relative_path);
+    }
+
+    /// References in `text` to files and flags the project does not have,
+    /// in order of appearance.
+    pub fn unknown_references(&self, text: &str) -> Vec<String> {
+        let mut unknown: Vec<String> = extract_paths(text)
+            .into_iter()
+            .filter(|path| !self.path_exists(path))
+            .collect();
+        unknown.extend(extract_flags(text).into_iter().filter(|flag| !self.flags.contains(flag)));
+        unknown
+    }
+
+    /// Whether a referenced path is in the scanned tree, as given or as a
+    /// suffix ("parser.rs" for "src/parser.rs"), or on disk. Paths outside
+    /// the project or in build output cannot be checked and always pass.
+    fn path_exists(&self, reference: &str) -> bool {
+        let path = reference.trim_start_matches("./");
+        if BUILD_OUTPUT_DIRS.iter().any(|dir| path.starts_with(dir)) {
+            return true;
+        }
+        let path = path.trim_end_matches('/');
+        if path.is_empty() || path.starts_with("..") || path.starts_with('/') || path.starts_with('~') {
+            return true;
+        }
+        let suffix = format!("/{path}");
+        self.paths.contains(path)
+            || self.paths.iter().any(|known| known.ends_with(&suffix))
+            || self.base_path.join(path).exists()
+    }
+
+    /// Every reference in the README that does not resolve: paths and link
+    /// targets anywhere, plus the binaries, subcommands, and flags of
+    /// commands in code blocks and inline code that run one of the
+    /// project's binaries. Commands of other tools are not checked.
+    pub fn lint(&self, readme: &str) -> Vec<BrokenReference> {
+        let mut broken: Vec<BrokenReference> = Vec::new();
+        let mut in_code_block = false;
+
+        for (index, line) in readme.lines().enumerate() {
+            let trimmed = line.trim();
+            if trimmed.starts_with("```") {
+                in_code_block = !in_code_block;
+                continue;
+            }
+
+            let mut problems: Vec<(ReferenceKind, String)> = extract_paths(line)
+                .into_iter()
+                .chain(link_targets(line))
+                .filter(|path| !self.path_exists(path))
+                .map(|path| (ReferenceKind::Path, path))
+                .collect();
+            let commands: Vec<&str> = if in_code_block {
+                vec![trimmed.trim_start_matches("$ ")]
+            } else {
+                line.split('`').skip(1).step_by(2).collect()
+            };
+            for command in commands {
+                problems.extend(self.check_command(command));
+            }
+
+            for (kind, reference) in problems {
+                if !broken.iter().any(|known| known.line_number == index + 1 && known.reference == reference) {
+                    broken.push(BrokenReference {
+                        line_number: index + 1,
+                        kind,
+                        reference,
+                        line_content: line.to_string(),
+                    });
+                }
+            }
+        }
+
+        broken
+    }
+
+    fn check_command(&self, command: &str) -> Vec<(ReferenceKind, String)> {
+        let tokens: Vec<&str> = command.split_whitespace().collect();
+        let mut problems = Vec::new();
+        let Some(program) = tokens.first() else {
+            return problems;
+        };
+        if self.binaries.is_empty() {
+            return problems;
+        }
+
+        // cargo run --bin <name>
+        if *program == "cargo" {
+            for (index, token) in tokens.iter().enumerate() {
+                let name = match token.strip_prefix("--bin") {
+                    Some("") => tokens.get(index + 1).copied(),
+                    Some(rest) => rest.strip_prefix('='),
+                    None => None,
+                };
+                if let Some(name) = name.filter(|name| !self.binaries.contains(*name)) {
+                    problems.push((ReferenceKind::Binary, name.to_string()));
+                }
+            }
+            return problems;
+        }
+
+        // ./target/release/<name>
+        let name = program.rsplit('/').next().unwrap_or(program);
+        if !self.binaries.contains(name) {
+            if program.contains("target/release/") || program.contains("target/debug/") {
+                problems.push((ReferenceKind::Binary, name.to_string()));
+            }
+            return problems;
+        }
+
+        if let Some(subcommand) = tokens.get(1).filter(|token| {
+            !token.starts_with('-') && token.chars().all(|c| c.is_ascii_lowercase() || c == '-')
+        }) {
+            if !self.subcommands.is_empty() && !self.subcommands.contains(*subcommand) {
+                problems.push((ReferenceKind::Subcommand, subcommand.to_string()));
+            }
+        }
+        for flag in extract_flags(command) {
+            if !self.flags.contains(&flag) {
+                problems.push((ReferenceKind::Flag, flag));
+            }
+        }
+        problems
+    }
+}
+
+/// Relative Markdown link targets, without their `#fragment`.
+fn link_targets(line: &str) -> Vec<String> {
+    let link = Regex::new(r"\]\(([^)\s]+)\)").expect("valid regex");
+    link.captures_iter(line)
+        .map(|captures| captures[1].split('#').next().unwrap_or_default().to_string())
+        .filter(|target| !target.is_empty() && !target.contains(':'))
+        .collect()
+}
+
+/// Path-like references: inline code spans that look like paths, and words
+/// ending in a known file extension. Spans holding a whole command only
+/// contribute the latter, since their arguments are often branch names or
+/// other slash-separated values.
+fn extract_paths(text: &str) -> Vec<String> {
+    let path_like = Regex::new(r"^[A-Za-z0-9_.\-/~]+$").expect("valid regex");
+    let is_candidate = |token: &str, in_code: bool| {
+        let has_extension = token
+            .rsplit_once('.')
+            .is_some_and(|(stem, extension)| !stem.is_empty() && FILE_EXTENSIONS.contains(&extension));
+        path_like.is_match(token)
+            && !token.starts_with('-')
+            && token.chars().any(|c| c.is_ascii_alphabetic())
+            && (has_extension || (in_code && token.contains('/')))
+    };
+
+    let mut paths = Vec::new();
+    let mut push = |token: &str| {
+        if !paths.iter().any(|path| path == token) {
+            paths.push(token.to_string());
+        }
+    };
+
+    for (index, segment) in text.split('`').enumerate() {
+        let in_code = index % 2 == 1 && segment.split_whitespace().count() == 1;
+        for word in segment.split_whitespace() {
+            if word.contains("://") {
+                continue;
+            }
+            let token = word.trim_matches(|c: char| "\"'()[]{}<>,;:*!?".contains(c)).trim_end_matches('.');
+            if is_candidate(token, in_code) {
+                push(token);
+            }
+        }
+    }
+
+    paths
+}
+
+/// Long `--flags`, without any `=value`.
+fn extract_flags(text: &str) -> Vec<String> {
+    let flag = Regex::new(r"(?:^|[\s`'\x22(\[])(--[A-Za-z][A-Za-z0-9-]*)").expect("valid regex");
+    let mut flags: Vec<String> = Vec::new();
+    for captures in flag.captures_iter(text) {
+        if !flags.iter().any(|known| *known == captures[1]) {
+            flags.push(captures[1].to_string());
+        }
+    }
+    flags
+}
+
+/// Flags declared with clap's derive API: `long = "name"`, or a bare `long`
+/// named after the field that follows.
+fn clap_flags(content: &str) -> Vec<String> {
+    let long = Regex::new(r#"\blong\b(?:\s*=\s*"([^"]+)")?"#).expect("valid regex");
+    let field = Regex::new(r"^\s*(?:pub(?:\([a-z]+\))?\s+)?([a-z_][a-z0-9_]*)\s*:").expect("valid regex");
+    let mut flags = Vec::new();
+    let mut pending = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("#[arg(") || trimmed.starts_with("#[clap(") {
+            if let Some(captures) = long.captures(trimmed) {
+                match captures.get(1) {
+                    Some(name) => flags.push(format!("--{}", name.as_str())),
+                    None => pending = true,
+                }
+            }
+        } else if pending {
+            if let Some(captures) = field.captures(line) {
+                flags.push(format!("--{}", captures[1].trim_start_matches("r#").replace('_', "-")));
+                pending = false;
+            }
+        }
+    }
+
+    flags
+}
+
+/// Subcommand names: variants of a clap `#[derive(Subcommand)]` enum
+/// (kebab-cased, or their `name = "..."`), argparse `add_parser`, and
+/// commander `.command`.
+fn subcommands(content: &str) -> Vec<String> {
+    let call = Regex::new(r#"(?:add_parser|\.command)\(\s*["']([A-Za-z0-9_-]+)"#).expect("valid regex");
+    let variant = Regex::new(r"^\s*([A-Z][A-Za-z0-9]*)\s*(?:[{(,]|$)").expect("valid regex");
+    let rename = Regex::new(r#"#\[(?:command|clap)\(.*\bname\s*=\s*"([^"]+)""#).expect("valid regex");
+
+    let mut names: Vec<String> = call.captures_iter(content).map(|captures| captures[1].to_string()).collect();
+    let mut derive_seen = false;
+    // Brace depth inside a Subcommand enum; variants sit at depth 1
+    let mut depth: Option<i32> = None;
+    let mut renamed: Option<String> = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        match depth {
+            None => {
+                if trimmed.starts_with("#[derive(") && trimmed.contains("Subcommand") {
+                    derive_seen = true;
+                } else if derive_seen && trimmed.contains("enum ") {
+                    derive_seen = false;
+                    depth = Some(brace_delta(trimmed));
+                }
+            }
+            Some(current) => {
+                if current == 1 {
+                    if let Some(captures) = rename.captures(trimmed) {
+                        renamed = Some(captures[1].to_string());
+                    } else if let Some(captures) = variant.captures(line) {
+                        names.push(renamed.take().unwrap_or_else(|| kebab_case(&captures[1])));
+                    }
+                }
+                let next = current + brace_delta(trimmed);
+                depth = (next > 0).then_some(next);
+            }
+        }
+    }
+
+    names
+}
+
+/// Net `{` minus `}` outside string literals.
+pub(crate) fn brace_delta(line: &str) -> i32 {
+    let mut delta = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in line.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '{' if !in_string => delta += 1,
+            '}' if !in_string => delta -= 1,
+            _ => {}
+        }
+    }
+    delta
+}
+
+pub(crate) fn kebab_case(name: &str) -> String {
+    let mut kebab = String::new();
+    for (index, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() && index > 0 {
+            kebab.push('-');
+        }
+        kebab.push(c.to_ascii_lowercase());
+    }
+    kebab
+}
+
+/// Executables declared in a Cargo.toml (`[[bin]]`, or the package itself),
+/// package.json (`bin`), or pyproject.toml (`[project.scripts]`).
+pub(crate) fn declared_binaries(relative_path: &str, content: &str) -> Vec<String> {
+    let file_name = relative_path.rsplit('/').next().unwrap_or(relative_path);
+    match file_name {
+        "Cargo.toml" => {
+            let Ok(manifest) = content.parse::<toml::Table>() else {
+                return Vec::new();
+            };
+            let mut binaries: Vec<String> = manifest
+                .get("bin")
+                .and_then(|bins| bins.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|bin| bin.get("name")?.as_str().map(str::to_string))
+                .collect();
+            if let Some(name) = manifest.get("package").and_then(|package| package.get("name")?.as_str()) {
+                binaries.push(name.to_string());
+            }
+            binaries
+        }
+        "package.json" => {
+            let Ok(package) = serde_json::from_str::<serde_json::Value>(content) else {
+                return Vec::new();
+            };
+            match &package["bin"] {
+                serde_json::Value::Object(bins) => bins.keys().cloned().collect(),
+                serde_json::Value::String(_) => package["name"]
+                    .as_str()
+                    .map(|name| vec![name.rsplit('/').next().unwrap_or(name).to_string()])
+                    .unwrap_or_default(),
+                _ => Vec::new(),
+            }
+        }
+        "pyproject.toml" => content
+            .parse::<toml::Table>()
+            .ok()
+            .and_then(|project| project.get("project")?.get("scripts")?.as_table().cloned())
+            .map(|scripts| scripts.keys().cloned().collect())
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheManager;
+    use crate::scanner::DirectoryScanner;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_unknown_references() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        fs::create_dir_all(base_path.join("src"))?;
+        let cli = "struct Cli {\n    #[arg(long)]\n    dry_run: bool,\n    #[arg(short, long = \"out-dir\")]\n    output: String,\n}\n";
+        fs::write(base_path.join("src/cli.rs"), cli)?;
+        fs::write(base_path.join("Cargo.toml"), "[package]")?;
+        let mut cache_manager = CacheManager::new(base_path, ".test_cache")?;
+        cache_manager.store_summary(&base_path.join("src/cli.rs"), "hash".to_string(), "Parses flags.".to_string())?;
+        let guard = ReferenceGuard::for_project(base_path, &cache_manager.get_all_summaries(), "Run `app --verbose`.");
+
+        let text = "Run `app --dry-run --out-dir=dist --verbose --fast` and see `src/cli.rs`, cli.rs, \
+            Cargo.toml, `src/server/` and lib/missing.py (https://example.com/docs/guide.md), e.g. v1.2.";
+        assert_eq!(guard.unknown_references(text), vec!["src/server/", "lib/missing.py", "--fast"]);
+        assert!(guard.unknown_references("Summaries are cached per file.").is_empty());
+        assert_eq!("strip".parse::<ReferenceCheck>().ok(), Some(ReferenceCheck::Strip));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lint_readme() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        fs::create_dir_all(base_path.join("src"))?;
+        fs::write(base_path.join("Cargo.toml"), "[package]\nname = \"app\"\n")?;
+        let main = "#[derive(Subcommand)]\nenum Commands {\n    #[command(about = \"Run {it}\")]\n    Run {\n        #[arg(long)]\n        dry_run: bool,\n    },\n    #[command(name = \"ls\")]\n    List,\n    RetryFailed,\n}\n";
+        fs::write(base_path.join("src/main.rs"), main)?;
+        let root = DirectoryScanner::new(base_path.to_path_buf()).scan_directory()?;
+        let guard = ReferenceGuard::from_tree(base_path, &root);
+
+        let readme = "# App\nSee [the guide](docs/guide.md#setup) and `src/main.rs`.\n```bash\napp run --dry-run\n\
+            app deploy --fast\ncargo run --bin server\ngit push --force\n```\nRun `app retry-failed` or `./target/release/app ls`.\n";
+        let broken = guard.lint(readme);
+        assert_eq!(
+            broken
+                .iter()
+                .map(|broken| (broken.line_number, broken.kind, broken.reference.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (2, ReferenceKind::Path, "docs/guide.md"),
+                (5, ReferenceKind::Subcommand, "deploy"),
+                (5, ReferenceKind::Flag, "--fast"),
+                (6, ReferenceKind::Binary, "server"),
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,577 @@
+use crate::error::{DocTreeError, Result};
+use crate::readme_validator::{ReadmeValidator, Severity, ValidationResult};
+use crate::tree::SummaryTreeNode;
+use serde::Serialize;
+use serde_json::json;
+use std::fmt::Write;
+
+const SEPARATOR: &str = "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━";
+
+/// What a renderer is asked to render: the cached summary tree, the README
+/// validation results, or both.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct RenderInput<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tree: Option<&'a SummaryTreeNode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub results: Option<&'a [ValidationResult]>,
+}
+
+impl<'a> RenderInput<'a> {
+    pub fn tree(tree: &'a SummaryTreeNode) -> Self {
+        Self { tree: Some(tree), results: None }
+    }
+
+    pub fn results(results: &'a [ValidationResult]) -> Self {
+        Self { tree: None, results: Some(results) }
+    }
+}
+
+/// An output format. New formats implement this and are added to a
+/// `RendererRegistry` instead of printing from main.rs.
+pub trait Renderer {
+    /// Name selected with `--format`.
+    fn name(&self) -> &'static str;
+
+    fn render(&self, input: &RenderInput) -> Result<String>;
+}
+
+/// Renderers by name, in registration order.
+pub struct RendererRegistry {
+    renderers: Vec<Box<dyn Renderer>>,
+}
+
+impl RendererRegistry {
+    /// A registry without any renderers.
+    pub fn empty() -> Self {
+        Self { renderers: Vec::new() }
+    }
+
+    /// text, markdown, json, html, and sarif.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::empty();
+        registry.register(Box::new(TextRenderer));
+        registry.register(Box::new(MarkdownRenderer));
+        registry.register(Box::new(JsonRenderer));
+        registry.register(Box::new(HtmlRenderer));
+        registry.register(Box::new(SarifRenderer));
+        registry
+    }
+
+    /// Adds a renderer, replacing any registered under the same name.
+    pub fn register(&mut self, renderer: Box<dyn Renderer>) {
+        self.renderers.retain(|existing| existing.name() != renderer.name());
+        self.renderers.push(renderer);
+    }
+
+    pub fn get(&self, name: &str) -> Result<&dyn Renderer> {
+        let name = name.trim().to_lowercase();
+        self.renderers
+            .iter()
+            .find(|renderer| renderer.name() == name)
+            .map(Box::as_ref)
+            .ok_or_else(|| {
+                DocTreeError::config(format!(
+                    "Unknown output format '{name}' (expected {})",
+                    self.names().join(", ")
+                ))
+            })
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.renderers.iter().map(|renderer| renderer.name()).collect()
+    }
+
+    pub fn render(&self, name: &str, input: &RenderInput) -> Result<String> {
+        self.get(name)?.render(input)
+    }
+}
+
+impl Default for RendererRegistry {
+    fn default() -> Self {
+        Self::with_builtins()
+    }
+}
+
+/// First line of a summary, cut to 100 characters.
+fn summary_preview(summary: &str) -> String {
+    let first_line = summary.lines().next().unwrap_or("");
+    if first_line.chars().count() > 100 {
+        format!("{}...", first_line.chars().take(97).collect::<String>())
+    } else {
+        first_line.to_string()
+    }
+}
+
+fn node_label(node: &SummaryTreeNode) -> String {
+    if node.path.as_os_str().is_empty() {
+        "./".to_string()
+    } else if node.is_directory {
+        format!("{}/", node.path.display())
+    } else {
+        node.path.display().to_string()
+    }
+}
+
+fn node_status(node: &SummaryTreeNode) -> String {
+    if node.pinned {
+        format!("{}, pinned", node.status)
+    } else {
+        node.status.to_string()
+    }
+}
+
+/// The terminal output: emoji-marked tree and validation report.
+pub struct TextRenderer;
+
+impl TextRenderer {
+    fn render_tree(out: &mut String, node: &SummaryTreeNode, indent: usize) {
+        let indent_str = "  ".repeat(indent);
+        let icon = if node.is_directory { "📁" } else { "📄" };
+        let _ = writeln!(out, "{indent_str}{icon} {} [{}]", node_label(node), node_status(node));
+        if let Some(summary) = &node.summary {
+            let _ = writeln!(out, "{indent_str}   → {}", summary_preview(summary));
+        }
+        for child in &node.children {
+            Self::render_tree(out, child, indent + 1);
+        }
+    }
+
+    fn render_results(out: &mut String, results: &[ValidationResult]) {
+        if results.is_empty() {
+            out.push_str("✅ README.md is up-to-date with the current codebase\n");
+            return;
+        }
+
+        let _ = writeln!(out, "📋 README.md Validation Results\n{SEPARATOR}");
+        for (section, group) in ReadmeValidator::group_by_section(results) {
+            let _ = writeln!(out, "\n📑 {} ({} lines)", ReadmeValidator::section_label(&section), group.len());
+            for result in group {
+                let _ = writeln!(
+                    out,
+                    "\n⚠️  Line {}: {} ({}, confidence {:.2})",
+                    result.line_number, result.reason, result.severity, result.confidence
+                );
+                let _ = writeln!(out, "   Current: \"{}\"", result.current_content);
+                let _ = writeln!(out, "   Suggested: \"{}\"", result.suggested_content);
+                if !result.affected_cache_entries.is_empty() {
+                    out.push_str("   Affected files:\n");
+                    for entry in &result.affected_cache_entries {
+                        let _ = writeln!(out, "     - {entry}");
+                    }
+                }
+            }
+        }
+        let _ = writeln!(out, "\n{SEPARATOR}\n💡 {} lines need updating", results.len());
+    }
+}
+
+impl Renderer for TextRenderer {
+    fn name(&self) -> &'static str {
+        "text"
+    }
+
+    fn render(&self, input: &RenderInput) -> Result<String> {
+        let mut out = String::new();
+        if let Some(tree) = input.tree {
+            Self::render_tree(&mut out, tree, 0);
+        }
+        if let Some(results) = input.results {
+            Self::render_results(&mut out, results);
+        }
+        Ok(out)
+    }
+}
+
+/// Nested bullet lists and one subsection per README section, for pasting
+/// into issues or docs.
+pub struct MarkdownRenderer;
+
+impl MarkdownRenderer {
+    fn render_tree(out: &mut String, node: &SummaryTreeNode, indent: usize) {
+        let indent_str = "  ".repeat(indent);
+        let _ = write!(out, "{indent_str}- `{}` _({})_", node_label(node), node_status(node));
+        match &node.summary {
+            Some(summary) => {
+                let _ = writeln!(out, ": {}", summary_preview(summary));
+            }
+            None => out.push('\n'),
+        }
+        for child in &node.children {
+            Self::render_tree(out, child, indent + 1);
+        }
+    }
+
+    fn render_results(out: &mut String, results: &[ValidationResult]) {
+        out.push_str("## README.md Validation Results\n\n");
+        if results.is_empty() {
+            out.push_str("README.md is up-to-date with the current codebase.\n");
+            return;
+        }
+
+        for (section, group) in ReadmeValidator::group_by_section(results) {
+            let _ = writeln!(out, "### {}\n", ReadmeValidator::section_label(&section));
+            for result in group {
+                let _ = writeln!(
+                    out,
+                    "- **Line {}**: {} ({}, confidence {:.2})",
+                    result.line_number, result.reason, result.severity, result.confidence
+                );
+                let _ = writeln!(out, "  - Current: `{}`", result.current_content);
+                let _ = writeln!(out, "  - Suggested: `{}`", result.suggested_content);
+                if !result.affected_cache_entries.is_empty() {
+                    let _ = writeln!(out, "  - Affected files: {}", result.affected_cache_entries.join(", "));
+                }
+            }
+            out.push('\n');
+        }
+        let _ = writeln!(out, "{} lines need updating.", results.len());
+    }
+}
+
+impl Renderer for MarkdownRenderer {
+    fn name(&self) -> &'static str {
+        "markdown"
+    }
+
+    fn render(&self, input: &RenderInput) -> Result<String> {
+        let mut out = String::new();
+        if let Some(tree) = input.tree {
+            out.push_str("## Summary Tree\n\n");
+            Self::render_tree(&mut out, tree, 0);
+        }
+        if let Some(results) = input.results {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            Self::render_results(&mut out, results);
+        }
+        Ok(out)
+    }
+}
+
+/// Pretty-printed JSON. A lone tree or result list is emitted as is, so
+/// scripts that read `tree --format json` keep working.
+pub struct JsonRenderer;
+
+impl Renderer for JsonRenderer {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+
+    fn render(&self, input: &RenderInput) -> Result<String> {
+        let json = match (input.tree, input.results) {
+            (Some(tree), None) => serde_json::to_string_pretty(tree),
+            (None, Some(results)) => serde_json::to_string_pretty(results),
+            _ => serde_json::to_string_pretty(input),
+        }
+        .map_err(|e| DocTreeError::config(format!("Failed to serialize output: {e}")))?;
+        Ok(format!("{json}\n"))
+    }
+}
+
+/// A standalone HTML page.
+pub struct HtmlRenderer;
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl HtmlRenderer {
+    fn render_tree(out: &mut String, node: &SummaryTreeNode) {
+        let _ = write!(
+            out,
+            "<li><code>{}</code> <span class=\"{}\">{}</span>",
+            escape_html(&node_label(node)),
+            node.status,
+            escape_html(&node_status(node))
+        );
+        if let Some(summary) = &node.summary {
+            let _ = write!(out, "<p>{}</p>", escape_html(&summary_preview(summary)));
+        }
+        if !node.children.is_empty() {
+            out.push_str("\n<ul>\n");
+            for child in &node.children {
+                Self::render_tree(out, child);
+            }
+            out.push_str("</ul>\n");
+        }
+        out.push_str("</li>\n");
+    }
+
+    fn render_results(out: &mut String, results: &[ValidationResult]) {
+        out.push_str("<h2>README.md Validation Results</h2>\n");
+        if results.is_empty() {
+            out.push_str("<p>README.md is up-to-date with the current codebase.</p>\n");
+            return;
+        }
+
+        for (section, group) in ReadmeValidator::group_by_section(results) {
+            let _ = writeln!(out, "<h3>{}</h3>", escape_html(ReadmeValidator::section_label(&section)));
+            out.push_str("<table>\n<tr><th>Line</th><th>Reason</th><th>Severity</th><th>Confidence</th><th>Current</th><th>Suggested</th><th>Affected files</th></tr>\n");
+            for result in group {
+                let _ = writeln!(
+                    out,
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:.2}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    result.line_number,
+                    escape_html(&result.reason),
+                    result.severity,
+                    result.confidence,
+                    escape_html(&result.current_content),
+                    escape_html(&result.suggested_content),
+                    escape_html(&result.affected_cache_entries.join(", "))
+                );
+            }
+            out.push_str("</table>\n");
+        }
+        let _ = writeln!(out, "<p>{} lines need updating.</p>", results.len());
+    }
+}
+
+impl Renderer for HtmlRenderer {
+    fn name(&self) -> &'static str {
+        "html"
+    }
+
+    fn render(&self, input: &RenderInput) -> Result<String> {
+        let mut out = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>doctreeai report</title>\n</head>\n<body>\n",
+        );
+        if let Some(tree) = input.tree {
+            out.push_str("<h2>Summary Tree</h2>\n<ul>\n");
+            Self::render_tree(&mut out, tree);
+            out.push_str("</ul>\n");
+        }
+        if let Some(results) = input.results {
+            Self::render_results(&mut out, results);
+        }
+        out.push_str("</body>\n</html>\n");
+        Ok(out)
+    }
+}
+
+/// SARIF 2.1.0, so GitHub code scanning and reviewdog can annotate
+/// README.md with the validation results. The tree is not rendered.
+pub struct SarifRenderer;
+
+impl SarifRenderer {
+    fn rule_id(severity: Severity) -> &'static str {
+        match severity {
+            Severity::Outdated => "doctreeai/outdated-readme-line",
+            Severity::Cosmetic => "doctreeai/cosmetic-readme-line",
+        }
+    }
+
+    fn render_result(result: &ValidationResult) -> serde_json::Value {
+        let level = match result.severity {
+            Severity::Outdated => "warning",
+            Severity::Cosmetic => "note",
+        };
+        let mut message = result.reason.clone();
+        if !result.section.is_empty() {
+            message.push_str(&format!(" (section '{}')", result.section));
+        }
+        message.push_str(&format!("\nSuggested: {}", result.suggested_content));
+
+        let artifact = json!({ "uri": "README.md" });
+        let mut location = json!({ "physicalLocation": { "artifactLocation": artifact } });
+        let mut sarif_result = json!({
+            "ruleId": Self::rule_id(result.severity),
+            "level": level,
+            "message": { "text": message },
+            "properties": {
+                "confidence": result.confidence,
+                "section": result.section,
+                "affectedFiles": result.affected_cache_entries,
+            },
+        });
+
+        // Line 0 means the README or the section is missing: report the
+        // file without a region and without a fix
+        if result.line_number > 0 {
+            let line_count = result.current_content.lines().count().max(1);
+            let region = json!({
+                "startLine": result.line_number,
+                "endLine": result.line_number + line_count - 1,
+            });
+            location["physicalLocation"]["region"] = region.clone();
+            sarif_result["fixes"] = json!([{
+                "description": { "text": "Apply the suggested README text" },
+                "artifactChanges": [{
+                    "artifactLocation": artifact,
+                    "replacements": [{
+                        "deletedRegion": region,
+                        "insertedContent": { "text": format!("{}\n", result.suggested_content) },
+                    }],
+                }],
+            }]);
+        }
+        sarif_result["locations"] = json!([location]);
+        sarif_result
+    }
+}
+
+impl Renderer for SarifRenderer {
+    fn name(&self) -> &'static str {
+        "sarif"
+    }
+
+    fn render(&self, input: &RenderInput) -> Result<String> {
+        let rules: Vec<serde_json::Value> = [
+            (Severity::Outdated, "README line describes code that has changed"),
+            (Severity::Cosmetic, "README line wording could better match the code"),
+        ]
+        .into_iter()
+        .map(|(severity, description)| {
+            json!({
+                "id": Self::rule_id(severity),
+                "shortDescription": { "text": description },
+            })
+        })
+        .collect();
+        let results: Vec<serde_json::Value> = input
+            .results
+            .unwrap_or_default()
+            .iter()
+            .map(Self::render_result)
+            .collect();
+
+        let sarif = json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "doctreeai",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules,
+                    },
+                },
+                "results": results,
+            }],
+        });
+        let json = serde_json::to_string_pretty(&sarif)
+            .map_err(|e| DocTreeError::config(format!("Failed to serialize output: {e}")))?;
+        Ok(format!("{json}\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::Freshness;
+    use std::path::PathBuf;
+
+    fn sample_tree() -> SummaryTreeNode {
+        SummaryTreeNode {
+            path: PathBuf::new(),
+            is_directory: true,
+            summary: Some("A <demo> project".to_string()),
+            status: Freshness::Fresh,
+            pinned: false,
+            children: vec![SummaryTreeNode {
+                path: PathBuf::from("main.rs"),
+                is_directory: false,
+                summary: Some("Entry point".to_string()),
+                status: Freshness::Stale,
+                pinned: true,
+                children: Vec::new(),
+            }],
+        }
+    }
+
+    fn sample_results() -> Vec<ValidationResult> {
+        vec![ValidationResult {
+            line_number: 3,
+            current_content: "Uses SQLite".to_string(),
+            suggested_content: "Uses Postgres".to_string(),
+            reason: "Storage changed".to_string(),
+            affected_cache_entries: vec!["src/db.rs".to_string()],
+            section: String::new(),
+            confidence: 0.9,
+            severity: Severity::Outdated,
+        }]
+    }
+
+    #[test]
+    fn test_builtin_renderers() -> Result<()> {
+        let registry = RendererRegistry::with_builtins();
+        assert_eq!(registry.names(), vec!["text", "markdown", "json", "html", "sarif"]);
+        assert!(registry.get("yaml").is_err());
+
+        let tree = sample_tree();
+        let text = registry.render("text", &RenderInput::tree(&tree))?;
+        assert_eq!(
+            text,
+            "📁 ./ [fresh]\n   → A <demo> project\n  📄 main.rs [stale, pinned]\n     → Entry point\n"
+        );
+
+        let markdown = registry.render("Markdown", &RenderInput::tree(&tree))?;
+        assert!(markdown.contains("  - `main.rs` _(stale, pinned)_: Entry point\n"));
+
+        let json: serde_json::Value = serde_json::from_str(&registry.render("json", &RenderInput::tree(&tree))?)
+            .map_err(|e| DocTreeError::config(e.to_string()))?;
+        assert_eq!(json["children"][0]["status"], "stale");
+
+        let results = sample_results();
+        let html = registry.render("html", &RenderInput::results(&results))?;
+        assert!(html.contains("<h3>Introduction</h3>"));
+        assert!(html.contains("<td>Uses SQLite</td>"));
+        let html_tree = registry.render("html", &RenderInput::tree(&tree))?;
+        assert!(html_tree.contains("A &lt;demo&gt; project"));
+
+        let both = RenderInput { tree: Some(&tree), results: Some(&results) };
+        let json: serde_json::Value = serde_json::from_str(&registry.render("json", &both)?)
+            .map_err(|e| DocTreeError::config(e.to_string()))?;
+        assert_eq!(json["results"][0]["line_number"], 3);
+        assert_eq!(json["results"][0]["severity"], "outdated");
+
+        let sarif: serde_json::Value = serde_json::from_str(&registry.render("sarif", &both)?)
+            .map_err(|e| DocTreeError::config(e.to_string()))?;
+        assert_eq!(sarif["version"], "2.1.0");
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!(result["ruleId"], "doctreeai/outdated-readme-line");
+        assert_eq!(result["level"], "warning");
+        let location = &result["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "README.md");
+        assert_eq!(location["region"]["startLine"], 3);
+        assert_eq!(
+            result["fixes"][0]["artifactChanges"][0]["replacements"][0]["insertedContent"]["text"],
+            "Uses Postgres\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_register_custom_renderer() -> Result<()> {
+        struct CountRenderer;
+        impl Renderer for CountRenderer {
+            fn name(&self) -> &'static str {
+                "count"
+            }
+
+            fn render(&self, input: &RenderInput) -> Result<String> {
+                Ok(input.results.map_or(0, <[_]>::len).to_string())
+            }
+        }
+
+        let mut registry = RendererRegistry::with_builtins();
+        registry.register(Box::new(CountRenderer));
+        assert_eq!(registry.render("count", &RenderInput::results(&sample_results()))?, "1");
+        assert_eq!(registry.names().len(), 6);
+
+        Ok(())
+    }
+}
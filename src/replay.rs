@@ -0,0 +1,122 @@
+use crate::audit::{AuditLog, AuditRecord};
+use crate::error::{DocTreeError, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Canned LLM responses recorded from a real run, for deterministic tests
+/// without a live server. Record a fixture by running with
+/// `DOCTREEAI_AUDIT_LOG=true`, then point `load` at the audit directory or at
+/// a JSON array of its records.
+///
+/// Prompts are matched exactly, ignoring the model, so a fixture keeps
+/// working when tests use a different model name than the recording did.
+/// Recorded errors are replayed as errors.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayFixture {
+    responses: Arc<HashMap<String, std::result::Result<String, String>>>,
+}
+
+impl ReplayFixture {
+    /// Later records win when the same prompt was sent more than once.
+    pub fn from_records(records: impl IntoIterator<Item = AuditRecord>) -> Self {
+        let responses = records
+            .into_iter()
+            .map(|record| {
+                let outcome = match (record.response, record.error) {
+                    (Some(response), _) => Ok(response),
+                    (None, error) => Err(error.unwrap_or_default()),
+                };
+                (record.prompt, outcome)
+            })
+            .collect();
+        Self {
+            responses: Arc::new(responses),
+        }
+    }
+
+    /// Reads an audit directory (`<cache>/audit`) or a JSON file holding an
+    /// array of audit records.
+    pub fn load(path: &Path) -> Result<Self> {
+        if path.is_dir() {
+            return Ok(Self::from_records(AuditLog::read_records(path)?));
+        }
+
+        let content = fs::read_to_string(path)
+            .map_err(|e| DocTreeError::config(format!("Failed to read replay fixture {}: {e}", path.display())))?;
+        let records: Vec<AuditRecord> = serde_json::from_str(&content)
+            .map_err(|e| DocTreeError::config(format!("Invalid replay fixture {}: {e}", path.display())))?;
+        Ok(Self::from_records(records))
+    }
+
+    pub fn len(&self) -> usize {
+        self.responses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.responses.is_empty()
+    }
+
+    pub(crate) fn complete(&self, prompt: &str) -> Result<String> {
+        match self.responses.get(prompt) {
+            Some(Ok(response)) => Ok(response.clone()),
+            Some(Err(error)) => Err(DocTreeError::summarizer(format!("Recorded failure: {error}"))),
+            None => Err(DocTreeError::summarizer(format!(
+                "No recorded response for prompt: {}",
+                prompt.lines().next().unwrap_or_default()
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::Redactor;
+    use crate::config::Config;
+    use crate::llm::LanguageModelClient;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_replay_serves_recorded_responses() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let config = Config::default();
+
+        // Record a run through the audit log, as a real run would
+        let provider = crate::testing::ScriptedProvider::new()
+            .respond("File: main.rs", "Starts the CLI.")
+            .respond("'src' directory", "Holds the CLI sources.");
+        let mut recording = provider.client(&config);
+        let audit_log = AuditLog::new(temp_dir.path(), Redactor::default());
+        recording.set_audit_log(audit_log.clone());
+        recording.generate_file_summary(Path::new("src/main.rs"), "fn main() {}").await?;
+        recording
+            .generate_directory_summary("src", &["Starts the CLI.".to_string()])
+            .await?;
+        assert!(recording.generate_file_summary(Path::new("lib.rs"), "").await.is_err());
+
+        let fixture = ReplayFixture::load(audit_log.dir())?;
+        assert_eq!(fixture.len(), 3);
+        let client = LanguageModelClient::replay(&config, fixture);
+
+        let summary = client.generate_file_summary(Path::new("src/main.rs"), "fn main() {}").await?;
+        assert_eq!(summary, "Starts the CLI.");
+        let directory = client
+            .generate_directory_summary("src", &["Starts the CLI.".to_string()])
+            .await?;
+        assert_eq!(directory, "Holds the CLI sources.");
+
+        // Recorded failures fail again; unrecorded prompts are an error too
+        assert!(client.generate_file_summary(Path::new("lib.rs"), "").await.is_err());
+        let missing = client.generate_file_summary(Path::new("src/main.rs"), "fn main() { run() }").await;
+        assert!(missing.is_err_and(|e| e.to_string().contains("No recorded response")));
+
+        // A fixture file holds the same records as a JSON array
+        let fixture_path = temp_dir.path().join("fixture.json");
+        fs::write(&fixture_path, serde_json::to_string(&audit_log.records()?).unwrap())?;
+        assert_eq!(ReplayFixture::load(&fixture_path)?.len(), 3);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+use crate::error::{DocTreeError, Result};
+use serde_json::json;
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+const SEPARATOR: &str = "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━";
+
+/// Where progress messages and reports go. Library code reports through
+/// this instead of printing, so frontends decide how output is presented.
+pub trait Reporter: Send + Sync {
+    fn info(&self, message: &str);
+
+    fn warn(&self, message: &str);
+
+    fn error(&self, message: &str);
+
+    /// A titled block of lines, e.g. the validation digest or cost estimate.
+    fn section(&self, title: &str, lines: &[String]);
+
+    /// Already rendered output (a report, diff, or document), passed through as is.
+    fn output(&self, text: &str);
+}
+
+/// Reporters selectable with `--reporter`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReporterKind {
+    #[default]
+    Console,
+    /// One JSON object per line on stdout.
+    Json,
+    Silent,
+}
+
+impl ReporterKind {
+    pub fn build(self) -> Box<dyn Reporter> {
+        match self {
+            ReporterKind::Console => Box::new(ConsoleReporter),
+            ReporterKind::Json => Box::new(JsonLinesReporter::stdout()),
+            ReporterKind::Silent => Box::new(SilentReporter),
+        }
+    }
+}
+
+impl FromStr for ReporterKind {
+    type Err = DocTreeError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "console" => Ok(ReporterKind::Console),
+            "json" => Ok(ReporterKind::Json),
+            "silent" => Ok(ReporterKind::Silent),
+            other => Err(DocTreeError::config(format!(
+                "Unknown reporter '{other}' (expected console, json, or silent)"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for ReporterKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ReporterKind::Console => "console",
+            ReporterKind::Json => "json",
+            ReporterKind::Silent => "silent",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Human-readable output: messages on stdout, warnings and errors on stderr.
+pub struct ConsoleReporter;
+
+impl Reporter for ConsoleReporter {
+    fn info(&self, message: &str) {
+        println!("{message}");
+    }
+
+    fn warn(&self, message: &str) {
+        eprintln!("{message}");
+    }
+
+    fn error(&self, message: &str) {
+        eprintln!("{message}");
+    }
+
+    fn section(&self, title: &str, lines: &[String]) {
+        if !title.is_empty() {
+            println!("{title}");
+        }
+        println!("{SEPARATOR}");
+        for line in lines {
+            println!("{line}");
+        }
+        println!("{SEPARATOR}");
+    }
+
+    fn output(&self, text: &str) {
+        print!("{text}");
+    }
+}
+
+/// One JSON object per event, e.g. `{"kind":"info","message":"..."}`, for
+/// tools that consume doctreeai's output.
+pub struct JsonLinesReporter {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl JsonLinesReporter {
+    pub fn new(writer: Box<dyn Write + Send>) -> Self {
+        Self { writer: Mutex::new(writer) }
+    }
+
+    pub fn stdout() -> Self {
+        Self::new(Box::new(std::io::stdout()))
+    }
+
+    fn emit(&self, event: serde_json::Value) {
+        if let Ok(mut writer) = self.writer.lock() {
+            let _ = writeln!(writer, "{event}");
+            let _ = writer.flush();
+        }
+    }
+}
+
+impl Reporter for JsonLinesReporter {
+    fn info(&self, message: &str) {
+        self.emit(json!({ "kind": "info", "message": message }));
+    }
+
+    fn warn(&self, message: &str) {
+        self.emit(json!({ "kind": "warn", "message": message }));
+    }
+
+    fn error(&self, message: &str) {
+        self.emit(json!({ "kind": "error", "message": message }));
+    }
+
+    fn section(&self, title: &str, lines: &[String]) {
+        self.emit(json!({ "kind": "section", "title": title, "lines": lines }));
+    }
+
+    fn output(&self, text: &str) {
+        self.emit(json!({ "kind": "output", "text": text }));
+    }
+}
+
+/// Discards everything.
+pub struct SilentReporter;
+
+impl Reporter for SilentReporter {
+    fn info(&self, _message: &str) {}
+
+    fn warn(&self, _message: &str) {}
+
+    fn error(&self, _message: &str) {}
+
+    fn section(&self, _title: &str, _lines: &[String]) {}
+
+    fn output(&self, _text: &str) {}
+}
+
+/// Shared in-memory buffer, so the writer handed to a `JsonLinesReporter`
+/// can still be read afterwards.
+#[derive(Debug, Clone, Default)]
+pub struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    pub fn contents(&self) -> String {
+        self.0
+            .lock()
+            .map(|buffer| String::from_utf8_lossy(&buffer).into_owned())
+            .unwrap_or_default()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .lock()
+            .map_err(|_| std::io::Error::other("buffer lock poisoned"))?
+            .write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_lines_reporter() -> Result<()> {
+        let buffer = SharedBuffer::default();
+        let reporter = JsonLinesReporter::new(Box::new(buffer.clone()));
+        reporter.info("Scanning");
+        reporter.section("Estimate", &["Files: 3".to_string()]);
+
+        let events: Vec<serde_json::Value> = buffer
+            .contents()
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<std::result::Result<_, _>>()?;
+        assert_eq!(events[0], json!({ "kind": "info", "message": "Scanning" }));
+        assert_eq!(events[1]["lines"][0], "Files: 3");
+
+        assert_eq!("JSON".parse::<ReporterKind>()?, ReporterKind::Json);
+        assert!("xml".parse::<ReporterKind>().is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,178 @@
+use crate::error::{DocTreeError, Result};
+use crate::format::current_timestamp;
+use crate::hasher::FileHasher;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Subdirectory of the cache directory holding cached responses.
+const RESPONSES_DIR: &str = "responses";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedResponse {
+    model: String,
+    /// Request parameters other than the prompt, e.g. `max_tokens=1024`.
+    #[serde(default)]
+    params: String,
+    response: String,
+    timestamp: u64,
+}
+
+/// Raw LLM completions keyed by a hash of model, request parameters, and
+/// prompt, so a retried run, a regenerated README, or re-validating
+/// unchanged lines reuses identical earlier responses until they are older
+/// than the TTL.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    ttl_secs: u64,
+    read_only: bool,
+    refresh: bool,
+}
+
+impl ResponseCache {
+    pub fn new(cache_dir: &Path, ttl_secs: u64) -> Self {
+        Self {
+            dir: cache_dir.join(RESPONSES_DIR),
+            ttl_secs,
+            read_only: false,
+            refresh: false,
+        }
+    }
+
+    /// Serves cached responses but never stores new ones.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    /// Ignores cached responses but still stores new ones, e.g. for `--force`.
+    pub fn set_refresh(&mut self, refresh: bool) {
+        self.refresh = refresh;
+    }
+
+    /// Deletes responses older than the TTL, which would otherwise pile up
+    /// as prompts change. Returns how many were deleted.
+    pub fn prune_expired(&self) -> Result<usize> {
+        if self.read_only {
+            return Ok(0);
+        }
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Ok(0);
+        };
+
+        let cutoff = SystemTime::now() - Duration::from_secs(self.ttl_secs);
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let expired = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified < cutoff);
+            if expired {
+                fs::remove_file(entry.path())
+                    .map_err(|e| DocTreeError::cache(format!("Failed to remove cached response: {e}")))?;
+                removed += 1;
+            }
+        }
+
+        if removed > 0 {
+            tracing::debug!("Removed {removed} expired cached responses");
+        }
+        Ok(removed)
+    }
+
+    fn entry_path(&self, model: &str, params: &str, prompt: &str) -> PathBuf {
+        let key = FileHasher::compute_content_hash(&format!("{model}\n{params}\n{prompt}"));
+        self.dir.join(format!("{key}.json"))
+    }
+
+    pub fn get(&self, model: &str, params: &str, prompt: &str) -> Option<String> {
+        if self.refresh {
+            return None;
+        }
+        let content = fs::read_to_string(self.entry_path(model, params, prompt)).ok()?;
+        let entry: CachedResponse = serde_json::from_str(&content).ok()?;
+
+        if entry.model != model
+            || entry.params != params
+            || current_timestamp().saturating_sub(entry.timestamp) > self.ttl_secs
+        {
+            return None;
+        }
+
+        Some(entry.response)
+    }
+
+    pub fn put(&self, model: &str, params: &str, prompt: &str, response: &str) -> Result<()> {
+        if self.read_only {
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| DocTreeError::cache(format!("Failed to create response cache directory: {e}")))?;
+
+        let entry = CachedResponse {
+            model: model.to_string(),
+            params: params.to_string(),
+            response: response.to_string(),
+            timestamp: current_timestamp(),
+        };
+        let content = serde_json::to_string_pretty(&entry)
+            .map_err(|e| DocTreeError::cache(format!("Failed to serialize cached response: {e}")))?;
+
+        // Written to a temporary file first so concurrent requests never
+        // read a half-written response
+        let path = self.entry_path(model, params, prompt);
+        tempfile::NamedTempFile::new_in(&self.dir)
+            .and_then(|mut temp_file| {
+                temp_file.write_all(content.as_bytes())?;
+                temp_file.persist(&path).map_err(|e| e.error)?;
+                Ok(())
+            })
+            .map_err(|e| DocTreeError::cache(format!("Failed to write cached response: {e}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_response_cache_round_trip() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let cache = ResponseCache::new(temp_dir.path(), 3600);
+        let params = "max_tokens=1024";
+
+        assert_eq!(cache.get("model-a", params, "prompt"), None);
+        cache.put("model-a", params, "prompt", "answer")?;
+        assert_eq!(cache.get("model-a", params, "prompt"), Some("answer".to_string()));
+        assert_eq!(cache.get("model-b", params, "prompt"), None);
+        assert_eq!(cache.get("model-a", "max_tokens=256", "prompt"), None);
+        assert_eq!(cache.get("model-a", params, "other prompt"), None);
+
+        let mut refresh = ResponseCache::new(temp_dir.path(), 3600);
+        refresh.set_refresh(true);
+        assert_eq!(refresh.get("model-a", params, "prompt"), None);
+
+        let expired = ResponseCache::new(temp_dir.path(), 0);
+        let path = cache.entry_path("model-a", params, "prompt");
+        let mut entry: CachedResponse = serde_json::from_str(&fs::read_to_string(&path)?).unwrap();
+        entry.timestamp -= 10;
+        fs::write(&path, serde_json::to_string(&entry).unwrap())?;
+        assert_eq!(expired.get("model-a", params, "prompt"), None);
+        let written = SystemTime::now() - Duration::from_secs(10);
+        fs::File::options().write(true).open(&path)?.set_modified(written)?;
+        assert_eq!(cache.prune_expired()?, 0);
+        assert_eq!(expired.prune_expired()?, 1);
+        assert!(!path.exists());
+
+        let mut read_only = ResponseCache::new(temp_dir.path(), 3600);
+        read_only.set_read_only(true);
+        read_only.put("model-a", params, "new prompt", "answer")?;
+        assert_eq!(cache.get("model-a", params, "new prompt"), None);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,287 @@
+use crate::config::Config;
+use crate::doctree::RunOptions;
+use crate::error::{DocTreeError, Result};
+use crate::format::current_timestamp;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Progress of the latest run, kept in the cache directory so `doctreeai
+/// resume` can continue an interrupted or partially failed run.
+pub const RUN_STATE_FILE: &str = "run_state.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    InProgress,
+    Completed,
+}
+
+/// The options a run was started with, so it resumes the same way.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedOptions {
+    pub force: bool,
+    pub scope: Option<PathBuf>,
+    pub strategy: String,
+    pub skip_validation: bool,
+    pub refresh_older_than: Option<u64>,
+    #[serde(default)]
+    pub min_confidence: f32,
+    #[serde(default)]
+    pub draft_sections: bool,
+    #[serde(default)]
+    pub batch: bool,
+    /// Settings the run was configured with, whether from a flag, the
+    /// environment, or the project file; see `restore_config`.
+    #[serde(default)]
+    pub small_model: bool,
+    #[serde(default)]
+    pub offline: bool,
+    #[serde(default)]
+    pub max_tokens_per_run: Option<u64>,
+}
+
+impl RecordedOptions {
+    /// Configures a resumed run the way the recorded one was, so a run
+    /// stopped by its token budget keeps the budget and an offline run stays
+    /// offline.
+    pub fn restore_config(&self, config: &mut Config) {
+        config.small_model = self.small_model;
+        config.offline = self.offline;
+        config.max_tokens_per_run = self.max_tokens_per_run;
+    }
+}
+
+/// Per-run progress. Paths are relative to the project root and include
+/// directories when the strategy summarizes them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunState {
+    pub started_at: u64,
+    pub updated_at: u64,
+    pub status: RunStatus,
+    pub options: RecordedOptions,
+    pub completed: BTreeSet<PathBuf>,
+    /// Paths whose summary fell back after an LLM error, with the error. A
+    /// file keeps an extractive summary, a directory a list of its children.
+    pub failed: BTreeMap<PathBuf, String>,
+    pub pending: BTreeSet<PathBuf>,
+    /// Files whose content had secrets replaced before it was sent to the
+    /// LLM, with what was replaced (e.g. "AWS access key on line 3").
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub redacted: BTreeMap<PathBuf, Vec<String>>,
+}
+
+impl RunState {
+    pub fn new(options: &RunOptions, config: &Config) -> Self {
+        let now = current_timestamp();
+        Self {
+            started_at: now,
+            updated_at: now,
+            status: RunStatus::InProgress,
+            options: RecordedOptions {
+                force: options.force,
+                scope: options.scope.clone(),
+                strategy: options.strategy.to_string(),
+                skip_validation: options.skip_validation,
+                refresh_older_than: options.refresh_older_than,
+                min_confidence: options.min_confidence,
+                draft_sections: options.draft_sections,
+                batch: options.batch,
+                small_model: config.small_model,
+                offline: config.offline,
+                max_tokens_per_run: config.max_tokens_per_run,
+            },
+            completed: BTreeSet::new(),
+            failed: BTreeMap::new(),
+            pending: BTreeSet::new(),
+            redacted: BTreeMap::new(),
+        }
+    }
+
+    pub fn load(cache_dir: &Path) -> Result<Option<Self>> {
+        let path = cache_dir.join(RUN_STATE_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| DocTreeError::cache(format!("Failed to read {}: {e}", path.display())))?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| DocTreeError::cache(format!("Failed to parse {}: {e}", path.display())))
+    }
+
+    pub fn save(&self, cache_dir: &Path) -> Result<()> {
+        fs::create_dir_all(cache_dir)
+            .map_err(|e| DocTreeError::cache(format!("Failed to create cache directory: {e}")))?;
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| DocTreeError::cache(format!("Failed to serialize run state: {e}")))?;
+
+        let path = cache_dir.join(RUN_STATE_FILE);
+        let temp_path = cache_dir.join(format!("{RUN_STATE_FILE}.tmp"));
+        fs::write(&temp_path, content)
+            .and_then(|_| fs::rename(&temp_path, &path))
+            .map_err(|e| DocTreeError::cache(format!("Failed to write run state: {e}")))
+    }
+
+    /// Whether the run stopped early or left files with fallback summaries.
+    pub fn is_resumable(&self) -> bool {
+        self.status != RunStatus::Completed || !self.failed.is_empty()
+    }
+
+    /// Options that continue this run: whatever it completed is kept, even
+    /// when it was a `--force` run. The settings it was configured with are
+    /// restored by `RecordedOptions::restore_config`.
+    pub fn resume_options(&self) -> Result<RunOptions> {
+        Ok(RunOptions {
+            force: self.options.force,
+            scope: self.options.scope.clone(),
+            strategy: self.options.strategy.parse()?,
+            skip_validation: self.options.skip_validation,
+            refresh_older_than: self.options.refresh_older_than,
+            min_confidence: self.options.min_confidence,
+            draft_sections: self.options.draft_sections,
+            batch: self.options.batch,
+            resume: true,
+            ..Default::default()
+        })
+    }
+
+    /// Keeps the failures of an earlier run, so files outside this run's
+    /// scope are still listed until they are retried.
+    pub fn carry_failures(&mut self, previous: &RunState) {
+        self.failed = previous.failed.clone();
+    }
+
+    /// Starts (or restarts) the summarization pass: every file not yet
+    /// completed is pending, and earlier failures among them get another try.
+    pub fn begin(&mut self, files: impl IntoIterator<Item = PathBuf>) {
+        self.status = RunStatus::InProgress;
+        self.pending = files.into_iter().filter(|path| !self.completed.contains(path)).collect();
+        let pending = &self.pending;
+        self.failed.retain(|path, _| !pending.contains(path));
+        self.updated_at = current_timestamp();
+    }
+
+    /// Adds a file found after the pass started, as `begin` would have.
+    pub fn add_pending(&mut self, path: PathBuf) {
+        if !self.completed.contains(&path) {
+            self.failed.remove(&path);
+            self.pending.insert(path);
+            self.updated_at = current_timestamp();
+        }
+    }
+
+    pub fn record_completed(&mut self, path: &Path) {
+        self.pending.remove(path);
+        if !self.failed.contains_key(path) {
+            self.completed.insert(path.to_path_buf());
+        }
+        self.updated_at = current_timestamp();
+    }
+
+    pub fn record_failed(&mut self, path: &Path, error: impl Into<String>) {
+        self.pending.remove(path);
+        self.completed.remove(path);
+        self.failed.insert(path.to_path_buf(), error.into());
+        self.updated_at = current_timestamp();
+    }
+
+    pub fn record_redacted(&mut self, path: &Path, findings: Vec<String>) {
+        self.redacted.insert(path.to_path_buf(), findings);
+        self.updated_at = current_timestamp();
+    }
+
+    pub fn complete(&mut self) {
+        self.status = RunStatus::Completed;
+        self.updated_at = current_timestamp();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CacheManager;
+    use crate::config::Config;
+    use crate::llm::LanguageModelClient;
+    use crate::progress::ProgressEvent;
+    use crate::strategy::StrategyKind;
+    use crate::summarizer::HierarchicalSummarizer;
+    use std::sync::{mpsc, Arc};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_run_state_round_trip_and_resume() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let options = RunOptions {
+            force: true,
+            strategy: StrategyKind::Flat,
+            ..Default::default()
+        };
+        let mut state = RunState::new(&options, &Config::default());
+        state.begin([PathBuf::from("a.rs"), PathBuf::from("b.rs"), PathBuf::from("c.rs")]);
+        state.record_completed(Path::new("a.rs"));
+        state.record_failed(Path::new("b.rs"), "connection refused");
+        state.save(temp_dir.path())?;
+
+        let mut loaded = RunState::load(temp_dir.path())?.unwrap();
+        assert_eq!(loaded, state);
+        assert!(loaded.is_resumable());
+        assert_eq!(loaded.pending, BTreeSet::from([PathBuf::from("c.rs")]));
+
+        let resume = loaded.resume_options()?;
+        assert!(resume.force && resume.resume);
+        assert_eq!(resume.strategy, StrategyKind::Flat);
+
+        // Resuming retries the failure and the pending file, not the completed one
+        loaded.begin([PathBuf::from("a.rs"), PathBuf::from("b.rs"), PathBuf::from("c.rs")]);
+        assert!(loaded.failed.is_empty());
+        assert_eq!(loaded.pending.len(), 2);
+
+        loaded.record_completed(Path::new("b.rs"));
+        loaded.record_completed(Path::new("c.rs"));
+        loaded.complete();
+        assert!(!loaded.is_resumable());
+
+        assert!(RunState::load(&temp_dir.path().join("missing"))?.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_resumed_force_run_keeps_completed_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        fs::write(base_path.join("main.rs"), "fn main() {}")?;
+        fs::write(base_path.join("lib.rs"), "pub fn lib() {}")?;
+
+        let summarizer = |force: bool| -> Result<HierarchicalSummarizer> {
+            let cache_manager = CacheManager::new(base_path, ".test_cache")?;
+            Ok(HierarchicalSummarizer::new(LanguageModelClient::mock(&Config::default()), cache_manager, force))
+        };
+        let cache_dir = base_path.join(".test_cache");
+
+        let mut first = summarizer(false)?;
+        first.set_run_state(Some(RunState::new(&RunOptions::default(), &Config::default())));
+        first.generate_project_summary(base_path).await?;
+        let mut state = RunState::load(&cache_dir)?.unwrap();
+        assert!(state.pending.is_empty());
+        assert!(state.completed.contains(Path::new("main.rs")));
+
+        // Pretend the run was interrupted before lib.rs was summarized
+        state.completed.remove(Path::new("lib.rs"));
+        let (sender, receiver) = mpsc::channel();
+        let mut resumed = summarizer(true)?;
+        resumed.set_run_state(Some(state));
+        resumed.set_progress(Some(Arc::new(sender)));
+        resumed.generate_project_summary(base_path).await?;
+        drop(resumed);
+
+        let events: Vec<ProgressEvent> = receiver.iter().collect();
+        assert!(events.contains(&ProgressEvent::CacheHit { path: PathBuf::from("main.rs") }));
+        assert!(events.contains(&ProgressEvent::FileStart { path: PathBuf::from("lib.rs") }));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,407 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use syn::{Attribute, Fields, ImplItem, Item, TraitItem, Visibility};
+
+/// Bytes of API listing added to the root summary prompt.
+const MAX_LISTING_BYTES: usize = 8_000;
+
+/// The public API of a Rust library crate: every module reachable through
+/// `pub mod` from the crate root, with its public items.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RustApi {
+    pub crate_name: String,
+    pub modules: Vec<ApiModule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiModule {
+    /// Path from the crate root, e.g. `cache::entry`; empty for the root.
+    pub path: String,
+    /// First paragraph of the module's rustdoc.
+    pub docs: Option<String>,
+    pub items: Vec<ApiItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiItem {
+    pub name: String,
+    /// The declaration without its body, e.g. `pub fn new(path: &Path) -> Self`.
+    pub signature: String,
+    /// First paragraph of the item's rustdoc.
+    pub docs: Option<String>,
+    /// Public fields, enum variants, trait methods, and inherent methods.
+    pub members: Vec<ApiItem>,
+}
+
+impl RustApi {
+    /// Reads the crate's library root (`src/lib.rs`, or `[lib] path`) and
+    /// follows its public modules. None for binary-only crates, files that
+    /// do not parse, and crates without public items.
+    pub fn detect(base_path: &Path) -> Option<Self> {
+        let manifest = fs::read_to_string(base_path.join("Cargo.toml")).ok()?.parse::<toml::Table>().ok()?;
+        let crate_name = manifest.get("package")?.get("name")?.as_str()?.replace('-', "_");
+        let lib_path = manifest
+            .get("lib")
+            .and_then(|lib| lib.get("path")?.as_str())
+            .unwrap_or("src/lib.rs");
+
+        let mut modules = Vec::new();
+        let lib_path = base_path.join(lib_path);
+        let source = fs::read_to_string(&lib_path).ok()?;
+        let file = syn::parse_file(&source).ok()?;
+        let directory = lib_path.parent()?.to_path_buf();
+        collect_module(String::new(), &file.attrs, &file.items, &directory, &mut modules);
+
+        modules.retain(|module| !module.items.is_empty() || module.docs.is_some());
+        (!modules.is_empty()).then_some(Self { crate_name, modules })
+    }
+
+    /// `crate_name::path`, or the crate name for the root module.
+    pub fn module_name(&self, module: &ApiModule) -> String {
+        match module.path.as_str() {
+            "" => self.crate_name.clone(),
+            path => format!("{}::{path}", self.crate_name),
+        }
+    }
+
+    /// The API as indented plain text for an LLM prompt, cut off at
+    /// `MAX_LISTING_BYTES`.
+    pub fn listing(&self) -> String {
+        let mut listing = String::new();
+        for module in &self.modules {
+            let mut lines = vec![with_docs(&format!("mod {}", self.module_name(module)), module.docs.as_deref())];
+            for item in &module.items {
+                lines.push(format!("  {}", with_docs(&item.signature, item.docs.as_deref())));
+                for member in &item.members {
+                    lines.push(format!("    {}", with_docs(&member.signature, member.docs.as_deref())));
+                }
+            }
+            for line in lines {
+                if listing.len() + line.len() + 1 > MAX_LISTING_BYTES {
+                    listing.push_str("...\n");
+                    return listing;
+                }
+                listing.push_str(&line);
+                listing.push('\n');
+            }
+        }
+        listing
+    }
+}
+
+fn with_docs(signature: &str, docs: Option<&str>) -> String {
+    match docs {
+        Some(docs) => format!("{signature} // {docs}"),
+        None => signature.to_string(),
+    }
+}
+
+fn collect_module(path: String, attrs: &[Attribute], items: &[Item], directory: &Path, modules: &mut Vec<ApiModule>) {
+    let mut module = ApiModule { path: path.clone(), docs: docs(attrs), items: Vec::new() };
+    let mut submodules = Vec::new();
+
+    for item in items {
+        match item {
+            Item::Mod(item_mod) if is_public(&item_mod.vis) && !is_hidden(&item_mod.attrs) => {
+                let name = item_mod.ident.to_string();
+                let child_path = if path.is_empty() { name.clone() } else { format!("{path}::{name}") };
+                submodules.push((child_path, item_mod.clone(), directory.join(&name)));
+            }
+            Item::Impl(item_impl) if item_impl.trait_.is_none() && !is_hidden(&item_impl.attrs) => {
+                let syn::Type::Path(self_type) = item_impl.self_ty.as_ref() else {
+                    continue;
+                };
+                let Some(type_name) = self_type.path.segments.last().map(|segment| segment.ident.to_string()) else {
+                    continue;
+                };
+                let methods = item_impl.items.iter().filter_map(|impl_item| match impl_item {
+                    ImplItem::Fn(method) if is_public(&method.vis) && !is_hidden(&method.attrs) => Some(ApiItem {
+                        name: method.sig.ident.to_string(),
+                        signature: function_signature(&method.vis, &method.sig),
+                        docs: docs(&method.attrs),
+                        members: Vec::new(),
+                    }),
+                    _ => None,
+                });
+                match module.items.iter_mut().find(|item| item.name == type_name) {
+                    Some(item) => item.members.extend(methods),
+                    None => {
+                        let members: Vec<ApiItem> = methods.collect();
+                        if !members.is_empty() {
+                            module.items.push(ApiItem {
+                                name: type_name.clone(),
+                                signature: format!("impl {type_name}"),
+                                docs: None,
+                                members,
+                            });
+                        }
+                    }
+                }
+            }
+            item => module.items.extend(api_item(item)),
+        }
+    }
+    modules.push(module);
+
+    for (child_path, item_mod, child_directory) in submodules {
+        if let Some((_, items)) = &item_mod.content {
+            collect_module(child_path, &item_mod.attrs, items, &child_directory, modules);
+            continue;
+        }
+        let Some(file_path) = module_file(&item_mod, directory) else {
+            continue;
+        };
+        let Some(file) = fs::read_to_string(&file_path).ok().and_then(|source| syn::parse_file(&source).ok()) else {
+            tracing::debug!("Could not parse {} for the public API", file_path.display());
+            continue;
+        };
+        // Modules declared in `foo/mod.rs` or `foo.rs` live in `foo/`
+        let mut attrs = item_mod.attrs.clone();
+        attrs.extend(file.attrs);
+        collect_module(child_path, &attrs, &file.items, &child_directory, modules);
+    }
+}
+
+/// `#[path]`, then `name.rs`, then `name/mod.rs`.
+fn module_file(item_mod: &syn::ItemMod, directory: &Path) -> Option<PathBuf> {
+    let explicit = item_mod.attrs.iter().find(|attr| attr.path().is_ident("path")).and_then(|attr| match &attr.meta {
+        syn::Meta::NameValue(syn::MetaNameValue {
+            value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(path), .. }),
+            ..
+        }) => Some(directory.join(path.value())),
+        _ => None,
+    });
+    let name = item_mod.ident.to_string();
+    explicit
+        .into_iter()
+        .chain([directory.join(format!("{name}.rs")), directory.join(&name).join("mod.rs")])
+        .find(|path| path.is_file())
+}
+
+fn api_item(item: &Item) -> Option<ApiItem> {
+    let (attrs, name, signature, members) = match item {
+        Item::Fn(item) if is_public(&item.vis) => {
+            (&item.attrs, item.sig.ident.to_string(), function_signature(&item.vis, &item.sig), Vec::new())
+        }
+        Item::Struct(item) if is_public(&item.vis) => {
+            let members = match &item.fields {
+                Fields::Named(fields) => fields
+                    .named
+                    .iter()
+                    .filter(|field| is_public(&field.vis) && !is_hidden(&field.attrs))
+                    .filter_map(|field| {
+                        let name = field.ident.as_ref()?.to_string();
+                        Some(ApiItem {
+                            signature: format!("pub {name}: {}", unparse_type(&field.ty)),
+                            name,
+                            docs: docs(&field.attrs),
+                            members: Vec::new(),
+                        })
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+            let mut stripped = item.clone();
+            stripped.attrs.clear();
+            stripped.fields = match &item.fields {
+                Fields::Unnamed(_) => item.fields.clone(),
+                _ => Fields::Unit,
+            };
+            (&item.attrs, item.ident.to_string(), unparse_item(Item::Struct(stripped)), members)
+        }
+        Item::Enum(item) if is_public(&item.vis) => {
+            let members = item
+                .variants
+                .iter()
+                .filter(|variant| !is_hidden(&variant.attrs))
+                .map(|variant| {
+                    let shape = match variant.fields {
+                        Fields::Named(_) => " { .. }",
+                        Fields::Unnamed(_) => "(..)",
+                        Fields::Unit => "",
+                    };
+                    ApiItem {
+                        name: variant.ident.to_string(),
+                        signature: format!("{}{shape}", variant.ident),
+                        docs: docs(&variant.attrs),
+                        members: Vec::new(),
+                    }
+                })
+                .collect();
+            let generics = unparse_generics(&item.generics);
+            (&item.attrs, item.ident.to_string(), format!("pub enum {}{generics}", item.ident), members)
+        }
+        Item::Trait(item) if is_public(&item.vis) => {
+            let members = item
+                .items
+                .iter()
+                .filter_map(|trait_item| match trait_item {
+                    TraitItem::Fn(method) if !is_hidden(&method.attrs) => Some(ApiItem {
+                        name: method.sig.ident.to_string(),
+                        signature: function_signature(&Visibility::Inherited, &method.sig),
+                        docs: docs(&method.attrs),
+                        members: Vec::new(),
+                    }),
+                    _ => None,
+                })
+                .collect();
+            let generics = unparse_generics(&item.generics);
+            (&item.attrs, item.ident.to_string(), format!("pub trait {}{generics}", item.ident), members)
+        }
+        Item::Type(item) if is_public(&item.vis) => {
+            let mut stripped = item.clone();
+            stripped.attrs.clear();
+            (&item.attrs, item.ident.to_string(), unparse_item(Item::Type(stripped)), Vec::new())
+        }
+        Item::Const(item) if is_public(&item.vis) => {
+            let signature = format!("pub const {}: {}", item.ident, unparse_type(&item.ty));
+            (&item.attrs, item.ident.to_string(), signature, Vec::new())
+        }
+        Item::Static(item) if is_public(&item.vis) => {
+            let signature = format!("pub static {}: {}", item.ident, unparse_type(&item.ty));
+            (&item.attrs, item.ident.to_string(), signature, Vec::new())
+        }
+        Item::Macro(item) if item.attrs.iter().any(|attr| attr.path().is_ident("macro_export")) => {
+            let name = item.ident.as_ref()?.to_string();
+            (&item.attrs, name.clone(), format!("macro_rules! {name}"), Vec::new())
+        }
+        _ => return None,
+    };
+    if is_hidden(attrs) {
+        return None;
+    }
+    Some(ApiItem { name, signature, docs: docs(attrs), members })
+}
+
+fn is_public(visibility: &Visibility) -> bool {
+    matches!(visibility, Visibility::Public(_))
+}
+
+/// `#[doc(hidden)]` and `#[cfg(test)]` items are not part of the API.
+fn is_hidden(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        let tokens = attr.meta.require_list().map(|list| list.tokens.to_string()).unwrap_or_default();
+        (attr.path().is_ident("doc") && tokens == "hidden") || (attr.path().is_ident("cfg") && tokens == "test")
+    })
+}
+
+/// The first paragraph of the rustdoc, on one line.
+fn docs(attrs: &[Attribute]) -> Option<String> {
+    let mut lines = Vec::new();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("doc")) {
+        let syn::Meta::NameValue(syn::MetaNameValue {
+            value: syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(text), .. }),
+            ..
+        }) = &attr.meta
+        else {
+            continue;
+        };
+        let text = text.value();
+        if text.trim().is_empty() {
+            if lines.is_empty() {
+                continue;
+            }
+            break;
+        }
+        lines.push(text.trim().to_string());
+    }
+    (!lines.is_empty()).then(|| lines.join(" "))
+}
+
+fn function_signature(visibility: &Visibility, signature: &syn::Signature) -> String {
+    let item = syn::ItemFn {
+        attrs: Vec::new(),
+        vis: visibility.clone(),
+        sig: signature.clone(),
+        block: Box::new(syn::Block { brace_token: Default::default(), stmts: Vec::new() }),
+    };
+    unparse_item(Item::Fn(item)).trim_end_matches("{}").trim_end().to_string()
+}
+
+/// An item as one line of Rust, the way rustfmt would print it.
+fn unparse_item(item: Item) -> String {
+    let file = syn::File { shebang: None, attrs: Vec::new(), items: vec![item] };
+    prettyplease::unparse(&file)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace("( ", "(")
+        .replace(", )", ")")
+        .replace("< ", "<")
+        .replace(", >", ">")
+        .trim_end_matches(';')
+        .to_string()
+}
+
+fn unparse_type(ty: &syn::Type) -> String {
+    let alias: syn::ItemType = syn::parse_quote!(type T = #ty;);
+    let unparsed = unparse_item(Item::Type(alias));
+    unparsed.trim_start_matches("type T = ").to_string()
+}
+
+fn unparse_generics(generics: &syn::Generics) -> String {
+    if generics.params.is_empty() {
+        return String::new();
+    }
+    let item: syn::ItemStruct = syn::parse_quote!(struct T #generics;);
+    let unparsed = unparse_item(Item::Struct(item));
+    let generics = unparsed.trim_start_matches("struct T");
+    // A where clause of the struct would follow its generics
+    generics.split(" where ").next().unwrap_or(generics).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_public_api() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        fs::create_dir_all(base_path.join("src/store"))?;
+        fs::write(base_path.join("Cargo.toml"), "[package]\nname = \"my-store\"\n")?;
+        fs::write(
+            base_path.join("src/lib.rs"),
+            "//! Stores things.\n\npub mod store;\nmod internal;\n\n/// Opens a store.\npub fn open(path: &std::path::Path) -> Result<store::Store, String> { todo!() }\nfn private() {}\n",
+        )?;
+        fs::write(base_path.join("src/internal.rs"), "pub fn hidden() {}\n")?;
+        fs::write(
+            base_path.join("src/store/mod.rs"),
+            "/// A key-value store.\n///\n/// Details.\n#[derive(Debug)]\npub struct Store {\n    /// Entries kept.\n    pub capacity: usize,\n    items: Vec<String>,\n}\n\nimpl Store {\n    /// Adds an item.\n    pub fn insert(&mut self, item: impl Into<String>) {}\n    fn grow(&mut self) {}\n}\n\npub enum Mode { Read, Write(bool) }\n\n#[cfg(test)]\nmod tests {}\n",
+        )?;
+
+        let api = RustApi::detect(base_path).unwrap();
+        assert_eq!(api.crate_name, "my_store");
+        let paths: Vec<&str> = api.modules.iter().map(|module| module.path.as_str()).collect();
+        assert_eq!(paths, vec!["", "store"]);
+        assert_eq!(api.modules[0].docs.as_deref(), Some("Stores things."));
+        assert_eq!(api.modules[0].items[0].signature, "pub fn open(path: &std::path::Path) -> Result<store::Store, String>");
+
+        let store = &api.modules[1].items[0];
+        assert_eq!(store.signature, "pub struct Store");
+        assert_eq!(store.docs.as_deref(), Some("A key-value store."));
+        let members: Vec<&str> = store.members.iter().map(|member| member.signature.as_str()).collect();
+        assert_eq!(members, vec!["pub capacity: usize", "pub fn insert(&mut self, item: impl Into<String>)"]);
+        assert_eq!(api.modules[1].items[1].members[1].signature, "Write(..)");
+        Ok(())
+    }
+
+    #[test]
+    fn test_listing() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        fs::create_dir(base_path.join("src"))?;
+        fs::write(base_path.join("Cargo.toml"), "[package]\nname = \"app\"\n")?;
+        assert!(RustApi::detect(base_path).is_none());
+
+        fs::write(base_path.join("src/lib.rs"), "/// Limits.\npub trait Limit<T> { fn check(&self, value: T) -> bool; }\n")?;
+        let api = RustApi::detect(base_path).unwrap();
+        assert_eq!(
+            api.listing(),
+            "mod app\n  pub trait Limit<T> // Limits.\n    fn check(&self, value: T) -> bool\n"
+        );
+        Ok(())
+    }
+}
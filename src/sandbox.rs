@@ -0,0 +1,148 @@
+use crate::config::Config;
+use crate::error::{DocTreeError, Result};
+use std::env;
+use std::path::Path;
+use std::process::Command;
+use std::str::FromStr;
+
+/// Mount point of the project inside the sandbox container.
+pub const CONTAINER_WORKSPACE: &str = "/workspace";
+
+/// Environment variable prefixes forwarded into the sandbox by name, so
+/// secrets never appear on the docker command line.
+const FORWARDED_ENV_PREFIXES: [&str; 2] = ["OPENAI_", "DOCTREEAI_"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SandboxKind {
+    Docker,
+}
+
+impl FromStr for SandboxKind {
+    type Err = DocTreeError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "docker" => Ok(SandboxKind::Docker),
+            other => Err(DocTreeError::config(format!(
+                "Unsupported sandbox '{other}' (expected docker)"
+            ))),
+        }
+    }
+}
+
+/// Runs doctreeai inside a container that sees the project through a
+/// read-only bind mount and can only reach the network it is attached to.
+pub struct DockerSandbox {
+    image: String,
+    network: Option<String>,
+}
+
+impl DockerSandbox {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            image: config.sandbox_image.clone(),
+            network: config.sandbox_network.clone(),
+        }
+    }
+
+    /// Builds the `docker run` arguments for running doctreeai with
+    /// `doctreeai_args` against the mounted project.
+    pub fn command_args(&self, project_path: &Path, doctreeai_args: &[String]) -> Result<Vec<String>> {
+        let network = self.network.as_deref().ok_or_else(|| {
+            DocTreeError::config(
+                "DOCTREEAI_SANDBOX_NETWORK must name a Docker network that only reaches the LLM endpoint \
+                 (e.g. `docker network create --internal doctreeai-llm` with the LLM server attached)",
+            )
+        })?;
+
+        let project_path = project_path.canonicalize().map_err(|e| {
+            DocTreeError::path(format!("Failed to resolve {}: {e}", project_path.display()))
+        })?;
+
+        let mut args = vec![
+            "run".to_string(),
+            "--rm".to_string(),
+            "--read-only".to_string(),
+            "--cap-drop=ALL".to_string(),
+            "--security-opt=no-new-privileges".to_string(),
+            format!("--network={network}"),
+            format!(
+                "--volume={}:{CONTAINER_WORKSPACE}:ro",
+                project_path.display()
+            ),
+            format!("--workdir={CONTAINER_WORKSPACE}"),
+        ];
+
+        let mut forwarded: Vec<String> = env::vars()
+            .map(|(name, _)| name)
+            .filter(|name| FORWARDED_ENV_PREFIXES.iter().any(|prefix| name.starts_with(prefix)))
+            .collect();
+        forwarded.sort();
+        for name in forwarded {
+            args.push("--env".to_string());
+            args.push(name);
+        }
+
+        args.push(self.image.clone());
+        args.push("doctreeai".to_string());
+        args.extend(doctreeai_args.iter().cloned());
+
+        Ok(args)
+    }
+
+    pub fn run(&self, project_path: &Path, doctreeai_args: &[String]) -> Result<()> {
+        let args = self.command_args(project_path, doctreeai_args)?;
+        tracing::debug!("docker {}", args.join(" "));
+
+        let status = Command::new("docker").args(&args).status().map_err(|e| {
+            DocTreeError::config(format!("Failed to start docker: {e}"))
+        })?;
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(DocTreeError::config(format!("Sandboxed run failed: {status}")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_docker_args_mount_read_only() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let sandbox = DockerSandbox {
+            image: "doctreeai:latest".to_string(),
+            network: Some("doctreeai-llm".to_string()),
+        };
+
+        let args = sandbox.command_args(temp_dir.path(), &["--no-write".to_string(), "run".to_string()])?;
+        let mount = format!(
+            "--volume={}:/workspace:ro",
+            temp_dir.path().canonicalize()?.display()
+        );
+
+        assert!(args.contains(&mount));
+        assert!(args.contains(&"--network=doctreeai-llm".to_string()));
+        assert!(args.contains(&"--read-only".to_string()));
+        assert_eq!(args[args.len() - 3..], ["doctreeai", "--no-write", "run"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_network_is_required() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let sandbox = DockerSandbox {
+            image: "doctreeai:latest".to_string(),
+            network: None,
+        };
+
+        assert!(sandbox.command_args(temp_dir.path(), &[]).is_err());
+        assert_eq!("docker".parse::<SandboxKind>()?, SandboxKind::Docker);
+        Ok(())
+    }
+}
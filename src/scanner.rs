@@ -1,7 +1,11 @@
 use crate::error::{DocTreeError, Result};
+use crate::limits::ScanLimits;
+use crate::submodules::{read_gitmodules, Submodule, SubmoduleMode, SubmodulePolicy};
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use ignore::WalkBuilder;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
+use std::process::Command;
 
 #[derive(Debug, Clone)]
 pub struct FileNode {
@@ -10,6 +14,8 @@ pub struct FileNode {
     pub children: Vec<FileNode>,
     pub content_hash: Option<String>,
     pub summary: Option<String>,
+    /// Set for a submodule scanned as an opaque node; its contents are not scanned.
+    pub submodule: Option<Submodule>,
 }
 
 impl FileNode {
@@ -20,6 +26,7 @@ impl FileNode {
             children: Vec::new(),
             content_hash: None,
             summary: None,
+            submodule: None,
         }
     }
 
@@ -27,93 +34,393 @@ impl FileNode {
         self.children.push(child);
     }
 
+    /// Directories first, then by path.
+    pub fn sort_children(&mut self) {
+        self.children.sort_by(|a, b| {
+            match (a.is_directory, b.is_directory) {
+                (true, false) => std::cmp::Ordering::Less,
+                (false, true) => std::cmp::Ordering::Greater,
+                _ => a.path.cmp(&b.path),
+            }
+        });
+    }
+
     pub fn get_relative_path(&self, base: &Path) -> Result<PathBuf> {
         pathdiff::diff_paths(&self.path, base)
             .ok_or_else(|| DocTreeError::path("Failed to compute relative path"))
     }
 
     pub fn is_source_code_file(&self) -> bool {
-        if self.is_directory {
-            return false;
+        self.is_source_file(&SourceFileMatcher::default())
+    }
+
+    pub fn is_source_file(&self, matcher: &SourceFileMatcher) -> bool {
+        !self.is_directory && matcher.matches(&self.path)
+    }
+}
+
+const DEFAULT_EXTENSIONS: &[&str] = &[
+    "rs", "py", "js", "ts", "tsx", "jsx", "go", "java", "cpp", "c", "h", "hpp",
+    "cs", "php", "rb", "swift", "kt", "scala", "clj", "hs", "elm", "dart",
+    "r", "jl", "ml", "fs", "pl", "sh", "bash", "zsh", "fish", "ps1",
+    "html", "css", "scss", "sass", "less", "vue", "svelte", "xml", "yaml", "yml",
+    "json", "toml", "ini", "cfg", "conf", "dockerfile", "makefile", "cmake",
+    "sql", "graphql", "proto", "thrift", "avro", "md", "mdx", "tex", "rst",
+    "zig", "nix", "lua", "ex", "exs", "erl", "tf", "hcl", "bzl", "gradle", "groovy", "mk",
+];
+
+/// Extension-less files that are still worth summarizing (compared case-insensitively).
+const DEFAULT_FILENAMES: &[&str] = &[
+    "dockerfile", "containerfile", "makefile", "gnumakefile", "justfile", "cmakelists.txt",
+    "rakefile", "gemfile", "vagrantfile", "jenkinsfile", "procfile", "tiltfile", "build.bazel",
+];
+
+/// Decides which files are summarized: the built-in extensions and special
+/// filenames plus any configured extras.
+#[derive(Debug, Clone)]
+pub struct SourceFileMatcher {
+    extra_extensions: HashSet<String>,
+    extra_filenames: GlobSet,
+}
+
+impl Default for SourceFileMatcher {
+    fn default() -> Self {
+        Self {
+            extra_extensions: HashSet::new(),
+            extra_filenames: GlobSet::empty(),
         }
+    }
+}
 
-        let extension = self.path.extension()
+impl SourceFileMatcher {
+    /// `extra_extensions` may be given with or without a leading dot;
+    /// `extra_filenames` are globs matched against the file name, e.g. `*.gotmpl`.
+    pub fn new(extra_extensions: &[String], extra_filenames: &[String]) -> Result<Self> {
+        let extra_extensions = extra_extensions
+            .iter()
+            .map(|ext| ext.trim().trim_start_matches('.').to_lowercase())
+            .filter(|ext| !ext.is_empty())
+            .collect();
+
+        Ok(Self {
+            extra_extensions,
+            extra_filenames: build_globs(extra_filenames, "glob")?,
+        })
+    }
+
+    pub fn matches(&self, path: &Path) -> bool {
+        let extension = path
+            .extension()
             .and_then(|ext| ext.to_str())
-            .unwrap_or("");
+            .unwrap_or("")
+            .to_lowercase();
+
+        if DEFAULT_EXTENSIONS.contains(&extension.as_str()) || self.extra_extensions.contains(&extension) {
+            return true;
+        }
+
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            return false;
+        };
 
-        matches!(extension.to_lowercase().as_str(),
-            "rs" | "py" | "js" | "ts" | "tsx" | "jsx" | "go" | "java" | "cpp" | "c" | "h" | "hpp" |
-            "cs" | "php" | "rb" | "swift" | "kt" | "scala" | "clj" | "hs" | "elm" | "dart" |
-            "r" | "jl" | "ml" | "fs" | "pl" | "sh" | "bash" | "zsh" | "fish" | "ps1" |
-            "html" | "css" | "scss" | "sass" | "less" | "vue" | "svelte" | "xml" | "yaml" | "yml" |
-            "json" | "toml" | "ini" | "cfg" | "conf" | "dockerfile" | "makefile" | "cmake" |
-            "sql" | "graphql" | "proto" | "thrift" | "avro" | "md" | "mdx" | "tex" | "rst"
-        )
+        DEFAULT_FILENAMES.contains(&file_name.to_lowercase().as_str())
+            || self.extra_filenames.is_match(file_name)
+    }
+}
+
+/// User-configured include/exclude globs, matched against paths relative to
+/// the scan root. Excludes win; an empty include list includes every file.
+#[derive(Debug, Clone)]
+pub struct ScanFilter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl Default for ScanFilter {
+    fn default() -> Self {
+        Self {
+            include: None,
+            exclude: GlobSet::empty(),
+        }
     }
 }
 
+impl ScanFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self> {
+        let include = if include.is_empty() {
+            None
+        } else {
+            Some(build_globs(include, "glob")?)
+        };
+
+        Ok(Self {
+            include,
+            exclude: build_globs(exclude, "glob")?,
+        })
+    }
+
+    /// Directories are only pruned by excludes, so `src/**` still descends into `src`.
+    pub fn allows(&self, relative_path: &Path, is_directory: bool) -> bool {
+        if self.exclude.is_match(relative_path) {
+            return false;
+        }
+
+        match &self.include {
+            Some(include) if !is_directory => include.is_match(relative_path),
+            _ => true,
+        }
+    }
+}
+
+/// Compiles glob patterns, naming the `kind` of pattern in errors (e.g. an
+/// invalid "private path" pattern).
+pub(crate) fn build_globs(patterns: &[String], kind: &str) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern).map_err(|e| {
+            DocTreeError::config(format!("Invalid {kind} pattern '{pattern}': {e}"))
+        })?;
+        builder.add(glob);
+    }
+
+    builder
+        .build()
+        .map_err(|e| DocTreeError::config(format!("Invalid {kind} patterns: {e}")))
+}
+
+/// Which entries a scan keeps: not on the built-in skip list and allowed by
+/// the include/exclude globs. A directory it rejects is not descended into.
+#[derive(Debug, Clone)]
+struct EntryFilter {
+    base_path: PathBuf,
+    filter: ScanFilter,
+}
+
+impl EntryFilter {
+    fn admits(&self, path: &Path, is_dir: bool) -> bool {
+        if path == self.base_path {
+            return true;
+        }
+        if DirectoryScanner::should_skip_path(path) {
+            return false;
+        }
+        let relative_path = path.strip_prefix(&self.base_path).unwrap_or(path);
+        let allowed = self.filter.allows(relative_path, is_dir);
+        if !allowed {
+            tracing::debug!("Filtered out by scan globs: {}", relative_path.display());
+        }
+        allowed
+    }
+}
+
+/// How the walker treats symlinks and mount points.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WalkOptions {
+    /// Descend into symlinked directories and files. Loops are detected and
+    /// skipped, and a target reached through several links is scanned once.
+    pub follow_symlinks: bool,
+    /// Descend into directories on other filesystems (mounts, bind mounts).
+    pub cross_filesystems: bool,
+    /// Scan only the files git tracks (`git ls-files`) instead of walking
+    /// the filesystem. Files outside a sparse checkout are skipped.
+    pub tracked_only: bool,
+}
+
 pub struct DirectoryScanner {
     base_path: PathBuf,
+    filter: ScanFilter,
+    limits: ScanLimits,
+    walk_options: WalkOptions,
+    submodule_policy: SubmodulePolicy,
 }
 
 impl DirectoryScanner {
     pub fn new(base_path: PathBuf) -> Self {
-        Self { base_path }
+        Self {
+            base_path,
+            filter: ScanFilter::default(),
+            limits: ScanLimits::default(),
+            walk_options: WalkOptions::default(),
+            submodule_policy: SubmodulePolicy::default(),
+        }
+    }
+
+    pub fn set_filter(&mut self, filter: ScanFilter) {
+        self.filter = filter;
+    }
+
+    pub fn set_limits(&mut self, limits: ScanLimits) {
+        self.limits = limits;
+    }
+
+    pub fn set_walk_options(&mut self, walk_options: WalkOptions) {
+        self.walk_options = walk_options;
+    }
+
+    pub fn set_submodule_policy(&mut self, submodule_policy: SubmodulePolicy) {
+        self.submodule_policy = submodule_policy;
     }
 
     pub fn scan_directory(&self) -> Result<FileNode> {
-        log::info!("Starting directory scan of: {}", self.base_path.display());
+        self.scan_directory_with(|_| {})
+    }
+
+    /// Like `scan_directory`, but hands each node to `on_node` as soon as
+    /// the walk accepts it, before the tree is complete.
+    pub fn scan_directory_with(&self, mut on_node: impl FnMut(&FileNode)) -> Result<FileNode> {
+        tracing::info!("Starting directory scan of: {}", self.base_path.display());
 
         let mut root = FileNode::new(self.base_path.clone(), true);
         let mut path_to_node: HashMap<PathBuf, Vec<FileNode>> = HashMap::new();
+        for event in self.scan_events()? {
+            let ScanEvent::Node(node) = event? else {
+                continue;
+            };
+            on_node(&node);
+
+            if let Some(parent_path) = node.path.parent() {
+                path_to_node.entry(parent_path.to_path_buf())
+                    .or_default()
+                    .push(node);
+            }
+        }
+
+        Self::build_tree(&mut root, &mut path_to_node)?;
 
+        tracing::info!("Directory scan completed. Found {} total items", Self::count_nodes(&root));
+        
+        Ok(root)
+    }
+
+    /// Streams the scan as events, so callers can start on the entries
+    /// found so far while the walk continues.
+    pub fn scan_events(&self) -> Result<ScanEvents<'_>> {
+        let submodules: Vec<(Submodule, SubmoduleMode)> = read_gitmodules(&self.base_path)
+            .into_iter()
+            .map(|submodule| {
+                let mode = self.submodule_policy.mode_for(&submodule.path);
+                (submodule, mode)
+            })
+            .filter(|(_, mode)| *mode != SubmoduleMode::Scan)
+            .collect();
+
+        // The walk is streamed so the scan limits can stop it early, and
+        // skipped directories are pruned before it descends into them, so
+        // their contents never count toward the limits.
+        let entries: Box<dyn Iterator<Item = (PathBuf, bool, usize)>> = if self.walk_options.tracked_only {
+            Box::new(self.tracked_entries()?.into_iter())
+        } else {
+            Box::new(self.walked_entries().fuse())
+        };
+
+        Ok(ScanEvents {
+            scanner: self,
+            entries,
+            submodules,
+            file_count: 0,
+            directory_file_counts: HashMap::new(),
+            seen_targets: HashSet::new(),
+            open_directories: vec![self.base_path.clone()],
+            ready: VecDeque::new(),
+            failed: false,
+        })
+    }
+
+    /// Entries from walking the filesystem as `(path, is_dir, depth)`.
+    fn walked_entries(&self) -> impl Iterator<Item = (PathBuf, bool, usize)> {
+        let entry_filter = self.entry_filter();
         let walker = WalkBuilder::new(&self.base_path)
             .hidden(true)
             .git_ignore(true)
             .git_global(true)
             .git_exclude(true)
             .require_git(false)
-            .follow_links(false)
-            .same_file_system(true)
+            .follow_links(self.walk_options.follow_symlinks)
+            .same_file_system(!self.walk_options.cross_filesystems)
+            .filter_entry(move |entry| {
+                entry_filter.admits(entry.path(), entry.file_type().is_some_and(|file_type| file_type.is_dir()))
+            })
             .build();
 
-        for result in walker {
-            match result {
+        walker
+            .filter_map(|result| match result {
                 Ok(entry) => {
-                    let path = entry.path();
                     let is_dir = entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false);
-
-                    if path == self.base_path {
-                        continue;
-                    }
-
-                    if self.should_skip_path(path) {
-                        continue;
-                    }
-
-                    let node = FileNode::new(path.to_path_buf(), is_dir);
-                    
-                    if let Some(parent_path) = path.parent() {
-                        path_to_node.entry(parent_path.to_path_buf())
-                            .or_default()
-                            .push(node);
-                    }
+                    Some((entry.path().to_path_buf(), is_dir, entry.depth()))
                 }
                 Err(err) => {
-                    log::warn!("Error walking directory: {err}");
-                    continue;
+                    tracing::warn!("Error walking directory: {err}");
+                    None
+                }
+            })
+    }
+
+    /// Entries for the files git tracks under the base path, plus their
+    /// parent directories. Index entries missing from the working tree
+    /// (sparse checkouts, pending deletions) are left out.
+    fn tracked_entries(&self) -> Result<Vec<(PathBuf, bool, usize)>> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&self.base_path)
+            .args(["ls-files", "-z", "--cached", "--recurse-submodules"])
+            .output()
+            .map_err(|e| DocTreeError::scanner(format!("Failed to run git ls-files: {e}")))?;
+
+        if !output.status.success() {
+            return Err(DocTreeError::scanner(format!(
+                "--tracked-only requires a git repository: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let entry_filter = self.entry_filter();
+        let mut entries = Vec::new();
+        let mut directories = BTreeSet::new();
+
+        for relative in String::from_utf8_lossy(&output.stdout).split('\0') {
+            if relative.is_empty() {
+                continue;
+            }
+
+            let relative_path = Path::new(relative);
+            let path = self.base_path.join(relative_path);
+            let admitted = entry_filter.admits(&path, false)
+                && relative_path
+                    .ancestors()
+                    .skip(1)
+                    .take_while(|ancestor| !ancestor.as_os_str().is_empty())
+                    .all(|ancestor| entry_filter.admits(&self.base_path.join(ancestor), true));
+            if !admitted {
+                continue;
+            }
+            if !path.symlink_metadata().is_ok_and(|metadata| !metadata.is_dir()) {
+                tracing::debug!("Tracked but not checked out: {relative}");
+                continue;
+            }
+
+            for ancestor in relative_path.ancestors().skip(1) {
+                if ancestor.as_os_str().is_empty() || !directories.insert(ancestor.to_path_buf()) {
+                    break;
                 }
             }
+            entries.push((path, false, relative_path.components().count()));
         }
 
-        Self::build_tree(&mut root, &mut path_to_node)?;
+        entries.extend(directories.into_iter().map(|relative_path| {
+            let depth = relative_path.components().count();
+            (self.base_path.join(relative_path), true, depth)
+        }));
+        // In walk order: each directory right before its contents
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
 
-        log::info!("Directory scan completed. Found {} total items", Self::count_nodes(&root));
-        
-        Ok(root)
+        Ok(entries)
+    }
+
+    fn entry_filter(&self) -> EntryFilter {
+        EntryFilter { base_path: self.base_path.clone(), filter: self.filter.clone() }
     }
 
-    fn should_skip_path(&self, path: &Path) -> bool {
+    fn should_skip_path(path: &Path) -> bool {
         let path_str = path.to_string_lossy();
         
         if path_str.contains(".doctreeai_cache") {
@@ -151,13 +458,7 @@ impl DirectoryScanner {
             }
         }
 
-        parent.children.sort_by(|a, b| {
-            match (a.is_directory, b.is_directory) {
-                (true, false) => std::cmp::Ordering::Less,
-                (false, true) => std::cmp::Ordering::Greater,
-                _ => a.path.cmp(&b.path),
-            }
-        });
+        parent.sort_children();
 
         Ok(())
     }
@@ -193,4 +494,110 @@ impl DirectoryScanner {
 
         result
     }
-}
\ No newline at end of file
+}
+/// What a streamed scan reports, in walk order: a directory's contents come
+/// after its `Node` and before its `DirectoryDone`.
+#[derive(Debug, Clone)]
+pub enum ScanEvent {
+    Node(FileNode),
+    /// Every entry under the directory has been reported. Sent for the scan
+    /// root last.
+    DirectoryDone(PathBuf),
+}
+
+/// A scan in progress (see `DirectoryScanner::scan_events`). Stops after
+/// the first error, e.g. a scan limit.
+pub struct ScanEvents<'a> {
+    scanner: &'a DirectoryScanner,
+    entries: Box<dyn Iterator<Item = (PathBuf, bool, usize)>>,
+    submodules: Vec<(Submodule, SubmoduleMode)>,
+    file_count: usize,
+    directory_file_counts: HashMap<PathBuf, usize>,
+    seen_targets: HashSet<PathBuf>,
+    /// Directories whose contents are still being walked, innermost last.
+    open_directories: Vec<PathBuf>,
+    ready: VecDeque<ScanEvent>,
+    failed: bool,
+}
+
+impl ScanEvents<'_> {
+    /// The node for a walked entry, or None when the scan leaves it out.
+    fn accept(&mut self, path: &Path, is_dir: bool, depth: usize) -> Result<Option<FileNode>> {
+        let base_path = &self.scanner.base_path;
+        if path == base_path {
+            return Ok(None);
+        }
+
+        let relative_path = path.strip_prefix(base_path).unwrap_or(path);
+        let mut submodule = None;
+        if let Some((declared, mode)) = self.submodules.iter().find(|(s, _)| relative_path.starts_with(&s.path)) {
+            if *mode == SubmoduleMode::Skip || relative_path != declared.path {
+                return Ok(None);
+            }
+            if is_dir {
+                submodule = Some(declared.clone());
+            }
+        }
+
+        if self.scanner.walk_options.follow_symlinks && !is_dir {
+            let target = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+            if !self.seen_targets.insert(target) {
+                tracing::debug!("Already scanned through another link: {}", relative_path.display());
+                return Ok(None);
+            }
+        }
+
+        let limits = &self.scanner.limits;
+        limits.check_depth(depth, relative_path)?;
+        if !is_dir {
+            self.file_count += 1;
+            limits.check_file_count(self.file_count)?;
+            let directory = relative_path.parent().unwrap_or(Path::new(""));
+            let directory_count = self.directory_file_counts.entry(directory.to_path_buf()).or_default();
+            *directory_count += 1;
+            limits.check_directory_file_count(*directory_count, directory)?;
+        }
+
+        let mut node = FileNode::new(path.to_path_buf(), is_dir);
+        node.submodule = submodule;
+        Ok(Some(node))
+    }
+}
+
+impl Iterator for ScanEvents<'_> {
+    type Item = Result<ScanEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.ready.pop_front() {
+                return Some(Ok(event));
+            }
+            if self.failed {
+                return None;
+            }
+
+            let Some((path, is_dir, depth)) = self.entries.next() else {
+                let directory = self.open_directories.pop()?;
+                self.ready.push_back(ScanEvent::DirectoryDone(directory));
+                continue;
+            };
+            let node = match self.accept(&path, is_dir, depth) {
+                Ok(Some(node)) => node,
+                Ok(None) => continue,
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(e));
+                }
+            };
+
+            // The walk has left every open directory this entry is not in
+            while let Some(directory) = self.open_directories.pop_if(|directory| !path.starts_with(directory)) {
+                self.ready.push_back(ScanEvent::DirectoryDone(directory));
+            }
+            if is_dir {
+                self.open_directories.push(path);
+            }
+            self.ready.push_back(ScanEvent::Node(node));
+        }
+    }
+}
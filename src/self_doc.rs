@@ -0,0 +1,188 @@
+use crate::cache::CacheManager;
+use crate::config::Config;
+use crate::error::{DocTreeError, Result};
+use crate::llm::LanguageModelClient;
+use crate::readme::ReadmeManager;
+use crate::scanner::ScanFilter;
+use crate::summarizer::HierarchicalSummarizer;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Golden files, relative to the doctreeai source root.
+pub const GOLDEN_DIR: &str = "tests/golden/self-doc";
+
+/// Only committed sources are documented so local scratch files don't
+/// change the output.
+const SELF_DOC_INCLUDE: &[&str] = &["Cargo.toml", "src/**"];
+
+/// Never created: the cache is read-only and starts empty.
+const SELF_DOC_CACHE_DIR: &str = ".doctreeai_self_doc";
+
+/// One generated document and its golden counterpart.
+#[derive(Debug, Clone)]
+pub struct GoldenFile {
+    pub name: &'static str,
+    pub actual: String,
+    pub expected: Option<String>,
+}
+
+impl GoldenFile {
+    pub fn matches(&self) -> bool {
+        self.expected.as_deref() == Some(self.actual.as_str())
+    }
+
+    /// First differing line as (line number, expected, actual).
+    pub fn first_difference(&self) -> Option<(usize, String, String)> {
+        let expected = self.expected.as_deref().unwrap_or_default();
+        let mut expected_lines = expected.lines();
+        let mut actual_lines = self.actual.lines();
+
+        for line_number in 1.. {
+            match (expected_lines.next(), actual_lines.next()) {
+                (None, None) => return None,
+                (expected, actual) if expected != actual => {
+                    return Some((
+                        line_number,
+                        expected.unwrap_or("<end of file>").to_string(),
+                        actual.unwrap_or("<end of file>").to_string(),
+                    ));
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+/// Runs the full pipeline against doctreeai's own source with the mock LLM
+/// and compares the output with the committed golden files.
+pub struct SelfDoc {
+    source_root: PathBuf,
+}
+
+impl SelfDoc {
+    pub fn new(source_root: &Path) -> Self {
+        Self {
+            source_root: source_root.to_path_buf(),
+        }
+    }
+
+    /// The source tree doctreeai was built from.
+    pub fn from_build() -> Result<Self> {
+        let source_root = Path::new(env!("CARGO_MANIFEST_DIR"));
+        if !source_root.join("src").is_dir() {
+            return Err(DocTreeError::config(format!(
+                "doctreeai sources not found at {} - pass --path to a doctreeai checkout",
+                source_root.display()
+            )));
+        }
+        Ok(Self::new(source_root))
+    }
+
+    pub fn golden_dir(&self) -> PathBuf {
+        self.source_root.join(GOLDEN_DIR)
+    }
+
+    /// Scans, summarizes, and synthesizes a README without writing anything.
+    pub async fn generate(&self) -> Result<Vec<GoldenFile>> {
+        let config = Self::config();
+        let mut cache_manager = CacheManager::new(&self.source_root, SELF_DOC_CACHE_DIR)?;
+        cache_manager.set_read_only(true);
+
+        let mut summarizer =
+            HierarchicalSummarizer::new(LanguageModelClient::mock(&config), cache_manager, true);
+        summarizer.set_scan_filter(ScanFilter::new(&config.include_patterns, &[])?);
+        let project_summary = summarizer.generate_project_summary(&self.source_root).await?;
+
+        let cache_manager = summarizer.into_cache_manager();
+        let mut summaries = cache_manager.get_all_summaries();
+        summaries.sort_by(|a, b| a.source_path.cmp(&b.source_path));
+
+        let mut tree = String::from("# Summaries\n");
+        for summary in &summaries {
+            let relative_path = summary
+                .source_path
+                .strip_prefix(&self.source_root)
+                .unwrap_or(&summary.source_path);
+            let display = if relative_path.as_os_str().is_empty() {
+                ".".to_string()
+            } else if summary.is_directory {
+                format!("{}/", relative_path.display())
+            } else {
+                relative_path.display().to_string()
+            };
+            tree.push_str(&format!("\n- `{display}`: {}", summary.summary));
+        }
+        tree.push('\n');
+
+        let architecture = ReadmeManager::new().generate_architecture_section(&summaries, &self.source_root);
+        let readme = LanguageModelClient::mock(&config)
+            .create_new_readme(&project_summary, "doctreeai")
+            .await?;
+
+        let outputs = [
+            ("summaries.md", tree),
+            ("architecture.md", architecture),
+            ("README.md", format!("{readme}\n")),
+        ];
+
+        let golden_dir = self.golden_dir();
+        Ok(outputs
+            .into_iter()
+            .map(|(name, actual)| GoldenFile {
+                name,
+                actual,
+                expected: fs::read_to_string(golden_dir.join(name)).ok(),
+            })
+            .collect())
+    }
+
+    /// Overwrites the golden files with the generated output.
+    pub fn bless(&self, files: &[GoldenFile]) -> Result<()> {
+        let golden_dir = self.golden_dir();
+        fs::create_dir_all(&golden_dir)?;
+        for file in files {
+            fs::write(golden_dir.join(file.name), &file.actual)?;
+        }
+        Ok(())
+    }
+
+    /// Defaults only, so the environment and local doctreeai.toml can't
+    /// change the output.
+    fn config() -> Config {
+        Config {
+            openai_model_name: "mock".to_string(),
+            include_patterns: SELF_DOC_INCLUDE.iter().map(|glob| glob.to_string()).collect(),
+            ..Config::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_difference() {
+        let file = GoldenFile {
+            name: "summaries.md",
+            actual: "a\nb\nc\n".to_string(),
+            expected: Some("a\nx\n".to_string()),
+        };
+        assert!(!file.matches());
+        assert_eq!(
+            file.first_difference(),
+            Some((2, "x".to_string(), "b".to_string()))
+        );
+
+        let missing = GoldenFile { expected: None, ..file.clone() };
+        assert_eq!(
+            missing.first_difference(),
+            Some((1, "<end of file>".to_string(), "a".to_string()))
+        );
+
+        let same = GoldenFile { expected: Some(file.actual.clone()), ..file };
+        assert!(same.matches());
+        assert_eq!(same.first_difference(), None);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::cache::ReadmeLineMapping;
+use crate::tree::{Freshness, SummaryTreeNode};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// A cached file or directory whose source changed or was deleted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StaleEntry {
+    /// Path relative to the project root ("" for the root).
+    pub path: PathBuf,
+    pub is_directory: bool,
+    pub status: Freshness,
+}
+
+/// A README line mapped to at least one stale entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StaleLine {
+    pub line_number: usize,
+    pub line_content: String,
+    /// The stale entries it maps to, relative to the project root.
+    pub entries: Vec<PathBuf>,
+}
+
+/// Result of `doctreeai run --no-llm`: what the cache says is out of date,
+/// found from content hashes alone without calling the LLM.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StalenessReport {
+    pub stale_entries: Vec<StaleEntry>,
+    pub stale_lines: Vec<StaleLine>,
+}
+
+impl StalenessReport {
+    pub fn collect(tree: &SummaryTreeNode, mappings: &[ReadmeLineMapping], base_path: &Path) -> Self {
+        let mut stale_entries = Vec::new();
+        collect_stale(tree, &mut stale_entries);
+
+        let stale_lines = mappings
+            .iter()
+            .filter_map(|mapping| {
+                let entries: Vec<PathBuf> = mapping
+                    .cache_keys
+                    .iter()
+                    .map(|key| {
+                        let source_path = Path::new(key);
+                        source_path.strip_prefix(base_path).unwrap_or(source_path).to_path_buf()
+                    })
+                    .filter(|path| stale_entries.iter().any(|entry| entry.path == *path))
+                    .collect();
+                (!entries.is_empty()).then(|| StaleLine {
+                    line_number: mapping.line_number,
+                    line_content: mapping.line_content.clone(),
+                    entries,
+                })
+            })
+            .collect();
+
+        Self { stale_entries, stale_lines }
+    }
+
+    /// Stale README lines mapped to any of these files (relative to the
+    /// project root), e.g. the files staged for a commit.
+    pub fn lines_for(&self, paths: &[PathBuf]) -> Vec<&StaleLine> {
+        self.stale_lines
+            .iter()
+            .filter(|line| line.entries.iter().any(|entry| paths.contains(entry)))
+            .collect()
+    }
+
+    /// Whether every summary still matches the code.
+    pub fn is_clean(&self) -> bool {
+        self.stale_entries.is_empty()
+    }
+}
+
+/// Stale and missing nodes in tree order. Expired entries still match the
+/// code, so they are not reported.
+fn collect_stale(node: &SummaryTreeNode, stale: &mut Vec<StaleEntry>) {
+    if matches!(node.status, Freshness::Stale | Freshness::Missing) {
+        stale.push(StaleEntry {
+            path: node.path.clone(),
+            is_directory: node.is_directory,
+            status: node.status,
+        });
+    }
+    for child in &node.children {
+        collect_stale(child, stale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::doctree::{DocTree, RunOptions};
+    use crate::error::Result;
+    use crate::llm::LanguageModelClient;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_staleness_report_after_code_change() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        fs::create_dir_all(base_path.join("src"))?;
+        fs::write(base_path.join("src/parser.rs"), "pub fn parse() {}")?;
+        fs::write(base_path.join("src/main.rs"), "fn main() {}")?;
+        fs::write(base_path.join("README.md"), "# Demo\n\nThe parser module reads input.\n")?;
+
+        let config = Config {
+            openai_api_base: "http://localhost:11434/v1".to_string(),
+            openai_model_name: "test-model".to_string(),
+            cache_dir_name: ".test_cache".to_string(),
+            ..Default::default()
+        };
+        let mut doc_tree = DocTree::with_config(base_path, config);
+        doc_tree.set_llm_client(LanguageModelClient::mock(doc_tree.config()));
+        doc_tree.run(&RunOptions::default()).await?;
+        assert!(doc_tree.check_staleness()?.is_clean());
+
+        fs::write(base_path.join("src/parser.rs"), "pub fn parse(input: &str) {}")?;
+        let report = doc_tree.check_staleness()?;
+        let stale: Vec<&Path> = report.stale_entries.iter().map(|entry| entry.path.as_path()).collect();
+        assert_eq!(stale, vec![Path::new(""), Path::new("src"), Path::new("src/parser.rs")]);
+        assert_eq!(report.stale_lines.len(), 1);
+        assert_eq!(report.stale_lines[0].line_number, 3);
+        assert!(report.stale_lines[0].entries.contains(&PathBuf::from("src/parser.rs")));
+        assert_eq!(report.lines_for(&[PathBuf::from("src/parser.rs")]).len(), 1);
+        assert!(report.lines_for(&[PathBuf::from("src/main.rs")]).is_empty());
+
+        Ok(())
+    }
+}
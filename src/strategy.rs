@@ -0,0 +1,205 @@
+use crate::budget::SummaryLevel;
+use crate::cache::CacheManager;
+use crate::classifier::DirectoryClassifier;
+use crate::error::{DocTreeError, Result};
+use crate::hasher::FileHasher;
+use crate::scanner::FileNode;
+use crate::summarizer::HierarchicalSummarizer;
+use std::fmt;
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::str::FromStr;
+
+pub type SummaryFuture<'a> = Pin<Box<dyn Future<Output = Result<String>> + 'a>>;
+
+/// A way of turning a project into cached summaries and a root-level
+/// project summary. New strategies implement this instead of changing
+/// `HierarchicalSummarizer`.
+pub trait SummarizationStrategy {
+    fn name(&self) -> &'static str;
+
+    /// Summarizes the project, storing summaries in the cache as it goes,
+    /// and returns the project summary used to validate the README.
+    fn generate_project_summary<'a>(&'a mut self, base_path: &'a Path) -> SummaryFuture<'a>;
+
+    fn get_cache_stats(&self) -> (usize, u64);
+
+    /// Hands back the cache manager, including any in-memory entries written
+    /// during a read-only run.
+    fn into_cache_manager(self: Box<Self>) -> CacheManager;
+}
+
+/// Strategies selectable with `run --strategy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StrategyKind {
+    /// Files, then directories bottom-up, then the project root.
+    #[default]
+    Hierarchical,
+    /// Files only; the project summary is reduced straight from file summaries.
+    Flat,
+}
+
+impl StrategyKind {
+    /// Wraps a configured summarizer in the selected strategy.
+    pub fn build(self, summarizer: HierarchicalSummarizer) -> Box<dyn SummarizationStrategy> {
+        match self {
+            StrategyKind::Hierarchical => Box::new(summarizer),
+            StrategyKind::Flat => Box::new(FlatSummarizer::new(summarizer)),
+        }
+    }
+}
+
+impl FromStr for StrategyKind {
+    type Err = DocTreeError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "hierarchical" => Ok(StrategyKind::Hierarchical),
+            "flat" => Ok(StrategyKind::Flat),
+            other => Err(DocTreeError::config(format!(
+                "Unknown summarization strategy '{other}' (expected hierarchical or flat)"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for StrategyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            StrategyKind::Hierarchical => "hierarchical",
+            StrategyKind::Flat => "flat",
+        };
+        f.write_str(name)
+    }
+}
+
+impl SummarizationStrategy for HierarchicalSummarizer {
+    fn name(&self) -> &'static str {
+        "hierarchical"
+    }
+
+    fn generate_project_summary<'a>(&'a mut self, base_path: &'a Path) -> SummaryFuture<'a> {
+        Box::pin(HierarchicalSummarizer::generate_project_summary(self, base_path))
+    }
+
+    fn get_cache_stats(&self) -> (usize, u64) {
+        HierarchicalSummarizer::get_cache_stats(self)
+    }
+
+    fn into_cache_manager(self: Box<Self>) -> CacheManager {
+        HierarchicalSummarizer::into_cache_manager(*self)
+    }
+}
+
+/// Summarizes every file with the hierarchical summarizer's per-file
+/// pipeline, but skips directory summaries: the project summary is reduced
+/// directly from the file summaries. Cheaper for shallow projects.
+pub struct FlatSummarizer {
+    inner: HierarchicalSummarizer,
+}
+
+impl FlatSummarizer {
+    pub fn new(inner: HierarchicalSummarizer) -> Self {
+        Self { inner }
+    }
+
+    async fn summarize(&mut self, base_path: &Path) -> Result<String> {
+        tracing::info!("Starting flat summarization for: {}", base_path.display());
+        self.inner.cache_manager_mut().initialize_cache_directory()?;
+
+        let root_node = self.inner.summarize_streaming(base_path, false).await?;
+        let mut leaves = Vec::new();
+        collect_leaves(&root_node, &mut leaves);
+
+        let mut summaries = Vec::new();
+        let mut hashes = Vec::new();
+        for leaf in leaves {
+            if let (Some(summary), Some(hash)) = (&leaf.summary, &leaf.content_hash) {
+                let relative_path = leaf.get_relative_path(base_path)?;
+                summaries.push(format!("**{}**: {summary}", relative_path.display()));
+                hashes.push(hash.clone());
+            }
+        }
+
+        if summaries.is_empty() {
+            return Err(DocTreeError::summarizer("No summarizable files found"));
+        }
+
+        let project_hash = FileHasher::compute_directory_hash(&hashes);
+        if !self.inner.force_regeneration() {
+            if let Some(cached) = self.inner.cached_directory_summary(base_path, &project_hash) {
+                return Ok(cached);
+            }
+        }
+
+        let summary = self.inner.reduce_directory_summary("project root", &summaries, SummaryLevel::Root).await?;
+        let role = DirectoryClassifier::classify("project root", &summary);
+        self.inner
+            .store_directory_summary(base_path, project_hash, summary.clone(), role)?;
+        tracing::info!("Generated flat project summary from {} files", summaries.len());
+
+        Ok(summary)
+    }
+}
+
+/// Files and opaque submodules, in tree order.
+fn collect_leaves<'a>(node: &'a FileNode, leaves: &mut Vec<&'a FileNode>) {
+    for child in &node.children {
+        if child.is_directory && child.submodule.is_none() {
+            collect_leaves(child, leaves);
+        } else {
+            leaves.push(child);
+        }
+    }
+}
+
+impl SummarizationStrategy for FlatSummarizer {
+    fn name(&self) -> &'static str {
+        "flat"
+    }
+
+    fn generate_project_summary<'a>(&'a mut self, base_path: &'a Path) -> SummaryFuture<'a> {
+        Box::pin(self.summarize(base_path))
+    }
+
+    fn get_cache_stats(&self) -> (usize, u64) {
+        self.inner.get_cache_stats()
+    }
+
+    fn into_cache_manager(self: Box<Self>) -> CacheManager {
+        self.inner.into_cache_manager()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::llm::LanguageModelClient;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_flat_strategy_skips_directory_summaries() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        fs::create_dir_all(base_path.join("src/api"))?;
+        fs::write(base_path.join("src/main.rs"), "fn main() {}")?;
+        fs::write(base_path.join("src/api/server.rs"), "fn serve() {}")?;
+
+        let cache_manager = CacheManager::new(base_path, ".test_cache")?;
+        let summarizer =
+            HierarchicalSummarizer::new(LanguageModelClient::mock(&Config::default()), cache_manager, false);
+        let mut strategy = "flat".parse::<StrategyKind>()?.build(summarizer);
+        assert_eq!(strategy.name(), "flat");
+
+        strategy.generate_project_summary(base_path).await?;
+        let cache_manager = strategy.into_cache_manager();
+        assert!(cache_manager.get_cache_summary(&base_path.join("src/api/server.rs")).is_some());
+        assert!(cache_manager.get_cache_summary(&base_path.join("src/api")).is_none());
+        assert!(cache_manager.get_cache_summary(base_path).is_some());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use crate::error::{DocTreeError, Result};
+use serde::{Deserialize, Serialize};
+
+/// JSON Schema the LLM is asked to follow for structured file summaries.
+pub const STRUCTURED_SUMMARY_SCHEMA: &str = r#"{
+  "type": "object",
+  "required": ["purpose"],
+  "properties": {
+    "purpose": {"type": "string", "description": "What the file is for, in 1-3 sentences"},
+    "public_api": {"type": "array", "items": {"type": "string"}, "description": "Exported functions, commands, or endpoints"},
+    "key_types": {"type": "array", "items": {"type": "string"}, "description": "Important types, structs, classes, or enums"},
+    "dependencies": {"type": "array", "items": {"type": "string"}, "description": "Libraries and project modules it relies on"},
+    "caveats": {"type": "array", "items": {"type": "string"}, "description": "Limitations, side effects, or surprising behavior"}
+  }
+}"#;
+
+/// A file summary as separate fields instead of free-form prose, kept next
+/// to the rendered text in the cache so README synthesis and queries can
+/// use individual fields.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct StructuredSummary {
+    pub purpose: String,
+    #[serde(default)]
+    pub public_api: Vec<String>,
+    #[serde(default)]
+    pub key_types: Vec<String>,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    #[serde(default)]
+    pub caveats: Vec<String>,
+}
+
+impl StructuredSummary {
+    /// Parses an LLM response against the schema. The JSON object may be
+    /// wrapped in a code fence or surrounded by prose; unknown fields are
+    /// ignored, but missing or mistyped ones are an error.
+    pub fn parse(response: &str) -> Result<Self> {
+        let invalid = |reason: String| DocTreeError::summarizer(format!("Invalid structured summary: {reason}"));
+
+        let start = response.find('{').ok_or_else(|| invalid("no JSON object in the response".to_string()))?;
+        let end = response.rfind('}').filter(|end| *end > start).ok_or_else(|| invalid("unterminated JSON object".to_string()))?;
+        let mut summary: Self = serde_json::from_str(&response[start..=end]).map_err(|e| invalid(e.to_string()))?;
+
+        summary.purpose = summary.purpose.trim().to_string();
+        if summary.purpose.is_empty() {
+            return Err(invalid("'purpose' is empty".to_string()));
+        }
+        for list in [&mut summary.public_api, &mut summary.key_types, &mut summary.dependencies, &mut summary.caveats] {
+            list.retain(|item| !item.trim().is_empty());
+        }
+        Ok(summary)
+    }
+
+    /// The same summary with `rewrite` applied to every field.
+    pub fn map_text(&self, rewrite: impl Fn(&str) -> String) -> Self {
+        let rewrite_all = |items: &[String]| items.iter().map(|item| rewrite(item)).collect();
+        Self {
+            purpose: rewrite(&self.purpose),
+            public_api: rewrite_all(&self.public_api),
+            key_types: rewrite_all(&self.key_types),
+            dependencies: rewrite_all(&self.dependencies),
+            caveats: rewrite_all(&self.caveats),
+        }
+    }
+
+    /// Plain-text form stored as the entry's summary and rolled up into
+    /// directory summaries: the purpose, then one line per non-empty list.
+    pub fn render(&self) -> String {
+        let mut text = self.purpose.clone();
+        for (label, items) in [
+            ("Public API", &self.public_api),
+            ("Key types", &self.key_types),
+            ("Dependencies", &self.dependencies),
+            ("Caveats", &self.caveats),
+        ] {
+            if !items.is_empty() {
+                text.push_str(&format!("\n{label}: {}.", items.join("; ").trim_end_matches('.')));
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_render_structured_summary() -> Result<()> {
+        let response = "Here is the summary:\n```json\n{\"purpose\": \" Parses config files. \", \"public_api\": [\"load\", \"\"], \"caveats\": [\"Reads the environment.\"], \"extra\": 1}\n```";
+        let summary = StructuredSummary::parse(response)?;
+        assert_eq!(summary.public_api, vec!["load"]);
+        assert!(summary.key_types.is_empty());
+        assert_eq!(summary.render(), "Parses config files.\nPublic API: load.\nCaveats: Reads the environment.");
+
+        assert!(StructuredSummary::parse("Parses config files.").is_err());
+        assert!(StructuredSummary::parse("{\"purpose\": \"\"}").is_err());
+        assert!(StructuredSummary::parse("{\"purpose\": \"x\", \"public_api\": \"load\"}").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+use crate::error::{DocTreeError, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// How the scanner treats a git submodule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SubmoduleMode {
+    /// Summarized as a single "external dependency" node without descending.
+    #[default]
+    Opaque,
+    /// Left out of the scan entirely.
+    Skip,
+    /// Scanned like the rest of the project.
+    Scan,
+}
+
+impl FromStr for SubmoduleMode {
+    type Err = DocTreeError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "opaque" => Ok(SubmoduleMode::Opaque),
+            "skip" => Ok(SubmoduleMode::Skip),
+            "scan" => Ok(SubmoduleMode::Scan),
+            other => Err(DocTreeError::config(format!(
+                "Invalid submodule mode '{other}' (expected opaque, skip, or scan)"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for SubmoduleMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SubmoduleMode::Opaque => "opaque",
+            SubmoduleMode::Skip => "skip",
+            SubmoduleMode::Scan => "scan",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The mode for every submodule, with per-path overrides.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubmodulePolicy {
+    pub default_mode: SubmoduleMode,
+    pub overrides: BTreeMap<PathBuf, SubmoduleMode>,
+}
+
+impl SubmodulePolicy {
+    /// Parses `path=mode` pairs separated by commas, where `*` (or a bare
+    /// mode) sets the default, e.g. `skip,vendor/openssl=opaque`.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let mut policy = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (path, mode) = entry.split_once('=').unwrap_or(("*", entry));
+            policy.set(path.trim(), mode.parse()?);
+        }
+        Ok(policy)
+    }
+
+    /// Sets the mode for one submodule path, or the default for `*`.
+    pub fn set(&mut self, path: &str, mode: SubmoduleMode) {
+        if path == "*" {
+            self.default_mode = mode;
+        } else {
+            self.overrides.insert(PathBuf::from(path.trim_end_matches('/')), mode);
+        }
+    }
+
+    pub fn mode_for(&self, relative_path: &Path) -> SubmoduleMode {
+        self.overrides.get(relative_path).copied().unwrap_or(self.default_mode)
+    }
+}
+
+impl fmt::Display for SubmodulePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.default_mode)?;
+        for (path, mode) in &self.overrides {
+            write!(f, ",{}={mode}", path.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// A submodule declared in `.gitmodules`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submodule {
+    /// Path relative to the project root.
+    pub path: PathBuf,
+    pub url: Option<String>,
+}
+
+impl Submodule {
+    /// Summary used for opaque submodules instead of asking the LLM.
+    pub fn summary(&self) -> String {
+        match &self.url {
+            Some(url) => format!("External dependency (git submodule from {url}); not documented here."),
+            None => "External dependency (git submodule); not documented here.".to_string(),
+        }
+    }
+}
+
+/// Reads the submodules declared in `<base_path>/.gitmodules`, if any.
+pub fn read_gitmodules(base_path: &Path) -> Vec<Submodule> {
+    match fs::read_to_string(base_path.join(".gitmodules")) {
+        Ok(content) => parse_gitmodules(&content),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn parse_gitmodules(content: &str) -> Vec<Submodule> {
+    let mut submodules = Vec::new();
+    let mut current: Option<(Option<PathBuf>, Option<String>)> = None;
+
+    let mut finish = |current: Option<(Option<PathBuf>, Option<String>)>| {
+        if let Some((Some(path), url)) = current {
+            submodules.push(Submodule { path, url });
+        }
+    };
+
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            finish(current.take());
+            if line.starts_with("[submodule") {
+                current = Some((None, None));
+            }
+            continue;
+        }
+
+        let (Some((path, url)), Some((key, value))) = (current.as_mut(), line.split_once('=')) else {
+            continue;
+        };
+        match key.trim() {
+            "path" => *path = Some(PathBuf::from(value.trim().trim_end_matches('/'))),
+            "url" => *url = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+    finish(current);
+
+    submodules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gitmodules_and_policy() -> Result<()> {
+        let submodules = parse_gitmodules(
+            r#"
+[submodule "openssl"]
+	path = vendor/openssl
+	url = https://github.com/openssl/openssl.git
+[core]
+	path = ignored
+[submodule "docs-theme"]
+	path = docs/theme/
+"#,
+        );
+        assert_eq!(
+            submodules,
+            vec![
+                Submodule {
+                    path: PathBuf::from("vendor/openssl"),
+                    url: Some("https://github.com/openssl/openssl.git".to_string()),
+                },
+                Submodule {
+                    path: PathBuf::from("docs/theme"),
+                    url: None,
+                },
+            ]
+        );
+
+        let policy = SubmodulePolicy::parse("skip, docs/theme/=scan")?;
+        assert_eq!(policy.mode_for(Path::new("vendor/openssl")), SubmoduleMode::Skip);
+        assert_eq!(policy.mode_for(Path::new("docs/theme")), SubmoduleMode::Scan);
+        assert_eq!(policy.to_string(), "skip,docs/theme=scan");
+        assert!(SubmodulePolicy::parse("vendor=ignore").is_err());
+
+        Ok(())
+    }
+}
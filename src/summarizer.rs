@@ -1,15 +1,352 @@
-use crate::cache::CacheManager;
+use crate::annotations::{apply_annotations, AnnotatedContent, IgnorePatterns, PrivatePatterns};
+use crate::budget::{SummaryLevel, TokenBudget};
+use crate::cache::{CacheManager, HashedFile, SourceHasher, SummaryMethod};
+use crate::cancel::CancellationToken;
+use crate::config::Config;
+use crate::classifier::{ChildImportance, DirectoryClassifier, DirectoryRole};
+use crate::digest::{
+    extractive_summary, heuristic_summary, structural_directory_summary, structural_summary, structure_only_summary,
+};
 use crate::error::{DocTreeError, Result};
+use crate::estimator::estimate_tokens;
+use crate::generated::GeneratedFileDetector;
+use crate::graph::DependencyGraph;
 use crate::hasher::FileHasher;
+use crate::limits::{FileSizeLimit, ScanLimits};
 use crate::llm::LanguageModelClient;
-use crate::scanner::{DirectoryScanner, FileNode};
+use crate::outline::{extract_outline, SummaryInput};
+use crate::progress::ProgressListener;
+use crate::reporter::Reporter;
+use crate::run_state::RunState;
+use crate::rust_api::RustApi;
+use crate::scanner::{DirectoryScanner, FileNode, ScanEvent, ScanFilter, SourceFileMatcher, WalkOptions};
+use crate::secrets::SecretScanner;
+use crate::structured::StructuredSummary;
+use crate::submodules::{Submodule, SubmodulePolicy};
+use regex::Regex;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio::task::{JoinError, JoinSet};
+use tracing::Instrument;
+
+/// Relative file size change above which a pinned summary is flagged.
+const PINNED_DRIFT_WARNING_THRESHOLD: f64 = 0.25;
+
+/// Most sibling summaries given to an entry point in the sibling-context pass.
+const MAX_SIBLING_SUMMARIES: usize = 12;
+
+/// Run state changes kept in memory before the run state is written again.
+const RUN_STATE_SAVE_EVERY: usize = 100;
+
+/// Longest a run state change stays unwritten while the run goes on.
+const RUN_STATE_SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Bytes of the files under a node, used to put large modules first.
+fn subtree_size(node: &FileNode) -> u64 {
+    if node.is_directory {
+        node.children.iter().map(subtree_size).sum()
+    } else {
+        fs::metadata(&node.path).map(|metadata| metadata.len()).unwrap_or(0)
+    }
+}
+
+/// Rewrites mentions of one file, by relative path or by name, as another.
+fn path_substitution(from: &Path, to: &Path) -> impl Fn(&str) -> String {
+    let file_name = |path: &Path| path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let (from_name, to_name) = (file_name(from), file_name(to));
+    let (from_path, to_path) = (from.to_string_lossy().into_owned(), to.to_string_lossy().into_owned());
+    // The whole path is tried first so it is not rewritten name by name
+    let pattern = Regex::new(&format!("{}|{}", regex::escape(&from_path), regex::escape(&from_name)))
+        .expect("escaped path pattern");
+    move |text: &str| {
+        pattern
+            .replace_all(text, |captures: &regex::Captures| {
+                if captures[0] == from_path { to_path.clone() } else { to_name.clone() }
+            })
+            .into_owned()
+    }
+}
+
+/// Asks for a file summary, structured when the client is set up for it.
+async fn request_file_summary(
+    llm_client: &LanguageModelClient,
+    relative_path: &Path,
+    llm_input: &str,
+    context: Option<&str>,
+) -> GeneratedSummary {
+    if llm_client.uses_structured_summaries() {
+        llm_client
+            .generate_structured_file_summary_with_context(relative_path, llm_input, context)
+            .await
+            .map(|structured| (structured.render(), Some(structured)))
+    } else {
+        llm_client
+            .generate_file_summary_with_context(relative_path, llm_input, context)
+            .await
+            .map(|summary| (summary, None))
+    }
+}
+
+/// A file summary from the LLM, with its fields when it is structured.
+type GeneratedSummary = Result<(String, Option<StructuredSummary>)>;
+
+/// A file summary left to the LLM, once everything cheaper was ruled out.
+struct FilePrompt {
+    relative_path: PathBuf,
+    content_hash: String,
+    /// What the LLM is sent: the file's content, or its outline.
+    llm_input: String,
+    /// For the extractive summary used when the request fails.
+    content: String,
+}
+
+/// An entry point summarized again with its siblings as context.
+struct SiblingPrompt {
+    /// Index of the entry point among the directory's children.
+    child: usize,
+    relative_path: PathBuf,
+    content_hash: String,
+    llm_input: String,
+    context: String,
+}
+
+/// A directory summary left to the LLM.
+struct DirectoryPrompt {
+    relative_path: PathBuf,
+    directory_name: String,
+    directory_hash: String,
+    children_summaries: Vec<String>,
+    level: SummaryLevel,
+}
+
+/// Work a task hands back to the summarizer when it finishes.
+enum Finished {
+    Hashed { node: FileNode, hashed: Result<HashedFile> },
+    File { node: FileNode, prompt: FilePrompt, generated: GeneratedSummary },
+    Siblings { node: FileNode, siblings_hash: String, generated: Vec<(SiblingPrompt, GeneratedSummary)> },
+    Directory { node: FileNode, prompt: DirectoryPrompt, generated: Result<String> },
+}
+
+/// LLM work held back until the scan ends and imports are resolved.
+enum Parked {
+    File(FileNode, FilePrompt),
+    /// A directory waiting for its sibling-context pass.
+    Siblings(FileNode),
+}
+
+/// A directory whose children are still being scanned or summarized.
+struct OpenDirectory {
+    node: FileNode,
+    /// Children taken in but not yet summarized.
+    pending: usize,
+    /// Whether the walk has left the directory.
+    scanned: bool,
+}
+
+/// The state of one streamed run (see
+/// `HierarchicalSummarizer::summarize_streaming`).
+struct Pipeline {
+    base_path: PathBuf,
+    /// Whether directories are summarized, or only hold their files.
+    directories: bool,
+    open: HashMap<PathBuf, OpenDirectory>,
+    /// Directories taken in as a whole; nothing under them is.
+    skipped: HashSet<PathBuf>,
+    /// Every scanned file, relative to the project root, for resolving
+    /// imports.
+    scanned_files: BTreeSet<PathBuf>,
+    /// None once the scan has ended.
+    awaiting_graph: Option<Vec<Parked>>,
+    /// Files waiting for one with the same content hash to be summarized by
+    /// the LLM, so they can reuse its summary. A hash is listed from the
+    /// time its prompt is made.
+    twins: HashMap<String, Vec<FileNode>>,
+    tasks: JoinSet<Finished>,
+    /// Bounds the LLM requests in flight.
+    llm_permits: Arc<Semaphore>,
+    /// Bounds the files hashed at once.
+    hash_permits: Arc<Semaphore>,
+    /// Set once the root directory is summarized.
+    root: Option<FileNode>,
+}
+
+impl Pipeline {
+    fn new(base_path: &Path, directories: bool, concurrency: usize) -> Self {
+        let root = OpenDirectory { node: FileNode::new(base_path.to_path_buf(), true), pending: 0, scanned: false };
+        let hashing_threads = std::thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        Self {
+            base_path: base_path.to_path_buf(),
+            directories,
+            open: HashMap::from([(base_path.to_path_buf(), root)]),
+            skipped: HashSet::new(),
+            scanned_files: BTreeSet::new(),
+            awaiting_graph: Some(Vec::new()),
+            twins: HashMap::new(),
+            tasks: JoinSet::new(),
+            llm_permits: Arc::new(Semaphore::new(concurrency.max(1))),
+            hash_permits: Arc::new(Semaphore::new(hashing_threads)),
+            root: None,
+        }
+    }
+
+    fn open_directory(&mut self, node: FileNode) {
+        self.expect_child(&node.path);
+        let path = node.path.clone();
+        self.open.insert(path, OpenDirectory { node, pending: 0, scanned: false });
+    }
+
+    /// Counts a child its directory has to wait for.
+    fn expect_child(&mut self, path: &Path) {
+        if let Some(parent) = path.parent().and_then(|parent| self.open.get_mut(parent)) {
+            parent.pending += 1;
+        }
+    }
+
+    /// Adds a node that is already done (or left out) to its directory.
+    fn attach(&mut self, node: FileNode) {
+        if node.is_directory {
+            self.skipped.insert(node.path.clone());
+        }
+        if let Some(parent) = node.path.parent().and_then(|parent| self.open.get_mut(parent)) {
+            parent.node.add_child(node);
+        }
+    }
+
+    /// Adds a child its directory waited for, returning the directory; the
+    /// root ends the run instead.
+    fn child_done(&mut self, node: FileNode) -> Option<PathBuf> {
+        if node.path == self.base_path {
+            self.root = Some(node);
+            return None;
+        }
+        let parent_path = node.path.parent()?.to_path_buf();
+        let parent = self.open.get_mut(&parent_path)?;
+        parent.pending -= 1;
+        parent.node.add_child(node);
+        Some(parent_path)
+    }
+
+    /// Takes out a directory the walk has left whose children are all done.
+    fn take_ready(&mut self, path: &Path) -> Option<FileNode> {
+        let ready = self.open.get(path).is_some_and(|directory| directory.scanned && directory.pending == 0);
+        ready.then(|| self.open.remove(path)).flatten().map(|directory| directory.node)
+    }
+
+    /// Hashes a file on a blocking thread.
+    fn hash(&mut self, hasher: SourceHasher, node: FileNode) {
+        let permits = self.hash_permits.clone();
+        self.tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await.expect("hashing permits are never closed");
+            let path = node.path.clone();
+            let hashed = tokio::task::spawn_blocking(move || hasher.hash(&path))
+                .await
+                .unwrap_or_else(|e| Err(DocTreeError::summarizer(format!("Hashing task failed: {e}"))));
+            Finished::Hashed { node, hashed }
+        });
+    }
+}
+
+/// What a directory summary takes, cloned into the task that makes it.
+#[derive(Clone)]
+struct DirectoryReducer {
+    llm_client: LanguageModelClient,
+    token_budget: u64,
+    offline: bool,
+    public_api: Option<Arc<RustApi>>,
+}
+
+impl DirectoryReducer {
+    /// Summarizes a directory from its children summaries. When the combined
+    /// summaries exceed the token budget they are split into groups, each
+    /// group is summarized, and the group summaries are summarized in turn.
+    /// Only the final summary is held to the level's length budget.
+    async fn reduce(&self, directory_name: &str, children_summaries: &[String], level: SummaryLevel) -> Result<String> {
+        if self.offline {
+            return Ok(structural_directory_summary(children_summaries));
+        }
+
+        let mut summaries = children_summaries.to_vec();
+
+        while summaries.len() > 1
+            && estimate_tokens(&summaries.join("\n\n")) > self.token_budget
+        {
+            let groups = HierarchicalSummarizer::group_by_token_budget(&summaries, self.token_budget);
+            if groups.len() == summaries.len() {
+                // Every summary already fills the budget on its own
+                break;
+            }
+
+            tracing::info!(
+                "Directory '{}' exceeds the token budget, summarizing {} groups",
+                directory_name,
+                groups.len()
+            );
+
+            let mut group_summaries = Vec::with_capacity(groups.len());
+            for (index, group) in groups.iter().enumerate() {
+                let part_name = format!("{directory_name} (part {}/{})", index + 1, groups.len());
+                let summary = self.llm_client.generate_directory_summary(&part_name, group).await?;
+                group_summaries.push(format!("**Part {}**: {summary}", index + 1));
+            }
+            summaries = group_summaries;
+        }
+
+        match level {
+            SummaryLevel::Root => {
+                if let Some(public_api) = &self.public_api {
+                    summaries.push(format!("**Public API** (pub items and rustdoc from the source):\n{}", public_api.listing()));
+                }
+                self.llm_client.generate_root_summary(directory_name, &summaries).await
+            }
+            _ => self.llm_client.generate_directory_summary(directory_name, &summaries).await,
+        }
+    }
+}
 
 pub struct HierarchicalSummarizer {
     llm_client: LanguageModelClient,
     cache_manager: CacheManager,
     force_regeneration: bool,
+    directory_token_budget: u64,
+    extractive_below_bytes: u64,
+    summary_input: SummaryInput,
+    sibling_context: bool,
+    /// Most LLM requests in flight at once.
+    concurrency: usize,
+    /// Public API of the Rust library crate, listed in the root prompt.
+    public_api: Option<Arc<RustApi>>,
+    /// Imports between the scanned files, for "depends on" and "used by"
+    /// notes in file prompts.
+    dependency_graph: Option<DependencyGraph>,
+    offline: bool,
+    ignore_patterns: IgnorePatterns,
+    private_patterns: PrivatePatterns,
+    /// Replaces secrets in file content before it reaches a prompt; None
+    /// when redaction is turned off.
+    secret_scanner: Option<SecretScanner>,
+    scan_filter: ScanFilter,
+    scan_limits: ScanLimits,
+    walk_options: WalkOptions,
+    submodule_policy: SubmodulePolicy,
+    source_matcher: SourceFileMatcher,
+    file_size_limit: FileSizeLimit,
+    generated_detector: GeneratedFileDetector,
+    /// Subtrees to summarize; empty means the whole project.
+    scopes: Vec<PathBuf>,
+    progress: Option<Arc<dyn ProgressListener>>,
+    cancellation: CancellationToken,
+    /// Summaries generated (not taken from the cache) during this run.
+    generated: usize,
+    run_state: Option<RunState>,
+    /// Run state changes not yet written, and when it was last written.
+    unsaved_run_state: usize,
+    run_state_saved_at: Instant,
 }
 
 impl HierarchicalSummarizer {
@@ -22,23 +359,275 @@ impl HierarchicalSummarizer {
             llm_client,
             cache_manager,
             force_regeneration,
+            directory_token_budget: 6000,
+            extractive_below_bytes: 0,
+            summary_input: SummaryInput::default(),
+            sibling_context: false,
+            concurrency: 1,
+            public_api: None,
+            dependency_graph: None,
+            offline: false,
+            ignore_patterns: IgnorePatterns::default(),
+            private_patterns: PrivatePatterns::default(),
+            secret_scanner: Some(SecretScanner::default()),
+            scan_filter: ScanFilter::default(),
+            scan_limits: ScanLimits::default(),
+            walk_options: WalkOptions::default(),
+            submodule_policy: SubmodulePolicy::default(),
+            source_matcher: SourceFileMatcher::default(),
+            file_size_limit: FileSizeLimit::default(),
+            generated_detector: GeneratedFileDetector::default(),
+            scopes: Vec::new(),
+            progress: None,
+            cancellation: CancellationToken::new(),
+            generated: 0,
+            run_state: None,
+            unsaved_run_state: 0,
+            run_state_saved_at: Instant::now(),
         }
     }
 
+    /// Restricts summarization to one subtree (relative to the project root)
+    /// and its ancestor directories. Everything else keeps its cached summary.
+    pub fn set_scope(&mut self, scope: Option<PathBuf>) {
+        self.scopes = scope.into_iter().collect();
+    }
+
+    /// Like `set_scope`, for several subtrees at once.
+    pub fn set_scopes(&mut self, scopes: Vec<PathBuf>) {
+        self.scopes = scopes;
+    }
+
+    pub fn set_scan_filter(&mut self, scan_filter: ScanFilter) {
+        self.scan_filter = scan_filter;
+    }
+
+    pub fn set_scan_limits(&mut self, scan_limits: ScanLimits) {
+        self.scan_limits = scan_limits;
+    }
+
+    pub fn set_walk_options(&mut self, walk_options: WalkOptions) {
+        self.walk_options = walk_options;
+    }
+
+    pub fn set_submodule_policy(&mut self, submodule_policy: SubmodulePolicy) {
+        self.submodule_policy = submodule_policy;
+    }
+
+    pub fn set_source_matcher(&mut self, source_matcher: SourceFileMatcher) {
+        self.source_matcher = source_matcher;
+    }
+
+    pub fn set_file_size_limit(&mut self, file_size_limit: FileSizeLimit) {
+        self.file_size_limit = file_size_limit;
+    }
+
+    pub fn set_generated_detector(&mut self, generated_detector: GeneratedFileDetector) {
+        self.generated_detector = generated_detector;
+    }
+
+    /// Paths matching these patterns are kept in the tree but never summarized.
+    pub fn set_ignore_patterns(&mut self, ignore_patterns: IgnorePatterns) {
+        self.ignore_patterns = ignore_patterns;
+    }
+
+    /// Files matching these patterns are summarized from their path, size,
+    /// and identifiers, without sending their contents to the LLM.
+    pub fn set_private_patterns(&mut self, private_patterns: PrivatePatterns) {
+        self.private_patterns = private_patterns;
+    }
+
+    /// Whether API keys, tokens, private keys, and connection string
+    /// passwords are replaced in file content before it is summarized.
+    pub fn set_secret_redaction(&mut self, enabled: bool) {
+        self.secret_scanner = enabled.then(SecretScanner::default);
+    }
+
+    /// Maximum tokens of combined children summaries sent in one directory
+    /// prompt; larger directories are summarized map-reduce style.
+    pub fn set_directory_token_budget(&mut self, budget: u64) {
+        self.directory_token_budget = budget;
+    }
+
+    /// Files smaller than this many bytes get an extractive summary instead
+    /// of an LLM call (0 disables the threshold).
+    pub fn set_extractive_below_bytes(&mut self, bytes: u64) {
+        self.extractive_below_bytes = bytes;
+    }
+
+    /// Whether the LLM is sent each file's content or its outline.
+    pub fn set_summary_input(&mut self, summary_input: SummaryInput) {
+        self.summary_input = summary_input;
+    }
+
+    /// Re-summarizes each directory's entry points with the summaries of the
+    /// other files next to them, once those are done.
+    pub fn set_sibling_context(&mut self, sibling_context: bool) {
+        self.sibling_context = sibling_context;
+    }
+
+    /// Sends up to this many LLM requests at once: file prompts, and the
+    /// prompts of directories whose children are all summarized.
+    pub fn set_concurrency(&mut self, concurrency: usize) {
+        self.concurrency = concurrency;
+    }
+
+    /// A Rust library crate's public API, so the project summary describes
+    /// the API it actually has.
+    pub fn set_public_api(&mut self, public_api: Option<RustApi>) {
+        self.public_api = public_api.map(Arc::new);
+    }
+
+    /// Builds structural summaries of every file and directory without the
+    /// LLM. They are cached as extractive, so an online run replaces them.
+    pub fn set_offline(&mut self, offline: bool) {
+        self.offline = offline;
+    }
+
+    /// Stops the run with a `Cancelled` error once its LLM calls have spent
+    /// this many tokens.
+    pub fn set_token_budget(&mut self, token_budget: TokenBudget) {
+        self.llm_client.set_token_budget(token_budget);
+    }
+
+    /// Receives an event as each file and directory is processed.
+    pub fn set_progress(&mut self, progress: Option<Arc<dyn ProgressListener>>) {
+        self.progress = progress;
+    }
+
+    /// Stops the run at the next file or directory, or during an LLM call.
+    pub fn set_cancellation(&mut self, cancellation: CancellationToken) {
+        self.cancellation = cancellation;
+    }
+
+    /// Fails once cancelled, saying how much work is already cached.
+    fn check_cancelled(&self) -> Result<()> {
+        if self.cancellation.is_cancelled() {
+            return Err(self.cancelled_error("Run was cancelled"));
+        }
+        Ok(())
+    }
+
+    /// Records progress in this state and saves it to the cache after every
+    /// file and directory. Paths it lists as completed are not regenerated,
+    /// even with `force_regeneration`.
+    pub fn set_run_state(&mut self, run_state: Option<RunState>) {
+        self.run_state = run_state;
+    }
+
+    /// Marks a path pending in the run state as the scan finds it.
+    fn add_pending(&mut self, relative_path: PathBuf) -> Result<()> {
+        if let Some(state) = &mut self.run_state {
+            state.add_pending(relative_path);
+            self.run_state_changed()?;
+        }
+        Ok(())
+    }
+
+    fn record_completed(&mut self, relative_path: &Path) -> Result<()> {
+        if let Some(state) = &mut self.run_state {
+            state.record_completed(relative_path);
+            self.run_state_changed()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the run state once `RUN_STATE_SAVE_EVERY` changes or
+    /// `RUN_STATE_SAVE_INTERVAL` have passed since it was last written,
+    /// rather than rewriting the whole file for every path.
+    fn run_state_changed(&mut self) -> Result<()> {
+        self.unsaved_run_state += 1;
+        if self.unsaved_run_state >= RUN_STATE_SAVE_EVERY || self.run_state_saved_at.elapsed() >= RUN_STATE_SAVE_INTERVAL {
+            self.save_run_state()?;
+        }
+        Ok(())
+    }
+
+    fn save_run_state(&mut self) -> Result<()> {
+        if let Some(state) = &self.run_state {
+            self.cache_manager.save_run_state(state)?;
+        }
+        self.unsaved_run_state = 0;
+        self.run_state_saved_at = Instant::now();
+        Ok(())
+    }
+
+    /// Replaces secrets in a file's content and records what was replaced
+    /// in the run state.
+    fn redact_secrets(&mut self, relative_path: &Path, content: String) -> String {
+        let Some(scanner) = &self.secret_scanner else {
+            return content;
+        };
+        let (redacted, findings) = scanner.redact(&content);
+        if findings.is_empty() {
+            return content;
+        }
+
+        tracing::warn!("Redacted {} secret(s) in {} before summarizing it", findings.len(), relative_path.display());
+        if let Some(state) = &mut self.run_state {
+            state.record_redacted(relative_path, findings.iter().map(ToString::to_string).collect());
+        }
+        redacted
+    }
+
+    fn record_failed(&mut self, relative_path: &Path, error: &DocTreeError) {
+        if let Some(state) = &mut self.run_state {
+            state.record_failed(relative_path, error.to_string());
+        }
+    }
+
+    /// Whether to skip the cache for this path: only with force regeneration,
+    /// and not for paths a resumed run already completed.
+    fn is_forced(&self, relative_path: &Path) -> bool {
+        self.force_regeneration
+            && !self
+                .run_state
+                .as_ref()
+                .is_some_and(|state| state.completed.contains(relative_path))
+    }
+
+    /// Why the run stopped (cancelled, or out of tokens), with how far it got.
+    fn cancelled_error(&self, reason: &str) -> DocTreeError {
+        DocTreeError::cancelled(format!("{reason} after {} new summaries", self.generated))
+    }
+
+    fn emit_progress(&self, event: impl FnOnce(&dyn ProgressListener)) {
+        if let Some(progress) = &self.progress {
+            event(progress.as_ref());
+        }
+    }
+
+    /// Applies every scan and summarization setting from the configuration.
+    pub fn apply_config(&mut self, config: &Config, base_path: &Path) -> Result<()> {
+        self.set_directory_token_budget(config.directory_token_budget);
+        self.set_extractive_below_bytes(config.extractive_below_bytes);
+        self.set_summary_input(config.summary_input);
+        self.set_sibling_context(config.sibling_context);
+        self.set_concurrency(config.concurrency);
+        self.set_public_api(RustApi::detect(base_path));
+        self.set_offline(config.offline);
+        self.set_ignore_patterns(IgnorePatterns::new(&config.ignore_paths)?);
+        self.set_private_patterns(PrivatePatterns::new(&config.private_paths)?);
+        self.set_secret_redaction(config.redact_secrets);
+        self.set_scan_filter(ScanFilter::new(&config.include_patterns, &config.exclude_patterns)?);
+        self.set_scan_limits(config.scan_limits);
+        self.set_walk_options(config.walk_options);
+        self.set_submodule_policy(config.submodule_policy.clone());
+        self.set_source_matcher(SourceFileMatcher::new(&config.extra_extensions, &config.extra_filenames)?);
+        self.set_file_size_limit(config.file_size_limit);
+        self.set_generated_detector(GeneratedFileDetector::from_config(config, base_path)?);
+        Ok(())
+    }
+
     pub async fn generate_project_summary(&mut self, base_path: &Path) -> Result<String> {
-        log::info!("Starting hierarchical summarization for: {}", base_path.display());
+        tracing::info!("Starting hierarchical summarization for: {}", base_path.display());
 
         // Initialize cache directory
         self.cache_manager.initialize_cache_directory()?;
 
-        // Scan directory structure
-        let scanner = DirectoryScanner::new(base_path.to_path_buf());
-        let mut root_node = scanner.scan_directory()?;
-
-        // Generate summaries in bottom-up fashion (post-order traversal)
-        self.summarize_tree(&mut root_node, base_path).await?;
-
-        // Cache is saved incrementally during processing
+        // Summaries are generated bottom-up while the project is scanned,
+        // and cached as they are made
+        let root_node = self.summarize_streaming(base_path, true).await?;
 
         // Return root-level summary
         root_node.summary.ok_or_else(|| {
@@ -46,44 +635,312 @@ impl HierarchicalSummarizer {
         })
     }
 
-    fn summarize_tree<'a>(
-        &'a mut self,
-        node: &'a mut FileNode,
-        base_path: &'a Path,
-    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + 'a>> {
-        Box::pin(async move {
-            if node.is_directory {
-                // First, recursively process all children
-                for child in &mut node.children {
-                    self.summarize_tree(child, base_path).await?;
+    /// Scans the project with the configured filter, limits, and walk options.
+    pub fn scan_tree(&self, base_path: &Path) -> Result<FileNode> {
+        self.scanner(base_path).scan_directory()
+    }
+
+    fn scanner(&self, base_path: &Path) -> DirectoryScanner {
+        let mut scanner = DirectoryScanner::new(base_path.to_path_buf());
+        scanner.set_filter(self.scan_filter.clone());
+        scanner.set_limits(self.scan_limits);
+        scanner.set_walk_options(self.walk_options);
+        scanner.set_submodule_policy(self.submodule_policy.clone());
+        scanner
+    }
+
+    pub(crate) fn cache_manager_mut(&mut self) -> &mut CacheManager {
+        &mut self.cache_manager
+    }
+
+    pub(crate) fn force_regeneration(&self) -> bool {
+        self.force_regeneration
+    }
+
+    /// A cached directory summary for this hash. Extractive ones only count
+    /// offline, so they are regenerated once the LLM is available.
+    pub(crate) fn cached_directory_summary(&self, path: &Path, content_hash: &str) -> Option<String> {
+        self.cache_manager
+            .get_cached_entry(path, content_hash)
+            .filter(|entry| self.offline || entry.method == SummaryMethod::Llm)
+            .map(|entry| entry.summary)
+    }
+
+    pub(crate) fn store_directory_summary(
+        &mut self,
+        path: &Path,
+        content_hash: String,
+        summary: String,
+        role: DirectoryRole,
+    ) -> Result<()> {
+        if self.offline {
+            self.cache_manager.store_extractive_directory_summary(path, content_hash, summary, role)
+        } else {
+            self.cache_manager.store_directory_summary(path, content_hash, summary, role)
+        }
+    }
+
+    /// Summarizes the project while it is scanned. Each file is hashed as
+    /// soon as the walk finds it, and each directory is summarized as soon
+    /// as the walk has left it and its last child is summarized, so work
+    /// starts long before the scan ends. Prompts that go to the LLM wait
+    /// for the end of the scan, since they name the files that import each
+    /// file. At most `concurrency` requests are in flight at once. Without
+    /// `directories` only files are summarized. Returns the project root
+    /// with the summarized tree under it.
+    pub(crate) async fn summarize_streaming(&mut self, base_path: &Path, directories: bool) -> Result<FileNode> {
+        if let Some(state) = &mut self.run_state {
+            state.begin(Vec::new());
+        }
+        self.save_run_state()?;
+        let summarized = self.run_pipeline(base_path, directories).await;
+        // Written however the run ends, so a cancelled or failed run can be
+        // resumed from where it stopped
+        let saved = self.save_run_state();
+        let root = summarized?;
+        saved.map(|()| root)
+    }
+
+    async fn run_pipeline(&mut self, base_path: &Path, directories: bool) -> Result<FileNode> {
+        let scanner = self.scanner(base_path);
+        let mut events = scanner.scan_events()?;
+        let mut pipeline = Pipeline::new(base_path, directories, self.concurrency);
+        if directories {
+            self.add_pending(PathBuf::new())?;
+        }
+
+        let mut scanning = true;
+        loop {
+            if let Some(root) = pipeline.root.take() {
+                return Ok(root);
+            }
+            self.check_cancelled()?;
+            if !scanning {
+                match pipeline.tasks.join_next().await {
+                    Some(finished) => self.on_finished(&mut pipeline, finished)?,
+                    None => return Err(DocTreeError::summarizer("Summarization stopped before reaching the project root")),
                 }
+                continue;
+            }
 
-                // Then generate summary for this directory
-                self.summarize_directory(node, base_path).await
-            } else {
-                // Generate summary for file
-                self.summarize_file(node, base_path).await
+            match events.next() {
+                Some(event) => self.on_scan_event(&mut pipeline, event?)?,
+                None => {
+                    scanning = false;
+                    self.on_scan_finished(&mut pipeline)?;
+                }
             }
-        })
+            while let Some(finished) = pipeline.tasks.try_join_next() {
+                self.on_finished(&mut pipeline, finished)?;
+            }
+            // Lets the tasks make progress between entries on a
+            // single-threaded runtime
+            tokio::task::yield_now().await;
+        }
     }
 
-    async fn summarize_file(&mut self, node: &mut FileNode, base_path: &Path) -> Result<()> {
-        if !node.is_source_code_file() {
-            log::debug!("Skipping non-source file: {}", node.path.display());
+    fn on_scan_event(&mut self, pipeline: &mut Pipeline, event: ScanEvent) -> Result<()> {
+        match event {
+            ScanEvent::Node(node) => self.on_scanned(pipeline, node),
+            ScanEvent::DirectoryDone(path) => {
+                let Some(directory) = pipeline.open.get_mut(&path) else {
+                    return Ok(());
+                };
+                directory.scanned = true;
+                self.start_if_ready(pipeline, &path)
+            }
+        }
+    }
+
+    /// Takes in a node as the walk finds it, before any of its children.
+    fn on_scanned(&mut self, pipeline: &mut Pipeline, mut node: FileNode) -> Result<()> {
+        let relative_path = node.get_relative_path(&pipeline.base_path)?;
+        if !node.is_directory {
+            pipeline.scanned_files.insert(relative_path.clone());
+        }
+        if node.path.parent().is_some_and(|parent| pipeline.skipped.contains(parent)) {
+            if node.is_directory {
+                pipeline.skipped.insert(node.path);
+            }
             return Ok(());
         }
 
-        log::debug!("Processing file: {}", node.path.display());
+        // Out-of-scope directories keep their cached summary as a whole,
+        // unless only files are summarized
+        if (pipeline.directories || !node.is_directory) && !self.in_scope(&node, &pipeline.base_path)? {
+            self.reuse_cached_summary(&mut node);
+            pipeline.attach(node);
+            return Ok(());
+        }
+        if let Some(submodule) = node.submodule.clone() {
+            self.summarize_submodule(&mut node, &submodule, &pipeline.base_path)?;
+            pipeline.attach(node);
+            return Ok(());
+        }
 
-        // Compute file hash
-        let content_hash = FileHasher::compute_file_hash(&node.path)?;
-        node.content_hash = Some(content_hash.clone());
+        if node.is_directory {
+            if pipeline.directories {
+                self.add_pending(relative_path)?;
+            }
+            pipeline.open_directory(node);
+            return Ok(());
+        }
+        if !node.is_source_file(&self.source_matcher) {
+            tracing::debug!("Skipping non-source file: {}", node.path.display());
+            pipeline.attach(node);
+            return Ok(());
+        }
+        if self.ignore_patterns.is_ignored(&relative_path) {
+            tracing::debug!("Skipping ignored path: {}", relative_path.display());
+            pipeline.attach(node);
+            return Ok(());
+        }
 
-        // Check cache first (unless force regeneration is enabled)
+        self.emit_progress(|progress| progress.on_file_discovered(&relative_path));
+        self.add_pending(relative_path)?;
+        pipeline.expect_child(&node.path);
         if !self.force_regeneration {
-            if let Some(cached_summary) = self.cache_manager.get_cached_summary(&node.path, &content_hash) {
-                node.summary = Some(cached_summary);
-                return Ok(());
+            if let Some(content_hash) = self.cache_manager.unchanged_hash(&node.path) {
+                return self.file_hashed(pipeline, node, content_hash);
+            }
+        }
+        pipeline.hash(self.cache_manager.source_hasher(), node);
+        Ok(())
+    }
+
+    /// Resolves the imports between the scanned files and sends the prompts
+    /// that waited for them. Offline runs make no prompts, so they skip it.
+    fn on_scan_finished(&mut self, pipeline: &mut Pipeline) -> Result<()> {
+        if !self.offline {
+            let graph = DependencyGraph::from_files(&pipeline.base_path, &pipeline.scanned_files);
+            tracing::debug!("Resolved imports between {} files", graph.edges().count());
+            self.dependency_graph = Some(graph);
+        }
+        for parked in pipeline.awaiting_graph.take().unwrap_or_default() {
+            match parked {
+                Parked::File(node, prompt) => self.spawn_file(pipeline, node, prompt),
+                Parked::Siblings(node) => self.start_siblings(pipeline, node)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn on_finished(&mut self, pipeline: &mut Pipeline, finished: std::result::Result<Finished, JoinError>) -> Result<()> {
+        match finished.map_err(|e| DocTreeError::summarizer(format!("Summarization task failed: {e}")))? {
+            Finished::Hashed { node, hashed } => {
+                let content_hash = self.cache_manager.record_hash(&node.path, hashed?);
+                self.file_hashed(pipeline, node, content_hash)
+            }
+            Finished::File { node, prompt, generated } => self.finish_file(pipeline, node, prompt, generated),
+            Finished::Siblings { node, siblings_hash, generated } => {
+                self.finish_siblings(pipeline, node, siblings_hash, generated)
+            }
+            Finished::Directory { node, prompt, generated } => self.finish_directory(pipeline, node, prompt, generated),
+        }
+    }
+
+    /// Starts on a directory once the walk has left it and its last child
+    /// is summarized.
+    fn start_if_ready(&mut self, pipeline: &mut Pipeline, path: &Path) -> Result<()> {
+        let Some(mut node) = pipeline.take_ready(path) else {
+            return Ok(());
+        };
+        node.sort_children();
+        if !pipeline.directories {
+            return self.child_done(pipeline, node);
+        }
+        if self.sibling_context && !self.offline {
+            match &mut pipeline.awaiting_graph {
+                Some(parked) => parked.push(Parked::Siblings(node)),
+                None => self.start_siblings(pipeline, node)?,
+            }
+            return Ok(());
+        }
+        self.start_directory(pipeline, node)
+    }
+
+    /// Records a summarized file or directory as completed and hands it to
+    /// its directory, or finishes the run when it is the root.
+    fn complete(&mut self, pipeline: &mut Pipeline, node: FileNode) -> Result<()> {
+        self.record_completed(&node.get_relative_path(&pipeline.base_path)?)?;
+        self.child_done(pipeline, node)
+    }
+
+    fn child_done(&mut self, pipeline: &mut Pipeline, node: FileNode) -> Result<()> {
+        match pipeline.child_done(node) {
+            Some(parent) => self.start_if_ready(pipeline, &parent),
+            None => Ok(()),
+        }
+    }
+
+    fn file_hashed(&mut self, pipeline: &mut Pipeline, mut node: FileNode, content_hash: String) -> Result<()> {
+        if let Some(twins) = pipeline.twins.get_mut(&content_hash) {
+            node.content_hash = Some(content_hash);
+            twins.push(node);
+            return Ok(());
+        }
+        let relative_path = node.get_relative_path(&pipeline.base_path)?;
+        let span = tracing::info_span!("file", path = %relative_path.display());
+        let Some(prompt) = span.in_scope(|| self.prepare_file(&mut node, &pipeline.base_path, content_hash))? else {
+            return self.complete(pipeline, node);
+        };
+        pipeline.twins.insert(prompt.content_hash.clone(), Vec::new());
+        match &mut pipeline.awaiting_graph {
+            Some(parked) => parked.push(Parked::File(node, prompt)),
+            None => self.spawn_file(pipeline, node, prompt),
+        }
+        Ok(())
+    }
+
+    /// Sends a file's prompt to the LLM on a task of its own.
+    fn spawn_file(&self, pipeline: &mut Pipeline, node: FileNode, prompt: FilePrompt) {
+        let context = self.dependency_graph.as_ref().and_then(|graph| graph.prompt_context(&prompt.relative_path));
+        let llm_client = self.llm_client.clone();
+        let cancellation = self.cancellation.clone();
+        let permits = pipeline.llm_permits.clone();
+        let span = tracing::info_span!("file", path = %prompt.relative_path.display());
+        pipeline.tasks.spawn(
+            async move {
+                let _permit = permits.acquire_owned().await.expect("LLM permits are never closed");
+                let request = request_file_summary(&llm_client, &prompt.relative_path, &prompt.llm_input, context.as_deref());
+                let generated = cancellation.run(request).await;
+                Finished::File { node, prompt, generated }
+            }
+            .instrument(span),
+        );
+    }
+
+    /// Everything a file's summary takes short of an LLM request: pinned,
+    /// cached, and reused summaries, and the summaries made without the
+    /// LLM. Returns the prompt when the LLM is still needed.
+    fn prepare_file(&mut self, node: &mut FileNode, base_path: &Path, content_hash: String) -> Result<Option<FilePrompt>> {
+        let relative_path = node.get_relative_path(base_path)?;
+        tracing::debug!("Processing file: {}", node.path.display());
+        node.content_hash = Some(content_hash.clone());
+
+        // Pinned summaries are curated by humans and always win, even over --force
+        if let Some(pinned) = self.pinned_summary(node, &content_hash) {
+            node.summary = Some(pinned);
+            self.emit_progress(|progress| progress.on_cache_hit(&relative_path));
+            return Ok(None);
+        }
+
+        // Check cache first (unless force regeneration is enabled). Extractive
+        // summaries are always retried so they get upgraded once the LLM can
+        // make one, and private files only keep structure-only summaries.
+        let private = self.private_patterns.is_private(&relative_path);
+        if !self.is_forced(&relative_path) {
+            if let Some(cached) = self.cache_manager.get_cached_entry(&node.path, &content_hash) {
+                let usable = if private {
+                    cached.method == SummaryMethod::StructureOnly
+                } else {
+                    self.offline || cached.method == SummaryMethod::Llm
+                };
+                if usable {
+                    node.summary = Some(cached.summary);
+                    self.emit_progress(|progress| progress.on_cache_hit(&relative_path));
+                    return Ok(None);
+                }
             }
         }
 
@@ -91,42 +948,343 @@ impl HierarchicalSummarizer {
         let content = match fs::read_to_string(&node.path) {
             Ok(content) => {
                 if content.trim().is_empty() {
-                    log::debug!("Skipping empty file: {}", node.path.display());
-                    return Ok(());
+                    tracing::debug!("Skipping empty file: {}", node.path.display());
+                    return Ok(None);
                 }
                 content
             }
             Err(e) => {
-                log::warn!("Failed to read file {}: {}", node.path.display(), e);
-                return Ok(());
+                tracing::warn!("Failed to read file {}: {}", node.path.display(), e);
+                return Ok(None);
             }
         };
 
-        // Generate summary using LLM
-        let relative_path = node.get_relative_path(base_path)?;
-        match self.llm_client.generate_file_summary(&relative_path, &content).await {
-            Ok(summary) => {
+        if let Some(reason) = self.generated_detector.check(&relative_path, &content) {
+            tracing::debug!("Skipping {} ({reason})", relative_path.display());
+            node.content_hash = None;
+            return Ok(None);
+        }
+
+        let content = match apply_annotations(&content) {
+            AnnotatedContent::Ignored => {
+                tracing::debug!("Skipping file marked doctreeai:ignore: {}", relative_path.display());
+                node.content_hash = None;
+                return Ok(None);
+            }
+            AnnotatedContent::Content(content) => content,
+        };
+        let content = self.redact_secrets(&relative_path, content);
+
+        if private {
+            let summary = structure_only_summary(&relative_path, &content);
+            node.summary = Some(summary.clone());
+            self.cache_manager.store_structure_only_summary(&node.path, content_hash, summary)?;
+            tracing::info!("Described private file from its structure only: {}", relative_path.display());
+            self.generated += 1;
+            self.emit_progress(|progress| progress.on_file_summarized(&relative_path, SummaryMethod::StructureOnly));
+            return Ok(None);
+        }
+
+        if !self.is_forced(&relative_path) && self.reuse_identical_summary(node, base_path, &content_hash)? {
+            return Ok(None);
+        }
+
+        let original_size = content.len();
+        let Some(content) = self.file_size_limit.apply(content) else {
+            tracing::info!(
+                "Skipping {} ({original_size} bytes exceeds the file size limit)",
+                relative_path.display()
+            );
+            node.content_hash = None;
+            return Ok(None);
+        };
+        if content.len() < original_size {
+            tracing::info!(
+                "Summarizing {} from head and tail excerpts ({original_size} bytes exceeds the file size limit)",
+                relative_path.display()
+            );
+        }
+
+        self.emit_progress(|progress| progress.on_file_start(&relative_path));
+
+        // Small models get trivial files described without an LLM call
+        if self.llm_client.is_small_model() {
+            if let Some(summary) = extractive_summary(&relative_path, &content) {
+                node.summary = Some(summary.clone());
+                self.cache_manager.store_extractive_summary(&node.path, content_hash, summary)?;
+                tracing::info!("Described trivial file without the LLM: {}", relative_path.display());
+                self.generated += 1;
+                self.emit_progress(|progress| progress.on_file_summarized(&relative_path, SummaryMethod::Extractive));
+                return Ok(None);
+            }
+        }
+
+        if (original_size as u64) < self.extractive_below_bytes {
+            let summary = heuristic_summary(&relative_path, &content);
+            node.summary = Some(summary.clone());
+            self.cache_manager.store_extractive_summary(&node.path, content_hash, summary)?;
+            tracing::info!("Described small file without the LLM: {}", relative_path.display());
+            self.generated += 1;
+            self.emit_progress(|progress| progress.on_file_summarized(&relative_path, SummaryMethod::Extractive));
+            return Ok(None);
+        }
+
+        if self.offline {
+            let summary = structural_summary(&relative_path, &content);
+            node.summary = Some(summary.clone());
+            self.cache_manager.store_extractive_summary(&node.path, content_hash, summary)?;
+            tracing::info!("Described file from its structure (offline): {}", relative_path.display());
+            self.generated += 1;
+            self.emit_progress(|progress| progress.on_file_summarized(&relative_path, SummaryMethod::Extractive));
+            return Ok(None);
+        }
+
+        let outline = (self.summary_input == SummaryInput::Outline)
+            .then(|| extract_outline(&relative_path, &content))
+            .flatten();
+        let llm_input = outline.unwrap_or_else(|| content.clone());
+        Ok(Some(FilePrompt { relative_path, content_hash, llm_input, content }))
+    }
+
+    fn finish_file(
+        &mut self,
+        pipeline: &mut Pipeline,
+        mut node: FileNode,
+        prompt: FilePrompt,
+        generated: GeneratedSummary,
+    ) -> Result<()> {
+        let twins = pipeline.twins.remove(&prompt.content_hash).unwrap_or_default();
+        let FilePrompt { relative_path, content_hash, content, .. } = prompt;
+        let method = match generated {
+            Err(DocTreeError::Cancelled(reason)) => return Err(self.cancelled_error(&reason)),
+            Ok((summary, structured)) => {
                 node.summary = Some(summary.clone());
                 // Store in cache
-                self.cache_manager.store_summary(&node.path, content_hash, summary)?;
-                log::info!("Generated summary for: {}", relative_path.display());
+                match structured {
+                    Some(structured) => self.cache_manager.store_structured_summary(&node.path, content_hash, structured)?,
+                    None => self.cache_manager.store_summary(&node.path, content_hash, summary)?,
+                }
+                tracing::info!("Generated summary for: {}", relative_path.display());
+                SummaryMethod::Llm
             }
             Err(e) => {
-                log::error!("Failed to generate summary for {}: {}", relative_path.display(), e);
-                // Continue processing other files even if one fails
+                // Fall back to an extractive summary so the rest of the tree
+                // still has something to roll up; it is retried next run
+                tracing::warn!(
+                    "Failed to generate summary for {}, using an extractive summary: {}",
+                    relative_path.display(),
+                    e
+                );
+                self.record_failed(&relative_path, &e);
+                let summary = heuristic_summary(&relative_path, &content);
+                node.summary = Some(summary.clone());
+                self.cache_manager.store_extractive_summary(&node.path, content_hash, summary)?;
+                SummaryMethod::Extractive
             }
-        }
+        };
+        self.generated += 1;
+        self.emit_progress(|progress| progress.on_file_summarized(&relative_path, method));
+        self.complete(pipeline, node)?;
 
+        // Usually reuse this file's summary now that it is cached
+        for twin in twins {
+            let content_hash = twin.content_hash.clone().expect("twins wait with their hash");
+            self.file_hashed(pipeline, twin, content_hash)?;
+        }
         Ok(())
     }
 
-    async fn summarize_directory(&mut self, node: &mut FileNode, base_path: &Path) -> Result<()> {
+    /// Copies the summary of an already summarized file with the same
+    /// contents, with its path and name replaced by this file's.
+    fn reuse_identical_summary(&mut self, node: &mut FileNode, base_path: &Path, content_hash: &str) -> Result<bool> {
+        let Some(twin) = self.cache_manager.find_identical(&node.path, content_hash) else {
+            return Ok(false);
+        };
         let relative_path = node.get_relative_path(base_path)?;
-        log::debug!("Processing directory: {}", relative_path.display());
+        let twin_relative = twin.source_path.strip_prefix(base_path).unwrap_or(&twin.source_path).to_path_buf();
+        let substitute = path_substitution(&twin_relative, &relative_path);
+
+        let summary = substitute(&twin.summary);
+        node.summary = Some(summary.clone());
+        match twin.structured {
+            Some(structured) => self.cache_manager.store_structured_summary(
+                &node.path,
+                content_hash.to_string(),
+                structured.map_text(&substitute),
+            )?,
+            None => self.cache_manager.store_summary(&node.path, content_hash.to_string(), summary)?,
+        }
+        tracing::info!("Reused the summary of identical file {} for {}", twin_relative.display(), relative_path.display());
+        self.emit_progress(|progress| progress.on_cache_hit(&relative_path));
+        Ok(true)
+    }
+
+    /// Second pass over a directory: its entry points (`mod.rs`,
+    /// `index.ts`, ...) are summarized again with the other children's
+    /// summaries as context, so they say how the directory fits together
+    /// before it is rolled up. The directory is summarized once the pass
+    /// is done.
+    fn start_siblings(&mut self, pipeline: &mut Pipeline, node: FileNode) -> Result<()> {
+        let Some((siblings_hash, prompts)) = self.prepare_siblings(&node, &pipeline.base_path)? else {
+            return self.start_directory(pipeline, node);
+        };
+        let llm_client = self.llm_client.clone();
+        let cancellation = self.cancellation.clone();
+        let permits = pipeline.llm_permits.clone();
+        pipeline.tasks.spawn(async move {
+            let mut generated = Vec::with_capacity(prompts.len());
+            for prompt in prompts {
+                let _permit = permits.clone().acquire_owned().await.expect("LLM permits are never closed");
+                let request =
+                    request_file_summary(&llm_client, &prompt.relative_path, &prompt.llm_input, Some(&prompt.context));
+                let result = cancellation.run(request).await;
+                generated.push((prompt, result));
+            }
+            Finished::Siblings { node, siblings_hash, generated }
+        });
+        Ok(())
+    }
+
+    /// The prompts for a directory's entry points whose cached summary was
+    /// not yet made with the current siblings, and the siblings' hash. The
+    /// hash is cached with the result, so the pass only runs again when a
+    /// sibling's summary changes.
+    fn prepare_siblings(&mut self, node: &FileNode, base_path: &Path) -> Result<Option<(String, Vec<SiblingPrompt>)>> {
+        let is_entry_point = |child: &FileNode| {
+            !child.is_directory
+                && child
+                    .path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| ChildImportance::of_file(name) == ChildImportance::EntryPoint)
+        };
+
+        let siblings: Vec<String> = node
+            .children
+            .iter()
+            .filter(|child| !is_entry_point(child))
+            .filter_map(|child| {
+                let name = child.path.file_name()?.to_str()?;
+                let summary = child.summary.as_ref()?;
+                Some(if child.is_directory { format!("- {name}/: {summary}") } else { format!("- {name}: {summary}") })
+            })
+            .take(MAX_SIBLING_SUMMARIES)
+            .collect();
+        if siblings.is_empty() {
+            return Ok(None);
+        }
+        let siblings_context = format!("Other files in this directory:\n{}", siblings.join("\n"));
+        let siblings_hash = FileHasher::compute_content_hash(&siblings_context);
+
+        let mut prompts = Vec::new();
+        for (index, child) in node.children.iter().enumerate().filter(|(_, child)| is_entry_point(child)) {
+            let (Some(content_hash), Some(_)) = (child.content_hash.clone(), child.summary.as_ref()) else {
+                continue;
+            };
+            let Some(entry) = self.cache_manager.get_cached_entry(&child.path, &content_hash) else {
+                continue;
+            };
+            if entry.pinned || entry.method != SummaryMethod::Llm || entry.sibling_context.as_ref() == Some(&siblings_hash) {
+                continue;
+            }
+
+            let relative_path = child.get_relative_path(base_path)?;
+            let Some(llm_input) = self.prompt_input(&child.path, &relative_path) else {
+                continue;
+            };
+            let context = match self.dependency_graph.as_ref().and_then(|graph| graph.prompt_context(&relative_path)) {
+                Some(dependencies) => format!("{dependencies}\n{siblings_context}"),
+                None => siblings_context.clone(),
+            };
+            prompts.push(SiblingPrompt { child: index, relative_path, content_hash, llm_input, context });
+        }
+        Ok((!prompts.is_empty()).then_some((siblings_hash, prompts)))
+    }
+
+    fn finish_siblings(
+        &mut self,
+        pipeline: &mut Pipeline,
+        mut node: FileNode,
+        siblings_hash: String,
+        generated: Vec<(SiblingPrompt, GeneratedSummary)>,
+    ) -> Result<()> {
+        for (prompt, result) in generated {
+            let child = &mut node.children[prompt.child];
+            match result {
+                Err(DocTreeError::Cancelled(reason)) => return Err(self.cancelled_error(&reason)),
+                Ok((summary, structured)) => {
+                    child.summary = Some(summary.clone());
+                    match structured {
+                        Some(structured) => {
+                            self.cache_manager.store_structured_summary(&child.path, prompt.content_hash, structured)?
+                        }
+                        None => self.cache_manager.store_summary(&child.path, prompt.content_hash, summary)?,
+                    }
+                    self.cache_manager.set_sibling_context(&child.path, siblings_hash.clone())?;
+                    self.generated += 1;
+                    tracing::info!("Summarized {} with its siblings as context", prompt.relative_path.display());
+                }
+                // The first-pass summary is still good, so it is kept
+                Err(e) => tracing::warn!("Failed to summarize {} with its siblings: {e}", prompt.relative_path.display()),
+            }
+        }
+        self.start_directory(pipeline, node)
+    }
+
+    /// What the LLM is sent for a file that was already summarized once:
+    /// its annotated and redacted content cut to the size limit, or its
+    /// outline.
+    fn prompt_input(&mut self, path: &Path, relative_path: &Path) -> Option<String> {
+        let content = fs::read_to_string(path).ok()?;
+        let AnnotatedContent::Content(content) = apply_annotations(&content) else {
+            return None;
+        };
+        let content = self.redact_secrets(relative_path, content);
+        let content = self.file_size_limit.apply(content)?;
+        let outline = (self.summary_input == SummaryInput::Outline)
+            .then(|| extract_outline(relative_path, &content))
+            .flatten();
+        Some(outline.unwrap_or(content))
+    }
+
+    /// Summarizes a directory whose children are all summarized, on a task
+    /// of its own when it takes the LLM.
+    fn start_directory(&mut self, pipeline: &mut Pipeline, mut node: FileNode) -> Result<()> {
+        let relative_path = node.get_relative_path(&pipeline.base_path)?;
+        let span = tracing::info_span!("directory", path = %relative_path.display());
+        let Some(prompt) = span.in_scope(|| self.prepare_directory(&mut node, relative_path, &pipeline.base_path))? else {
+            return self.complete(pipeline, node);
+        };
+        let reducer = self.reducer();
+        let cancellation = self.cancellation.clone();
+        let permits = pipeline.llm_permits.clone();
+        pipeline.tasks.spawn(
+            async move {
+                let _permit = permits.acquire_owned().await.expect("LLM permits are never closed");
+                let request = reducer.reduce(&prompt.directory_name, &prompt.children_summaries, prompt.level);
+                let generated = cancellation.run(request).await;
+                Finished::Directory { node, prompt, generated }
+            }
+            .instrument(span),
+        );
+        Ok(())
+    }
+
+    /// Everything a directory's summary takes short of an LLM request:
+    /// pinned and cached summaries. Returns the prompt when the LLM is
+    /// still needed.
+    fn prepare_directory(
+        &mut self,
+        node: &mut FileNode,
+        relative_path: PathBuf,
+        base_path: &Path,
+    ) -> Result<Option<DirectoryPrompt>> {
+        tracing::debug!("Processing directory: {}", relative_path.display());
+
+        // Collect summaries from children, most important first so they
+        // survive if the prompt has to be split or truncated
+        let mut ranked_summaries = Vec::new();
 
-        // Collect summaries from children
-        let mut children_summaries = Vec::new();
-        
         for child in &node.children {
             if let Some(ref summary) = child.summary {
                 let child_relative_path = child.get_relative_path(base_path)?;
@@ -134,20 +1292,37 @@ impl HierarchicalSummarizer {
                     .file_name()
                     .and_then(|n| n.to_str())
                     .unwrap_or("unknown");
-                
-                let formatted_summary = if child.is_directory {
-                    format!("**{child_name}/** (directory): {summary}")
+
+                let (importance, formatted_summary) = if child.is_directory {
+                    let role = self
+                        .cache_manager
+                        .get_cache_summary(&child.path)
+                        .and_then(|entry| entry.role)
+                        .unwrap_or_else(|| DirectoryClassifier::classify(child_name, summary));
+                    (
+                        ChildImportance::of_directory(role),
+                        format!("**{child_name}/** (directory, {role}): {summary}"),
+                    )
                 } else {
-                    format!("**{child_name}**: {summary}")
+                    let importance = ChildImportance::of_file(child_name);
+                    let formatted_summary = match importance.label() {
+                        Some(label) => format!("**{child_name}** ({label}): {summary}"),
+                        None => format!("**{child_name}**: {summary}"),
+                    };
+                    (importance, formatted_summary)
                 };
-                
-                children_summaries.push(formatted_summary);
+
+                ranked_summaries.push((importance, Reverse(subtree_size(child)), formatted_summary));
             }
         }
 
+        ranked_summaries.sort();
+        let children_summaries: Vec<String> =
+            ranked_summaries.into_iter().map(|(_, _, summary)| summary).collect();
+
         if children_summaries.is_empty() {
-            log::debug!("No summarizable content in directory: {}", relative_path.display());
-            return Ok(());
+            tracing::debug!("No summarizable content in directory: {}", relative_path.display());
+            return Ok(None);
         }
 
         // Compute directory hash based on children hashes
@@ -155,15 +1330,24 @@ impl HierarchicalSummarizer {
             .iter()
             .filter_map(|child| child.content_hash.clone())
             .collect();
-        
+
         let directory_hash = FileHasher::compute_directory_hash(&children_hashes);
         node.content_hash = Some(directory_hash.clone());
 
+        if let Some(pinned) = self.pinned_summary(node, &directory_hash) {
+            node.summary = Some(pinned);
+            self.emit_progress(|progress| progress.on_cache_hit(&relative_path));
+            self.emit_progress(|progress| progress.on_directory_done(&relative_path));
+            return Ok(None);
+        }
+
         // Check cache for directory summary
-        if !self.force_regeneration {
-            if let Some(cached_summary) = self.cache_manager.get_cached_summary(&node.path, &directory_hash) {
+        if !self.is_forced(&relative_path) {
+            if let Some(cached_summary) = self.cached_directory_summary(&node.path, &directory_hash) {
                 node.summary = Some(cached_summary);
-                return Ok(());
+                self.emit_progress(|progress| progress.on_cache_hit(&relative_path));
+                self.emit_progress(|progress| progress.on_directory_done(&relative_path));
+                return Ok(None);
             }
         }
 
@@ -171,26 +1355,157 @@ impl HierarchicalSummarizer {
         let directory_name = relative_path
             .file_name()
             .and_then(|n| n.to_str())
-            .unwrap_or("project root");
+            .unwrap_or("project root")
+            .to_string();
+
+        let level = if relative_path.as_os_str().is_empty() { SummaryLevel::Root } else { SummaryLevel::Directory };
+        Ok(Some(DirectoryPrompt { relative_path, directory_name, directory_hash, children_summaries, level }))
+    }
 
-        match self.llm_client.generate_directory_summary(directory_name, &children_summaries).await {
+    fn finish_directory(
+        &mut self,
+        pipeline: &mut Pipeline,
+        mut node: FileNode,
+        prompt: DirectoryPrompt,
+        generated: Result<String>,
+    ) -> Result<()> {
+        let DirectoryPrompt { relative_path, directory_name, directory_hash, children_summaries, .. } = prompt;
+        match generated {
+            Err(DocTreeError::Cancelled(reason)) => return Err(self.cancelled_error(&reason)),
             Ok(summary) => {
                 node.summary = Some(summary.clone());
+                self.generated += 1;
+                let role = DirectoryClassifier::classify(&directory_name, &summary);
                 // Store in cache
-                self.cache_manager.store_summary(&node.path, directory_hash, summary)?;
-                log::info!("Generated directory summary for: {}", relative_path.display());
+                self.store_directory_summary(&node.path, directory_hash, summary, role)?;
+                tracing::info!("Generated directory summary for: {} ({role})", relative_path.display());
             }
             Err(e) => {
-                log::error!("Failed to generate directory summary for {}: {}", relative_path.display(), e);
+                tracing::error!("Failed to generate directory summary for {}: {}", relative_path.display(), e);
+                self.record_failed(&relative_path, &e);
                 // Fall back to concatenating children summaries
                 let fallback_summary = format!("Contains: {}", children_summaries.join(", "));
                 node.summary = Some(fallback_summary);
             }
         }
+        self.emit_progress(|progress| progress.on_directory_done(&relative_path));
+        self.complete(pipeline, node)
+    }
+
+    /// Opaque submodules get a fixed "external dependency" summary instead of
+    /// an LLM call; the hash changes only with the submodule's URL.
+    fn summarize_submodule(&mut self, node: &mut FileNode, submodule: &Submodule, base_path: &Path) -> Result<()> {
+        let relative_path = node.get_relative_path(base_path)?;
+        let summary = submodule.summary();
+        let content_hash = FileHasher::compute_content_hash(&summary);
 
+        if self.cache_manager.get_cached_summary(&node.path, &content_hash).is_none() {
+            let name = relative_path.file_name().and_then(|n| n.to_str()).unwrap_or("submodule");
+            let role = DirectoryClassifier::classify(name, &summary);
+            self.cache_manager.store_directory_summary(&node.path, content_hash.clone(), summary.clone(), role)?;
+            tracing::info!("Recorded submodule as external dependency: {}", relative_path.display());
+        }
+
+        node.content_hash = Some(content_hash);
+        node.summary = Some(summary);
         Ok(())
     }
 
+    fn in_scope(&self, node: &FileNode, base_path: &Path) -> Result<bool> {
+        if self.scopes.is_empty() {
+            return Ok(true);
+        }
+        let relative_path = node.get_relative_path(base_path)?;
+        Ok(self
+            .scopes
+            .iter()
+            .any(|scope| relative_path.starts_with(scope) || scope.starts_with(&relative_path)))
+    }
+
+    /// Takes an out-of-scope node's summary and hash from the cache without
+    /// rehashing, so its ancestors can still be re-rolled.
+    fn reuse_cached_summary(&self, node: &mut FileNode) {
+        if let Some(entry) = self.cache_manager.get_cache_summary(&node.path) {
+            node.content_hash = Some(entry.content_hash);
+            node.summary = Some(entry.summary);
+        }
+    }
+
+    /// Returns the pinned summary for a node, warning when the source has
+    /// drifted substantially since the summary was curated.
+    fn pinned_summary(&self, node: &FileNode, content_hash: &str) -> Option<String> {
+        let entry = self.cache_manager.get_cache_summary(&node.path)?;
+        if !entry.pinned {
+            return None;
+        }
+
+        if entry.content_hash != content_hash {
+            let drift = fs::metadata(&node.path)
+                .ok()
+                .filter(|metadata| metadata.is_file())
+                .and_then(|metadata| entry.size_drift(metadata.len()));
+
+            match drift {
+                Some(drift) if drift > PINNED_DRIFT_WARNING_THRESHOLD => tracing::warn!(
+                    "Pinned summary for {} may be outdated: file size changed by {:.0}%",
+                    node.path.display(),
+                    drift * 100.0
+                ),
+                _ => tracing::debug!("Pinned summary kept for changed: {}", node.path.display()),
+            }
+        }
+
+        Some(entry.summary)
+    }
+
+    /// Summarizes a directory from its children summaries (see
+    /// `DirectoryReducer::reduce`).
+    pub(crate) async fn reduce_directory_summary(
+        &self,
+        directory_name: &str,
+        children_summaries: &[String],
+        level: SummaryLevel,
+    ) -> Result<String> {
+        self.reducer().reduce(directory_name, children_summaries, level).await
+    }
+
+    fn reducer(&self) -> DirectoryReducer {
+        DirectoryReducer {
+            llm_client: self.llm_client.clone(),
+            token_budget: self.directory_token_budget,
+            offline: self.offline,
+            public_api: self.public_api.clone(),
+        }
+    }
+
+    fn group_by_token_budget(summaries: &[String], budget: u64) -> Vec<Vec<String>> {
+        let mut groups: Vec<Vec<String>> = Vec::new();
+        let mut current: Vec<String> = Vec::new();
+        let mut current_tokens = 0;
+
+        for summary in summaries {
+            let tokens = estimate_tokens(summary);
+            if !current.is_empty() && current_tokens + tokens > budget {
+                groups.push(std::mem::take(&mut current));
+                current_tokens = 0;
+            }
+            current_tokens += tokens;
+            current.push(summary.clone());
+        }
+
+        if !current.is_empty() {
+            groups.push(current);
+        }
+
+        groups
+    }
+
+    /// Hands back the cache manager, including any in-memory entries written
+    /// during a read-only run.
+    pub fn into_cache_manager(self) -> CacheManager {
+        self.cache_manager
+    }
+
     pub fn get_cache_stats(&self) -> (usize, u64) {
         self.cache_manager.get_cache_stats()
     }
@@ -199,27 +1514,33 @@ impl HierarchicalSummarizer {
         self.cache_manager.cleanup_old_entries(max_age_days)
     }
 
-    pub fn print_tree_summary(node: &FileNode, base_path: &Path, indent: usize) {
+    pub fn report_tree_summary(reporter: &dyn Reporter, node: &FileNode, base_path: &Path) {
+        let mut lines = Vec::new();
+        Self::tree_summary_lines(node, base_path, 0, &mut lines);
+        reporter.section("🌳 Summary Tree", &lines);
+    }
+
+    fn tree_summary_lines(node: &FileNode, base_path: &Path, indent: usize, lines: &mut Vec<String>) {
         let relative_path = node.get_relative_path(base_path).unwrap_or_else(|_| node.path.clone());
         let indent_str = "  ".repeat(indent);
-        
+
         if node.is_directory {
-            println!("{}📁 {}/", indent_str, relative_path.display());
+            lines.push(format!("{}📁 {}/", indent_str, relative_path.display()));
         } else {
-            println!("{}📄 {}", indent_str, relative_path.display());
+            lines.push(format!("{}📄 {}", indent_str, relative_path.display()));
         }
 
         if let Some(ref summary) = node.summary {
-            let summary_preview = if summary.len() > 100 {
-                format!("{}...", &summary[..97])
+            let summary_preview = if summary.chars().count() > 100 {
+                format!("{}...", summary.chars().take(97).collect::<String>())
             } else {
                 summary.clone()
             };
-            println!("{indent_str}   → {summary_preview}");
+            lines.push(format!("{indent_str}   → {summary_preview}"));
         }
 
         for child in &node.children {
-            Self::print_tree_summary(child, base_path, indent + 1);
+            Self::tree_summary_lines(child, base_path, indent + 1, lines);
         }
     }
 }
@@ -239,6 +1560,7 @@ mod tests {
             openai_model_name: "test-model".to_string(),
             cache_dir_name: ".test_cache".to_string(),
             log_level: "debug".to_string(),
+            ..Default::default()
         };
 
         let llm_client = LanguageModelClient::new(&config).unwrap();
@@ -270,6 +1592,252 @@ mod tests {
         assert!(!non_source_file.is_source_code_file());
     }
 
+    #[test]
+    fn test_group_by_token_budget() {
+        let summaries: Vec<String> = (0..5).map(|_| "x".repeat(40)).collect();
+
+        // Each summary is ~10 tokens, so a 25 token budget fits two per group
+        let groups = HierarchicalSummarizer::group_by_token_budget(&summaries, 25);
+        assert_eq!(groups.iter().map(Vec::len).collect::<Vec<_>>(), vec![2, 2, 1]);
+
+        // Oversized summaries still get a group of their own
+        let groups = HierarchicalSummarizer::group_by_token_budget(&summaries, 5);
+        assert_eq!(groups.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_extractive_summaries_are_upgraded() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        fs::write(base_path.join("lib.rs"), "//! Shared helpers.\npub fn helper() {}\n")?;
+
+        let config = Config::default();
+        let cache_manager = CacheManager::new(base_path, ".test_cache")?;
+        let mut summarizer =
+            HierarchicalSummarizer::new(LanguageModelClient::mock(&config), cache_manager, false);
+        summarizer.set_extractive_below_bytes(1024);
+        summarizer.generate_project_summary(base_path).await?;
+
+        let entry = summarizer.cache_manager.get_cache_summary(&base_path.join("lib.rs")).unwrap();
+        assert_eq!(entry.method, SummaryMethod::Extractive);
+        assert_eq!(entry.summary, "Shared helpers. Defines helper.");
+
+        summarizer.set_extractive_below_bytes(0);
+        summarizer.generate_project_summary(base_path).await?;
+        let entry = summarizer.cache_manager.get_cache_summary(&base_path.join("lib.rs")).unwrap();
+        assert_eq!(entry.method, SummaryMethod::Llm);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_offline_run_is_upgraded_online() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        fs::create_dir_all(base_path.join("src"))?;
+        fs::write(base_path.join("src/parser.rs"), "use std::str::FromStr;\n\npub fn parse() {}\n")?;
+
+        // No rules: any LLM call would fail and be recorded
+        let provider = crate::testing::ScriptedProvider::new();
+        let config = Config::default();
+        let mut summarizer =
+            HierarchicalSummarizer::new(provider.client(&config), CacheManager::new(base_path, ".test_cache")?, false);
+        summarizer.set_offline(true);
+        let project_summary = summarizer.generate_project_summary(base_path).await?;
+        assert!(provider.prompts().is_empty());
+        assert!(project_summary.starts_with("Contains 1 entry:\n- **src/** (directory, domain): Contains 1 entry:\n  - **parser.rs**"));
+        assert!(project_summary.contains("Defines parse. Imports std::str::FromStr."));
+
+        let entry = summarizer.cache_manager.get_cache_summary(&base_path.join("src/parser.rs")).unwrap();
+        assert_eq!(entry.method, SummaryMethod::Extractive);
+        assert_eq!(entry.summary, "Defines parse. Imports std::str::FromStr. 3 lines, 42 bytes.");
+
+        // Back online, the file and both directories are summarized again
+        let mut summarizer =
+            HierarchicalSummarizer::new(LanguageModelClient::mock(&config), CacheManager::new(base_path, ".test_cache")?, false);
+        summarizer.generate_project_summary(base_path).await?;
+        for path in [base_path.join("src/parser.rs"), base_path.join("src"), base_path.to_path_buf()] {
+            assert_eq!(summarizer.cache_manager.get_cache_summary(&path).unwrap().method, SummaryMethod::Llm);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_entry_points_are_summarized_with_siblings() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        fs::create_dir_all(base_path.join("src"))?;
+        fs::write(base_path.join("src/mod.rs"), "pub mod parser;\n")?;
+        fs::write(base_path.join("src/parser.rs"), "pub fn parse() {}\n")?;
+
+        let provider = crate::testing::ScriptedProvider::new()
+            .respond("Other files in this directory:\n- parser.rs: Parses input.", "Wires up the parser.")
+            .respond("File: parser.rs", "Parses input.")
+            .fallback("Declares modules.");
+        let config = Config::default();
+        let run = || -> Result<HierarchicalSummarizer> {
+            let mut summarizer =
+                HierarchicalSummarizer::new(provider.client(&config), CacheManager::new(base_path, ".test_cache")?, false);
+            summarizer.set_sibling_context(true);
+            Ok(summarizer)
+        };
+
+        run()?.generate_project_summary(base_path).await?;
+        let summarizer = run()?;
+        let entry = summarizer.cache_manager.get_cache_summary(&base_path.join("src/mod.rs")).unwrap();
+        assert_eq!(entry.summary, "Wires up the parser.");
+        assert!(entry.sibling_context.is_some());
+
+        // Unchanged siblings are not prompted for again
+        let prompts = provider.prompts().len();
+        run()?.generate_project_summary(base_path).await?;
+        assert_eq!(provider.prompts().len(), prompts);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_private_files_are_summarized_from_structure_only() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        fs::create_dir_all(base_path.join("secrets"))?;
+        fs::write(base_path.join("secrets/pricing.rs"), "// Margin is 42%.\npub fn quote() -> u32 { 42 }\n")?;
+
+        let provider = crate::testing::ScriptedProvider::new().fallback("Summary.");
+        let config = Config { private_paths: vec!["secrets/**".to_string()], ..Default::default() };
+        let mut summarizer =
+            HierarchicalSummarizer::new(provider.client(&config), CacheManager::new(base_path, ".test_cache")?, false);
+        summarizer.apply_config(&config, base_path)?;
+        summarizer.generate_project_summary(base_path).await?;
+
+        let entry = summarizer.cache_manager.get_cache_summary(&base_path.join("secrets/pricing.rs")).unwrap();
+        assert_eq!(entry.method, SummaryMethod::StructureOnly);
+        assert_eq!(entry.summary, "rs file with private contents (structure only). Defines quote. 2 lines, 47 bytes.");
+        assert!(provider.prompts().iter().all(|prompt| !prompt.contains("Margin")));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_identical_files_reuse_one_summary() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        fs::create_dir_all(base_path.join("src"))?;
+        fs::create_dir_all(base_path.join("vendor"))?;
+        let content = "pub fn parse(input: &str) -> Vec<&str> { input.split(',').collect() }\n";
+        fs::write(base_path.join("src/parse.rs"), content)?;
+        fs::write(base_path.join("vendor/parse_copy.rs"), content)?;
+
+        let provider = crate::testing::ScriptedProvider::new()
+            .respond("File: parse.rs", "parse.rs splits input on commas.")
+            .respond("File: parse_copy.rs", "parse_copy.rs splits input on commas.")
+            .fallback("Summary.");
+        let mut summarizer =
+            HierarchicalSummarizer::new(provider.client(&Config::default()), CacheManager::new(base_path, ".test_cache")?, false);
+        summarizer.generate_project_summary(base_path).await?;
+
+        let file_prompts = provider.prompts().iter().filter(|prompt| prompt.contains("File: parse")).count();
+        assert_eq!(file_prompts, 1);
+        for name in ["src/parse.rs", "vendor/parse_copy.rs"] {
+            let entry = summarizer.cache_manager.get_cache_summary(&base_path.join(name)).unwrap();
+            let file_name = Path::new(name).file_name().unwrap().to_str().unwrap();
+            assert_eq!(entry.summary, format!("{file_name} splits input on commas."));
+            assert_eq!(entry.method, SummaryMethod::Llm);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_run_stops_when_token_budget_is_spent() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        fs::write(base_path.join("a.rs"), "fn a() {}")?;
+        fs::write(base_path.join("b.rs"), "fn b() {}")?;
+
+        let provider = crate::testing::ScriptedProvider::new().fallback("Summary.");
+        let mut summarizer =
+            HierarchicalSummarizer::new(provider.client(&Config::default()), CacheManager::new(base_path, ".test_cache")?, false);
+        summarizer.set_token_budget(TokenBudget::new(1));
+
+        match summarizer.generate_project_summary(base_path).await {
+            Err(DocTreeError::Cancelled(message)) => {
+                assert!(message.starts_with("Token budget of 1 per run exhausted"));
+                assert!(message.ends_with("after 1 new summaries"));
+            }
+            other => panic!("expected the run to stop, got {other:?}"),
+        }
+        assert_eq!(provider.prompts().len(), 1);
+        assert!(summarizer.cache_manager.get_cache_summary(&base_path.join("a.rs")).is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_structured_summaries_are_cached_with_their_fields() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        fs::write(base_path.join("parser.rs"), "pub fn parse() {}")?;
+
+        let provider = crate::testing::ScriptedProvider::new()
+            .respond(
+                "Describe this source file as a JSON object",
+                r#"{"purpose": "Parses input.", "public_api": ["parse"], "dependencies": []}"#,
+            )
+            .respond("project root", "A parser.");
+        let config = Config {
+            structured_summaries: true,
+            ..Default::default()
+        };
+        let mut summarizer =
+            HierarchicalSummarizer::new(provider.client(&config), CacheManager::new(base_path, ".test_cache")?, false);
+        summarizer.generate_project_summary(base_path).await?;
+
+        let entry = summarizer.cache_manager.get_cache_summary(&base_path.join("parser.rs")).unwrap();
+        assert_eq!(entry.summary, "Parses input.\nPublic API: parse.");
+        assert_eq!(entry.structured.unwrap().public_api, vec!["parse"]);
+        assert!(provider.prompts().iter().any(|prompt| prompt.contains("Parses input.\nPublic API: parse.")));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_scoped_run_leaves_other_subtrees_cached() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        fs::create_dir_all(base_path.join("api"))?;
+        fs::create_dir_all(base_path.join("web"))?;
+        fs::write(base_path.join("api/server.rs"), "fn serve() {}")?;
+        fs::write(base_path.join("web/app.ts"), "export const app = 1;")?;
+
+        let config = Config::default();
+        let cache_manager = CacheManager::new(base_path, ".test_cache")?;
+        let mut summarizer =
+            HierarchicalSummarizer::new(LanguageModelClient::mock(&config), cache_manager, false);
+        summarizer.generate_project_summary(base_path).await?;
+
+        let cached_hash = |summarizer: &HierarchicalSummarizer, path: &str| {
+            summarizer
+                .cache_manager
+                .get_cache_summary(&base_path.join(path))
+                .map(|entry| entry.content_hash)
+        };
+        let web_hash = cached_hash(&summarizer, "web/app.ts");
+        let root_hash = cached_hash(&summarizer, "");
+
+        fs::write(base_path.join("api/server.rs"), "fn serve() { listen() }")?;
+        fs::write(base_path.join("web/app.ts"), "export const app = 2;")?;
+
+        summarizer.set_scope(Some(PathBuf::from("api")));
+        summarizer.generate_project_summary(base_path).await?;
+
+        assert_eq!(
+            cached_hash(&summarizer, "api/server.rs"),
+            Some(FileHasher::compute_file_hash(&base_path.join("api/server.rs"))?)
+        );
+        assert_eq!(cached_hash(&summarizer, "web/app.ts"), web_hash);
+        assert_ne!(cached_hash(&summarizer, ""), root_hash);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_cache_stats() {
         let (summarizer, _temp_dir) = create_test_summarizer().await;
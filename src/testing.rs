@@ -0,0 +1,130 @@
+//! Helpers for testing code built on doctreeai, enabled with the `test-util`
+//! feature: a scripted LLM provider and golden-file assertions.
+
+use crate::config::Config;
+use crate::error::{DocTreeError, Result};
+use crate::llm::LanguageModelClient;
+use crate::self_doc::GoldenFile;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Set to any value to overwrite golden files instead of comparing.
+pub const BLESS_ENV: &str = "DOCTREEAI_BLESS";
+
+/// Fake LLM that answers prompts from a script. Rules are checked in order
+/// and the first whose pattern occurs in the prompt wins; prompts matching
+/// nothing get the fallback response, or fail when there is none.
+///
+/// Clones share the prompt log, so a provider handed to a client can still
+/// be inspected afterwards.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedProvider {
+    rules: Vec<(String, String)>,
+    fallback: Option<String>,
+    prompts: Arc<Mutex<Vec<String>>>,
+}
+
+impl ScriptedProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answers prompts containing `pattern` with `response`.
+    pub fn respond(mut self, pattern: &str, response: &str) -> Self {
+        self.rules.push((pattern.to_string(), response.to_string()));
+        self
+    }
+
+    /// Answers every prompt no rule matches.
+    pub fn fallback(mut self, response: &str) -> Self {
+        self.fallback = Some(response.to_string());
+        self
+    }
+
+    /// Every prompt sent so far, in order.
+    pub fn prompts(&self) -> Vec<String> {
+        self.prompts.lock().map(|prompts| prompts.clone()).unwrap_or_default()
+    }
+
+    /// A client backed by this script. Failures are never retried since the
+    /// script would give the same answer again.
+    pub fn client(&self, config: &Config) -> LanguageModelClient {
+        LanguageModelClient::scripted(config, self.clone())
+    }
+
+    pub(crate) fn complete(&self, prompt: &str) -> Result<String> {
+        if let Ok(mut prompts) = self.prompts.lock() {
+            prompts.push(prompt.to_string());
+        }
+
+        self.rules
+            .iter()
+            .find(|(pattern, _)| prompt.contains(pattern.as_str()))
+            .map(|(_, response)| response.clone())
+            .or_else(|| self.fallback.clone())
+            .ok_or_else(|| {
+                DocTreeError::summarizer(format!(
+                    "No scripted response for prompt: {}",
+                    prompt.lines().next().unwrap_or_default()
+                ))
+            })
+    }
+}
+
+/// Compares `actual` with the golden file at `path`, or writes it when
+/// `DOCTREEAI_BLESS` is set. Panics with the first differing line otherwise.
+pub fn assert_golden(path: impl AsRef<Path>, actual: &str) {
+    let path = path.as_ref();
+
+    if std::env::var_os(BLESS_ENV).is_some() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("failed to create golden directory");
+        }
+        fs::write(path, actual).expect("failed to write golden file");
+        return;
+    }
+
+    let file = GoldenFile {
+        name: "",
+        actual: actual.to_string(),
+        expected: fs::read_to_string(path).ok(),
+    };
+    if file.expected.is_none() {
+        panic!("Golden file {} is missing; rerun with {BLESS_ENV}=1 to create it", path.display());
+    }
+    if let Some((line, expected, actual)) = file.first_difference() {
+        panic!(
+            "{} differs at line {line}\n   expected: {expected}\n   actual:   {actual}\nRerun with {BLESS_ENV}=1 if the change is intended",
+            path.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_scripted_provider_and_golden() -> Result<()> {
+        let provider = ScriptedProvider::new()
+            .respond("main.rs", "Starts the CLI.")
+            .respond("lib.rs", "Declares the modules.");
+        let client = provider.client(&Config::default());
+
+        let summary = client.generate_file_summary(&PathBuf::from("src/main.rs"), "fn main() {}").await?;
+        assert_eq!(summary, "Starts the CLI.");
+        assert!(client.generate_file_summary(&PathBuf::from("build.rs"), "fn main() {}").await.is_err());
+        assert_eq!(provider.prompts().len(), 2);
+
+        let temp_dir = TempDir::new()?;
+        let golden = temp_dir.path().join("summary.md");
+        fs::write(&golden, "Starts the CLI.")?;
+        assert_golden(&golden, &summary);
+        assert!(std::panic::catch_unwind(|| assert_golden(&golden, "Something else.")).is_err());
+
+        Ok(())
+    }
+}
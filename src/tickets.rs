@@ -0,0 +1,121 @@
+use crate::config::Config;
+use crate::reporter::Reporter;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Ticket {
+    pub id: String,
+    pub url: String,
+}
+
+/// Links documentation drift to the JIRA/Linear tickets mentioned in recent
+/// commits that touched the affected paths. Both trackers use `KEY-123` IDs.
+pub struct TicketLinker {
+    url_template: String,
+    projects: Vec<String>,
+    commit_limit: usize,
+}
+
+impl TicketLinker {
+    /// Returns None unless a ticket URL template is configured.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        let url_template = config.ticket_url_template.clone()?;
+        Some(Self {
+            url_template,
+            projects: config.ticket_projects.clone(),
+            commit_limit: config.ticket_commit_limit,
+        })
+    }
+
+    /// Tickets referenced by the most recent commits touching any of the paths.
+    pub fn tickets_for_paths(&self, base_path: &Path, paths: &[PathBuf]) -> Vec<Ticket> {
+        if paths.is_empty() {
+            return Vec::new();
+        }
+
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(base_path)
+            .args(["log", "-n", &self.commit_limit.to_string(), "--format=%s%n%b", "--"])
+            .args(paths)
+            .output();
+
+        let messages = match output {
+            Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).to_string(),
+            Ok(output) => {
+                tracing::debug!("git log failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+                return Vec::new();
+            }
+            Err(e) => {
+                tracing::debug!("Failed to run git: {e}");
+                return Vec::new();
+            }
+        };
+
+        self.extract_ids(&messages)
+            .into_iter()
+            .map(|id| Ticket {
+                url: self.url_template.replace("{id}", &id),
+                id,
+            })
+            .collect()
+    }
+
+    /// Finds `KEY-123` style IDs, limited to the configured project keys if any.
+    fn extract_ids(&self, text: &str) -> BTreeSet<String> {
+        let mut ids = BTreeSet::new();
+
+        for word in text.split(|c: char| !(c.is_ascii_alphanumeric() || c == '-')) {
+            let parts: Vec<&str> = word.split('-').collect();
+            for pair in parts.windows(2) {
+                let (key, number) = (pair[0], pair[1]);
+                let valid_key = key.len() >= 2
+                    && key.starts_with(|c: char| c.is_ascii_uppercase())
+                    && key.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+                let valid_number = !number.is_empty() && number.chars().all(|c| c.is_ascii_digit());
+                let allowed = self.projects.is_empty() || self.projects.iter().any(|project| project == key);
+
+                if valid_key && valid_number && allowed {
+                    ids.insert(format!("{key}-{number}"));
+                }
+            }
+        }
+
+        ids
+    }
+
+    pub fn report_tickets(reporter: &dyn Reporter, tickets: &[Ticket]) {
+        if tickets.is_empty() {
+            return;
+        }
+
+        let lines: Vec<String> = tickets.iter().map(|ticket| format!("{} - {}", ticket.id, ticket.url)).collect();
+        reporter.section("🔗 Related tickets from recent commits:", &lines);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn linker(projects: &[&str]) -> TicketLinker {
+        TicketLinker {
+            url_template: "https://linear.app/acme/issue/{id}".to_string(),
+            projects: projects.iter().map(|p| p.to_string()).collect(),
+            commit_limit: 20,
+        }
+    }
+
+    #[test]
+    fn test_extract_ticket_ids() {
+        let messages = "ENG-42: rework cache\nFixes OPS-7 and eng-9, see UTF-8 handling\nRefs [ENG-100]";
+
+        let all: Vec<String> = linker(&[]).extract_ids(messages).into_iter().collect();
+        assert_eq!(all, vec!["ENG-100", "ENG-42", "OPS-7", "UTF-8"]);
+
+        let scoped: Vec<String> = linker(&["ENG"]).extract_ids(messages).into_iter().collect();
+        assert_eq!(scoped, vec!["ENG-100", "ENG-42"]);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::cache::{CacheSummary, RefreshPolicy};
+#[cfg(test)]
+use crate::error::Result;
+use crate::hasher::FileHasher;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Whether a cached summary still describes the source on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Freshness {
+    Fresh,
+    /// Older than its cache TTL, so the next run regenerates it.
+    Expired,
+    /// The source (or something under a directory) changed since it was summarized.
+    Stale,
+    /// The source no longer exists.
+    Missing,
+}
+
+impl fmt::Display for Freshness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Freshness::Fresh => "fresh",
+            Freshness::Expired => "expired",
+            Freshness::Stale => "stale",
+            Freshness::Missing => "missing",
+        };
+        f.write_str(name)
+    }
+}
+
+/// One node of the summary tree rebuilt from the cache.
+#[derive(Debug, Clone, Serialize)]
+pub struct SummaryTreeNode {
+    /// Path relative to the project root ("" for the root).
+    pub path: PathBuf,
+    pub is_directory: bool,
+    /// None for directories that only hold summarized children.
+    pub summary: Option<String>,
+    pub status: Freshness,
+    pub pinned: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<SummaryTreeNode>,
+}
+
+impl SummaryTreeNode {
+    fn new(path: PathBuf, is_directory: bool) -> Self {
+        Self {
+            path,
+            is_directory,
+            summary: None,
+            status: Freshness::Fresh,
+            pinned: false,
+            children: Vec::new(),
+        }
+    }
+
+    /// Rebuilds the tree from cached summaries. Files are checked against
+    /// their content hash; a directory takes the worst status below it, with
+    /// a deleted child making it stale.
+    pub fn from_cache(summaries: &[CacheSummary], base_path: &Path, policy: &RefreshPolicy, now: u64) -> Self {
+        let entries: BTreeMap<PathBuf, &CacheSummary> = summaries
+            .iter()
+            .map(|entry| {
+                let relative_path = entry.source_path.strip_prefix(base_path).unwrap_or(&entry.source_path);
+                (relative_path.to_path_buf(), entry)
+            })
+            .collect();
+
+        let mut root = Self::new(PathBuf::new(), true);
+        for relative_path in entries.keys() {
+            root.insert(relative_path, &entries);
+        }
+        root.resolve(&entries, base_path, policy, now);
+        root
+    }
+
+    fn insert(&mut self, relative_path: &Path, entries: &BTreeMap<PathBuf, &CacheSummary>) {
+        let Ok(rest) = relative_path.strip_prefix(&self.path) else {
+            return;
+        };
+        let Some(next) = rest.components().next() else {
+            return;
+        };
+
+        let child_path = self.path.join(next);
+        let position = match self.children.iter().position(|child| child.path == child_path) {
+            Some(position) => position,
+            None => {
+                let is_directory = child_path != relative_path
+                    || entries.get(&child_path).is_some_and(|entry| entry.is_directory);
+                self.children.push(Self::new(child_path, is_directory));
+                self.children.len() - 1
+            }
+        };
+        self.children[position].insert(relative_path, entries);
+    }
+
+    fn resolve(&mut self, entries: &BTreeMap<PathBuf, &CacheSummary>, base_path: &Path, policy: &RefreshPolicy, now: u64) {
+        for child in &mut self.children {
+            child.resolve(entries, base_path, policy, now);
+        }
+        self.children.sort_by(|a, b| b.is_directory.cmp(&a.is_directory).then_with(|| a.path.cmp(&b.path)));
+
+        let source_path = base_path.join(&self.path);
+        let entry = entries.get(&self.path);
+        self.summary = entry.map(|entry| entry.summary.clone());
+        self.pinned = entry.is_some_and(|entry| entry.pinned);
+
+        self.status = if !source_path.exists() {
+            Freshness::Missing
+        } else if self.is_directory {
+            self.children
+                .iter()
+                .map(|child| child.status.min(Freshness::Stale))
+                .max()
+                .unwrap_or(Freshness::Fresh)
+        } else {
+            match entry {
+                Some(entry) if FileHasher::compute_source_hash(&source_path, entry.normalized_hash).ok().as_ref() != Some(&entry.content_hash) => {
+                    Freshness::Stale
+                }
+                _ => Freshness::Fresh,
+            }
+        };
+
+        if let Some(entry) = entry {
+            if self.status == Freshness::Fresh && policy.is_expired(&self.path, entry.timestamp, now) {
+                self.status = Freshness::Expired;
+            }
+        }
+    }
+
+    /// The node for a path relative to the project root.
+    pub fn find(&self, relative_path: &Path) -> Option<&SummaryTreeNode> {
+        if self.path == relative_path {
+            return Some(self);
+        }
+        self.children
+            .iter()
+            .find(|child| relative_path.starts_with(&child.path))
+            .and_then(|child| child.find(relative_path))
+    }
+
+    /// Drops everything deeper than `depth` levels below this node.
+    pub fn truncate(&mut self, depth: usize) {
+        if depth == 0 {
+            self.children.clear();
+        }
+        for child in &mut self.children {
+            child.truncate(depth.saturating_sub(1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::SummaryMethod;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn entry(base_path: &Path, path: &str, is_directory: bool, content_hash: String) -> CacheSummary {
+        CacheSummary {
+            source_path: base_path.join(path),
+            content_hash,
+            summary: format!("Summary of {path}"),
+            timestamp: 0,
+            is_directory,
+            role: None,
+            pinned: false,
+            content_size: None,
+            modified: None,
+            normalized_hash: false,
+            language: None,
+            method: SummaryMethod::Llm,
+            structured: None,
+            sibling_context: None,
+        }
+    }
+
+    #[test]
+    fn test_tree_from_cache_with_freshness() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base_path = temp_dir.path();
+        fs::create_dir_all(base_path.join("src/api"))?;
+        fs::write(base_path.join("src/main.rs"), "fn main() {}")?;
+        fs::write(base_path.join("src/api/server.rs"), "fn serve() {}")?;
+
+        let summaries = vec![
+            entry(base_path, "src", true, "dir".to_string()),
+            entry(base_path, "src/main.rs", false, FileHasher::compute_content_hash("fn main() {}")),
+            entry(base_path, "src/api/server.rs", false, "outdated".to_string()),
+            entry(base_path, "src/old.rs", false, "gone".to_string()),
+        ];
+
+        let tree = SummaryTreeNode::from_cache(&summaries, base_path, &RefreshPolicy::default(), 0);
+        let src = &tree.children[0];
+        assert_eq!(src.path, PathBuf::from("src"));
+        assert_eq!(src.status, Freshness::Stale);
+        let statuses: Vec<_> = src.children.iter().map(|child| (child.path.clone(), child.status)).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                (PathBuf::from("src/api"), Freshness::Stale),
+                (PathBuf::from("src/main.rs"), Freshness::Fresh),
+                (PathBuf::from("src/old.rs"), Freshness::Missing),
+            ]
+        );
+        assert!(src.children[0].summary.is_none());
+
+        let expired = SummaryTreeNode::from_cache(&summaries[1..2], base_path, &RefreshPolicy::uniform(60), 120);
+        assert_eq!(expired.children[0].children[0].status, Freshness::Expired);
+
+        assert_eq!(tree.find(Path::new("src/main.rs")).map(|node| node.status), Some(Freshness::Fresh));
+        assert!(tree.find(Path::new("src/unknown.rs")).is_none());
+
+        let mut shallow = tree.clone();
+        shallow.truncate(1);
+        assert!(shallow.children[0].children.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use crate::config::Config;
+use crate::error::Result;
+use crate::models::list_models;
+use std::fs;
+use std::path::Path;
+
+/// An LLM server the init wizard can set a project up with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Endpoint {
+    pub name: &'static str,
+    pub api_base: &'static str,
+    /// Whether requests need a real API key.
+    pub needs_key: bool,
+}
+
+/// Local servers probed by the wizard, on their default ports.
+pub const LOCAL_ENDPOINTS: [Endpoint; 2] = [
+    Endpoint { name: "Ollama", api_base: "http://localhost:11434/v1", needs_key: false },
+    Endpoint { name: "LM Studio", api_base: "http://localhost:1234/v1", needs_key: false },
+];
+
+pub const OPENAI_ENDPOINT: Endpoint =
+    Endpoint { name: "OpenAI", api_base: "https://api.openai.com/v1", needs_key: true };
+
+/// How long a local server gets to answer the probe.
+const PROBE_TIMEOUT_SECS: u64 = 3;
+
+/// Starter project file: every setting commented out, for editing later.
+pub const PROJECT_FILE_TEMPLATE: &str = r#"# DocTreeAI project settings; environment variables take precedence.
+# 'doctreeai config list' shows the effective value of every setting.
+
+[scan]
+# include = ["src/**"]
+# exclude = ["examples/**"]
+
+[readme]
+# required_sections = ["Installation", "Usage", "License"]
+"#;
+
+/// The models a server offers, or None when it does not answer.
+pub async fn probe(api_base: &str, api_key: &str) -> Option<Vec<String>> {
+    let config = Config {
+        openai_api_base: api_base.to_string(),
+        openai_api_key: api_key.to_string(),
+        request_timeout_secs: PROBE_TIMEOUT_SECS,
+        ..Config::default()
+    };
+    match list_models(&config).await {
+        Ok(models) => Some(models.into_iter().map(|model| model.id).collect()),
+        Err(e) => {
+            tracing::debug!("{e}");
+            None
+        }
+    }
+}
+
+/// Sets `KEY=value` lines in a .env file, replacing existing ones in place
+/// and keeping every other line.
+pub fn update_env_file(path: &Path, values: &[(&str, &str)]) -> Result<()> {
+    let content = if path.is_file() { fs::read_to_string(path)? } else { String::new() };
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+
+    for (key, value) in values {
+        let line = format!("{key}={value}");
+        let existing = lines.iter_mut().find(|existing| {
+            let existing = existing.trim_start();
+            let existing = existing.strip_prefix("export ").unwrap_or(existing);
+            existing.split_once('=').is_some_and(|(name, _)| name.trim() == *key)
+        });
+        match existing {
+            Some(existing) => *existing = line,
+            None => lines.push(line),
+        }
+    }
+
+    fs::write(path, lines.join("\n") + "\n")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_file_keeps_other_lines() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let path = temp_dir.path().join(".env");
+        fs::write(&path, "# LLM server\nexport OPENAI_API_BASE=http://old/v1\nDOCTREEAI_LOG_LEVEL=debug\n")?;
+
+        update_env_file(&path, &[("OPENAI_API_BASE", "http://localhost:11434/v1"), ("OPENAI_MODEL_NAME", "gpt-oss:20b")])?;
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            "# LLM server\nOPENAI_API_BASE=http://localhost:11434/v1\nDOCTREEAI_LOG_LEVEL=debug\nOPENAI_MODEL_NAME=gpt-oss:20b\n"
+        );
+        Ok(())
+    }
+}
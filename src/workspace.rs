@@ -0,0 +1,274 @@
+use crate::error::Result;
+use globset::{Glob, GlobMatcher};
+use ignore::WalkBuilder;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Files at the project root that mark a monorepo / workspace.
+const WORKSPACE_MARKERS: &[&str] = &[
+    "go.work",
+    "pnpm-workspace.yaml",
+    "lerna.json",
+    "nx.json",
+    "turbo.json",
+    "rush.json",
+];
+
+/// Manifests that make a directory a package.
+const PACKAGE_MANIFESTS: &[&str] = &["Cargo.toml", "package.json", "pyproject.toml", "go.mod"];
+
+/// How deep below the root packages are searched for.
+const MAX_PACKAGE_DEPTH: usize = 4;
+
+const CODEOWNERS_LOCATIONS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Package {
+    pub name: String,
+    /// Path relative to the workspace root.
+    pub path: PathBuf,
+    pub owner: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Workspace {
+    pub packages: Vec<Package>,
+}
+
+impl Workspace {
+    /// Detects a monorepo at `base_path`: either a root workspace manifest
+    /// (Cargo `[workspace]`, npm `workspaces`, go.work, pnpm, lerna, nx, ...)
+    /// or at least two nested packages. Returns None for single-package repos.
+    pub fn detect(base_path: &Path) -> Result<Option<Self>> {
+        let packages = Self::find_packages(base_path)?;
+        if packages.is_empty() {
+            return Ok(None);
+        }
+
+        if !Self::has_workspace_manifest(base_path) && packages.len() < 2 {
+            return Ok(None);
+        }
+
+        let owners = CodeOwners::load(base_path);
+        let packages = packages
+            .into_iter()
+            .map(|path| {
+                let name = Self::package_name(&base_path.join(&path))
+                    .unwrap_or_else(|| path.to_string_lossy().to_string());
+                let owner = owners.owner_of(&path);
+                Package { name, path, owner }
+            })
+            .collect();
+
+        Ok(Some(Self { packages }))
+    }
+
+    fn has_workspace_manifest(base_path: &Path) -> bool {
+        if WORKSPACE_MARKERS
+            .iter()
+            .any(|marker| base_path.join(marker).is_file())
+        {
+            return true;
+        }
+
+        let cargo_workspace = fs::read_to_string(base_path.join("Cargo.toml"))
+            .ok()
+            .and_then(|content| content.parse::<toml::Table>().ok())
+            .is_some_and(|manifest| manifest.contains_key("workspace"));
+
+        let npm_workspaces = fs::read_to_string(base_path.join("package.json"))
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .is_some_and(|manifest| manifest.get("workspaces").is_some());
+
+        cargo_workspace || npm_workspaces
+    }
+
+    fn find_packages(base_path: &Path) -> Result<Vec<PathBuf>> {
+        let mut packages = Vec::new();
+
+        let walker = WalkBuilder::new(base_path)
+            .max_depth(Some(MAX_PACKAGE_DEPTH))
+            .hidden(true)
+            .git_ignore(true)
+            .require_git(false)
+            .filter_entry(|entry| {
+                !matches!(
+                    entry.file_name().to_str(),
+                    Some("node_modules" | "target" | "dist" | "build" | "vendor")
+                )
+            })
+            .build();
+
+        for entry in walker.flatten() {
+            let path = entry.path();
+            if path == base_path || !entry.file_type().is_some_and(|ft| ft.is_dir()) {
+                continue;
+            }
+
+            if PACKAGE_MANIFESTS
+                .iter()
+                .any(|manifest| path.join(manifest).is_file())
+            {
+                if let Ok(relative) = path.strip_prefix(base_path) {
+                    packages.push(relative.to_path_buf());
+                }
+            }
+        }
+
+        packages.sort();
+        Ok(packages)
+    }
+
+    fn package_name(package_path: &Path) -> Option<String> {
+        if let Ok(content) = fs::read_to_string(package_path.join("Cargo.toml")) {
+            if let Some(name) = content
+                .parse::<toml::Table>()
+                .ok()
+                .and_then(|manifest| manifest.get("package")?.get("name")?.as_str().map(String::from))
+            {
+                return Some(name);
+            }
+        }
+
+        if let Ok(content) = fs::read_to_string(package_path.join("package.json")) {
+            if let Some(name) = serde_json::from_str::<serde_json::Value>(&content)
+                .ok()
+                .and_then(|manifest| manifest.get("name")?.as_str().map(String::from))
+            {
+                return Some(name);
+            }
+        }
+
+        if let Ok(content) = fs::read_to_string(package_path.join("pyproject.toml")) {
+            if let Some(name) = content.parse::<toml::Table>().ok().and_then(|manifest| {
+                let project = manifest.get("project").or_else(|| manifest.get("tool")?.get("poetry"))?;
+                project.get("name")?.as_str().map(String::from)
+            }) {
+                return Some(name);
+            }
+        }
+
+        if let Ok(content) = fs::read_to_string(package_path.join("go.mod")) {
+            if let Some(module) = content
+                .lines()
+                .find_map(|line| line.trim().strip_prefix("module "))
+            {
+                return Some(module.trim().to_string());
+            }
+        }
+
+        None
+    }
+}
+
+/// Minimal CODEOWNERS support: the last matching rule wins, as on GitHub.
+#[derive(Debug, Default)]
+pub struct CodeOwners {
+    rules: Vec<(GlobMatcher, String)>,
+}
+
+impl CodeOwners {
+    pub fn load(base_path: &Path) -> Self {
+        CODEOWNERS_LOCATIONS
+            .iter()
+            .find_map(|location| fs::read_to_string(base_path.join(location)).ok())
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default()
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let rules = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let pattern = parts.next()?;
+                let owners: Vec<&str> = parts.collect();
+                if owners.is_empty() {
+                    return None;
+                }
+                let matcher = Glob::new(&Self::to_glob(pattern)).ok()?.compile_matcher();
+                Some((matcher, owners.join(" ")))
+            })
+            .collect();
+
+        Self { rules }
+    }
+
+    /// Translates a gitignore-style CODEOWNERS pattern into a glob.
+    fn to_glob(pattern: &str) -> String {
+        let anchored = pattern.starts_with('/') || pattern.trim_end_matches('/').contains('/');
+        let mut glob = pattern.trim_start_matches('/').to_string();
+
+        if glob.ends_with('/') {
+            glob.push_str("**");
+        }
+        if !anchored && glob != "*" {
+            glob = format!("**/{glob}");
+        }
+        glob
+    }
+
+    /// Owner of a directory: rules may match the directory itself or files within it.
+    pub fn owner_of(&self, relative_path: &Path) -> Option<String> {
+        let inner = relative_path.join("_");
+        self.rules
+            .iter()
+            .rev()
+            .find(|(matcher, _)| matcher.is_match(relative_path) || matcher.is_match(&inner))
+            .map(|(_, owners)| owners.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_cargo_workspace() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let base = temp_dir.path();
+
+        fs::write(base.join("Cargo.toml"), "[workspace]\nmembers = [\"crates/*\"]\n")?;
+        fs::create_dir_all(base.join("crates/api"))?;
+        fs::write(base.join("crates/api/Cargo.toml"), "[package]\nname = \"acme-api\"\n")?;
+        fs::create_dir_all(base.join("web/node_modules/dep"))?;
+        fs::write(base.join("web/package.json"), "{\"name\": \"@acme/web\"}")?;
+        fs::write(base.join("web/node_modules/dep/package.json"), "{\"name\": \"dep\"}")?;
+        fs::create_dir_all(base.join(".github"))?;
+        fs::write(base.join(".github/CODEOWNERS"), "* @acme/core\n/web/ @acme/frontend\n")?;
+
+        let workspace = Workspace::detect(base)?.expect("workspace detected");
+        assert_eq!(
+            workspace.packages,
+            vec![
+                Package {
+                    name: "acme-api".to_string(),
+                    path: PathBuf::from("crates/api"),
+                    owner: Some("@acme/core".to_string()),
+                },
+                Package {
+                    name: "@acme/web".to_string(),
+                    path: PathBuf::from("web"),
+                    owner: Some("@acme/frontend".to_string()),
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_single_package_is_not_a_workspace() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        fs::write(temp_dir.path().join("Cargo.toml"), "[package]\nname = \"solo\"\n")?;
+        fs::create_dir_all(temp_dir.path().join("tools"))?;
+        fs::write(temp_dir.path().join("tools/go.mod"), "module example.com/tools\n")?;
+
+        assert!(Workspace::detect(temp_dir.path())?.is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,13 @@
+mod parser;
+
+use std::io::{self, Read};
+
+fn main() {
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input).expect("failed to read stdin");
+
+    match parser::parse(&input) {
+        Ok(total) => println!("{total}"),
+        Err(e) => eprintln!("error: {e}"),
+    }
+}
@@ -0,0 +1,7 @@
+/// Parses a whitespace-separated list of integers and returns their sum.
+pub fn parse(input: &str) -> Result<i64, String> {
+    input
+        .split_whitespace()
+        .map(|token| token.parse::<i64>().map_err(|e| format!("invalid number '{token}': {e}")))
+        .sum()
+}
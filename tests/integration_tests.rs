@@ -1,8 +1,15 @@
 use doctreeai::{
     cache::CacheManager,
     config::Config,
+    doctree::{DocTree, RunOptions},
     hasher::FileHasher,
-    scanner::{DirectoryScanner, FileNode},
+    limits::ScanLimits,
+    llm::LanguageModelClient,
+    readme_validator::Severity,
+    replay::ReplayFixture,
+    scanner::{DirectoryScanner, FileNode, ScanFilter, SourceFileMatcher, WalkOptions},
+    self_doc::SelfDoc,
+    submodules::SubmodulePolicy,
 };
 use std::fs;
 use tempfile::TempDir;
@@ -59,6 +66,176 @@ fn test_directory_scanner() -> doctreeai::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_directory_scanner_globs() -> doctreeai::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let base_path = temp_dir.path();
+
+    fs::create_dir_all(base_path.join("src/generated"))?;
+    fs::create_dir_all(base_path.join("examples"))?;
+    fs::write(base_path.join("src/main.rs"), "fn main() {}")?;
+    fs::write(base_path.join("src/generated/api.rs"), "// generated")?;
+    fs::write(base_path.join("examples/demo.rs"), "fn main() {}")?;
+    fs::write(base_path.join("README.md"), "# Test Project")?;
+
+    let mut scanner = DirectoryScanner::new(base_path.to_path_buf());
+    scanner.set_filter(ScanFilter::new(
+        &["src/**".to_string()],
+        &["src/generated".to_string()],
+    )?);
+    let root_node = scanner.scan_directory()?;
+
+    let source_files: Vec<_> = DirectoryScanner::filter_source_files(&root_node)
+        .iter()
+        .filter_map(|node| node.get_relative_path(base_path).ok())
+        .collect();
+
+    assert_eq!(source_files, vec![std::path::PathBuf::from("src/main.rs")]);
+
+    Ok(())
+}
+
+#[test]
+fn test_directory_scanner_limits_ignore_pruned_directories() -> doctreeai::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let base_path = temp_dir.path();
+
+    fs::create_dir_all(base_path.join("src"))?;
+    fs::create_dir_all(base_path.join("node_modules/pkg"))?;
+    fs::write(base_path.join("src/main.rs"), "fn main() {}")?;
+    for i in 0..30 {
+        fs::write(base_path.join(format!("node_modules/pkg/file{i}.js")), "module.exports = {};")?;
+    }
+
+    let mut scanner = DirectoryScanner::new(base_path.to_path_buf());
+    scanner.set_limits(ScanLimits { max_depth: Some(2), max_files: Some(10), max_files_per_dir: Some(2) });
+    let mut discovered = Vec::new();
+    scanner.scan_directory_with(|node| discovered.push(node.path.clone()))?;
+    assert_eq!(discovered, vec![base_path.join("src"), base_path.join("src/main.rs")]);
+
+    fs::write(base_path.join("src/lib.rs"), "")?;
+    fs::write(base_path.join("src/util.rs"), "")?;
+    let error = scanner.scan_directory().unwrap_err().to_string();
+    assert!(error.contains("--max-files-per-dir"), "{error}");
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_directory_scanner_symlinks() -> doctreeai::Result<()> {
+    use std::os::unix::fs::symlink;
+
+    let temp_dir = TempDir::new()?;
+    let project = temp_dir.path().join("project");
+    let vendored = temp_dir.path().join("shared");
+    fs::create_dir_all(project.join("src"))?;
+    fs::create_dir_all(&vendored)?;
+    fs::write(project.join("src/main.rs"), "fn main() {}")?;
+    fs::write(vendored.join("lib.rs"), "pub fn shared() {}")?;
+    symlink(&vendored, project.join("vendor"))?;
+    symlink(&vendored, project.join("vendor_again"))?;
+    symlink(&project, project.join("src/loop"))?;
+
+    let scan = |walk_options: WalkOptions| -> doctreeai::Result<Vec<std::path::PathBuf>> {
+        let mut scanner = DirectoryScanner::new(project.clone());
+        scanner.set_walk_options(walk_options);
+        let root_node = scanner.scan_directory()?;
+        Ok(DirectoryScanner::filter_source_files(&root_node)
+            .iter()
+            .filter_map(|node| node.get_relative_path(&project).ok())
+            .collect())
+    };
+
+    assert_eq!(scan(WalkOptions::default())?, vec![std::path::PathBuf::from("src/main.rs")]);
+
+    let followed = scan(WalkOptions {
+        follow_symlinks: true,
+        ..Default::default()
+    })?;
+    assert_eq!(followed.len(), 2);
+    assert!(followed.contains(&std::path::PathBuf::from("src/main.rs")));
+    assert!(followed.iter().any(|path| path.ends_with("lib.rs")));
+
+    Ok(())
+}
+
+#[test]
+fn test_directory_scanner_tracked_only() -> doctreeai::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let project = temp_dir.path();
+    fs::create_dir_all(project.join("src/nested"))?;
+    fs::write(project.join("src/main.rs"), "fn main() {}")?;
+    fs::write(project.join("src/nested/util.rs"), "pub fn util() {}")?;
+    fs::write(project.join("src/scratch.rs"), "fn scratch() {}")?;
+
+    let git = |args: &[&str]| std::process::Command::new("git").arg("-C").arg(project).args(args).output();
+    if !git(&["init", "-q"]).is_ok_and(|output| output.status.success()) {
+        eprintln!("git unavailable, skipping");
+        return Ok(());
+    }
+    git(&["add", "src/main.rs", "src/nested/util.rs"])?;
+
+    let mut scanner = DirectoryScanner::new(project.to_path_buf());
+    scanner.set_walk_options(WalkOptions {
+        tracked_only: true,
+        ..Default::default()
+    });
+    let root_node = scanner.scan_directory()?;
+    let files: Vec<_> = DirectoryScanner::filter_source_files(&root_node)
+        .iter()
+        .filter_map(|node| node.get_relative_path(project).ok())
+        .collect();
+
+    assert_eq!(
+        files,
+        vec![
+            std::path::PathBuf::from("src/nested/util.rs"),
+            std::path::PathBuf::from("src/main.rs"),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_directory_scanner_submodules() -> doctreeai::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let project = temp_dir.path();
+    fs::create_dir_all(project.join("src"))?;
+    fs::create_dir_all(project.join("vendor/ui"))?;
+    fs::write(project.join("src/main.rs"), "fn main() {}")?;
+    fs::write(project.join("vendor/ui/button.rs"), "pub struct Button;")?;
+    fs::write(
+        project.join(".gitmodules"),
+        "[submodule \"ui\"]\n\tpath = vendor/ui\n\turl = https://example.com/ui.git\n",
+    )?;
+
+    let scan = |spec: &str| -> doctreeai::Result<FileNode> {
+        let mut scanner = DirectoryScanner::new(project.to_path_buf());
+        scanner.set_submodule_policy(SubmodulePolicy::parse(spec)?);
+        let root = scanner.scan_directory()?;
+        Ok(root.children.into_iter().find(|child| child.path.ends_with("vendor")).unwrap())
+    };
+
+    let vendor = scan("opaque")?;
+    assert_eq!(vendor.children.len(), 1);
+    let submodule = &vendor.children[0];
+    assert!(submodule.children.is_empty());
+    assert_eq!(
+        submodule.submodule.as_ref().and_then(|s| s.url.as_deref()),
+        Some("https://example.com/ui.git")
+    );
+
+    assert!(scan("skip")?.children.is_empty());
+
+    let scanned = scan("opaque,vendor/ui=scan")?;
+    assert!(scanned.children[0].submodule.is_none());
+    assert_eq!(scanned.children[0].children.len(), 1);
+
+    Ok(())
+}
+
 #[test]
 fn test_cache_manager() -> doctreeai::Result<()> {
     let temp_dir = TempDir::new()?;
@@ -131,6 +308,23 @@ fn test_file_node_operations() {
     assert!(!directory.is_source_code_file());
 }
 
+#[test]
+fn test_source_file_matcher() -> doctreeai::Result<()> {
+    let default_matcher = SourceFileMatcher::default();
+    assert!(FileNode::new("build.zig".into(), false).is_source_file(&default_matcher));
+    assert!(FileNode::new("Justfile".into(), false).is_source_file(&default_matcher));
+    assert!(FileNode::new("docker/Dockerfile".into(), false).is_source_file(&default_matcher));
+    assert!(!FileNode::new("chart.gotmpl".into(), false).is_source_file(&default_matcher));
+    assert!(!FileNode::new("BUCK".into(), false).is_source_file(&default_matcher));
+
+    let matcher = SourceFileMatcher::new(&[".gotmpl".to_string()], &["BUCK".to_string()])?;
+    assert!(FileNode::new("chart.gotmpl".into(), false).is_source_file(&matcher));
+    assert!(FileNode::new("lib/BUCK".into(), false).is_source_file(&matcher));
+    assert!(!FileNode::new("notes.txt".into(), false).is_source_file(&matcher));
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_end_to_end_workflow() -> doctreeai::Result<()> {
     let temp_dir = TempDir::new()?;
@@ -231,6 +425,105 @@ fn test_gitignore_patterns() -> doctreeai::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_self_doc_matches_golden_files() -> doctreeai::Result<()> {
+    let self_doc = SelfDoc::from_build()?;
+    let files = self_doc.generate().await?;
+
+    for file in &files {
+        assert!(
+            file.matches(),
+            "{} differs from its golden file at {:?}; run `doctreeai self-doc --bless` if intended",
+            file.name,
+            file.first_difference()
+        );
+    }
+
+    Ok(())
+}
+
+/// Runs the pipeline over `tests/fixtures/replay/sample` with the LLM
+/// responses recorded in `responses.json`.
+#[tokio::test]
+async fn test_replayed_run_is_deterministic() -> doctreeai::Result<()> {
+    let fixtures = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/replay");
+    let temp_dir = TempDir::new()?;
+    // Prompts name the project directory, so the copy keeps the recorded name
+    let base_path = temp_dir.path().join("sample");
+    copy_dir(&fixtures.join("sample"), &base_path)?;
+
+    let config = Config {
+        openai_api_base: "http://localhost:11434/v1".to_string(),
+        openai_api_key: "test".to_string(),
+        openai_model_name: "test-model".to_string(),
+        ..Default::default()
+    };
+    let mut doc_tree = DocTree::with_config(&base_path, config);
+    let fixture = ReplayFixture::load(&fixtures.join("responses.json"))?;
+    doc_tree.set_llm_client(LanguageModelClient::replay(doc_tree.config(), fixture));
+
+    let report = doc_tree
+        .run(&RunOptions {
+            render_document: true,
+            ..Default::default()
+        })
+        .await?;
+
+    // Every prompt was answered from the recording, so nothing fell back
+    assert!(doc_tree.last_run()?.is_some_and(|state| state.failed.is_empty()));
+    assert!(report.project_summary.contains("command-line calculator"));
+    let parser = report.summaries.iter().find(|entry| entry.source_path.ends_with("src/parser.rs"));
+    assert!(parser.is_some_and(|entry| entry.summary.contains("returns the sum")));
+
+    assert_eq!(report.validation_results.len(), 1);
+    let result = &report.validation_results[0];
+    assert_eq!((result.line_number, result.section.as_str()), (7, "Usage"));
+    assert_eq!(result.suggested_content, "The parser module sums every number it reads.");
+    assert_eq!((result.severity, result.confidence), (Severity::Outdated, 0.95));
+    assert!(report
+        .document
+        .is_some_and(|document| document.contains("sums every number") && !document.contains("multiplies")));
+
+    Ok(())
+}
+
+/// Refreshes `tests/fixtures/replay/responses.json` from a live LLM server
+/// after prompts change, using the model from the environment.
+#[tokio::test]
+#[ignore] // Requires local LLM server
+async fn record_replay_fixture() -> doctreeai::Result<()> {
+    let fixtures = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/replay");
+    let temp_dir = TempDir::new()?;
+    let base_path = temp_dir.path().join("sample");
+    copy_dir(&fixtures.join("sample"), &base_path)?;
+
+    let mut config = Config::load()?;
+    config.audit_log = true;
+    config.audit_redact.clear();
+    let cache_dir = config.get_cache_dir_path(&base_path);
+    DocTree::with_config(&base_path, config).run(&RunOptions::default()).await?;
+
+    let records = doctreeai::audit::AuditLog::read_records(&cache_dir.join(doctreeai::audit::AUDIT_DIR))?;
+    let content = serde_json::to_string_pretty(&records).expect("audit records serialize");
+    fs::write(fixtures.join("responses.json"), content + "\n")?;
+
+    Ok(())
+}
+
+fn copy_dir(from: &std::path::Path, to: &std::path::Path) -> doctreeai::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
+
 fn collect_all_files(node: &FileNode) -> Vec<std::path::PathBuf> {
     let mut files = Vec::new();
 